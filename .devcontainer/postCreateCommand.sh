@@ -40,8 +40,12 @@ done
 echo "opensearch launched"
 
 echo "opensearch initialization start"
-# インデックスの生成
-curl -s -XPUT -H "Content-Type: application/json" --data "@data_store_setup_files/opensearch/index_definition/index.json" "http://opensearch:9200/users" > /dev/null
+# インデックスとエイリアスの生成
+export OPENSEARCH_ENDPOINT_URI=http://opensearch:9200
+export OPENSEARCH_AUTH=false
+export OPENSEARCH_USERNAME=admin
+export OPENSEARCH_PASSWORD=admin
+cargo run -q -p users_index_tool -- init > /dev/null
 # replicaシャードの数を0に設定（開発環境の設定であり、本番環境では実施しない設定）
 # 開発環境では、OpenSearchは単一ノードで構成する。単一ノードの場合、replicaシャードを配置するための別ノードが存在しない。
 # そのため、それに起因してインデックスのステータスがyellowとなる。開発環境においては、replicaシャードが存在しないことは問題とならない。
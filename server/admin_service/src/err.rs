@@ -36,6 +36,26 @@ pub(crate) enum Code {
     InvalidNewsId = 30030,
     NoAwaitingPaymentFound = 30031,
     NoAwaitingWithdrawalFound = 30032,
+    InvalidCouponCodeFormat = 30033,
+    IllegalDiscountType = 30034,
+    IllegalDiscountValue = 30035,
+    IllegalMaxNumOfCouponUses = 30036,
+    IllegalDiscountBearer = 30037,
+    IllegalCouponValidPeriod = 30038,
+    CouponCodeAlreadyExists = 30039,
+    InvalidCompanyNameLength = 30040,
+    IllegalCharInCompanyName = 30041,
+    InvalidCorporateNumber = 30042,
+    CorporateNumberAlreadyExists = 30043,
+    NoCompanyFound = 30044,
+    IllegalConsultantSearchReportPeriod = 30045,
+    NoUpdateCareerReqDetailFound = 30046,
+    NoReviewRequestFound = 30047,
+    ReviewRequestClaimedByOtherAdmin = 30048,
+    NoRejectionReasonTemplateFound = 30049,
+    InvalidRejectionReasonTemplateCodeFormat = 30050,
+    InvalidRejectionReasonTemplateTitleFormat = 30051,
+    InvalidRejectionReasonTemplateBodyFormat = 30052,
 }
 
 pub(crate) fn unexpected_err_resp() -> ErrResp {
@@ -5,10 +5,18 @@ use std::str::FromStr;
 use axum::{http::StatusCode, Json};
 use chrono::{DateTime, Datelike, FixedOffset, Timelike};
 use common::{
+    coupon::{DISCOUNT_BORNE_BY_CONSULTANT, DISCOUNT_BORNE_BY_PLATFORM},
+    payment::{
+        record_card_payment_refund_request, CardPaymentRefund,
+        WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS,
+    },
     util::{Identity, Ymd},
+    virtual_bank_account::release_virtual_bank_account,
     ApiError, ErrResp, ErrRespStruct,
 };
-use entity::sea_orm::{DatabaseConnection, DatabaseTransaction, EntityTrait, QuerySelect};
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, QuerySelect,
+};
 use once_cell::sync::Lazy;
 use rust_decimal::{prelude::FromPrimitive, Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
@@ -20,7 +28,10 @@ pub(crate) mod admin;
 pub(crate) mod awaiting_payment;
 pub(crate) mod awaiting_withdrawal;
 pub(crate) mod career_request;
+pub(crate) mod company;
+pub(crate) mod consultant_search_report;
 pub(crate) mod consultation;
+pub(crate) mod coupon;
 mod document_operation;
 pub(crate) mod identity_by_user_account_id;
 pub(crate) mod identity_request;
@@ -29,16 +40,16 @@ pub(crate) mod maintenance;
 pub(crate) mod neglected_payment;
 pub(crate) mod news;
 pub(crate) mod pagination;
+pub(crate) mod payment_event;
 mod reason_validator;
 pub(crate) mod receipt_of_consultation;
 pub(crate) mod refresh;
 pub(crate) mod refunded_payment;
+pub(crate) mod rejection_reason_template;
+pub(crate) mod review_claim;
 pub(crate) mod user_account;
 mod user_account_operation;
 
-/// コンサルタントへ報酬を振り込むまで待機する期間（単位：日）
-const WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS: i64 = 8;
-
 pub(crate) const KEY_TO_TRANSFER_FEE_IN_YEN: &str = "TRANSFER_FEE_IN_YEN";
 static TRANSFER_FEE_IN_YEN: Lazy<i32> = Lazy::new(|| {
     let transfer_fee_in_yen = std::env::var(KEY_TO_TRANSFER_FEE_IN_YEN).unwrap_or_else(|_| {
@@ -60,15 +71,30 @@ static PLATFORM_FEE_RATE_IN_PERCENTAGE: Lazy<String> = Lazy::new(|| {
     })
 });
 
+/// コンサルタントへの報酬を返す。
+///
+/// sale_in_yenは割引前の相談料。割引額をプラットフォームが負担する場合（またはクーポンが利用されていない場合）、
+/// 報酬は割引前の相談料を基に計算する。割引額をコンサルタントが負担する場合、その計算結果からさらに割引額を差し引く。
+/// 差し引いた結果が負となる場合、報酬は0とする。
 fn calculate_reward(
     sale_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<&str>,
     platform_fee_rate_in_percentage: &str,
     transfer_fee_in_yen: i32,
 ) -> Result<i32, ErrResp> {
     let platform_fee_in_yen =
         calculate_platform_fee_in_yen(sale_in_yen, platform_fee_rate_in_percentage)?;
     let reward = sale_in_yen - platform_fee_in_yen - transfer_fee_in_yen;
-    Ok(reward)
+    match discount_borne_by {
+        None => Ok(reward),
+        Some(DISCOUNT_BORNE_BY_PLATFORM) => Ok(reward),
+        Some(DISCOUNT_BORNE_BY_CONSULTANT) => Ok((reward - discount_in_yen).max(0)),
+        Some(other) => {
+            error!("unexpected discount_borne_by ({})", other);
+            Err(unexpected_err_resp())
+        }
+    }
 }
 
 // platform_fee_rate_in_percentageはパーセンテージを示す少数の文字列。返り値は、sale_in_yen * (platform_fee_rate_in_percentage/100) の結果の少数部分を切り捨てた値。
@@ -170,6 +196,8 @@ async fn delete_awaiting_payment(
                 err_resp: unexpected_err_resp(),
             }
         })?;
+    // awaiting_paymentが削除されるのは入金に関して最終的な状態が確定したときなので、割り当てていたバーチャル口座を解放する
+    release_virtual_bank_account(consultation_id, txn).await?;
     Ok(())
 }
 
@@ -212,6 +240,45 @@ async fn delete_awaiting_withdrawal(
     Ok(())
 }
 
+/// 相談に対してクレジットカードで支払われ、まだ返金されていない決済を取得する
+///
+/// 二重払いの決済は受け取った時点で返金を依頼済みとなるため、該当する決済は高々一つとなる。
+async fn find_paid_card_payment_with_exclusive_lock(
+    consultation_id: i64,
+    txn: &DatabaseTransaction,
+) -> Result<Option<entity::card_payment::Model>, ErrRespStruct> {
+    let model_option = entity::card_payment::Entity::find()
+        .filter(entity::card_payment::Column::ConsultationId.eq(consultation_id))
+        .filter(entity::card_payment::Column::PaidAt.is_not_null())
+        .filter(entity::card_payment::Column::RefundIdempotencyKey.is_null())
+        .lock_exclusive()
+        .one(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find card_payment (consultation_id: {}): {}",
+                consultation_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(model_option)
+}
+
+/// クレジットカードで支払われた相談料の返金を依頼したことを記録する
+///
+/// 決済代行サービスへの返金依頼は取り消しができないため、ここでは記録のみ行う。
+/// トランザクションをコミットした後、返り値を[common::payment::execute_card_payment_refund]に渡して返金する。
+async fn record_refund_request_of_card_payment(
+    card_payment: entity::card_payment::Model,
+    txn: &DatabaseTransaction,
+) -> Result<CardPaymentRefund, ErrRespStruct> {
+    // 返金処理のリトライ時に二重に返金されないように決済ページ単位で同じキーを利用する
+    let idempotency_key = format!("refund-{}", card_payment.checkout_session_id);
+    record_card_payment_refund_request(card_payment, idempotency_key, txn).await
+}
+
 async fn find_identity_by_user_account_id(
     pool: &DatabaseConnection,
     user_account_id: i64,
@@ -415,7 +482,7 @@ pub(super) mod tests {
         let platform_fee_rate_in_percentage = "50.0";
         let transfer_fee = 250;
 
-        let result = calculate_reward(fee, platform_fee_rate_in_percentage, transfer_fee)
+        let result = calculate_reward(fee, 0, None, platform_fee_rate_in_percentage, transfer_fee)
             .expect("failed to get Ok");
 
         assert_eq!(1250, result);
@@ -427,7 +494,7 @@ pub(super) mod tests {
         let platform_fee_rate_in_percentage = "50.0";
         let transfer_fee = 250;
 
-        let result = calculate_reward(fee, platform_fee_rate_in_percentage, transfer_fee)
+        let result = calculate_reward(fee, 0, None, platform_fee_rate_in_percentage, transfer_fee)
             .expect("failed to get Ok");
 
         assert_eq!(1251, result);
@@ -439,9 +506,86 @@ pub(super) mod tests {
         let platform_fee_rate_in_percentage = "60.0";
         let transfer_fee = 250;
 
-        let result = calculate_reward(fee, platform_fee_rate_in_percentage, transfer_fee)
+        let result = calculate_reward(fee, 0, None, platform_fee_rate_in_percentage, transfer_fee)
             .expect("failed to get Ok");
 
         assert_eq!(952, result);
     }
+
+    #[test]
+    fn test_calculate_reward_discount_borne_by_platform() {
+        let fee = 3000;
+        let discount = 500;
+        let platform_fee_rate_in_percentage = "50.0";
+        let transfer_fee = 250;
+
+        let result = calculate_reward(
+            fee,
+            discount,
+            Some(DISCOUNT_BORNE_BY_PLATFORM),
+            platform_fee_rate_in_percentage,
+            transfer_fee,
+        )
+        .expect("failed to get Ok");
+
+        assert_eq!(1250, result);
+    }
+
+    #[test]
+    fn test_calculate_reward_discount_borne_by_consultant() {
+        let fee = 3000;
+        let discount = 500;
+        let platform_fee_rate_in_percentage = "50.0";
+        let transfer_fee = 250;
+
+        let result = calculate_reward(
+            fee,
+            discount,
+            Some(DISCOUNT_BORNE_BY_CONSULTANT),
+            platform_fee_rate_in_percentage,
+            transfer_fee,
+        )
+        .expect("failed to get Ok");
+
+        assert_eq!(750, result);
+    }
+
+    #[test]
+    fn test_calculate_reward_discount_borne_by_consultant_exceeds_reward() {
+        let fee = 3000;
+        let discount = 3000;
+        let platform_fee_rate_in_percentage = "50.0";
+        let transfer_fee = 250;
+
+        let result = calculate_reward(
+            fee,
+            discount,
+            Some(DISCOUNT_BORNE_BY_CONSULTANT),
+            platform_fee_rate_in_percentage,
+            transfer_fee,
+        )
+        .expect("failed to get Ok");
+
+        assert_eq!(0, result);
+    }
+
+    #[test]
+    fn test_calculate_reward_fail_unexpected_discount_borne_by() {
+        let fee = 3000;
+        let discount = 500;
+        let platform_fee_rate_in_percentage = "50.0";
+        let transfer_fee = 250;
+
+        let result = calculate_reward(
+            fee,
+            discount,
+            Some("user"),
+            platform_fee_rate_in_percentage,
+            transfer_fee,
+        )
+        .expect_err("failed to get Err");
+
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, result.0);
+        assert_eq!(Code::UnexpectedErr as u32, result.1 .0.code);
+    }
 }
@@ -1,6 +1,13 @@
 // Copyright 2023 Ken Miura
 
+use std::collections::HashMap;
+
+use common::ErrResp;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use serde::Serialize;
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
 
 pub(crate) mod awaiting_payment_by_consultation_id;
 pub(crate) mod expired_list;
@@ -13,5 +20,32 @@ struct AwaitingPayment {
     user_account_id: i64,
     meeting_at: String, // RFC 3339形式の文字列
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<String>,
     sender_name: Option<String>,
+    virtual_bank_account_number: Option<String>,
+}
+
+/// consultation_idをキー、割り当てられているバーチャル口座の口座番号を値とするHashMapを返す
+async fn find_virtual_bank_account_numbers(
+    consultation_ids: Vec<i64>,
+    pool: &DatabaseConnection,
+) -> Result<HashMap<i64, String>, ErrResp> {
+    let models = entity::virtual_bank_account::Entity::find()
+        .filter(
+            entity::virtual_bank_account::Column::ConsultationId.is_in(consultation_ids.clone()),
+        )
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find virtual_bank_account (consultation_ids: {:?}): {}",
+                consultation_ids, e
+            );
+            unexpected_err_resp()
+        })?;
+    Ok(models
+        .into_iter()
+        .filter_map(|m| m.consultation_id.map(|c| (c, m.account_number)))
+        .collect())
 }
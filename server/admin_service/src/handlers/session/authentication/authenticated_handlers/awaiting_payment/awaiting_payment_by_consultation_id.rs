@@ -12,6 +12,7 @@ use crate::err::unexpected_err_resp;
 
 use super::super::admin::Admin;
 use super::super::{validate_consultation_id_is_positive, ConsultationIdQuery};
+use super::find_virtual_bank_account_numbers;
 
 pub(crate) async fn get_awaiting_payment_by_consultation_id(
     Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
@@ -36,6 +37,9 @@ struct AwaitingPayment {
     consultant_id: i64,
     meeting_at: String, // RFC 3339形式の文字列
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<String>,
+    virtual_bank_account_number: Option<String>,
     created_at: String, // RFC 3339形式の文字列
 }
 
@@ -83,6 +87,8 @@ impl AwaitingPaymentOperation for AwaitingPaymentOperationImpl {
                 );
                 unexpected_err_resp()
             })?;
+        let virtual_bank_account_numbers =
+            find_virtual_bank_account_numbers(vec![consultation_id], &self.pool).await?;
         Ok(model.map(|m| AwaitingPayment {
             consultation_id: m.consultation_id,
             user_account_id: m.user_account_id,
@@ -92,6 +98,11 @@ impl AwaitingPaymentOperation for AwaitingPaymentOperationImpl {
                 .with_timezone(&(*JAPANESE_TIME_ZONE))
                 .to_rfc3339(),
             fee_per_hour_in_yen: m.fee_per_hour_in_yen,
+            discount_in_yen: m.discount_in_yen,
+            discount_borne_by: m.discount_borne_by,
+            virtual_bank_account_number: virtual_bank_account_numbers
+                .get(&m.consultation_id)
+                .cloned(),
             created_at: m
                 .created_at
                 .with_timezone(&(*JAPANESE_TIME_ZONE))
@@ -135,6 +146,9 @@ mod tests {
             consultant_id: 68,
             meeting_at: "2023-04-13T14:00:00.0000+09:00".to_string(),
             fee_per_hour_in_yen: 5000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
+            virtual_bank_account_number: Some("1234567".to_string()),
             created_at: "2023-03-28T14:00:00.0000+09:00".to_string(),
         }
     }
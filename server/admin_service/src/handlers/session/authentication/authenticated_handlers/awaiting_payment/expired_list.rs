@@ -22,7 +22,7 @@ use crate::{
     },
 };
 
-use super::AwaitingPayment;
+use super::{find_virtual_bank_account_numbers, AwaitingPayment};
 
 const VALID_PAGE_SIZE: u64 = 20;
 
@@ -98,6 +98,11 @@ impl ExpiredAwaitingPaymentsOperation for ExpiredAwaitingPaymentsOperationImpl {
                 );
                 unexpected_err_resp()
             })?;
+        let virtual_bank_account_numbers = find_virtual_bank_account_numbers(
+            models.iter().map(|m| m.0.consultation_id).collect(),
+            &self.pool,
+        )
+        .await?;
         models
             .into_iter()
             .map(|m| {
@@ -126,7 +131,12 @@ impl ExpiredAwaitingPaymentsOperation for ExpiredAwaitingPaymentsOperationImpl {
                     user_account_id: ap.user_account_id,
                     meeting_at: meeting_at_str,
                     fee_per_hour_in_yen: ap.fee_per_hour_in_yen,
+                    discount_in_yen: ap.discount_in_yen,
+                    discount_borne_by: ap.discount_borne_by,
                     sender_name,
+                    virtual_bank_account_number: virtual_bank_account_numbers
+                        .get(&ap.consultation_id)
+                        .cloned(),
                 })
             })
             .collect::<Result<Vec<AwaitingPayment>, ErrResp>>()
@@ -229,10 +239,13 @@ mod tests {
             user_account_id,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at),
             fee_per_hour_in_yen,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
@@ -275,10 +288,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -294,10 +310,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
@@ -340,10 +359,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -359,10 +381,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
@@ -405,10 +430,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -424,10 +452,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
@@ -470,10 +501,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -489,10 +523,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
@@ -535,10 +572,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -554,10 +594,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = ExpiredAwaitingPaymentsOperationMock {
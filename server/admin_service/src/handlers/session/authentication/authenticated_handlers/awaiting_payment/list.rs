@@ -22,7 +22,7 @@ use crate::{
     },
 };
 
-use super::AwaitingPayment;
+use super::{find_virtual_bank_account_numbers, AwaitingPayment};
 
 const VALID_PAGE_SIZE: u64 = 20;
 
@@ -98,6 +98,11 @@ impl AwaitingPaymentsOperation for AwaitingPaymentsOperationImpl {
                 );
                 unexpected_err_resp()
             })?;
+        let virtual_bank_account_numbers = find_virtual_bank_account_numbers(
+            models.iter().map(|m| m.0.consultation_id).collect(),
+            &self.pool,
+        )
+        .await?;
         models
             .into_iter()
             .map(|m| {
@@ -125,7 +130,12 @@ impl AwaitingPaymentsOperation for AwaitingPaymentsOperationImpl {
                     user_account_id: ap.user_account_id,
                     meeting_at: meeting_at_str,
                     fee_per_hour_in_yen: ap.fee_per_hour_in_yen,
+                    discount_in_yen: ap.discount_in_yen,
+                    discount_borne_by: ap.discount_borne_by,
                     sender_name: Some(sender_name),
+                    virtual_bank_account_number: virtual_bank_account_numbers
+                        .get(&ap.consultation_id)
+                        .cloned(),
                 })
             })
             .collect::<Result<Vec<AwaitingPayment>, ErrResp>>()
@@ -228,10 +238,13 @@ mod tests {
             user_account_id,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at),
             fee_per_hour_in_yen,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
         let op = AwaitingPaymentsOperationMock {
             page,
@@ -273,10 +286,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -292,10 +308,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = AwaitingPaymentsOperationMock {
@@ -338,10 +357,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -357,10 +379,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = AwaitingPaymentsOperationMock {
@@ -403,10 +428,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -422,10 +450,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = AwaitingPaymentsOperationMock {
@@ -468,10 +499,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -487,10 +521,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = AwaitingPaymentsOperationMock {
@@ -533,10 +570,13 @@ mod tests {
             user_account_id: user_account_id1,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("タナカ".to_string(), "タロウ".to_string(), meeting_at1)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let consultation_id2 = 4;
@@ -552,10 +592,13 @@ mod tests {
             user_account_id: user_account_id2,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: Some(
                 generate_sender_name("スズキ".to_string(), "ジロウ".to_string(), meeting_at2)
                     .expect("failed to get Ok"),
             ),
+            virtual_bank_account_number: Some("1234567".to_string()),
         };
 
         let op = AwaitingPaymentsOperationMock {
@@ -36,6 +36,8 @@ struct AwaitingWithdrawal {
     consultant_id: i64,
     meeting_at: String, // RFC 3339形式の文字列
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<String>,
     sender_name: String,
     payment_confirmed_by: String,
     created_at: String, // RFC 3339形式の文字列
@@ -96,6 +98,8 @@ impl AwaitingWithdrawalOperation for AwaitingWithdrawalOperationImpl {
                 .with_timezone(&(*JAPANESE_TIME_ZONE))
                 .to_rfc3339(),
             fee_per_hour_in_yen: m.fee_per_hour_in_yen,
+            discount_in_yen: m.discount_in_yen,
+            discount_borne_by: m.discount_borne_by,
             sender_name: m.sender_name,
             payment_confirmed_by: m.payment_confirmed_by,
             created_at: m
@@ -143,6 +147,8 @@ mod tests {
             consultant_id: 68,
             meeting_at: "2023-04-13T14:00:00.0000+09:00".to_string(),
             fee_per_hour_in_yen: 5000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -47,6 +47,8 @@ struct AwaitingWithdrawal {
     consultant_id: i64,
     meeting_at: String, // RFC 3339形式の文字列,
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<String>,
     sender_name: String,
     payment_confirmed_by: String,
     created_at: String, // RFC 3339形式の文字列
@@ -113,9 +115,9 @@ impl AwaitingWithdrawalsOperation for AwaitingWithdrawalsOperationImpl {
                     } else {
                         (None, None, None, None, None)
                     };
-                let reward = calculate_reward(aw.fee_per_hour_in_yen, &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(), *TRANSFER_FEE_IN_YEN).map_err(|e|{
-                    error!("failed to calculate_reward (fee_per_hour_in_yen: {}, platform_fee_rate_in_percentage: {}, transfer_fee_in_yen: {}): {:?}",
-                        aw.fee_per_hour_in_yen, *PLATFORM_FEE_RATE_IN_PERCENTAGE, *TRANSFER_FEE_IN_YEN, e);
+                let reward = calculate_reward(aw.fee_per_hour_in_yen, aw.discount_in_yen, aw.discount_borne_by.as_deref(), &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(), *TRANSFER_FEE_IN_YEN).map_err(|e|{
+                    error!("failed to calculate_reward (fee_per_hour_in_yen: {}, discount_in_yen: {}, discount_borne_by: {:?}, platform_fee_rate_in_percentage: {}, transfer_fee_in_yen: {}): {:?}",
+                        aw.fee_per_hour_in_yen, aw.discount_in_yen, aw.discount_borne_by, *PLATFORM_FEE_RATE_IN_PERCENTAGE, *TRANSFER_FEE_IN_YEN, e);
                     unexpected_err_resp()
                 })?;
                 Ok(AwaitingWithdrawal {
@@ -124,6 +126,8 @@ impl AwaitingWithdrawalsOperation for AwaitingWithdrawalsOperationImpl {
                     consultant_id: aw.consultant_id,
                     meeting_at: convert_date_time_to_rfc3339_string(aw.meeting_at),
                     fee_per_hour_in_yen: aw.fee_per_hour_in_yen,
+                    discount_in_yen: aw.discount_in_yen,
+                    discount_borne_by: aw.discount_borne_by,
                     sender_name: aw.sender_name,
                     payment_confirmed_by: aw.payment_confirmed_by,
                     created_at: convert_date_time_to_rfc3339_string(aw.created_at),
@@ -264,6 +268,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -281,6 +287,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -326,6 +334,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -343,6 +353,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -358,6 +370,8 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "サトウ".to_string(),
                 "サブロウ".to_string(),
@@ -375,6 +389,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen2,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -420,6 +436,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -437,6 +455,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -452,6 +472,8 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "サトウ".to_string(),
                 "サブロウ".to_string(),
@@ -469,6 +491,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen2,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -514,6 +538,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -531,6 +557,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -546,6 +574,8 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "サトウ".to_string(),
                 "サブロウ".to_string(),
@@ -563,6 +593,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen2,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -608,6 +640,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -625,6 +659,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -640,6 +676,8 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "サトウ".to_string(),
                 "サブロウ".to_string(),
@@ -657,6 +695,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen2,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -702,6 +742,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -719,6 +761,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -734,6 +778,8 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: fee_per_hour_in_yen2,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "サトウ".to_string(),
                 "サブロウ".to_string(),
@@ -751,6 +797,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen2,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
@@ -796,6 +844,8 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: fee_per_hour_in_yen1,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -813,6 +863,8 @@ mod tests {
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
             reward: calculate_reward(
                 fee_per_hour_in_yen1,
+                0,
+                None,
                 &PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
                 *TRANSFER_FEE_IN_YEN,
             )
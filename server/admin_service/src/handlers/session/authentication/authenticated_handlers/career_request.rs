@@ -1,8 +1,12 @@
 // Copyright 2022 Ken Miura
 
 pub(crate) mod create_request;
+pub(crate) mod update_request;
 
-use common::ErrRespStruct;
+use common::{
+    storage::{StorageClient, CAREER_IMAGES_BUCKET_NAME},
+    ErrRespStruct,
+};
 use entity::{
     create_career_req,
     sea_orm::{DatabaseTransaction, EntityTrait, QuerySelect},
@@ -39,3 +43,42 @@ async fn find_create_career_req_model_by_create_career_req_id_with_exclusive_loc
     })?;
     Ok(req)
 }
+
+async fn delete_career_images(
+    storage_client: StorageClient,
+    user_account_id: i64,
+    image1_file_name_without_ext: String,
+    image2_file_name_without_ext: Option<String>,
+) -> Result<(), ErrRespStruct> {
+    let image1_key = format!("{}/{}.png", user_account_id, image1_file_name_without_ext);
+    storage_client
+        .delete_object(CAREER_IMAGES_BUCKET_NAME.as_str(), image1_key.as_str())
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete career image1 (key: {}): {}",
+                image1_key, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+
+    if let Some(image2_file_name_without_ext) = image2_file_name_without_ext {
+        let image2_key = format!("{}/{}.png", user_account_id, image2_file_name_without_ext);
+        storage_client
+            .delete_object(CAREER_IMAGES_BUCKET_NAME.as_str(), image2_key.as_str())
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to delete career image2 (key: {}): {}",
+                    image2_key, e
+                );
+                ErrRespStruct {
+                    err_resp: unexpected_err_resp(),
+                }
+            })?;
+    }
+
+    Ok(())
+}
@@ -28,5 +28,18 @@ async fn delete_create_career_req(
                 err_resp: unexpected_err_resp(),
             }
         })?;
+    // 勤務先のメールアドレスによる確認の途中で承認、拒否された場合に備え、確認コードも合わせて削除する
+    let _ = entity::work_email_verification_code::Entity::delete_by_id(create_career_req_id)
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete work_email_verification_code (create_career_req_id: {}): {}",
+                create_career_req_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
     Ok(())
 }
@@ -2,15 +2,17 @@
 
 use async_session::serde_json::json;
 use axum::{async_trait, Json};
-use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use common::{
-    opensearch::{index_document, update_document, INDEX_NAME},
+    company::insert_company_alias_if_not_exists,
+    opensearch::{create_career_document, INDEX_NAME},
     smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS},
-    ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
 };
 
 use axum::extract::State;
 use axum::http::StatusCode;
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
 use entity::{
     approved_create_career_req, career, create_career_req, document,
     sea_orm::{
@@ -20,15 +22,15 @@ use entity::{
     },
 };
 use once_cell::sync::Lazy;
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use crate::{
-    err::unexpected_err_resp,
+    err::{unexpected_err_resp, Code},
     handlers::session::authentication::authenticated_handlers::{
         admin::Admin,
         document_operation::find_document_model_by_user_account_id_with_exclusive_lock,
+        review_claim::ensure_not_claimed_by_other_admin,
         user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
     },
 };
@@ -44,14 +46,18 @@ pub(crate) async fn post_create_career_request_approval(
     Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
     State(smtp_client): State<SmtpClient>,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(create_career_req_approval): Json<CreateCareerReqApproval>,
 ) -> RespResult<CreateCareerReqApprovalResult> {
     let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
-    let op = CreateCareerReqApprovalOperationImpl { pool, index_client };
+    let op = CreateCareerReqApprovalOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     handle_create_career_request_approval(
         admin_info.email_address,
         create_career_req_approval.create_career_req_id,
+        create_career_req_approval.company_id,
         current_date_time,
         op,
         smtp_client,
@@ -62,6 +68,8 @@ pub(crate) async fn post_create_career_request_approval(
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub(crate) struct CreateCareerReqApproval {
     create_career_req_id: i64,
+    /// 職務経歴の会社名に対応する会社（管理者が/companies-by-nameの結果から選択、または/set-company-reqで作成したもの）
+    company_id: i64,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -70,6 +78,7 @@ pub(crate) struct CreateCareerReqApprovalResult {}
 async fn handle_create_career_request_approval(
     admin_email_address: String,
     create_career_req_id: i64,
+    company_id: i64,
     approved_time: DateTime<FixedOffset>,
     op: impl CreateCareerReqApprovalOperation,
     send_mail: impl SendMail,
@@ -85,10 +94,22 @@ async fn handle_create_career_request_approval(
         unexpected_err_resp()
     })?;
 
+    let company_exists = op.company_exists(company_id).await?;
+    if !company_exists {
+        error!("no company (company_id: {}) found", company_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCompanyFound as u32,
+            }),
+        ));
+    }
+
     let approved_user = op
         .approve_create_career_req(
             user_account_id,
             create_career_req_id,
+            company_id,
             admin_email_address,
             approved_time,
         )
@@ -125,10 +146,13 @@ trait CreateCareerReqApprovalOperation {
         create_career_req_id: i64,
     ) -> Result<Option<i64>, ErrResp>;
 
+    async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp>;
+
     async fn approve_create_career_req(
         &self,
         user_account_id: i64,
         create_career_req_id: i64,
+        company_id: i64,
         approver_email_address: String,
         approved_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp>;
@@ -136,7 +160,7 @@ trait CreateCareerReqApprovalOperation {
 
 struct CreateCareerReqApprovalOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -158,14 +182,26 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
         Ok(model.map(|m| m.user_account_id))
     }
 
+    async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp> {
+        let model = entity::company::Entity::find_by_id(company_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to find company (company_id: {}): {}", company_id, e);
+                unexpected_err_resp()
+            })?;
+        Ok(model.is_some())
+    }
+
     async fn approve_create_career_req(
         &self,
         user_account_id: i64,
         create_career_req_id: i64,
+        company_id: i64,
         approver_email_address: String,
         approved_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         let notification_email_address_option = self
             .pool
             .transaction::<_, Option<String>, ErrRespStruct>(|txn| {
@@ -179,6 +215,15 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
                     )
                     .await?;
 
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        approver_email_address.as_str(),
+                        approved_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
                     let user = match user_option {
                         Some(m) => m,
                         None => return {
@@ -191,7 +236,20 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
                         return Ok(None)
                     }
 
-                    let career_active_model = generate_career_active_model(req.clone());
+                    // 次回以降、同じ表記の会社名で会社を見つけられるように別名として登録しておく
+                    let _ = insert_company_alias_if_not_exists(company_id, &req.company_name, txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to insert company_alias (company_id: {}, company_name: {}): {}",
+                                company_id, req.company_name, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+
+                    let career_active_model = generate_career_active_model(req.clone(), company_id);
                     let career_model = career_active_model.insert(txn).await.map_err(|e| {
                         error!(
                             "failed to insert career (user_account_id: {}): {}",
@@ -204,6 +262,7 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
 
                     let approved_req = generate_approved_create_career_req_active_model(
                         req,
+                        company_id,
                         approved_time,
                         approver_email_address,
                     );
@@ -243,7 +302,7 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
                             career_model,
                             num_of_careers,
                             approved_time,
-                            index_client
+                            consultant_search_client
                         )
                         .await?;
                     } else {
@@ -257,7 +316,7 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
                             career_model,
                             num_of_careers,
                             approved_time,
-                            index_client
+                            consultant_search_client
                         )
                         .await?;
                     };
@@ -282,6 +341,7 @@ impl CreateCareerReqApprovalOperation for CreateCareerReqApprovalOperationImpl {
 
 fn generate_approved_create_career_req_active_model(
     model: create_career_req::Model,
+    company_id: i64,
     approved_time: DateTime<FixedOffset>,
     approver_email_address: String,
 ) -> approved_create_career_req::ActiveModel {
@@ -289,6 +349,7 @@ fn generate_approved_create_career_req_active_model(
         appr_cre_career_req_id: NotSet,
         user_account_id: Set(model.user_account_id),
         company_name: Set(model.company_name),
+        company_id: Set(company_id),
         department_name: Set(model.department_name),
         office: Set(model.office),
         career_start_date: Set(model.career_start_date),
@@ -304,14 +365,19 @@ fn generate_approved_create_career_req_active_model(
         image2_file_name_without_ext: Set(model.image2_file_name_without_ext),
         approved_at: Set(approved_time),
         approved_by: Set(approver_email_address),
+        verified_work_email_domain: Set(model.verified_work_email_domain),
     }
 }
 
-fn generate_career_active_model(model: create_career_req::Model) -> career::ActiveModel {
+fn generate_career_active_model(
+    model: create_career_req::Model,
+    company_id: i64,
+) -> career::ActiveModel {
     career::ActiveModel {
         career_id: NotSet,
         user_account_id: Set(model.user_account_id),
         company_name: Set(model.company_name),
+        company_id: Set(company_id),
         department_name: Set(model.department_name),
         office: Set(model.office),
         career_start_date: Set(model.career_start_date),
@@ -323,6 +389,7 @@ fn generate_career_active_model(model: create_career_req::Model) -> career::Acti
         position_name: Set(model.position_name),
         is_new_graduate: Set(model.is_new_graduate),
         note: Set(model.note),
+        verified_work_email_domain: Set(model.verified_work_email_domain),
     }
 }
 
@@ -353,34 +420,12 @@ async fn add_new_document_with_career(
     career_model: career::Model,
     num_of_careers: u64,
     current_time: DateTime<FixedOffset>,
-    client: OpenSearch,
+    client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
-    let employed = career_model.career_end_date.is_none();
-    let years_of_service = if let Some(career_end_date) = career_model.career_end_date {
-        calculate_years_of_service(career_model.career_start_date, career_end_date)
-    } else {
-        calculate_years_of_service(
-            career_model.career_start_date,
-            current_time.naive_local().date(),
-        )
-    };
+    let career = create_career_document(&career_model, current_time.naive_local().date());
     let new_document = json!({
         "user_account_id": career_model.user_account_id,
-        "careers": [{
-            "career_id": career_model.career_id,
-            "company_name": career_model.company_name,
-            "department_name": career_model.department_name,
-            "office": career_model.office,
-            "years_of_service": years_of_service,
-            "employed": employed,
-            "contract_type": career_model.contract_type,
-            "profession": career_model.profession,
-            "annual_income_in_man_yen": career_model.annual_income_in_man_yen,
-            "is_manager": career_model.is_manager,
-            "position_name": career_model.position_name,
-            "is_new_graduate": career_model.is_new_graduate,
-            "note": career_model.note,
-        }],
+        "careers": [career],
         "num_of_careers": num_of_careers,
         "fee_per_hour_in_yen": null,
         "is_bank_account_registered": false,
@@ -388,7 +433,8 @@ async fn add_new_document_with_career(
         "num_of_rated": 0,
         "disabled": false
     });
-    index_document(index_name, document_id, &new_document, &client)
+    client
+        .index_document(index_name, document_id, &new_document)
         .await
         .map_err(|e| {
             error!(
@@ -400,29 +446,15 @@ async fn add_new_document_with_career(
     Ok(())
 }
 
-fn calculate_years_of_service(from: NaiveDate, to: NaiveDate) -> i64 {
-    let days_in_year = 365; // 1日の誤差（1年が365日か366日か）は、年という単位に対して無視して良いと判断し、365日固定で計算する
-    let days_of_service = (to - from).num_days();
-    days_of_service / days_in_year
-}
-
 async fn insert_new_career_into_document(
     index_name: &str,
     document_id: &str,
     career_model: career::Model,
     num_of_careers: u64,
     current_time: DateTime<FixedOffset>,
-    client: OpenSearch,
+    client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
-    let employed = career_model.career_end_date.is_none();
-    let years_of_service = if let Some(career_end_date) = career_model.career_end_date {
-        calculate_years_of_service(career_model.career_start_date, career_end_date)
-    } else {
-        calculate_years_of_service(
-            career_model.career_start_date,
-            current_time.naive_local().date(),
-        )
-    };
+    let career = create_career_document(&career_model, current_time.naive_local().date());
     let source = format!(
         "ctx._source.careers.add(params.career); ctx._source.num_of_careers = {}",
         num_of_careers
@@ -431,25 +463,12 @@ async fn insert_new_career_into_document(
         "script": {
             "source": source,
             "params": {
-              "career": {
-                "career_id": career_model.career_id,
-                "company_name": career_model.company_name,
-                "department_name": career_model.department_name,
-                "office": career_model.office,
-                "years_of_service": years_of_service,
-                "employed": employed,
-                "contract_type": career_model.contract_type,
-                "profession": career_model.profession,
-                "annual_income_in_man_yen": career_model.annual_income_in_man_yen,
-                "is_manager": career_model.is_manager,
-                "position_name": career_model.position_name,
-                "is_new_graduate": career_model.is_new_graduate,
-                "note": career_model.note,
-              }
+              "career": career
             }
         }
     });
-    update_document(index_name, document_id, &script, &client)
+    client
+        .update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
@@ -483,9 +502,8 @@ Email: {}",
 mod tests {
     use axum::async_trait;
     use axum::http::StatusCode;
-    use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+    use chrono::{DateTime, FixedOffset, TimeZone};
     use common::{smtp::SYSTEM_EMAIL_ADDRESS, ErrResp, JAPANESE_TIME_ZONE};
-    use once_cell::sync::Lazy;
 
     use crate::handlers::session::authentication::authenticated_handlers::tests::SendMailMock;
 
@@ -507,6 +525,8 @@ mod tests {
         admin_email_address: String,
         user_option: Option<User>,
         create_career_req_mock: CreateCareerReqMock,
+        company_id: i64,
+        company_exists: bool,
         approved_time: DateTime<FixedOffset>,
     }
 
@@ -523,10 +543,16 @@ mod tests {
             Ok(Some(self.create_career_req_mock.user_account_id))
         }
 
+        async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp> {
+            assert_eq!(self.company_id, company_id);
+            Ok(self.company_exists)
+        }
+
         async fn approve_create_career_req(
             &self,
             user_account_id: i64,
             create_career_req_id: i64,
+            company_id: i64,
             approver_email_address: String,
             approved_time: DateTime<FixedOffset>,
         ) -> Result<Option<String>, ErrResp> {
@@ -537,6 +563,7 @@ mod tests {
                     self.create_career_req_mock.create_career_req_id,
                     create_career_req_id
                 );
+                assert_eq!(self.company_id, company_id);
                 assert_eq!(self.approved_time, approved_time);
                 Ok(Some(user.email_address))
             } else {
@@ -566,6 +593,8 @@ mod tests {
             admin_email_address: admin_email_address.clone(),
             user_option,
             create_career_req_mock: create_career_req,
+            company_id: 12,
+            company_exists: true,
             approved_time: approval_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -578,6 +607,7 @@ mod tests {
         let result = handle_create_career_request_approval(
             admin_email_address,
             create_career_req_id,
+            12,
             approval_time,
             op_mock,
             send_mail_mock,
@@ -606,6 +636,8 @@ mod tests {
             admin_email_address: admin_email_address.clone(),
             user_option: None,
             create_career_req_mock: create_career_req,
+            company_id: 12,
+            company_exists: true,
             approved_time: approval_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -618,6 +650,7 @@ mod tests {
         let result = handle_create_career_request_approval(
             admin_email_address,
             create_career_req_id,
+            12,
             approval_time,
             op_mock,
             send_mail_mock,
@@ -629,79 +662,110 @@ mod tests {
         assert_eq!(CreateCareerReqApprovalResult {}, resp.1 .0);
     }
 
-    #[derive(Debug)]
-    struct TestCase {
-        name: String,
-        input: Input,
-        expected: i64,
-    }
+    #[tokio::test]
+    async fn handle_create_career_request_approval_fail_no_company_found() {
+        let admin_email_address = String::from("admin@test.com");
+        let user_account_id = 432;
+        let user_email_address = String::from("test@test.com");
+        let user_option = Some(User {
+            user_account_id,
+            email_address: user_email_address.clone(),
+        });
+        let create_career_req_id = 53215;
+        let create_career_req = CreateCareerReqMock {
+            create_career_req_id,
+            user_account_id,
+        };
+        let approval_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 4, 1, 21, 0, 40)
+            .unwrap();
+        let op_mock = CreateCareerReqApprovalOperationMock {
+            admin_email_address: admin_email_address.clone(),
+            user_option,
+            create_career_req_mock: create_career_req,
+            company_id: 12,
+            company_exists: false,
+            approved_time: approval_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(),
+        );
+
+        let result = handle_create_career_request_approval(
+            admin_email_address,
+            create_career_req_id,
+            12,
+            approval_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
 
-    #[derive(Debug)]
-    struct Input {
-        from: NaiveDate,
-        to: NaiveDate,
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCompanyFound as u32, resp.1 .0.code);
     }
 
-    static TEST_CASE_SET: Lazy<Vec<TestCase>> = Lazy::new(|| {
-        vec![
-            TestCase {
-                name: "less 1 year".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2009, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2010, 3, 31).expect("failed to get NaiveDate"),
-                },
-                expected: 0,
-            },
-            TestCase {
-                name: "just 1 year".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2009, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
-                },
-                expected: 1,
-            },
-            TestCase {
-                name: "less 1 year (leap year)".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2011, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2012, 3, 30).expect("failed to get NaiveDate"),
-                },
-                expected: 0,
-            },
-            TestCase {
-                name: "just 1 year (leap year)".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2011, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2012, 3, 31).expect("failed to get NaiveDate"),
-                },
-                expected: 1,
-            },
-            TestCase {
-                name: "passed leap year 2 times".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2019, 3, 30).expect("failed to get NaiveDate"),
-                },
-                expected: 9,
-            },
-            TestCase {
-                name: "passed leap year 3 times".to_string(),
-                input: Input {
-                    from: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
-                    to: NaiveDate::from_ymd_opt(2020, 3, 29).expect("failed to get NaiveDate"),
-                },
-                expected: 10,
-            },
-        ]
-    });
+    #[test]
+    fn generate_approved_create_career_req_active_model_copies_verified_work_email_domain() {
+        let model = create_dummy_create_career_req_model();
+        let approved_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 4, 1, 21, 0, 40)
+            .unwrap();
+
+        let result = generate_approved_create_career_req_active_model(
+            model,
+            12,
+            approved_time,
+            String::from("admin@test.com"),
+        );
+
+        assert_eq!(
+            Set(Some("example.co.jp".to_string())),
+            result.verified_work_email_domain
+        );
+    }
 
     #[test]
-    fn calculate_years_of_service_tests() {
-        for test_case in TEST_CASE_SET.iter() {
-            let years_of_service =
-                calculate_years_of_service(test_case.input.from, test_case.input.to);
-            let message = format!("test case \"{}\" failed", test_case.name.clone());
-            assert_eq!(test_case.expected, years_of_service, "{}", message);
+    fn generate_career_active_model_copies_verified_work_email_domain() {
+        let model = create_dummy_create_career_req_model();
+
+        let result = generate_career_active_model(model, 12);
+
+        assert_eq!(
+            Set(Some("example.co.jp".to_string())),
+            result.verified_work_email_domain
+        );
+    }
+
+    fn create_dummy_create_career_req_model() -> create_career_req::Model {
+        create_career_req::Model {
+            create_career_req_id: 1,
+            user_account_id: 432,
+            company_name: "テスト株式会社".to_string(),
+            department_name: None,
+            office: None,
+            career_start_date: chrono::NaiveDate::from_ymd_opt(2015, 4, 1)
+                .expect("failed to get Ok"),
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: None,
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+            image1_file_name_without_ext: "7a2b4e5c9d1f4a3b8c6d0e2f1a3b5c7d".to_string(),
+            image2_file_name_without_ext: None,
+            requested_at: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2023, 3, 31, 10, 0, 0)
+                .unwrap(),
+            claimed_by: None,
+            claimed_at: None,
+            verified_work_email_domain: Some("example.co.jp".to_string()),
         }
     }
 }
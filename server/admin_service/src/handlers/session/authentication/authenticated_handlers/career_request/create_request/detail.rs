@@ -50,6 +50,8 @@ pub(crate) struct CreateCareerReqDetail {
     note: Option<String>,
     image1_file_name_without_ext: String,
     image2_file_name_without_ext: Option<String>,
+    /// ユーザーが勤務先のメールアドレスによる確認に成功している場合、そのメールアドレスのドメイン
+    verified_work_email_domain: Option<String>,
 }
 
 async fn get_create_career_req_detail(
@@ -126,6 +128,7 @@ impl CreateCareerReqDetailOperation for CreateCareerReqDetailOperationImpl {
             note: m.note,
             image1_file_name_without_ext: m.image1_file_name_without_ext,
             image2_file_name_without_ext: m.image2_file_name_without_ext,
+            verified_work_email_domain: m.verified_work_email_domain,
         }))
     }
 }
@@ -182,6 +185,7 @@ mod tests {
             note: None,
             image1_file_name_without_ext: String::from("bcc72c586be3b2a70d6652ff74c6a484"),
             image2_file_name_without_ext: None,
+            verified_work_email_domain: Some("example.co.jp".to_string()),
         };
         let op_mock = CreateCareerReqDetailOperationMock {
             create_career_req_id,
@@ -220,6 +224,7 @@ mod tests {
             note: None,
             image1_file_name_without_ext: String::from("bcc72c586be3b2a70d6652ff74c6a484"),
             image2_file_name_without_ext: None,
+            verified_work_email_domain: None,
         };
         let op_mock = CreateCareerReqDetailOperationMock {
             create_career_req_id: create_career_req_id + 6230,
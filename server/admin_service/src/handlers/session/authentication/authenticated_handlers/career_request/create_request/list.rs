@@ -1,13 +1,13 @@
 // Copyright 2022 Ken Miura
 
 use axum::{async_trait, Json};
-use chrono::{DateTime, FixedOffset};
-use common::{ErrResp, RespResult};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::{ErrResp, RespResult, JAPANESE_TIME_ZONE};
 
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use entity::create_career_req;
-use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryOrder};
+use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
 use serde::Serialize;
 use tracing::error;
 
@@ -16,16 +16,26 @@ use crate::handlers::session::authentication::authenticated_handlers::admin::Adm
 use crate::handlers::session::authentication::authenticated_handlers::pagination::{
     validate_page_size, Pagination,
 };
+use crate::handlers::session::authentication::authenticated_handlers::review_claim::{
+    ClaimStatusQuery, ReviewClaimFilter,
+};
 
 pub(crate) async fn get_create_career_requests(
-    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
     pagination: Query<Pagination>,
+    claim_status_query: Query<ClaimStatusQuery>,
     State(pool): State<DatabaseConnection>,
 ) -> RespResult<Vec<CreateCareerReqItem>> {
     let pagination = pagination.0;
     validate_page_size(pagination.per_page)?;
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let review_claim_filter = ReviewClaimFilter::new(
+        claim_status_query.0,
+        admin_info.email_address,
+        current_date_time,
+    );
     let op = CreateCareerRequestItemsOperationImpl { pool };
-    get_create_career_request_items(pagination, op).await
+    get_create_career_request_items(pagination, review_claim_filter, op).await
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -37,9 +47,12 @@ pub(crate) struct CreateCareerReqItem {
 
 async fn get_create_career_request_items(
     pagination: Pagination,
+    review_claim_filter: Option<ReviewClaimFilter>,
     op: impl CreateCareerRequestItemsOperation,
 ) -> RespResult<Vec<CreateCareerReqItem>> {
-    let items = op.get_items(pagination.page, pagination.per_page).await?;
+    let items = op
+        .get_items(pagination.page, pagination.per_page, review_claim_filter)
+        .await?;
     Ok((StatusCode::OK, Json(items)))
 }
 
@@ -49,6 +62,7 @@ trait CreateCareerRequestItemsOperation {
         &self,
         page: u64,
         page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
     ) -> Result<Vec<CreateCareerReqItem>, ErrResp>;
 }
 
@@ -62,8 +76,16 @@ impl CreateCareerRequestItemsOperation for CreateCareerRequestItemsOperationImpl
         &self,
         page: u64,
         page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
     ) -> Result<Vec<CreateCareerReqItem>, ErrResp> {
-        let items = create_career_req::Entity::find()
+        let mut query = create_career_req::Entity::find();
+        if let Some(review_claim_filter) = review_claim_filter {
+            query = query.filter(review_claim_filter.create_condition(
+                create_career_req::Column::ClaimedBy,
+                create_career_req::Column::ClaimedAt,
+            ));
+        }
+        let items = query
             .order_by_asc(create_career_req::Column::RequestedAt)
             .paginate(&self.pool, page_size)
             .fetch_page(page)
@@ -109,6 +131,7 @@ mod tests {
             &self,
             page: u64,
             page_size: u64,
+            _review_claim_filter: Option<ReviewClaimFilter>,
         ) -> Result<Vec<CreateCareerReqItem>, ErrResp> {
             let items = self.items.clone();
             let length = items.len() as u64;
@@ -142,7 +165,7 @@ mod tests {
             per_page: 3,
         };
 
-        let result = get_create_career_request_items(pagination, op_mock).await;
+        let result = get_create_career_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -160,7 +183,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_create_career_request_items(pagination, op_mock).await;
+        let result = get_create_career_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -178,7 +201,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_create_career_request_items(pagination, op_mock).await;
+        let result = get_create_career_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -197,7 +220,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_create_career_request_items(pagination, op_mock).await;
+        let result = get_create_career_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
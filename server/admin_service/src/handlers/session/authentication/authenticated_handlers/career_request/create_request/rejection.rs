@@ -4,8 +4,8 @@ use axum::{async_trait, Json};
 use chrono::{DateTime, FixedOffset, Utc};
 use common::{
     smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS},
-    storage::{StorageClient, CAREER_IMAGES_BUCKET_NAME},
-    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
+    storage::StorageClient,
+    ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
 };
 
 use axum::extract::State;
@@ -22,15 +22,24 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use crate::{
-    err::{unexpected_err_resp, Code},
+    err::unexpected_err_resp,
     handlers::session::authentication::authenticated_handlers::{
-        admin::Admin, reason_validator::validate_reason,
+        admin::Admin,
+        rejection_reason_template::{
+            create_rejection_reason, find_rejection_reason_template_by_code,
+            no_rejection_reason_template_found, validate_additional_rejection_reason,
+            RejectionReasonTemplate,
+        },
+        review_claim::ensure_not_claimed_by_other_admin,
         user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
     },
 };
 
 use super::{
-    super::find_create_career_req_model_by_create_career_req_id_with_exclusive_lock,
+    super::{
+        delete_career_images,
+        find_create_career_req_model_by_create_career_req_id_with_exclusive_lock,
+    },
     delete_create_career_req,
 };
 
@@ -51,7 +60,8 @@ pub(crate) async fn post_create_career_request_rejection(
     handle_create_career_request_rejection(
         admin_info.email_address,
         create_career_req_rejection.create_career_req_id,
-        create_career_req_rejection.rejection_reason,
+        create_career_req_rejection.rejection_reason_template_code,
+        create_career_req_rejection.additional_rejection_reason,
         current_date_time,
         op,
         smtp_client,
@@ -62,7 +72,8 @@ pub(crate) async fn post_create_career_request_rejection(
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub(crate) struct CreateCareerReqRejection {
     create_career_req_id: i64,
-    rejection_reason: String,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -71,20 +82,21 @@ pub(crate) struct CreateCareerReqRejectionResult {}
 async fn handle_create_career_request_rejection(
     admin_email_address: String,
     create_career_req_id: i64,
-    rejection_reason: String,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
     rejected_time: DateTime<FixedOffset>,
     op: impl CreateCareerReqRejectionOperation,
     send_mail: impl SendMail,
 ) -> RespResult<CreateCareerReqRejectionResult> {
-    validate_reason(rejection_reason.as_str()).map_err(|e| {
-        error!("invalid format reason ({}): {}", rejection_reason, e);
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError {
-                code: Code::InvalidFormatReason as u32,
-            }),
-        )
-    })?;
+    validate_additional_rejection_reason(additional_rejection_reason.as_deref())?;
+    let template = op
+        .find_rejection_reason_template(rejection_reason_template_code.clone())
+        .await?
+        .ok_or_else(|| {
+            no_rejection_reason_template_found(rejection_reason_template_code.as_str())
+        })?;
+    let rejection_reason =
+        create_rejection_reason(&template, additional_rejection_reason.as_deref());
 
     let user_account_id_option = op
         .get_user_account_id_by_create_career_req_id(create_career_req_id)
@@ -103,6 +115,7 @@ async fn handle_create_career_request_rejection(
             create_career_req_id,
             admin_email_address,
             rejection_reason.clone(),
+            template.code,
             rejected_time,
         )
         .await?;
@@ -133,6 +146,11 @@ async fn handle_create_career_request_rejection(
 
 #[async_trait]
 trait CreateCareerReqRejectionOperation {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp>;
+
     async fn get_user_account_id_by_create_career_req_id(
         &self,
         create_career_req_id: i64,
@@ -144,6 +162,7 @@ trait CreateCareerReqRejectionOperation {
         create_career_req_id: i64,
         refuser_email_address: String,
         rejection_reason: String,
+        rejection_reason_template_code: String,
         rejected_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp>;
 }
@@ -155,6 +174,13 @@ struct CreateCareerReqRejectionOperationImpl {
 
 #[async_trait]
 impl CreateCareerReqRejectionOperation for CreateCareerReqRejectionOperationImpl {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+        find_rejection_reason_template_by_code(&self.pool, code.as_str()).await
+    }
+
     async fn get_user_account_id_by_create_career_req_id(
         &self,
         create_career_req_id: i64,
@@ -178,6 +204,7 @@ impl CreateCareerReqRejectionOperation for CreateCareerReqRejectionOperationImpl
         create_career_req_id: i64,
         refuser_email_address: String,
         rejection_reason: String,
+        rejection_reason_template_code: String,
         rejected_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp> {
         let storage_client = self.storage_client.clone();
@@ -198,6 +225,15 @@ impl CreateCareerReqRejectionOperation for CreateCareerReqRejectionOperationImpl
                         )
                         .await?;
 
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        refuser_email_address.as_str(),
+                        rejected_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
                     let user = match user_option {
                         Some(m) => m,
                         None => {
@@ -217,6 +253,7 @@ impl CreateCareerReqRejectionOperation for CreateCareerReqRejectionOperationImpl
                             req.clone(),
                             rejected_time,
                             rejection_reason,
+                            rejection_reason_template_code,
                             refuser_email_address,
                         );
                     let _ = rejected_req_active_model.insert(txn).await.map_err(|e| {
@@ -261,6 +298,7 @@ fn generate_rejected_create_career_req_active_model(
     model: create_career_req::Model,
     rejected_time: DateTime<FixedOffset>,
     rejection_reason: String,
+    rejection_reason_template_code: String,
     refuser_email_address: String,
 ) -> rejected_create_career_req::ActiveModel {
     rejected_create_career_req::ActiveModel {
@@ -279,50 +317,12 @@ fn generate_rejected_create_career_req_active_model(
         is_new_graduate: Set(model.is_new_graduate),
         note: Set(model.note),
         reason: Set(rejection_reason),
+        reason_template_code: Set(Some(rejection_reason_template_code)),
         rejected_at: Set(rejected_time),
         rejected_by: Set(refuser_email_address),
     }
 }
 
-async fn delete_career_images(
-    storage_client: StorageClient,
-    user_account_id: i64,
-    image1_file_name_without_ext: String,
-    image2_file_name_without_ext: Option<String>,
-) -> Result<(), ErrRespStruct> {
-    let image1_key = format!("{}/{}.png", user_account_id, image1_file_name_without_ext);
-    storage_client
-        .delete_object(CAREER_IMAGES_BUCKET_NAME.as_str(), image1_key.as_str())
-        .await
-        .map_err(|e| {
-            error!(
-                "failed to delete career image1 (key: {}): {}",
-                image1_key, e
-            );
-            ErrRespStruct {
-                err_resp: unexpected_err_resp(),
-            }
-        })?;
-
-    if let Some(image2_file_name_without_ext) = image2_file_name_without_ext {
-        let image2_key = format!("{}/{}.png", user_account_id, image2_file_name_without_ext);
-        storage_client
-            .delete_object(CAREER_IMAGES_BUCKET_NAME.as_str(), image2_key.as_str())
-            .await
-            .map_err(|e| {
-                error!(
-                    "failed to delete career image2 (key: {}): {}",
-                    image2_key, e
-                );
-                ErrRespStruct {
-                    err_resp: unexpected_err_resp(),
-                }
-            })?;
-    }
-
-    Ok(())
-}
-
 fn create_text(rejection_reason: String) -> String {
     format!(
         r"下記の【拒否理由】により、職務経歴の登録を拒否いたしました。お手数ですが、再度職務経歴確認依頼をお願いいたします。
@@ -371,11 +371,22 @@ mod tests {
         user_option: Option<User>,
         create_career_req_mock: CreateCareerReqMock,
         rejection_reason: String,
+        rejection_reason_template: RejectionReasonTemplate,
         rejected_time: DateTime<FixedOffset>,
     }
 
     #[async_trait]
     impl CreateCareerReqRejectionOperation for CreateCareerReqRejectionOperationMock {
+        async fn find_rejection_reason_template(
+            &self,
+            code: String,
+        ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+            if self.rejection_reason_template.code != code {
+                return Ok(None);
+            }
+            Ok(Some(self.rejection_reason_template.clone()))
+        }
+
         async fn get_user_account_id_by_create_career_req_id(
             &self,
             create_career_req_id: i64,
@@ -393,6 +404,7 @@ mod tests {
             create_career_req_id: i64,
             refuser_email_address: String,
             rejection_reason: String,
+            rejection_reason_template_code: String,
             rejected_time: DateTime<FixedOffset>,
         ) -> Result<Option<String>, ErrResp> {
             if let Some(user) = self.user_option.clone() {
@@ -403,6 +415,10 @@ mod tests {
                     create_career_req_id
                 );
                 assert_eq!(self.rejection_reason, rejection_reason);
+                assert_eq!(
+                    self.rejection_reason_template.code,
+                    rejection_reason_template_code
+                );
                 assert_eq!(self.rejected_time, rejected_time);
                 Ok(Some(user.email_address))
             } else {
@@ -411,6 +427,13 @@ mod tests {
         }
     }
 
+    fn create_rejection_reason_template() -> RejectionReasonTemplate {
+        RejectionReasonTemplate {
+            code: "blurred-image".to_string(),
+            body: "画像が不鮮明なため".to_string(),
+        }
+    }
+
     #[tokio::test]
     async fn handle_create_career_request_rejection_success() {
         let admin_email_address = String::from("admin@test.com");
@@ -434,6 +457,7 @@ mod tests {
             user_option,
             create_career_req_mock: create_career_req,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -446,7 +470,8 @@ mod tests {
         let result = handle_create_career_request_rejection(
             admin_email_address,
             create_career_req_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            None,
             rejected_time,
             op_mock,
             send_mail_mock,
@@ -481,6 +506,7 @@ mod tests {
             user_option,
             create_career_req_mock: create_career_req,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -493,7 +519,8 @@ mod tests {
         let result = handle_create_career_request_rejection(
             admin_email_address,
             create_career_req_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            Some(rejection_reason.to_string()),
             rejected_time,
             op_mock,
             send_mail_mock,
@@ -505,6 +532,55 @@ mod tests {
         assert_eq!(Code::InvalidFormatReason as u32, resp.1 .0.code);
     }
 
+    #[tokio::test]
+    async fn handle_create_career_request_rejection_fail_no_rejection_reason_template_found() {
+        let admin_email_address = String::from("admin@test.com");
+        let user_account_id = 53;
+        let user_email_address = String::from("test@test.com");
+        let user_option = Some(User {
+            user_account_id,
+            email_address: user_email_address.clone(),
+        });
+        let create_career_req_id = 51514;
+        let create_career_req = CreateCareerReqMock {
+            create_career_req_id,
+            user_account_id,
+        };
+        let rejection_reason = "画像が不鮮明なため";
+        let rejected_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 4, 5, 21, 0, 40)
+            .unwrap();
+        let op_mock = CreateCareerReqRejectionOperationMock {
+            admin_email_address: admin_email_address.clone(),
+            user_option,
+            create_career_req_mock: create_career_req,
+            rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
+            rejected_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            user_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_create_career_request_rejection(
+            admin_email_address,
+            create_career_req_id,
+            "unknown-code".to_string(),
+            None,
+            rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoRejectionReasonTemplateFound as u32, resp.1 .0.code);
+    }
+
     #[tokio::test]
     async fn handle_create_career_request_rejection_success_no_user_account_found() {
         let admin_email_address = String::from("admin@test.com");
@@ -524,6 +600,7 @@ mod tests {
             user_option: None,
             create_career_req_mock: create_career_req,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -536,7 +613,8 @@ mod tests {
         let result = handle_create_career_request_rejection(
             admin_email_address,
             create_career_req_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            None,
             rejected_time,
             op_mock,
             send_mail_mock,
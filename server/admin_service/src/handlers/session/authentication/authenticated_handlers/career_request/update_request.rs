@@ -0,0 +1,60 @@
+// Copyright 2023 Ken Miura
+
+pub(crate) mod approval;
+pub(crate) mod detail;
+pub(crate) mod list;
+pub(crate) mod rejection;
+
+use common::ErrRespStruct;
+use entity::{
+    sea_orm::{DatabaseTransaction, EntityTrait, QuerySelect},
+    update_career_req,
+};
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+
+async fn delete_update_career_req(
+    career_id: i64,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let _ = update_career_req::Entity::delete_by_id(career_id)
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete update_career_req (career_id: {}): {}",
+                career_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(())
+}
+
+async fn find_update_career_req_model_by_career_id_with_exclusive_lock(
+    txn: &DatabaseTransaction,
+    career_id: i64,
+) -> Result<update_career_req::Model, ErrRespStruct> {
+    let req_option = update_career_req::Entity::find_by_id(career_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find update_career_req (career_id: {}): {}",
+                career_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    let req = req_option.ok_or_else(|| {
+        error!("no update_career_req (career_id: {}) found", career_id);
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(req)
+}
@@ -0,0 +1,636 @@
+// Copyright 2023 Ken Miura
+
+use async_session::serde_json::json;
+use axum::{async_trait, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::{
+    company::insert_company_alias_if_not_exists,
+    opensearch::{create_career_document, INDEX_NAME},
+    smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS},
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
+};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use entity::{
+    approved_update_career_req, career,
+    sea_orm::{
+        ActiveModelTrait, ActiveValue::NotSet, DatabaseConnection, DatabaseTransaction,
+        EntityTrait, QuerySelect, Set, TransactionError, TransactionTrait,
+    },
+    update_career_req,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    err::{unexpected_err_resp, Code},
+    handlers::session::authentication::authenticated_handlers::{
+        admin::Admin,
+        document_operation::find_document_model_by_user_account_id_with_exclusive_lock,
+        review_claim::ensure_not_claimed_by_other_admin,
+        user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
+    },
+};
+
+use super::{
+    delete_update_career_req, find_update_career_req_model_by_career_id_with_exclusive_lock,
+};
+
+static SUBJECT: Lazy<String> = Lazy::new(|| format!("[{}] 職務経歴更新完了通知", WEB_SITE_NAME));
+
+pub(crate) async fn post_update_career_request_approval(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(smtp_client): State<SmtpClient>,
+    State(pool): State<DatabaseConnection>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
+    Json(update_career_req_approval): Json<UpdateCareerReqApproval>,
+) -> RespResult<UpdateCareerReqApprovalResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = UpdateCareerReqApprovalOperationImpl {
+        pool,
+        consultant_search_client,
+    };
+    handle_update_career_request_approval(
+        admin_info.email_address,
+        update_career_req_approval.career_id,
+        update_career_req_approval.company_id,
+        current_date_time,
+        op,
+        smtp_client,
+    )
+    .await
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqApproval {
+    career_id: i64,
+    /// 更新後の職務経歴の会社名に対応する会社（会社名を更新しない場合、現在の職務経歴の会社）
+    company_id: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqApprovalResult {}
+
+async fn handle_update_career_request_approval(
+    admin_email_address: String,
+    career_id: i64,
+    company_id: i64,
+    approved_time: DateTime<FixedOffset>,
+    op: impl UpdateCareerReqApprovalOperation,
+    send_mail: impl SendMail,
+) -> RespResult<UpdateCareerReqApprovalResult> {
+    let user_account_id_option = op
+        .get_user_account_id_by_career_id_in_update_career_req(career_id)
+        .await?;
+    let user_account_id = user_account_id_option.ok_or_else(|| {
+        error!("no update career request (career id: {}) found", career_id);
+        unexpected_err_resp()
+    })?;
+
+    let company_exists = op.company_exists(company_id).await?;
+    if !company_exists {
+        error!("no company (company_id: {}) found", company_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCompanyFound as u32,
+            }),
+        ));
+    }
+
+    let approved_user = op
+        .approve_update_career_req(
+            user_account_id,
+            career_id,
+            company_id,
+            admin_email_address,
+            approved_time,
+        )
+        .await?;
+
+    let user_email_address = match approved_user {
+        Some(u) => u,
+        None => {
+            // 承認をしようとした際、既にユーザーがアカウントを削除しているケース、Disabledになっているケース、または職務経歴を削除しているケース
+            info!(
+                "no user account (user account id: {}) found, the account is disabled or no career (career id: {}) found",
+                user_account_id, career_id
+            );
+            return Ok((StatusCode::OK, Json(UpdateCareerReqApprovalResult {})));
+        }
+    };
+
+    send_mail
+        .send_mail(
+            &user_email_address,
+            SYSTEM_EMAIL_ADDRESS.as_str(),
+            &SUBJECT,
+            create_text().as_str(),
+        )
+        .await?;
+
+    Ok((StatusCode::OK, Json(UpdateCareerReqApprovalResult {})))
+}
+
+#[async_trait]
+trait UpdateCareerReqApprovalOperation {
+    async fn get_user_account_id_by_career_id_in_update_career_req(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<i64>, ErrResp>;
+
+    async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp>;
+
+    async fn approve_update_career_req(
+        &self,
+        user_account_id: i64,
+        career_id: i64,
+        company_id: i64,
+        approver_email_address: String,
+        approved_time: DateTime<FixedOffset>,
+    ) -> Result<Option<String>, ErrResp>;
+}
+
+struct UpdateCareerReqApprovalOperationImpl {
+    pool: DatabaseConnection,
+    consultant_search_client: ConsultantSearchClient,
+}
+
+#[async_trait]
+impl UpdateCareerReqApprovalOperation for UpdateCareerReqApprovalOperationImpl {
+    async fn get_user_account_id_by_career_id_in_update_career_req(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<i64>, ErrResp> {
+        let model = update_career_req::Entity::find_by_id(career_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find update_career_req (career_id: {}): {}",
+                    career_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.map(|m| m.user_account_id))
+    }
+
+    async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp> {
+        let model = entity::company::Entity::find_by_id(company_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to find company (company_id: {}): {}", company_id, e);
+                unexpected_err_resp()
+            })?;
+        Ok(model.is_some())
+    }
+
+    async fn approve_update_career_req(
+        &self,
+        user_account_id: i64,
+        career_id: i64,
+        company_id: i64,
+        approver_email_address: String,
+        approved_time: DateTime<FixedOffset>,
+    ) -> Result<Option<String>, ErrResp> {
+        let consultant_search_client = self.consultant_search_client.clone();
+        let notification_email_address_option = self
+            .pool
+            .transaction::<_, Option<String>, ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let user_option =
+                        find_user_account_model_by_user_account_id_with_shared_lock(txn, user_account_id).await?;
+
+                    let career_option =
+                        find_career_model_by_career_id_with_exclusive_lock(txn, career_id).await?;
+
+                    let req =
+                        find_update_career_req_model_by_career_id_with_exclusive_lock(txn, career_id).await?;
+
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        approver_email_address.as_str(),
+                        approved_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
+                    let user = match user_option {
+                        Some(m) => m,
+                        None => {
+                            delete_update_career_req(career_id, txn).await?;
+                            return Ok(None);
+                        }
+                    };
+                    if user.disabled_at.is_some() {
+                        delete_update_career_req(career_id, txn).await?;
+                        return Ok(None);
+                    }
+                    // 依頼後にユーザーが職務経歴を削除しているケース
+                    if career_option.is_none() {
+                        delete_update_career_req(career_id, txn).await?;
+                        return Ok(None);
+                    }
+
+                    // 次回以降、同じ表記の会社名で会社を見つけられるように別名として登録しておく
+                    let _ = insert_company_alias_if_not_exists(company_id, &req.company_name, txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to insert company_alias (company_id: {}, company_name: {}): {}",
+                                company_id, req.company_name, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+
+                    let verified_work_email_domain = career_option
+                        .and_then(|c| find_verified_work_email_domain_to_inherit(c, company_id));
+                    let career_active_model = generate_career_active_model(req.clone(), company_id, verified_work_email_domain);
+                    let career_model = career_active_model.update(txn).await.map_err(|e| {
+                        error!(
+                            "failed to update career (career_id: {}): {}",
+                            career_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+
+                    let approved_req = generate_approved_update_career_req_active_model(
+                        req,
+                        company_id,
+                        approved_time,
+                        approver_email_address,
+                    );
+                    let _ = approved_req.insert(txn).await.map_err(|e| {
+                        error!(
+                            "failed to insert approved_update_career_req (career_id: {}): {}",
+                            career_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+
+                    delete_update_career_req(career_id, txn).await?;
+
+                    let document_option =
+                        find_document_model_by_user_account_id_with_exclusive_lock(txn, user_account_id).await?;
+                    if let Some(document) = document_option {
+                        info!("update career in document (user_account_id: {}, document_id: {}, career_model: {:?})", user_account_id, document.document_id, career_model);
+                        let _ = replace_career_in_document(
+                            INDEX_NAME,
+                            document.document_id.to_string().as_str(),
+                            career_model,
+                            approved_time,
+                            consultant_search_client,
+                        )
+                        .await?;
+                    } else {
+                        // ドキュメントが存在しない場合、インデックスに職務経歴の情報は存在しないため、インデックスの更新は不要
+                        info!("no document found (user_account_id: {})", user_account_id);
+                    }
+
+                    Ok(Some(user.email_address))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!("failed to approve update_career_req: {}", err_resp_struct);
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(notification_email_address_option)
+    }
+}
+
+async fn find_career_model_by_career_id_with_exclusive_lock(
+    txn: &DatabaseTransaction,
+    career_id: i64,
+) -> Result<Option<career::Model>, ErrRespStruct> {
+    let career_option = career::Entity::find_by_id(career_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+        .map_err(|e| {
+            error!("failed to find career (career_id: {}): {}", career_id, e);
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(career_option)
+}
+
+/// 更新後の職務経歴に引き継ぐ確認済みの勤務先メールアドレスのドメインを返す
+///
+/// 勤務先メールアドレスの確認は更新前の会社に対して行われたものなので、会社が変わる場合は引き継がない
+fn find_verified_work_email_domain_to_inherit(
+    career_model: career::Model,
+    company_id: i64,
+) -> Option<String> {
+    if career_model.company_id != company_id {
+        return None;
+    }
+    career_model.verified_work_email_domain
+}
+
+fn generate_career_active_model(
+    model: update_career_req::Model,
+    company_id: i64,
+    verified_work_email_domain: Option<String>,
+) -> career::ActiveModel {
+    career::ActiveModel {
+        career_id: Set(model.career_id),
+        user_account_id: Set(model.user_account_id),
+        company_name: Set(model.company_name),
+        company_id: Set(company_id),
+        department_name: Set(model.department_name),
+        office: Set(model.office),
+        career_start_date: Set(model.career_start_date),
+        career_end_date: Set(model.career_end_date),
+        contract_type: Set(model.contract_type),
+        profession: Set(model.profession),
+        annual_income_in_man_yen: Set(model.annual_income_in_man_yen),
+        is_manager: Set(model.is_manager),
+        position_name: Set(model.position_name),
+        is_new_graduate: Set(model.is_new_graduate),
+        note: Set(model.note),
+        verified_work_email_domain: Set(verified_work_email_domain),
+    }
+}
+
+fn generate_approved_update_career_req_active_model(
+    model: update_career_req::Model,
+    company_id: i64,
+    approved_time: DateTime<FixedOffset>,
+    approver_email_address: String,
+) -> approved_update_career_req::ActiveModel {
+    approved_update_career_req::ActiveModel {
+        appr_upd_career_req_id: NotSet,
+        career_id: Set(model.career_id),
+        user_account_id: Set(model.user_account_id),
+        company_name: Set(model.company_name),
+        company_id: Set(company_id),
+        department_name: Set(model.department_name),
+        office: Set(model.office),
+        career_start_date: Set(model.career_start_date),
+        career_end_date: Set(model.career_end_date),
+        contract_type: Set(model.contract_type),
+        profession: Set(model.profession),
+        annual_income_in_man_yen: Set(model.annual_income_in_man_yen),
+        is_manager: Set(model.is_manager),
+        position_name: Set(model.position_name),
+        is_new_graduate: Set(model.is_new_graduate),
+        note: Set(model.note),
+        image1_file_name_without_ext: Set(model.image1_file_name_without_ext),
+        image2_file_name_without_ext: Set(model.image2_file_name_without_ext),
+        approved_at: Set(approved_time),
+        approved_by: Set(approver_email_address),
+    }
+}
+
+/// ドキュメントのcareersのうち、career_idが一致する職務経歴を更新後の職務経歴で置き換える
+///
+/// 職務経歴の数は変わらないため、num_of_careersは更新しない
+async fn replace_career_in_document(
+    index_name: &str,
+    document_id: &str,
+    career_model: career::Model,
+    current_time: DateTime<FixedOffset>,
+    client: ConsultantSearchClient,
+) -> Result<(), ErrRespStruct> {
+    let career = create_career_document(&career_model, current_time.naive_local().date());
+    let script = json!({
+        "script": {
+            "source": "ctx._source.careers.replaceAll(career -> career.career_id == params.career.career_id ? params.career : career)",
+            "params": {
+              "career": career
+            }
+        }
+    });
+    client
+        .update_document(index_name, document_id, &script)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to replace career in document (document_id: {}, career_id: {})",
+                document_id, career_model.career_id
+            );
+            ErrRespStruct { err_resp: e }
+        })?;
+    Ok(())
+}
+
+fn create_text() -> String {
+    format!(
+        r"職務経歴の更新内容の確認が完了し、職務経歴を更新致しました。
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+        INQUIRY_EMAIL_ADDRESS.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+    use axum::http::StatusCode;
+    use chrono::{DateTime, FixedOffset, TimeZone};
+    use common::{smtp::SYSTEM_EMAIL_ADDRESS, ErrResp, JAPANESE_TIME_ZONE};
+
+    use crate::handlers::session::authentication::authenticated_handlers::tests::SendMailMock;
+
+    use super::*;
+
+    struct UpdateCareerReqApprovalOperationMock {
+        admin_email_address: String,
+        user_email_address_option: Option<String>,
+        user_account_id: i64,
+        career_id: i64,
+        company_id: i64,
+        company_exists: bool,
+        approved_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl UpdateCareerReqApprovalOperation for UpdateCareerReqApprovalOperationMock {
+        async fn get_user_account_id_by_career_id_in_update_career_req(
+            &self,
+            career_id: i64,
+        ) -> Result<Option<i64>, ErrResp> {
+            assert_eq!(self.career_id, career_id);
+            Ok(Some(self.user_account_id))
+        }
+
+        async fn company_exists(&self, company_id: i64) -> Result<bool, ErrResp> {
+            assert_eq!(self.company_id, company_id);
+            Ok(self.company_exists)
+        }
+
+        async fn approve_update_career_req(
+            &self,
+            user_account_id: i64,
+            career_id: i64,
+            company_id: i64,
+            approver_email_address: String,
+            approved_time: DateTime<FixedOffset>,
+        ) -> Result<Option<String>, ErrResp> {
+            assert_eq!(self.user_account_id, user_account_id);
+            assert_eq!(self.career_id, career_id);
+            assert_eq!(self.company_id, company_id);
+            assert_eq!(self.admin_email_address, approver_email_address);
+            assert_eq!(self.approved_time, approved_time);
+            Ok(self.user_email_address_option.clone())
+        }
+    }
+
+    fn create_op_mock(
+        user_email_address_option: Option<String>,
+        company_exists: bool,
+    ) -> UpdateCareerReqApprovalOperationMock {
+        UpdateCareerReqApprovalOperationMock {
+            admin_email_address: String::from("admin@test.com"),
+            user_email_address_option,
+            user_account_id: 432,
+            career_id: 7612,
+            company_id: 12,
+            company_exists,
+            approved_time: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2023, 4, 1, 21, 0, 40)
+                .unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_approval_success() {
+        let user_email_address = String::from("test@test.com");
+        let op_mock = create_op_mock(Some(user_email_address.clone()), true);
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(),
+        );
+
+        let result = handle_update_career_request_approval(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            op_mock.company_id,
+            op_mock.approved_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerReqApprovalResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_approval_success_no_user_account_or_career_found() {
+        let op_mock = create_op_mock(None, true);
+        let send_mail_mock = SendMailMock::new(
+            String::from("test@test.com"),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(),
+        );
+
+        let result = handle_update_career_request_approval(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            op_mock.company_id,
+            op_mock.approved_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerReqApprovalResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_approval_fail_no_company_found() {
+        let user_email_address = String::from("test@test.com");
+        let op_mock = create_op_mock(Some(user_email_address.clone()), false);
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(),
+        );
+
+        let result = handle_update_career_request_approval(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            op_mock.company_id,
+            op_mock.approved_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCompanyFound as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn find_verified_work_email_domain_to_inherit_returns_domain_if_company_is_not_changed() {
+        let career_model = create_dummy_career_model(12);
+
+        let result = find_verified_work_email_domain_to_inherit(career_model, 12);
+
+        assert_eq!(Some("example.co.jp".to_string()), result);
+    }
+
+    #[test]
+    fn find_verified_work_email_domain_to_inherit_returns_none_if_company_is_changed() {
+        let career_model = create_dummy_career_model(12);
+
+        let result = find_verified_work_email_domain_to_inherit(career_model, 13);
+
+        assert_eq!(None, result);
+    }
+
+    fn create_dummy_career_model(company_id: i64) -> career::Model {
+        career::Model {
+            career_id: 7612,
+            user_account_id: 432,
+            company_name: "テスト株式会社".to_string(),
+            company_id,
+            department_name: None,
+            office: None,
+            career_start_date: chrono::NaiveDate::from_ymd_opt(2015, 4, 1)
+                .expect("failed to get Ok"),
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: None,
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+            verified_work_email_domain: Some("example.co.jp".to_string()),
+        }
+    }
+}
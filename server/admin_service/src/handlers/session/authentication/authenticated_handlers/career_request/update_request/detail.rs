@@ -0,0 +1,278 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Datelike, NaiveDate};
+use common::util::Ymd;
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::{DatabaseConnection, EntityTrait};
+use entity::{career, update_career_req};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+use crate::err::Code::NoUpdateCareerReqDetailFound;
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+
+pub(crate) async fn get_update_career_request_detail(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<UpdateCareerReqDetailQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<UpdateCareerReqDetail> {
+    let query = query.0;
+    let op = UpdateCareerReqDetailOperationImpl { pool };
+    get_update_career_req_detail(query.career_id, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateCareerReqDetailQuery {
+    career_id: i64,
+}
+
+/// 職務経歴の更新依頼の内容
+///
+/// 管理者が変更点を確認できるように、更新後の値に加えて現在の職務経歴を返す。
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqDetail {
+    user_account_id: i64,
+    company_name: String,
+    department_name: Option<String>,
+    office: Option<String>,
+    career_start_date: Ymd,
+    career_end_date: Option<Ymd>,
+    contract_type: String,
+    profession: Option<String>,
+    annual_income_in_man_yen: Option<i32>,
+    is_manager: bool,
+    position_name: Option<String>,
+    is_new_graduate: bool,
+    note: Option<String>,
+    /// 退社日と備考のみの更新の場合、画像は提出されないためNone
+    image1_file_name_without_ext: Option<String>,
+    image2_file_name_without_ext: Option<String>,
+    /// 依頼後にユーザーが職務経歴を削除した場合、None
+    current_career: Option<CurrentCareer>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct CurrentCareer {
+    company_name: String,
+    company_id: i64,
+    department_name: Option<String>,
+    office: Option<String>,
+    career_start_date: Ymd,
+    career_end_date: Option<Ymd>,
+    contract_type: String,
+    profession: Option<String>,
+    annual_income_in_man_yen: Option<i32>,
+    is_manager: bool,
+    position_name: Option<String>,
+    is_new_graduate: bool,
+    note: Option<String>,
+}
+
+async fn get_update_career_req_detail(
+    career_id: i64,
+    op: impl UpdateCareerReqDetailOperation,
+) -> RespResult<UpdateCareerReqDetail> {
+    let req_detail_option = op.get_update_career_req_detail(career_id).await?;
+    let req_detail = req_detail_option.ok_or_else(|| {
+        error!("no update career request (career id: {}) found", career_id);
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: NoUpdateCareerReqDetailFound as u32,
+            }),
+        )
+    })?;
+    Ok((StatusCode::OK, Json(req_detail)))
+}
+
+#[async_trait]
+trait UpdateCareerReqDetailOperation {
+    async fn get_update_career_req_detail(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<UpdateCareerReqDetail>, ErrResp>;
+}
+
+struct UpdateCareerReqDetailOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl UpdateCareerReqDetailOperation for UpdateCareerReqDetailOperationImpl {
+    async fn get_update_career_req_detail(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<UpdateCareerReqDetail>, ErrResp> {
+        let req_option = update_career_req::Entity::find_by_id(career_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find update_career_req (career_id: {}): {}",
+                    career_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        let req = match req_option {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        let career_option = career::Entity::find_by_id(career_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to find career (career_id: {}): {}", career_id, e);
+                unexpected_err_resp()
+            })?;
+        Ok(Some(UpdateCareerReqDetail {
+            user_account_id: req.user_account_id,
+            company_name: req.company_name,
+            department_name: req.department_name,
+            office: req.office,
+            career_start_date: convert_date_to_ymd(req.career_start_date),
+            career_end_date: req.career_end_date.map(convert_date_to_ymd),
+            contract_type: req.contract_type,
+            profession: req.profession,
+            annual_income_in_man_yen: req.annual_income_in_man_yen,
+            is_manager: req.is_manager,
+            position_name: req.position_name,
+            is_new_graduate: req.is_new_graduate,
+            note: req.note,
+            image1_file_name_without_ext: req.image1_file_name_without_ext,
+            image2_file_name_without_ext: req.image2_file_name_without_ext,
+            current_career: career_option.map(|m| CurrentCareer {
+                company_name: m.company_name,
+                company_id: m.company_id,
+                department_name: m.department_name,
+                office: m.office,
+                career_start_date: convert_date_to_ymd(m.career_start_date),
+                career_end_date: m.career_end_date.map(convert_date_to_ymd),
+                contract_type: m.contract_type,
+                profession: m.profession,
+                annual_income_in_man_yen: m.annual_income_in_man_yen,
+                is_manager: m.is_manager,
+                position_name: m.position_name,
+                is_new_graduate: m.is_new_graduate,
+                note: m.note,
+            }),
+        }))
+    }
+}
+
+fn convert_date_to_ymd(date: NaiveDate) -> Ymd {
+    Ymd {
+        year: date.year(),
+        month: date.month(),
+        day: date.day(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::err::Code::NoUpdateCareerReqDetailFound;
+    use async_session::async_trait;
+    use axum::http::StatusCode;
+    use common::{util::Ymd, ErrResp};
+
+    use super::*;
+
+    struct UpdateCareerReqDetailOperationMock {
+        career_id: i64,
+        update_career_req_detail: UpdateCareerReqDetail,
+    }
+
+    #[async_trait]
+    impl UpdateCareerReqDetailOperation for UpdateCareerReqDetailOperationMock {
+        async fn get_update_career_req_detail(
+            &self,
+            career_id: i64,
+        ) -> Result<Option<UpdateCareerReqDetail>, ErrResp> {
+            if self.career_id != career_id {
+                return Ok(None);
+            }
+            Ok(Some(self.update_career_req_detail.clone()))
+        }
+    }
+
+    fn create_dummy_update_career_req_detail() -> UpdateCareerReqDetail {
+        let career_start_date = Ymd {
+            year: 1991,
+            month: 4,
+            day: 1,
+        };
+        UpdateCareerReqDetail {
+            user_account_id: 123,
+            company_name: "テスト株式会社１".to_string(),
+            department_name: None,
+            office: None,
+            career_start_date: career_start_date.clone(),
+            career_end_date: Some(Ymd {
+                year: 2023,
+                month: 3,
+                day: 31,
+            }),
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: None,
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+            image1_file_name_without_ext: None,
+            image2_file_name_without_ext: None,
+            current_career: Some(CurrentCareer {
+                company_name: "テスト株式会社１".to_string(),
+                company_id: 51,
+                department_name: None,
+                office: None,
+                career_start_date,
+                career_end_date: None,
+                contract_type: "regular".to_string(),
+                profession: None,
+                annual_income_in_man_yen: None,
+                is_manager: false,
+                position_name: None,
+                is_new_graduate: true,
+                note: None,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_update_career_req_detail_success() {
+        let career_id = 5135;
+        let update_career_req_detail = create_dummy_update_career_req_detail();
+        let op_mock = UpdateCareerReqDetailOperationMock {
+            career_id,
+            update_career_req_detail: update_career_req_detail.clone(),
+        };
+
+        let result = get_update_career_req_detail(career_id, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(update_career_req_detail, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn get_update_career_req_detail_fail_no_req_detail_found() {
+        let career_id = 5135;
+        let op_mock = UpdateCareerReqDetailOperationMock {
+            career_id: career_id + 6230,
+            update_career_req_detail: create_dummy_update_career_req_detail(),
+        };
+
+        let result = get_update_career_req_detail(career_id, op_mock).await;
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(NoUpdateCareerReqDetailFound as u32, err_resp.1 .0.code);
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2023 Ken Miura
+
+use axum::{async_trait, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::{ErrResp, RespResult, JAPANESE_TIME_ZONE};
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
+use entity::update_career_req;
+use serde::Serialize;
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+use crate::handlers::session::authentication::authenticated_handlers::pagination::{
+    validate_page_size, Pagination,
+};
+use crate::handlers::session::authentication::authenticated_handlers::review_claim::{
+    ClaimStatusQuery, ReviewClaimFilter,
+};
+
+pub(crate) async fn get_update_career_requests(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    pagination: Query<Pagination>,
+    claim_status_query: Query<ClaimStatusQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<Vec<UpdateCareerReqItem>> {
+    let pagination = pagination.0;
+    validate_page_size(pagination.per_page)?;
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let review_claim_filter = ReviewClaimFilter::new(
+        claim_status_query.0,
+        admin_info.email_address,
+        current_date_time,
+    );
+    let op = UpdateCareerRequestItemsOperationImpl { pool };
+    get_update_career_request_items(pagination, review_claim_filter, op).await
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqItem {
+    career_id: i64,
+    company_name: String,
+    requested_at: DateTime<FixedOffset>,
+}
+
+async fn get_update_career_request_items(
+    pagination: Pagination,
+    review_claim_filter: Option<ReviewClaimFilter>,
+    op: impl UpdateCareerRequestItemsOperation,
+) -> RespResult<Vec<UpdateCareerReqItem>> {
+    let items = op
+        .get_items(pagination.page, pagination.per_page, review_claim_filter)
+        .await?;
+    Ok((StatusCode::OK, Json(items)))
+}
+
+#[async_trait]
+trait UpdateCareerRequestItemsOperation {
+    async fn get_items(
+        &self,
+        page: u64,
+        page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
+    ) -> Result<Vec<UpdateCareerReqItem>, ErrResp>;
+}
+
+struct UpdateCareerRequestItemsOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl UpdateCareerRequestItemsOperation for UpdateCareerRequestItemsOperationImpl {
+    async fn get_items(
+        &self,
+        page: u64,
+        page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
+    ) -> Result<Vec<UpdateCareerReqItem>, ErrResp> {
+        let mut query = update_career_req::Entity::find();
+        if let Some(review_claim_filter) = review_claim_filter {
+            query = query.filter(review_claim_filter.create_condition(
+                update_career_req::Column::ClaimedBy,
+                update_career_req::Column::ClaimedAt,
+            ));
+        }
+        let items = query
+            .order_by_asc(update_career_req::Column::RequestedAt)
+            .paginate(&self.pool, page_size)
+            .fetch_page(page)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to fetch page (page: {}, page_size: {}) in update_career_req: {}",
+                    page, page_size, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(items
+            .iter()
+            .map(|model| UpdateCareerReqItem {
+                career_id: model.career_id,
+                company_name: model.company_name.to_string(),
+                requested_at: model.requested_at,
+            })
+            .collect::<Vec<UpdateCareerReqItem>>())
+    }
+}
+
+// ロジックはDBへのクエリのみでテストは必要ないかもしれないが、
+// DBへのクエリ（ORMのAPI）への期待する動作を記すためにテストを記載しておく。
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+    use axum::http::StatusCode;
+    use chrono::{Duration, TimeZone};
+    use common::{ErrResp, JAPANESE_TIME_ZONE};
+
+    use crate::handlers::session::authentication::authenticated_handlers::pagination::Pagination;
+
+    use super::*;
+
+    struct UpdateCareerRequestItemsOperationMock {
+        items: Vec<UpdateCareerReqItem>,
+    }
+
+    #[async_trait]
+    impl UpdateCareerRequestItemsOperation for UpdateCareerRequestItemsOperationMock {
+        async fn get_items(
+            &self,
+            page: u64,
+            page_size: u64,
+            _review_claim_filter: Option<ReviewClaimFilter>,
+        ) -> Result<Vec<UpdateCareerReqItem>, ErrResp> {
+            let items = self.items.clone();
+            let length = items.len() as u64;
+            let start = page * page_size;
+            if start >= length {
+                return Ok(vec![]);
+            }
+            let end = if start + page_size > length {
+                length
+            } else {
+                start + page_size
+            };
+            let items = items
+                .get(
+                    start.try_into().expect("failed to get Ok")
+                        ..end.try_into().expect("failed to get Ok"),
+                )
+                .expect("failed to get value");
+            Ok(items.to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn get_update_career_request_items_success1() {
+        let items = create_3_dummy_items();
+        let op_mock = UpdateCareerRequestItemsOperationMock {
+            items: items.clone(),
+        };
+        let pagination = Pagination {
+            page: 0,
+            per_page: 3,
+        };
+
+        let result = get_update_career_request_items(pagination, None, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(items, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn get_update_career_request_items_success2() {
+        let items = create_3_dummy_items();
+        let op_mock = UpdateCareerRequestItemsOperationMock {
+            items: items.clone(),
+        };
+        let pagination = Pagination {
+            page: 0,
+            per_page: 2,
+        };
+
+        let result = get_update_career_request_items(pagination, None, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(items.get(0..2).expect("failed to get value"), resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn get_update_career_request_items_success3() {
+        let items = create_3_dummy_items();
+        let op_mock = UpdateCareerRequestItemsOperationMock {
+            items: items.clone(),
+        };
+        let pagination = Pagination {
+            page: 1,
+            per_page: 2,
+        };
+
+        let result = get_update_career_request_items(pagination, None, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        let item = items.get(2).expect("failed to get value");
+        assert_eq!(vec![item.clone()], resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn get_update_career_request_items_success4() {
+        let items = create_3_dummy_items();
+        let op_mock = UpdateCareerRequestItemsOperationMock {
+            items: items.clone(),
+        };
+        let pagination = Pagination {
+            page: 2,
+            per_page: 2,
+        };
+
+        let result = get_update_career_request_items(pagination, None, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(Vec::<UpdateCareerReqItem>::with_capacity(0), resp.1 .0);
+    }
+
+    fn create_3_dummy_items() -> Vec<UpdateCareerReqItem> {
+        let mut items = Vec::with_capacity(3);
+        let requested_at_1 = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2021, 9, 11, 15, 30, 45)
+            .unwrap();
+        let item1 = UpdateCareerReqItem {
+            career_id: 1,
+            company_name: String::from("テスト１株式会社"),
+            requested_at: requested_at_1,
+        };
+        items.push(item1);
+        let requested_at_2 = requested_at_1 + Duration::days(1);
+        let item2 = UpdateCareerReqItem {
+            career_id: 2,
+            company_name: String::from("テスト２株式会社"),
+            requested_at: requested_at_2,
+        };
+        items.push(item2);
+        let requested_at_3 = requested_at_2 + Duration::days(1);
+        let item3 = UpdateCareerReqItem {
+            career_id: 3,
+            company_name: String::from("テスト３株式会社"),
+            requested_at: requested_at_3,
+        };
+        items.push(item3);
+        items
+    }
+}
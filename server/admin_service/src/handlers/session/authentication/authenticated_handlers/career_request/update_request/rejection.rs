@@ -0,0 +1,566 @@
+// Copyright 2023 Ken Miura
+
+use axum::{async_trait, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::{
+    smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS},
+    storage::StorageClient,
+    ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
+};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use entity::{
+    rejected_update_career_req,
+    sea_orm::{
+        ActiveModelTrait, ActiveValue::NotSet, DatabaseConnection, EntityTrait, Set,
+        TransactionError, TransactionTrait,
+    },
+    update_career_req,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    err::unexpected_err_resp,
+    handlers::session::authentication::authenticated_handlers::{
+        admin::Admin,
+        rejection_reason_template::{
+            create_rejection_reason, find_rejection_reason_template_by_code,
+            no_rejection_reason_template_found, validate_additional_rejection_reason,
+            RejectionReasonTemplate,
+        },
+        review_claim::ensure_not_claimed_by_other_admin,
+        user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
+    },
+};
+
+use super::{
+    super::delete_career_images, delete_update_career_req,
+    find_update_career_req_model_by_career_id_with_exclusive_lock,
+};
+
+static SUBJECT: Lazy<String> = Lazy::new(|| format!("[{}] 職務経歴更新拒否通知", WEB_SITE_NAME));
+
+pub(crate) async fn post_update_career_request_rejection(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(smtp_client): State<SmtpClient>,
+    State(storage_client): State<StorageClient>,
+    State(pool): State<DatabaseConnection>,
+    Json(update_career_req_rejection): Json<UpdateCareerReqRejection>,
+) -> RespResult<UpdateCareerReqRejectionResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = UpdateCareerReqRejectionOperationImpl {
+        pool,
+        storage_client,
+    };
+    handle_update_career_request_rejection(
+        admin_info.email_address,
+        update_career_req_rejection.career_id,
+        update_career_req_rejection.rejection_reason_template_code,
+        update_career_req_rejection.additional_rejection_reason,
+        current_date_time,
+        op,
+        smtp_client,
+    )
+    .await
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqRejection {
+    career_id: i64,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct UpdateCareerReqRejectionResult {}
+
+async fn handle_update_career_request_rejection(
+    admin_email_address: String,
+    career_id: i64,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
+    rejected_time: DateTime<FixedOffset>,
+    op: impl UpdateCareerReqRejectionOperation,
+    send_mail: impl SendMail,
+) -> RespResult<UpdateCareerReqRejectionResult> {
+    validate_additional_rejection_reason(additional_rejection_reason.as_deref())?;
+    let template = op
+        .find_rejection_reason_template(rejection_reason_template_code.clone())
+        .await?
+        .ok_or_else(|| {
+            no_rejection_reason_template_found(rejection_reason_template_code.as_str())
+        })?;
+    let rejection_reason =
+        create_rejection_reason(&template, additional_rejection_reason.as_deref());
+
+    let user_account_id_option = op
+        .get_user_account_id_by_career_id_in_update_career_req(career_id)
+        .await?;
+    let user_account_id = user_account_id_option.ok_or_else(|| {
+        error!("no update career request (career id: {}) found", career_id);
+        unexpected_err_resp()
+    })?;
+
+    let rejected_user = op
+        .reject_update_career_req(
+            user_account_id,
+            career_id,
+            admin_email_address,
+            rejection_reason.clone(),
+            template.code,
+            rejected_time,
+        )
+        .await?;
+
+    let user_email_address = match rejected_user {
+        Some(u) => u,
+        None => {
+            // 拒否をしようとした際、既にユーザーがアカウントを削除しているケース、またはDisabledになっているケース
+            info!(
+                "no user account (user account id: {}) found or the account is disabled",
+                user_account_id
+            );
+            return Ok((StatusCode::OK, Json(UpdateCareerReqRejectionResult {})));
+        }
+    };
+
+    send_mail
+        .send_mail(
+            &user_email_address,
+            SYSTEM_EMAIL_ADDRESS.as_str(),
+            &SUBJECT,
+            create_text(rejection_reason).as_str(),
+        )
+        .await?;
+
+    Ok((StatusCode::OK, Json(UpdateCareerReqRejectionResult {})))
+}
+
+#[async_trait]
+trait UpdateCareerReqRejectionOperation {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp>;
+
+    async fn get_user_account_id_by_career_id_in_update_career_req(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<i64>, ErrResp>;
+
+    async fn reject_update_career_req(
+        &self,
+        user_account_id: i64,
+        career_id: i64,
+        refuser_email_address: String,
+        rejection_reason: String,
+        rejection_reason_template_code: String,
+        rejected_time: DateTime<FixedOffset>,
+    ) -> Result<Option<String>, ErrResp>;
+}
+
+struct UpdateCareerReqRejectionOperationImpl {
+    pool: DatabaseConnection,
+    storage_client: StorageClient,
+}
+
+#[async_trait]
+impl UpdateCareerReqRejectionOperation for UpdateCareerReqRejectionOperationImpl {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+        find_rejection_reason_template_by_code(&self.pool, code.as_str()).await
+    }
+
+    async fn get_user_account_id_by_career_id_in_update_career_req(
+        &self,
+        career_id: i64,
+    ) -> Result<Option<i64>, ErrResp> {
+        let model = update_career_req::Entity::find_by_id(career_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find update_career_req (career_id: {}): {}",
+                    career_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.map(|m| m.user_account_id))
+    }
+
+    async fn reject_update_career_req(
+        &self,
+        user_account_id: i64,
+        career_id: i64,
+        refuser_email_address: String,
+        rejection_reason: String,
+        rejection_reason_template_code: String,
+        rejected_time: DateTime<FixedOffset>,
+    ) -> Result<Option<String>, ErrResp> {
+        let storage_client = self.storage_client.clone();
+        let notification_email_address_option = self
+            .pool
+            .transaction::<_, Option<String>, ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let user_option = find_user_account_model_by_user_account_id_with_shared_lock(
+                        txn,
+                        user_account_id,
+                    )
+                    .await?;
+
+                    let req = find_update_career_req_model_by_career_id_with_exclusive_lock(
+                        txn, career_id,
+                    )
+                    .await?;
+
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        refuser_email_address.as_str(),
+                        rejected_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
+                    let user = match user_option {
+                        Some(m) => m,
+                        None => {
+                            delete_update_career_req(career_id, txn).await?;
+                            return Ok(None);
+                        }
+                    };
+                    if user.disabled_at.is_some() {
+                        delete_update_career_req(career_id, txn).await?;
+                        return Ok(None);
+                    }
+
+                    let rejected_req_active_model =
+                        generate_rejected_update_career_req_active_model(
+                            req.clone(),
+                            rejected_time,
+                            rejection_reason,
+                            rejection_reason_template_code,
+                            refuser_email_address,
+                        );
+                    let _ = rejected_req_active_model.insert(txn).await.map_err(|e| {
+                        error!(
+                            "failed to insert rejected_update_career_req (career_id: {}): {}",
+                            career_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+
+                    delete_update_career_req(career_id, txn).await?;
+
+                    // 退社日と備考のみの更新依頼の場合、画像は提出されていない
+                    if let Some(image1_file_name_without_ext) = req.image1_file_name_without_ext {
+                        let _ = delete_career_images(
+                            storage_client,
+                            user_account_id,
+                            image1_file_name_without_ext,
+                            req.image2_file_name_without_ext,
+                        )
+                        .await?;
+                    }
+
+                    Ok(Some(user.email_address))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!("failed to reject update_career_req: {}", err_resp_struct);
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(notification_email_address_option)
+    }
+}
+
+fn generate_rejected_update_career_req_active_model(
+    model: update_career_req::Model,
+    rejected_time: DateTime<FixedOffset>,
+    rejection_reason: String,
+    rejection_reason_template_code: String,
+    refuser_email_address: String,
+) -> rejected_update_career_req::ActiveModel {
+    rejected_update_career_req::ActiveModel {
+        rjd_upd_career_req_id: NotSet,
+        career_id: Set(model.career_id),
+        user_account_id: Set(model.user_account_id),
+        company_name: Set(model.company_name),
+        department_name: Set(model.department_name),
+        office: Set(model.office),
+        career_start_date: Set(model.career_start_date),
+        career_end_date: Set(model.career_end_date),
+        contract_type: Set(model.contract_type),
+        profession: Set(model.profession),
+        annual_income_in_man_yen: Set(model.annual_income_in_man_yen),
+        is_manager: Set(model.is_manager),
+        position_name: Set(model.position_name),
+        is_new_graduate: Set(model.is_new_graduate),
+        note: Set(model.note),
+        reason: Set(rejection_reason),
+        reason_template_code: Set(Some(rejection_reason_template_code)),
+        rejected_at: Set(rejected_time),
+        rejected_by: Set(refuser_email_address),
+    }
+}
+
+fn create_text(rejection_reason: String) -> String {
+    format!(
+        r"下記の【拒否理由】により、職務経歴の更新を拒否いたしました。職務経歴は更新前の内容のままとなっております。お手数ですが、再度職務経歴確認依頼をお願いいたします。
+
+【拒否理由】
+{}
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+        rejection_reason,
+        INQUIRY_EMAIL_ADDRESS.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+    use axum::http::StatusCode;
+    use chrono::{DateTime, FixedOffset, TimeZone};
+    use common::{smtp::SYSTEM_EMAIL_ADDRESS, ErrResp, JAPANESE_TIME_ZONE};
+
+    use crate::{
+        err::Code, handlers::session::authentication::authenticated_handlers::tests::SendMailMock,
+    };
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct User {
+        user_account_id: i64,
+        email_address: String,
+    }
+
+    struct UpdateCareerReqRejectionOperationMock {
+        admin_email_address: String,
+        user_option: Option<User>,
+        career_id: i64,
+        user_account_id: i64,
+        rejection_reason: String,
+        rejection_reason_template: RejectionReasonTemplate,
+        rejected_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl UpdateCareerReqRejectionOperation for UpdateCareerReqRejectionOperationMock {
+        async fn find_rejection_reason_template(
+            &self,
+            code: String,
+        ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+            if self.rejection_reason_template.code != code {
+                return Ok(None);
+            }
+            Ok(Some(self.rejection_reason_template.clone()))
+        }
+
+        async fn get_user_account_id_by_career_id_in_update_career_req(
+            &self,
+            career_id: i64,
+        ) -> Result<Option<i64>, ErrResp> {
+            assert_eq!(self.career_id, career_id);
+            Ok(Some(self.user_account_id))
+        }
+
+        async fn reject_update_career_req(
+            &self,
+            user_account_id: i64,
+            career_id: i64,
+            refuser_email_address: String,
+            rejection_reason: String,
+            rejection_reason_template_code: String,
+            rejected_time: DateTime<FixedOffset>,
+        ) -> Result<Option<String>, ErrResp> {
+            if let Some(user) = self.user_option.clone() {
+                assert_eq!(user.user_account_id, user_account_id);
+                assert_eq!(self.admin_email_address, refuser_email_address);
+                assert_eq!(self.career_id, career_id);
+                assert_eq!(self.rejection_reason, rejection_reason);
+                assert_eq!(
+                    self.rejection_reason_template.code,
+                    rejection_reason_template_code
+                );
+                assert_eq!(self.rejected_time, rejected_time);
+                Ok(Some(user.email_address))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    fn create_op_mock(
+        user_option: Option<User>,
+        rejection_reason: &str,
+    ) -> UpdateCareerReqRejectionOperationMock {
+        UpdateCareerReqRejectionOperationMock {
+            admin_email_address: String::from("admin@test.com"),
+            user_option,
+            career_id: 8716,
+            user_account_id: 53,
+            rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
+            rejected_time: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2023, 4, 5, 21, 0, 40)
+                .unwrap(),
+        }
+    }
+
+    fn create_rejection_reason_template() -> RejectionReasonTemplate {
+        RejectionReasonTemplate {
+            code: "blurred-image".to_string(),
+            body: "画像が不鮮明なため".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_rejection_success() {
+        let user_email_address = String::from("test@test.com");
+        let rejection_reason = "画像が不鮮明なため";
+        let op_mock = create_op_mock(
+            Some(User {
+                user_account_id: 53,
+                email_address: user_email_address.clone(),
+            }),
+            rejection_reason,
+        );
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_update_career_request_rejection(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            create_rejection_reason_template().code,
+            None,
+            op_mock.rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerReqRejectionResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_rejection_fail_invalid_format_reason() {
+        let user_email_address = String::from("test@test.com");
+        let rejection_reason = "<script>alert('test');<script>";
+        let op_mock = create_op_mock(
+            Some(User {
+                user_account_id: 53,
+                email_address: user_email_address.clone(),
+            }),
+            rejection_reason,
+        );
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_update_career_request_rejection(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            create_rejection_reason_template().code,
+            Some(rejection_reason.to_string()),
+            op_mock.rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidFormatReason as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_rejection_fail_no_rejection_reason_template_found() {
+        let user_email_address = String::from("test@test.com");
+        let rejection_reason = "画像が不鮮明なため";
+        let op_mock = create_op_mock(
+            Some(User {
+                user_account_id: 53,
+                email_address: user_email_address.clone(),
+            }),
+            rejection_reason,
+        );
+        let send_mail_mock = SendMailMock::new(
+            user_email_address,
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_update_career_request_rejection(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            "unknown-code".to_string(),
+            None,
+            op_mock.rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoRejectionReasonTemplateFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_request_rejection_success_no_user_account_found() {
+        let rejection_reason = "画像が不鮮明なため";
+        let op_mock = create_op_mock(None, rejection_reason);
+        let send_mail_mock = SendMailMock::new(
+            String::from("test@test.com"),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_update_career_request_rejection(
+            op_mock.admin_email_address.clone(),
+            op_mock.career_id,
+            create_rejection_reason_template().code,
+            None,
+            op_mock.rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerReqRejectionResult {}, resp.1 .0);
+    }
+}
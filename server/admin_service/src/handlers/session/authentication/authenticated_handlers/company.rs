@@ -0,0 +1,69 @@
+// Copyright 2023 Ken Miura
+
+use axum::{http::StatusCode, Json};
+use common::{util::validator::has_control_char, ApiError, ErrResp};
+use tracing::error;
+
+use crate::err::Code;
+
+pub(crate) mod companies_by_name;
+pub(crate) mod set_company_req;
+
+const COMPANY_NAME_MIN_LENGTH: usize = 1;
+const COMPANY_NAME_MAX_LENGTH: usize = 256;
+
+fn validate_company_name(company_name: &str) -> Result<(), ErrResp> {
+    let company_name_length = company_name.chars().count();
+    if !(COMPANY_NAME_MIN_LENGTH..=COMPANY_NAME_MAX_LENGTH).contains(&company_name_length) {
+        error!(
+            "invalid company_name length (length: {}, min_length: {}, max_length: {})",
+            company_name_length, COMPANY_NAME_MIN_LENGTH, COMPANY_NAME_MAX_LENGTH
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidCompanyNameLength as u32,
+            }),
+        ));
+    }
+    if has_control_char(company_name) {
+        error!("company_name ({}) has control char", company_name);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalCharInCompanyName as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn validate_company_name_returns_ok_if_valid_company_name_is_passed() {
+        assert!(validate_company_name("トヨタ自動車株式会社").is_ok());
+        assert!(validate_company_name(&"あ".repeat(COMPANY_NAME_MAX_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn validate_company_name_returns_err_if_invalid_length_company_name_is_passed() {
+        for company_name in ["".to_string(), "あ".repeat(COMPANY_NAME_MAX_LENGTH + 1)] {
+            let resp = validate_company_name(&company_name).expect_err("failed to get Err");
+
+            assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+            assert_eq!(Code::InvalidCompanyNameLength as u32, resp.1 .0.code);
+        }
+    }
+
+    #[test]
+    fn validate_company_name_returns_err_if_company_name_has_control_char() {
+        let resp = validate_company_name("テスト\n株式会社").expect_err("failed to get Err");
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::IllegalCharInCompanyName as u32, resp.1 .0.code);
+    }
+}
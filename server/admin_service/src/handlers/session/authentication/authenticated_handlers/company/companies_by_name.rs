@@ -0,0 +1,285 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use common::{company::create_normalized_name, ErrResp, RespResult};
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    err::unexpected_err_resp,
+    handlers::session::authentication::authenticated_handlers::admin::Admin,
+};
+
+use super::validate_company_name;
+
+/// 返却する会社の最大数
+const MAX_NUM_OF_COMPANIES: u64 = 20;
+
+/// 職務経歴確認依頼の承認時、ユーザーが入力した会社名に対応する会社を管理者が選択するための候補を返す
+///
+/// 入力された会社名を正規化した値を含む別名を持つ会社を返す（別名が完全に一致する会社を先頭にする）
+pub(crate) async fn get_companies_by_name(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<CompaniesByNameQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<CompaniesResult> {
+    let query = query.0;
+    let op = CompaniesByNameOperationImpl { pool };
+    handle_companies_by_name(query.company_name, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CompaniesByNameQuery {
+    company_name: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct CompaniesResult {
+    companies: Vec<Company>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct Company {
+    company_id: i64,
+    company_name: String,
+    corporate_number: Option<String>,
+}
+
+#[async_trait]
+trait CompaniesByNameOperation {
+    /// normalized_nameを含む別名を持つ会社のcompany_idと、その別名を返す
+    async fn find_company_aliases_containing(
+        &self,
+        normalized_name: &str,
+        limit: u64,
+    ) -> Result<Vec<(i64, String)>, ErrResp>;
+
+    async fn find_companies_by_company_ids(
+        &self,
+        company_ids: &[i64],
+    ) -> Result<Vec<Company>, ErrResp>;
+}
+
+struct CompaniesByNameOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl CompaniesByNameOperation for CompaniesByNameOperationImpl {
+    async fn find_company_aliases_containing(
+        &self,
+        normalized_name: &str,
+        limit: u64,
+    ) -> Result<Vec<(i64, String)>, ErrResp> {
+        let pattern = format!("%{}%", escape_like_pattern(normalized_name));
+        let models = entity::company_alias::Entity::find()
+            .filter(entity::company_alias::Column::NormalizedName.like(pattern.as_str()))
+            .order_by_asc(entity::company_alias::Column::CompanyAliasId)
+            .limit(limit)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find company_alias (normalized_name: {}): {}",
+                    normalized_name, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| (m.company_id, m.normalized_name))
+            .collect())
+    }
+
+    async fn find_companies_by_company_ids(
+        &self,
+        company_ids: &[i64],
+    ) -> Result<Vec<Company>, ErrResp> {
+        let models = entity::company::Entity::find()
+            .filter(entity::company::Column::CompanyId.is_in(company_ids.to_vec()))
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find company (company_ids: {:?}): {}",
+                    company_ids, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| Company {
+                company_id: m.company_id,
+                company_name: m.company_name,
+                corporate_number: m.corporate_number,
+            })
+            .collect())
+    }
+}
+
+/// LIKEの特殊文字（%、_）とエスケープ文字（\）をエスケープする
+fn escape_like_pattern(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+async fn handle_companies_by_name(
+    company_name: String,
+    op: impl CompaniesByNameOperation,
+) -> RespResult<CompaniesResult> {
+    validate_company_name(&company_name)?;
+    let normalized_name = create_normalized_name(&company_name);
+
+    // 同じ会社が複数の別名で一致する場合があるため、多めに取得してから会社の単位で絞り込む
+    let aliases = op
+        .find_company_aliases_containing(&normalized_name, MAX_NUM_OF_COMPANIES * 5)
+        .await?;
+    let mut exact_matches = vec![];
+    let mut partial_matches = vec![];
+    for (company_id, alias) in aliases {
+        if alias == normalized_name {
+            exact_matches.push(company_id);
+        } else {
+            partial_matches.push(company_id);
+        }
+    }
+    let mut company_ids: Vec<i64> = vec![];
+    for company_id in exact_matches.into_iter().chain(partial_matches) {
+        if !company_ids.contains(&company_id) {
+            company_ids.push(company_id);
+        }
+    }
+    company_ids.truncate(MAX_NUM_OF_COMPANIES as usize);
+    if company_ids.is_empty() {
+        return Ok((StatusCode::OK, Json(CompaniesResult { companies: vec![] })));
+    }
+
+    let companies = op.find_companies_by_company_ids(&company_ids).await?;
+    let companies = company_ids
+        .iter()
+        .filter_map(|id| companies.iter().find(|c| c.company_id == *id).cloned())
+        .collect();
+
+    Ok((StatusCode::OK, Json(CompaniesResult { companies })))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::err::Code;
+
+    use super::*;
+
+    struct CompaniesByNameOperationMock {
+        normalized_name: String,
+        aliases: Vec<(i64, String)>,
+        companies: Vec<Company>,
+    }
+
+    #[async_trait]
+    impl CompaniesByNameOperation for CompaniesByNameOperationMock {
+        async fn find_company_aliases_containing(
+            &self,
+            normalized_name: &str,
+            limit: u64,
+        ) -> Result<Vec<(i64, String)>, ErrResp> {
+            assert_eq!(self.normalized_name, normalized_name);
+            assert_eq!(MAX_NUM_OF_COMPANIES * 5, limit);
+            Ok(self.aliases.clone())
+        }
+
+        async fn find_companies_by_company_ids(
+            &self,
+            company_ids: &[i64],
+        ) -> Result<Vec<Company>, ErrResp> {
+            Ok(self
+                .companies
+                .iter()
+                .filter(|c| company_ids.contains(&c.company_id))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn create_company(company_id: i64, company_name: &str) -> Company {
+        Company {
+            company_id,
+            company_name: company_name.to_string(),
+            corporate_number: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_companies_by_name_returns_exact_match_first() {
+        let op = CompaniesByNameOperationMock {
+            normalized_name: "とよた".to_string(),
+            aliases: vec![
+                (1, "とよた自動車".to_string()),
+                (2, "とよた".to_string()),
+                (1, "とよた自動車販売".to_string()),
+            ],
+            companies: vec![
+                create_company(1, "トヨタ自動車株式会社"),
+                create_company(2, "株式会社トヨタ"),
+            ],
+        };
+
+        let result = handle_companies_by_name("トヨタ（株）".to_string(), op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            CompaniesResult {
+                companies: vec![
+                    create_company(2, "株式会社トヨタ"),
+                    create_company(1, "トヨタ自動車株式会社")
+                ]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_companies_by_name_returns_empty_if_no_alias_found() {
+        let op = CompaniesByNameOperationMock {
+            normalized_name: "とよた".to_string(),
+            aliases: vec![],
+            companies: vec![],
+        };
+
+        let result = handle_companies_by_name("トヨタ".to_string(), op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(CompaniesResult { companies: vec![] }, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_companies_by_name_fail_empty_company_name() {
+        let op = CompaniesByNameOperationMock {
+            normalized_name: "".to_string(),
+            aliases: vec![],
+            companies: vec![],
+        };
+
+        let result = handle_companies_by_name("".to_string(), op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidCompanyNameLength as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn escape_like_pattern_escapes_special_chars() {
+        assert_eq!("100\\%\\_\\\\", escape_like_pattern("100%_\\"));
+    }
+}
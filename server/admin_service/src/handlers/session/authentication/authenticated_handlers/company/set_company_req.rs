@@ -0,0 +1,288 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::company::insert_company;
+use common::util::validator::corporate_number_validator::validate_corporate_number;
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+
+use super::validate_company_name;
+
+pub(crate) async fn post_set_company_req(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<SetCompanyReq>,
+) -> RespResult<SetCompanyReqResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = SetCompanyReqOperationImpl { pool };
+    handle_set_company_req(req, admin_info.email_address, current_date_time, &op).await
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SetCompanyReq {
+    company_name: String,
+    corporate_number: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SetCompanyReqResult {
+    company_id: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Company {
+    company_name: String,
+    corporate_number: Option<String>,
+    created_by: String,
+    created_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait SetCompanyReqOperation {
+    async fn count_companies_by_corporate_number(
+        &self,
+        corporate_number: String,
+    ) -> Result<u64, ErrResp>;
+
+    async fn set_company(&self, company: Company) -> Result<i64, ErrResp>;
+}
+
+struct SetCompanyReqOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SetCompanyReqOperation for SetCompanyReqOperationImpl {
+    async fn count_companies_by_corporate_number(
+        &self,
+        corporate_number: String,
+    ) -> Result<u64, ErrResp> {
+        entity::company::Entity::find()
+            .filter(entity::company::Column::CorporateNumber.eq(corporate_number.clone()))
+            .count(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to count company (corporate_number: {}): {}",
+                    corporate_number, e
+                );
+                unexpected_err_resp()
+            })
+    }
+
+    async fn set_company(&self, company: Company) -> Result<i64, ErrResp> {
+        // companyとcompany_aliasを一緒に作成するため、トランザクション内で実行する
+        let c = company.clone();
+        self.pool
+            .transaction::<_, i64, entity::sea_orm::DbErr>(|txn| {
+                Box::pin(async move {
+                    insert_company(
+                        &c.company_name,
+                        c.corporate_number.clone(),
+                        &c.created_by,
+                        c.created_at,
+                        txn,
+                    )
+                    .await
+                })
+            })
+            .await
+            .map_err(|e| {
+                error!("failed to insert company ({:?}): {}", company, e);
+                unexpected_err_resp()
+            })
+    }
+}
+
+async fn handle_set_company_req(
+    req: SetCompanyReq,
+    admin_email_address: String,
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl SetCompanyReqOperation,
+) -> RespResult<SetCompanyReqResult> {
+    validate_company_name(&req.company_name)?;
+    if let Some(corporate_number) = req.corporate_number.clone() {
+        validate_corporate_number(&corporate_number).map_err(|e| {
+            error!("invalid corporate number: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::InvalidCorporateNumber as u32,
+                }),
+            )
+        })?;
+        let count = op
+            .count_companies_by_corporate_number(corporate_number.clone())
+            .await?;
+        if count != 0 {
+            error!("corporate number ({}) already exists", corporate_number);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::CorporateNumberAlreadyExists as u32,
+                }),
+            ));
+        }
+    }
+
+    let company_id = op
+        .set_company(Company {
+            company_name: req.company_name,
+            corporate_number: req.corporate_number,
+            created_by: admin_email_address,
+            created_at: current_date_time,
+        })
+        .await?;
+
+    Ok((StatusCode::OK, Json(SetCompanyReqResult { company_id })))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct SetCompanyReqOperationMock {
+        num_of_companies_with_same_corporate_number: u64,
+        company: Company,
+        company_id: i64,
+    }
+
+    #[async_trait]
+    impl SetCompanyReqOperation for SetCompanyReqOperationMock {
+        async fn count_companies_by_corporate_number(
+            &self,
+            corporate_number: String,
+        ) -> Result<u64, ErrResp> {
+            assert_eq!(self.company.corporate_number, Some(corporate_number));
+            Ok(self.num_of_companies_with_same_corporate_number)
+        }
+
+        async fn set_company(&self, company: Company) -> Result<i64, ErrResp> {
+            assert_eq!(self.company, company);
+            Ok(self.company_id)
+        }
+    }
+
+    fn create_op_mock(
+        corporate_number: Option<String>,
+        num_of_companies_with_same_corporate_number: u64,
+    ) -> SetCompanyReqOperationMock {
+        SetCompanyReqOperationMock {
+            num_of_companies_with_same_corporate_number,
+            company: Company {
+                company_name: "トヨタ自動車株式会社".to_string(),
+                corporate_number,
+                created_by: "admin@test.com".to_string(),
+                created_at: current_date_time(),
+            },
+            company_id: 12,
+        }
+    }
+
+    fn current_date_time() -> DateTime<FixedOffset> {
+        JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 21, 32, 21)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn handle_set_company_req_success() {
+        let corporate_number = Some("1180301018771".to_string());
+        let req = SetCompanyReq {
+            company_name: "トヨタ自動車株式会社".to_string(),
+            corporate_number: corporate_number.clone(),
+        };
+        let op = create_op_mock(corporate_number, 0);
+
+        let result =
+            handle_set_company_req(req, "admin@test.com".to_string(), current_date_time(), &op)
+                .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(SetCompanyReqResult { company_id: 12 }, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_set_company_req_success_without_corporate_number() {
+        let req = SetCompanyReq {
+            company_name: "トヨタ自動車株式会社".to_string(),
+            corporate_number: None,
+        };
+        let op = create_op_mock(None, 0);
+
+        let result =
+            handle_set_company_req(req, "admin@test.com".to_string(), current_date_time(), &op)
+                .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(SetCompanyReqResult { company_id: 12 }, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_set_company_req_fail_invalid_company_name() {
+        let req = SetCompanyReq {
+            company_name: "".to_string(),
+            corporate_number: None,
+        };
+        let op = create_op_mock(None, 0);
+
+        let result =
+            handle_set_company_req(req, "admin@test.com".to_string(), current_date_time(), &op)
+                .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidCompanyNameLength as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_set_company_req_fail_invalid_corporate_number() {
+        let corporate_number = Some("2180301018771".to_string());
+        let req = SetCompanyReq {
+            company_name: "トヨタ自動車株式会社".to_string(),
+            corporate_number: corporate_number.clone(),
+        };
+        let op = create_op_mock(corporate_number, 0);
+
+        let result =
+            handle_set_company_req(req, "admin@test.com".to_string(), current_date_time(), &op)
+                .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidCorporateNumber as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_set_company_req_fail_corporate_number_already_exists() {
+        let corporate_number = Some("1180301018771".to_string());
+        let req = SetCompanyReq {
+            company_name: "トヨタ自動車株式会社".to_string(),
+            corporate_number: corporate_number.clone(),
+        };
+        let op = create_op_mock(corporate_number, 1);
+
+        let result =
+            handle_set_company_req(req, "admin@test.com".to_string(), current_date_time(), &op)
+                .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::CorporateNumberAlreadyExists as u32, resp.1 .0.code);
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+
+use super::admin::Admin;
+
+/// 集計の対象とする期間（日数）の最大値
+const MAX_DAYS: i64 = 365;
+/// 検索結果が0件だった会社名、職種として返す最大数（件数の多い順）
+const MAX_NUM_OF_ZERO_RESULT_KEYWORDS: i64 = 20;
+
+const FIND_SUMMARY_SQL: &str = "SELECT COUNT(*) AS num_of_searches, \
+     COUNT(*) FILTER (WHERE l.num_of_hits = 0) AS num_of_zero_result_searches, \
+     COUNT(*) FILTER (WHERE EXISTS (SELECT 1 FROM ccs_schema.consultant_search_click c WHERE c.search_id = l.search_id)) AS num_of_searches_with_click, \
+     (SELECT COUNT(*) FROM ccs_schema.consultant_search_click c INNER JOIN ccs_schema.consultant_search_log cl ON c.search_id = cl.search_id WHERE cl.searched_at >= $1) AS num_of_clicks \
+     FROM ccs_schema.consultant_search_log l WHERE l.searched_at >= $1";
+
+const FIND_CRITERION_COUNTS_SQL: &str = "SELECT criterion, COUNT(*) AS count \
+     FROM ccs_schema.consultant_search_log, jsonb_array_elements_text(criteria) AS criterion \
+     WHERE searched_at >= $1 GROUP BY criterion ORDER BY count DESC, criterion ASC";
+
+const FIND_ZERO_RESULT_COMPANY_NAMES_SQL: &str =
+    "SELECT company_name AS keyword, COUNT(*) AS count \
+     FROM ccs_schema.consultant_search_log \
+     WHERE searched_at >= $1 AND num_of_hits = 0 AND company_name IS NOT NULL \
+     GROUP BY company_name ORDER BY count DESC, keyword ASC LIMIT $2";
+
+const FIND_ZERO_RESULT_PROFESSIONS_SQL: &str = "SELECT profession AS keyword, COUNT(*) AS count \
+     FROM ccs_schema.consultant_search_log \
+     WHERE searched_at >= $1 AND num_of_hits = 0 AND profession IS NOT NULL \
+     GROUP BY profession ORDER BY count DESC, keyword ASC LIMIT $2";
+
+pub(crate) async fn get_consultant_search_report(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<ConsultantSearchReportQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<ConsultantSearchReport> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = ConsultantSearchReportOperationImpl { pool };
+    handle_consultant_search_report(query.days, current_date_time, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ConsultantSearchReportQuery {
+    /// 現在日時から遡って集計の対象とする日数
+    days: i64,
+}
+
+/// 期間内のコンサルタントの検索の集計結果
+///
+/// クリックは、検索結果からコンサルタントの詳細を表示したこと（同じ検索結果から同じコンサルタントを複数回表示した場合は一回と数える）
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct ConsultantSearchReport {
+    num_of_searches: i64,
+    num_of_zero_result_searches: i64,
+    num_of_searches_with_click: i64,
+    num_of_clicks: i64,
+    /// 検索条件ごとの指定された回数（回数の多い順）
+    criteria: Vec<CriterionCount>,
+    /// 検索結果が0件だった検索で指定された会社名（回数の多い順）
+    zero_result_company_names: Vec<KeywordCount>,
+    /// 検索結果が0件だった検索で指定された職種（回数の多い順）
+    zero_result_professions: Vec<KeywordCount>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct CriterionCount {
+    criterion: String,
+    count: i64,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct KeywordCount {
+    keyword: String,
+    count: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SearchSummary {
+    num_of_searches: i64,
+    num_of_zero_result_searches: i64,
+    num_of_searches_with_click: i64,
+    num_of_clicks: i64,
+}
+
+#[async_trait]
+trait ConsultantSearchReportOperation {
+    async fn find_summary(&self, since: DateTime<FixedOffset>) -> Result<SearchSummary, ErrResp>;
+
+    async fn find_criterion_counts(
+        &self,
+        since: DateTime<FixedOffset>,
+    ) -> Result<Vec<CriterionCount>, ErrResp>;
+
+    async fn find_zero_result_company_names(
+        &self,
+        since: DateTime<FixedOffset>,
+        limit: i64,
+    ) -> Result<Vec<KeywordCount>, ErrResp>;
+
+    async fn find_zero_result_professions(
+        &self,
+        since: DateTime<FixedOffset>,
+        limit: i64,
+    ) -> Result<Vec<KeywordCount>, ErrResp>;
+}
+
+struct ConsultantSearchReportOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl ConsultantSearchReportOperation for ConsultantSearchReportOperationImpl {
+    async fn find_summary(&self, since: DateTime<FixedOffset>) -> Result<SearchSummary, ErrResp> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            FIND_SUMMARY_SQL,
+            [since.into()],
+        );
+        let row = self
+            .pool
+            .query_one(stmt)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find consultant search summary (since: {}): {}",
+                    since, e
+                );
+                unexpected_err_resp()
+            })?
+            .ok_or_else(|| {
+                error!("no consultant search summary found (since: {})", since);
+                unexpected_err_resp()
+            })?;
+        Ok(SearchSummary {
+            num_of_searches: get_i64(&row, "num_of_searches")?,
+            num_of_zero_result_searches: get_i64(&row, "num_of_zero_result_searches")?,
+            num_of_searches_with_click: get_i64(&row, "num_of_searches_with_click")?,
+            num_of_clicks: get_i64(&row, "num_of_clicks")?,
+        })
+    }
+
+    async fn find_criterion_counts(
+        &self,
+        since: DateTime<FixedOffset>,
+    ) -> Result<Vec<CriterionCount>, ErrResp> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            FIND_CRITERION_COUNTS_SQL,
+            [since.into()],
+        );
+        let rows = self.pool.query_all(stmt).await.map_err(|e| {
+            error!("failed to find criterion counts (since: {}): {}", since, e);
+            unexpected_err_resp()
+        })?;
+        rows.into_iter()
+            .map(|row| {
+                let criterion: String = row.try_get("", "criterion").map_err(|e| {
+                    error!("failed to get criterion: {}", e);
+                    unexpected_err_resp()
+                })?;
+                Ok(CriterionCount {
+                    criterion,
+                    count: get_i64(&row, "count")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn find_zero_result_company_names(
+        &self,
+        since: DateTime<FixedOffset>,
+        limit: i64,
+    ) -> Result<Vec<KeywordCount>, ErrResp> {
+        find_keyword_counts(&self.pool, FIND_ZERO_RESULT_COMPANY_NAMES_SQL, since, limit).await
+    }
+
+    async fn find_zero_result_professions(
+        &self,
+        since: DateTime<FixedOffset>,
+        limit: i64,
+    ) -> Result<Vec<KeywordCount>, ErrResp> {
+        find_keyword_counts(&self.pool, FIND_ZERO_RESULT_PROFESSIONS_SQL, since, limit).await
+    }
+}
+
+async fn find_keyword_counts(
+    pool: &DatabaseConnection,
+    sql: &str,
+    since: DateTime<FixedOffset>,
+    limit: i64,
+) -> Result<Vec<KeywordCount>, ErrResp> {
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        sql,
+        [since.into(), limit.into()],
+    );
+    let rows = pool.query_all(stmt).await.map_err(|e| {
+        error!(
+            "failed to find keyword counts (sql: {}, since: {}, limit: {}): {}",
+            sql, since, limit, e
+        );
+        unexpected_err_resp()
+    })?;
+    rows.into_iter()
+        .map(|row| {
+            let keyword: String = row.try_get("", "keyword").map_err(|e| {
+                error!("failed to get keyword: {}", e);
+                unexpected_err_resp()
+            })?;
+            Ok(KeywordCount {
+                keyword,
+                count: get_i64(&row, "count")?,
+            })
+        })
+        .collect()
+}
+
+fn get_i64(row: &QueryResult, column: &str) -> Result<i64, ErrResp> {
+    row.try_get("", column).map_err(|e| {
+        error!("failed to get {}: {}", column, e);
+        unexpected_err_resp()
+    })
+}
+
+async fn handle_consultant_search_report(
+    days: i64,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl ConsultantSearchReportOperation,
+) -> RespResult<ConsultantSearchReport> {
+    if !(1..=MAX_DAYS).contains(&days) {
+        error!("invalid days: {}", days);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalConsultantSearchReportPeriod as u32,
+            }),
+        ));
+    }
+    let since = current_date_time - Duration::days(days);
+
+    let summary = op.find_summary(since).await?;
+    let criteria = op.find_criterion_counts(since).await?;
+    let zero_result_company_names = op
+        .find_zero_result_company_names(since, MAX_NUM_OF_ZERO_RESULT_KEYWORDS)
+        .await?;
+    let zero_result_professions = op
+        .find_zero_result_professions(since, MAX_NUM_OF_ZERO_RESULT_KEYWORDS)
+        .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ConsultantSearchReport {
+            num_of_searches: summary.num_of_searches,
+            num_of_zero_result_searches: summary.num_of_zero_result_searches,
+            num_of_searches_with_click: summary.num_of_searches_with_click,
+            num_of_clicks: summary.num_of_clicks,
+            criteria,
+            zero_result_company_names,
+            zero_result_professions,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct ConsultantSearchReportOperationMock {
+        since: DateTime<FixedOffset>,
+        summary: SearchSummary,
+        criteria: Vec<CriterionCount>,
+        zero_result_company_names: Vec<KeywordCount>,
+        zero_result_professions: Vec<KeywordCount>,
+    }
+
+    #[async_trait]
+    impl ConsultantSearchReportOperation for ConsultantSearchReportOperationMock {
+        async fn find_summary(
+            &self,
+            since: DateTime<FixedOffset>,
+        ) -> Result<SearchSummary, ErrResp> {
+            assert_eq!(self.since, since);
+            Ok(self.summary.clone())
+        }
+
+        async fn find_criterion_counts(
+            &self,
+            since: DateTime<FixedOffset>,
+        ) -> Result<Vec<CriterionCount>, ErrResp> {
+            assert_eq!(self.since, since);
+            Ok(self.criteria.clone())
+        }
+
+        async fn find_zero_result_company_names(
+            &self,
+            since: DateTime<FixedOffset>,
+            limit: i64,
+        ) -> Result<Vec<KeywordCount>, ErrResp> {
+            assert_eq!(self.since, since);
+            assert_eq!(MAX_NUM_OF_ZERO_RESULT_KEYWORDS, limit);
+            Ok(self.zero_result_company_names.clone())
+        }
+
+        async fn find_zero_result_professions(
+            &self,
+            since: DateTime<FixedOffset>,
+            limit: i64,
+        ) -> Result<Vec<KeywordCount>, ErrResp> {
+            assert_eq!(self.since, since);
+            assert_eq!(MAX_NUM_OF_ZERO_RESULT_KEYWORDS, limit);
+            Ok(self.zero_result_professions.clone())
+        }
+    }
+
+    fn create_op(since: DateTime<FixedOffset>) -> ConsultantSearchReportOperationMock {
+        ConsultantSearchReportOperationMock {
+            since,
+            summary: SearchSummary {
+                num_of_searches: 120,
+                num_of_zero_result_searches: 15,
+                num_of_searches_with_click: 48,
+                num_of_clicks: 73,
+            },
+            criteria: vec![
+                CriterionCount {
+                    criterion: "company_name".to_string(),
+                    count: 80,
+                },
+                CriterionCount {
+                    criterion: "profession".to_string(),
+                    count: 31,
+                },
+            ],
+            zero_result_company_names: vec![KeywordCount {
+                keyword: "テスト株式会社".to_string(),
+                count: 9,
+            }],
+            zero_result_professions: vec![KeywordCount {
+                keyword: "データサイエンティスト".to_string(),
+                count: 4,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_consultant_search_report_success() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 30, 12, 0, 0)
+            .unwrap();
+        let since = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 8, 31, 12, 0, 0)
+            .unwrap();
+        let op = create_op(since);
+
+        let result = handle_consultant_search_report(30, current_date_time, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            ConsultantSearchReport {
+                num_of_searches: 120,
+                num_of_zero_result_searches: 15,
+                num_of_searches_with_click: 48,
+                num_of_clicks: 73,
+                criteria: vec![
+                    CriterionCount {
+                        criterion: "company_name".to_string(),
+                        count: 80,
+                    },
+                    CriterionCount {
+                        criterion: "profession".to_string(),
+                        count: 31,
+                    },
+                ],
+                zero_result_company_names: vec![KeywordCount {
+                    keyword: "テスト株式会社".to_string(),
+                    count: 9,
+                }],
+                zero_result_professions: vec![KeywordCount {
+                    keyword: "データサイエンティスト".to_string(),
+                    count: 4,
+                }],
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultant_search_report_fails_if_days_is_out_of_range() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 30, 12, 0, 0)
+            .unwrap();
+        for days in [0, -1, MAX_DAYS + 1] {
+            let op = create_op(current_date_time);
+
+            let result = handle_consultant_search_report(days, current_date_time, op).await;
+
+            let resp = result.expect_err("failed to get Err");
+            assert_eq!(StatusCode::BAD_REQUEST, resp.0, "days: {}", days);
+            assert_eq!(
+                Code::IllegalConsultantSearchReportPeriod as u32,
+                resp.1 .0.code,
+                "days: {}",
+                days
+            );
+        }
+    }
+}
@@ -0,0 +1,4 @@
+// Copyright 2023 Ken Miura
+
+pub(crate) mod list;
+pub(crate) mod set_coupon_req;
@@ -0,0 +1,198 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use common::{ErrResp, RespResult};
+use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryOrder};
+use serde::Serialize;
+use tracing::error;
+
+use crate::{
+    err::unexpected_err_resp,
+    handlers::session::authentication::authenticated_handlers::{
+        admin::Admin, convert_date_time_to_rfc3339_string, pagination::Pagination,
+    },
+};
+
+const VALID_PAGE_SIZE: u64 = 20;
+
+pub(crate) async fn get_coupons(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<Pagination>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<CouponsResult> {
+    let op = CouponsOperationImpl { pool };
+    handle_coupons(query.page, query.per_page, op).await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct CouponsResult {
+    coupons: Vec<Coupon>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct Coupon {
+    coupon_id: i64,
+    code: String,
+    discount_type: String,
+    discount_value: i32,
+    max_num_of_uses: Option<i32>,
+    max_num_of_uses_per_user: Option<i32>,
+    valid_from: String,  // RFC 3339形式の文字列
+    valid_until: String, // RFC 3339形式の文字列
+    discount_borne_by: String,
+    created_by: String,
+    created_at: String, // RFC 3339形式の文字列
+}
+
+#[async_trait]
+trait CouponsOperation {
+    async fn get_coupons(&self, page: u64, per_page: u64) -> Result<Vec<Coupon>, ErrResp>;
+}
+
+struct CouponsOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl CouponsOperation for CouponsOperationImpl {
+    async fn get_coupons(&self, page: u64, per_page: u64) -> Result<Vec<Coupon>, ErrResp> {
+        let models = entity::coupon::Entity::find()
+            .order_by_desc(entity::coupon::Column::CreatedAt)
+            .paginate(&self.pool, per_page)
+            .fetch_page(page)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find coupon (page: {}, per_page: {}): {}",
+                    page, per_page, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| Coupon {
+                coupon_id: m.coupon_id,
+                code: m.code,
+                discount_type: m.discount_type,
+                discount_value: m.discount_value,
+                max_num_of_uses: m.max_num_of_uses,
+                max_num_of_uses_per_user: m.max_num_of_uses_per_user,
+                valid_from: convert_date_time_to_rfc3339_string(m.valid_from),
+                valid_until: convert_date_time_to_rfc3339_string(m.valid_until),
+                discount_borne_by: m.discount_borne_by,
+                created_by: m.created_by,
+                created_at: convert_date_time_to_rfc3339_string(m.created_at),
+            })
+            .collect())
+    }
+}
+
+async fn handle_coupons(
+    page: u64,
+    per_page: u64,
+    op: impl CouponsOperation,
+) -> RespResult<CouponsResult> {
+    if per_page > VALID_PAGE_SIZE {
+        error!("invalid per_page ({})", per_page);
+        return Err(unexpected_err_resp());
+    };
+
+    let coupons = op.get_coupons(page, per_page).await?;
+
+    Ok((StatusCode::OK, Json(CouponsResult { coupons })))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+    use common::{
+        coupon::{DISCOUNT_BORNE_BY_PLATFORM, DISCOUNT_TYPE_PERCENTAGE},
+        JAPANESE_TIME_ZONE,
+    };
+
+    use crate::err::Code;
+
+    use super::*;
+
+    struct CouponsOperationMock {
+        page: u64,
+        per_page: u64,
+        coupons: Vec<Coupon>,
+    }
+
+    #[async_trait]
+    impl CouponsOperation for CouponsOperationMock {
+        async fn get_coupons(&self, page: u64, per_page: u64) -> Result<Vec<Coupon>, ErrResp> {
+            assert_eq!(self.page, page);
+            assert_eq!(self.per_page, per_page);
+            Ok(self.coupons.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_coupons_success() {
+        let page = 0;
+        let per_page = VALID_PAGE_SIZE;
+        let valid_from = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 4, 1, 0, 0, 0)
+            .unwrap();
+        let valid_until = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 4, 30, 23, 59, 59)
+            .unwrap();
+        let created_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let coupon = Coupon {
+            coupon_id: 1,
+            code: "FIRST2023".to_string(),
+            discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+            discount_value: 10,
+            max_num_of_uses: Some(100),
+            max_num_of_uses_per_user: Some(1),
+            valid_from: convert_date_time_to_rfc3339_string(valid_from),
+            valid_until: convert_date_time_to_rfc3339_string(valid_until),
+            discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            created_by: "admin@test.com".to_string(),
+            created_at: convert_date_time_to_rfc3339_string(created_at),
+        };
+        let op = CouponsOperationMock {
+            page,
+            per_page,
+            coupons: vec![coupon.clone()],
+        };
+
+        let result = handle_coupons(page, per_page, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            CouponsResult {
+                coupons: vec![coupon]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_coupons_fail() {
+        let page = 0;
+        let per_page = VALID_PAGE_SIZE + 1;
+        let op = CouponsOperationMock {
+            page,
+            per_page,
+            coupons: vec![],
+        };
+
+        let result = handle_coupons(page, per_page, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.0);
+        assert_eq!(Code::UnexpectedErr as u32, resp.1 .0.code);
+    }
+}
@@ -0,0 +1,624 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use common::coupon::{
+    DISCOUNT_BORNE_BY_CONSULTANT, DISCOUNT_BORNE_BY_PLATFORM, DISCOUNT_TYPE_FIXED_AMOUNT,
+    DISCOUNT_TYPE_PERCENTAGE,
+};
+use common::util::validator::coupon_code_validator::validate_coupon_code;
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::ActiveValue::NotSet;
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+
+// 100%の割引は支払額が0円となり、支払い手段が存在しないため許容しない
+const MAX_DISCOUNT_RATE_IN_PERCENTAGE: i32 = 99;
+
+pub(crate) async fn post_set_coupon_req(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<SetCouponReq>,
+) -> RespResult<SetCouponReqResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = SetCouponReqOperationImpl { pool };
+    handle_set_coupon_req(req, admin_info.email_address, current_date_time, &op).await
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SetCouponReq {
+    code: String,
+    discount_type: String,
+    discount_value: i32,
+    max_num_of_uses: Option<i32>,
+    max_num_of_uses_per_user: Option<i32>,
+    valid_from_in_jst: CouponTime,
+    valid_until_in_jst: CouponTime,
+    discount_borne_by: String,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct CouponTime {
+    year: u16, // 西暦
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SetCouponReqResult {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Coupon {
+    code: String,
+    discount_type: String,
+    discount_value: i32,
+    max_num_of_uses: Option<i32>,
+    max_num_of_uses_per_user: Option<i32>,
+    valid_from: DateTime<FixedOffset>,
+    valid_until: DateTime<FixedOffset>,
+    discount_borne_by: String,
+    created_by: String,
+    created_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait SetCouponReqOperation {
+    async fn count_coupons_by_code(&self, code: String) -> Result<u64, ErrResp>;
+
+    async fn set_coupon(&self, coupon: Coupon) -> Result<(), ErrResp>;
+}
+
+struct SetCouponReqOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SetCouponReqOperation for SetCouponReqOperationImpl {
+    async fn count_coupons_by_code(&self, code: String) -> Result<u64, ErrResp> {
+        entity::coupon::Entity::find()
+            .filter(entity::coupon::Column::Code.eq(code.clone()))
+            .count(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to count coupon (code: {}): {}", code, e);
+                unexpected_err_resp()
+            })
+    }
+
+    async fn set_coupon(&self, coupon: Coupon) -> Result<(), ErrResp> {
+        let am = entity::coupon::ActiveModel {
+            coupon_id: NotSet,
+            code: Set(coupon.code.clone()),
+            discount_type: Set(coupon.discount_type.clone()),
+            discount_value: Set(coupon.discount_value),
+            max_num_of_uses: Set(coupon.max_num_of_uses),
+            max_num_of_uses_per_user: Set(coupon.max_num_of_uses_per_user),
+            valid_from: Set(coupon.valid_from),
+            valid_until: Set(coupon.valid_until),
+            discount_borne_by: Set(coupon.discount_borne_by.clone()),
+            created_by: Set(coupon.created_by.clone()),
+            created_at: Set(coupon.created_at),
+        };
+        am.insert(&self.pool).await.map_err(|e| {
+            error!("failed to insert coupon ({:?}): {}", coupon, e);
+            unexpected_err_resp()
+        })?;
+        Ok(())
+    }
+}
+
+async fn handle_set_coupon_req(
+    req: SetCouponReq,
+    admin_email_address: String,
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl SetCouponReqOperation,
+) -> RespResult<SetCouponReqResult> {
+    validate_coupon_code(&req.code).map_err(|e| {
+        error!("invalid coupon code: {}", e);
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidCouponCodeFormat as u32,
+            }),
+        )
+    })?;
+    validate_discount(&req.discount_type, req.discount_value)?;
+    validate_max_num_of_uses(req.max_num_of_uses)?;
+    validate_max_num_of_uses(req.max_num_of_uses_per_user)?;
+    validate_discount_bearer(&req.discount_borne_by)?;
+
+    let valid_from = convert_coupon_time_type(&req.valid_from_in_jst)?;
+    let valid_until = convert_coupon_time_type(&req.valid_until_in_jst)?;
+    if valid_from >= valid_until {
+        error!(
+            "valid_from ({}) is equal to or after valid_until ({})",
+            valid_from, valid_until
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalCouponValidPeriod as u32,
+            }),
+        ));
+    }
+    if current_date_time >= valid_until {
+        error!(
+            "current_date_time ({}) is equal to or after valid_until ({})",
+            current_date_time, valid_until
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalCouponValidPeriod as u32,
+            }),
+        ));
+    }
+
+    let count = op.count_coupons_by_code(req.code.clone()).await?;
+    if count != 0 {
+        error!("coupon code ({}) already exists", req.code);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::CouponCodeAlreadyExists as u32,
+            }),
+        ));
+    }
+
+    op.set_coupon(Coupon {
+        code: req.code,
+        discount_type: req.discount_type,
+        discount_value: req.discount_value,
+        max_num_of_uses: req.max_num_of_uses,
+        max_num_of_uses_per_user: req.max_num_of_uses_per_user,
+        valid_from,
+        valid_until,
+        discount_borne_by: req.discount_borne_by,
+        created_by: admin_email_address,
+        created_at: current_date_time,
+    })
+    .await?;
+
+    Ok((StatusCode::OK, Json(SetCouponReqResult {})))
+}
+
+fn validate_discount(discount_type: &str, discount_value: i32) -> Result<(), ErrResp> {
+    if discount_type != DISCOUNT_TYPE_PERCENTAGE && discount_type != DISCOUNT_TYPE_FIXED_AMOUNT {
+        error!("illegal discount_type ({})", discount_type);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalDiscountType as u32,
+            }),
+        ));
+    }
+    if discount_value <= 0 {
+        error!("discount_value ({}) is not positive", discount_value);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalDiscountValue as u32,
+            }),
+        ));
+    }
+    if discount_type == DISCOUNT_TYPE_PERCENTAGE && discount_value > MAX_DISCOUNT_RATE_IN_PERCENTAGE
+    {
+        error!(
+            "discount_value ({}) exceeds max discount rate ({})",
+            discount_value, MAX_DISCOUNT_RATE_IN_PERCENTAGE
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalDiscountValue as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_max_num_of_uses(max_num_of_uses: Option<i32>) -> Result<(), ErrResp> {
+    if let Some(max_num_of_uses) = max_num_of_uses {
+        if max_num_of_uses <= 0 {
+            error!("max_num_of_uses ({}) is not positive", max_num_of_uses);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::IllegalMaxNumOfCouponUses as u32,
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_discount_bearer(discount_borne_by: &str) -> Result<(), ErrResp> {
+    if discount_borne_by != DISCOUNT_BORNE_BY_PLATFORM
+        && discount_borne_by != DISCOUNT_BORNE_BY_CONSULTANT
+    {
+        error!("illegal discount_borne_by ({})", discount_borne_by);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::IllegalDiscountBearer as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn convert_coupon_time_type(ct: &CouponTime) -> Result<DateTime<FixedOffset>, ErrResp> {
+    let result = JAPANESE_TIME_ZONE
+        .with_ymd_and_hms(
+            ct.year as i32,
+            ct.month as u32,
+            ct.day as u32,
+            ct.hour as u32,
+            ct.minute as u32,
+            ct.second as u32,
+        )
+        .single()
+        .ok_or_else(|| {
+            error!("illegal date time: {:?}", ct);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::IllegalDateTime as u32,
+                }),
+            )
+        })?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct SetCouponReqOperationMock {
+        num_of_coupons: u64,
+        coupon: Coupon,
+    }
+
+    #[async_trait]
+    impl SetCouponReqOperation for SetCouponReqOperationMock {
+        async fn count_coupons_by_code(&self, code: String) -> Result<u64, ErrResp> {
+            assert_eq!(self.coupon.code, code);
+            Ok(self.num_of_coupons)
+        }
+
+        async fn set_coupon(&self, coupon: Coupon) -> Result<(), ErrResp> {
+            assert_eq!(self.coupon, coupon);
+            Ok(())
+        }
+    }
+
+    fn create_dummy_req() -> SetCouponReq {
+        SetCouponReq {
+            code: "FIRST2023".to_string(),
+            discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+            discount_value: 10,
+            max_num_of_uses: Some(100),
+            max_num_of_uses_per_user: Some(1),
+            valid_from_in_jst: CouponTime {
+                year: 2023,
+                month: 4,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+            },
+            valid_until_in_jst: CouponTime {
+                year: 2023,
+                month: 4,
+                day: 30,
+                hour: 23,
+                minute: 59,
+                second: 59,
+            },
+            discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+        }
+    }
+
+    fn create_dummy_coupon(
+        req: &SetCouponReq,
+        admin_email_address: &str,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Coupon {
+        Coupon {
+            code: req.code.clone(),
+            discount_type: req.discount_type.clone(),
+            discount_value: req.discount_value,
+            max_num_of_uses: req.max_num_of_uses,
+            max_num_of_uses_per_user: req.max_num_of_uses_per_user,
+            valid_from: convert_coupon_time_type(&req.valid_from_in_jst).expect("failed to get Ok"),
+            valid_until: convert_coupon_time_type(&req.valid_until_in_jst)
+                .expect("failed to get Ok"),
+            discount_borne_by: req.discount_borne_by.clone(),
+            created_by: admin_email_address.to_string(),
+            created_at: current_date_time,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_success() {
+        let req = create_dummy_req();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect("failed to get Ok");
+
+        assert_eq!(result.0, StatusCode::OK);
+        assert_eq!(result.1 .0, SetCouponReqResult {});
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_success_fixed_amount_borne_by_consultant_without_limit() {
+        let mut req = create_dummy_req();
+        req.discount_type = DISCOUNT_TYPE_FIXED_AMOUNT.to_string();
+        req.discount_value = 1000;
+        req.max_num_of_uses = None;
+        req.max_num_of_uses_per_user = None;
+        req.discount_borne_by = DISCOUNT_BORNE_BY_CONSULTANT.to_string();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect("failed to get Ok");
+
+        assert_eq!(result.0, StatusCode::OK);
+        assert_eq!(result.1 .0, SetCouponReqResult {});
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_invalid_coupon_code() {
+        let mut req = create_dummy_req();
+        req.code = "first-2023".to_string();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::InvalidCouponCodeFormat as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_illegal_discount_type() {
+        let mut req = create_dummy_req();
+        req.discount_type = "other".to_string();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalDiscountType as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_discount_value_is_not_positive() {
+        let mut req = create_dummy_req();
+        req.discount_value = 0;
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalDiscountValue as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_discount_rate_exceeds_max() {
+        let mut req = create_dummy_req();
+        req.discount_value = MAX_DISCOUNT_RATE_IN_PERCENTAGE + 1;
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalDiscountValue as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_max_num_of_uses_per_user_is_not_positive() {
+        let mut req = create_dummy_req();
+        req.max_num_of_uses_per_user = Some(0);
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalMaxNumOfCouponUses as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_illegal_discount_bearer() {
+        let mut req = create_dummy_req();
+        req.discount_borne_by = "user".to_string();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalDiscountBearer as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_illegal_date_time() {
+        let mut req = create_dummy_req();
+        req.valid_until_in_jst.month = 2;
+        req.valid_until_in_jst.day = 30;
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(
+                &create_dummy_req(),
+                admin_email_address,
+                current_date_time,
+            ),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalDateTime as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_valid_until_is_before_valid_from() {
+        let mut req = create_dummy_req();
+        req.valid_until_in_jst.month = 3;
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalCouponValidPeriod as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_valid_until_has_already_passed() {
+        let req = create_dummy_req();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 0, 0, 0)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 0,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::IllegalCouponValidPeriod as u32);
+    }
+
+    #[tokio::test]
+    async fn handle_set_coupon_req_fail_coupon_code_already_exists() {
+        let req = create_dummy_req();
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 3, 11, 21, 32, 21)
+            .unwrap();
+        let op = SetCouponReqOperationMock {
+            num_of_coupons: 1,
+            coupon: create_dummy_coupon(&req, admin_email_address, current_date_time),
+        };
+
+        let result =
+            handle_set_coupon_req(req, admin_email_address.to_string(), current_date_time, &op)
+                .await
+                .expect_err("failed to get Err");
+
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1.code, Code::CouponCodeAlreadyExists as u32);
+    }
+}
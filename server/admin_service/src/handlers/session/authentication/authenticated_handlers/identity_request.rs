@@ -1,6 +1,7 @@
 // Copyright 2022 Ken Miura
 
 pub(crate) mod create_request;
+pub(crate) mod duplicate_identity_candidates;
 pub(crate) mod identity_images;
 pub(crate) mod update_request;
 
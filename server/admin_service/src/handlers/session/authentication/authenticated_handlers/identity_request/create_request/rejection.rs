@@ -5,7 +5,7 @@ use chrono::{DateTime, FixedOffset, Utc};
 use common::{
     smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS},
     storage::StorageClient,
-    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
+    ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME,
 };
 
 use axum::extract::State;
@@ -22,9 +22,16 @@ use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use crate::{
-    err::{unexpected_err_resp, Code},
+    err::unexpected_err_resp,
     handlers::session::authentication::authenticated_handlers::{
-        admin::Admin, identity_request::delete_identity_images, reason_validator::validate_reason,
+        admin::Admin,
+        identity_request::delete_identity_images,
+        rejection_reason_template::{
+            create_rejection_reason, find_rejection_reason_template_by_code,
+            no_rejection_reason_template_found, validate_additional_rejection_reason,
+            RejectionReasonTemplate,
+        },
+        review_claim::ensure_not_claimed_by_other_admin,
         user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
     },
 };
@@ -52,7 +59,8 @@ pub(crate) async fn post_create_identity_request_rejection(
     handle_create_identity_request_rejection(
         admin_info.email_address,
         create_identity_req_rejection.user_account_id,
-        create_identity_req_rejection.rejection_reason,
+        create_identity_req_rejection.rejection_reason_template_code,
+        create_identity_req_rejection.additional_rejection_reason,
         current_date_time,
         op,
         smtp_client,
@@ -63,7 +71,8 @@ pub(crate) async fn post_create_identity_request_rejection(
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub(crate) struct CreateIdentityReqRejection {
     user_account_id: i64,
-    rejection_reason: String,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -72,26 +81,28 @@ pub(crate) struct CreateIdentityReqRejectionResult {}
 async fn handle_create_identity_request_rejection(
     admin_email_address: String,
     user_account_id: i64,
-    rejection_reason: String,
+    rejection_reason_template_code: String,
+    additional_rejection_reason: Option<String>,
     rejected_time: DateTime<FixedOffset>,
     op: impl CreateIdentityReqRejectionOperation,
     send_mail: impl SendMail,
 ) -> RespResult<CreateIdentityReqRejectionResult> {
-    validate_reason(rejection_reason.as_str()).map_err(|e| {
-        error!("invalid format reason ({}): {}", rejection_reason, e);
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ApiError {
-                code: Code::InvalidFormatReason as u32,
-            }),
-        )
-    })?;
+    validate_additional_rejection_reason(additional_rejection_reason.as_deref())?;
+    let template = op
+        .find_rejection_reason_template(rejection_reason_template_code.clone())
+        .await?
+        .ok_or_else(|| {
+            no_rejection_reason_template_found(rejection_reason_template_code.as_str())
+        })?;
+    let rejection_reason =
+        create_rejection_reason(&template, additional_rejection_reason.as_deref());
 
     let rejected_user = op
         .reject_create_identity_req(
             user_account_id,
             admin_email_address,
             rejection_reason.clone(),
+            template.code,
             rejected_time,
         )
         .await?;
@@ -122,11 +133,17 @@ async fn handle_create_identity_request_rejection(
 
 #[async_trait]
 trait CreateIdentityReqRejectionOperation {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp>;
+
     async fn reject_create_identity_req(
         &self,
         user_account_id: i64,
         refuser_email_address: String,
         rejection_reason: String,
+        rejection_reason_template_code: String,
         rejected_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp>;
 }
@@ -138,11 +155,19 @@ struct CreateIdentityReqRejectionOperationImpl {
 
 #[async_trait]
 impl CreateIdentityReqRejectionOperation for CreateIdentityReqRejectionOperationImpl {
+    async fn find_rejection_reason_template(
+        &self,
+        code: String,
+    ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+        find_rejection_reason_template_by_code(&self.pool, code.as_str()).await
+    }
+
     async fn reject_create_identity_req(
         &self,
         user_account_id: i64,
         refuser_email_address: String,
         rejection_reason: String,
+        rejection_reason_template_code: String,
         rejected_time: DateTime<FixedOffset>,
     ) -> Result<Option<String>, ErrResp> {
         let storage_client = self.storage_client.clone();
@@ -154,6 +179,15 @@ impl CreateIdentityReqRejectionOperation for CreateIdentityReqRejectionOperation
 
                     let req = find_create_identity_req_model_by_user_account_id_with_exclusive_lock(txn, user_account_id).await?;
 
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        refuser_email_address.as_str(),
+                        rejected_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
                     let user = match user_option {
                         Some(m) => m,
                         None => {
@@ -166,7 +200,7 @@ impl CreateIdentityReqRejectionOperation for CreateIdentityReqRejectionOperation
                         return Ok(None)
                     }
 
-                    let rejected_req_active_model = generate_rejected_create_identity_req_active_model(req.clone(), rejected_time, rejection_reason, refuser_email_address);
+                    let rejected_req_active_model = generate_rejected_create_identity_req_active_model(req.clone(), rejected_time, rejection_reason, rejection_reason_template_code, refuser_email_address);
                     let _ = rejected_req_active_model.insert(txn).await.map_err(|e| {
                         error!(
                             "failed to insert rejected_create_identity_req (user_account_id: {}): {}",
@@ -204,6 +238,7 @@ fn generate_rejected_create_identity_req_active_model(
     model: create_identity_req::Model,
     rejected_time: DateTime<FixedOffset>,
     rejection_reason: String,
+    rejection_reason_template_code: String,
     refuser_email_address: String,
 ) -> rejected_create_identity_req::ActiveModel {
     rejected_create_identity_req::ActiveModel {
@@ -220,6 +255,7 @@ fn generate_rejected_create_identity_req_active_model(
         address_line2: Set(model.address_line2),
         telephone_number: Set(model.telephone_number),
         reason: Set(rejection_reason),
+        reason_template_code: Set(Some(rejection_reason_template_code)),
         rejected_at: Set(rejected_time),
         rejected_by: Set(refuser_email_address),
     }
@@ -266,22 +302,38 @@ mod tests {
         admin_email_address: String,
         user_option: Option<User>,
         rejection_reason: String,
+        rejection_reason_template: RejectionReasonTemplate,
         rejected_time: DateTime<FixedOffset>,
     }
 
     #[async_trait]
     impl CreateIdentityReqRejectionOperation for CreateIdentityReqRejectionOperationMock {
+        async fn find_rejection_reason_template(
+            &self,
+            code: String,
+        ) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+            if self.rejection_reason_template.code != code {
+                return Ok(None);
+            }
+            Ok(Some(self.rejection_reason_template.clone()))
+        }
+
         async fn reject_create_identity_req(
             &self,
             user_account_id: i64,
             refuser_email_address: String,
             rejection_reason: String,
+            rejection_reason_template_code: String,
             rejected_time: DateTime<FixedOffset>,
         ) -> Result<Option<String>, ErrResp> {
             if let Some(user) = self.user_option.clone() {
                 assert_eq!(user.user_account_id, user_account_id);
                 assert_eq!(self.admin_email_address, refuser_email_address);
                 assert_eq!(self.rejection_reason, rejection_reason);
+                assert_eq!(
+                    self.rejection_reason_template.code,
+                    rejection_reason_template_code
+                );
                 assert_eq!(self.rejected_time, rejected_time);
                 Ok(Some(user.email_address))
             } else {
@@ -290,6 +342,13 @@ mod tests {
         }
     }
 
+    fn create_rejection_reason_template() -> RejectionReasonTemplate {
+        RejectionReasonTemplate {
+            code: "blurred-image".to_string(),
+            body: "画像が不鮮明なため".to_string(),
+        }
+    }
+
     #[tokio::test]
     async fn handle_create_identity_request_rejection_success() {
         let admin_email_address = String::from("admin@test.com");
@@ -307,6 +366,7 @@ mod tests {
             admin_email_address: admin_email_address.clone(),
             user_option,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -319,7 +379,8 @@ mod tests {
         let result = handle_create_identity_request_rejection(
             admin_email_address,
             user_account_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            None,
             rejected_time,
             op_mock,
             send_mail_mock,
@@ -348,6 +409,7 @@ mod tests {
             admin_email_address: admin_email_address.clone(),
             user_option,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -360,7 +422,8 @@ mod tests {
         let result = handle_create_identity_request_rejection(
             admin_email_address,
             user_account_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            Some(rejection_reason.to_string()),
             rejected_time,
             op_mock,
             send_mail_mock,
@@ -372,6 +435,93 @@ mod tests {
         assert_eq!(Code::InvalidFormatReason as u32, resp.1 .0.code);
     }
 
+    #[tokio::test]
+    async fn handle_create_identity_request_rejection_success_with_additional_rejection_reason() {
+        let admin_email_address = String::from("admin@test.com");
+        let user_account_id = 53215;
+        let user_email_address = String::from("test@test.com");
+        let user_option = Some(User {
+            user_account_id,
+            email_address: user_email_address.clone(),
+        });
+        let additional_rejection_reason = "二枚目の画像が判読できません";
+        let rejection_reason = "画像が不鮮明なため\n二枚目の画像が判読できません";
+        let rejected_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 4, 5, 21, 0, 40)
+            .unwrap();
+        let op_mock = CreateIdentityReqRejectionOperationMock {
+            admin_email_address: admin_email_address.clone(),
+            user_option,
+            rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
+            rejected_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            user_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_create_identity_request_rejection(
+            admin_email_address,
+            user_account_id,
+            create_rejection_reason_template().code,
+            Some(additional_rejection_reason.to_string()),
+            rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(CreateIdentityReqRejectionResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_create_identity_request_rejection_fail_no_rejection_reason_template_found() {
+        let admin_email_address = String::from("admin@test.com");
+        let user_account_id = 53215;
+        let user_email_address = String::from("test@test.com");
+        let user_option = Some(User {
+            user_account_id,
+            email_address: user_email_address.clone(),
+        });
+        let rejection_reason = "画像が不鮮明なため";
+        let rejected_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 4, 5, 21, 0, 40)
+            .unwrap();
+        let op_mock = CreateIdentityReqRejectionOperationMock {
+            admin_email_address: admin_email_address.clone(),
+            user_option,
+            rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
+            rejected_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            user_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(rejection_reason.to_string()),
+        );
+
+        let result = handle_create_identity_request_rejection(
+            admin_email_address,
+            user_account_id,
+            "unknown-code".to_string(),
+            None,
+            rejected_time,
+            op_mock,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoRejectionReasonTemplateFound as u32, resp.1 .0.code);
+    }
+
     #[tokio::test]
     async fn handle_create_identity_request_rejection_success_no_user_account_found() {
         let admin_email_address = String::from("admin@test.com");
@@ -385,6 +535,7 @@ mod tests {
             admin_email_address: admin_email_address.clone(),
             user_option: None,
             rejection_reason: rejection_reason.to_string(),
+            rejection_reason_template: create_rejection_reason_template(),
             rejected_time,
         };
         let send_mail_mock = SendMailMock::new(
@@ -397,7 +548,8 @@ mod tests {
         let result = handle_create_identity_request_rejection(
             admin_email_address,
             user_account_id,
-            rejection_reason.to_string(),
+            create_rejection_reason_template().code,
+            None,
             rejected_time,
             op_mock,
             send_mail_mock,
@@ -0,0 +1,1093 @@
+// Copyright 2023 Ken Miura
+
+use std::collections::HashMap;
+
+use axum::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Datelike, NaiveDate};
+use common::opensearch::normalization::normalize_text;
+use common::util::Ymd;
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use entity::{bank_account, create_identity_req, identity, update_identity_req};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+
+/// 重複の候補として返す最大数（スコアの高い順）
+const MAX_NUM_OF_CANDIDATES: usize = 20;
+/// 重複の候補として扱うスコアの最小値
+///
+/// 生年月日の一致のみ（生年月日の一致はusers_by_date_of_birthで確認できる）は候補としない
+const MIN_SCORE: u32 = 30;
+/// 類似とみなす類似度（%）の最小値
+const MIN_SIMILARITY_IN_PERCENT: u32 = 80;
+/// スコアを計算する前にDBから取得する候補の最大数（項目毎）
+///
+/// 一つの項目で一致（類似）する身分情報が多い場合でも他の項目で一致する身分情報を取りこぼさないように、項目毎に取得する
+const MAX_NUM_OF_ROWS_TO_COMPARE_PER_ITEM: usize = 200;
+
+const SAME_TELEPHONE_NUMBER_SCORE: u32 = 40;
+const SAME_BANK_ACCOUNT_HOLDER_NAME_SCORE: u32 = 30;
+const SAME_DATE_OF_BIRTH_SCORE: u32 = 20;
+/// 類似度100%の場合のスコア（類似度に比例してスコアを与える）
+const MAX_SIMILAR_FURIGANA_SCORE: u32 = 30;
+/// 類似度100%の場合のスコア（類似度に比例してスコアを与える）
+const MAX_SIMILAR_ADDRESS_SCORE: u32 = 30;
+
+pub(crate) async fn get_duplicate_identity_candidates(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<DuplicateIdentityCandidatesQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<Vec<DuplicateIdentityCandidate>> {
+    let query = query.0;
+    let op = DuplicateIdentityCandidatesOperationImpl { pool };
+    handle_duplicate_identity_candidates(query.identity_req_type, query.user_account_id, op).await
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum IdentityReqType {
+    Create,
+    Update,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DuplicateIdentityCandidatesQuery {
+    identity_req_type: IdentityReqType,
+    user_account_id: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct DuplicateIdentityCandidate {
+    user_account_id: i64,
+    last_name: String,
+    first_name: String,
+    last_name_furigana: String,
+    first_name_furigana: String,
+    date_of_birth: Ymd,
+    prefecture: String,
+    city: String,
+    address_line1: String,
+    address_line2: Option<String>,
+    telephone_number: String,
+    score: u32,
+    /// 重複の候補とした理由（一致、または類似した項目）
+    reasons: Vec<DuplicateReason>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct DuplicateReason {
+    item: DuplicateItem,
+    /// 一致した項目の場合、100
+    similarity_in_percent: u32,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DuplicateItem {
+    Furigana,
+    TelephoneNumber,
+    Address,
+    BankAccountHolderName,
+    DateOfBirth,
+}
+
+/// 比較に利用する依頼者の身分情報
+#[derive(Clone, Debug, PartialEq)]
+struct IdentityToCompare {
+    user_account_id: i64,
+    last_name: String,
+    first_name: String,
+    last_name_furigana: String,
+    first_name_furigana: String,
+    date_of_birth: NaiveDate,
+    prefecture: String,
+    city: String,
+    address_line1: String,
+    address_line2: Option<String>,
+    telephone_number: String,
+    /// 口座を登録していない場合、None
+    bank_account_holder_name: Option<String>,
+}
+
+async fn handle_duplicate_identity_candidates(
+    identity_req_type: IdentityReqType,
+    user_account_id: i64,
+    op: impl DuplicateIdentityCandidatesOperation,
+) -> RespResult<Vec<DuplicateIdentityCandidate>> {
+    let req = op
+        .find_identity_req(identity_req_type, user_account_id)
+        .await?;
+    let req = req.ok_or_else(|| {
+        error!(
+            "no identity request (identity_req_type: {:?}, user_account_id: {}) found",
+            identity_req_type, user_account_id
+        );
+        let code = match identity_req_type {
+            IdentityReqType::Create => Code::NoCreateIdentityReqDetailFound,
+            IdentityReqType::Update => Code::NoUpdateIdentityReqDetailFound,
+        };
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError { code: code as u32 }),
+        )
+    })?;
+
+    let identities = op.find_identities_to_compare(&req).await?;
+
+    let mut candidates = identities
+        .into_iter()
+        .filter(|identity| identity.user_account_id != req.user_account_id)
+        .filter_map(|identity| create_candidate(&req, identity))
+        .collect::<Vec<DuplicateIdentityCandidate>>();
+    candidates.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.user_account_id.cmp(&b.user_account_id))
+    });
+    candidates.truncate(MAX_NUM_OF_CANDIDATES);
+
+    Ok((StatusCode::OK, Json(candidates)))
+}
+
+fn create_candidate(
+    req: &IdentityToCompare,
+    identity: IdentityToCompare,
+) -> Option<DuplicateIdentityCandidate> {
+    let reasons = find_duplicate_reasons(req, &identity);
+    let score = reasons.iter().map(calculate_score).sum::<u32>();
+    if score < MIN_SCORE {
+        return None;
+    }
+    Some(DuplicateIdentityCandidate {
+        user_account_id: identity.user_account_id,
+        last_name: identity.last_name,
+        first_name: identity.first_name,
+        last_name_furigana: identity.last_name_furigana,
+        first_name_furigana: identity.first_name_furigana,
+        date_of_birth: Ymd {
+            year: identity.date_of_birth.year(),
+            month: identity.date_of_birth.month(),
+            day: identity.date_of_birth.day(),
+        },
+        prefecture: identity.prefecture,
+        city: identity.city,
+        address_line1: identity.address_line1,
+        address_line2: identity.address_line2,
+        telephone_number: identity.telephone_number,
+        score,
+        reasons,
+    })
+}
+
+fn find_duplicate_reasons(
+    req: &IdentityToCompare,
+    identity: &IdentityToCompare,
+) -> Vec<DuplicateReason> {
+    let mut reasons = Vec::new();
+
+    let req_furigana = normalize_furigana(&req.last_name_furigana, &req.first_name_furigana);
+    let furigana = normalize_furigana(&identity.last_name_furigana, &identity.first_name_furigana);
+    let furigana_similarity = calculate_similarity_in_percent(&req_furigana, &furigana);
+    if furigana_similarity >= MIN_SIMILARITY_IN_PERCENT {
+        reasons.push(DuplicateReason {
+            item: DuplicateItem::Furigana,
+            similarity_in_percent: furigana_similarity,
+        });
+    }
+
+    if normalize_telephone_number(&req.telephone_number)
+        == normalize_telephone_number(&identity.telephone_number)
+    {
+        reasons.push(DuplicateReason {
+            item: DuplicateItem::TelephoneNumber,
+            similarity_in_percent: 100,
+        });
+    }
+
+    let address_similarity =
+        calculate_similarity_in_percent(&normalize_address(req), &normalize_address(identity));
+    if address_similarity >= MIN_SIMILARITY_IN_PERCENT {
+        reasons.push(DuplicateReason {
+            item: DuplicateItem::Address,
+            similarity_in_percent: address_similarity,
+        });
+    }
+
+    if bank_account_holder_name_matches(req, identity, &req_furigana) {
+        reasons.push(DuplicateReason {
+            item: DuplicateItem::BankAccountHolderName,
+            similarity_in_percent: 100,
+        });
+    }
+
+    if req.date_of_birth == identity.date_of_birth {
+        reasons.push(DuplicateReason {
+            item: DuplicateItem::DateOfBirth,
+            similarity_in_percent: 100,
+        });
+    }
+
+    reasons
+}
+
+/// 口座名義が依頼者のフリガナ、または依頼者の口座名義と一致するか確認する
+fn bank_account_holder_name_matches(
+    req: &IdentityToCompare,
+    identity: &IdentityToCompare,
+    req_furigana: &str,
+) -> bool {
+    let holder_name = match identity.bank_account_holder_name.as_ref() {
+        Some(h) => normalize_furigana(h, ""),
+        None => return false,
+    };
+    if holder_name == req_furigana {
+        return true;
+    }
+    match req.bank_account_holder_name.as_ref() {
+        Some(req_holder_name) => normalize_furigana(req_holder_name, "") == holder_name,
+        None => false,
+    }
+}
+
+fn calculate_score(reason: &DuplicateReason) -> u32 {
+    match reason.item {
+        DuplicateItem::Furigana => MAX_SIMILAR_FURIGANA_SCORE * reason.similarity_in_percent / 100,
+        DuplicateItem::TelephoneNumber => SAME_TELEPHONE_NUMBER_SCORE,
+        DuplicateItem::Address => MAX_SIMILAR_ADDRESS_SCORE * reason.similarity_in_percent / 100,
+        DuplicateItem::BankAccountHolderName => SAME_BANK_ACCOUNT_HOLDER_NAME_SCORE,
+        DuplicateItem::DateOfBirth => SAME_DATE_OF_BIRTH_SCORE,
+    }
+}
+
+/// フリガナ（口座名義）を比較用に正規化する
+///
+/// 口座名義では小さい仮名（ャ、ッ等）が大きい仮名で登録されることがあるため、小さい仮名は大きい仮名に揃える
+fn normalize_furigana(last_name_furigana: &str, first_name_furigana: &str) -> String {
+    normalize_text(format!("{}{}", last_name_furigana, first_name_furigana).as_str())
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(small_kana_to_large_kana)
+        .collect()
+}
+
+fn small_kana_to_large_kana(c: char) -> char {
+    match c {
+        'ぁ' => 'あ',
+        'ぃ' => 'い',
+        'ぅ' => 'う',
+        'ぇ' => 'え',
+        'ぉ' => 'お',
+        'っ' => 'つ',
+        'ゃ' => 'や',
+        'ゅ' => 'ゆ',
+        'ょ' => 'よ',
+        'ゎ' => 'わ',
+        _ => c,
+    }
+}
+
+fn normalize_telephone_number(telephone_number: &str) -> String {
+    normalize_text(telephone_number)
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect()
+}
+
+/// 住所を比較用に正規化する
+///
+/// 漢数字を算用数字に変換し、「丁目」「番地」「番」「の」（数字の区切りとして利用されている場合）を「-」に揃え、「号」と空白を除去する。
+/// 例えば、「三丁目十二番地五号」と「３－１２－５」は、どちらも「3-12-5」となる
+fn normalize_address(identity: &IdentityToCompare) -> String {
+    let address = format!(
+        "{}{}{}{}",
+        identity.prefecture,
+        identity.city,
+        identity.address_line1,
+        identity.address_line2.clone().unwrap_or_default()
+    );
+    let address = convert_kanji_numerals(normalize_text(address.as_str()).as_str());
+    let address = address
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            // 住所の区切りとして利用されることのある記号を「-」に揃える
+            'ー' | '‐' | '−' | '—' | '–' => '-',
+            _ => c,
+        })
+        .collect::<String>()
+        .replace("丁目", "-")
+        .replace("番地", "-")
+        .replace('番', "-")
+        .replace('号', "");
+    let chars = address.chars().collect::<Vec<char>>();
+    let mut result = String::with_capacity(address.len());
+    for (i, c) in chars.iter().enumerate() {
+        let c = if *c == 'の'
+            && i > 0
+            && chars[i - 1].is_ascii_digit()
+            && chars.get(i + 1).map_or(false, char::is_ascii_digit)
+        {
+            '-'
+        } else {
+            *c
+        };
+        if c == '-' && result.ends_with('-') {
+            continue;
+        }
+        result.push(c);
+    }
+    result.trim_end_matches('-').to_string()
+}
+
+/// 文字列中の漢数字（一〜九、〇、十、百、千）の並びを算用数字に変換する
+fn convert_kanji_numerals(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut numerals = Vec::new();
+    for c in text.chars() {
+        if kanji_numeral_to_digit(c).is_some() || kanji_numeral_to_unit(c).is_some() {
+            numerals.push(c);
+            continue;
+        }
+        if !numerals.is_empty() {
+            result.push_str(convert_kanji_numeral_sequence(&numerals).as_str());
+            numerals.clear();
+        }
+        result.push(c);
+    }
+    if !numerals.is_empty() {
+        result.push_str(convert_kanji_numeral_sequence(&numerals).as_str());
+    }
+    result
+}
+
+fn convert_kanji_numeral_sequence(numerals: &[char]) -> String {
+    // 「十」「百」「千」を含まない場合、位取り記数法（例: 「一〇五」）として扱う
+    if numerals.iter().all(|c| kanji_numeral_to_unit(*c).is_none()) {
+        return numerals
+            .iter()
+            .filter_map(|c| kanji_numeral_to_digit(*c))
+            .map(|d| char::from_digit(d, 10).unwrap_or('0'))
+            .collect();
+    }
+    let mut total = 0;
+    let mut current_digit = None;
+    for c in numerals {
+        if let Some(d) = kanji_numeral_to_digit(*c) {
+            current_digit = Some(d);
+        } else if let Some(unit) = kanji_numeral_to_unit(*c) {
+            total += current_digit.unwrap_or(1) * unit;
+            current_digit = None;
+        }
+    }
+    total += current_digit.unwrap_or(0);
+    total.to_string()
+}
+
+fn kanji_numeral_to_digit(c: char) -> Option<u32> {
+    match c {
+        '〇' => Some(0),
+        '一' => Some(1),
+        '二' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    }
+}
+
+fn kanji_numeral_to_unit(c: char) -> Option<u32> {
+    match c {
+        '十' => Some(10),
+        '百' => Some(100),
+        '千' => Some(1000),
+        _ => None,
+    }
+}
+
+/// 二つの文字列の類似度（%）をレーベンシュタイン距離を元に計算する
+fn calculate_similarity_in_percent(a: &str, b: &str) -> u32 {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 0;
+    }
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution_cost = usize::from(ca != cb);
+            current[j + 1] = (prev[j + 1] + 1)
+                .min(current[j] + 1)
+                .min(prev[j] + substitution_cost);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+    let distance = prev[b.len()];
+    ((max_len - distance) * 100 / max_len) as u32
+}
+
+#[async_trait]
+trait DuplicateIdentityCandidatesOperation {
+    async fn find_identity_req(
+        &self,
+        identity_req_type: IdentityReqType,
+        user_account_id: i64,
+    ) -> Result<Option<IdentityToCompare>, ErrResp>;
+
+    /// 比較対象とする身分情報を取得する
+    ///
+    /// 全件を比較すると時間がかかるため、電話番号、口座名義のいずれかが一致する、またはフリガナ、住所のいずれかが類似する身分情報に絞って取得する。
+    /// 生年月日の一致のみでは[MIN_SCORE]に届かず、生年月日以外の項目でも一致（類似）する身分情報は他の項目で取得できるため、生年月日では絞り込まない
+    async fn find_identities_to_compare(
+        &self,
+        req: &IdentityToCompare,
+    ) -> Result<Vec<IdentityToCompare>, ErrResp>;
+}
+
+struct DuplicateIdentityCandidatesOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl DuplicateIdentityCandidatesOperation for DuplicateIdentityCandidatesOperationImpl {
+    async fn find_identity_req(
+        &self,
+        identity_req_type: IdentityReqType,
+        user_account_id: i64,
+    ) -> Result<Option<IdentityToCompare>, ErrResp> {
+        let identity_to_compare = match identity_req_type {
+            IdentityReqType::Create => create_identity_req::Entity::find_by_id(user_account_id)
+                .one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!(
+                        "failed to find create_identity_req (user_account_id: {}): {}",
+                        user_account_id, e
+                    );
+                    unexpected_err_resp()
+                })?
+                .map(|m| IdentityToCompare {
+                    user_account_id: m.user_account_id,
+                    last_name: m.last_name,
+                    first_name: m.first_name,
+                    last_name_furigana: m.last_name_furigana,
+                    first_name_furigana: m.first_name_furigana,
+                    date_of_birth: m.date_of_birth,
+                    prefecture: m.prefecture,
+                    city: m.city,
+                    address_line1: m.address_line1,
+                    address_line2: m.address_line2,
+                    telephone_number: m.telephone_number,
+                    bank_account_holder_name: None,
+                }),
+            IdentityReqType::Update => update_identity_req::Entity::find_by_id(user_account_id)
+                .one(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!(
+                        "failed to find update_identity_req (user_account_id: {}): {}",
+                        user_account_id, e
+                    );
+                    unexpected_err_resp()
+                })?
+                .map(|m| IdentityToCompare {
+                    user_account_id: m.user_account_id,
+                    last_name: m.last_name,
+                    first_name: m.first_name,
+                    last_name_furigana: m.last_name_furigana,
+                    first_name_furigana: m.first_name_furigana,
+                    date_of_birth: m.date_of_birth,
+                    prefecture: m.prefecture,
+                    city: m.city,
+                    address_line1: m.address_line1,
+                    address_line2: m.address_line2,
+                    telephone_number: m.telephone_number,
+                    bank_account_holder_name: None,
+                }),
+        };
+        let mut identity_to_compare = match identity_to_compare {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+        let bank_account_holder_names =
+            find_bank_account_holder_names(&self.pool, vec![user_account_id]).await?;
+        identity_to_compare.bank_account_holder_name =
+            bank_account_holder_names.get(&user_account_id).cloned();
+        Ok(Some(identity_to_compare))
+    }
+
+    async fn find_identities_to_compare(
+        &self,
+        req: &IdentityToCompare,
+    ) -> Result<Vec<IdentityToCompare>, ErrResp> {
+        let user_account_ids_with_same_telephone_number =
+            find_user_account_ids_with_same_telephone_number(&self.pool, req).await?;
+        let user_account_ids_with_same_holder_name =
+            find_user_account_ids_with_same_holder_name(&self.pool, req).await?;
+        let user_account_ids_with_similar_furigana =
+            find_user_account_ids_with_similar_furigana(&self.pool, req).await?;
+        let user_account_ids_with_similar_address =
+            find_user_account_ids_with_similar_address(&self.pool, req).await?;
+        let user_account_ids = merge_user_account_ids(
+            vec![
+                user_account_ids_with_same_telephone_number,
+                user_account_ids_with_same_holder_name,
+                user_account_ids_with_similar_furigana,
+                user_account_ids_with_similar_address,
+            ],
+            req.user_account_id,
+        );
+        if user_account_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let models = identity::Entity::find()
+            .filter(identity::Column::UserAccountId.is_in(user_account_ids.clone()))
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find identities to compare (user_account_id: {}): {}",
+                    req.user_account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+
+        let bank_account_holder_names =
+            find_bank_account_holder_names(&self.pool, user_account_ids).await?;
+
+        Ok(models
+            .into_iter()
+            .map(|m| IdentityToCompare {
+                user_account_id: m.user_account_id,
+                last_name: m.last_name,
+                first_name: m.first_name,
+                last_name_furigana: m.last_name_furigana,
+                first_name_furigana: m.first_name_furigana,
+                date_of_birth: m.date_of_birth,
+                prefecture: m.prefecture,
+                city: m.city,
+                address_line1: m.address_line1,
+                address_line2: m.address_line2,
+                telephone_number: m.telephone_number,
+                bank_account_holder_name: bank_account_holder_names
+                    .get(&m.user_account_id)
+                    .cloned(),
+            })
+            .collect())
+    }
+}
+
+async fn find_user_account_ids_with_same_telephone_number(
+    pool: &DatabaseConnection,
+    req: &IdentityToCompare,
+) -> Result<Vec<i64>, ErrResp> {
+    identity::Entity::find()
+        .select_only()
+        .column(identity::Column::UserAccountId)
+        .filter(identity::Column::TelephoneNumber.eq(req.telephone_number.as_str()))
+        .filter(identity::Column::UserAccountId.ne(req.user_account_id))
+        .order_by_asc(identity::Column::UserAccountId)
+        .limit(MAX_NUM_OF_ROWS_TO_COMPARE_PER_ITEM as u64)
+        .into_tuple::<i64>()
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find identities with same telephone number (user_account_id: {}): {}",
+                req.user_account_id, e
+            );
+            unexpected_err_resp()
+        })
+}
+
+async fn find_user_account_ids_with_same_holder_name(
+    pool: &DatabaseConnection,
+    req: &IdentityToCompare,
+) -> Result<Vec<i64>, ErrResp> {
+    // 口座名義は全角カタカナと全角空白で登録されるため、フリガナから想定される口座名義で絞り込む
+    let mut holder_names = vec![
+        format!("{}　{}", req.last_name_furigana, req.first_name_furigana),
+        format!("{}{}", req.last_name_furigana, req.first_name_furigana),
+    ];
+    if let Some(holder_name) = req.bank_account_holder_name.clone() {
+        holder_names.push(holder_name);
+    }
+    bank_account::Entity::find()
+        .select_only()
+        .column(bank_account::Column::UserAccountId)
+        .filter(bank_account::Column::AccountHolderName.is_in(holder_names))
+        .filter(bank_account::Column::UserAccountId.ne(req.user_account_id))
+        .order_by_asc(bank_account::Column::UserAccountId)
+        .limit(MAX_NUM_OF_ROWS_TO_COMPARE_PER_ITEM as u64)
+        .into_tuple::<i64>()
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find bank_account with same holder name (user_account_id: {}): {}",
+                req.user_account_id, e
+            );
+            unexpected_err_resp()
+        })
+}
+
+/// フリガナが類似する身分情報のユーザーアカウントIDを類似度の高い順に取得する
+///
+/// 表記揺れ（小さい仮名、半角カナ等）や一文字違いは完全一致の条件では見つけられないため、
+/// 全ての身分情報のフリガナ（のみ）を取得し、類似度を計算して絞り込む
+async fn find_user_account_ids_with_similar_furigana(
+    pool: &DatabaseConnection,
+    req: &IdentityToCompare,
+) -> Result<Vec<i64>, ErrResp> {
+    let furiganas = identity::Entity::find()
+        .select_only()
+        .column(identity::Column::UserAccountId)
+        .column(identity::Column::LastNameFurigana)
+        .column(identity::Column::FirstNameFurigana)
+        .filter(identity::Column::UserAccountId.ne(req.user_account_id))
+        .into_tuple::<(i64, String, String)>()
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find furigana of identities (user_account_id: {}): {}",
+                req.user_account_id, e
+            );
+            unexpected_err_resp()
+        })?;
+    let req_furigana = normalize_furigana(&req.last_name_furigana, &req.first_name_furigana);
+    let furiganas = furiganas
+        .into_iter()
+        .map(
+            |(user_account_id, last_name_furigana, first_name_furigana)| {
+                (
+                    user_account_id,
+                    normalize_furigana(&last_name_furigana, &first_name_furigana),
+                )
+            },
+        )
+        .collect();
+    Ok(select_similar_user_account_ids(
+        &req_furigana,
+        furiganas,
+        MAX_NUM_OF_ROWS_TO_COMPARE_PER_ITEM,
+    ))
+}
+
+/// 住所が類似する身分情報のユーザーアカウントIDを類似度の高い順に取得する
+///
+/// 都道府県と市区町村が一致する身分情報の住所を取得し、類似度を計算して絞り込む
+async fn find_user_account_ids_with_similar_address(
+    pool: &DatabaseConnection,
+    req: &IdentityToCompare,
+) -> Result<Vec<i64>, ErrResp> {
+    let addresses = identity::Entity::find()
+        .select_only()
+        .column(identity::Column::UserAccountId)
+        .column(identity::Column::AddressLine1)
+        .column(identity::Column::AddressLine2)
+        .filter(identity::Column::Prefecture.eq(req.prefecture.as_str()))
+        .filter(identity::Column::City.eq(req.city.as_str()))
+        .filter(identity::Column::UserAccountId.ne(req.user_account_id))
+        .into_tuple::<(i64, String, Option<String>)>()
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find addresses of identities (user_account_id: {}): {}",
+                req.user_account_id, e
+            );
+            unexpected_err_resp()
+        })?;
+    let req_address = normalize_address(req);
+    let addresses = addresses
+        .into_iter()
+        .map(|(user_account_id, address_line1, address_line2)| {
+            let identity = IdentityToCompare {
+                address_line1,
+                address_line2,
+                ..req.clone()
+            };
+            (user_account_id, normalize_address(&identity))
+        })
+        .collect();
+    Ok(select_similar_user_account_ids(
+        &req_address,
+        addresses,
+        MAX_NUM_OF_ROWS_TO_COMPARE_PER_ITEM,
+    ))
+}
+
+/// 類似度が[MIN_SIMILARITY_IN_PERCENT]以上のユーザーアカウントIDを、類似度の高い順に最大limit件返す
+///
+/// valuesは（ユーザーアカウントID, 正規化済みの文字列）の組で、targetも正規化済みの文字列を渡す
+fn select_similar_user_account_ids(
+    target: &str,
+    values: Vec<(i64, String)>,
+    limit: usize,
+) -> Vec<i64> {
+    let mut similarities = values
+        .into_iter()
+        .map(|(user_account_id, value)| {
+            (
+                user_account_id,
+                calculate_similarity_in_percent(target, &value),
+            )
+        })
+        .filter(|(_, similarity)| *similarity >= MIN_SIMILARITY_IN_PERCENT)
+        .collect::<Vec<(i64, u32)>>();
+    similarities.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    similarities.truncate(limit);
+    similarities
+        .into_iter()
+        .map(|(user_account_id, _)| user_account_id)
+        .collect()
+}
+
+/// 項目毎に取得したユーザーアカウントIDを、重複と依頼者自身を除いて一つにまとめる
+fn merge_user_account_ids(
+    user_account_ids_list: Vec<Vec<i64>>,
+    req_user_account_id: i64,
+) -> Vec<i64> {
+    let mut merged = Vec::new();
+    for user_account_id in user_account_ids_list.into_iter().flatten() {
+        if user_account_id != req_user_account_id && !merged.contains(&user_account_id) {
+            merged.push(user_account_id);
+        }
+    }
+    merged
+}
+
+async fn find_bank_account_holder_names(
+    pool: &DatabaseConnection,
+    user_account_ids: Vec<i64>,
+) -> Result<HashMap<i64, String>, ErrResp> {
+    let models = bank_account::Entity::find()
+        .filter(bank_account::Column::UserAccountId.is_in(user_account_ids))
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find bank_account: {}", e);
+            unexpected_err_resp()
+        })?;
+    Ok(models
+        .into_iter()
+        .map(|m| (m.user_account_id, m.account_holder_name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct DuplicateIdentityCandidatesOperationMock {
+        identity_req_type: IdentityReqType,
+        req: IdentityToCompare,
+        identities: Vec<IdentityToCompare>,
+    }
+
+    #[async_trait]
+    impl DuplicateIdentityCandidatesOperation for DuplicateIdentityCandidatesOperationMock {
+        async fn find_identity_req(
+            &self,
+            identity_req_type: IdentityReqType,
+            user_account_id: i64,
+        ) -> Result<Option<IdentityToCompare>, ErrResp> {
+            if self.identity_req_type != identity_req_type
+                || self.req.user_account_id != user_account_id
+            {
+                return Ok(None);
+            }
+            Ok(Some(self.req.clone()))
+        }
+
+        async fn find_identities_to_compare(
+            &self,
+            req: &IdentityToCompare,
+        ) -> Result<Vec<IdentityToCompare>, ErrResp> {
+            assert_eq!(&self.req, req);
+            Ok(self.identities.clone())
+        }
+    }
+
+    fn create_dummy_identity(user_account_id: i64) -> IdentityToCompare {
+        IdentityToCompare {
+            user_account_id,
+            last_name: "山田".to_string(),
+            first_name: "太郎".to_string(),
+            last_name_furigana: "ヤマダ".to_string(),
+            first_name_furigana: "タロウ".to_string(),
+            date_of_birth: NaiveDate::from_ymd_opt(1990, 10, 11).expect("failed to get Ok"),
+            prefecture: "東京都".to_string(),
+            city: "町田市".to_string(),
+            address_line1: "森野二丁目十二番地三号".to_string(),
+            address_line2: None,
+            telephone_number: "09012345678".to_string(),
+            bank_account_holder_name: None,
+        }
+    }
+
+    fn create_unrelated_identity(user_account_id: i64) -> IdentityToCompare {
+        IdentityToCompare {
+            user_account_id,
+            last_name: "佐藤".to_string(),
+            first_name: "花子".to_string(),
+            last_name_furigana: "サトウ".to_string(),
+            first_name_furigana: "ハナコ".to_string(),
+            date_of_birth: NaiveDate::from_ymd_opt(1985, 1, 2).expect("failed to get Ok"),
+            prefecture: "大阪府".to_string(),
+            city: "大阪市北区".to_string(),
+            address_line1: "梅田1-1-1".to_string(),
+            address_line2: Some("梅田ビル101".to_string()),
+            telephone_number: "08098765432".to_string(),
+            bank_account_holder_name: Some("サトウ　ハナコ".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_duplicate_identity_candidates_success() {
+        let req = create_dummy_identity(10);
+        // 電話番号のみ一致
+        let mut same_telephone_number = create_unrelated_identity(2);
+        same_telephone_number.telephone_number = req.telephone_number.clone();
+        // フリガナ、住所、生年月日が一致（住所は表記揺れあり）
+        let mut same_person = create_unrelated_identity(3);
+        same_person.last_name_furigana = "ヤマダ".to_string();
+        same_person.first_name_furigana = "タロウ".to_string();
+        same_person.date_of_birth = req.date_of_birth;
+        same_person.prefecture = "東京都".to_string();
+        same_person.city = "町田市".to_string();
+        same_person.address_line1 = "森野２－１２－３".to_string();
+        same_person.address_line2 = None;
+        // 生年月日のみ一致（候補としない）
+        let mut same_date_of_birth = create_unrelated_identity(4);
+        same_date_of_birth.date_of_birth = req.date_of_birth;
+        let op_mock = DuplicateIdentityCandidatesOperationMock {
+            identity_req_type: IdentityReqType::Create,
+            req: req.clone(),
+            identities: vec![
+                same_telephone_number,
+                same_person,
+                same_date_of_birth,
+                // 依頼者自身（候補としない）
+                create_dummy_identity(10),
+            ],
+        };
+
+        let result =
+            handle_duplicate_identity_candidates(IdentityReqType::Create, 10, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        let candidates = resp.1 .0;
+        assert_eq!(2, candidates.len());
+        assert_eq!(3, candidates[0].user_account_id);
+        assert_eq!(
+            MAX_SIMILAR_FURIGANA_SCORE + MAX_SIMILAR_ADDRESS_SCORE + SAME_DATE_OF_BIRTH_SCORE,
+            candidates[0].score
+        );
+        assert_eq!(
+            vec![
+                DuplicateReason {
+                    item: DuplicateItem::Furigana,
+                    similarity_in_percent: 100
+                },
+                DuplicateReason {
+                    item: DuplicateItem::Address,
+                    similarity_in_percent: 100
+                },
+                DuplicateReason {
+                    item: DuplicateItem::DateOfBirth,
+                    similarity_in_percent: 100
+                },
+            ],
+            candidates[0].reasons
+        );
+        assert_eq!(2, candidates[1].user_account_id);
+        assert_eq!(SAME_TELEPHONE_NUMBER_SCORE, candidates[1].score);
+    }
+
+    #[tokio::test]
+    async fn handle_duplicate_identity_candidates_success_bank_account_holder_name_matches() {
+        let req = create_dummy_identity(10);
+        let mut same_holder_name = create_unrelated_identity(5);
+        same_holder_name.bank_account_holder_name = Some("ヤマダ　タロウ".to_string());
+        let op_mock = DuplicateIdentityCandidatesOperationMock {
+            identity_req_type: IdentityReqType::Update,
+            req,
+            identities: vec![same_holder_name],
+        };
+
+        let result =
+            handle_duplicate_identity_candidates(IdentityReqType::Update, 10, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        let candidates = resp.1 .0;
+        assert_eq!(1, candidates.len());
+        assert_eq!(5, candidates[0].user_account_id);
+        assert_eq!(SAME_BANK_ACCOUNT_HOLDER_NAME_SCORE, candidates[0].score);
+        assert_eq!(
+            vec![DuplicateReason {
+                item: DuplicateItem::BankAccountHolderName,
+                similarity_in_percent: 100
+            }],
+            candidates[0].reasons
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_duplicate_identity_candidates_success_no_candidate_found() {
+        let op_mock = DuplicateIdentityCandidatesOperationMock {
+            identity_req_type: IdentityReqType::Create,
+            req: create_dummy_identity(10),
+            identities: vec![create_unrelated_identity(2)],
+        };
+
+        let result =
+            handle_duplicate_identity_candidates(IdentityReqType::Create, 10, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(Vec::<DuplicateIdentityCandidate>::new(), resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_duplicate_identity_candidates_fail_no_create_identity_req_found() {
+        let op_mock = DuplicateIdentityCandidatesOperationMock {
+            identity_req_type: IdentityReqType::Update,
+            req: create_dummy_identity(10),
+            identities: vec![],
+        };
+
+        let result =
+            handle_duplicate_identity_candidates(IdentityReqType::Create, 10, op_mock).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCreateIdentityReqDetailFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_duplicate_identity_candidates_fail_no_update_identity_req_found() {
+        let op_mock = DuplicateIdentityCandidatesOperationMock {
+            identity_req_type: IdentityReqType::Create,
+            req: create_dummy_identity(10),
+            identities: vec![],
+        };
+
+        let result =
+            handle_duplicate_identity_candidates(IdentityReqType::Update, 10, op_mock).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoUpdateIdentityReqDetailFound as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn normalize_address_unifies_notation() {
+        let mut identity1 = create_dummy_identity(1);
+        identity1.address_line1 = "森野二丁目十二番地三号".to_string();
+        identity1.address_line2 = Some("森野マンション ２０１".to_string());
+        let mut identity2 = create_dummy_identity(2);
+        identity2.address_line1 = "森野2-12-3".to_string();
+        identity2.address_line2 = Some("森野マンション201".to_string());
+        let mut identity3 = create_dummy_identity(3);
+        identity3.address_line1 = "森野２丁目１２番３号".to_string();
+        identity3.address_line2 = Some("森野マンション201".to_string());
+        let mut identity4 = create_dummy_identity(4);
+        identity4.address_line1 = "森野2の12の3".to_string();
+        identity4.address_line2 = Some("森野マンション201".to_string());
+
+        let expected = "東京都町田市森野2-12-3森野まんしょん201";
+        assert_eq!(expected, normalize_address(&identity1));
+        assert_eq!(expected, normalize_address(&identity2));
+        assert_eq!(expected, normalize_address(&identity3));
+        assert_eq!(expected, normalize_address(&identity4));
+    }
+
+    #[test]
+    fn convert_kanji_numerals_success() {
+        assert_eq!("3", convert_kanji_numerals("三"));
+        assert_eq!("10", convert_kanji_numerals("十"));
+        assert_eq!("12", convert_kanji_numerals("十二"));
+        assert_eq!("25", convert_kanji_numerals("二十五"));
+        assert_eq!("1234", convert_kanji_numerals("千二百三十四"));
+        assert_eq!("105", convert_kanji_numerals("一〇五"));
+        assert_eq!("森野2丁目", convert_kanji_numerals("森野二丁目"));
+    }
+
+    #[test]
+    fn normalize_furigana_unifies_notation() {
+        assert_eq!("きやのんたろう", normalize_furigana("キャノン", "タロウ"));
+        assert_eq!("きやのんたろう", normalize_furigana("キヤノン　タロウ", ""));
+        assert_eq!("きやのんたろう", normalize_furigana("ｷｬﾉﾝ ﾀﾛｳ", ""));
+    }
+
+    #[test]
+    fn select_similar_user_account_ids_returns_ids_in_descending_order_of_similarity() {
+        let target = normalize_furigana("ヤマダ", "タロウ");
+        let values = vec![
+            (1, normalize_furigana("サトウ", "ハナコ")),
+            (2, normalize_furigana("ヤマダ", "ジロウ")),
+            (3, normalize_furigana("ﾔﾏﾀﾞ", "ﾀﾛｳ")),
+            (4, normalize_furigana("ヤマダ", "タロウ")),
+        ];
+
+        let result = select_similar_user_account_ids(&target, values, 10);
+
+        assert_eq!(vec![3, 4, 2], result);
+    }
+
+    #[test]
+    fn select_similar_user_account_ids_drops_less_similar_ids_over_limit() {
+        let target = normalize_furigana("ヤマダ", "タロウ");
+        let values = vec![
+            (1, normalize_furigana("ヤマダ", "ジロウ")),
+            (2, normalize_furigana("ヤマダ", "タロウ")),
+            (3, normalize_furigana("ヤマタ", "タロウ")),
+        ];
+
+        let result = select_similar_user_account_ids(&target, values, 1);
+
+        assert_eq!(vec![2], result);
+    }
+
+    #[test]
+    fn merge_user_account_ids_removes_duplicates_and_req_user_account_id() {
+        let result = merge_user_account_ids(vec![vec![3, 10], vec![5, 3], vec![], vec![7, 5]], 10);
+
+        assert_eq!(vec![3, 5, 7], result);
+    }
+
+    #[test]
+    fn calculate_similarity_in_percent_success() {
+        assert_eq!(
+            100,
+            calculate_similarity_in_percent("やまだたろう", "やまだたろう")
+        );
+        assert_eq!(
+            83,
+            calculate_similarity_in_percent("やまだたろう", "やまだじろう")
+        );
+        assert_eq!(0, calculate_similarity_in_percent("あいう", "えおか"));
+        assert_eq!(0, calculate_similarity_in_percent("", ""));
+        assert_eq!(0, calculate_similarity_in_percent("あ", ""));
+    }
+}
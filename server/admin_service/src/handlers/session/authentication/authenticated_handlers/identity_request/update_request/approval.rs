@@ -24,7 +24,7 @@ use tracing::{error, info};
 use crate::{
     err::unexpected_err_resp,
     handlers::session::authentication::authenticated_handlers::{
-        admin::Admin,
+        admin::Admin, review_claim::ensure_not_claimed_by_other_admin,
         user_account_operation::find_user_account_model_by_user_account_id_with_shared_lock,
     },
 };
@@ -151,6 +151,15 @@ impl UpdateIdentityReqApprovalOperation for UpdateIdentityReqApprovalOperationIm
 
                     let req = find_update_identity_req_model_by_user_account_id_with_exclusive_lock(txn, user_account_id).await?;
 
+                    // 他の管理者が確認作業中の依頼は承認、拒否できないようにする
+                    ensure_not_claimed_by_other_admin(
+                        req.claimed_by.as_deref(),
+                        req.claimed_at,
+                        approver_email_address.as_str(),
+                        approved_time,
+                    )
+                    .map_err(|e| ErrRespStruct { err_resp: e })?;
+
                     let identity_model = generate_identity_active_model(req.clone());
                     let _  = identity_model.update(txn).await.map_err(|e| {
                         error!(
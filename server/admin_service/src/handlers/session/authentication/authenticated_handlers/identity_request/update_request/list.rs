@@ -1,12 +1,12 @@
 // Copyright 2021 Ken Miura
 
 use axum::{async_trait, Json};
-use chrono::{DateTime, FixedOffset};
-use common::{ErrResp, RespResult};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::{ErrResp, RespResult, JAPANESE_TIME_ZONE};
 
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
-use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryOrder};
+use entity::sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
 use entity::update_identity_req;
 use serde::Serialize;
 use tracing::error;
@@ -16,16 +16,26 @@ use crate::handlers::session::authentication::authenticated_handlers::admin::Adm
 use crate::handlers::session::authentication::authenticated_handlers::pagination::{
     validate_page_size, Pagination,
 };
+use crate::handlers::session::authentication::authenticated_handlers::review_claim::{
+    ClaimStatusQuery, ReviewClaimFilter,
+};
 
 pub(crate) async fn get_update_identity_requests(
-    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
     pagination: Query<Pagination>,
+    claim_status_query: Query<ClaimStatusQuery>,
     State(pool): State<DatabaseConnection>,
 ) -> RespResult<Vec<UpdateIdentityReqItem>> {
     let pagination = pagination.0;
     validate_page_size(pagination.per_page)?;
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let review_claim_filter = ReviewClaimFilter::new(
+        claim_status_query.0,
+        admin_info.email_address,
+        current_date_time,
+    );
     let op = UpdateIdentityRequestItemsOperationImpl { pool };
-    get_update_identity_request_items(pagination, op).await
+    get_update_identity_request_items(pagination, review_claim_filter, op).await
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -37,9 +47,12 @@ pub(crate) struct UpdateIdentityReqItem {
 
 async fn get_update_identity_request_items(
     pagination: Pagination,
+    review_claim_filter: Option<ReviewClaimFilter>,
     op: impl UpdateIdentityRequestItemsOperation,
 ) -> RespResult<Vec<UpdateIdentityReqItem>> {
-    let items = op.get_items(pagination.page, pagination.per_page).await?;
+    let items = op
+        .get_items(pagination.page, pagination.per_page, review_claim_filter)
+        .await?;
     Ok((StatusCode::OK, Json(items)))
 }
 
@@ -49,6 +62,7 @@ trait UpdateIdentityRequestItemsOperation {
         &self,
         page: u64,
         page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
     ) -> Result<Vec<UpdateIdentityReqItem>, ErrResp>;
 }
 
@@ -62,8 +76,16 @@ impl UpdateIdentityRequestItemsOperation for UpdateIdentityRequestItemsOperation
         &self,
         page: u64,
         page_size: u64,
+        review_claim_filter: Option<ReviewClaimFilter>,
     ) -> Result<Vec<UpdateIdentityReqItem>, ErrResp> {
-        let items = update_identity_req::Entity::find()
+        let mut query = update_identity_req::Entity::find();
+        if let Some(review_claim_filter) = review_claim_filter {
+            query = query.filter(review_claim_filter.create_condition(
+                update_identity_req::Column::ClaimedBy,
+                update_identity_req::Column::ClaimedAt,
+            ));
+        }
+        let items = query
             .order_by_asc(update_identity_req::Column::RequestedAt)
             .paginate(&self.pool, page_size)
             .fetch_page(page)
@@ -109,6 +131,7 @@ mod tests {
             &self,
             page: u64,
             page_size: u64,
+            _review_claim_filter: Option<ReviewClaimFilter>,
         ) -> Result<Vec<UpdateIdentityReqItem>, ErrResp> {
             let items = self.items.clone();
             let length = items.len() as u64;
@@ -142,7 +165,7 @@ mod tests {
             per_page: 3,
         };
 
-        let result = get_update_identity_request_items(pagination, op_mock).await;
+        let result = get_update_identity_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -160,7 +183,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_update_identity_request_items(pagination, op_mock).await;
+        let result = get_update_identity_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -178,7 +201,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_update_identity_request_items(pagination, op_mock).await;
+        let result = get_update_identity_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
@@ -197,7 +220,7 @@ mod tests {
             per_page: 2,
         };
 
-        let result = get_update_identity_request_items(pagination, op_mock).await;
+        let result = get_update_identity_request_items(pagination, None, op_mock).await;
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
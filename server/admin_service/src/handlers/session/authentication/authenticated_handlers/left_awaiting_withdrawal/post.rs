@@ -4,8 +4,12 @@ use async_session::async_trait;
 use axum::{extract::State, http::StatusCode, Json};
 use chrono::{DateTime, FixedOffset, Utc};
 use common::{
-    util::validator::email_address_validator::validate_email_address, ApiError, ErrResp,
-    ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
+    payment_event::{
+        insert_payment_event, PAYMENT_STATE_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL,
+    },
+    util::validator::email_address_validator::validate_email_address,
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
 };
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, DatabaseTransaction, Set, TransactionError,
@@ -103,6 +107,16 @@ impl LeftAwaitingWithdrawalOperation for LeftAwaitingWithdrawalOperationImpl {
                         }
                     })?;
 
+                    insert_payment_event(
+                        consultation_id,
+                        Some(PAYMENT_STATE_AWAITING_WITHDRAWAL),
+                        PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL,
+                        admin_email_address.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+
                     insert_left_awaiting_withdrawal(
                         aw,
                         admin_email_address,
@@ -143,6 +157,9 @@ async fn insert_left_awaiting_withdrawal(
         consultant_id: Set(aw.consultant_id),
         meeting_at: Set(aw.meeting_at),
         fee_per_hour_in_yen: Set(aw.fee_per_hour_in_yen),
+        coupon_id: Set(aw.coupon_id),
+        discount_in_yen: Set(aw.discount_in_yen),
+        discount_borne_by: Set(aw.discount_borne_by.clone()),
         sender_name: Set(aw.sender_name.clone()),
         confirmed_by: Set(confirmed_by.clone()),
         created_at: Set(created_at),
@@ -4,8 +4,11 @@ use async_session::async_trait;
 use axum::{extract::State, http::StatusCode, Json};
 use chrono::{DateTime, FixedOffset, Utc};
 use common::{
-    util::validator::email_address_validator::validate_email_address, ApiError, ErrResp,
-    ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
+    payment_event::{
+        insert_payment_event, PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_NEGLECTED_PAYMENT,
+    },
+    util::validator::email_address_validator::validate_email_address,
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
 };
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, DatabaseTransaction, Set, TransactionError,
@@ -100,6 +103,16 @@ impl NeglectedPaymentOperation for NeglectedPaymentOperationImpl {
                         }
                     })?;
 
+                    insert_payment_event(
+                        consultation_id,
+                        Some(PAYMENT_STATE_AWAITING_PAYMENT),
+                        PAYMENT_STATE_NEGLECTED_PAYMENT,
+                        admin_email_address.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+
                     insert_neglected_payment(ap, admin_email_address, current_date_time, txn)
                         .await?;
 
@@ -135,6 +148,9 @@ async fn insert_neglected_payment(
         consultant_id: Set(ap.consultant_id),
         meeting_at: Set(ap.meeting_at),
         fee_per_hour_in_yen: Set(ap.fee_per_hour_in_yen),
+        coupon_id: Set(ap.coupon_id),
+        discount_in_yen: Set(ap.discount_in_yen),
+        discount_borne_by: Set(ap.discount_borne_by.clone()),
         neglect_confirmed_by: Set(neglect_confirmed_by.clone()),
         created_at: Set(created_at),
     };
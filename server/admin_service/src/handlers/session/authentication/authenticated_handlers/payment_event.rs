@@ -0,0 +1,4 @@
+// Copyright 2023 Ken Miura
+
+pub(crate) mod payment_state_inconsistencies;
+pub(crate) mod payment_timeline;
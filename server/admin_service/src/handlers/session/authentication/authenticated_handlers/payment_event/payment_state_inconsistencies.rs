@@ -0,0 +1,323 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use common::{
+    payment_event::{
+        PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL, PAYMENT_STATE_NEGLECTED_PAYMENT,
+        PAYMENT_STATE_RECEIPT_OF_CONSULTATION, PAYMENT_STATE_REFUNDED_PAYMENT,
+    },
+    ErrResp, RespResult,
+};
+use entity::sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, Statement};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tracing::error;
+
+use crate::{
+    err::unexpected_err_resp,
+    handlers::session::authentication::authenticated_handlers::{
+        admin::Admin, pagination::Pagination,
+    },
+};
+
+const VALID_PAGE_SIZE: u64 = 20;
+
+/// 支払いに関する状態のテーブルのうち、相談（consultation）に対してちょうど一つだけ存在していないもの（どの状態のテーブルにも存在しない、または複数の状態のテーブルに存在する）を抽出するSQL
+///
+/// 状態のテーブル名と状態を表す文字列は同じ値を利用している
+static FIND_INCONSISTENT_PAYMENT_STATES_SQL: Lazy<String> = Lazy::new(|| {
+    let states = [
+        PAYMENT_STATE_AWAITING_PAYMENT,
+        PAYMENT_STATE_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_NEGLECTED_PAYMENT,
+        PAYMENT_STATE_RECEIPT_OF_CONSULTATION,
+        PAYMENT_STATE_REFUNDED_PAYMENT,
+    ];
+    let union_of_states = states
+        .iter()
+        .map(|s| {
+            format!(
+                "SELECT consultation_id, '{}' AS state FROM ccs_schema.{}",
+                s, s
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" UNION ALL ");
+    format!(
+        "SELECT c.consultation_id, COALESCE(string_agg(s.state, ',' ORDER BY s.state), '') AS states \
+         FROM ccs_schema.consultation c LEFT JOIN ({}) s ON c.consultation_id = s.consultation_id \
+         GROUP BY c.consultation_id HAVING COUNT(s.consultation_id) <> 1 \
+         ORDER BY c.consultation_id DESC LIMIT $1 OFFSET $2",
+        union_of_states
+    )
+});
+
+pub(crate) async fn get_payment_state_inconsistencies(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<Pagination>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<PaymentStateInconsistenciesResult> {
+    let op = PaymentStateInconsistenciesOperationImpl { pool };
+    handle_payment_state_inconsistencies(query.page, query.per_page, op).await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct PaymentStateInconsistenciesResult {
+    payment_state_inconsistencies: Vec<PaymentStateInconsistency>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+struct PaymentStateInconsistency {
+    consultation_id: i64,
+    states: Vec<String>, // 空の場合、どの状態のテーブルにも存在しないことを示す
+}
+
+#[async_trait]
+trait PaymentStateInconsistenciesOperation {
+    /// consultation_idの降順で返す
+    async fn get_payment_state_inconsistencies(
+        &self,
+        page: u64,
+        per_page: u64,
+    ) -> Result<Vec<PaymentStateInconsistency>, ErrResp>;
+}
+
+struct PaymentStateInconsistenciesOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl PaymentStateInconsistenciesOperation for PaymentStateInconsistenciesOperationImpl {
+    async fn get_payment_state_inconsistencies(
+        &self,
+        page: u64,
+        per_page: u64,
+    ) -> Result<Vec<PaymentStateInconsistency>, ErrResp> {
+        let limit = per_page as i64;
+        let offset = (page * per_page) as i64;
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            FIND_INCONSISTENT_PAYMENT_STATES_SQL.as_str(),
+            [limit.into(), offset.into()],
+        );
+        let rows = self.pool.query_all(stmt).await.map_err(|e| {
+            error!(
+                "failed to find payment state inconsistencies (page: {}, per_page: {}): {}",
+                page, per_page, e
+            );
+            unexpected_err_resp()
+        })?;
+        rows.into_iter()
+            .map(|row| {
+                let consultation_id: i64 = row.try_get("", "consultation_id").map_err(|e| {
+                    error!("failed to get consultation_id: {}", e);
+                    unexpected_err_resp()
+                })?;
+                let states: String = row.try_get("", "states").map_err(|e| {
+                    error!("failed to get states: {}", e);
+                    unexpected_err_resp()
+                })?;
+                Ok(PaymentStateInconsistency {
+                    consultation_id,
+                    states: split_states(states.as_str()),
+                })
+            })
+            .collect()
+    }
+}
+
+fn split_states(states: &str) -> Vec<String> {
+    states
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+async fn handle_payment_state_inconsistencies(
+    page: u64,
+    per_page: u64,
+    op: impl PaymentStateInconsistenciesOperation,
+) -> RespResult<PaymentStateInconsistenciesResult> {
+    if per_page > VALID_PAGE_SIZE {
+        error!("invalid per_page ({})", per_page);
+        return Err(unexpected_err_resp());
+    };
+
+    let payment_state_inconsistencies =
+        op.get_payment_state_inconsistencies(page, per_page).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PaymentStateInconsistenciesResult {
+            payment_state_inconsistencies,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::err::Code;
+
+    use super::*;
+
+    struct PaymentStateInconsistenciesOperationMock {
+        page: u64,
+        per_page: u64,
+        payment_state_inconsistencies: Vec<PaymentStateInconsistency>,
+    }
+
+    #[async_trait]
+    impl PaymentStateInconsistenciesOperation for PaymentStateInconsistenciesOperationMock {
+        async fn get_payment_state_inconsistencies(
+            &self,
+            page: u64,
+            per_page: u64,
+        ) -> Result<Vec<PaymentStateInconsistency>, ErrResp> {
+            assert_eq!(self.page, page);
+            assert_eq!(self.per_page, per_page);
+            let mut inconsistencies = self.payment_state_inconsistencies.clone();
+            inconsistencies.sort_by(|a, b| b.consultation_id.cmp(&a.consultation_id));
+            let length = inconsistencies.len();
+            let page = page as usize;
+            let per_page = per_page as usize;
+            let start_index = page * per_page;
+            let num = if length > per_page { per_page } else { length };
+            let end_index = start_index + num;
+            Ok(if length <= start_index {
+                vec![]
+            } else {
+                inconsistencies[start_index..end_index].to_vec()
+            })
+        }
+    }
+
+    fn create_dummy_inconsistencies() -> Vec<PaymentStateInconsistency> {
+        vec![
+            PaymentStateInconsistency {
+                consultation_id: 3,
+                states: vec![],
+            },
+            PaymentStateInconsistency {
+                consultation_id: 8,
+                states: vec![
+                    PAYMENT_STATE_AWAITING_PAYMENT.to_string(),
+                    PAYMENT_STATE_AWAITING_WITHDRAWAL.to_string(),
+                ],
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_handle_payment_state_inconsistencies_success_case1() {
+        let page = 0;
+        let per_page = VALID_PAGE_SIZE;
+        let op = PaymentStateInconsistenciesOperationMock {
+            page,
+            per_page,
+            payment_state_inconsistencies: vec![],
+        };
+
+        let result = handle_payment_state_inconsistencies(page, per_page, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            PaymentStateInconsistenciesResult {
+                payment_state_inconsistencies: vec![]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_payment_state_inconsistencies_success_case2() {
+        let page = 0;
+        let per_page = VALID_PAGE_SIZE;
+        let inconsistencies = create_dummy_inconsistencies();
+        let op = PaymentStateInconsistenciesOperationMock {
+            page,
+            per_page,
+            payment_state_inconsistencies: inconsistencies.clone(),
+        };
+
+        let result = handle_payment_state_inconsistencies(page, per_page, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            PaymentStateInconsistenciesResult {
+                payment_state_inconsistencies: vec![
+                    inconsistencies[1].clone(),
+                    inconsistencies[0].clone()
+                ]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_payment_state_inconsistencies_success_case3() {
+        let page = 1;
+        let per_page = 1;
+        let inconsistencies = create_dummy_inconsistencies();
+        let op = PaymentStateInconsistenciesOperationMock {
+            page,
+            per_page,
+            payment_state_inconsistencies: inconsistencies.clone(),
+        };
+
+        let result = handle_payment_state_inconsistencies(page, per_page, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            PaymentStateInconsistenciesResult {
+                payment_state_inconsistencies: vec![inconsistencies[0].clone()]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_payment_state_inconsistencies_fail_case1() {
+        let page = 0;
+        let per_page = VALID_PAGE_SIZE + 1;
+        let op = PaymentStateInconsistenciesOperationMock {
+            page,
+            per_page,
+            payment_state_inconsistencies: create_dummy_inconsistencies(),
+        };
+
+        let result = handle_payment_state_inconsistencies(page, per_page, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, resp.0);
+        assert_eq!(Code::UnexpectedErr as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn test_split_states() {
+        assert_eq!(Vec::<String>::new(), split_states(""));
+        assert_eq!(
+            vec![PAYMENT_STATE_REFUNDED_PAYMENT.to_string()],
+            split_states(PAYMENT_STATE_REFUNDED_PAYMENT)
+        );
+        assert_eq!(
+            vec![
+                PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL.to_string(),
+                PAYMENT_STATE_NEGLECTED_PAYMENT.to_string(),
+                PAYMENT_STATE_RECEIPT_OF_CONSULTATION.to_string()
+            ],
+            split_states("left_awaiting_withdrawal,neglected_payment,receipt_of_consultation")
+        );
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2023 Ken Miura
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::{async_trait, Json};
+use common::{ErrResp, RespResult};
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+
+use super::super::admin::Admin;
+use super::super::{
+    convert_date_time_to_rfc3339_string, validate_consultation_id_is_positive, ConsultationIdQuery,
+};
+
+pub(crate) async fn get_payment_timeline(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    query: Query<ConsultationIdQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<PaymentTimelineResult> {
+    let query = query.0;
+    let op = PaymentTimelineOperationImpl { pool };
+    handle_payment_timeline(query.consultation_id, op).await
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PaymentTimelineResult {
+    payment_events: Vec<PaymentEvent>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+struct PaymentEvent {
+    payment_event_id: i64,
+    consultation_id: i64,
+    from_state: Option<String>,
+    to_state: String,
+    performed_by: String,
+    created_at: String, // RFC 3339形式の文字列
+}
+
+async fn handle_payment_timeline(
+    consultation_id: i64,
+    op: impl PaymentTimelineOperation,
+) -> RespResult<PaymentTimelineResult> {
+    validate_consultation_id_is_positive(consultation_id)?;
+
+    let payment_events = op
+        .get_payment_events_by_consultation_id(consultation_id)
+        .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PaymentTimelineResult { payment_events }),
+    ))
+}
+
+#[async_trait]
+trait PaymentTimelineOperation {
+    /// 状態遷移が発生した順（古い順）に履歴を返す
+    async fn get_payment_events_by_consultation_id(
+        &self,
+        consultation_id: i64,
+    ) -> Result<Vec<PaymentEvent>, ErrResp>;
+}
+
+struct PaymentTimelineOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl PaymentTimelineOperation for PaymentTimelineOperationImpl {
+    async fn get_payment_events_by_consultation_id(
+        &self,
+        consultation_id: i64,
+    ) -> Result<Vec<PaymentEvent>, ErrResp> {
+        // 同一トランザクション内で生成された履歴はcreated_atが等しくなるため、payment_event_idで順序を確定させる
+        let models = entity::payment_event::Entity::find()
+            .filter(entity::payment_event::Column::ConsultationId.eq(consultation_id))
+            .order_by_asc(entity::payment_event::Column::CreatedAt)
+            .order_by_asc(entity::payment_event::Column::PaymentEventId)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to filter payment_event (consultation_id: {}): {}",
+                    consultation_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| PaymentEvent {
+                payment_event_id: m.payment_event_id,
+                consultation_id: m.consultation_id,
+                from_state: m.from_state,
+                to_state: m.to_state,
+                performed_by: m.performed_by,
+                created_at: convert_date_time_to_rfc3339_string(m.created_at),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+    use axum::http::StatusCode;
+    use chrono::TimeZone;
+    use common::payment_event::{
+        PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_RECEIPT_OF_CONSULTATION,
+    };
+    use common::{ErrResp, JAPANESE_TIME_ZONE};
+
+    use crate::err::Code;
+
+    use super::*;
+
+    struct PaymentTimelineOperationMock {
+        consultation_id: i64,
+        payment_events: Vec<PaymentEvent>,
+    }
+
+    #[async_trait]
+    impl PaymentTimelineOperation for PaymentTimelineOperationMock {
+        async fn get_payment_events_by_consultation_id(
+            &self,
+            consultation_id: i64,
+        ) -> Result<Vec<PaymentEvent>, ErrResp> {
+            if self.consultation_id != consultation_id {
+                return Ok(vec![]);
+            }
+            Ok(self.payment_events.clone())
+        }
+    }
+
+    fn create_dummy_payment_events(consultation_id: i64) -> Vec<PaymentEvent> {
+        let accepted_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 1, 12, 0, 0)
+            .unwrap();
+        let paid_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 3, 10, 30, 0)
+            .unwrap();
+        let withdrawn_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 20, 15, 0, 0)
+            .unwrap();
+        vec![
+            PaymentEvent {
+                payment_event_id: 10,
+                consultation_id,
+                from_state: None,
+                to_state: PAYMENT_STATE_AWAITING_PAYMENT.to_string(),
+                performed_by: "consultant@test.com".to_string(),
+                created_at: convert_date_time_to_rfc3339_string(accepted_at),
+            },
+            PaymentEvent {
+                payment_event_id: 25,
+                consultation_id,
+                from_state: Some(PAYMENT_STATE_AWAITING_PAYMENT.to_string()),
+                to_state: PAYMENT_STATE_AWAITING_WITHDRAWAL.to_string(),
+                performed_by: "admin@test.com".to_string(),
+                created_at: convert_date_time_to_rfc3339_string(paid_at),
+            },
+            PaymentEvent {
+                payment_event_id: 71,
+                consultation_id,
+                from_state: Some(PAYMENT_STATE_AWAITING_WITHDRAWAL.to_string()),
+                to_state: PAYMENT_STATE_RECEIPT_OF_CONSULTATION.to_string(),
+                performed_by: "admin@test.com".to_string(),
+                created_at: convert_date_time_to_rfc3339_string(withdrawn_at),
+            },
+        ]
+    }
+
+    #[tokio::test]
+
+    async fn handle_payment_timeline_success() {
+        let consultation_id = 1234;
+        let payment_events = create_dummy_payment_events(consultation_id);
+        let op_mock = PaymentTimelineOperationMock {
+            consultation_id,
+            payment_events: payment_events.clone(),
+        };
+
+        let result = handle_payment_timeline(consultation_id, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(resp.0, StatusCode::OK);
+        assert_eq!(resp.1 .0.payment_events, payment_events);
+    }
+
+    #[tokio::test]
+
+    async fn handle_payment_timeline_success_no_payment_event_found() {
+        let consultation_id = 1234;
+        let op_mock = PaymentTimelineOperationMock {
+            consultation_id,
+            payment_events: create_dummy_payment_events(consultation_id),
+        };
+        let dummy_id = consultation_id + 510;
+
+        let result = handle_payment_timeline(dummy_id, op_mock).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(resp.0, StatusCode::OK);
+        assert_eq!(resp.1 .0.payment_events, vec![]);
+    }
+
+    #[tokio::test]
+
+    async fn handle_payment_timeline_fail() {
+        let consultation_id = -1;
+        let op_mock = PaymentTimelineOperationMock {
+            consultation_id,
+            payment_events: create_dummy_payment_events(consultation_id),
+        };
+
+        let result = handle_payment_timeline(consultation_id, op_mock).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(resp.0, StatusCode::BAD_REQUEST);
+        assert_eq!(resp.1 .0.code, Code::ConsultationIdIsNotPositive as u32);
+    }
+}
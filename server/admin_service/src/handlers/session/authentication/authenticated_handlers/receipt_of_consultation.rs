@@ -13,6 +13,8 @@ struct ReceiptOfConsultation {
     consultant_id: i64,
     meeting_at: String, // RFC 3339形式の文字列,
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+    discount_borne_by: Option<String>,
     platform_fee_rate_in_percentage: String,
     transfer_fee_in_yen: i32,
     reward: i32,
@@ -76,6 +76,8 @@ impl ReceiptsOfConsultationOperation for ReceiptsOfConsultationOperationImpl {
                 consultant_id: m.consultant_id,
                 meeting_at: convert_date_time_to_rfc3339_string(m.meeting_at),
                 fee_per_hour_in_yen: m.fee_per_hour_in_yen,
+                discount_in_yen: m.discount_in_yen,
+                discount_borne_by: m.discount_borne_by,
                 platform_fee_rate_in_percentage: m.platform_fee_rate_in_percentage,
                 transfer_fee_in_yen: m.transfer_fee_in_yen,
                 reward: m.reward,
@@ -204,9 +206,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -258,9 +263,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -287,9 +295,12 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "スズキ".to_string(),
                 "ジロウ".to_string(),
@@ -341,9 +352,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -370,9 +384,12 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "スズキ".to_string(),
                 "ジロウ".to_string(),
@@ -424,9 +441,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -453,9 +473,12 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "スズキ".to_string(),
                 "ジロウ".to_string(),
@@ -507,9 +530,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -536,9 +562,12 @@ mod tests {
             consultant_id: 6,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at2),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "スズキ".to_string(),
                 "ジロウ".to_string(),
@@ -590,9 +619,12 @@ mod tests {
             consultant_id: 3,
             meeting_at: convert_date_time_to_rfc3339_string(meeting_at1),
             fee_per_hour_in_yen: 4000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: *TRANSFER_FEE_IN_YEN,
-            reward: calculate_reward(4000, "50.0", *TRANSFER_FEE_IN_YEN).expect("failed to get Ok"),
+            reward: calculate_reward(4000, 0, None, "50.0", *TRANSFER_FEE_IN_YEN)
+                .expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
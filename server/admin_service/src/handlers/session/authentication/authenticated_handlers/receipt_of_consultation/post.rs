@@ -4,8 +4,12 @@ use async_session::async_trait;
 use axum::{extract::State, http::StatusCode, Json};
 use chrono::{DateTime, FixedOffset, Utc};
 use common::{
-    util::validator::email_address_validator::validate_email_address, ApiError, ErrResp,
-    ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
+    payment_event::{
+        insert_payment_event, PAYMENT_STATE_AWAITING_WITHDRAWAL,
+        PAYMENT_STATE_RECEIPT_OF_CONSULTATION,
+    },
+    util::validator::email_address_validator::validate_email_address,
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
 };
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, Set, TransactionError,
@@ -128,13 +132,17 @@ impl ReceiptOfConsultationOperation for ReceiptOfConsultationOperationImpl {
 
                     let reward = calculate_reward(
                         aw.fee_per_hour_in_yen,
+                        aw.discount_in_yen,
+                        aw.discount_borne_by.as_deref(),
                         &platform_fee_rate_in_percentage,
                         transfer_fee_in_yen,
                     )
                     .map_err(|e| {
                         error!(
-                            "failed calculate_reward ({}, {}, {})",
+                            "failed calculate_reward ({}, {}, {:?}, {}, {})",
                             aw.fee_per_hour_in_yen,
+                            aw.discount_in_yen,
+                            aw.discount_borne_by,
                             &platform_fee_rate_in_percentage,
                             transfer_fee_in_yen
                         );
@@ -146,6 +154,16 @@ impl ReceiptOfConsultationOperation for ReceiptOfConsultationOperationImpl {
                         platform_fee_rate_in_percentage,
                     };
 
+                    insert_payment_event(
+                        consultation_id,
+                        Some(PAYMENT_STATE_AWAITING_WITHDRAWAL),
+                        PAYMENT_STATE_RECEIPT_OF_CONSULTATION,
+                        admin_email_address.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+
                     insert_receipt_of_consultation(
                         aw,
                         ba,
@@ -216,6 +234,9 @@ async fn insert_receipt_of_consultation(
         consultant_id: Set(aw.consultant_id),
         meeting_at: Set(aw.meeting_at),
         fee_per_hour_in_yen: Set(aw.fee_per_hour_in_yen),
+        coupon_id: Set(aw.coupon_id),
+        discount_in_yen: Set(aw.discount_in_yen),
+        discount_borne_by: Set(aw.discount_borne_by.clone()),
         platform_fee_rate_in_percentage: Set(fee_related_info
             .platform_fee_rate_in_percentage
             .clone()),
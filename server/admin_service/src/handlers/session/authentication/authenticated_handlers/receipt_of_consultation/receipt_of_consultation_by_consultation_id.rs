@@ -84,6 +84,8 @@ impl ReceiptOfConsultationOperation for ReceiptOfConsultationOperationImpl {
                 .with_timezone(&(*JAPANESE_TIME_ZONE))
                 .to_rfc3339(),
             fee_per_hour_in_yen: m.fee_per_hour_in_yen,
+            discount_in_yen: m.discount_in_yen,
+            discount_borne_by: m.discount_borne_by,
             platform_fee_rate_in_percentage: m.platform_fee_rate_in_percentage,
             transfer_fee_in_yen: m.transfer_fee_in_yen,
             reward: m.reward,
@@ -141,9 +143,11 @@ mod tests {
             consultant_id: 68,
             meeting_at: "2023-04-13T14:00:00.0000+09:00 ".to_string(),
             fee_per_hour_in_yen: 5000,
+            discount_in_yen: 0,
+            discount_borne_by: None,
             platform_fee_rate_in_percentage: "50.0".to_string(),
             transfer_fee_in_yen: 250,
-            reward: calculate_reward(5000, "50.0", 250).expect("failed to get Ok"),
+            reward: calculate_reward(5000, 0, None, "50.0", 250).expect("failed to get Ok"),
             sender_name: generate_sender_name(
                 "タナカ".to_string(),
                 "タロウ".to_string(),
@@ -4,8 +4,12 @@ use async_session::async_trait;
 use axum::{extract::State, http::StatusCode, Json};
 use chrono::{DateTime, FixedOffset, Utc};
 use common::{
-    util::validator::email_address_validator::validate_email_address, ApiError, ErrResp,
-    ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
+    payment::{execute_card_payment_refund, CardPaymentRefund, PaymentGatewayClient},
+    payment_event::{
+        insert_payment_event, PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_REFUNDED_PAYMENT,
+    },
+    util::validator::email_address_validator::validate_email_address,
+    ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE,
 };
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, DatabaseTransaction, Set, TransactionError,
@@ -18,8 +22,10 @@ use crate::{
     err::{unexpected_err_resp, Code},
     handlers::session::authentication::authenticated_handlers::{
         admin::Admin, delete_awaiting_payment, find_awaiting_payment_with_exclusive_lock,
-        find_identity_by_user_account_id_in_transaction, generate_sender_name,
-        validate_consultation_id_is_positive, ConsultationIdBody, TRANSFER_FEE_IN_YEN,
+        find_identity_by_user_account_id_in_transaction,
+        find_paid_card_payment_with_exclusive_lock, generate_sender_name,
+        record_refund_request_of_card_payment, validate_consultation_id_is_positive,
+        ConsultationIdBody, TRANSFER_FEE_IN_YEN,
     },
 };
 
@@ -28,11 +34,15 @@ const REASON: &str = "管理者が相談開始時刻までに入金を確認出
 pub(crate) async fn post_refund_from_awaiting_payment(
     Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
     State(pool): State<DatabaseConnection>,
+    State(payment_gateway_client): State<PaymentGatewayClient>,
     Json(req): Json<ConsultationIdBody>,
 ) -> RespResult<RefundFromAwaitingPaymentResult> {
     let consultation_id = req.consultation_id;
     let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
-    let op = RefundFromAwaitingPaymentOperationImpl { pool };
+    let op = RefundFromAwaitingPaymentOperationImpl {
+        pool,
+        payment_gateway_client,
+    };
     handle_refund_from_awaiting_payment(
         consultation_id,
         admin_info.email_address,
@@ -84,6 +94,7 @@ trait RefundFromAwaitingPaymentOperation {
 
 struct RefundFromAwaitingPaymentOperationImpl {
     pool: DatabaseConnection,
+    payment_gateway_client: PaymentGatewayClient,
 }
 
 #[async_trait]
@@ -96,8 +107,9 @@ impl RefundFromAwaitingPaymentOperation for RefundFromAwaitingPaymentOperationIm
         reason: String,
         transfer_fee_in_yen: i32,
     ) -> Result<(), ErrResp> {
-        self.pool
-            .transaction::<_, (), ErrRespStruct>(|txn| {
+        let card_payment_refund = self
+            .pool
+            .transaction::<_, Option<CardPaymentRefund>, ErrRespStruct>(|txn| {
                 Box::pin(async move {
                     let ap_option =
                         find_awaiting_payment_with_exclusive_lock(consultation_id, txn).await?;
@@ -137,12 +149,38 @@ impl RefundFromAwaitingPaymentOperation for RefundFromAwaitingPaymentOperationIm
                             }
                         })?;
 
+                    // クレジットカードで支払われている場合、決済代行サービスを通じて返金するため振込手数料はかからない
+                    let card_payment =
+                        find_paid_card_payment_with_exclusive_lock(consultation_id, txn).await?;
+                    let transfer_fee_in_yen = if card_payment.is_some() {
+                        0
+                    } else {
+                        transfer_fee_in_yen
+                    };
+
+                    insert_payment_event(
+                        consultation_id,
+                        Some(PAYMENT_STATE_AWAITING_PAYMENT),
+                        PAYMENT_STATE_REFUNDED_PAYMENT,
+                        admin_email_address.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+
                     insert_refunded_payment(ap, sender_name, admin_email_address, current_date_time, reason, transfer_fee_in_yen, txn)
                         .await?;
 
                     delete_awaiting_payment(consultation_id, txn).await?;
 
-                    Ok(())
+                    let card_payment_refund = match card_payment {
+                        Some(card_payment) => {
+                            Some(record_refund_request_of_card_payment(card_payment, txn).await?)
+                        }
+                        None => None,
+                    };
+
+                    Ok(card_payment_refund)
                 })
             })
             .await
@@ -159,6 +197,17 @@ impl RefundFromAwaitingPaymentOperation for RefundFromAwaitingPaymentOperationIm
                     err_resp_struct.err_resp
                 }
             })?;
+        // 返金する状態への遷移をコミットした後に決済代行サービスへ返金を依頼する。
+        // 返金依頼に失敗した場合でも、記録した冪等キーを使って二重に返金されることなく再試行できる。
+        if let Some(card_payment_refund) = card_payment_refund {
+            execute_card_payment_refund(
+                &card_payment_refund,
+                current_date_time,
+                &self.payment_gateway_client,
+                &self.pool,
+            )
+            .await?;
+        }
         Ok(())
     }
 }
@@ -178,6 +227,9 @@ async fn insert_refunded_payment(
         consultant_id: Set(ap.consultant_id),
         meeting_at: Set(ap.meeting_at),
         fee_per_hour_in_yen: Set(ap.fee_per_hour_in_yen),
+        coupon_id: Set(ap.coupon_id),
+        discount_in_yen: Set(ap.discount_in_yen),
+        discount_borne_by: Set(ap.discount_borne_by.clone()),
         transfer_fee_in_yen: Set(transfer_fee_in_yen),
         sender_name: Set(sender_name),
         reason: Set(reason.clone()),
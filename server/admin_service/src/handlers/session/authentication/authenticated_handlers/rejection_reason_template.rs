@@ -0,0 +1,172 @@
+// Copyright 2023 Ken Miura
+
+//! 本人確認、職務経歴確認の依頼を拒否する際に利用する拒否理由のテンプレートを扱うモジュール
+
+pub(crate) mod list;
+pub(crate) mod set_rejection_reason_template_req;
+
+use axum::{http::StatusCode, Json};
+use common::{ApiError, ErrResp};
+use entity::sea_orm::{DatabaseConnection, EntityTrait};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+
+use super::reason_validator::{validate_reason, REASON_MAX_LENGTH};
+
+const CODE_MIN_LENGTH: usize = 1;
+const CODE_MAX_LENGTH: usize = 32;
+const TITLE_MIN_LENGTH: usize = 1;
+const TITLE_MAX_LENGTH: usize = 64;
+const BODY_MIN_LENGTH: usize = 1;
+const BODY_MAX_LENGTH: usize = 128;
+/// テンプレートの本文と、本文との区切りの改行を合わせて、拒否理由の最大長に収まる長さとする
+const ADDITIONAL_REJECTION_REASON_MAX_LENGTH: usize = REASON_MAX_LENGTH - BODY_MAX_LENGTH - 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct RejectionReasonTemplate {
+    pub(super) code: String,
+    pub(super) body: String,
+}
+
+pub(super) async fn find_rejection_reason_template_by_code(
+    pool: &DatabaseConnection,
+    code: &str,
+) -> Result<Option<RejectionReasonTemplate>, ErrResp> {
+    let model_option = entity::rejection_reason_template::Entity::find_by_id(code)
+        .one(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find rejection_reason_template (code: {}): {}",
+                code, e
+            );
+            unexpected_err_resp()
+        })?;
+    Ok(model_option.map(|m| RejectionReasonTemplate {
+        code: m.code,
+        body: m.body,
+    }))
+}
+
+/// 拒否理由のテンプレートが見つからなかったときのエラーレスポンスを返す
+pub(super) fn no_rejection_reason_template_found(code: &str) -> ErrResp {
+    error!("no rejection_reason_template (code: {}) found", code);
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiError {
+            code: Code::NoRejectionReasonTemplateFound as u32,
+        }),
+    )
+}
+
+/// テンプレートの本文に追加する拒否理由（管理者が任意で入力する）を検証する
+pub(super) fn validate_additional_rejection_reason(
+    additional_rejection_reason: Option<&str>,
+) -> Result<(), ErrResp> {
+    let additional_rejection_reason = match additional_rejection_reason {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let invalid_format_reason = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidFormatReason as u32,
+            }),
+        )
+    };
+    validate_reason(additional_rejection_reason).map_err(|e| {
+        error!(
+            "invalid format additional rejection reason ({}): {}",
+            additional_rejection_reason, e
+        );
+        invalid_format_reason()
+    })?;
+    let length = additional_rejection_reason.chars().count();
+    if length > ADDITIONAL_REJECTION_REASON_MAX_LENGTH {
+        error!(
+            "too long additional rejection reason (length: {}, max_length: {})",
+            length, ADDITIONAL_REJECTION_REASON_MAX_LENGTH
+        );
+        return Err(invalid_format_reason());
+    }
+    Ok(())
+}
+
+/// テンプレートの本文と追加の拒否理由から、ユーザーに通知し、記録する拒否理由を生成する
+pub(super) fn create_rejection_reason(
+    template: &RejectionReasonTemplate,
+    additional_rejection_reason: Option<&str>,
+) -> String {
+    match additional_rejection_reason {
+        Some(additional_rejection_reason) => {
+            format!("{}\n{}", template.body, additional_rejection_reason)
+        }
+        None => template.body.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_template() -> RejectionReasonTemplate {
+        RejectionReasonTemplate {
+            code: "blurred-image".to_string(),
+            body: "画像が不鮮明なため".to_string(),
+        }
+    }
+
+    #[test]
+    fn create_rejection_reason_returns_template_body_if_no_additional_reason() {
+        let template = create_template();
+
+        let result = create_rejection_reason(&template, None);
+
+        assert_eq!(template.body, result);
+    }
+
+    #[test]
+    fn create_rejection_reason_appends_additional_reason_to_template_body() {
+        let template = create_template();
+
+        let result = create_rejection_reason(&template, Some("二枚目の画像が判読できません"));
+
+        assert_eq!("画像が不鮮明なため\n二枚目の画像が判読できません", result);
+    }
+
+    #[test]
+    fn validate_additional_rejection_reason_returns_ok_if_none() {
+        validate_additional_rejection_reason(None).expect("failed to get Ok");
+    }
+
+    #[test]
+    fn validate_additional_rejection_reason_returns_ok_if_max_length() {
+        let additional_rejection_reason = "あ".repeat(ADDITIONAL_REJECTION_REASON_MAX_LENGTH);
+
+        validate_additional_rejection_reason(Some(additional_rejection_reason.as_str()))
+            .expect("failed to get Ok");
+    }
+
+    #[test]
+    fn validate_additional_rejection_reason_returns_err_if_too_long() {
+        let additional_rejection_reason = "あ".repeat(ADDITIONAL_REJECTION_REASON_MAX_LENGTH + 1);
+
+        let result =
+            validate_additional_rejection_reason(Some(additional_rejection_reason.as_str()));
+
+        let err = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err.0);
+        assert_eq!(Code::InvalidFormatReason as u32, err.1 .0.code);
+    }
+
+    #[test]
+    fn validate_additional_rejection_reason_returns_err_if_illegal_char_included() {
+        let result = validate_additional_rejection_reason(Some("<script>alert('test');<script>"));
+
+        let err = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err.0);
+        assert_eq!(Code::InvalidFormatReason as u32, err.1 .0.code);
+    }
+}
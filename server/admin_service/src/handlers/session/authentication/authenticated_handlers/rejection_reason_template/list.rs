@@ -0,0 +1,156 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{extract::State, http::StatusCode, Json};
+use common::{ErrResp, RespResult};
+use entity::sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
+use serde::Serialize;
+use tracing::error;
+
+use crate::{
+    err::unexpected_err_resp,
+    handlers::session::authentication::authenticated_handlers::{
+        admin::Admin, convert_date_time_to_rfc3339_string,
+    },
+};
+
+pub(crate) async fn get_rejection_reason_templates(
+    Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<RejectionReasonTemplatesResult> {
+    let op = RejectionReasonTemplatesOperationImpl { pool };
+    handle_rejection_reason_templates(op).await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct RejectionReasonTemplatesResult {
+    rejection_reason_templates: Vec<RejectionReasonTemplate>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct RejectionReasonTemplate {
+    code: String,
+    title: String,
+    body: String,
+    updated_at: String,         // RFC 3339形式の文字列
+    updated_by: Option<String>, // マイグレーションで登録した初期テンプレートで、管理者が更新していない場合はNone
+}
+
+#[async_trait]
+trait RejectionReasonTemplatesOperation {
+    async fn get_rejection_reason_templates(&self)
+        -> Result<Vec<RejectionReasonTemplate>, ErrResp>;
+}
+
+struct RejectionReasonTemplatesOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl RejectionReasonTemplatesOperation for RejectionReasonTemplatesOperationImpl {
+    async fn get_rejection_reason_templates(
+        &self,
+    ) -> Result<Vec<RejectionReasonTemplate>, ErrResp> {
+        let models = entity::rejection_reason_template::Entity::find()
+            .order_by_asc(entity::rejection_reason_template::Column::Code)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to find rejection_reason_template: {}", e);
+                unexpected_err_resp()
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| RejectionReasonTemplate {
+                code: m.code,
+                title: m.title,
+                body: m.body,
+                updated_at: convert_date_time_to_rfc3339_string(m.updated_at),
+                updated_by: m.updated_by,
+            })
+            .collect())
+    }
+}
+
+/// 拒否理由のテンプレートは管理者が作成する少数のデータのため、ページングせずに全件返す
+async fn handle_rejection_reason_templates(
+    op: impl RejectionReasonTemplatesOperation,
+) -> RespResult<RejectionReasonTemplatesResult> {
+    let rejection_reason_templates = op.get_rejection_reason_templates().await?;
+    Ok((
+        StatusCode::OK,
+        Json(RejectionReasonTemplatesResult {
+            rejection_reason_templates,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RejectionReasonTemplatesOperationMock {
+        rejection_reason_templates: Vec<RejectionReasonTemplate>,
+    }
+
+    #[async_trait]
+    impl RejectionReasonTemplatesOperation for RejectionReasonTemplatesOperationMock {
+        async fn get_rejection_reason_templates(
+            &self,
+        ) -> Result<Vec<RejectionReasonTemplate>, ErrResp> {
+            Ok(self.rejection_reason_templates.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_rejection_reason_templates_success() {
+        let rejection_reason_templates = vec![
+            RejectionReasonTemplate {
+                code: "blurred-image".to_string(),
+                title: "画像が不鮮明".to_string(),
+                body: "提出された画像が不鮮明で、記載内容を確認できなかったため".to_string(),
+                updated_at: "2023-06-01T12:00:00+09:00".to_string(),
+                updated_by: None,
+            },
+            RejectionReasonTemplate {
+                code: "name-mismatch".to_string(),
+                title: "氏名の不一致".to_string(),
+                body: "入力された氏名と、画像に記載された氏名が一致しなかったため".to_string(),
+                updated_at: "2023-06-02T12:00:00+09:00".to_string(),
+                updated_by: Some("admin@test.com".to_string()),
+            },
+        ];
+        let op = RejectionReasonTemplatesOperationMock {
+            rejection_reason_templates: rejection_reason_templates.clone(),
+        };
+
+        let result = handle_rejection_reason_templates(op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            RejectionReasonTemplatesResult {
+                rejection_reason_templates
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_rejection_reason_templates_success_empty() {
+        let op = RejectionReasonTemplatesOperationMock {
+            rejection_reason_templates: vec![],
+        };
+
+        let result = handle_rejection_reason_templates(op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            RejectionReasonTemplatesResult {
+                rejection_reason_templates: vec![]
+            },
+            resp.1 .0
+        );
+    }
+}
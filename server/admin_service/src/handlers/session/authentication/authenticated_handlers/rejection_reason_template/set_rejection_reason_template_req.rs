@@ -0,0 +1,372 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::util::validator::{has_control_char, SYMBOL_CHAR_RE};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::rejection_reason_template::{ActiveModel, Column, Entity};
+use entity::sea_orm::sea_query::OnConflict;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::admin::Admin;
+
+use super::{
+    BODY_MAX_LENGTH, BODY_MIN_LENGTH, CODE_MAX_LENGTH, CODE_MIN_LENGTH, TITLE_MAX_LENGTH,
+    TITLE_MIN_LENGTH,
+};
+
+/// 拒否理由のテンプレートを登録する。既に同じコードのテンプレートが存在する場合、その内容を更新する<br>
+/// <br>
+/// # Errors
+/// コードが不正な形式の場合、ステータスコード400、エラーコード[Code::InvalidRejectionReasonTemplateCodeFormat]を返す<br>
+/// タイトルが不正な形式の場合、ステータスコード400、エラーコード[Code::InvalidRejectionReasonTemplateTitleFormat]を返す<br>
+/// 本文が不正な形式の場合、ステータスコード400、エラーコード[Code::InvalidRejectionReasonTemplateBodyFormat]を返す
+pub(crate) async fn post_set_rejection_reason_template_req(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<SetRejectionReasonTemplateReq>,
+) -> RespResult<SetRejectionReasonTemplateReqResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = SetRejectionReasonTemplateReqOperationImpl { pool };
+    handle_set_rejection_reason_template_req(req, admin_info.email_address, current_date_time, &op)
+        .await
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct SetRejectionReasonTemplateReq {
+    code: String,
+    title: String,
+    body: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SetRejectionReasonTemplateReqResult {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct RejectionReasonTemplate {
+    code: String,
+    title: String,
+    body: String,
+    updated_by: String,
+    updated_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait SetRejectionReasonTemplateReqOperation {
+    async fn set_rejection_reason_template(
+        &self,
+        template: RejectionReasonTemplate,
+    ) -> Result<(), ErrResp>;
+}
+
+struct SetRejectionReasonTemplateReqOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SetRejectionReasonTemplateReqOperation for SetRejectionReasonTemplateReqOperationImpl {
+    async fn set_rejection_reason_template(
+        &self,
+        template: RejectionReasonTemplate,
+    ) -> Result<(), ErrResp> {
+        let active_model = ActiveModel {
+            code: Set(template.code.clone()),
+            title: Set(template.title.clone()),
+            body: Set(template.body.clone()),
+            updated_at: Set(template.updated_at),
+            updated_by: Set(Some(template.updated_by.clone())),
+        };
+        let on_conflict = OnConflict::column(Column::Code)
+            .update_columns([
+                Column::Title,
+                Column::Body,
+                Column::UpdatedAt,
+                Column::UpdatedBy,
+            ])
+            .to_owned();
+        let _ = Entity::insert(active_model)
+            .on_conflict(on_conflict)
+            .exec(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to insert rejection_reason_template (template: {:?}): {}",
+                    template, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(())
+    }
+}
+
+async fn handle_set_rejection_reason_template_req(
+    req: SetRejectionReasonTemplateReq,
+    admin_email_address: String,
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl SetRejectionReasonTemplateReqOperation,
+) -> RespResult<SetRejectionReasonTemplateReqResult> {
+    validate_code(&req.code)?;
+    validate_title(&req.title)?;
+    validate_body(&req.body)?;
+
+    op.set_rejection_reason_template(RejectionReasonTemplate {
+        code: req.code,
+        title: req.title,
+        body: req.body,
+        updated_by: admin_email_address,
+        updated_at: current_date_time,
+    })
+    .await?;
+
+    Ok((StatusCode::OK, Json(SetRejectionReasonTemplateReqResult {})))
+}
+
+/// コードは統計で利用するため、英小文字、数字と区切りのハイフンのみ（例: blurred-image）を許容する
+fn validate_code(code: &str) -> Result<(), ErrResp> {
+    let length = code.chars().count();
+    let has_valid_chars = code
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !(CODE_MIN_LENGTH..=CODE_MAX_LENGTH).contains(&length)
+        || !has_valid_chars
+        || code.starts_with('-')
+        || code.ends_with('-')
+    {
+        error!("invalid rejection reason template code ({})", code);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidRejectionReasonTemplateCodeFormat as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_title(title: &str) -> Result<(), ErrResp> {
+    let length = title.chars().count();
+    if !(TITLE_MIN_LENGTH..=TITLE_MAX_LENGTH).contains(&length)
+        || has_control_char(title)
+        || SYMBOL_CHAR_RE.is_match(title)
+    {
+        error!(
+            "invalid rejection reason template title ({}, length: {})",
+            title, length
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidRejectionReasonTemplateTitleFormat as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_body(body: &str) -> Result<(), ErrResp> {
+    let length = body.chars().count();
+    if !(BODY_MIN_LENGTH..=BODY_MAX_LENGTH).contains(&length)
+        || has_control_char(body)
+        || SYMBOL_CHAR_RE.is_match(body)
+    {
+        error!(
+            "invalid rejection reason template body ({}, length: {})",
+            body, length
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidRejectionReasonTemplateBodyFormat as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct SetRejectionReasonTemplateReqOperationMock {
+        template: RejectionReasonTemplate,
+    }
+
+    #[async_trait]
+    impl SetRejectionReasonTemplateReqOperation for SetRejectionReasonTemplateReqOperationMock {
+        async fn set_rejection_reason_template(
+            &self,
+            template: RejectionReasonTemplate,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.template, template);
+            Ok(())
+        }
+    }
+
+    fn create_req(code: &str, title: &str, body: &str) -> SetRejectionReasonTemplateReq {
+        SetRejectionReasonTemplateReq {
+            code: code.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn create_op_mock(
+        req: &SetRejectionReasonTemplateReq,
+        admin_email_address: &str,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> SetRejectionReasonTemplateReqOperationMock {
+        SetRejectionReasonTemplateReqOperationMock {
+            template: RejectionReasonTemplate {
+                code: req.code.clone(),
+                title: req.title.clone(),
+                body: req.body.clone(),
+                updated_by: admin_email_address.to_string(),
+                updated_at: current_date_time,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_set_rejection_reason_template_req_success() {
+        let req = create_req(
+            "blurred-image",
+            "画像が不鮮明",
+            "提出された画像が不鮮明で、記載内容を確認できなかったため",
+        );
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 6, 1, 12, 0, 0)
+            .unwrap();
+        let op = create_op_mock(&req, admin_email_address, current_date_time);
+
+        let result = handle_set_rejection_reason_template_req(
+            req,
+            admin_email_address.to_string(),
+            current_date_time,
+            &op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(SetRejectionReasonTemplateReqResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_set_rejection_reason_template_req_fail_invalid_code() {
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 6, 1, 12, 0, 0)
+            .unwrap();
+        let too_long_code = "a".repeat(CODE_MAX_LENGTH + 1);
+        let invalid_codes = [
+            "",
+            "Blurred-Image",
+            "blurred_image",
+            "-blurred-image",
+            "blurred-image-",
+            "画像",
+            too_long_code.as_str(),
+        ];
+        for code in invalid_codes {
+            let req = create_req(code, "画像が不鮮明", "画像が不鮮明なため");
+            let op = create_op_mock(&req, admin_email_address, current_date_time);
+
+            let result = handle_set_rejection_reason_template_req(
+                req,
+                admin_email_address.to_string(),
+                current_date_time,
+                &op,
+            )
+            .await;
+
+            let resp = result.expect_err("failed to get Err");
+            assert_eq!(StatusCode::BAD_REQUEST, resp.0, "code: {}", code);
+            assert_eq!(
+                Code::InvalidRejectionReasonTemplateCodeFormat as u32,
+                resp.1 .0.code,
+                "code: {}",
+                code
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_set_rejection_reason_template_req_fail_invalid_title() {
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 6, 1, 12, 0, 0)
+            .unwrap();
+        let too_long_title = "あ".repeat(TITLE_MAX_LENGTH + 1);
+        let invalid_titles = [
+            "",
+            "画像が<不鮮明>",
+            "画像が\n不鮮明",
+            too_long_title.as_str(),
+        ];
+        for title in invalid_titles {
+            let req = create_req("blurred-image", title, "画像が不鮮明なため");
+            let op = create_op_mock(&req, admin_email_address, current_date_time);
+
+            let result = handle_set_rejection_reason_template_req(
+                req,
+                admin_email_address.to_string(),
+                current_date_time,
+                &op,
+            )
+            .await;
+
+            let resp = result.expect_err("failed to get Err");
+            assert_eq!(StatusCode::BAD_REQUEST, resp.0, "title: {}", title);
+            assert_eq!(
+                Code::InvalidRejectionReasonTemplateTitleFormat as u32,
+                resp.1 .0.code,
+                "title: {}",
+                title
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_set_rejection_reason_template_req_fail_invalid_body() {
+        let admin_email_address = "admin@test.com";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 6, 1, 12, 0, 0)
+            .unwrap();
+        let too_long_body = "あ".repeat(BODY_MAX_LENGTH + 1);
+        let invalid_bodies = [
+            "",
+            "<script>alert('test');<script>",
+            "画像が\n不鮮明なため",
+            too_long_body.as_str(),
+        ];
+        for body in invalid_bodies {
+            let req = create_req("blurred-image", "画像が不鮮明", body);
+            let op = create_op_mock(&req, admin_email_address, current_date_time);
+
+            let result = handle_set_rejection_reason_template_req(
+                req,
+                admin_email_address.to_string(),
+                current_date_time,
+                &op,
+            )
+            .await;
+
+            let resp = result.expect_err("failed to get Err");
+            assert_eq!(StatusCode::BAD_REQUEST, resp.0, "body: {}", body);
+            assert_eq!(
+                Code::InvalidRejectionReasonTemplateBodyFormat as u32,
+                resp.1 .0.code,
+                "body: {}",
+                body
+            );
+        }
+    }
+}
@@ -0,0 +1,633 @@
+// Copyright 2023 Ken Miura
+
+use async_session::async_trait;
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::{
+    create_career_req, create_identity_req,
+    sea_orm::{
+        sea_query::{Expr, SimpleExpr},
+        ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    },
+    update_career_req, update_identity_req,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+
+use super::admin::Admin;
+
+/// 確認作業の開始から、確認作業中として扱う時間（分）
+///
+/// この時間を経過した依頼は、確認作業中ではないものとして扱う（他の管理者が確認作業を開始できる）
+const CLAIM_EXPIRY_IN_MINUTES: i64 = 30;
+
+/// 管理者が確認する依頼の種類
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ReviewQueue {
+    CreateIdentity,
+    UpdateIdentity,
+    CreateCareer,
+    UpdateCareer,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct ReviewClaimTarget {
+    review_queue: ReviewQueue,
+    /// 依頼を特定するID
+    ///
+    /// 本人確認依頼の場合、user_account_id。職務経歴確認依頼 (新規) の場合、create_career_req_id。職務経歴確認依頼 (更新) の場合、career_id
+    request_id: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct ReviewClaimResult {
+    claimed_at: DateTime<FixedOffset>,
+    /// この日時を過ぎると、確認作業中ではないものとして扱われる
+    expires_at: DateTime<FixedOffset>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct ReviewClaimReleaseResult {}
+
+/// 依頼の確認作業を開始する（他の管理者が承認、拒否できないようにする）
+///
+/// 既に自身が確認作業中の依頼の場合、確認作業の開始日時を更新する
+pub(crate) async fn post_review_claim(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+    Json(target): Json<ReviewClaimTarget>,
+) -> RespResult<ReviewClaimResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = ReviewClaimOperationImpl { pool };
+    handle_review_claim(admin_info.email_address, target, current_date_time, op).await
+}
+
+/// 依頼の確認作業を終了する（他の管理者が確認作業を開始できるようにする）
+pub(crate) async fn post_review_claim_release(
+    Admin { admin_info }: Admin, // 認証されていることを保証するために必須のパラメータ
+    State(pool): State<DatabaseConnection>,
+    Json(target): Json<ReviewClaimTarget>,
+) -> RespResult<ReviewClaimReleaseResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = ReviewClaimOperationImpl { pool };
+    handle_review_claim_release(admin_info.email_address, target, current_date_time, op).await
+}
+
+async fn handle_review_claim(
+    admin_email_address: String,
+    target: ReviewClaimTarget,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl ReviewClaimOperation,
+) -> RespResult<ReviewClaimResult> {
+    update_claim(
+        &target,
+        Some(admin_email_address.clone()),
+        admin_email_address.as_str(),
+        current_date_time,
+        &op,
+    )
+    .await?;
+    Ok((
+        StatusCode::OK,
+        Json(ReviewClaimResult {
+            claimed_at: current_date_time,
+            expires_at: current_date_time + Duration::minutes(CLAIM_EXPIRY_IN_MINUTES),
+        }),
+    ))
+}
+
+async fn handle_review_claim_release(
+    admin_email_address: String,
+    target: ReviewClaimTarget,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl ReviewClaimOperation,
+) -> RespResult<ReviewClaimReleaseResult> {
+    update_claim(
+        &target,
+        None,
+        admin_email_address.as_str(),
+        current_date_time,
+        &op,
+    )
+    .await?;
+    Ok((StatusCode::OK, Json(ReviewClaimReleaseResult {})))
+}
+
+async fn update_claim(
+    target: &ReviewClaimTarget,
+    new_claimed_by: Option<String>,
+    admin_email_address: &str,
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl ReviewClaimOperation,
+) -> Result<(), ErrResp> {
+    let updated = op
+        .update_claim_if_not_claimed_by_other_admin(
+            target.review_queue,
+            target.request_id,
+            new_claimed_by,
+            admin_email_address,
+            current_date_time,
+        )
+        .await?;
+    if updated {
+        return Ok(());
+    }
+    // 更新できなかった理由（依頼が存在しない、または他の管理者が確認作業中）をエラーとして返す
+    let exists = op
+        .request_exists(target.review_queue, target.request_id)
+        .await?;
+    if !exists {
+        error!(
+            "no review request (review_queue: {:?}, request_id: {}) found",
+            target.review_queue, target.request_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoReviewRequestFound as u32,
+            }),
+        ));
+    }
+    error!(
+        "review request (review_queue: {:?}, request_id: {}) is claimed by other admin",
+        target.review_queue, target.request_id
+    );
+    Err(create_claimed_by_other_admin_err_resp())
+}
+
+#[async_trait]
+trait ReviewClaimOperation {
+    /// 依頼を他の管理者が確認作業中でない場合に限り、確認作業中の管理者を更新する（Noneの場合、確認作業中の管理者なしとする）
+    ///
+    /// 更新した場合、trueを返す
+    async fn update_claim_if_not_claimed_by_other_admin(
+        &self,
+        review_queue: ReviewQueue,
+        request_id: i64,
+        new_claimed_by: Option<String>,
+        admin_email_address: &str,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<bool, ErrResp>;
+
+    async fn request_exists(
+        &self,
+        review_queue: ReviewQueue,
+        request_id: i64,
+    ) -> Result<bool, ErrResp>;
+}
+
+struct ReviewClaimOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl ReviewClaimOperation for ReviewClaimOperationImpl {
+    async fn update_claim_if_not_claimed_by_other_admin(
+        &self,
+        review_queue: ReviewQueue,
+        request_id: i64,
+        new_claimed_by: Option<String>,
+        admin_email_address: &str,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<bool, ErrResp> {
+        let claim = ClaimUpdate {
+            new_claimed_by,
+            admin_email_address,
+            current_date_time,
+        };
+        // 確認作業中かどうかの判定と更新を一つのUPDATE文で行い、複数の管理者が同時に確認作業を開始できないようにする
+        match review_queue {
+            ReviewQueue::CreateIdentity => {
+                claim
+                    .exec::<create_identity_req::Entity>(
+                        &self.pool,
+                        create_identity_req::Column::UserAccountId.eq(request_id),
+                        create_identity_req::Column::ClaimedBy,
+                        create_identity_req::Column::ClaimedAt,
+                    )
+                    .await
+            }
+            ReviewQueue::UpdateIdentity => {
+                claim
+                    .exec::<update_identity_req::Entity>(
+                        &self.pool,
+                        update_identity_req::Column::UserAccountId.eq(request_id),
+                        update_identity_req::Column::ClaimedBy,
+                        update_identity_req::Column::ClaimedAt,
+                    )
+                    .await
+            }
+            ReviewQueue::CreateCareer => {
+                claim
+                    .exec::<create_career_req::Entity>(
+                        &self.pool,
+                        create_career_req::Column::CreateCareerReqId.eq(request_id),
+                        create_career_req::Column::ClaimedBy,
+                        create_career_req::Column::ClaimedAt,
+                    )
+                    .await
+            }
+            ReviewQueue::UpdateCareer => {
+                claim
+                    .exec::<update_career_req::Entity>(
+                        &self.pool,
+                        update_career_req::Column::CareerId.eq(request_id),
+                        update_career_req::Column::ClaimedBy,
+                        update_career_req::Column::ClaimedAt,
+                    )
+                    .await
+            }
+        }
+    }
+
+    async fn request_exists(
+        &self,
+        review_queue: ReviewQueue,
+        request_id: i64,
+    ) -> Result<bool, ErrResp> {
+        let result = match review_queue {
+            ReviewQueue::CreateIdentity => {
+                create_identity_req::Entity::find_by_id(request_id)
+                    .count(&self.pool)
+                    .await
+            }
+            ReviewQueue::UpdateIdentity => {
+                update_identity_req::Entity::find_by_id(request_id)
+                    .count(&self.pool)
+                    .await
+            }
+            ReviewQueue::CreateCareer => {
+                create_career_req::Entity::find_by_id(request_id)
+                    .count(&self.pool)
+                    .await
+            }
+            ReviewQueue::UpdateCareer => {
+                update_career_req::Entity::find_by_id(request_id)
+                    .count(&self.pool)
+                    .await
+            }
+        };
+        let count = result.map_err(|e| {
+            error!(
+                "failed to count review request (review_queue: {:?}, request_id: {}): {}",
+                review_queue, request_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(count != 0)
+    }
+}
+
+struct ClaimUpdate<'a> {
+    new_claimed_by: Option<String>,
+    admin_email_address: &'a str,
+    current_date_time: DateTime<FixedOffset>,
+}
+
+impl ClaimUpdate<'_> {
+    async fn exec<E: EntityTrait>(
+        self,
+        pool: &DatabaseConnection,
+        request_id_expr: SimpleExpr,
+        claimed_by_column: E::Column,
+        claimed_at_column: E::Column,
+    ) -> Result<bool, ErrResp> {
+        let new_claimed_at = self.new_claimed_by.as_ref().map(|_| self.current_date_time);
+        let result = E::update_many()
+            .col_expr(claimed_by_column, Expr::value(self.new_claimed_by))
+            .col_expr(claimed_at_column, Expr::value(new_claimed_at))
+            .filter(request_id_expr)
+            .filter(create_not_claimed_by_other_admin_condition(
+                claimed_by_column,
+                claimed_at_column,
+                self.admin_email_address,
+                self.current_date_time,
+            ))
+            .exec(pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to update claim (admin_email_address: {}): {}",
+                    self.admin_email_address, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(result.rows_affected != 0)
+    }
+}
+
+fn calculate_claim_expiry_criteria(
+    current_date_time: DateTime<FixedOffset>,
+) -> DateTime<FixedOffset> {
+    current_date_time - Duration::minutes(CLAIM_EXPIRY_IN_MINUTES)
+}
+
+fn create_not_claimed_by_other_admin_condition<C: ColumnTrait>(
+    claimed_by_column: C,
+    claimed_at_column: C,
+    admin_email_address: &str,
+    current_date_time: DateTime<FixedOffset>,
+) -> Condition {
+    Condition::any()
+        .add(claimed_by_column.is_null())
+        .add(claimed_by_column.eq(admin_email_address))
+        .add(claimed_at_column.is_null())
+        .add(claimed_at_column.lte(calculate_claim_expiry_criteria(current_date_time)))
+}
+
+fn create_claimed_by_other_admin_err_resp() -> ErrResp {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiError {
+            code: Code::ReviewRequestClaimedByOtherAdmin as u32,
+        }),
+    )
+}
+
+/// 依頼を他の管理者が確認作業中の場合、エラーを返す
+///
+/// 承認、拒否の処理で依頼をロックした後に呼び出す
+pub(super) fn ensure_not_claimed_by_other_admin(
+    claimed_by: Option<&str>,
+    claimed_at: Option<DateTime<FixedOffset>>,
+    admin_email_address: &str,
+    current_date_time: DateTime<FixedOffset>,
+) -> Result<(), ErrResp> {
+    let (claimed_by, claimed_at) = match (claimed_by, claimed_at) {
+        (Some(claimed_by), Some(claimed_at)) => (claimed_by, claimed_at),
+        _ => return Ok(()),
+    };
+    if claimed_by == admin_email_address
+        || claimed_at <= calculate_claim_expiry_criteria(current_date_time)
+    {
+        return Ok(());
+    }
+    error!(
+        "review request is claimed by other admin (claimed_by: {}, claimed_at: {})",
+        claimed_by, claimed_at
+    );
+    Err(create_claimed_by_other_admin_err_resp())
+}
+
+/// 依頼の一覧を確認作業の状態で絞り込む際の条件
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ClaimStatus {
+    /// 誰も確認作業中でない（期限切れを含む）
+    Unclaimed,
+    /// 自身が確認作業中
+    ClaimedByMe,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ClaimStatusQuery {
+    pub(super) claim_status: Option<ClaimStatus>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ReviewClaimFilter {
+    claim_status: ClaimStatus,
+    admin_email_address: String,
+    current_date_time: DateTime<FixedOffset>,
+}
+
+impl ReviewClaimFilter {
+    pub(super) fn new(
+        claim_status_query: ClaimStatusQuery,
+        admin_email_address: String,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Option<Self> {
+        claim_status_query
+            .claim_status
+            .map(|claim_status| ReviewClaimFilter {
+                claim_status,
+                admin_email_address,
+                current_date_time,
+            })
+    }
+
+    pub(super) fn create_condition<C: ColumnTrait>(
+        &self,
+        claimed_by_column: C,
+        claimed_at_column: C,
+    ) -> Condition {
+        let criteria = calculate_claim_expiry_criteria(self.current_date_time);
+        match self.claim_status {
+            ClaimStatus::Unclaimed => Condition::any()
+                .add(claimed_by_column.is_null())
+                .add(claimed_at_column.is_null())
+                .add(claimed_at_column.lte(criteria)),
+            ClaimStatus::ClaimedByMe => Condition::all()
+                .add(claimed_by_column.eq(self.admin_email_address.as_str()))
+                .add(claimed_at_column.gt(criteria)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct ReviewClaimOperationMock {
+        review_queue: ReviewQueue,
+        request_id: i64,
+        claimed_by_other_admin: bool,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl ReviewClaimOperation for ReviewClaimOperationMock {
+        async fn update_claim_if_not_claimed_by_other_admin(
+            &self,
+            review_queue: ReviewQueue,
+            request_id: i64,
+            _new_claimed_by: Option<String>,
+            _admin_email_address: &str,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.current_date_time, current_date_time);
+            if self.review_queue != review_queue || self.request_id != request_id {
+                return Ok(false);
+            }
+            Ok(!self.claimed_by_other_admin)
+        }
+
+        async fn request_exists(
+            &self,
+            review_queue: ReviewQueue,
+            request_id: i64,
+        ) -> Result<bool, ErrResp> {
+            Ok(self.review_queue == review_queue && self.request_id == request_id)
+        }
+    }
+
+    fn create_current_date_time() -> DateTime<FixedOffset> {
+        JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 10, 14, 0, 0)
+            .unwrap()
+    }
+
+    fn create_op_mock(claimed_by_other_admin: bool) -> ReviewClaimOperationMock {
+        ReviewClaimOperationMock {
+            review_queue: ReviewQueue::CreateCareer,
+            request_id: 4512,
+            claimed_by_other_admin,
+            current_date_time: create_current_date_time(),
+        }
+    }
+
+    fn create_target(review_queue: ReviewQueue, request_id: i64) -> ReviewClaimTarget {
+        ReviewClaimTarget {
+            review_queue,
+            request_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_review_claim_success() {
+        let current_date_time = create_current_date_time();
+
+        let result = handle_review_claim(
+            String::from("admin@test.com"),
+            create_target(ReviewQueue::CreateCareer, 4512),
+            current_date_time,
+            create_op_mock(false),
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            ReviewClaimResult {
+                claimed_at: current_date_time,
+                expires_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 5, 10, 14, 30, 0)
+                    .unwrap(),
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_review_claim_fail_claimed_by_other_admin() {
+        let result = handle_review_claim(
+            String::from("admin@test.com"),
+            create_target(ReviewQueue::CreateCareer, 4512),
+            create_current_date_time(),
+            create_op_mock(true),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::ReviewRequestClaimedByOtherAdmin as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_review_claim_fail_no_review_request_found() {
+        let result = handle_review_claim(
+            String::from("admin@test.com"),
+            create_target(ReviewQueue::UpdateCareer, 4512),
+            create_current_date_time(),
+            create_op_mock(false),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoReviewRequestFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_review_claim_release_success() {
+        let result = handle_review_claim_release(
+            String::from("admin@test.com"),
+            create_target(ReviewQueue::CreateCareer, 4512),
+            create_current_date_time(),
+            create_op_mock(false),
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(ReviewClaimReleaseResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_review_claim_release_fail_claimed_by_other_admin() {
+        let result = handle_review_claim_release(
+            String::from("admin@test.com"),
+            create_target(ReviewQueue::CreateCareer, 4512),
+            create_current_date_time(),
+            create_op_mock(true),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::ReviewRequestClaimedByOtherAdmin as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[test]
+    fn ensure_not_claimed_by_other_admin_success_unclaimed() {
+        ensure_not_claimed_by_other_admin(None, None, "admin@test.com", create_current_date_time())
+            .expect("failed to get Ok");
+    }
+
+    #[test]
+    fn ensure_not_claimed_by_other_admin_success_claimed_by_me() {
+        let current_date_time = create_current_date_time();
+        ensure_not_claimed_by_other_admin(
+            Some("admin@test.com"),
+            Some(current_date_time - Duration::minutes(1)),
+            "admin@test.com",
+            current_date_time,
+        )
+        .expect("failed to get Ok");
+    }
+
+    #[test]
+    fn ensure_not_claimed_by_other_admin_success_claim_by_other_admin_expired() {
+        let current_date_time = create_current_date_time();
+        ensure_not_claimed_by_other_admin(
+            Some("admin2@test.com"),
+            Some(current_date_time - Duration::minutes(CLAIM_EXPIRY_IN_MINUTES)),
+            "admin@test.com",
+            current_date_time,
+        )
+        .expect("failed to get Ok");
+    }
+
+    #[test]
+    fn ensure_not_claimed_by_other_admin_fail_claimed_by_other_admin() {
+        let current_date_time = create_current_date_time();
+        let result = ensure_not_claimed_by_other_admin(
+            Some("admin2@test.com"),
+            Some(
+                current_date_time - Duration::minutes(CLAIM_EXPIRY_IN_MINUTES)
+                    + Duration::seconds(1),
+            ),
+            "admin@test.com",
+            current_date_time,
+        );
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::ReviewRequestClaimedByOtherAdmin as u32,
+            resp.1 .0.code
+        );
+    }
+}
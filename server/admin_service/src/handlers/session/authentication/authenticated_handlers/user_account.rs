@@ -22,12 +22,11 @@ pub(crate) mod user_account_retrieval_by_user_account_id;
 
 use async_session::serde_json::json;
 use axum::{http::StatusCode, Json};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
 use common::{
-    opensearch::update_document,
     rating::{calculate_average_rating, round_rating_to_one_decimal_places},
     ApiError, ErrResp, ErrRespStruct,
 };
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
@@ -116,7 +115,7 @@ async fn update_disabled_on_document(
     index_name: &str,
     document_id: &str,
     disabled: bool,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let value = format!("ctx._source.disabled = {}", disabled);
     let script = json!({
@@ -124,7 +123,8 @@ async fn update_disabled_on_document(
             "source": value
         }
     });
-    update_document(index_name, document_id, &script, &index_client)
+    consultant_search_client
+        .update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
@@ -4,13 +4,13 @@ use axum::extract::State;
 use axum::http::StatusCode;
 use axum::{async_trait, Json};
 use chrono::{DateTime, FixedOffset, Utc};
+use common::consultant_search::ConsultantSearchClient;
 use common::opensearch::INDEX_NAME;
 use common::{ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE};
 use entity::sea_orm::{
-    ActiveModelTrait, DatabaseConnection, DatabaseTransaction, Set, TransactionError,
-    TransactionTrait,
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, Set, TransactionError, TransactionTrait,
 };
-use opensearch::OpenSearch;
 use serde::Deserialize;
 use tracing::{error, info};
 
@@ -25,10 +25,13 @@ use super::{validate_account_id_is_positive, UserAccount, UserAccountRetrievalRe
 pub(crate) async fn post_disable_user_account_req(
     Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(req): Json<DisableUserAccountReq>,
 ) -> RespResult<UserAccountRetrievalResult> {
-    let op = DisableUserAccountReqOperationImpl { pool, index_client };
+    let op = DisableUserAccountReqOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
     handle_disable_user_account_req(req.user_account_id, current_date_time, &op).await
 }
@@ -67,7 +70,7 @@ trait DisableUserAccountReqOperation {
 
 struct DisableUserAccountReqOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -78,7 +81,7 @@ impl DisableUserAccountReqOperation for DisableUserAccountReqOperationImpl {
         index_name: String,
         current_date_time: DateTime<FixedOffset>,
     ) -> Result<UserAccount, ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         let result = self.pool
             .transaction::<_, entity::user_account::Model, ErrRespStruct>(|txn| {
                 Box::pin(async move {
@@ -93,11 +96,13 @@ impl DisableUserAccountReqOperation for DisableUserAccountReqOperationImpl {
                         }
                     })?;
 
+                    delete_favorite_consultants_by_consultant_id(user_account_id, txn).await?;
+
                     let doc_option = find_document_model_by_user_account_id_with_exclusive_lock(txn, user_account_id).await?;
                     if let Some(doc) = doc_option {
                         info!("document (user_account_id: {}, document_id: {}) exists and set disabled to true on document", user_account_id, doc.document_id);
                         let document_id = doc.document_id.to_string();
-                        update_disabled_on_document(&index_name, &document_id, true, index_client).await?;
+                        update_disabled_on_document(&index_name, &document_id, true, consultant_search_client).await?;
                     }
 
                     Ok(result)
@@ -154,6 +159,31 @@ async fn find_user_model_with_exclusive_lock(
     Ok(user_model)
 }
 
+/// 無効化されたコンサルタントは相談を受け付けられないため、全ユーザーのお気に入りから削除する
+async fn delete_favorite_consultants_by_consultant_id(
+    consultant_id: i64,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let result = entity::favorite_consultant::Entity::delete_many()
+        .filter(entity::favorite_consultant::Column::ConsultantId.eq(consultant_id))
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete favorite_consultant (consultant_id: {}): {}",
+                consultant_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    info!(
+        "deleted {} favorite_consultant (consultant_id: {})",
+        result.rows_affected, consultant_id
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -3,13 +3,13 @@
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::{async_trait, Json};
+use common::consultant_search::ConsultantSearchClient;
 use common::opensearch::INDEX_NAME;
 use common::{ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE};
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, DatabaseTransaction, Set, TransactionError,
     TransactionTrait,
 };
-use opensearch::OpenSearch;
 use serde::Deserialize;
 use tracing::{error, info};
 
@@ -24,10 +24,13 @@ use super::{validate_account_id_is_positive, UserAccount, UserAccountRetrievalRe
 pub(crate) async fn post_enable_user_account_req(
     Admin { admin_info: _ }: Admin, // 認証されていることを保証するために必須のパラメータ
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(req): Json<EnableUserAccountReq>,
 ) -> RespResult<UserAccountRetrievalResult> {
-    let op = EnableUserAccountReqOperationImpl { pool, index_client };
+    let op = EnableUserAccountReqOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     handle_enable_user_account_req(req.user_account_id, &op).await
 }
 
@@ -64,7 +67,7 @@ trait EnableUserAccountReqOperation {
 
 struct EnableUserAccountReqOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -74,7 +77,7 @@ impl EnableUserAccountReqOperation for EnableUserAccountReqOperationImpl {
         user_account_id: i64,
         index_name: String,
     ) -> Result<UserAccount, ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         let result = self.pool
             .transaction::<_, entity::user_account::Model, ErrRespStruct>(|txn| {
                 Box::pin(async move {
@@ -93,7 +96,7 @@ impl EnableUserAccountReqOperation for EnableUserAccountReqOperationImpl {
                     if let Some(doc) = doc_option {
                         info!("document (user_account_id: {}, document_id: {}) exists and set disabled to false on document", user_account_id, doc.document_id);
                         let document_id = doc.document_id.to_string();
-                        update_disabled_on_document(&index_name, &document_id, false, index_client).await?;
+                        update_disabled_on_document(&index_name, &document_id, false, consultant_search_client).await?;
                     }
 
                     Ok(result)
@@ -25,7 +25,20 @@ use crate::handlers::session::authentication::authenticated_handlers::career_req
 use crate::handlers::session::authentication::authenticated_handlers::career_request::create_request::detail::get_create_career_request_detail;
 use crate::handlers::session::authentication::authenticated_handlers::career_request::create_request::rejection::post_create_career_request_rejection;
 use crate::handlers::session::authentication::authenticated_handlers::career_request::create_request::list::get_create_career_requests;
+use crate::handlers::session::authentication::authenticated_handlers::career_request::update_request::approval::post_update_career_request_approval;
+use crate::handlers::session::authentication::authenticated_handlers::career_request::update_request::detail::get_update_career_request_detail;
+use crate::handlers::session::authentication::authenticated_handlers::career_request::update_request::rejection::post_update_career_request_rejection;
+use crate::handlers::session::authentication::authenticated_handlers::career_request::update_request::list::get_update_career_requests;
+use crate::handlers::session::authentication::authenticated_handlers::review_claim::post_review_claim;
+use crate::handlers::session::authentication::authenticated_handlers::review_claim::post_review_claim_release;
+use crate::handlers::session::authentication::authenticated_handlers::rejection_reason_template::list::get_rejection_reason_templates;
+use crate::handlers::session::authentication::authenticated_handlers::rejection_reason_template::set_rejection_reason_template_req::post_set_rejection_reason_template_req;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant_rating_by_consultation_id::get_consultant_rating_by_consultation_id;
+use crate::handlers::session::authentication::authenticated_handlers::company::companies_by_name::get_companies_by_name;
+use crate::handlers::session::authentication::authenticated_handlers::consultant_search_report::get_consultant_search_report;
+use crate::handlers::session::authentication::authenticated_handlers::company::set_company_req::post_set_company_req;
+use crate::handlers::session::authentication::authenticated_handlers::coupon::list::get_coupons;
+use crate::handlers::session::authentication::authenticated_handlers::coupon::set_coupon_req::post_set_coupon_req;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_by_consultation_id::get_consultation_by_consultation_id;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::user_rating_by_consultation_id::get_user_rating_by_consultation_id;
 use crate::handlers::session::authentication::authenticated_handlers::identity_by_user_account_id::get_identity_by_user_account_id;
@@ -34,6 +47,7 @@ use crate::handlers::session::authentication::authenticated_handlers::identity_r
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::create_request::rejection::post_create_identity_request_rejection;
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::create_request::list::get_create_identity_requests;
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::create_request::users_by_date_of_birth::get_users_by_date_of_birth;
+use crate::handlers::session::authentication::authenticated_handlers::identity_request::duplicate_identity_candidates::get_duplicate_identity_candidates;
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::identity_images::get_identity_images;
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::update_request::approval::post_update_identity_request_approval;
 use crate::handlers::session::authentication::authenticated_handlers::identity_request::update_request::detail::get_update_identity_request_detail;
@@ -47,6 +61,8 @@ use crate::handlers::session::authentication::authenticated_handlers::news::dele
 use crate::handlers::session::authentication::authenticated_handlers::news::latest_news::get_latest_news;
 use crate::handlers::session::authentication::authenticated_handlers::news::set_news_req::post_set_news_req;
 use crate::handlers::session::authentication::authenticated_handlers::refunded_payment::list::get_refunded_payments;
+use crate::handlers::session::authentication::authenticated_handlers::payment_event::payment_state_inconsistencies::get_payment_state_inconsistencies;
+use crate::handlers::session::authentication::authenticated_handlers::payment_event::payment_timeline::get_payment_timeline;
 use crate::handlers::session::authentication::authenticated_handlers::refunded_payment::refund_from_awaiting_payment::post_refund_from_awaiting_payment;
 use crate::handlers::session::authentication::authenticated_handlers::user_account::agreements_by_user_account_id::get_agreements_by_user_account_id;
 use crate::handlers::session::authentication::authenticated_handlers::user_account::career_creation::approval_records::get_career_creation_approval_records;
@@ -97,6 +113,12 @@ use common::storage::{
      KEY_TO_AWS_S3_REGION, KEY_TO_AWS_S3_ENDPOINT_URI,
      KEY_TO_IDENTITY_IMAGES_BUCKET_NAME, KEY_TO_CAREER_IMAGES_BUCKET_NAME, AWS_S3_REGION, AWS_S3_ACCESS_KEY_ID, AWS_S3_SECRET_ACCESS_KEY, AWS_S3_ENDPOINT_URI, StorageClient,
 };
+use common::consultant_search::{
+    KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH, ConsultantSearchClient, USE_DB_FOR_CONSULTANT_SEARCH,
+};
+use common::payment::{
+    KEY_TO_USE_STUB_PAYMENT_GATEWAY, KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI, KEY_TO_PAYMENT_GATEWAY_SECRET_KEY, PaymentGatewayClient, USE_STUB_PAYMENT_GATEWAY, PAYMENT_GATEWAY_ENDPOINT_URI, PAYMENT_GATEWAY_SECRET_KEY,
+};
 use common::util::{check_env_vars};
 use common::{AppState, RequestLogElements, create_key_for_singed_cookie, KEY_TO_USE_ECS_TASK_ROLE, USE_ECS_TASK_ROLE};
 use dotenv::dotenv;
@@ -140,6 +162,10 @@ static ENV_VARS: Lazy<Vec<String>> = Lazy::new(|| {
         KEY_TO_AWS_SES_REGION.to_string(),
         KEY_TO_AWS_SES_ENDPOINT_URI.to_string(),
         KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+        KEY_TO_USE_STUB_PAYMENT_GATEWAY.to_string(),
+        KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH.to_string(),
+        KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI.to_string(),
+        KEY_TO_PAYMENT_GATEWAY_SECRET_KEY.to_string(),
         KEY_TO_TRANSFER_FEE_IN_YEN.to_string(),
         KEY_TO_PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
     ]
@@ -197,7 +223,7 @@ async fn main_internal(num_of_cpus: u32) {
         .wait_for_connect()
         .await
         .expect("failed to connect redis");
-    let store = RedisSessionStore::from_pool(redis_pool, None);
+    let store = RedisSessionStore::from_pool(redis_pool.clone(), None);
 
     let opensearch_url = var(KEY_TO_OPENSEARCH_ENDPOINT_URI).unwrap_or_else(|_| {
         panic!(
@@ -263,13 +289,31 @@ async fn main_internal(num_of_cpus: u32) {
         .await
     };
 
+    let payment_gateway_client = if *USE_STUB_PAYMENT_GATEWAY {
+        PaymentGatewayClient::new_stub()
+    } else {
+        PaymentGatewayClient::new(
+            PAYMENT_GATEWAY_ENDPOINT_URI.as_str(),
+            PAYMENT_GATEWAY_SECRET_KEY.as_str(),
+        )
+    };
+
+    let consultant_search_client = if *USE_DB_FOR_CONSULTANT_SEARCH {
+        ConsultantSearchClient::new_database(pool.clone())
+    } else {
+        ConsultantSearchClient::new(index_client.clone())
+    };
+
     let state = AppState {
         store,
+        redis_pool,
         index_client,
         pool,
         key_for_signed_cookie,
         smtp_client,
         storage_client,
+        payment_gateway_client,
+        consultant_search_client,
     };
 
     let app = Router::new()
@@ -289,6 +333,10 @@ async fn main_internal(num_of_cpus: u32) {
                     get(get_create_identity_request_detail),
                 )
                 .route("/users-by-date-of-birth", get(get_users_by_date_of_birth))
+                .route(
+                    "/duplicate-identity-candidates",
+                    get(get_duplicate_identity_candidates),
+                )
                 .route(
                     "/identity-images/:user_account_id/:image_name",
                     get(get_identity_images),
@@ -340,6 +388,24 @@ async fn main_internal(num_of_cpus: u32) {
                     "/create-career-request-rejection",
                     post(post_create_career_request_rejection),
                 )
+                .route(
+                    "/update-career-requests",
+                    get(get_update_career_requests),
+                )
+                .route(
+                    "/update-career-request-detail",
+                    get(get_update_career_request_detail),
+                )
+                .route(
+                    "/update-career-request-approval",
+                    post(post_update_career_request_approval),
+                )
+                .route(
+                    "/update-career-request-rejection",
+                    post(post_update_career_request_rejection),
+                )
+                .route("/review-claim", post(post_review_claim))
+                .route("/review-claim-release", post(post_review_claim_release))
                 .route(
                     "/user-account-retrieval-by-user-account-id",
                     post(post_user_account_retrieval_by_user_account_id),
@@ -500,6 +566,14 @@ async fn main_internal(num_of_cpus: u32) {
                     "/refunded-payments",
                     get(get_refunded_payments),
                 )
+                .route(
+                    "/payment-timeline",
+                    get(get_payment_timeline),
+                )
+                .route(
+                    "/payment-state-inconsistencies",
+                    get(get_payment_state_inconsistencies),
+                )
                 .route(
                     "/neglected-payment",
                     post(post_neglected_payment),
@@ -540,6 +614,34 @@ async fn main_internal(num_of_cpus: u32) {
                     "/left-awaiting-withdrawal-by-consultation-id",
                     get(get_left_awaiting_withdrawal_by_consultation_id),
                 )
+                .route(
+                    "/set-coupon-req",
+                    post(post_set_coupon_req),
+                )
+                .route(
+                    "/coupons",
+                    get(get_coupons),
+                )
+                .route(
+                    "/set-company-req",
+                    post(post_set_company_req),
+                )
+                .route(
+                    "/companies-by-name",
+                    get(get_companies_by_name),
+                )
+                .route(
+                    "/consultant-search-report",
+                    get(get_consultant_search_report),
+                )
+                .route(
+                    "/set-rejection-reason-template-req",
+                    post(post_set_rejection_reason_template_req),
+                )
+                .route(
+                    "/rejection-reason-templates",
+                    get(get_rejection_reason_templates),
+                )
                 .with_state(state),
         )
         .layer(
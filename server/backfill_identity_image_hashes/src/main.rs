@@ -0,0 +1,534 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, FixedOffset};
+use dotenv::dotenv;
+use entity::identity_image_hash;
+use entity::sea_orm::{
+    prelude::async_trait::async_trait, ActiveModelTrait, ConnectOptions, ConnectionTrait, Database,
+    DatabaseBackend, DatabaseConnection, QueryResult, Set, Statement,
+};
+use image::ImageFormat;
+use std::{env::set_var, error::Error, process::exit};
+use tracing::{error, info};
+
+use common::{
+    admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD},
+    db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT},
+    log::{init_log, LOG_LEVEL},
+    perceptual_hash::calculate_perceptual_hash,
+    storage::{
+        StorageClient, AWS_S3_ACCESS_KEY_ID, AWS_S3_ENDPOINT_URI, AWS_S3_REGION,
+        AWS_S3_SECRET_ACCESS_KEY, IDENTITY_IMAGES_BUCKET_NAME, KEY_TO_AWS_S3_ENDPOINT_URI,
+        KEY_TO_AWS_S3_REGION, KEY_TO_IDENTITY_IMAGES_BUCKET_NAME,
+    },
+    util::check_env_vars,
+    JAPANESE_TIME_ZONE, KEY_TO_USE_ECS_TASK_ROLE, USE_ECS_TASK_ROLE,
+};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const APPLICATION_ERR: i32 = 3;
+
+/// 一度に処理する（DBから取得し、ストレージからダウンロードしてハッシュを計算する）画像の数
+const NUM_OF_IMAGES_PER_CHUNK: u64 = 100;
+
+/// 本人確認の申請（承認済みを含む）で提出された身分証の画像のうち、知覚ハッシュが登録されていないものを、
+/// ファイル名が$1より大きいものからファイル名の昇順に$2件取得する
+///
+/// 拒否された申請の画像はストレージから削除されているため対象に含めない。
+const FIND_IDENTITY_IMAGES_WITHOUT_HASH_SQL: &str = r"
+SELECT identity_image.user_account_id, identity_image.image_file_name_without_ext
+FROM (
+  SELECT user_account_id, image1_file_name_without_ext AS image_file_name_without_ext FROM ccs_schema.create_identity_req
+  UNION SELECT user_account_id, image2_file_name_without_ext FROM ccs_schema.create_identity_req
+  UNION SELECT user_account_id, image1_file_name_without_ext FROM ccs_schema.update_identity_req
+  UNION SELECT user_account_id, image2_file_name_without_ext FROM ccs_schema.update_identity_req
+  UNION SELECT user_account_id, image1_file_name_without_ext FROM ccs_schema.approved_create_identity_req
+  UNION SELECT user_account_id, image2_file_name_without_ext FROM ccs_schema.approved_create_identity_req
+  UNION SELECT user_account_id, image1_file_name_without_ext FROM ccs_schema.approved_update_identity_req
+  UNION SELECT user_account_id, image2_file_name_without_ext FROM ccs_schema.approved_update_identity_req
+) AS identity_image
+WHERE identity_image.image_file_name_without_ext IS NOT NULL
+  AND identity_image.image_file_name_without_ext > $1
+  AND NOT EXISTS (
+    SELECT 1 FROM ccs_schema.identity_image_hash
+    WHERE identity_image_hash.image_file_name_without_ext = identity_image.image_file_name_without_ext
+  )
+ORDER BY identity_image.image_file_name_without_ext
+LIMIT $2;
+";
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        KEY_TO_AWS_S3_REGION.to_string(),
+        KEY_TO_AWS_S3_ENDPOINT_URI.to_string(),
+        KEY_TO_IDENTITY_IMAGES_BUCKET_NAME.to_string(),
+        KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    let log_conf = format!(
+        "backfill_identity_image_hashes={},common={},sea_orm={}",
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str()
+    );
+    set_var("RUST_LOG", log_conf);
+    init_log();
+
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let mut opt = ConnectOptions::new(database_url.clone());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let pool = Database::connect(opt).await.unwrap_or_else(|e| {
+        error!("failed to connect database: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let storage_client = if *USE_ECS_TASK_ROLE {
+        StorageClient::new_with_ecs_task_role(AWS_S3_REGION.as_str(), AWS_S3_ENDPOINT_URI.as_str())
+            .await
+    } else {
+        StorageClient::new(
+            AWS_S3_REGION.as_str(),
+            AWS_S3_ACCESS_KEY_ID.as_str(),
+            AWS_S3_SECRET_ACCESS_KEY.as_str(),
+            AWS_S3_ENDPOINT_URI.as_str(),
+        )
+        .await
+    };
+
+    let op = BackfillIdentityImageHashesOperationImpl {
+        pool,
+        storage_client,
+    };
+
+    let result = backfill_identity_image_hashes(current_date_time, &op).await;
+
+    let num_of_images = result.unwrap_or_else(|e| {
+        error!("failed to backfill identity image hashes: {}", e);
+        exit(APPLICATION_ERR)
+    });
+
+    info!(
+        "{} identity image hash(es) were (was) backfilled successfully",
+        num_of_images
+    );
+    exit(SUCCESS)
+}
+
+/// 知覚ハッシュが登録されていない身分証の画像のハッシュを計算し、登録する
+///
+/// 一部の画像の処理に失敗した場合も残りの画像の処理を続け、最後に失敗した画像の一覧をエラーとして返す。
+/// 戻り値は登録したハッシュの数
+async fn backfill_identity_image_hashes(
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl BackfillIdentityImageHashesOperation,
+) -> Result<usize, Box<dyn Error>> {
+    let mut num_of_backfilled = 0;
+    let mut backfill_failed: Vec<IdentityImage> = Vec::new();
+    let mut last_image_file_name_without_ext = String::new();
+
+    loop {
+        let identity_images = op
+            .get_identity_images_without_hash(
+                &last_image_file_name_without_ext,
+                NUM_OF_IMAGES_PER_CHUNK,
+            )
+            .await?;
+        let Some(last_identity_image) = identity_images.last() else {
+            break;
+        };
+        last_image_file_name_without_ext = last_identity_image.image_file_name_without_ext.clone();
+
+        for identity_image in identity_images {
+            let result = backfill_identity_image_hash(&identity_image, current_date_time, op).await;
+            if let Err(e) = result {
+                error!(
+                    "failed to backfill identity image hash ({:?}): {}",
+                    identity_image, e
+                );
+                backfill_failed.push(identity_image);
+                continue;
+            }
+            num_of_backfilled += 1;
+        }
+    }
+
+    if !backfill_failed.is_empty() {
+        return Err(format!(
+            "{} backfilled, {} failed (detail: {:?})",
+            num_of_backfilled,
+            backfill_failed.len(),
+            backfill_failed
+        )
+        .into());
+    }
+
+    Ok(num_of_backfilled)
+}
+
+async fn backfill_identity_image_hash(
+    identity_image: &IdentityImage,
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl BackfillIdentityImageHashesOperation,
+) -> Result<(), Box<dyn Error>> {
+    let png_image = op.download_identity_image(identity_image).await?;
+    // ストレージには、アップロード時の画像をpngに変換したものが格納されている。
+    // pngは可逆圧縮のため、アップロード時と同じハッシュが得られる。
+    let img = image::load_from_memory_with_format(&png_image, ImageFormat::Png)?;
+    let perceptual_hash = calculate_perceptual_hash(&img.to_luma8());
+    op.insert_identity_image_hash(identity_image, perceptual_hash, current_date_time)
+        .await
+}
+
+#[async_trait]
+trait BackfillIdentityImageHashesOperation {
+    /// 知覚ハッシュが登録されていない身分証の画像を、ファイル名がlast_image_file_name_without_extより大きいものからファイル名の昇順にlimit件取得する
+    async fn get_identity_images_without_hash(
+        &self,
+        last_image_file_name_without_ext: &str,
+        limit: u64,
+    ) -> Result<Vec<IdentityImage>, Box<dyn Error>>;
+
+    async fn download_identity_image(
+        &self,
+        identity_image: &IdentityImage,
+    ) -> Result<Vec<u8>, Box<dyn Error>>;
+
+    async fn insert_identity_image_hash(
+        &self,
+        identity_image: &IdentityImage,
+        perceptual_hash: u64,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct IdentityImage {
+    user_account_id: i64,
+    image_file_name_without_ext: String,
+}
+
+struct BackfillIdentityImageHashesOperationImpl {
+    pool: DatabaseConnection,
+    storage_client: StorageClient,
+}
+
+#[async_trait]
+impl BackfillIdentityImageHashesOperation for BackfillIdentityImageHashesOperationImpl {
+    async fn get_identity_images_without_hash(
+        &self,
+        last_image_file_name_without_ext: &str,
+        limit: u64,
+    ) -> Result<Vec<IdentityImage>, Box<dyn Error>> {
+        let rows = self
+            .pool
+            .query_all(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                FIND_IDENTITY_IMAGES_WITHOUT_HASH_SQL,
+                [last_image_file_name_without_ext.into(), (limit as i64).into()],
+            ))
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to get identity images without hash (last_image_file_name_without_ext: {}, limit: {}): {}",
+                    last_image_file_name_without_ext, limit, e
+                )
+            })?;
+        rows.iter().map(convert_to_identity_image).collect()
+    }
+
+    async fn download_identity_image(
+        &self,
+        identity_image: &IdentityImage,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let key = format!(
+            "{}/{}.png",
+            identity_image.user_account_id, identity_image.image_file_name_without_ext
+        );
+        let object = self
+            .storage_client
+            .download_object(IDENTITY_IMAGES_BUCKET_NAME.as_str(), key.as_str())
+            .await
+            .map_err(|e| format!("failed to download object (key: {}): {}", key, e))?;
+        Ok(object)
+    }
+
+    async fn insert_identity_image_hash(
+        &self,
+        identity_image: &IdentityImage,
+        perceptual_hash: u64,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>> {
+        let active_model = identity_image_hash::ActiveModel {
+            image_file_name_without_ext: Set(identity_image.image_file_name_without_ext.clone()),
+            user_account_id: Set(identity_image.user_account_id),
+            perceptual_hash: Set(perceptual_hash as i64),
+            created_at: Set(current_date_time),
+        };
+        let _ = active_model.insert(&self.pool).await.map_err(|e| {
+            format!(
+                "failed to insert identity_image_hash ({:?}): {}",
+                identity_image, e
+            )
+        })?;
+        Ok(())
+    }
+}
+
+fn convert_to_identity_image(row: &QueryResult) -> Result<IdentityImage, Box<dyn Error>> {
+    let user_account_id: i64 = row.try_get("", "user_account_id")?;
+    let image_file_name_without_ext: String = row.try_get("", "image_file_name_without_ext")?;
+    Ok(IdentityImage {
+        user_account_id,
+        image_file_name_without_ext,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashMap, io::Cursor, sync::Mutex};
+
+    use chrono::TimeZone;
+    use image::{ImageBuffer, ImageOutputFormat, RgbImage};
+
+    use super::*;
+
+    struct BackfillIdentityImageHashesOperationMock {
+        identity_images: Vec<IdentityImage>,
+        /// ファイル名をキー、ストレージに格納されている画像を値とするマップ
+        objects: HashMap<String, Vec<u8>>,
+        current_date_time: DateTime<FixedOffset>,
+        inserted: Mutex<Vec<(IdentityImage, u64)>>,
+    }
+
+    #[async_trait]
+    impl BackfillIdentityImageHashesOperation for BackfillIdentityImageHashesOperationMock {
+        async fn get_identity_images_without_hash(
+            &self,
+            last_image_file_name_without_ext: &str,
+            limit: u64,
+        ) -> Result<Vec<IdentityImage>, Box<dyn Error>> {
+            assert_eq!(NUM_OF_IMAGES_PER_CHUNK, limit);
+            let inserted = self.inserted.lock().expect("failed to get lock");
+            let mut identity_images: Vec<IdentityImage> = self
+                .identity_images
+                .iter()
+                .filter(|i| {
+                    i.image_file_name_without_ext.as_str() > last_image_file_name_without_ext
+                })
+                .filter(|i| !inserted.iter().any(|(inserted, _)| inserted == *i))
+                .cloned()
+                .collect();
+            identity_images.sort_by(|a, b| {
+                a.image_file_name_without_ext
+                    .cmp(&b.image_file_name_without_ext)
+            });
+            identity_images.truncate(limit as usize);
+            Ok(identity_images)
+        }
+
+        async fn download_identity_image(
+            &self,
+            identity_image: &IdentityImage,
+        ) -> Result<Vec<u8>, Box<dyn Error>> {
+            let object = self
+                .objects
+                .get(&identity_image.image_file_name_without_ext)
+                .ok_or("object not found")?;
+            Ok(object.clone())
+        }
+
+        async fn insert_identity_image_hash(
+            &self,
+            identity_image: &IdentityImage,
+            perceptual_hash: u64,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<(), Box<dyn Error>> {
+            assert_eq!(self.current_date_time, current_date_time);
+            self.inserted
+                .lock()
+                .expect("failed to get lock")
+                .push((identity_image.clone(), perceptual_hash));
+            Ok(())
+        }
+    }
+
+    fn create_dummy_image(horizontal_gradient: bool) -> RgbImage {
+        ImageBuffer::from_fn(160, 120, |x, y| {
+            let luma = if horizontal_gradient {
+                x * 255 / 160
+            } else {
+                y * 255 / 120
+            } as u8;
+            image::Rgb([luma, luma, luma])
+        })
+    }
+
+    fn encode_to_png(img: &RgbImage) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
+        img.write_to(&mut bytes, ImageOutputFormat::Png)
+            .expect("failed to get Ok");
+        bytes.into_inner()
+    }
+
+    fn create_identity_image(user_account_id: i64, index: usize) -> IdentityImage {
+        IdentityImage {
+            user_account_id,
+            image_file_name_without_ext: format!("{:032x}", index),
+        }
+    }
+
+    fn create_current_date_time() -> DateTime<FixedOffset> {
+        JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 4, 5, 0, 1, 7)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn backfill_identity_image_hashes_success_no_image() {
+        let current_date_time = create_current_date_time();
+        let op = BackfillIdentityImageHashesOperationMock {
+            identity_images: vec![],
+            objects: HashMap::new(),
+            current_date_time,
+            inserted: Mutex::new(vec![]),
+        };
+
+        let result = backfill_identity_image_hashes(current_date_time, &op).await;
+
+        let num_of_images = result.expect("failed to get Ok");
+        assert_eq!(0, num_of_images);
+        assert!(op.inserted.lock().expect("failed to get lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn backfill_identity_image_hashes_success_images_over_multiple_chunks() {
+        let current_date_time = create_current_date_time();
+        let num_of_images = NUM_OF_IMAGES_PER_CHUNK as usize + 1;
+        let png_image = encode_to_png(&create_dummy_image(true));
+        let identity_images: Vec<IdentityImage> = (0..num_of_images)
+            .map(|i| create_identity_image(i as i64 / 2 + 1, i))
+            .collect();
+        let objects = identity_images
+            .iter()
+            .map(|i| (i.image_file_name_without_ext.clone(), png_image.clone()))
+            .collect();
+        let op = BackfillIdentityImageHashesOperationMock {
+            identity_images: identity_images.clone(),
+            objects,
+            current_date_time,
+            inserted: Mutex::new(vec![]),
+        };
+
+        let result = backfill_identity_image_hashes(current_date_time, &op).await;
+
+        assert_eq!(num_of_images, result.expect("failed to get Ok"));
+        let inserted = op.inserted.lock().expect("failed to get lock");
+        let inserted_images: Vec<IdentityImage> = inserted.iter().map(|i| i.0.clone()).collect();
+        assert_eq!(identity_images, inserted_images);
+        let expected_hash = calculate_perceptual_hash(
+            &image::DynamicImage::from(create_dummy_image(true)).to_luma8(),
+        );
+        assert!(inserted.iter().all(|i| i.1 == expected_hash));
+    }
+
+    #[tokio::test]
+    async fn backfill_identity_image_hashes_calculates_hash_for_each_image() {
+        let current_date_time = create_current_date_time();
+        let identity_image1 = create_identity_image(1, 1);
+        let identity_image2 = create_identity_image(2, 2);
+        let objects = HashMap::from([
+            (
+                identity_image1.image_file_name_without_ext.clone(),
+                encode_to_png(&create_dummy_image(true)),
+            ),
+            (
+                identity_image2.image_file_name_without_ext.clone(),
+                encode_to_png(&create_dummy_image(false)),
+            ),
+        ]);
+        let op = BackfillIdentityImageHashesOperationMock {
+            identity_images: vec![identity_image1.clone(), identity_image2.clone()],
+            objects,
+            current_date_time,
+            inserted: Mutex::new(vec![]),
+        };
+
+        let result = backfill_identity_image_hashes(current_date_time, &op).await;
+
+        assert_eq!(2, result.expect("failed to get Ok"));
+        let inserted = op.inserted.lock().expect("failed to get lock");
+        assert_eq!(2, inserted.len());
+        assert_eq!(identity_image1, inserted[0].0);
+        assert_eq!(identity_image2, inserted[1].0);
+        assert_ne!(inserted[0].1, inserted[1].1);
+    }
+
+    #[tokio::test]
+    async fn backfill_identity_image_hashes_continues_and_fails_if_some_images_cannot_be_processed()
+    {
+        let current_date_time = create_current_date_time();
+        // 1はストレージに存在しない、2は画像として読み込めない、3は正常な画像
+        let identity_image1 = create_identity_image(1, 1);
+        let identity_image2 = create_identity_image(2, 2);
+        let identity_image3 = create_identity_image(3, 3);
+        let objects = HashMap::from([
+            (
+                identity_image2.image_file_name_without_ext.clone(),
+                vec![0_u8, 1, 2, 3],
+            ),
+            (
+                identity_image3.image_file_name_without_ext.clone(),
+                encode_to_png(&create_dummy_image(true)),
+            ),
+        ]);
+        let op = BackfillIdentityImageHashesOperationMock {
+            identity_images: vec![
+                identity_image1.clone(),
+                identity_image2.clone(),
+                identity_image3.clone(),
+            ],
+            objects,
+            current_date_time,
+            inserted: Mutex::new(vec![]),
+        };
+
+        let result = backfill_identity_image_hashes(current_date_time, &op).await;
+
+        let err_message = result.expect_err("failed to get Err").to_string();
+        assert!(err_message.starts_with("1 backfilled, 2 failed"));
+        let inserted = op.inserted.lock().expect("failed to get lock");
+        assert_eq!(1, inserted.len());
+        assert_eq!(identity_image3, inserted[0].0);
+    }
+}
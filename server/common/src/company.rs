@@ -0,0 +1,95 @@
+// Copyright 2023 Ken Miura
+
+//! 会社のマスタ（company、company_alias）を扱う関数を集約するモジュール
+//!
+//! 職務経歴の会社名は表記揺れがあるため、[normalize_company_name]で正規化した会社名を別名（company_alias）として登録し、
+//! 同じ会社を異なる表記で入力しても同一の会社（company_id）を見つけられるようにする。
+
+use chrono::{DateTime, FixedOffset};
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, Set,
+};
+
+use crate::opensearch::normalization::normalize_company_name;
+
+/// company_aliasのnormalized_nameの最大長
+const NORMALIZED_NAME_MAX_LENGTH: usize = 256;
+
+/// 会社を作成し、その会社名を別名として登録する
+///
+/// 戻り値は作成した会社のcompany_id
+pub async fn insert_company(
+    company_name: &str,
+    corporate_number: Option<String>,
+    created_by: &str,
+    created_at: DateTime<FixedOffset>,
+    conn: &impl ConnectionTrait,
+) -> Result<i64, DbErr> {
+    let active_model = entity::company::ActiveModel {
+        company_name: Set(company_name.to_string()),
+        corporate_number: Set(corporate_number),
+        created_by: Set(created_by.to_string()),
+        created_at: Set(created_at),
+        ..Default::default()
+    };
+    let model = active_model.insert(conn).await?;
+    let _ = insert_company_alias_if_not_exists(model.company_id, company_name, conn).await?;
+    Ok(model.company_id)
+}
+
+/// company_nameを正規化した値を、company_idの会社の別名として登録する
+///
+/// 既に同じ別名が登録されている場合は何もしない。戻り値は別名を新たに登録した場合true
+pub async fn insert_company_alias_if_not_exists(
+    company_id: i64,
+    company_name: &str,
+    conn: &impl ConnectionTrait,
+) -> Result<bool, DbErr> {
+    let normalized_name = create_normalized_name(company_name);
+    let count = entity::company_alias::Entity::find()
+        .filter(entity::company_alias::Column::CompanyId.eq(company_id))
+        .filter(entity::company_alias::Column::NormalizedName.eq(normalized_name.clone()))
+        .count(conn)
+        .await?;
+    if count != 0 {
+        return Ok(false);
+    }
+    let active_model = entity::company_alias::ActiveModel {
+        company_id: Set(company_id),
+        normalized_name: Set(normalized_name),
+        ..Default::default()
+    };
+    let _ = active_model.insert(conn).await?;
+    Ok(true)
+}
+
+/// 別名として登録する値（[normalize_company_name]で正規化し、最大長を超える分は切り捨てた値）を返す
+pub fn create_normalized_name(company_name: &str) -> String {
+    normalize_company_name(company_name)
+        .chars()
+        .take(NORMALIZED_NAME_MAX_LENGTH)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn create_normalized_name_returns_normalized_company_name() {
+        assert_eq!("てすと", create_normalized_name("株式会社テスト"));
+    }
+
+    #[test]
+    fn create_normalized_name_truncates_long_company_name() {
+        // NFKC正規化で文字数が増える（㈱が(株)になる）ケース
+        let company_name = "あ".repeat(NORMALIZED_NAME_MAX_LENGTH - 1) + "㈱い";
+
+        let result = create_normalized_name(&company_name);
+
+        assert_eq!(NORMALIZED_NAME_MAX_LENGTH, result.chars().count());
+        assert!(result.ends_with("あ("));
+    }
+}
@@ -0,0 +1,1273 @@
+// Copyright 2023 Ken Miura
+
+//! コンサルタントの検索と、検索対象のドキュメントの追加、更新、削除を行うバックエンドを集約するモジュール
+//!
+//! 通常はOpenSearchを利用する。ローカル環境での動作確認やE2Eテストのように、OpenSearchを用意しない環境ではDBを直接検索するバックエンドを利用する。
+
+mod database;
+
+use std::env::var;
+
+use axum::async_trait;
+use entity::sea_orm::DatabaseConnection;
+use once_cell::sync::Lazy;
+use opensearch::OpenSearch;
+use serde_json::Value;
+use tracing::info;
+
+use crate::opensearch::consultant_query::{
+    create_aggregations_json, create_consultant_query_json, create_consultants_query_json,
+    create_query_json, create_similar_consultants_query_json, create_sort_json,
+    create_suggestions_query_json, CareerFeature, CareerParam, FeePerHourInYenParam,
+};
+use crate::opensearch::{
+    create_point_in_time, delete_document, delete_point_in_time, index_document, search_documents,
+    search_documents_with_point_in_time, update_document, Sort, INDEX_NAME,
+};
+use crate::ErrResp;
+
+pub const KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH: &str = "USE_DB_FOR_CONSULTANT_SEARCH";
+/// コンサルタントの検索にOpenSearchの代わりにDBを使うかどうかを示す値
+///
+/// ローカル環境での動作確認やE2Eテストの場合のみtrueを指定する。DBを使う場合、コンサルタントの検索、詳細の取得、お気に入り、似たコンサルタント及び入力補完でOpenSearchへの通信は発生せず、
+/// ドキュメントの追加、更新、削除は何もしない（DBの内容から検索の度にドキュメントを作成するため）。
+pub static USE_DB_FOR_CONSULTANT_SEARCH: Lazy<bool> = Lazy::new(|| {
+    var(KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH).unwrap_or_else(|_| {
+        panic!(
+            "Not environment variable found: environment variable \"{}\" (example value: \"false\") must be set",
+            KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH
+        );
+    }).parse().expect("failed to parse")
+});
+
+/// コンサルタントの検索条件
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsultantSearchRequest {
+    /// 検索を行うユーザー（検索結果から除外する）
+    pub account_id: i64,
+    pub career_param: CareerParam,
+    pub fee_per_hour_in_yen_param: FeePerHourInYenParam,
+    pub sort: Option<Sort>,
+    pub include_aggregations: bool,
+}
+
+impl ConsultantSearchRequest {
+    /// OpenSearchの[Search API](https://opensearch.org/docs/2.2/api-reference/search/)のリクエストボディを返す
+    pub fn create_query_json(&self) -> Value {
+        let mut query = create_query_json(
+            self.account_id,
+            self.career_param.clone(),
+            self.fee_per_hour_in_yen_param.clone(),
+        );
+        if self.include_aggregations {
+            // 集計はqueryに一致したドキュメントに対して行われるため、相談を受け付けているコンサルタントのみという条件が常に適用される
+            query["aggs"] = create_aggregations_json();
+        }
+        query["sort"] = Value::Array(create_sort_json(self.sort.as_ref()));
+        query
+    }
+
+    /// 検索結果の各ヒットに含まれるsortの値（search_afterに指定する値）の数
+    pub fn num_of_sort_criteria(&self) -> usize {
+        create_sort_json(self.sort.as_ref()).len()
+    }
+}
+
+/// コンサルタントの検索を行うバックエンド
+///
+/// 検索結果は、バックエンドによらずOpenSearchの[Search API](https://opensearch.org/docs/2.2/api-reference/search/)のレスポンスと同じ形式（took、hits、aggregations）で返す。
+/// ヒットした各ドキュメント（_source）の形式は[crate::opensearch::create_document_source]で作成するものと同じ。
+#[async_trait]
+pub trait ConsultantSearchBackend {
+    async fn search_consultants(
+        &self,
+        request: &ConsultantSearchRequest,
+        from: i64,
+        size: i64,
+    ) -> Result<Value, ErrResp>;
+
+    /// Point in Timeを作成し、そのIDを返す
+    async fn create_point_in_time(&self, keep_alive: &str) -> Result<String, ErrResp>;
+
+    /// Point in Timeを削除する。期限が切れている（または存在しない）場合もエラーとしない
+    async fn delete_point_in_time(&self, pit_id: &str) -> Result<(), ErrResp>;
+
+    /// Point in Timeの期限が切れている場合、Noneを返す
+    async fn search_consultants_with_point_in_time(
+        &self,
+        pit_id: &str,
+        keep_alive: &str,
+        request: &ConsultantSearchRequest,
+        size: i64,
+        search_after: Option<&[Value]>,
+    ) -> Result<Option<Value>, ErrResp>;
+
+    /// 相談を受け付けられるコンサルタント（consultant_id）を検索する。検索を行うユーザー自身（account_id）は検索結果から除外する
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp>;
+
+    /// 相談を受け付けられるコンサルタントのうち、指定したコンサルタント（consultant_ids）を検索する。検索を行うユーザー自身（account_id）は検索結果から除外する
+    ///
+    /// 検索結果の並び順は定めない
+    async fn search_consultants_by_ids(
+        &self,
+        consultant_ids: &[i64],
+        account_id: i64,
+    ) -> Result<Value, ErrResp>;
+
+    /// 職務経歴の項目（career_features）と一致する項目が多い職務経歴を持つコンサルタントから順に最大size件検索する
+    ///
+    /// 相談を受け付けられるコンサルタントのみを対象とし、検索を行うユーザー自身（account_id）と比較対象のコンサルタント（consultant_id）は検索結果から除外する
+    async fn search_similar_consultants(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+        career_features: &[CareerFeature],
+        size: i64,
+    ) -> Result<Value, ErrResp>;
+
+    /// 会社名、職種それぞれについて、prefixに前方一致する値の集計結果（[create_suggestions_query_json]の集計結果と同じ形式のaggregations）を返す
+    ///
+    /// 相談を受け付けられるコンサルタントのみを対象とし、検索を行うユーザー自身（account_id）は集計から除外する
+    async fn search_suggestions(&self, account_id: i64, prefix: &str) -> Result<Value, ErrResp>;
+
+    async fn index_document(
+        &self,
+        index_name: &str,
+        document_id: &str,
+        json_value: &Value,
+    ) -> Result<(), ErrResp>;
+
+    async fn update_document(
+        &self,
+        index_name: &str,
+        document_id: &str,
+        json_value: &Value,
+    ) -> Result<(), ErrResp>;
+
+    async fn delete_document(&self, index_name: &str, document_id: &str) -> Result<(), ErrResp>;
+}
+
+#[derive(Clone)]
+pub struct ConsultantSearchClient {
+    inner: ConsultantSearchClientInner,
+}
+
+#[derive(Clone)]
+enum ConsultantSearchClientInner {
+    OpenSearch(OpenSearch),
+    Database(DatabaseConnection),
+}
+
+impl ConsultantSearchClient {
+    /// OpenSearchで検索を行うクライアントを生成する。
+    pub fn new(index_client: OpenSearch) -> Self {
+        Self {
+            inner: ConsultantSearchClientInner::OpenSearch(index_client),
+        }
+    }
+
+    /// DBを直接検索するクライアントを生成する。ローカル環境での動作確認、E2Eテスト用
+    pub fn new_database(pool: DatabaseConnection) -> Self {
+        Self {
+            inner: ConsultantSearchClientInner::Database(pool),
+        }
+    }
+}
+
+#[async_trait]
+impl ConsultantSearchBackend for ConsultantSearchClient {
+    async fn search_consultants(
+        &self,
+        request: &ConsultantSearchRequest,
+        from: i64,
+        size: i64,
+    ) -> Result<Value, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = request.create_query_json();
+                search_documents(INDEX_NAME, from, size, None, &query, client).await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                database::search_consultants(pool, request, from, size, None).await
+            }
+        }
+    }
+
+    async fn create_point_in_time(&self, keep_alive: &str) -> Result<String, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                create_point_in_time(INDEX_NAME, keep_alive, client).await
+            }
+            ConsultantSearchClientInner::Database(_) => Ok(database::POINT_IN_TIME_ID.to_string()),
+        }
+    }
+
+    async fn delete_point_in_time(&self, pit_id: &str) -> Result<(), ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                delete_point_in_time(pit_id, client).await
+            }
+            ConsultantSearchClientInner::Database(_) => Ok(()),
+        }
+    }
+
+    async fn search_consultants_with_point_in_time(
+        &self,
+        pit_id: &str,
+        keep_alive: &str,
+        request: &ConsultantSearchRequest,
+        size: i64,
+        search_after: Option<&[Value]>,
+    ) -> Result<Option<Value>, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = request.create_query_json();
+                search_documents_with_point_in_time(
+                    pit_id,
+                    keep_alive,
+                    size,
+                    search_after,
+                    &query,
+                    client,
+                )
+                .await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                if pit_id != database::POINT_IN_TIME_ID {
+                    return Ok(None);
+                }
+                let mut result =
+                    database::search_consultants(pool, request, 0, size, search_after).await?;
+                result["pit_id"] = Value::String(pit_id.to_string());
+                Ok(Some(result))
+            }
+        }
+    }
+
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = create_consultant_query_json(consultant_id, account_id);
+                search_documents(INDEX_NAME, 0, 1, None, &query, client).await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                database::search_consultant(pool, consultant_id, account_id).await
+            }
+        }
+    }
+
+    async fn search_consultants_by_ids(
+        &self,
+        consultant_ids: &[i64],
+        account_id: i64,
+    ) -> Result<Value, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = create_consultants_query_json(consultant_ids, account_id);
+                let size = consultant_ids.len() as i64;
+                search_documents(INDEX_NAME, 0, size, None, &query, client).await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                database::search_consultants_by_ids(pool, consultant_ids, account_id).await
+            }
+        }
+    }
+
+    async fn search_similar_consultants(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+        career_features: &[CareerFeature],
+        size: i64,
+    ) -> Result<Value, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = create_similar_consultants_query_json(
+                    consultant_id,
+                    account_id,
+                    career_features,
+                );
+                search_documents(INDEX_NAME, 0, size, None, &query, client).await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                database::search_similar_consultants(
+                    pool,
+                    consultant_id,
+                    account_id,
+                    career_features,
+                    size,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn search_suggestions(&self, account_id: i64, prefix: &str) -> Result<Value, ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                let query = create_suggestions_query_json(account_id, prefix);
+                // 集計結果のみを利用するため、ドキュメント自体は取得しない
+                search_documents(INDEX_NAME, 0, 0, None, &query, client).await
+            }
+            ConsultantSearchClientInner::Database(pool) => {
+                database::search_suggestions(pool, account_id, prefix).await
+            }
+        }
+    }
+
+    async fn index_document(
+        &self,
+        index_name: &str,
+        document_id: &str,
+        json_value: &Value,
+    ) -> Result<(), ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                index_document(index_name, document_id, json_value, client).await
+            }
+            ConsultantSearchClientInner::Database(_) => {
+                info!(
+                    "skip indexing document on database backend (index_name: {}, document_id: {})",
+                    index_name, document_id
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn update_document(
+        &self,
+        index_name: &str,
+        document_id: &str,
+        json_value: &Value,
+    ) -> Result<(), ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                update_document(index_name, document_id, json_value, client).await
+            }
+            ConsultantSearchClientInner::Database(_) => {
+                info!(
+                    "skip updating document on database backend (index_name: {}, document_id: {})",
+                    index_name, document_id
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn delete_document(&self, index_name: &str, document_id: &str) -> Result<(), ErrResp> {
+        match &self.inner {
+            ConsultantSearchClientInner::OpenSearch(client) => {
+                delete_document(index_name, document_id, client).await
+            }
+            ConsultantSearchClientInner::Database(_) => {
+                info!(
+                    "skip deleting document on database backend (index_name: {}, document_id: {})",
+                    index_name, document_id
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 同じデータを登録したOpenSearchとDBのそれぞれのバックエンドに同じ条件で検索を行い、両者が同じ結果を返すことを確認するテスト
+///
+/// 外部のサーバ（空のDB、usersインデックスの存在しないOpenSearch）が必要なため、通常のテストでは実行しない（#[ignore]）。
+/// 実行方法はserver/README.mdの「コンサルタント検索のバックエンドのテスト」を参照
+#[cfg(test)]
+mod tests {
+    use std::env::var;
+
+    use chrono::{Days, NaiveDate, Utc};
+    use entity::sea_orm::{
+        ActiveModelTrait, ColumnTrait, Database, EntityTrait, PaginatorTrait, QueryFilter, Set,
+    };
+    use opensearch::indices::{
+        IndicesCreateParts, IndicesDeleteParts, IndicesExistsParts, IndicesRefreshParts,
+    };
+    use opensearch::CountParts;
+    use serde_json::json;
+
+    use super::*;
+    use crate::opensearch::consultant_query::{AnnualInComeInManYenParam, YearsOfServiceParam};
+    use crate::opensearch::index_definition::create_index_definition;
+    use crate::opensearch::{create_client, create_document_source};
+    use crate::JAPANESE_TIME_ZONE;
+
+    /// 接続先のDB（マイグレーション済みで、ユーザーの存在しないDB）のURL
+    const KEY_TO_TEST_DATABASE_URL: &str = "CONSULTANT_SEARCH_TEST_DATABASE_URL";
+    /// 接続先のOpenSearch（usersインデックスの存在しないOpenSearch）のエンドポイント
+    const KEY_TO_TEST_OPENSEARCH_ENDPOINT_URI: &str =
+        "CONSULTANT_SEARCH_TEST_OPENSEARCH_ENDPOINT_URI";
+
+    /// 検索を行うユーザー（相談を受け付けられるコンサルタントでもあるが、検索結果から除外される）
+    const ACCOUNT_ID: i64 = 100;
+
+    struct CareerFixture {
+        company_name: &'static str,
+        company_id: i64,
+        years_of_service: u64,
+        employed: bool,
+        contract_type: &'static str,
+        profession: Option<&'static str>,
+        is_manager: bool,
+    }
+
+    struct ConsultantFixture {
+        user_account_id: i64,
+        fee_per_hour_in_yen: Option<i32>,
+        is_bank_account_registered: bool,
+        disabled: bool,
+        ratings: Vec<i16>,
+        careers: Vec<CareerFixture>,
+    }
+
+    fn career(
+        company_name: &'static str,
+        company_id: i64,
+        years_of_service: u64,
+        employed: bool,
+        contract_type: &'static str,
+        profession: Option<&'static str>,
+        is_manager: bool,
+    ) -> CareerFixture {
+        CareerFixture {
+            company_name,
+            company_id,
+            years_of_service,
+            employed,
+            contract_type,
+            profession,
+            is_manager,
+        }
+    }
+
+    /// 相談を受け付けられるコンサルタントは101から105。106から109は相談を受け付けられない（いずれの検索結果にも含まれない）
+    fn create_consultant_fixtures() -> Vec<ConsultantFixture> {
+        let consultant = |user_account_id, fee, ratings, careers| ConsultantFixture {
+            user_account_id,
+            fee_per_hour_in_yen: Some(fee),
+            is_bank_account_registered: true,
+            disabled: false,
+            ratings,
+            careers,
+        };
+        vec![
+            consultant(
+                ACCOUNT_ID,
+                7000,
+                vec![5],
+                vec![career(
+                    "テスト株式会社",
+                    1,
+                    3,
+                    true,
+                    "regular",
+                    Some("エンジニア"),
+                    false,
+                )],
+            ),
+            consultant(
+                101,
+                5000,
+                vec![5, 4],
+                vec![career(
+                    "テスト株式会社",
+                    1,
+                    7,
+                    true,
+                    "regular",
+                    Some("エンジニア"),
+                    false,
+                )],
+            ),
+            consultant(
+                102,
+                8000,
+                vec![3],
+                vec![
+                    career("サンプル商事", 2, 2, false, "contract", Some("営業"), false),
+                    career(
+                        "テスト株式会社",
+                        1,
+                        12,
+                        true,
+                        "regular",
+                        Some("エンジニア"),
+                        true,
+                    ),
+                ],
+            ),
+            consultant(
+                103,
+                3000,
+                vec![],
+                vec![career(
+                    "サンプル商事",
+                    2,
+                    4,
+                    true,
+                    "regular",
+                    Some("営業"),
+                    false,
+                )],
+            ),
+            consultant(
+                104,
+                10000,
+                vec![2, 1],
+                vec![career("テスト工業", 3, 22, true, "other", None, true)],
+            ),
+            consultant(
+                105,
+                6000,
+                vec![4],
+                vec![career(
+                    "テスト株式会社",
+                    1,
+                    1,
+                    true,
+                    "contract",
+                    Some("デザイナー"),
+                    false,
+                )],
+            ),
+            ConsultantFixture {
+                is_bank_account_registered: false,
+                ..consultant(
+                    106,
+                    5000,
+                    vec![],
+                    vec![career(
+                        "テスト株式会社",
+                        1,
+                        7,
+                        true,
+                        "regular",
+                        Some("エンジニア"),
+                        false,
+                    )],
+                )
+            },
+            ConsultantFixture {
+                disabled: true,
+                ..consultant(
+                    107,
+                    5000,
+                    vec![5],
+                    vec![career(
+                        "テスト株式会社",
+                        1,
+                        7,
+                        true,
+                        "regular",
+                        Some("エンジニア"),
+                        false,
+                    )],
+                )
+            },
+            ConsultantFixture {
+                fee_per_hour_in_yen: None,
+                ..consultant(
+                    108,
+                    0,
+                    vec![],
+                    vec![career(
+                        "テスト株式会社",
+                        1,
+                        7,
+                        true,
+                        "regular",
+                        Some("エンジニア"),
+                        false,
+                    )],
+                )
+            },
+            consultant(109, 5000, vec![], vec![]),
+        ]
+    }
+
+    fn find_fixture_ids(fixtures: &[ConsultantFixture]) -> Vec<i64> {
+        fixtures.iter().map(|f| f.user_account_id).collect()
+    }
+
+    /// 在籍年数がcurrent_date時点でyears_of_serviceとなるように入社日（と退社日）を定める
+    fn create_career_models(
+        fixture: &ConsultantFixture,
+        current_date: NaiveDate,
+    ) -> Vec<entity::career::Model> {
+        fixture
+            .careers
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let (career_start_date, career_end_date) = if c.employed {
+                    (
+                        current_date - Days::new(365 * c.years_of_service + 30),
+                        None,
+                    )
+                } else {
+                    let end = current_date - Days::new(30);
+                    (end - Days::new(365 * c.years_of_service + 10), Some(end))
+                };
+                entity::career::Model {
+                    career_id: fixture.user_account_id * 10 + i as i64,
+                    user_account_id: fixture.user_account_id,
+                    company_name: c.company_name.to_string(),
+                    company_id: c.company_id,
+                    department_name: None,
+                    office: None,
+                    career_start_date,
+                    career_end_date,
+                    contract_type: c.contract_type.to_string(),
+                    profession: c.profession.map(|p| p.to_string()),
+                    annual_income_in_man_yen: None,
+                    is_manager: c.is_manager,
+                    position_name: None,
+                    is_new_graduate: false,
+                    note: None,
+                    verified_work_email_domain: None,
+                }
+            })
+            .collect()
+    }
+
+    fn create_empty_career_param() -> CareerParam {
+        CareerParam {
+            company_name: None,
+            company_id: None,
+            department_name: None,
+            office: None,
+            years_of_service: YearsOfServiceParam {
+                equal_or_more: None,
+                less_than: None,
+            },
+            employed: None,
+            contract_type: None,
+            profession: None,
+            annual_income_in_man_yen: AnnualInComeInManYenParam {
+                equal_or_more: None,
+                equal_or_less: None,
+            },
+            is_manager: None,
+            position_name: None,
+            is_new_graduate: None,
+            note: None,
+        }
+    }
+
+    fn create_request(
+        career_param: CareerParam,
+        fee_per_hour_in_yen_param: FeePerHourInYenParam,
+        sort: Option<(&str, &str)>,
+    ) -> ConsultantSearchRequest {
+        ConsultantSearchRequest {
+            account_id: ACCOUNT_ID,
+            career_param,
+            fee_per_hour_in_yen_param,
+            sort: sort.map(|(key, order)| Sort {
+                key: key.to_string(),
+                order: order.to_string(),
+            }),
+            include_aggregations: false,
+        }
+    }
+
+    fn no_fee_param() -> FeePerHourInYenParam {
+        FeePerHourInYenParam {
+            equal_or_more: None,
+            equal_or_less: None,
+        }
+    }
+
+    struct SearchTestCase {
+        name: &'static str,
+        request: ConsultantSearchRequest,
+        from: i64,
+        size: i64,
+        expected_ids: Vec<i64>,
+        expected_total: i64,
+    }
+
+    fn create_search_test_cases() -> Vec<SearchTestCase> {
+        let case = |name, request, from, size, expected_ids, expected_total| SearchTestCase {
+            name,
+            request,
+            from,
+            size,
+            expected_ids,
+            expected_total,
+        };
+        vec![
+            case(
+                "no criteria (sorted by user_account_id desc)",
+                create_request(create_empty_career_param(), no_fee_param(), None),
+                0,
+                20,
+                vec![105, 104, 103, 102, 101],
+                5,
+            ),
+            case(
+                "paging",
+                create_request(create_empty_career_param(), no_fee_param(), None),
+                1,
+                2,
+                vec![104, 103],
+                5,
+            ),
+            case(
+                "career filter (company_id)",
+                create_request(
+                    CareerParam {
+                        company_id: Some(1),
+                        ..create_empty_career_param()
+                    },
+                    no_fee_param(),
+                    Some(("fee_per_hour_in_yen", "asc")),
+                ),
+                0,
+                20,
+                vec![101, 105, 102],
+                3,
+            ),
+            case(
+                "career filter (company_name)",
+                create_request(
+                    CareerParam {
+                        company_name: Some("テスト".to_string()),
+                        ..create_empty_career_param()
+                    },
+                    no_fee_param(),
+                    Some(("fee_per_hour_in_yen", "asc")),
+                ),
+                0,
+                20,
+                vec![101, 105, 102, 104],
+                4,
+            ),
+            case(
+                "career filter (years_of_service and employed)",
+                create_request(
+                    CareerParam {
+                        years_of_service: YearsOfServiceParam {
+                            equal_or_more: Some(5),
+                            less_than: Some(15),
+                        },
+                        employed: Some(true),
+                        ..create_empty_career_param()
+                    },
+                    no_fee_param(),
+                    Some(("fee_per_hour_in_yen", "desc")),
+                ),
+                0,
+                20,
+                vec![102, 101],
+                2,
+            ),
+            case(
+                "career filter (is_manager)",
+                create_request(
+                    CareerParam {
+                        is_manager: Some(true),
+                        ..create_empty_career_param()
+                    },
+                    no_fee_param(),
+                    Some(("fee_per_hour_in_yen", "asc")),
+                ),
+                0,
+                20,
+                vec![102, 104],
+                2,
+            ),
+            case(
+                "fee range",
+                create_request(
+                    create_empty_career_param(),
+                    FeePerHourInYenParam {
+                        equal_or_more: Some(5000),
+                        equal_or_less: Some(8000),
+                    },
+                    Some(("fee_per_hour_in_yen", "desc")),
+                ),
+                0,
+                20,
+                vec![102, 105, 101],
+                3,
+            ),
+            case(
+                "sort by rating desc (no rating last)",
+                create_request(
+                    create_empty_career_param(),
+                    no_fee_param(),
+                    Some(("rating", "desc")),
+                ),
+                0,
+                20,
+                vec![101, 105, 102, 104, 103],
+                5,
+            ),
+            case(
+                "sort by rating asc (no rating last)",
+                create_request(
+                    create_empty_career_param(),
+                    no_fee_param(),
+                    Some(("rating", "asc")),
+                ),
+                0,
+                20,
+                vec![104, 102, 105, 101, 103],
+                5,
+            ),
+            case(
+                "paging sorted by rating",
+                create_request(
+                    create_empty_career_param(),
+                    no_fee_param(),
+                    Some(("rating", "desc")),
+                ),
+                2,
+                2,
+                vec![102, 104],
+                5,
+            ),
+            case(
+                "sort by max_years_of_service desc",
+                create_request(
+                    create_empty_career_param(),
+                    no_fee_param(),
+                    Some(("max_years_of_service", "desc")),
+                ),
+                0,
+                20,
+                vec![104, 102, 101, 103, 105],
+                5,
+            ),
+            case(
+                "sort by joined_at asc",
+                create_request(
+                    create_empty_career_param(),
+                    no_fee_param(),
+                    Some(("joined_at", "asc")),
+                ),
+                0,
+                20,
+                vec![101, 102, 103, 104, 105],
+                5,
+            ),
+        ]
+    }
+
+    fn extract_ids(result: &Value) -> Vec<i64> {
+        result["hits"]["hits"]
+            .as_array()
+            .expect("failed to get hits")
+            .iter()
+            .map(|hit| {
+                hit["_source"]["user_account_id"]
+                    .as_i64()
+                    .expect("failed to get user_account_id")
+            })
+            .collect()
+    }
+
+    fn extract_total(result: &Value) -> i64 {
+        result["hits"]["total"]["value"]
+            .as_i64()
+            .expect("failed to get total")
+    }
+
+    /// バックエンドによって異なる項目（sum_other_doc_count、rangeのkey等）を除き、比較する値（key、doc_count、consultants.doc_count）のみを取り出す
+    fn extract_terms_buckets(aggregation: &Value) -> Vec<(String, i64, i64)> {
+        aggregation["buckets"]
+            .as_array()
+            .expect("failed to get buckets")
+            .iter()
+            .map(|b| {
+                let key = match b["key_as_string"].as_str() {
+                    Some(key) => key.to_string(),
+                    None => b["key"].as_str().expect("failed to get key").to_string(),
+                };
+                (
+                    key,
+                    b["doc_count"].as_i64().expect("failed to get doc_count"),
+                    b["consultants"]["doc_count"]
+                        .as_i64()
+                        .expect("failed to get doc_count of consultants"),
+                )
+            })
+            .collect()
+    }
+
+    fn extract_range_doc_counts(aggregation: &Value) -> Vec<i64> {
+        aggregation["buckets"]
+            .as_array()
+            .expect("failed to get buckets")
+            .iter()
+            .map(|b| b["doc_count"].as_i64().expect("failed to get doc_count"))
+            .collect()
+    }
+
+    fn terms(buckets: &[(&str, i64, i64)]) -> Vec<(String, i64, i64)> {
+        buckets
+            .iter()
+            .map(|(key, doc_count, consultants)| (key.to_string(), *doc_count, *consultants))
+            .collect()
+    }
+
+    /// 全ての確認をバックエンドに対して行う。OpenSearchとDBで同じ期待値を利用する
+    async fn assert_backend_returns_expected_results(client: &ConsultantSearchClient) {
+        for case in create_search_test_cases() {
+            let result = client
+                .search_consultants(&case.request, case.from, case.size)
+                .await
+                .unwrap_or_else(|e| panic!("{}: {:?}", case.name, e));
+            assert_eq!(case.expected_ids, extract_ids(&result), "{}", case.name);
+            assert_eq!(case.expected_total, extract_total(&result), "{}", case.name);
+        }
+
+        let mut request = create_request(create_empty_career_param(), no_fee_param(), None);
+        request.include_aggregations = true;
+        let result = client
+            .search_consultants(&request, 0, 1)
+            .await
+            .expect("failed to get Ok");
+        let aggregations = &result["aggregations"];
+        assert_eq!(
+            terms(&[("エンジニア", 2, 2), ("営業", 2, 2), ("デザイナー", 1, 1)]),
+            extract_terms_buckets(&aggregations["careers"]["professions"])
+        );
+        assert_eq!(
+            terms(&[("regular", 3, 3), ("contract", 2, 2), ("other", 1, 1)]),
+            extract_terms_buckets(&aggregations["careers"]["contract_types"])
+        );
+        assert_eq!(
+            terms(&[("false", 4, 4), ("true", 2, 2)]),
+            extract_terms_buckets(&aggregations["careers"]["is_manager"])
+        );
+        assert_eq!(
+            vec![1, 2, 1, 1],
+            extract_range_doc_counts(&aggregations["fee_per_hour_in_yen"])
+        );
+        assert_eq!(
+            vec![1, 0, 1, 2],
+            extract_range_doc_counts(&aggregations["rating"])
+        );
+
+        let result = client
+            .search_consultant(101, ACCOUNT_ID)
+            .await
+            .expect("failed to get Ok");
+        assert_eq!(vec![101], extract_ids(&result));
+        let result = client
+            .search_consultant(106, ACCOUNT_ID)
+            .await
+            .expect("failed to get Ok");
+        assert_eq!(Vec::<i64>::new(), extract_ids(&result));
+
+        let result = client
+            .search_consultants_by_ids(&[103, 106, ACCOUNT_ID, 101, 109], ACCOUNT_ID)
+            .await
+            .expect("failed to get Ok");
+        // 並び順は定めないため、並び替えて比較する
+        let mut ids = extract_ids(&result);
+        ids.sort();
+        assert_eq!(vec![101, 103], ids);
+
+        // スコアの計算方法はバックエンドによって異なる（モジュールdatabaseのドキュメントを参照）ため、ヒットしたコンサルタントのみを比較する
+        let career_features = vec![CareerFeature {
+            company_id: Some(1),
+            profession: Some("エンジニア".to_string()),
+            contract_type: "regular".to_string(),
+            is_manager: false,
+            years_of_service_equal_or_more: 5,
+            years_of_service_less_than: Some(10),
+        }];
+        let result = client
+            .search_similar_consultants(101, ACCOUNT_ID, &career_features, 10)
+            .await
+            .expect("failed to get Ok");
+        let mut ids = extract_ids(&result);
+        ids.sort();
+        assert_eq!(vec![102, 103, 105], ids);
+
+        let result = client
+            .search_suggestions(ACCOUNT_ID, "テスト")
+            .await
+            .expect("failed to get Ok");
+        let aggregations = &result["aggregations"]["careers"];
+        assert_eq!(
+            terms(&[("テスト株式会社", 3, 3), ("テスト工業", 1, 1)]),
+            extract_terms_buckets(&aggregations["company_names"]["values"])
+        );
+        assert_eq!(
+            terms(&[]),
+            extract_terms_buckets(&aggregations["professions"]["values"])
+        );
+        // カタカナとひらがなは区別しない
+        let result = client
+            .search_suggestions(ACCOUNT_ID, "え")
+            .await
+            .expect("failed to get Ok");
+        let aggregations = &result["aggregations"]["careers"];
+        assert_eq!(
+            terms(&[]),
+            extract_terms_buckets(&aggregations["company_names"]["values"])
+        );
+        assert_eq!(
+            terms(&[("エンジニア", 2, 2)]),
+            extract_terms_buckets(&aggregations["professions"]["values"])
+        );
+    }
+
+    fn find_current_date() -> NaiveDate {
+        Utc::now()
+            .with_timezone(&(*JAPANESE_TIME_ZONE))
+            .date_naive()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn database_backend_returns_expected_results() {
+        let url = var(KEY_TO_TEST_DATABASE_URL).unwrap_or_else(|_| {
+            panic!(
+                "environment variable \"{}\" must be set",
+                KEY_TO_TEST_DATABASE_URL
+            )
+        });
+        let pool = Database::connect(url)
+            .await
+            .expect("failed to connect database");
+        let fixtures = create_consultant_fixtures();
+        let ids = find_fixture_ids(&fixtures);
+        // 前回の実行が途中で失敗した場合に残ったデータを削除する
+        delete_fixtures_from_database(&pool, &ids).await;
+        let num_of_users = entity::user_account::Entity::find()
+            .count(&pool)
+            .await
+            .expect("failed to count user_account");
+        assert_eq!(
+            0, num_of_users,
+            "database for this test must not have any user_account"
+        );
+        insert_fixtures_into_database(&pool, &fixtures, find_current_date()).await;
+
+        let client = ConsultantSearchClient::new_database(pool.clone());
+        assert_backend_returns_expected_results(&client).await;
+
+        delete_fixtures_from_database(&pool, &ids).await;
+    }
+
+    async fn insert_fixtures_into_database(
+        pool: &DatabaseConnection,
+        fixtures: &[ConsultantFixture],
+        current_date: NaiveDate,
+    ) {
+        let now = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        for fixture in fixtures {
+            let id = fixture.user_account_id;
+            entity::user_account::ActiveModel {
+                user_account_id: Set(id),
+                email_address: Set(format!("consultant{}@test.com", id)),
+                hashed_password: Set(b"dummy".to_vec()),
+                last_login_time: Set(None),
+                created_at: Set(now),
+                mfa_enabled_at: Set(None),
+                disabled_at: Set(fixture.disabled.then_some(now)),
+            }
+            .insert(pool)
+            .await
+            .expect("failed to insert user_account");
+            for career in create_career_models(fixture, current_date) {
+                entity::career::ActiveModel::from(career)
+                    .insert(pool)
+                    .await
+                    .expect("failed to insert career");
+            }
+            if let Some(fee_per_hour_in_yen) = fixture.fee_per_hour_in_yen {
+                entity::consulting_fee::ActiveModel {
+                    user_account_id: Set(id),
+                    fee_per_hour_in_yen: Set(fee_per_hour_in_yen),
+                }
+                .insert(pool)
+                .await
+                .expect("failed to insert consulting_fee");
+            }
+            if fixture.is_bank_account_registered {
+                entity::bank_account::ActiveModel {
+                    user_account_id: Set(id),
+                    bank_code: Set("0001".to_string()),
+                    branch_code: Set("001".to_string()),
+                    account_type: Set("普通".to_string()),
+                    account_number: Set("1234567".to_string()),
+                    account_holder_name: Set("タナカ タロウ".to_string()),
+                }
+                .insert(pool)
+                .await
+                .expect("failed to insert bank_account");
+            }
+            for (i, rating) in fixture.ratings.iter().enumerate() {
+                entity::consultant_rating::ActiveModel {
+                    consultation_id: Set(id * 10 + i as i64),
+                    user_account_id: Set(ACCOUNT_ID),
+                    consultant_id: Set(id),
+                    meeting_at: Set(now),
+                    rating: Set(Some(*rating)),
+                    rated_at: Set(Some(now)),
+                }
+                .insert(pool)
+                .await
+                .expect("failed to insert consultant_rating");
+            }
+        }
+    }
+
+    async fn delete_fixtures_from_database(pool: &DatabaseConnection, ids: &[i64]) {
+        entity::consultant_rating::Entity::delete_many()
+            .filter(entity::consultant_rating::Column::ConsultantId.is_in(ids.to_vec()))
+            .exec(pool)
+            .await
+            .expect("failed to delete consultant_rating");
+        entity::bank_account::Entity::delete_many()
+            .filter(entity::bank_account::Column::UserAccountId.is_in(ids.to_vec()))
+            .exec(pool)
+            .await
+            .expect("failed to delete bank_account");
+        entity::consulting_fee::Entity::delete_many()
+            .filter(entity::consulting_fee::Column::UserAccountId.is_in(ids.to_vec()))
+            .exec(pool)
+            .await
+            .expect("failed to delete consulting_fee");
+        entity::career::Entity::delete_many()
+            .filter(entity::career::Column::UserAccountId.is_in(ids.to_vec()))
+            .exec(pool)
+            .await
+            .expect("failed to delete career");
+        entity::user_account::Entity::delete_many()
+            .filter(entity::user_account::Column::UserAccountId.is_in(ids.to_vec()))
+            .exec(pool)
+            .await
+            .expect("failed to delete user_account");
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn opensearch_backend_returns_expected_results() {
+        let endpoint_uri = var(KEY_TO_TEST_OPENSEARCH_ENDPOINT_URI).unwrap_or_else(|_| {
+            panic!(
+                "environment variable \"{}\" must be set",
+                KEY_TO_TEST_OPENSEARCH_ENDPOINT_URI
+            )
+        });
+        let index_client =
+            create_client(&endpoint_uri, false, "", "").expect("failed to create client");
+        let fixtures = create_consultant_fixtures();
+        let ids = find_fixture_ids(&fixtures);
+        // 前回の実行が途中で失敗した場合に残ったインデックスを削除する（このテストのドキュメント以外を含む場合は削除しない）
+        if index_exists(&index_client).await {
+            let num_of_other_documents = count_documents_except(&index_client, &ids).await;
+            assert_eq!(
+                0, num_of_other_documents,
+                "opensearch for this test must not have \"{}\" index",
+                INDEX_NAME
+            );
+            delete_index(&index_client).await;
+        }
+        let response = index_client
+            .indices()
+            .create(IndicesCreateParts::Index(INDEX_NAME))
+            .body(create_index_definition())
+            .send()
+            .await
+            .expect("failed to create index");
+        assert!(response.status_code().is_success(), "{:?}", response);
+        let current_date = find_current_date();
+        for fixture in &fixtures {
+            let careers = create_career_models(fixture, current_date);
+            let careers = careers.iter().collect::<Vec<&entity::career::Model>>();
+            let document = create_document_source(
+                fixture.user_account_id,
+                &careers,
+                fixture.fee_per_hour_in_yen,
+                fixture.is_bank_account_registered,
+                fixture.ratings.clone(),
+                fixture.disabled,
+                current_date,
+            );
+            index_document(
+                INDEX_NAME,
+                &fixture.user_account_id.to_string(),
+                &document,
+                &index_client,
+            )
+            .await
+            .expect("failed to index document");
+        }
+        let response = index_client
+            .indices()
+            .refresh(IndicesRefreshParts::Index(&[INDEX_NAME]))
+            .send()
+            .await
+            .expect("failed to refresh index");
+        assert!(response.status_code().is_success(), "{:?}", response);
+
+        let client = ConsultantSearchClient::new(index_client.clone());
+        assert_backend_returns_expected_results(&client).await;
+
+        delete_index(&index_client).await;
+    }
+
+    async fn index_exists(index_client: &OpenSearch) -> bool {
+        let response = index_client
+            .indices()
+            .exists(IndicesExistsParts::Index(&[INDEX_NAME]))
+            .send()
+            .await
+            .expect("failed to check index");
+        response.status_code().is_success()
+    }
+
+    async fn count_documents_except(index_client: &OpenSearch, ids: &[i64]) -> i64 {
+        let response = index_client
+            .count(CountParts::Index(&[INDEX_NAME]))
+            .body(json!({
+                "query": {
+                    "bool": {
+                        "must_not": [
+                            {
+                                "terms": {
+                                    "user_account_id": ids
+                                }
+                            }
+                        ]
+                    }
+                }
+            }))
+            .send()
+            .await
+            .expect("failed to count documents");
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("failed to parse response");
+        body["count"].as_i64().expect("failed to get count")
+    }
+
+    async fn delete_index(index_client: &OpenSearch) {
+        let response = index_client
+            .indices()
+            .delete(IndicesDeleteParts::Index(&[INDEX_NAME]))
+            .send()
+            .await
+            .expect("failed to delete index");
+        assert!(response.status_code().is_success(), "{:?}", response);
+    }
+}
@@ -0,0 +1,1287 @@
+// Copyright 2023 Ken Miura
+
+//! DBの内容からドキュメントを作成し、OpenSearchと同じ条件で検索するバックエンド
+//!
+//! 絞り込み、並び替え、ページング及び集計はSQLで行い、返却するページに含まれるコンサルタントのドキュメントのみを[create_document_source]で作成する。
+//! ローカル環境やE2Eテストのような、OpenSearchを用意しない環境での利用のみを想定している。
+//!
+//! OpenSearchとの違いは下記の通り
+//! <ul>
+//!   <li>文字列の検索条件は、ngramによるフレーズ検索の代わりに正規化した文字列の部分一致で判定する（正規化は[crate::opensearch::normalization]と同等の処理をSQLで行う。ただし、小文字化はASCII文字のみを対象とする）</li>
+//!   <li>関連度（_score）は計算しない（全てのドキュメントで同じ値となるため、関連度による並び替えはuser_account_idの降順と同じ結果になる）</li>
+//!   <li>Point in Timeは存在しない（カーソルによるページングの間にDBの内容が変わった場合、検索結果がずれることがある）</li>
+//!   <li>似たコンサルタントのスコアは、一致した項目の重み（[crate::opensearch::consultant_query::COMPANY_BOOST]等）の合計とする（BM25による補正は行わない）。また、職種は正規化した文字列の完全一致で判定する</li>
+//!   <li>入力補完の候補は、edge_ngramの代わりに正規化した文字列の前方一致で判定する</li>
+//! </ul>
+
+use std::time::Instant;
+
+use axum::{http::StatusCode, Json};
+use chrono::{NaiveDate, Utc};
+use entity::sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QueryResult, Statement,
+};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use tracing::error;
+
+use super::ConsultantSearchRequest;
+use crate::err::Code;
+use crate::opensearch::consultant_query::{
+    create_ranges, create_sort_json, CareerFeature, CareerParam, FeePerHourInYenParam,
+    COMPANY_BOOST, CONTRACT_TYPE_BOOST, FEE_PER_HOUR_IN_YEN_BOUNDARIES, IS_MANAGER_BOOST,
+    MAX_NUM_OF_PROFESSION_BUCKETS, MAX_NUM_OF_SUGGESTIONS, NUM_OF_CONTRACT_TYPES, PROFESSION_BOOST,
+    RATING_BOUNDARIES, YEARS_OF_SERVICE_BOOST,
+};
+use crate::opensearch::normalization::{
+    normalize_company_name, normalize_text, LEGAL_ENTITY_DESIGNATIONS,
+};
+use crate::opensearch::{create_document_source, INDEX_NAME};
+use crate::{ApiError, ErrResp, JAPANESE_TIME_ZONE};
+
+/// DBを検索するバックエンドで利用するPoint in TimeのID（DBにはPoint in Timeが存在しないため、固定の値を利用する）
+pub(super) const POINT_IN_TIME_ID: &str = "database";
+
+/// ngramのトークンの長さ（[crate::opensearch::index_definition]のja_ngram_tokenizerのmin_gram）
+///
+/// これより短い文字列はトークンに分割されず、OpenSearchでは何もヒットしないため、同じ扱いとする
+const MIN_GRAM: usize = 2;
+
+/// ァ（U+30A1）からヶ（U+30F6）までのカタカナ（[HIRAGANA]と同じ並びで対応している）
+static KATAKANA: Lazy<String> = Lazy::new(|| ('\u{30A1}'..='\u{30F6}').collect());
+/// ぁ（U+3041）からゖ（U+3096）までのひらがな
+static HIRAGANA: Lazy<String> = Lazy::new(|| ('\u{3041}'..='\u{3096}').collect());
+
+pub(super) async fn search_consultants(
+    pool: &DatabaseConnection,
+    request: &ConsultantSearchRequest,
+    from: i64,
+    size: i64,
+    search_after: Option<&[Value]>,
+) -> Result<Value, ErrResp> {
+    let started_at = Instant::now();
+    let current_date = Utc::now()
+        .with_timezone(&(*JAPANESE_TIME_ZONE))
+        .date_naive();
+    let sort = create_sort_json(request.sort.as_ref());
+    let total = count_consultants(pool, request, current_date).await?;
+    let consultant_ids =
+        find_consultant_ids(pool, request, current_date, &sort, from, size, search_after).await?;
+    let hits = find_documents(pool, &consultant_ids, current_date)
+        .await?
+        .into_iter()
+        .map(|d| {
+            let sort_values = create_sort_values(&d, &sort);
+            create_hit(d, sort_values)
+        })
+        .collect();
+    let aggregations = if request.include_aggregations {
+        Some(find_aggregations(pool, request, current_date).await?)
+    } else {
+        None
+    };
+    let mut result = create_search_response(hits, total, aggregations);
+    result["took"] = json!(started_at.elapsed().as_millis() as i64);
+    Ok(result)
+}
+
+pub(super) async fn search_consultant(
+    pool: &DatabaseConnection,
+    consultant_id: i64,
+    account_id: i64,
+) -> Result<Value, ErrResp> {
+    let started_at = Instant::now();
+    let current_date = Utc::now()
+        .with_timezone(&(*JAPANESE_TIME_ZONE))
+        .date_naive();
+    let documents = find_documents(pool, &[consultant_id], current_date).await?;
+    let hits = documents
+        .into_iter()
+        .filter(|d| is_available(d, account_id))
+        .map(|d| create_hit(d, vec![]))
+        .collect::<Vec<Value>>();
+    let total = hits.len() as i64;
+    let mut result = create_search_response(hits, total, None);
+    result["took"] = json!(started_at.elapsed().as_millis() as i64);
+    Ok(result)
+}
+
+pub(super) async fn search_consultants_by_ids(
+    pool: &DatabaseConnection,
+    consultant_ids: &[i64],
+    account_id: i64,
+) -> Result<Value, ErrResp> {
+    let started_at = Instant::now();
+    let current_date = Utc::now()
+        .with_timezone(&(*JAPANESE_TIME_ZONE))
+        .date_naive();
+    let documents = find_documents(pool, consultant_ids, current_date).await?;
+    let hits = documents
+        .into_iter()
+        .filter(|d| is_available(d, account_id))
+        .map(|d| create_hit(d, vec![]))
+        .collect::<Vec<Value>>();
+    let total = hits.len() as i64;
+    let mut result = create_search_response(hits, total, None);
+    result["took"] = json!(started_at.elapsed().as_millis() as i64);
+    Ok(result)
+}
+
+pub(super) async fn search_similar_consultants(
+    pool: &DatabaseConnection,
+    consultant_id: i64,
+    account_id: i64,
+    career_features: &[CareerFeature],
+    size: i64,
+) -> Result<Value, ErrResp> {
+    let started_at = Instant::now();
+    let current_date = Utc::now()
+        .with_timezone(&(*JAPANESE_TIME_ZONE))
+        .date_naive();
+    let mut builder = SqlBuilder::default();
+    let cte = create_available_consultant_cte(&[account_id, consultant_id], &mut builder);
+    let current_date_placeholder = builder.bind(current_date);
+    let score =
+        create_similarity_score_sql(career_features, &current_date_placeholder, &mut builder);
+    let limit = builder.bind(size.max(0));
+    let sql = format!(
+        r"{}
+SELECT user_account_id, score, COUNT(*) OVER () AS total
+FROM (SELECT cs.user_account_id, {} AS score FROM consultant cs) s
+WHERE score > 0
+ORDER BY score DESC, user_account_id DESC
+LIMIT {}",
+        cte, score, limit
+    );
+    let rows = pool.query_all(builder.build(sql)).await.map_err(|e| {
+        error!(
+            "failed to find similar consultants (consultant_id: {}, account_id: {}, career_features: {:?}): {}",
+            consultant_id, account_id, career_features, e
+        );
+        unexpected_err_resp()
+    })?;
+    let mut scores = Vec::with_capacity(rows.len());
+    for row in rows.iter() {
+        let score: f64 = row.try_get("", "score").map_err(|e| {
+            error!("failed to get score: {}", e);
+            unexpected_err_resp()
+        })?;
+        scores.push((get_i64(row, "user_account_id")?, score));
+    }
+    let total = match rows.first() {
+        Some(row) => get_i64(row, "total")?,
+        None => 0,
+    };
+    let consultant_ids = scores.iter().map(|(id, _)| *id).collect::<Vec<i64>>();
+    let hits = find_documents(pool, &consultant_ids, current_date)
+        .await?
+        .into_iter()
+        .map(|d| {
+            let score = scores
+                .iter()
+                .find(|(id, _)| d["user_account_id"].as_i64() == Some(*id))
+                .map(|(_, score)| *score);
+            let mut hit = create_hit(d, vec![]);
+            hit["_score"] = json!(score);
+            hit
+        })
+        .collect();
+    let mut result = create_search_response(hits, total, None);
+    result["took"] = json!(started_at.elapsed().as_millis() as i64);
+    Ok(result)
+}
+
+pub(super) async fn search_suggestions(
+    pool: &DatabaseConnection,
+    account_id: i64,
+    prefix: &str,
+) -> Result<Value, ErrResp> {
+    let started_at = Instant::now();
+    let company_names = find_suggestions(pool, account_id, prefix, "company_name").await?;
+    let professions = find_suggestions(pool, account_id, prefix, "profession").await?;
+    let mut result = create_search_response(vec![], 0, None);
+    result["aggregations"] = json!({
+        "careers": {
+            "company_names": {
+                "values": company_names
+            },
+            "professions": {
+                "values": professions
+            }
+        }
+    });
+    result["took"] = json!(started_at.elapsed().as_millis() as i64);
+    Ok(result)
+}
+
+/// 検索条件に一致したコンサルタントの数を返す
+async fn count_consultants(
+    pool: &DatabaseConnection,
+    request: &ConsultantSearchRequest,
+    current_date: NaiveDate,
+) -> Result<i64, ErrResp> {
+    let mut builder = SqlBuilder::default();
+    let cte = create_consultant_cte(request, current_date, &mut builder);
+    let sql = format!("{}\nSELECT COUNT(*) AS total FROM consultant", cte);
+    let row = pool
+        .query_one(builder.build(sql))
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to count consultants (request: {:?}): {}",
+                request, e
+            );
+            unexpected_err_resp()
+        })?
+        .ok_or_else(|| {
+            error!("no count of consultants found (request: {:?})", request);
+            unexpected_err_resp()
+        })?;
+    get_i64(&row, "total")
+}
+
+/// 検索条件に一致したコンサルタントを並び替え、返却するページに含まれるコンサルタントのID（user_account_id）を順に返す
+async fn find_consultant_ids(
+    pool: &DatabaseConnection,
+    request: &ConsultantSearchRequest,
+    current_date: NaiveDate,
+    sort: &[Value],
+    from: i64,
+    size: i64,
+    search_after: Option<&[Value]>,
+) -> Result<Vec<i64>, ErrResp> {
+    let mut builder = SqlBuilder::default();
+    let cte = create_consultant_cte(request, current_date, &mut builder);
+    let search_after_condition = match search_after {
+        Some(search_after) => create_search_after_sql(sort, search_after, &mut builder)?,
+        None => "TRUE".to_string(),
+    };
+    let order_by = create_order_by_sql(sort)?;
+    let offset = builder.bind(from.max(0));
+    let limit = builder.bind(size.max(0));
+    let sql = format!(
+        "{}\nSELECT user_account_id FROM consultant WHERE {} ORDER BY {} OFFSET {} LIMIT {}",
+        cte, search_after_condition, order_by, offset, limit
+    );
+    let rows = pool.query_all(builder.build(sql)).await.map_err(|e| {
+        error!(
+            "failed to find consultant ids (request: {:?}, from: {}, size: {}, search_after: {:?}): {}",
+            request, from, size, search_after, e
+        );
+        unexpected_err_resp()
+    })?;
+    rows.iter()
+        .map(|row| get_i64(row, "user_account_id"))
+        .collect()
+}
+
+/// 指定したユーザー（user_account_ids）のうち、相談料を登録済みのユーザーのドキュメントをDBの内容から作成し、user_account_idsと同じ順序で返す
+///
+/// 相談を受け付けられるかどうか（職務経歴、銀行口座が登録済みで、アカウントが無効化されていない）の判定は、OpenSearchと同じくドキュメントに対して行う
+async fn find_documents(
+    pool: &DatabaseConnection,
+    user_account_ids: &[i64],
+    current_date: NaiveDate,
+) -> Result<Vec<Value>, ErrResp> {
+    if user_account_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let fees = entity::consulting_fee::Entity::find()
+        .filter(entity::consulting_fee::Column::UserAccountId.is_in(user_account_ids.to_vec()))
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find consulting_fee (user_account_ids: {:?}): {}",
+                user_account_ids, e
+            );
+            unexpected_err_resp()
+        })?;
+    let user_accounts = entity::user_account::Entity::find()
+        .filter(entity::user_account::Column::UserAccountId.is_in(user_account_ids.to_vec()))
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find user_account: {}", e);
+            unexpected_err_resp()
+        })?;
+    let careers = entity::career::Entity::find()
+        .filter(entity::career::Column::UserAccountId.is_in(user_account_ids.to_vec()))
+        .order_by_asc(entity::career::Column::CareerId)
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find career: {}", e);
+            unexpected_err_resp()
+        })?;
+    let bank_accounts = entity::bank_account::Entity::find()
+        .filter(entity::bank_account::Column::UserAccountId.is_in(user_account_ids.to_vec()))
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find bank_account: {}", e);
+            unexpected_err_resp()
+        })?;
+    let ratings = entity::consultant_rating::Entity::find()
+        .filter(entity::consultant_rating::Column::ConsultantId.is_in(user_account_ids.to_vec()))
+        .filter(entity::consultant_rating::Column::Rating.is_not_null())
+        .all(pool)
+        .await
+        .map_err(|e| {
+            error!("failed to find consultant_rating: {}", e);
+            unexpected_err_resp()
+        })?;
+
+    let mut documents = Vec::with_capacity(user_account_ids.len());
+    for user_account_id in user_account_ids {
+        let Some(fee) = fees.iter().find(|f| f.user_account_id == *user_account_id) else {
+            continue;
+        };
+        let Some(user_account) = user_accounts
+            .iter()
+            .find(|u| u.user_account_id == *user_account_id)
+        else {
+            continue;
+        };
+        let careers: Vec<&entity::career::Model> = careers
+            .iter()
+            .filter(|c| c.user_account_id == *user_account_id)
+            .collect();
+        let is_bank_account_registered = bank_accounts
+            .iter()
+            .any(|b| b.user_account_id == *user_account_id);
+        let ratings = ratings
+            .iter()
+            .filter(|r| r.consultant_id == *user_account_id)
+            .filter_map(|r| r.rating)
+            .collect();
+        documents.push(create_document_source(
+            *user_account_id,
+            &careers,
+            Some(fee.fee_per_hour_in_yen),
+            is_bank_account_registered,
+            ratings,
+            user_account.disabled_at.is_some(),
+            current_date,
+        ));
+    }
+    Ok(documents)
+}
+
+fn create_hit(document: Value, sort_values: Vec<Value>) -> Value {
+    json!({
+        "_index": INDEX_NAME,
+        "_id": document["user_account_id"].to_string(),
+        "_score": 1.0,
+        "_source": document,
+        "sort": sort_values
+    })
+}
+
+fn create_search_response(hits: Vec<Value>, total: i64, aggregations: Option<Value>) -> Value {
+    let mut response = json!({
+        "took": 0,
+        "timed_out": false,
+        "hits": {
+            "total": {
+                "value": total,
+                "relation": "eq"
+            },
+            "max_score": null,
+            "hits": hits
+        }
+    });
+    if let Some(aggregations) = aggregations {
+        response["aggregations"] = aggregations;
+    }
+    response
+}
+
+/// [crate::opensearch::consultant_query::create_available_consultant_filter_json]と同じ条件
+fn is_available(document: &Value, account_id: i64) -> bool {
+    document["user_account_id"].as_i64() != Some(account_id)
+        && document["num_of_careers"].as_i64().unwrap_or(0) > 0
+        && !document["fee_per_hour_in_yen"].is_null()
+        && document["is_bank_account_registered"].as_bool() == Some(true)
+        && document["disabled"].as_bool() == Some(false)
+}
+
+/// 値をバインドしながらSQLを組み立てる
+#[derive(Default)]
+struct SqlBuilder {
+    values: Vec<entity::sea_orm::Value>,
+}
+
+impl SqlBuilder {
+    /// 値を追加し、その値を参照するプレースホルダ（$1、$2、...）を返す
+    fn bind(&mut self, value: impl Into<entity::sea_orm::Value>) -> String {
+        self.values.push(value.into());
+        format!("${}", self.values.len())
+    }
+
+    fn build(self, sql: String) -> Statement {
+        Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, self.values)
+    }
+}
+
+/// 検索条件に一致したコンサルタントと、その並び替えに利用する値を持つconsultantを定義するWITH句を返す
+///
+/// 相談を受け付けられるかどうかの条件は[crate::opensearch::consultant_query::create_available_consultant_filter_json]と同じ。
+/// 職務経歴の条件はそれぞれ独立したnestedクエリのため、条件ごとに一致する職務経歴が異なっていても良い（条件ごとにEXISTSで判定する）
+fn create_consultant_cte(
+    request: &ConsultantSearchRequest,
+    current_date: NaiveDate,
+    builder: &mut SqlBuilder,
+) -> String {
+    let current_date = builder.bind(current_date);
+    let mut conditions = create_available_consultant_conditions(&[request.account_id], builder);
+    for criterion in create_career_criteria(&request.career_param) {
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM ccs_schema.career c WHERE c.user_account_id = ua.user_account_id AND {})",
+            criterion.create_sql(&current_date, builder)
+        ));
+    }
+    for criterion in create_fee_per_hour_in_yen_criteria(&request.fee_per_hour_in_yen_param) {
+        conditions.push(criterion.create_sql(&current_date, builder));
+    }
+    format!(
+        r"WITH consultant AS (
+  SELECT
+    ua.user_account_id,
+    cf.fee_per_hour_in_yen,
+    r.rating,
+    r.num_of_rated,
+    (SELECT MAX({}) FROM ccs_schema.career c WHERE c.user_account_id = ua.user_account_id) AS max_years_of_service
+  FROM ccs_schema.user_account ua
+  INNER JOIN ccs_schema.consulting_fee cf ON cf.user_account_id = ua.user_account_id
+  CROSS JOIN LATERAL (
+    SELECT AVG(cr.rating::DOUBLE PRECISION) AS rating, COUNT(*) AS num_of_rated
+    FROM ccs_schema.consultant_rating cr
+    WHERE cr.consultant_id = ua.user_account_id AND cr.rating IS NOT NULL
+  ) r
+  WHERE {}
+)",
+        create_years_of_service_sql(&current_date),
+        conditions.join("\n    AND ")
+    )
+}
+
+/// 相談を受け付けられるコンサルタント（consulting_feeと結合したuser_account（ua））の条件を返す。excluded_account_idsのユーザーは除外する
+///
+/// [crate::opensearch::consultant_query::create_available_consultant_filter_json]と同じ条件（相談料の登録はconsulting_feeとの結合で判定する）
+fn create_available_consultant_conditions(
+    excluded_account_ids: &[i64],
+    builder: &mut SqlBuilder,
+) -> Vec<String> {
+    let mut conditions = excluded_account_ids
+        .iter()
+        .map(|id| format!("ua.user_account_id <> {}", builder.bind(*id)))
+        .collect::<Vec<String>>();
+    conditions.push("ua.disabled_at IS NULL".to_string());
+    conditions.push(
+        "EXISTS (SELECT 1 FROM ccs_schema.career c WHERE c.user_account_id = ua.user_account_id)"
+            .to_string(),
+    );
+    conditions.push(
+        "EXISTS (SELECT 1 FROM ccs_schema.bank_account b WHERE b.user_account_id = ua.user_account_id)"
+            .to_string(),
+    );
+    conditions
+}
+
+/// 相談を受け付けられるコンサルタント（excluded_account_idsのユーザーを除く）のuser_account_idを持つconsultantを定義するWITH句を返す
+fn create_available_consultant_cte(
+    excluded_account_ids: &[i64],
+    builder: &mut SqlBuilder,
+) -> String {
+    let conditions = create_available_consultant_conditions(excluded_account_ids, builder);
+    format!(
+        r"WITH consultant AS (
+  SELECT ua.user_account_id
+  FROM ccs_schema.user_account ua
+  INNER JOIN ccs_schema.consulting_fee cf ON cf.user_account_id = ua.user_account_id
+  WHERE {}
+)",
+        conditions.join("\n    AND ")
+    )
+}
+
+/// コンサルタント（cs）の似ている度合いを返すSQLの式
+///
+/// [crate::opensearch::consultant_query::create_similar_consultants_query_json]と同様に、職務経歴の項目（career_features）ごとに
+/// コンサルタントの職務経歴のうち最も一致する職務経歴のスコア（一致した項目の重みの合計）を求め、その合計をスコアとする
+fn create_similarity_score_sql(
+    career_features: &[CareerFeature],
+    current_date: &str,
+    builder: &mut SqlBuilder,
+) -> String {
+    if career_features.is_empty() {
+        return "0.0::DOUBLE PRECISION".to_string();
+    }
+    career_features
+        .iter()
+        .map(|f| {
+            let mut terms = Vec::with_capacity(5);
+            if let Some(company_id) = f.company_id {
+                terms.push(create_boost_sql(
+                    &format!("c.company_id = {}", builder.bind(company_id)),
+                    COMPANY_BOOST,
+                ));
+            }
+            if let Some(profession) = f.profession.as_ref() {
+                terms.push(create_boost_sql(
+                    &format!(
+                        "{} = {}",
+                        create_normalize_text_sql("c.profession"),
+                        builder.bind(normalize_text(profession))
+                    ),
+                    PROFESSION_BOOST,
+                ));
+            }
+            terms.push(create_boost_sql(
+                &format!("c.contract_type = {}", builder.bind(f.contract_type.clone())),
+                CONTRACT_TYPE_BOOST,
+            ));
+            terms.push(create_boost_sql(
+                &format!("c.is_manager = {}", builder.bind(f.is_manager)),
+                IS_MANAGER_BOOST,
+            ));
+            let years_of_service = create_years_of_service_sql(current_date);
+            let mut years_of_service_condition = format!(
+                "{} >= {}",
+                years_of_service,
+                builder.bind(f.years_of_service_equal_or_more)
+            );
+            if let Some(less_than) = f.years_of_service_less_than {
+                years_of_service_condition = format!(
+                    "{} AND {} < {}",
+                    years_of_service_condition,
+                    years_of_service,
+                    builder.bind(less_than)
+                );
+            }
+            terms.push(create_boost_sql(
+                &years_of_service_condition,
+                YEARS_OF_SERVICE_BOOST,
+            ));
+            format!(
+                "COALESCE((SELECT MAX({}) FROM ccs_schema.career c WHERE c.user_account_id = cs.user_account_id), 0.0)",
+                terms.join(" + ")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" + ")
+}
+
+/// 条件に一致する場合に重み（boost）、一致しない場合（NULLを含む）に0を返すSQLの式
+fn create_boost_sql(condition: &str, boost: f64) -> String {
+    format!(
+        "(CASE WHEN {} THEN {:.1}::DOUBLE PRECISION ELSE 0.0::DOUBLE PRECISION END)",
+        condition, boost
+    )
+}
+
+/// 職務経歴の値（field）のうち、正規化した値がprefix（正規化したもの）に前方一致するものを、その値を持つコンサルタントの数の多い順（同数の場合は値の昇順）に返す
+///
+/// [crate::opensearch::consultant_query::create_suggestions_query_json]の集計結果（values）と同じ形式で返す
+async fn find_suggestions(
+    pool: &DatabaseConnection,
+    account_id: i64,
+    prefix: &str,
+    field: &str,
+) -> Result<Value, ErrResp> {
+    let mut builder = SqlBuilder::default();
+    let cte = create_available_consultant_cte(&[account_id], &mut builder);
+    let prefix_placeholder = builder.bind(normalize_text(prefix));
+    let limit = builder.bind(MAX_NUM_OF_SUGGESTIONS as i64);
+    let sql = format!(
+        r#"{0}
+SELECT c.{1} AS key, COUNT(*) AS doc_count, COUNT(DISTINCT c.user_account_id) AS num_of_consultants
+FROM ccs_schema.career c
+INNER JOIN consultant cs ON cs.user_account_id = c.user_account_id
+WHERE c.{1} IS NOT NULL AND starts_with({2}, {3})
+GROUP BY c.{1}
+ORDER BY num_of_consultants DESC, c.{1} COLLATE "C" ASC
+LIMIT {4}"#,
+        cte,
+        field,
+        create_normalize_text_sql(&format!("c.{}", field)),
+        prefix_placeholder,
+        limit
+    );
+    let rows = pool.query_all(builder.build(sql)).await.map_err(|e| {
+        error!(
+            "failed to find suggestions (account_id: {}, prefix: {}, field: {}): {}",
+            account_id, prefix, field, e
+        );
+        unexpected_err_resp()
+    })?;
+    let mut buckets = Vec::with_capacity(rows.len());
+    for row in rows {
+        let key: String = row.try_get("", "key").map_err(|e| {
+            error!("failed to get key: {}", e);
+            unexpected_err_resp()
+        })?;
+        buckets.push(json!({
+            "key": key,
+            "doc_count": get_i64(&row, "doc_count")?,
+            "consultants": { "doc_count": get_i64(&row, "num_of_consultants")? }
+        }));
+    }
+    Ok(json!({ "buckets": buckets }))
+}
+
+/// 職務経歴（c）の在籍年数を返すSQLの式（[crate::opensearch::calculate_time_dependent_career_fields]と同じ計算）
+fn create_years_of_service_sql(current_date: &str) -> String {
+    format!(
+        "((COALESCE(c.career_end_date, {}) - c.career_start_date) / 365)",
+        current_date
+    )
+}
+
+/// 職務経歴（c）が在籍中かどうかを返すSQLの式（[crate::opensearch::calculate_time_dependent_career_fields]と同じ判定）
+fn create_employed_sql() -> String {
+    "(c.career_end_date IS NULL)".to_string()
+}
+
+/// [normalize_text]と同じ正規化を行うSQLの式
+fn create_normalize_text_sql(expr: &str) -> String {
+    format!(
+        r"regexp_replace(regexp_replace(translate(lower(normalize({}, NFKC)), '{}', '{}'), '^\s+|\s+$', '', 'g'), '\s+', ' ', 'g')",
+        expr, *KATAKANA, *HIRAGANA
+    )
+}
+
+/// [normalize_company_name]と同じ正規化（前後に付く法人格の表記の除去）を行うSQLの式
+fn create_normalize_company_name_sql(expr: &str) -> String {
+    let normalized = create_normalize_text_sql(expr);
+    let designations = LEGAL_ENTITY_DESIGNATIONS
+        .iter()
+        .map(|d| escape_regex(d))
+        .collect::<Vec<String>>()
+        .join("|");
+    let stripped = format!(
+        r"regexp_replace(regexp_replace({}, '^(?:(?:{})\s*)+', ''), '[ ,]*(?:(?:{})[ ,]*)+$', '')",
+        normalized, designations, designations
+    );
+    // 法人格の表記を除去した結果が空文字となる場合、法人格の表記は除去しない
+    format!("COALESCE(NULLIF({}, ''), {})", stripped, normalized)
+}
+
+fn escape_regex(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            if r"\^$.|?*+()[]{}".contains(c) {
+                vec!['\\', c]
+            } else {
+                vec![c]
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    /// 正規化済みの文字列を部分一致で判定する
+    Text(String),
+    Equal(entity::sea_orm::Value),
+    EqualOrMore(i64),
+    LessThan(i64),
+    EqualOrLess(i64),
+}
+
+/// ドキュメント（または職務経歴）のフィールドに対する条件
+#[derive(Clone, Debug, PartialEq)]
+struct Criterion {
+    field: &'static str,
+    condition: Condition,
+}
+
+impl Criterion {
+    fn new(field: &'static str, condition: Condition) -> Self {
+        Self { field, condition }
+    }
+
+    /// 条件をSQLの式として返す。職務経歴のフィールドは職務経歴（c）の列、相談料は相談料（cf）の列を対象とする
+    fn create_sql(&self, current_date: &str, builder: &mut SqlBuilder) -> String {
+        let target = match (self.field, &self.condition) {
+            ("normalized_company_name", _) => create_normalize_company_name_sql("c.company_name"),
+            ("years_of_service", _) => create_years_of_service_sql(current_date),
+            ("employed", _) => create_employed_sql(),
+            ("fee_per_hour_in_yen", _) => "cf.fee_per_hour_in_yen".to_string(),
+            (field, Condition::Text(_)) => create_normalize_text_sql(&format!("c.{}::TEXT", field)),
+            (field, _) => format!("c.{}", field),
+        };
+        match &self.condition {
+            Condition::Text(query) => {
+                if query.chars().count() < MIN_GRAM {
+                    return "FALSE".to_string();
+                }
+                format!("strpos({}, {}) > 0", target, builder.bind(query.clone()))
+            }
+            Condition::Equal(expected) => {
+                format!("{} = {}", target, builder.bind(expected.clone()))
+            }
+            Condition::EqualOrMore(min) => format!("{} >= {}", target, builder.bind(*min)),
+            Condition::LessThan(max) => format!("{} < {}", target, builder.bind(*max)),
+            Condition::EqualOrLess(max) => format!("{} <= {}", target, builder.bind(*max)),
+        }
+    }
+}
+
+/// [crate::opensearch::consultant_query::create_query_json]で職務経歴に対して指定する条件と同じ条件を同じ順序で返す
+fn create_career_criteria(career_param: &CareerParam) -> Vec<Criterion> {
+    let mut criteria = Vec::new();
+    if let Some(company_name) = career_param.company_name.as_ref() {
+        criteria.push(Criterion::new(
+            "normalized_company_name",
+            Condition::Text(normalize_company_name(company_name)),
+        ));
+    }
+    if let Some(company_id) = career_param.company_id {
+        criteria.push(Criterion::new(
+            "company_id",
+            Condition::Equal(company_id.into()),
+        ));
+    }
+    if let Some(department_name) = career_param.department_name.as_ref() {
+        criteria.push(Criterion::new(
+            "department_name",
+            Condition::Text(normalize_text(department_name)),
+        ));
+    }
+    if let Some(office) = career_param.office.as_ref() {
+        criteria.push(Criterion::new(
+            "office",
+            Condition::Text(normalize_text(office)),
+        ));
+    }
+    if let Some(equal_or_more) = career_param.years_of_service.equal_or_more {
+        criteria.push(Criterion::new(
+            "years_of_service",
+            Condition::EqualOrMore(equal_or_more as i64),
+        ));
+    }
+    if let Some(less_than) = career_param.years_of_service.less_than {
+        criteria.push(Criterion::new(
+            "years_of_service",
+            Condition::LessThan(less_than as i64),
+        ));
+    }
+    if let Some(employed) = career_param.employed {
+        criteria.push(Criterion::new(
+            "employed",
+            Condition::Equal(employed.into()),
+        ));
+    }
+    if let Some(contract_type) = career_param.contract_type.as_ref() {
+        criteria.push(Criterion::new(
+            "contract_type",
+            Condition::Text(normalize_text(contract_type)),
+        ));
+    }
+    if let Some(profession) = career_param.profession.as_ref() {
+        criteria.push(Criterion::new(
+            "profession",
+            Condition::Text(normalize_text(profession)),
+        ));
+    }
+    if let Some(equal_or_more) = career_param.annual_income_in_man_yen.equal_or_more {
+        criteria.push(Criterion::new(
+            "annual_income_in_man_yen",
+            Condition::EqualOrMore(equal_or_more as i64),
+        ));
+    }
+    if let Some(equal_or_less) = career_param.annual_income_in_man_yen.equal_or_less {
+        criteria.push(Criterion::new(
+            "annual_income_in_man_yen",
+            Condition::EqualOrLess(equal_or_less as i64),
+        ));
+    }
+    if let Some(is_manager) = career_param.is_manager {
+        criteria.push(Criterion::new(
+            "is_manager",
+            Condition::Equal(is_manager.into()),
+        ));
+    }
+    if let Some(position_name) = career_param.position_name.as_ref() {
+        criteria.push(Criterion::new(
+            "position_name",
+            Condition::Text(normalize_text(position_name)),
+        ));
+    }
+    if let Some(is_new_graduate) = career_param.is_new_graduate {
+        criteria.push(Criterion::new(
+            "is_new_graduate",
+            Condition::Equal(is_new_graduate.into()),
+        ));
+    }
+    if let Some(note) = career_param.note.as_ref() {
+        criteria.push(Criterion::new(
+            "note",
+            Condition::Text(normalize_text(note)),
+        ));
+    }
+    criteria
+}
+
+fn create_fee_per_hour_in_yen_criteria(
+    fee_per_hour_in_yen_param: &FeePerHourInYenParam,
+) -> Vec<Criterion> {
+    let mut criteria = Vec::new();
+    if let Some(equal_or_more) = fee_per_hour_in_yen_param.equal_or_more {
+        criteria.push(Criterion::new(
+            "fee_per_hour_in_yen",
+            Condition::EqualOrMore(equal_or_more as i64),
+        ));
+    }
+    if let Some(equal_or_less) = fee_per_hour_in_yen_param.equal_or_less {
+        criteria.push(Criterion::new(
+            "fee_per_hour_in_yen",
+            Condition::EqualOrLess(equal_or_less as i64),
+        ));
+    }
+    criteria
+}
+
+/// [create_sort_json]で作成したソートの条件それぞれについて、ドキュメントの値（OpenSearchの検索結果の各ヒットに含まれるsortの値）を返す
+fn create_sort_values(document: &Value, sort: &[Value]) -> Vec<Value> {
+    sort.iter()
+        .map(|criterion| match find_sort_key(criterion) {
+            "_score" => json!(1.0),
+            "careers.years_of_service" => document["careers"]
+                .as_array()
+                .and_then(|careers| {
+                    careers
+                        .iter()
+                        .filter_map(|c| c["years_of_service"].as_i64())
+                        .max()
+                })
+                .map(|max| json!(max))
+                .unwrap_or(Value::Null),
+            key => document[key].clone(),
+        })
+        .collect()
+}
+
+fn find_sort_key(criterion: &Value) -> &str {
+    criterion
+        .as_object()
+        .and_then(|o| o.keys().next())
+        .map(|k| k.as_str())
+        .unwrap_or("")
+}
+
+fn is_desc(criterion: &Value) -> bool {
+    criterion[find_sort_key(criterion)]["order"].as_str() == Some("desc")
+}
+
+/// ソートの条件に対応するconsultantの列を返す
+///
+/// 関連度（_score）は全てのドキュメントで同じ値となり並び順に影響しないため、Noneを返す
+fn find_sort_column(criterion: &Value) -> Result<Option<&'static str>, ErrResp> {
+    match find_sort_key(criterion) {
+        "_score" => Ok(None),
+        "careers.years_of_service" => Ok(Some("max_years_of_service")),
+        "fee_per_hour_in_yen" => Ok(Some("fee_per_hour_in_yen")),
+        "rating" => Ok(Some("rating")),
+        "num_of_rated" => Ok(Some("num_of_rated")),
+        "user_account_id" => Ok(Some("user_account_id")),
+        key => {
+            error!("unexpected sort key: {}", key);
+            Err(unexpected_err_resp())
+        }
+    }
+}
+
+/// OpenSearchと同様に、値の存在しないもの（NULL）は昇順、降順によらず最後に並べる
+fn create_order_by_sql(sort: &[Value]) -> Result<String, ErrResp> {
+    let mut order_by = Vec::with_capacity(sort.len());
+    for criterion in sort {
+        if let Some(column) = find_sort_column(criterion)? {
+            let order = if is_desc(criterion) { "DESC" } else { "ASC" };
+            order_by.push(format!("{} {} NULLS LAST", column, order));
+        }
+    }
+    Ok(order_by.join(", "))
+}
+
+/// [create_order_by_sql]の並び順で、search_after（sortの値）より後ろに並ぶものを絞り込む条件を返す
+///
+/// NULLは昇順、降順によらず最後に並ぶため、search_afterの値がNULLでない場合はNULLのものも後ろに並ぶ。
+/// search_afterの値が数値でない場合、NULLとして扱う（OpenSearchのsortの値は全て数値かNULLとなる）
+fn create_search_after_sql(
+    sort: &[Value],
+    search_after: &[Value],
+    builder: &mut SqlBuilder,
+) -> Result<String, ErrResp> {
+    let mut columns = Vec::with_capacity(sort.len());
+    for (i, criterion) in sort.iter().enumerate() {
+        if let Some(column) = find_sort_column(criterion)? {
+            let value = search_after.get(i).and_then(|v| v.as_f64());
+            columns.push((column, is_desc(criterion), value));
+        }
+    }
+    // 後ろの条件から順に、前の条件の値が等しい場合の条件として組み立てる
+    let mut condition = "FALSE".to_string();
+    for (column, desc, value) in columns.into_iter().rev() {
+        condition = match value {
+            Some(value) => {
+                let value = builder.bind(value);
+                let operator = if desc { "<" } else { ">" };
+                format!(
+                    "({0} IS NULL OR {0}::DOUBLE PRECISION {1} {2} OR ({0}::DOUBLE PRECISION = {2} AND {3}))",
+                    column, operator, value, condition
+                )
+            }
+            None => format!("({} IS NULL AND {})", column, condition),
+        };
+    }
+    Ok(condition)
+}
+
+/// [crate::opensearch::consultant_query::create_aggregations_json]の集計結果と同じ形式で返す
+async fn find_aggregations(
+    pool: &DatabaseConnection,
+    request: &ConsultantSearchRequest,
+    current_date: NaiveDate,
+) -> Result<Value, ErrResp> {
+    let professions = find_terms_aggregation(
+        pool,
+        request,
+        current_date,
+        "profession",
+        MAX_NUM_OF_PROFESSION_BUCKETS,
+    )
+    .await?;
+    let contract_types = find_terms_aggregation(
+        pool,
+        request,
+        current_date,
+        "contract_type",
+        NUM_OF_CONTRACT_TYPES,
+    )
+    .await?;
+    let is_manager = find_terms_aggregation(pool, request, current_date, "is_manager", 2).await?;
+
+    let fee_ranges = create_ranges(&FEE_PER_HOUR_IN_YEN_BOUNDARIES);
+    let rating_ranges = create_ranges(&RATING_BOUNDARIES);
+    let mut builder = SqlBuilder::default();
+    let cte = create_consultant_cte(request, current_date, &mut builder);
+    let mut columns = vec!["(SELECT COUNT(*) FROM ccs_schema.career c INNER JOIN consultant cs ON cs.user_account_id = c.user_account_id) AS num_of_careers".to_string()];
+    columns.extend(create_range_count_sql(
+        "fee_per_hour_in_yen",
+        &fee_ranges,
+        &mut builder,
+    ));
+    columns.extend(create_range_count_sql(
+        "rating",
+        &rating_ranges,
+        &mut builder,
+    ));
+    let sql = format!("{}\nSELECT {} FROM consultant", cte, columns.join(", "));
+    let row = pool
+        .query_one(builder.build(sql))
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find aggregations (request: {:?}): {}",
+                request, e
+            );
+            unexpected_err_resp()
+        })?
+        .ok_or_else(|| {
+            error!("no aggregations found (request: {:?})", request);
+            unexpected_err_resp()
+        })?;
+
+    Ok(json!({
+        "careers": {
+            "doc_count": get_i64(&row, "num_of_careers")?,
+            "professions": professions,
+            "contract_types": contract_types,
+            "is_manager": is_manager
+        },
+        "fee_per_hour_in_yen": create_range_buckets(&row, "fee_per_hour_in_yen", &fee_ranges)?,
+        "rating": create_range_buckets(&row, "rating", &rating_ranges)?
+    }))
+}
+
+/// 職務経歴の値ごとの職務経歴の数（doc_count）とコンサルタントの数（consultants.doc_count）
+///
+/// OpenSearchのtermsと同様に、職務経歴の数の多い順（同数の場合は値の昇順）にsize件返す
+async fn find_terms_aggregation(
+    pool: &DatabaseConnection,
+    request: &ConsultantSearchRequest,
+    current_date: NaiveDate,
+    field: &str,
+    size: u32,
+) -> Result<Value, ErrResp> {
+    let mut builder = SqlBuilder::default();
+    let cte = create_consultant_cte(request, current_date, &mut builder);
+    let limit = builder.bind(size as i64);
+    let sql = format!(
+        r#"{0}
+SELECT c.{1}::TEXT AS key, COUNT(*) AS doc_count, COUNT(DISTINCT c.user_account_id) AS num_of_consultants
+FROM ccs_schema.career c
+INNER JOIN consultant cs ON cs.user_account_id = c.user_account_id
+WHERE c.{1} IS NOT NULL
+GROUP BY c.{1}
+ORDER BY doc_count DESC, c.{1}::TEXT COLLATE "C" ASC
+LIMIT {2}"#,
+        cte, field, limit
+    );
+    let rows = pool.query_all(builder.build(sql)).await.map_err(|e| {
+        error!(
+            "failed to find terms aggregation (request: {:?}, field: {}): {}",
+            request, field, e
+        );
+        unexpected_err_resp()
+    })?;
+    let mut buckets = Vec::with_capacity(rows.len());
+    for row in rows {
+        let key: String = row.try_get("", "key").map_err(|e| {
+            error!("failed to get key: {}", e);
+            unexpected_err_resp()
+        })?;
+        let mut bucket = json!({
+            "key": key,
+            "doc_count": get_i64(&row, "doc_count")?,
+            "consultants": { "doc_count": get_i64(&row, "num_of_consultants")? }
+        });
+        // booleanのフィールドのkeyは1または0となり、文字列表現はkey_as_stringに格納される
+        if key == "true" || key == "false" {
+            bucket["key"] = json!(if key == "true" { 1 } else { 0 });
+            bucket["key_as_string"] = json!(key);
+        }
+        buckets.push(bucket);
+    }
+    Ok(json!({ "buckets": buckets }))
+}
+
+/// 範囲ごとの数を返す列（{field}_{範囲の順番}）を返す。fromは範囲に含み、toは範囲に含まない
+fn create_range_count_sql(field: &str, ranges: &[Value], builder: &mut SqlBuilder) -> Vec<String> {
+    ranges
+        .iter()
+        .enumerate()
+        .map(|(i, range)| {
+            let mut conditions = vec![format!("{} IS NOT NULL", field)];
+            if let Some(from) = range["from"].as_f64() {
+                conditions.push(format!("{} >= {}", field, builder.bind(from)));
+            }
+            if let Some(to) = range["to"].as_f64() {
+                conditions.push(format!("{} < {}", field, builder.bind(to)));
+            }
+            format!(
+                "COUNT(*) FILTER (WHERE {}) AS {}_{}",
+                conditions.join(" AND "),
+                field,
+                i
+            )
+        })
+        .collect()
+}
+
+fn create_range_buckets(
+    row: &QueryResult,
+    field: &str,
+    ranges: &[Value],
+) -> Result<Value, ErrResp> {
+    let mut buckets = Vec::with_capacity(ranges.len());
+    for (i, range) in ranges.iter().enumerate() {
+        let mut bucket = json!({ "doc_count": get_i64(row, &format!("{}_{}", field, i))? });
+        if let Some(from) = range["from"].as_f64() {
+            bucket["from"] = json!(from);
+        }
+        if let Some(to) = range["to"].as_f64() {
+            bucket["to"] = json!(to);
+        }
+        buckets.push(bucket);
+    }
+    Ok(json!({ "buckets": buckets }))
+}
+
+fn get_i64(row: &QueryResult, column: &str) -> Result<i64, ErrResp> {
+    row.try_get("", column).map_err(|e| {
+        error!("failed to get {}: {}", column, e);
+        unexpected_err_resp()
+    })
+}
+
+fn unexpected_err_resp() -> ErrResp {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiError {
+            code: Code::UnexpectedErr as u32,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opensearch::consultant_query::{
+        create_query_json, AnnualInComeInManYenParam, YearsOfServiceParam,
+    };
+    use crate::opensearch::Sort;
+
+    use super::*;
+
+    /// 検索を行うユーザー
+    const ACCOUNT_ID: i64 = 6;
+
+    /// 各条件が、OpenSearchのクエリで指定するフィールドと同じフィールドを対象としていることを確認する
+    #[test]
+    fn criteria_target_same_fields_as_opensearch_query() {
+        let career_param = CareerParam {
+            company_name: Some("テスト".to_string()),
+            company_id: Some(1),
+            department_name: Some("開発部".to_string()),
+            office: Some("東京".to_string()),
+            years_of_service: YearsOfServiceParam {
+                equal_or_more: Some(3),
+                less_than: Some(10),
+            },
+            employed: Some(true),
+            contract_type: Some("regular".to_string()),
+            profession: Some("エンジニア".to_string()),
+            annual_income_in_man_yen: AnnualInComeInManYenParam {
+                equal_or_more: Some(300),
+                equal_or_less: Some(800),
+            },
+            is_manager: Some(false),
+            position_name: Some("主任".to_string()),
+            is_new_graduate: Some(true),
+            note: Some("備考".to_string()),
+        };
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: Some(3000),
+            equal_or_less: Some(8000),
+        };
+        let query = create_query_json(
+            ACCOUNT_ID,
+            career_param.clone(),
+            fee_per_hour_in_yen_param.clone(),
+        );
+
+        let opensearch_fields: Vec<String> = query["query"]["bool"]["must"]
+            .as_array()
+            .expect("failed to get must")
+            .iter()
+            .map(|criterion| {
+                let criterion = if criterion["nested"].is_object() {
+                    &criterion["nested"]["query"]["bool"]["must"][0]
+                } else {
+                    criterion
+                };
+                if let Some(fields) = criterion["multi_match"]["fields"].as_array() {
+                    let field = fields[0].as_str().expect("failed to get field");
+                    return field
+                        .trim_end_matches("^1")
+                        .trim_end_matches(".ngram")
+                        .to_string();
+                }
+                let clause = if criterion["term"].is_object() {
+                    &criterion["term"]
+                } else {
+                    &criterion["range"]
+                };
+                find_sort_key(clause).to_string()
+            })
+            .collect();
+        let mut database_fields: Vec<String> = create_career_criteria(&career_param)
+            .iter()
+            .map(|c| format!("careers.{}", c.field))
+            .collect();
+        database_fields.extend(
+            create_fee_per_hour_in_yen_criteria(&fee_per_hour_in_yen_param)
+                .iter()
+                .map(|c| c.field.to_string()),
+        );
+
+        assert_eq!(opensearch_fields, database_fields);
+    }
+
+    #[test]
+    fn create_order_by_sql_ignores_score() {
+        let sort = create_sort_json(None);
+
+        let result = create_order_by_sql(&sort).expect("failed to get Ok");
+
+        assert_eq!("user_account_id DESC NULLS LAST", result);
+    }
+
+    #[test]
+    fn create_order_by_sql_puts_null_last() {
+        let sort = create_sort_json(Some(&Sort {
+            key: "rating".to_string(),
+            order: "asc".to_string(),
+        }));
+
+        let result = create_order_by_sql(&sort).expect("failed to get Ok");
+
+        assert_eq!(
+            "rating ASC NULLS LAST, user_account_id DESC NULLS LAST",
+            result
+        );
+    }
+
+    #[test]
+    fn create_search_after_sql_includes_null_after_value() {
+        let sort = create_sort_json(Some(&Sort {
+            key: "fee_per_hour_in_yen".to_string(),
+            order: "asc".to_string(),
+        }));
+        let mut builder = SqlBuilder::default();
+
+        let result = create_search_after_sql(&sort, &[json!(6000), json!(2)], &mut builder)
+            .expect("failed to get Ok");
+
+        assert_eq!(
+            "(fee_per_hour_in_yen IS NULL OR fee_per_hour_in_yen::DOUBLE PRECISION > $2 OR (fee_per_hour_in_yen::DOUBLE PRECISION = $2 AND (user_account_id IS NULL OR user_account_id::DOUBLE PRECISION < $1 OR (user_account_id::DOUBLE PRECISION = $1 AND FALSE))))",
+            result
+        );
+        assert_eq!(
+            vec![
+                entity::sea_orm::Value::from(2.0),
+                entity::sea_orm::Value::from(6000.0)
+            ],
+            builder.values
+        );
+    }
+
+    #[test]
+    fn create_search_after_sql_continues_within_null_values() {
+        let sort = create_sort_json(Some(&Sort {
+            key: "rating".to_string(),
+            order: "desc".to_string(),
+        }));
+        let mut builder = SqlBuilder::default();
+
+        let result = create_search_after_sql(&sort, &[Value::Null, json!(2)], &mut builder)
+            .expect("failed to get Ok");
+
+        assert_eq!(
+            "(rating IS NULL AND (user_account_id IS NULL OR user_account_id::DOUBLE PRECISION < $1 OR (user_account_id::DOUBLE PRECISION = $1 AND FALSE)))",
+            result
+        );
+    }
+
+    #[test]
+    fn create_sql_of_text_shorter_than_ngram_matches_nothing() {
+        let criterion = Criterion::new("profession", Condition::Text("あ".to_string()));
+        let mut builder = SqlBuilder::default();
+
+        let result = criterion.create_sql("$1", &mut builder);
+
+        assert_eq!("FALSE", result);
+        assert!(builder.values.is_empty());
+    }
+
+    #[test]
+    fn escape_regex_escapes_designations() {
+        assert_eq!(r"\(株\)", escape_regex("(株)"));
+        assert_eq!(r"co\.", escape_regex("co."));
+        assert_eq!("株式会社", escape_regex("株式会社"));
+    }
+}
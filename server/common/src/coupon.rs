@@ -0,0 +1,102 @@
+// Copyright 2023 Ken Miura
+
+/// 相談料の割合（パーセント）で割り引くクーポンを示す
+pub const DISCOUNT_TYPE_PERCENTAGE: &str = "percentage";
+/// 固定額（円）で割り引くクーポンを示す
+pub const DISCOUNT_TYPE_FIXED_AMOUNT: &str = "fixed_amount";
+
+/// 割引額をプラットフォームが負担することを示す
+pub const DISCOUNT_BORNE_BY_PLATFORM: &str = "platform";
+/// 割引額をコンサルタントが負担することを示す
+pub const DISCOUNT_BORNE_BY_CONSULTANT: &str = "consultant";
+
+/// 相談料に対する割引額（円）を返す。
+///
+/// discount_typeが[DISCOUNT_TYPE_PERCENTAGE]の場合、相談料にdiscount_value（パーセント）を掛けた値の小数点以下を切り捨てた値を返す。
+/// discount_typeが[DISCOUNT_TYPE_FIXED_AMOUNT]の場合、discount_valueを返す。
+/// discount_typeがそれ以外の場合、Noneを返す。
+///
+/// 割引額が相談料以上となる（支払額が0円以下となる）場合でも、その値をそのまま返す。
+/// 支払額が0円以下となる相談は支払い手段が存在しないため、呼び出し側でそのような割引を受け付けないようにする必要がある。
+pub fn calculate_discount_in_yen(
+    fee_per_hour_in_yen: i32,
+    discount_type: &str,
+    discount_value: i32,
+) -> Option<i32> {
+    if discount_type == DISCOUNT_TYPE_PERCENTAGE {
+        let discount = (fee_per_hour_in_yen as i64 * discount_value as i64) / 100;
+        i32::try_from(discount).ok()
+    } else if discount_type == DISCOUNT_TYPE_FIXED_AMOUNT {
+        Some(discount_value)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct CalculateDiscountInYenTestCase {
+        name: String,
+        input: (i32, String, i32),
+        expected: Option<i32>,
+    }
+
+    static CALCULATE_DISCOUNT_IN_YEN_TEST_CASE_SET: Lazy<Vec<CalculateDiscountInYenTestCase>> =
+        Lazy::new(|| {
+            vec![
+                CalculateDiscountInYenTestCase {
+                    name: "percentage 10".to_string(),
+                    input: (5000, DISCOUNT_TYPE_PERCENTAGE.to_string(), 10),
+                    expected: Some(500),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "percentage truncates fraction".to_string(),
+                    input: (3333, DISCOUNT_TYPE_PERCENTAGE.to_string(), 15),
+                    expected: Some(499),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "percentage 100".to_string(),
+                    input: (3000, DISCOUNT_TYPE_PERCENTAGE.to_string(), 100),
+                    expected: Some(3000),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "fixed amount".to_string(),
+                    input: (5000, DISCOUNT_TYPE_FIXED_AMOUNT.to_string(), 1000),
+                    expected: Some(1000),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "fixed amount equals fee".to_string(),
+                    input: (3000, DISCOUNT_TYPE_FIXED_AMOUNT.to_string(), 3000),
+                    expected: Some(3000),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "fixed amount exceeds fee".to_string(),
+                    input: (3000, DISCOUNT_TYPE_FIXED_AMOUNT.to_string(), 5000),
+                    expected: Some(5000),
+                },
+                CalculateDiscountInYenTestCase {
+                    name: "unknown discount type".to_string(),
+                    input: (3000, "other".to_string(), 10),
+                    expected: None,
+                },
+            ]
+        });
+
+    #[test]
+    fn test_calculate_discount_in_yen() {
+        for test_case in CALCULATE_DISCOUNT_IN_YEN_TEST_CASE_SET.iter() {
+            let result = calculate_discount_in_yen(
+                test_case.input.0,
+                test_case.input.1.as_str(),
+                test_case.input.2,
+            );
+            let message = format!("test case \"{}\" failed", test_case.name.clone());
+            assert_eq!(test_case.expected, result, "{}", message);
+        }
+    }
+}
@@ -1,17 +1,24 @@
 // Copyright 2021 Ken Miura
 
 pub mod admin;
+pub mod company;
+pub mod consultant_search;
+pub mod coupon;
 pub mod db;
 pub mod err;
 pub mod log;
 pub mod mfa;
 pub mod opensearch;
 pub mod password;
+pub mod payment;
+pub mod payment_event;
+pub mod perceptual_hash;
 pub mod rating;
 pub mod redis;
 pub mod smtp;
 pub mod storage;
 pub mod util;
+pub mod virtual_bank_account;
 
 use std::{
     env::var,
@@ -20,7 +27,7 @@ use std::{
 };
 
 use ::opensearch::OpenSearch;
-use async_fred_session::RedisSessionStore;
+use async_fred_session::{fred::pool::RedisPool, RedisSessionStore};
 use axum::{
     async_trait,
     body::Body,
@@ -31,8 +38,10 @@ use axum::{
 };
 use axum_extra::extract::cookie::Key;
 use chrono::FixedOffset;
+use consultant_search::ConsultantSearchClient;
 use entity::sea_orm::DatabaseConnection;
 use once_cell::sync::Lazy;
+use payment::PaymentGatewayClient;
 use serde::Deserialize;
 use serde::Serialize;
 use smtp::SmtpClient;
@@ -244,11 +253,15 @@ pub const DUMMY_HASHED_PASSWORD: &[u8] =
 #[derive(Clone, FromRef)]
 pub struct AppState {
     pub store: RedisSessionStore,
+    /// セッション以外にRedisを直接利用する（リクエスト数の制限など）ためのコネクションプール。storeと同じプールを共有する
+    pub redis_pool: RedisPool,
     pub index_client: OpenSearch,
     pub pool: DatabaseConnection,
     pub key_for_signed_cookie: Key,
     pub smtp_client: SmtpClient,
     pub storage_client: StorageClient,
+    pub payment_gateway_client: PaymentGatewayClient,
+    pub consultant_search_client: ConsultantSearchClient,
 }
 
 impl From<AppState> for Key {
@@ -1,23 +1,38 @@
 // Copyright 2022 Ken Miura
 
+pub mod consultant_query;
+pub mod index_definition;
+pub mod normalization;
+
 use std::error::Error;
 
 use axum::{http::StatusCode, Json};
+use chrono::NaiveDate;
 use opensearch::{
     auth::Credentials,
-    http::transport::{SingleNodeConnectionPool, TransportBuilder},
-    DeleteParts, IndexParts, OpenSearch, SearchParts, UpdateParts,
+    http::{
+        request::JsonBody,
+        transport::{SingleNodeConnectionPool, TransportBuilder},
+    },
+    BulkParts, CreatePitParts, DeleteParts, IndexParts, OpenSearch, SearchParts, UpdateParts,
 };
+use serde_json::json;
 use serde_json::Value;
 use tracing::error;
 
-use crate::{err::Code, ApiError, ErrResp};
+use crate::{err::Code, rating::calculate_average_rating, ApiError, ErrResp};
+
+use self::normalization::normalize_company_name;
 
 pub const KEY_TO_OPENSEARCH_ENDPOINT_URI: &str = "OPENSEARCH_ENDPOINT_URI";
 pub const KEY_TO_OPENSEARCH_AUTH: &str = "OPENSEARCH_AUTH";
 pub const KEY_TO_OPENSEARCH_USERNAME: &str = "OPENSEARCH_USERNAME";
 pub const KEY_TO_OPENSEARCH_PASSWORD: &str = "OPENSEARCH_PASSWORD";
 
+/// ドキュメントの追加、更新、検索、削除で指定するインデックス名
+///
+/// インデックスそのものではなくエイリアスの名前で、実体はusers_vN（Nは世代を示す数字）という名前のインデックスとなる。
+/// エイリアスの向き先の切り替え（インデックスの再構築や定義の変更時の移行）はusers_index_toolで行う。
 pub const INDEX_NAME: &str = "users";
 
 pub async fn index_document(
@@ -95,7 +110,92 @@ pub async fn update_document(
     Ok(())
 }
 
-#[derive(Debug, Clone)]
+/// 複数のドキュメントを[Bulk API](https://opensearch.org/docs/2.2/api-reference/document-apis/bulk/)で一度に更新する
+///
+/// updatesの各要素は、ドキュメントIDと[update_document]に渡すものと同じ形式のjson
+pub async fn bulk_update_documents(
+    index_name: &str,
+    updates: &[(String, Value)],
+    client: &OpenSearch,
+) -> Result<(), ErrResp> {
+    let mut body: Vec<JsonBody<Value>> = Vec::with_capacity(updates.len() * 2);
+    for (document_id, json_value) in updates {
+        body.push(json!({"update": {"_id": document_id}}).into());
+        body.push(json_value.clone().into());
+    }
+    send_bulk_request(index_name, body, client).await
+}
+
+/// 複数のドキュメントを[Bulk API](https://opensearch.org/docs/2.2/api-reference/document-apis/bulk/)で一度に追加する（同じIDのドキュメントが存在する場合、置き換える）
+///
+/// documentsの各要素は、ドキュメントIDと[index_document]に渡すものと同じ形式のjson
+pub async fn bulk_index_documents(
+    index_name: &str,
+    documents: &[(String, Value)],
+    client: &OpenSearch,
+) -> Result<(), ErrResp> {
+    let mut body: Vec<JsonBody<Value>> = Vec::with_capacity(documents.len() * 2);
+    for (document_id, json_value) in documents {
+        body.push(json!({"index": {"_id": document_id}}).into());
+        body.push(json_value.clone().into());
+    }
+    send_bulk_request(index_name, body, client).await
+}
+
+async fn send_bulk_request(
+    index_name: &str,
+    body: Vec<JsonBody<Value>>,
+    client: &OpenSearch,
+) -> Result<(), ErrResp> {
+    let num_of_lines = body.len();
+    let response = client
+        .bulk(BulkParts::Index(index_name))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to send bulk request (index_name: {}, num_of_lines: {}): {}",
+                index_name, num_of_lines, e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError {
+                    code: Code::UnexpectedErr as u32,
+                }),
+            )
+        })?;
+    let status_code = response.status_code();
+    let response_body = response.json::<Value>().await.map_err(|e| {
+        error!(
+            "failed to read body as json (index_name: {}, num_of_lines: {}): {}",
+            index_name, num_of_lines, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    // Bulk APIは一部の操作が失敗してもステータスコードは成功となるため、errorsも確認する
+    let has_errors = response_body["errors"].as_bool().unwrap_or(true);
+    if !status_code.is_success() || has_errors {
+        error!(
+            "failed to request bulk operation (index_name: {}, response_body: {})",
+            index_name, response_body
+        );
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Sort {
     pub key: String,
     pub order: String,
@@ -165,6 +265,178 @@ pub async fn search_documents(
     Ok(response_body)
 }
 
+/// [Point in Time](https://opensearch.org/docs/latest/search-plugins/point-in-time/)を作成し、そのIDを返す
+///
+/// keep_aliveはPoint in Timeを保持する期間（例: "5m"）。Point in Timeを指定して検索する度に延長される。
+pub async fn create_point_in_time(
+    index_name: &str,
+    keep_alive: &str,
+    client: &OpenSearch,
+) -> Result<String, ErrResp> {
+    let response = client
+        .create_pit(CreatePitParts::Index(&[index_name]))
+        .keep_alive(keep_alive)
+        .send()
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to create point in time (index_name: {}, keep_alive: {}): {}",
+                index_name, keep_alive, e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError {
+                    code: Code::UnexpectedErr as u32,
+                }),
+            )
+        })?;
+    let status_code = response.status_code();
+    let response_body = response.json::<Value>().await.map_err(|e| {
+        error!(
+            "failed to read body as json (index_name: {}, keep_alive: {}): {}",
+            index_name, keep_alive, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    if !status_code.is_success() {
+        error!(
+            "failed to create point in time (index_name: {}, keep_alive: {}, response_body: {})",
+            index_name, keep_alive, response_body
+        );
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        ));
+    }
+    let pit_id = response_body["pit_id"].as_str().ok_or_else(|| {
+        error!("failed to find pit_id in response: {}", response_body);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    Ok(pit_id.to_string())
+}
+
+/// Point in Timeを削除する
+///
+/// 期限切れなどで既に存在しないPoint in Timeを指定した場合もエラーとしない（期限が切れたPoint in Timeは自動で削除されるため）。
+pub async fn delete_point_in_time(pit_id: &str, client: &OpenSearch) -> Result<(), ErrResp> {
+    let response = client
+        .delete_pit()
+        .body(json!({
+            "pit_id": [pit_id]
+        }))
+        .send()
+        .await
+        .map_err(|e| {
+            error!("failed to delete point in time (pit_id: {}): {}", pit_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError {
+                    code: Code::UnexpectedErr as u32,
+                }),
+            )
+        })?;
+    let status_code = response.status_code();
+    if !status_code.is_success() && status_code.as_u16() != 404 {
+        let response_body = response.text().await.unwrap_or_default();
+        error!(
+            "failed to delete point in time (pit_id: {}, status_code: {}, response_body: {})",
+            pit_id, status_code, response_body
+        );
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Point in Timeを指定してドキュメントを検索する
+///
+/// queryにはsort（ドキュメントを一意に並べられるもの）を含めておく必要がある。search_afterには前回の検索結果の最後のヒットのsortの値を指定する。
+/// Point in Timeの期限が切れている（または存在しない）場合、Noneを返す。
+pub async fn search_documents_with_point_in_time(
+    pit_id: &str,
+    keep_alive: &str,
+    size: i64,
+    search_after: Option<&[Value]>,
+    query: &Value,
+    client: &OpenSearch,
+) -> Result<Option<Value>, ErrResp> {
+    let mut body = query.clone();
+    body["pit"] = json!({
+        "id": pit_id,
+        "keep_alive": keep_alive
+    });
+    if let Some(search_after) = search_after {
+        body["search_after"] = json!(search_after);
+    }
+    let response = client
+        .search(SearchParts::None)
+        .size(size)
+        .body(body.clone())
+        .send()
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to search documents with point in time (size: {}, body: {}): {}",
+                size, body, e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError {
+                    code: Code::UnexpectedErr as u32,
+                }),
+            )
+        })?;
+    let status_code = response.status_code();
+    let response_body = response.json::<Value>().await.map_err(|e| {
+        error!(
+            "failed to read body as json (size: {}, body: {}): {}",
+            size, body, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    if status_code == opensearch::http::StatusCode::NOT_FOUND {
+        error!(
+            "point in time not found (size: {}, body: {}, response_body: {})",
+            size, body, response_body
+        );
+        return Ok(None);
+    }
+    if !status_code.is_success() {
+        error!(
+            "failed to search documents with point in time (size: {}, body: {}, response_body: {})",
+            size, body, response_body
+        );
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        ));
+    }
+    Ok(Some(response_body))
+}
+
 pub async fn delete_document(
     index_name: &str,
     document_id: &str,
@@ -202,6 +474,102 @@ pub async fn delete_document(
     Ok(())
 }
 
+/// 職務経歴のうち、時間の経過によって値が変化するドキュメントのフィールド
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeDependentCareerFields {
+    pub years_of_service: i64,
+    pub employed: bool,
+}
+
+/// current_date時点での職務経歴の在籍年数と在籍中かどうかを計算する
+///
+/// 退社日が登録されている職務経歴は（退社日が未来の日付であっても）在籍中ではないものとして扱い、在籍年数は退社日までで計算する。
+/// そのため、時間の経過によって値が変化するのは退社日が登録されていない職務経歴の在籍年数のみとなる。
+/// ドキュメントに職務経歴を追加するときと、定期実行ツールで値を更新するときの両方でこの関数を利用し、計算結果が食い違わないようにする。
+pub fn calculate_time_dependent_career_fields(
+    career_start_date: NaiveDate,
+    career_end_date: Option<NaiveDate>,
+    current_date: NaiveDate,
+) -> TimeDependentCareerFields {
+    match career_end_date {
+        Some(career_end_date) => TimeDependentCareerFields {
+            years_of_service: calculate_years_of_service(career_start_date, career_end_date),
+            employed: false,
+        },
+        None => TimeDependentCareerFields {
+            years_of_service: calculate_years_of_service(career_start_date, current_date),
+            employed: true,
+        },
+    }
+}
+
+/// ドキュメントのcareersに格納する職務経歴のjsonを作成する
+///
+/// 在籍年数と在籍中かどうかはcurrent_date時点の値で計算する
+pub fn create_career_document(career: &entity::career::Model, current_date: NaiveDate) -> Value {
+    let TimeDependentCareerFields {
+        years_of_service,
+        employed,
+    } = calculate_time_dependent_career_fields(
+        career.career_start_date,
+        career.career_end_date,
+        current_date,
+    );
+    json!({
+        "career_id": career.career_id,
+        "company_name": career.company_name,
+        "normalized_company_name": normalize_company_name(&career.company_name),
+        "company_id": career.company_id,
+        "department_name": career.department_name,
+        "office": career.office,
+        "years_of_service": years_of_service,
+        "employed": employed,
+        "contract_type": career.contract_type,
+        "profession": career.profession,
+        "annual_income_in_man_yen": career.annual_income_in_man_yen,
+        "is_manager": career.is_manager,
+        "position_name": career.position_name,
+        "is_new_graduate": career.is_new_graduate,
+        "note": career.note,
+    })
+}
+
+/// DBの内容（職務経歴、相談料、銀行口座、評価、アカウントの無効化の有無）からユーザー（コンサルタント）のドキュメントを作成する
+///
+/// インデックスの再構築と、OpenSearchを利用せずにDBを検索するバックエンドの両方でこの関数を利用し、ドキュメントの内容が食い違わないようにする。
+pub fn create_document_source(
+    user_account_id: i64,
+    careers: &[&entity::career::Model],
+    fee_per_hour_in_yen: Option<i32>,
+    is_bank_account_registered: bool,
+    ratings: Vec<i16>,
+    disabled: bool,
+    current_date: NaiveDate,
+) -> Value {
+    let career_documents: Vec<Value> = careers
+        .iter()
+        .map(|c| create_career_document(c, current_date))
+        .collect();
+    let num_of_rated = ratings.len();
+    let rating = calculate_average_rating(ratings);
+    json!({
+        "user_account_id": user_account_id,
+        "careers": career_documents,
+        "num_of_careers": careers.len(),
+        "fee_per_hour_in_yen": fee_per_hour_in_yen,
+        "is_bank_account_registered": is_bank_account_registered,
+        "rating": rating,
+        "num_of_rated": num_of_rated,
+        "disabled": disabled
+    })
+}
+
+fn calculate_years_of_service(from: NaiveDate, to: NaiveDate) -> i64 {
+    let days_in_year = 365; // 1日の誤差（1年が365日か366日か）は、年という単位に対して無視して良いと判断し、365日固定で計算する
+    let days_of_service = (to - from).num_days();
+    days_of_service / days_in_year
+}
+
 /// OpenSearchノードへアクセスするためのクライアントを作成する
 ///
 /// # Panics
@@ -230,3 +598,242 @@ pub fn create_client(
     let transport = builder.build()?;
     Ok(OpenSearch::new(transport))
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestCase {
+        name: String,
+        input: Input,
+        expected: i64,
+    }
+
+    #[derive(Debug)]
+    struct Input {
+        from: NaiveDate,
+        to: NaiveDate,
+    }
+
+    static TEST_CASE_SET: Lazy<Vec<TestCase>> = Lazy::new(|| {
+        vec![
+            TestCase {
+                name: "less 1 year".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2009, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2010, 3, 31).expect("failed to get NaiveDate"),
+                },
+                expected: 0,
+            },
+            TestCase {
+                name: "just 1 year".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2009, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
+                },
+                expected: 1,
+            },
+            TestCase {
+                name: "less 1 year (leap year)".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2011, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2012, 3, 30).expect("failed to get NaiveDate"),
+                },
+                expected: 0,
+            },
+            TestCase {
+                name: "just 1 year (leap year)".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2011, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2012, 3, 31).expect("failed to get NaiveDate"),
+                },
+                expected: 1,
+            },
+            TestCase {
+                name: "passed leap year 2 times".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2019, 3, 30).expect("failed to get NaiveDate"),
+                },
+                expected: 9,
+            },
+            TestCase {
+                name: "passed leap year 3 times".to_string(),
+                input: Input {
+                    from: NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get NaiveDate"),
+                    to: NaiveDate::from_ymd_opt(2020, 3, 29).expect("failed to get NaiveDate"),
+                },
+                expected: 10,
+            },
+        ]
+    });
+
+    #[test]
+    fn calculate_years_of_service_tests() {
+        for test_case in TEST_CASE_SET.iter() {
+            let years_of_service =
+                calculate_years_of_service(test_case.input.from, test_case.input.to);
+            let message = format!("test case \"{}\" failed", test_case.name.clone());
+            assert_eq!(test_case.expected, years_of_service, "{}", message);
+        }
+    }
+
+    #[test]
+    fn calculate_time_dependent_career_fields_employed() {
+        let career_start_date = NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok");
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+
+        let result = calculate_time_dependent_career_fields(career_start_date, None, current_date);
+
+        assert_eq!(
+            TimeDependentCareerFields {
+                years_of_service: 8,
+                employed: true
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn calculate_time_dependent_career_fields_retired() {
+        let career_start_date = NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok");
+        let career_end_date = NaiveDate::from_ymd_opt(2020, 3, 31).expect("failed to get Ok");
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+
+        let result = calculate_time_dependent_career_fields(
+            career_start_date,
+            Some(career_end_date),
+            current_date,
+        );
+
+        assert_eq!(
+            TimeDependentCareerFields {
+                years_of_service: 5,
+                employed: false
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn calculate_time_dependent_career_fields_career_end_date_is_today() {
+        let career_start_date = NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok");
+        let career_end_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let current_date = career_end_date;
+
+        let result = calculate_time_dependent_career_fields(
+            career_start_date,
+            Some(career_end_date),
+            current_date,
+        );
+
+        assert_eq!(
+            TimeDependentCareerFields {
+                years_of_service: 8,
+                employed: false
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn calculate_time_dependent_career_fields_career_end_date_is_future() {
+        let career_start_date = NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok");
+        let career_end_date = NaiveDate::from_ymd_opt(2025, 3, 31).expect("failed to get Ok");
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+
+        let result = calculate_time_dependent_career_fields(
+            career_start_date,
+            Some(career_end_date),
+            current_date,
+        );
+
+        assert_eq!(
+            TimeDependentCareerFields {
+                years_of_service: 10,
+                employed: false
+            },
+            result
+        );
+    }
+
+    fn create_career(career_id: i64, user_account_id: i64) -> entity::career::Model {
+        entity::career::Model {
+            career_id,
+            user_account_id,
+            company_name: "テスト株式会社".to_string(),
+            company_id: 1,
+            department_name: None,
+            office: Some("東京事業所".to_string()),
+            career_start_date: NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok"),
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: Some(500),
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+            verified_work_email_domain: None,
+        }
+    }
+
+    #[test]
+    fn create_document_source_without_careers() {
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+
+        let result = create_document_source(1, &[], Some(3000), false, vec![], false, current_date);
+
+        assert_eq!(
+            json!({
+                "user_account_id": 1,
+                "careers": [],
+                "num_of_careers": 0,
+                "fee_per_hour_in_yen": 3000,
+                "is_bank_account_registered": false,
+                "rating": null,
+                "num_of_rated": 0,
+                "disabled": false
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn create_document_source_with_careers_and_ratings() {
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let career1 = create_career(10, 1);
+        let career2 = create_career(11, 1);
+
+        let result = create_document_source(
+            1,
+            &[&career1, &career2],
+            None,
+            true,
+            vec![4, 5],
+            true,
+            current_date,
+        );
+
+        assert_eq!(
+            json!({
+                "user_account_id": 1,
+                "careers": [
+                    create_career_document(&career1, current_date),
+                    create_career_document(&career2, current_date)
+                ],
+                "num_of_careers": 2,
+                "fee_per_hour_in_yen": null,
+                "is_bank_account_registered": true,
+                "rating": 4.5,
+                "num_of_rated": 2,
+                "disabled": true
+            }),
+            result
+        );
+    }
+}
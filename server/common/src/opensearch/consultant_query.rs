@@ -0,0 +1,1111 @@
+// Copyright 2023 Ken Miura
+
+//! コンサルタントの検索条件と、検索条件からクエリを生成する関数を定義するモジュール
+//!
+//! ユーザー向けサービスのコンサルタント検索と、保存された検索条件を定期的に実行するバッチで共通して利用する。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::normalization::{normalize_company_name, normalize_text};
+use super::Sort;
+
+/// 保存された検索条件（saved_searchのsearch_param）としてJSONで保持する値
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SearchCondition {
+    pub career_param: CareerParam,
+    pub fee_per_hour_in_yen_param: FeePerHourInYenParam,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct CareerParam {
+    pub company_name: Option<String>,
+    /// 会社マスタの会社（company_id）で絞り込む場合に指定する（会社名の表記によらず同一の会社で絞り込める）
+    pub company_id: Option<i64>,
+    pub department_name: Option<String>,
+    pub office: Option<String>,
+    pub years_of_service: YearsOfServiceParam,
+    pub employed: Option<bool>,
+    pub contract_type: Option<String>,
+    pub profession: Option<String>,
+    pub annual_income_in_man_yen: AnnualInComeInManYenParam,
+    pub is_manager: Option<bool>,
+    pub position_name: Option<String>,
+    pub is_new_graduate: Option<bool>,
+    pub note: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct YearsOfServiceParam {
+    pub equal_or_more: Option<i32>,
+    pub less_than: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AnnualInComeInManYenParam {
+    pub equal_or_more: Option<i32>,
+    pub equal_or_less: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct FeePerHourInYenParam {
+    pub equal_or_more: Option<i32>,
+    pub equal_or_less: Option<i32>,
+}
+
+/// 検索条件に一致するコンサルタントを検索するクエリ（[Search API](https://opensearch.org/docs/2.2/api-reference/search/)のリクエストボディ）を返す
+///
+/// 検索条件に加え、相談を受け付けられるコンサルタント（職務経歴、相談料、銀行口座が登録済みで、アカウントが無効化されていない）のみに絞り込む。
+/// また、検索を行うユーザー自身（account_id）は検索結果から除外する。
+pub fn create_query_json(
+    account_id: i64,
+    career_param: CareerParam,
+    fee_per_hour_in_yen_param: FeePerHourInYenParam,
+) -> Value {
+    let mut params = Vec::<Value>::new();
+    if let Some(company_name) = career_param.company_name {
+        let company_name_criteria = create_company_name_criteria(company_name.as_str());
+        params.push(company_name_criteria);
+    }
+    if let Some(company_id) = career_param.company_id {
+        let company_id_criteria = create_company_id_criteria(company_id);
+        params.push(company_id_criteria);
+    }
+    if let Some(department_name) = career_param.department_name {
+        let department_name_criteria = create_department_name_criteria(department_name.as_str());
+        params.push(department_name_criteria);
+    }
+    if let Some(office) = career_param.office {
+        let office_criteria = create_office_criteria(office.as_str());
+        params.push(office_criteria);
+    }
+    if let Some(equal_or_more) = career_param.years_of_service.equal_or_more {
+        let years_of_service_equal_or_more_criteria =
+            create_years_of_service_equal_or_more_criteria(equal_or_more);
+        params.push(years_of_service_equal_or_more_criteria);
+    }
+    if let Some(less_than) = career_param.years_of_service.less_than {
+        let years_of_service_less_than_criteria =
+            create_years_of_service_less_than_criteria(less_than);
+        params.push(years_of_service_less_than_criteria);
+    }
+    if let Some(employed) = career_param.employed {
+        let employed_criteria = create_employed_criteria(employed);
+        params.push(employed_criteria);
+    }
+    if let Some(contract_type) = career_param.contract_type {
+        let contract_type_criteria = create_contract_type_criteria(contract_type.as_str());
+        params.push(contract_type_criteria);
+    }
+    if let Some(profession) = career_param.profession {
+        let profession_criteria = create_profession_criteria(profession.as_str());
+        params.push(profession_criteria);
+    }
+    if let Some(equal_or_more) = career_param.annual_income_in_man_yen.equal_or_more {
+        let equal_or_more_criteria =
+            create_annual_income_in_man_yen_equal_or_more_criteria(equal_or_more);
+        params.push(equal_or_more_criteria);
+    }
+    if let Some(equal_or_less) = career_param.annual_income_in_man_yen.equal_or_less {
+        let equal_or_less_criteria =
+            create_annual_income_in_man_yen_equal_or_less_criteria(equal_or_less);
+        params.push(equal_or_less_criteria);
+    }
+    if let Some(is_manager) = career_param.is_manager {
+        let is_manager_criteria = create_is_manager_criteria(is_manager);
+        params.push(is_manager_criteria);
+    }
+    if let Some(position_name) = career_param.position_name {
+        let position_name_criteria = create_position_name_criteria(position_name.as_str());
+        params.push(position_name_criteria);
+    }
+    if let Some(is_new_graduate) = career_param.is_new_graduate {
+        let is_new_graduate_criteria = create_is_new_graduate_criteria(is_new_graduate);
+        params.push(is_new_graduate_criteria);
+    }
+    if let Some(note) = career_param.note {
+        let note_criteria = create_note_criteria(note.as_str());
+        params.push(note_criteria);
+    }
+    if let Some(equal_or_more) = fee_per_hour_in_yen_param.equal_or_more {
+        let equal_or_more_criteria =
+            create_fee_per_hour_in_yen_equal_or_more_criteria(equal_or_more);
+        params.push(equal_or_more_criteria);
+    }
+    if let Some(equal_or_less) = fee_per_hour_in_yen_param.equal_or_less {
+        let equal_or_less_criteria =
+            create_fee_per_hour_in_yen_equal_or_less_criteria(equal_or_less);
+        params.push(equal_or_less_criteria);
+    }
+    generate_query_json(account_id, params)
+}
+
+/// 法人格の表記（株式会社、(株)など）や全角半角、カタカナひらがなの違いを吸収するため、正規化した会社名で絞り込む
+///
+/// 入力された会社名そのものは関連度のスコアの計算にのみ利用する
+fn create_company_name_criteria(company_name: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_company_name(company_name),
+                                "fields": [
+                                    "careers.normalized_company_name^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": company_name,
+                                "fields": [
+                                    "careers.company_name^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_company_id_criteria(company_id: i64) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "term": {
+                                "careers.company_id": company_id
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_department_name_criteria(department_name: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_text(department_name),
+                                "fields": [
+                                    "careers.department_name.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": department_name,
+                                "fields": [
+                                    "careers.department_name^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_office_criteria(office: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_text(office),
+                                "fields": [
+                                    "careers.office.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": office,
+                                "fields": [
+                                    "careers.office^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_years_of_service_equal_or_more_criteria(years_of_service: i32) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "range": {
+                                "careers.years_of_service": {
+                                    "gte": years_of_service
+                                }
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_years_of_service_less_than_criteria(years_of_service: i32) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "range": {
+                                "careers.years_of_service": {
+                                    "lt": years_of_service
+                                }
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_employed_criteria(employed: bool) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "term": {
+                                "careers.employed": employed
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_contract_type_criteria(contract_type: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": contract_type,
+                                "fields": [
+                                    "careers.contract_type.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": contract_type,
+                                "fields": [
+                                    "careers.contract_type^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_profession_criteria(profession: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_text(profession),
+                                "fields": [
+                                    "careers.profession.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": profession,
+                                "fields": [
+                                    "careers.profession^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_annual_income_in_man_yen_equal_or_more_criteria(equal_or_more: i32) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "range": {
+                                "careers.annual_income_in_man_yen": {
+                                    "gte": equal_or_more
+                                }
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_annual_income_in_man_yen_equal_or_less_criteria(equal_or_less: i32) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "range": {
+                                "careers.annual_income_in_man_yen": {
+                                    "lte": equal_or_less
+                                }
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_is_manager_criteria(is_manager: bool) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "term": {
+                                "careers.is_manager": is_manager
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_position_name_criteria(position_name: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_text(position_name),
+                                "fields": [
+                                    "careers.position_name.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": position_name,
+                                "fields": [
+                                    "careers.position_name^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_is_new_graduate_criteria(is_new_graduate: bool) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "term": {
+                                "careers.is_new_graduate": is_new_graduate
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_note_criteria(note: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "bool": {
+                    "must": [
+                        {
+                            "multi_match": {
+                                "query": normalize_text(note),
+                                "fields": [
+                                    "careers.note.ngram^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ],
+                    "should": [
+                        {
+                            "multi_match": {
+                                "query": note,
+                                "fields": [
+                                    "careers.note^1"
+                                ],
+                                "type": "phrase"
+                            }
+                        }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+fn create_fee_per_hour_in_yen_equal_or_more_criteria(equal_or_more: i32) -> Value {
+    json!({
+        "range": {
+            "fee_per_hour_in_yen": {
+                "gte": equal_or_more
+            }
+        }
+    })
+}
+
+fn create_fee_per_hour_in_yen_equal_or_less_criteria(equal_or_less: i32) -> Value {
+    json!({
+        "range": {
+            "fee_per_hour_in_yen": {
+                "lte": equal_or_less
+            }
+        }
+    })
+}
+
+fn generate_query_json(account_id: i64, params: Vec<Value>) -> Value {
+    json!({
+        "query": {
+            "bool": {
+                "must": params,
+                "filter": create_available_consultant_filter_json(),
+                "must_not": [
+                    {
+                        "term": {
+                            "user_account_id": account_id
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+/// 相談を受け付けられるコンサルタント（職務経歴、相談料、銀行口座が登録済みで、アカウントが無効化されていない）のみに絞り込む条件（boolクエリのfilter）を返す
+pub fn create_available_consultant_filter_json() -> Value {
+    json!([
+        {
+            "range": {
+                "num_of_careers": {
+                    "gt": 0
+                }
+            }
+        },
+        {
+            "exists": {
+                "field": "fee_per_hour_in_yen"
+            }
+        },
+        {
+            "term": {
+                "is_bank_account_registered": true
+            }
+        },
+        {
+            "term": {
+                "disabled": false
+            }
+        }
+    ])
+}
+
+/// 指定したコンサルタント（consultant_id）を検索するクエリを返す
+///
+/// [create_query_json]と同様に、相談を受け付けられるコンサルタントのみを対象とし、検索を行うユーザー自身（account_id）は除外する。
+pub fn create_consultant_query_json(consultant_id: i64, account_id: i64) -> Value {
+    json!({
+        "query": {
+            "bool": {
+                "must": [
+                    {
+                        "term": {
+                            "user_account_id": consultant_id
+                        }
+                    }
+                ],
+                "filter": create_available_consultant_filter_json(),
+                "must_not": [
+                    {
+                        "term": {
+                            "user_account_id": account_id
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+/// 指定したコンサルタント（consultant_ids）を検索するクエリを返す
+///
+/// [create_consultant_query_json]と同様に、相談を受け付けられるコンサルタントのみを対象とし、検索を行うユーザー自身（account_id）は除外する。
+pub fn create_consultants_query_json(consultant_ids: &[i64], account_id: i64) -> Value {
+    json!({
+        "query": {
+            "bool": {
+                "must": [
+                    {
+                        "terms": {
+                            "user_account_id": consultant_ids
+                        }
+                    }
+                ],
+                "filter": create_available_consultant_filter_json(),
+                "must_not": [
+                    {
+                        "term": {
+                            "user_account_id": account_id
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+/// 職務経歴の各項目が一致した際のスコアの重み
+pub const COMPANY_BOOST: f64 = 3.0;
+pub const PROFESSION_BOOST: f64 = 2.0;
+pub const CONTRACT_TYPE_BOOST: f64 = 1.0;
+pub const IS_MANAGER_BOOST: f64 = 1.0;
+pub const YEARS_OF_SERVICE_BOOST: f64 = 1.0;
+
+/// 似たコンサルタントを探す際に利用する職務経歴の項目
+#[derive(Clone, Debug, PartialEq)]
+pub struct CareerFeature {
+    pub company_id: Option<i64>,
+    pub profession: Option<String>,
+    pub contract_type: String,
+    pub is_manager: bool,
+    /// 在籍年数が含まれる区分の下限（以上）
+    pub years_of_service_equal_or_more: i64,
+    /// 在籍年数が含まれる区分の上限（未満）。最も長い区分の場合はNone
+    pub years_of_service_less_than: Option<i64>,
+}
+
+/// 表示中のコンサルタント（consultant_id）の職務経歴（会社、職種、雇用形態、管理職か否か、在籍年数の区分）と一致する項目が多い職務経歴を持つコンサルタントほど高いスコアとなるクエリを返す
+///
+/// 検索と同様に相談を受け付けられるコンサルタントのみを対象とし、ユーザー自身（account_id）と表示中のコンサルタントは除外する
+pub fn create_similar_consultants_query_json(
+    consultant_id: i64,
+    account_id: i64,
+    career_features: &[CareerFeature],
+) -> Value {
+    let should = career_features
+        .iter()
+        .map(create_career_similarity_criteria)
+        .collect::<Vec<Value>>();
+    json!({
+        "query": {
+            "bool": {
+                "should": should,
+                "minimum_should_match": 1,
+                "filter": create_available_consultant_filter_json(),
+                "must_not": [
+                    {
+                        "terms": {
+                            "user_account_id": [account_id, consultant_id]
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+/// 職務経歴一つ分の条件。コンサルタントの職務経歴のうち、最も一致する職務経歴のスコアを用いる
+fn create_career_similarity_criteria(career_feature: &CareerFeature) -> Value {
+    let mut should = Vec::with_capacity(5);
+    if let Some(company_id) = career_feature.company_id {
+        should.push(json!({
+            "term": {
+                "careers.company_id": {
+                    "value": company_id,
+                    "boost": COMPANY_BOOST
+                }
+            }
+        }));
+    }
+    if let Some(profession) = career_feature.profession.as_ref() {
+        should.push(json!({
+            "match": {
+                "careers.profession": {
+                    "query": profession,
+                    "boost": PROFESSION_BOOST
+                }
+            }
+        }));
+    }
+    should.push(json!({
+        "term": {
+            "careers.contract_type": {
+                "value": career_feature.contract_type,
+                "boost": CONTRACT_TYPE_BOOST
+            }
+        }
+    }));
+    should.push(json!({
+        "term": {
+            "careers.is_manager": {
+                "value": career_feature.is_manager,
+                "boost": IS_MANAGER_BOOST
+            }
+        }
+    }));
+    let mut years_of_service_range = json!({
+        "gte": career_feature.years_of_service_equal_or_more,
+        "boost": YEARS_OF_SERVICE_BOOST
+    });
+    if let Some(lt) = career_feature.years_of_service_less_than {
+        years_of_service_range["lt"] = json!(lt);
+    }
+    should.push(json!({
+        "range": {
+            "careers.years_of_service": years_of_service_range
+        }
+    }));
+    json!({
+        "nested": {
+            "path": "careers",
+            "score_mode": "max",
+            "query": {
+                "bool": {
+                    "should": should
+                }
+            }
+        }
+    })
+}
+
+/// 入力補完で会社名、職種それぞれについて返す候補の最大数
+pub const MAX_NUM_OF_SUGGESTIONS: u32 = 10;
+
+/// 会社名、職種それぞれについて、prefixに前方一致する値をコンサルタントの数が多い順に集計するクエリを返す
+///
+/// 検索と同様に、相談を受け付けられるコンサルタントのみを対象とし、ユーザー自身（account_id）は除外する。
+/// 集計結果のみを利用するため、ドキュメント自体は取得しない（size 0で検索する）ことを想定している。
+pub fn create_suggestions_query_json(account_id: i64, prefix: &str) -> Value {
+    json!({
+        "query": {
+            "bool": {
+                "should": [
+                    create_nested_suggest_match_json("careers.company_name.suggest", prefix),
+                    create_nested_suggest_match_json("careers.profession.suggest", prefix)
+                ],
+                "minimum_should_match": 1,
+                "filter": create_available_consultant_filter_json(),
+                "must_not": [
+                    {
+                        "term": {
+                            "user_account_id": account_id
+                        }
+                    }
+                ]
+            }
+        },
+        "aggs": {
+            "careers": {
+                "nested": {
+                    "path": "careers"
+                },
+                "aggs": {
+                    "company_names": create_suggestions_aggregation_json(
+                        "careers.company_name.suggest",
+                        "careers.company_name.keyword",
+                        prefix
+                    ),
+                    "professions": create_suggestions_aggregation_json(
+                        "careers.profession.suggest",
+                        "careers.profession.keyword",
+                        prefix
+                    )
+                }
+            }
+        }
+    })
+}
+
+fn create_nested_suggest_match_json(suggest_field: &str, prefix: &str) -> Value {
+    json!({
+        "nested": {
+            "path": "careers",
+            "query": {
+                "match": {
+                    suggest_field: prefix
+                }
+            }
+        }
+    })
+}
+
+/// prefixに前方一致する職務経歴のみに絞り込んだ上で値ごとに集計する（コンサルタントが複数の職務経歴で同じ値を持つ場合、1人として数える）
+fn create_suggestions_aggregation_json(
+    suggest_field: &str,
+    keyword_field: &str,
+    prefix: &str,
+) -> Value {
+    json!({
+        "filter": {
+            "match": {
+                suggest_field: prefix
+            }
+        },
+        "aggs": {
+            "values": {
+                "terms": {
+                    "field": keyword_field,
+                    "size": MAX_NUM_OF_SUGGESTIONS,
+                    "order": {
+                        "consultants>_count": "desc"
+                    }
+                },
+                "aggs": {
+                    "consultants": {
+                        "reverse_nested": {}
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// ソートの条件を返す
+///
+/// 値が同じドキュメントの並び順を一意に定める（search_afterによるページングで重複や欠落が起きないようにする）ため、最後にuser_account_idの条件を加える
+pub fn create_sort_json(sort: Option<&Sort>) -> Vec<Value> {
+    let mut sort_json = Vec::with_capacity(2);
+    let Some(sort) = sort else {
+        sort_json.push(json!({ "_score": { "order": "desc" } }));
+        sort_json.push(json!({ "user_account_id": { "order": "desc" } }));
+        return sort_json;
+    };
+    let order = sort.order.as_str();
+    match sort.key.as_str() {
+        "max_years_of_service" => sort_json.push(json!({
+            "careers.years_of_service": {
+                "order": order,
+                "mode": "max",
+                "nested": {
+                    "path": "careers"
+                }
+            }
+        })),
+        // user_account_idは連番で採番されるため、その順序はアカウントの作成日時の順序と一致する
+        "joined_at" => {
+            sort_json.push(json!({ "user_account_id": { "order": order } }));
+            return sort_json;
+        }
+        // 会社名や職種等の文字列の検索条件に対する一致度
+        "relevance" => sort_json.push(json!({ "_score": { "order": order } })),
+        key => sort_json.push(json!({ key: { "order": order } })),
+    };
+    sort_json.push(json!({ "user_account_id": { "order": "desc" } }));
+    sort_json
+}
+
+/// 職種の集計で返す職種の最大数（件数の多い順）
+pub const MAX_NUM_OF_PROFESSION_BUCKETS: u32 = 20;
+/// 雇用形態（regular、contract、other）の数
+pub const NUM_OF_CONTRACT_TYPES: u32 = 3;
+/// 相談料の集計で利用する範囲の境界値（円）
+pub const FEE_PER_HOUR_IN_YEN_BOUNDARIES: [i32; 3] = [5000, 7000, 9000];
+/// 評価の集計で利用する範囲の境界値
+pub const RATING_BOUNDARIES: [f64; 3] = [2.0, 3.0, 4.0];
+
+/// 検索条件に一致したコンサルタントの数を職種、雇用形態、管理職か否か、相談料及び評価ごとに集計する条件（aggs）を返す
+pub fn create_aggregations_json() -> Value {
+    let fee_ranges: Vec<Value> = create_ranges(&FEE_PER_HOUR_IN_YEN_BOUNDARIES);
+    let rating_ranges: Vec<Value> = create_ranges(&RATING_BOUNDARIES);
+    json!({
+        "careers": {
+            "nested": {
+                "path": "careers"
+            },
+            "aggs": {
+                "professions": create_nested_terms_aggregation("careers.profession.keyword", MAX_NUM_OF_PROFESSION_BUCKETS),
+                "contract_types": create_nested_terms_aggregation("careers.contract_type", NUM_OF_CONTRACT_TYPES),
+                "is_manager": create_nested_terms_aggregation("careers.is_manager", 2)
+            }
+        },
+        "fee_per_hour_in_yen": {
+            "range": {
+                "field": "fee_per_hour_in_yen",
+                "ranges": fee_ranges
+            }
+        },
+        "rating": {
+            "range": {
+                "field": "rating",
+                "ranges": rating_ranges
+            }
+        }
+    })
+}
+
+/// 職務経歴（nested）の値ごとに、その値を持つ職務経歴のあるコンサルタントの数を集計する
+///
+/// 1人のコンサルタントが同じ値の職務経歴を複数持つ場合もあるため、reverse_nestedで親のドキュメントの数を数える
+fn create_nested_terms_aggregation(field: &str, size: u32) -> Value {
+    json!({
+        "terms": {
+            "field": field,
+            "size": size
+        },
+        "aggs": {
+            "consultants": {
+                "reverse_nested": {}
+            }
+        }
+    })
+}
+
+/// 境界値の一覧から、境界値で区切った範囲（最初の範囲は下限なし、最後の範囲は上限なし）を生成する
+pub fn create_ranges<T: Serialize + Copy>(boundaries: &[T]) -> Vec<Value> {
+    let mut ranges = Vec::with_capacity(boundaries.len() + 1);
+    for (i, boundary) in boundaries.iter().enumerate() {
+        if i == 0 {
+            ranges.push(json!({ "to": boundary }));
+        } else {
+            ranges.push(json!({ "from": boundaries[i - 1], "to": boundary }));
+        }
+    }
+    if let Some(last) = boundaries.last() {
+        ranges.push(json!({ "from": last }));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    #[test]
+    fn create_company_name_criteria_filters_by_normalized_company_name() {
+        let criteria = create_company_name_criteria("ﾄﾖﾀ自動車（株）");
+
+        let must = &criteria["nested"]["query"]["bool"]["must"][0]["multi_match"];
+        assert_eq!(json!("とよた自動車"), must["query"]);
+        assert_eq!(json!(["careers.normalized_company_name^1"]), must["fields"]);
+        let should = &criteria["nested"]["query"]["bool"]["should"][0]["multi_match"];
+        assert_eq!(json!("ﾄﾖﾀ自動車（株）"), should["query"]);
+        assert_eq!(json!(["careers.company_name^1"]), should["fields"]);
+    }
+
+    #[test]
+    fn create_profession_criteria_filters_by_normalized_text() {
+        let criteria = create_profession_criteria("ｴﾝｼﾞﾆｱ");
+
+        let must = &criteria["nested"]["query"]["bool"]["must"][0]["multi_match"];
+        assert_eq!(json!("えんじにあ"), must["query"]);
+        assert_eq!(json!(["careers.profession.ngram^1"]), must["fields"]);
+    }
+
+    #[test]
+    fn create_annual_income_in_man_yen_criteria_filters_by_annual_income_in_man_yen() {
+        let equal_or_more = create_annual_income_in_man_yen_equal_or_more_criteria(500);
+        let equal_or_less = create_annual_income_in_man_yen_equal_or_less_criteria(800);
+
+        let range = &equal_or_more["nested"]["query"]["bool"]["must"][0]["range"];
+        assert_eq!(
+            json!({ "gte": 500 }),
+            range["careers.annual_income_in_man_yen"]
+        );
+        let range = &equal_or_less["nested"]["query"]["bool"]["must"][0]["range"];
+        assert_eq!(
+            json!({ "lte": 800 }),
+            range["careers.annual_income_in_man_yen"]
+        );
+    }
+
+    #[test]
+    fn create_ranges_returns_ranges_split_by_boundaries() {
+        let ranges = create_ranges(&FEE_PER_HOUR_IN_YEN_BOUNDARIES);
+
+        assert_eq!(
+            vec![
+                json!({ "to": 5000 }),
+                json!({ "from": 5000, "to": 7000 }),
+                json!({ "from": 7000, "to": 9000 }),
+                json!({ "from": 9000 })
+            ],
+            ranges
+        );
+    }
+
+    #[test]
+    fn create_sort_json_adds_user_account_id_as_tiebreaker() {
+        let sort = create_sort_json(Some(&Sort {
+            key: "max_years_of_service".to_string(),
+            order: "desc".to_string(),
+        }));
+
+        assert_eq!(
+            vec![
+                json!({
+                    "careers.years_of_service": {
+                        "order": "desc",
+                        "mode": "max",
+                        "nested": {
+                            "path": "careers"
+                        }
+                    }
+                }),
+                json!({ "user_account_id": { "order": "desc" } })
+            ],
+            sort
+        );
+    }
+
+    #[test]
+    fn create_sort_json_sorts_by_relevance_if_no_sort_param_specified() {
+        let sort = create_sort_json(None);
+
+        assert_eq!(
+            vec![
+                json!({ "_score": { "order": "desc" } }),
+                json!({ "user_account_id": { "order": "desc" } })
+            ],
+            sort
+        );
+    }
+
+    #[test]
+    fn create_sort_json_sorts_by_user_account_id_only_if_joined_at_specified() {
+        let sort = create_sort_json(Some(&Sort {
+            key: "joined_at".to_string(),
+            order: "desc".to_string(),
+        }));
+
+        assert_eq!(
+            vec![json!({ "user_account_id": { "order": "desc" } })],
+            sort
+        );
+    }
+
+    #[test]
+    fn create_company_id_criteria_filters_by_company_id() {
+        let criteria = create_company_id_criteria(12);
+
+        let must = &criteria["nested"]["query"]["bool"]["must"][0]["term"];
+        assert_eq!(json!(12), must["careers.company_id"]);
+    }
+
+    #[test]
+    fn create_career_similarity_criteria_skips_missing_fields() {
+        let criteria = create_career_similarity_criteria(&CareerFeature {
+            company_id: None,
+            profession: None,
+            contract_type: "contract".to_string(),
+            is_manager: true,
+            years_of_service_equal_or_more: 20,
+            years_of_service_less_than: None,
+        });
+
+        let should = criteria["nested"]["query"]["bool"]["should"]
+            .as_array()
+            .expect("failed to get array");
+        assert_eq!(3, should.len());
+        assert_eq!(
+            json!({ "gte": 20, "boost": YEARS_OF_SERVICE_BOOST }),
+            should[2]["range"]["careers.years_of_service"]
+        );
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2023 Ken Miura
+
+//! インデックス（users）の設定とマッピングを定義するモジュール
+//!
+//! アナライザの変更やフィールドの追加など、定義を変更する場合は[INDEX_DEFINITION_VERSION]を1つ増やし、
+//! users_index_toolのmigrateを実行して新しい定義のインデックスへ移行する。
+//! ただし、ドキュメントに格納する値の追加や変更を伴う場合（既存のドキュメントをコピーするだけでは値が揃わない場合）は、migrateではなくrebuildを実行する。
+
+use serde_json::{json, Value};
+
+/// インデックスの定義のバージョン
+///
+/// 定義を変更した際に1つ増やす。インデックスを作成する際、マッピングの_meta.versionとして記録する。
+pub const INDEX_DEFINITION_VERSION: u32 = 5;
+
+/// インデックスの設定とマッピングを[Create index API](https://opensearch.org/docs/2.2/api-reference/index-apis/create-index/)のリクエストボディとして返す
+pub fn create_index_definition() -> Value {
+    json!({
+        "settings": create_settings(),
+        "mappings": create_mappings()
+    })
+}
+
+/// インデックスに記録されたマッピングから定義のバージョンを取得する
+///
+/// mappingは[Get mapping API](https://opensearch.org/docs/2.2/api-reference/index-apis/get-mapping/)のレスポンスに含まれる各インデックスのmappings
+pub fn find_index_definition_version(mapping: &Value) -> Option<u32> {
+    mapping["_meta"]["version"]
+        .as_u64()
+        .and_then(|v| u32::try_from(v).ok())
+}
+
+fn create_settings() -> Value {
+    json!({
+        "analysis": {
+            "char_filter": {
+                "normalize": {
+                    "type": "icu_normalizer",
+                    "name": "nfkc",
+                    "mode": "compose"
+                }
+            },
+            "tokenizer": {
+                "ja_kuromoji_tokenizer": {
+                    "mode": "search",
+                    "type": "kuromoji_tokenizer",
+                    "discard_compound_token": true
+                },
+                "ja_ngram_tokenizer": {
+                    "type": "ngram",
+                    "min_gram": 2,
+                    "max_gram": 2,
+                    "token_chars": ["letter", "digit"]
+                },
+                // 入力補完用に、入力された文字列全体の前方一致を扱う（token_charsを指定しないため空白等でも分割しない）
+                "ja_edge_ngram_tokenizer": {
+                    "type": "edge_ngram",
+                    "min_gram": 1,
+                    "max_gram": 20
+                }
+            },
+            "filter": {
+                "ja_index_synonym": {
+                    "type": "synonym",
+                    "lenient": false,
+                    "synonyms": []
+                },
+                "ja_search_synonym": {
+                    "type": "synonym_graph",
+                    "lenient": false,
+                    "synonyms": []
+                },
+                // normalization::normalize_textと同様にカタカナをひらがなに揃える
+                "ja_katakana_to_hiragana": {
+                    "type": "icu_transform",
+                    "id": "Katakana-Hiragana"
+                }
+            },
+            "analyzer": {
+                "ja_kuromoji_index_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "ja_kuromoji_tokenizer",
+                    "filter": [
+                        "kuromoji_baseform",
+                        "kuromoji_part_of_speech",
+                        "ja_index_synonym",
+                        "cjk_width",
+                        "ja_stop",
+                        "kuromoji_stemmer",
+                        "lowercase"
+                    ]
+                },
+                "ja_kuromoji_search_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "ja_kuromoji_tokenizer",
+                    "filter": [
+                        "kuromoji_baseform",
+                        "kuromoji_part_of_speech",
+                        "ja_search_synonym",
+                        "cjk_width",
+                        "ja_stop",
+                        "kuromoji_stemmer",
+                        "lowercase"
+                    ]
+                },
+                "ja_ngram_index_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "ja_ngram_tokenizer",
+                    "filter": ["lowercase", "ja_katakana_to_hiragana"]
+                },
+                "ja_ngram_search_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "ja_ngram_tokenizer",
+                    "filter": ["ja_search_synonym", "lowercase", "ja_katakana_to_hiragana"]
+                },
+                "ja_edge_ngram_index_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "ja_edge_ngram_tokenizer",
+                    "filter": ["lowercase", "ja_katakana_to_hiragana"]
+                },
+                // 入力された文字列全体を前方一致の対象とするため、検索時は分割しない
+                "ja_edge_ngram_search_analyzer": {
+                    "type": "custom",
+                    "char_filter": ["normalize"],
+                    "tokenizer": "keyword",
+                    "filter": ["lowercase", "ja_katakana_to_hiragana"]
+                }
+            }
+        }
+    })
+}
+
+fn create_mappings() -> Value {
+    json!({
+        "dynamic": "strict",
+        "_meta": {
+            "version": INDEX_DEFINITION_VERSION
+        },
+        "properties": {
+            "user_account_id": {
+                "type": "long"
+            },
+            "careers": {
+                "type": "nested",
+                "properties": {
+                    "career_id": {
+                        "type": "long"
+                    },
+                    // 入力補完で会社名の候補を返すため、keywordとsuggestとしても格納する
+                    "company_name": create_japanese_text_field_with_suggestion(),
+                    // normalization::normalize_company_nameで正規化した会社名（法人格の表記を除去しているため、形態素解析は行わずngramでのみ解析する）
+                    "normalized_company_name": {
+                        "type": "text",
+                        "search_analyzer": "ja_ngram_search_analyzer",
+                        "analyzer": "ja_ngram_index_analyzer"
+                    },
+                    // 会社のマスタ（company）のID。表記に依らず同一の会社で絞り込む際に利用する
+                    "company_id": {
+                        "type": "long"
+                    },
+                    "department_name": create_japanese_text_field(),
+                    "office": create_japanese_text_field(),
+                    "years_of_service": {
+                        "type": "integer"
+                    },
+                    "employed": {
+                        "type": "boolean"
+                    },
+                    "contract_type": {
+                        "type": "keyword"
+                    },
+                    // 検索結果の集計（職種ごとの件数）と入力補完で利用するため、keywordとsuggestとしても格納する
+                    "profession": create_japanese_text_field_with_suggestion(),
+                    "annual_income_in_man_yen": {
+                        "type": "integer"
+                    },
+                    "is_manager": {
+                        "type": "boolean"
+                    },
+                    "position_name": create_japanese_text_field(),
+                    "is_new_graduate": {
+                        "type": "boolean"
+                    },
+                    "note": create_japanese_text_field()
+                }
+            },
+            "num_of_careers": {
+                "type": "integer"
+            },
+            "fee_per_hour_in_yen": {
+                "type": "integer"
+            },
+            "is_bank_account_registered": {
+                "type": "boolean"
+            },
+            "rating": {
+                "type": "double"
+            },
+            "num_of_rated": {
+                "type": "integer"
+            },
+            "disabled": {
+                "type": "boolean"
+            }
+        }
+    })
+}
+
+/// 日本語の文字列を格納するフィールド（形態素解析用とngram用の二種類のアナライザで解析する）
+fn create_japanese_text_field() -> Value {
+    json!({
+        "type": "text",
+        "search_analyzer": "ja_kuromoji_search_analyzer",
+        "analyzer": "ja_kuromoji_index_analyzer",
+        "fields": {
+            "ngram": {
+                "type": "text",
+                "search_analyzer": "ja_ngram_search_analyzer",
+                "analyzer": "ja_ngram_index_analyzer"
+            }
+        }
+    })
+}
+
+/// [create_japanese_text_field]に加え、集計用に入力された文字列をそのままkeywordとして格納する
+fn create_japanese_text_field_with_keyword() -> Value {
+    let mut field = create_japanese_text_field();
+    field["fields"]["keyword"] = json!({
+        "type": "keyword",
+        "ignore_above": 256
+    });
+    field
+}
+
+/// [create_japanese_text_field_with_keyword]に加え、入力補完用に前方一致で検索できるようsuggestとして格納する
+fn create_japanese_text_field_with_suggestion() -> Value {
+    let mut field = create_japanese_text_field_with_keyword();
+    field["fields"]["suggest"] = json!({
+        "type": "text",
+        "search_analyzer": "ja_edge_ngram_search_analyzer",
+        "analyzer": "ja_edge_ngram_index_analyzer"
+    });
+    field
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::NaiveDate;
+
+    use crate::opensearch::create_career_document;
+
+    use super::*;
+
+    #[test]
+    fn create_index_definition_records_version() {
+        let definition = create_index_definition();
+
+        let version = find_index_definition_version(&definition["mappings"]);
+
+        assert_eq!(Some(INDEX_DEFINITION_VERSION), version);
+    }
+
+    #[test]
+    fn find_index_definition_version_returns_none_if_not_recorded() {
+        let mapping = json!({
+            "dynamic": "strict",
+            "properties": {}
+        });
+
+        let version = find_index_definition_version(&mapping);
+
+        assert_eq!(None, version);
+    }
+
+    #[test]
+    fn create_index_definition_has_mapping_for_all_career_fields() {
+        // マッピングはstrictのため、ドキュメントに格納するフィールドは全てマッピングに定義されている必要がある
+        let career = entity::career::Model {
+            career_id: 1,
+            user_account_id: 1,
+            company_name: "テスト株式会社".to_string(),
+            company_id: 1,
+            department_name: None,
+            office: None,
+            career_start_date: NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok"),
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: None,
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: false,
+            note: None,
+            verified_work_email_domain: None,
+        };
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let career_document = create_career_document(&career, current_date);
+        let definition = create_index_definition();
+        let career_properties = &definition["mappings"]["properties"]["careers"]["properties"];
+
+        let career_document = career_document.as_object().expect("failed to get object");
+        for key in career_document.keys() {
+            assert!(
+                !career_properties[key].is_null(),
+                "no mapping found for {}",
+                key
+            );
+        }
+        assert_eq!(
+            career_document.len(),
+            career_properties
+                .as_object()
+                .expect("failed to get object")
+                .len()
+        );
+    }
+
+    #[test]
+    fn create_index_definition_has_suggest_fields_with_defined_analyzers() {
+        let definition = create_index_definition();
+        let career_properties = &definition["mappings"]["properties"]["careers"]["properties"];
+        let analyzers = &definition["settings"]["analysis"]["analyzer"];
+
+        for key in ["company_name", "profession"] {
+            let suggest = &career_properties[key]["fields"]["suggest"];
+            let keyword = &career_properties[key]["fields"]["keyword"];
+            assert_eq!(json!("keyword"), keyword["type"], "{}", key);
+            for analyzer_key in ["analyzer", "search_analyzer"] {
+                let analyzer = suggest[analyzer_key]
+                    .as_str()
+                    .expect("failed to get analyzer");
+                assert!(!analyzers[analyzer].is_null(), "{}: {}", key, analyzer);
+            }
+        }
+    }
+}
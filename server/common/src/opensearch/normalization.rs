@@ -0,0 +1,189 @@
+// Copyright 2023 Ken Miura
+
+//! インデックスへの登録時と検索時で共通して利用する文字列の正規化を行うモジュール
+//!
+//! 登録時と検索時で同じ正規化を行わないと表記揺れにより一致しなくなるため、
+//! インデックスに登録する値と検索条件の値の両方で必ずこのモジュールの関数を通す。
+
+use unicode_normalization::UnicodeNormalization;
+
+/// 会社名の前後に付く法人格の表記（NFKC正規化、小文字化した後の表記で記載する）
+///
+/// 長い表記を先に判定する必要があるため、同じ表記を含むものは長い方を先に記載する。
+/// 英語表記の「Co., Ltd.」は「ltd.」と「co.」を順に除去することで対応する
+pub(crate) const LEGAL_ENTITY_DESIGNATIONS: [&str; 29] = [
+    "特定非営利活動法人",
+    "一般社団法人",
+    "一般財団法人",
+    "公益社団法人",
+    "公益財団法人",
+    "独立行政法人",
+    "社会福祉法人",
+    "株式会社",
+    "有限会社",
+    "合同会社",
+    "合資会社",
+    "合名会社",
+    "相互会社",
+    "社団法人",
+    "財団法人",
+    "医療法人",
+    "学校法人",
+    "npo法人",
+    "(株)",
+    "(有)",
+    "(同)",
+    "(資)",
+    "(名)",
+    "(社)",
+    "(財)",
+    "ltd.",
+    "co.",
+    "inc.",
+    "corp.",
+];
+
+/// 文字列を正規化する
+///
+/// 下記を順に行う
+/// <ul>
+///   <li>NFKC正規化（全角英数字、半角カナ、丸囲み文字（例: ㈱）などを標準的な表記に揃える）</li>
+///   <li>小文字化</li>
+///   <li>カタカナをひらがなに変換</li>
+///   <li>前後の空白を除去し、連続する空白を一つにまとめる</li>
+/// </ul>
+pub fn normalize_text(text: &str) -> String {
+    let normalized = text
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .map(katakana_to_hiragana)
+        .collect::<String>();
+    normalized
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// 会社名を正規化する
+///
+/// [normalize_text]による正規化に加え、前後に付く法人格の表記（例: 株式会社、(株)）を除去する。
+/// 法人格の表記を除去した結果が空文字となる場合、法人格の表記は除去しない。
+pub fn normalize_company_name(company_name: &str) -> String {
+    let normalized = normalize_text(company_name);
+    let stripped = strip_legal_entity_designations(&normalized);
+    if stripped.is_empty() {
+        normalized
+    } else {
+        stripped
+    }
+}
+
+fn strip_legal_entity_designations(company_name: &str) -> String {
+    let mut result = company_name;
+    loop {
+        let before = result;
+        for designation in LEGAL_ENTITY_DESIGNATIONS {
+            if let Some(stripped) = result.strip_prefix(designation) {
+                result = stripped.trim();
+            }
+            if let Some(stripped) = result.strip_suffix(designation) {
+                result = stripped.trim_end_matches([' ', ',']);
+            }
+        }
+        if result == before {
+            break;
+        }
+    }
+    result.to_string()
+}
+
+fn katakana_to_hiragana(c: char) -> char {
+    // ァ（U+30A1）からヶ（U+30F6）は、ぁ（U+3041）からゖ（U+3096）と同じ並びで対応している
+    if ('\u{30A1}'..='\u{30F6}').contains(&c) {
+        char::from_u32(c as u32 - 0x60).unwrap_or(c)
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn normalize_text_converts_full_width_alphanumeric_to_half_width_lower_case() {
+        assert_eq!("abc123", normalize_text("ＡＢＣ１２３"));
+    }
+
+    #[test]
+    fn normalize_text_converts_half_width_katakana_to_hiragana() {
+        assert_eq!("とよた", normalize_text("ﾄﾖﾀ"));
+    }
+
+    #[test]
+    fn normalize_text_converts_katakana_to_hiragana() {
+        assert_eq!("えんじにあ", normalize_text("エンジニア"));
+        assert_eq!("とよた", normalize_text("とよた"));
+    }
+
+    #[test]
+    fn normalize_text_keeps_prolonged_sound_mark_and_kanji() {
+        assert_eq!("さーびす開発部", normalize_text("サービス開発部"));
+    }
+
+    #[test]
+    fn normalize_text_collapses_white_spaces() {
+        assert_eq!("営業部 第一課", normalize_text("　営業部　 第一課 "));
+    }
+
+    #[test]
+    fn normalize_company_name_strips_legal_entity_designations() {
+        let expected = "とよた自動車";
+        assert_eq!(expected, normalize_company_name("トヨタ自動車株式会社"));
+        assert_eq!(expected, normalize_company_name("株式会社トヨタ自動車"));
+        assert_eq!(expected, normalize_company_name("トヨタ自動車(株)"));
+        assert_eq!(expected, normalize_company_name("トヨタ自動車（株）"));
+        assert_eq!(expected, normalize_company_name("トヨタ自動車㈱"));
+        assert_eq!(expected, normalize_company_name("(株) トヨタ自動車"));
+        assert_eq!(expected, normalize_company_name("ﾄﾖﾀ自動車 株式会社"));
+    }
+
+    #[test]
+    fn normalize_company_name_strips_other_legal_entity_designations() {
+        assert_eq!("てすと", normalize_company_name("有限会社テスト"));
+        assert_eq!("てすと", normalize_company_name("テスト合同会社"));
+        assert_eq!("てすと", normalize_company_name("一般社団法人テスト"));
+        assert_eq!("てすと", normalize_company_name("特定非営利活動法人テスト"));
+        assert_eq!("てすと", normalize_company_name("ＮＰＯ法人テスト"));
+    }
+
+    #[test]
+    fn normalize_company_name_strips_english_legal_entity_designations() {
+        assert_eq!("example", normalize_company_name("Example Co., Ltd."));
+        assert_eq!("example", normalize_company_name("Example, Inc."));
+        assert_eq!("example", normalize_company_name("EXAMPLE Corp."));
+    }
+
+    #[test]
+    fn normalize_company_name_does_not_strip_legal_entity_designation_in_middle() {
+        assert_eq!(
+            "てすと株式会社ほーるでぃんぐす",
+            normalize_company_name("テスト株式会社ホールディングス")
+        );
+    }
+
+    #[test]
+    fn normalize_company_name_keeps_legal_entity_designation_if_nothing_remains() {
+        assert_eq!("株式会社", normalize_company_name("株式会社"));
+    }
+
+    #[test]
+    fn normalize_company_name_returns_same_value_for_query_and_document() {
+        // 検索時の入力（会社名の一部）が、登録時の値に含まれること
+        let document = normalize_company_name("トヨタ自動車株式会社");
+        let query = normalize_company_name("トヨタ");
+
+        assert!(document.contains(&query));
+    }
+}
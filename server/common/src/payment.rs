@@ -0,0 +1,599 @@
+// Copyright 2023 Ken Miura
+
+//! 決済代行サービス（クレジットカード決済）に関連する構造体、関数を集約するモジュール
+
+use axum::{async_trait, http::StatusCode, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use entity::sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, QueryFilter, Set,
+};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{env::var, error::Error, fmt::Display};
+use tracing::error;
+
+use crate::{err, ApiError, ErrResp, ErrRespStruct};
+
+/// コンサルタントへ報酬を振り込むまで待機する期間（単位：日）
+pub const WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS: i64 = 8;
+
+pub const KEY_TO_USE_STUB_PAYMENT_GATEWAY: &str = "USE_STUB_PAYMENT_GATEWAY";
+/// 決済代行サービスの代わりにスタブを使うかどうかを示す値
+///
+/// ローカル環境での動作確認時のみtrueを指定する。スタブを使う場合、決済代行サービスへの通信は一切発生しない。
+pub static USE_STUB_PAYMENT_GATEWAY: Lazy<bool> = Lazy::new(|| {
+    var(KEY_TO_USE_STUB_PAYMENT_GATEWAY).unwrap_or_else(|_| {
+        panic!(
+            "Not environment variable found: environment variable \"{}\" (example value: \"false\") must be set",
+            KEY_TO_USE_STUB_PAYMENT_GATEWAY
+        );
+    }).parse().expect("failed to parse")
+});
+
+pub const KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI: &str = "PAYMENT_GATEWAY_ENDPOINT_URI";
+pub static PAYMENT_GATEWAY_ENDPOINT_URI: Lazy<String> = Lazy::new(|| {
+    var(KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI).unwrap_or_else(|_| {
+        panic!(
+            "Not environment variable found: environment variable \"{}\" (example value: \"https://api.payment-gateway.example.com\") must be set",
+            KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI
+        );
+    })
+});
+
+pub const KEY_TO_PAYMENT_GATEWAY_SECRET_KEY: &str = "PAYMENT_GATEWAY_SECRET_KEY";
+pub static PAYMENT_GATEWAY_SECRET_KEY: Lazy<String> = Lazy::new(|| {
+    var(KEY_TO_PAYMENT_GATEWAY_SECRET_KEY).unwrap_or_else(|_| {
+        panic!(
+            "Not environment variable found: environment variable \"{}\" must be set",
+            KEY_TO_PAYMENT_GATEWAY_SECRET_KEY
+        );
+    })
+});
+
+pub const KEY_TO_PAYMENT_GATEWAY_WEBHOOK_SECRET: &str = "PAYMENT_GATEWAY_WEBHOOK_SECRET";
+/// 決済代行サービスから送られてくるWebhookの署名を検証するための秘密鍵
+pub static PAYMENT_GATEWAY_WEBHOOK_SECRET: Lazy<String> = Lazy::new(|| {
+    var(KEY_TO_PAYMENT_GATEWAY_WEBHOOK_SECRET).unwrap_or_else(|_| {
+        // UTのためにダミー用の初期値を設定しているだけ
+        // 実行時には環境変数を指定して適切なものに入れ替える
+        "whsec_test".to_string()
+    })
+});
+
+/// Webhookの署名を含むHTTPヘッダ名
+pub const PAYMENT_GATEWAY_SIGNATURE_HEADER: &str = "payment-gateway-signature";
+
+/// Webhookの署名に含まれるタイムスタンプと現在時刻の差として許容する最大値（単位：秒）
+///
+/// 過去に送られたWebhookを再送するリプレイ攻撃を防ぐため、これを超える差がある場合は署名の検証に失敗したものとして扱う。
+pub const WEBHOOK_TOLERANCE_IN_SECONDS: i64 = 300;
+
+/// 決済が完了したことを示すWebhookのイベント種別
+pub const EVENT_TYPE_CHECKOUT_SESSION_COMPLETED: &str = "checkout.session.completed";
+
+/// クレジットカード決済時に振込依頼人の代わりとして記録する文字列を返す
+///
+/// クレジットカード決済には振込依頼人が存在しないため、決済を識別するIDを含めた文字列を記録する。
+pub fn create_sender_name_for_card_payment(payment_id: &str) -> String {
+    format!("カード決済（{}）", payment_id)
+}
+
+/// 返金を依頼したクレジットカード決済
+///
+/// [record_card_payment_refund_request]で返金の依頼をDBに記録し、トランザクションをコミットした後、[execute_card_payment_refund]で決済代行サービスを通じて返金する。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CardPaymentRefund {
+    pub checkout_session_id: String,
+    pub payment_id: String,
+    pub amount_in_yen: i32,
+    pub idempotency_key: String,
+}
+
+/// クレジットカード決済の返金を依頼したことを記録する
+///
+/// 決済代行サービスへの返金依頼は取り消しができないため、返金依頼の前に、返金する状態への遷移とあわせて同じトランザクション内で呼び出す。
+/// 返金に利用する冪等キーを記録しておくことで、返金依頼に失敗した場合でも、同じキーで二重に返金されることなく再試行できる。
+pub async fn record_card_payment_refund_request(
+    card_payment: entity::card_payment::Model,
+    idempotency_key: String,
+    txn: &DatabaseTransaction,
+) -> Result<CardPaymentRefund, ErrRespStruct> {
+    let payment_id = card_payment.payment_id.clone().ok_or_else(|| {
+        error!("no payment_id found (card_payment: {:?})", card_payment);
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    let refund = CardPaymentRefund {
+        checkout_session_id: card_payment.checkout_session_id.clone(),
+        payment_id,
+        amount_in_yen: card_payment.amount_in_yen,
+        idempotency_key: idempotency_key.clone(),
+    };
+    let mut active_model: entity::card_payment::ActiveModel = card_payment.into();
+    active_model.refund_idempotency_key = Set(Some(idempotency_key));
+    let _ = active_model.update(txn).await.map_err(|e| {
+        error!(
+            "failed to update card_payment (card_payment_refund: {:?}): {}",
+            refund, e
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(refund)
+}
+
+/// 決済代行サービスを通じてクレジットカード決済を返金し、返金したことを記録する
+///
+/// [record_card_payment_refund_request]を呼び出したトランザクションをコミットした後に呼び出す。
+/// 返金後の記録に失敗した場合でも、同じ引数で再度呼び出せば二重に返金されることはない。
+pub async fn execute_card_payment_refund(
+    refund: &CardPaymentRefund,
+    current_date_time: DateTime<FixedOffset>,
+    payment_gateway: &(impl PaymentGateway + Sync),
+    pool: &DatabaseConnection,
+) -> Result<(), ErrResp> {
+    let refund_id = payment_gateway
+        .refund(
+            &refund.payment_id,
+            refund.amount_in_yen,
+            &refund.idempotency_key,
+        )
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to refund card_payment, retry is required (card_payment_refund: {:?})",
+                refund
+            );
+            e
+        })?;
+    let _ = entity::card_payment::Entity::update_many()
+        .col_expr(
+            entity::card_payment::Column::RefundId,
+            Expr::value(refund_id.clone()),
+        )
+        .col_expr(
+            entity::card_payment::Column::RefundedAt,
+            Expr::value(current_date_time),
+        )
+        .filter(entity::card_payment::Column::CheckoutSessionId.eq(refund.checkout_session_id.clone()))
+        .filter(entity::card_payment::Column::RefundIdempotencyKey.eq(refund.idempotency_key.clone()))
+        .filter(entity::card_payment::Column::RefundId.is_null())
+        .exec(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to record refund of card_payment, retry is required (card_payment_refund: {:?}, refund_id: {}): {}",
+                refund, refund_id, e
+            );
+            unexpected_err_resp()
+        })?;
+    Ok(())
+}
+
+/// 決済代行サービス上に作成された決済ページ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckoutSession {
+    pub checkout_session_id: String,
+    /// ユーザーをリダイレクトさせる決済ページのURL
+    pub url: String,
+}
+
+#[async_trait]
+pub trait PaymentGateway {
+    /// 相談料をクレジットカードで支払うための決済ページを作成する
+    ///
+    /// 決済が完了すると、決済代行サービスからWebhookで通知される。
+    async fn create_checkout_session(
+        &self,
+        consultation_id: i64,
+        amount_in_yen: i32,
+        success_url: &str,
+        cancel_url: &str,
+    ) -> Result<CheckoutSession, ErrResp>;
+
+    /// 決済済みの支払いを返金し、返金を識別するIDを返す
+    ///
+    /// idempotency_keyが同じ場合、決済代行サービス側で二重に返金されることはない。
+    async fn refund(
+        &self,
+        payment_id: &str,
+        amount_in_yen: i32,
+        idempotency_key: &str,
+    ) -> Result<String, ErrResp>;
+}
+
+#[derive(Clone)]
+pub struct PaymentGatewayClient {
+    inner: PaymentGatewayClientInner,
+}
+
+#[derive(Clone)]
+enum PaymentGatewayClientInner {
+    Http {
+        client: reqwest::Client,
+        endpoint_uri: String,
+        secret_key: String,
+    },
+    Stub(StubPaymentGateway),
+}
+
+impl PaymentGatewayClient {
+    /// 決済代行サービスのAPIを呼び出すクライアントを生成する。
+    pub fn new(endpoint_uri: &str, secret_key: &str) -> Self {
+        Self {
+            inner: PaymentGatewayClientInner::Http {
+                client: reqwest::Client::new(),
+                endpoint_uri: endpoint_uri.to_string(),
+                secret_key: secret_key.to_string(),
+            },
+        }
+    }
+
+    /// [StubPaymentGateway]を利用するクライアントを生成する。ローカル環境での動作確認用
+    pub fn new_stub() -> Self {
+        Self {
+            inner: PaymentGatewayClientInner::Stub(StubPaymentGateway),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CheckoutSessionResp {
+    id: String,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RefundResp {
+    id: String,
+}
+
+#[async_trait]
+impl PaymentGateway for PaymentGatewayClient {
+    async fn create_checkout_session(
+        &self,
+        consultation_id: i64,
+        amount_in_yen: i32,
+        success_url: &str,
+        cancel_url: &str,
+    ) -> Result<CheckoutSession, ErrResp> {
+        let (client, endpoint_uri, secret_key) = match &self.inner {
+            PaymentGatewayClientInner::Http {
+                client,
+                endpoint_uri,
+                secret_key,
+            } => (client, endpoint_uri, secret_key),
+            PaymentGatewayClientInner::Stub(stub) => {
+                return stub
+                    .create_checkout_session(
+                        consultation_id,
+                        amount_in_yen,
+                        success_url,
+                        cancel_url,
+                    )
+                    .await
+            }
+        };
+        let params = [
+            ("client_reference_id", consultation_id.to_string()),
+            ("amount", amount_in_yen.to_string()),
+            ("currency", "jpy".to_string()),
+            ("success_url", success_url.to_string()),
+            ("cancel_url", cancel_url.to_string()),
+        ];
+        let resp = client
+            .post(format!("{}/v1/checkout/sessions", endpoint_uri))
+            .bearer_auth(secret_key)
+            .form(&params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                error!(
+                    "failed to create checkout session (consultation_id: {}, amount_in_yen: {}): {}",
+                    consultation_id, amount_in_yen, e
+                );
+                unexpected_err_resp()
+            })?;
+        let session = resp.json::<CheckoutSessionResp>().await.map_err(|e| {
+            error!(
+                "failed to parse checkout session response (consultation_id: {}): {}",
+                consultation_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(CheckoutSession {
+            checkout_session_id: session.id,
+            url: session.url,
+        })
+    }
+
+    async fn refund(
+        &self,
+        payment_id: &str,
+        amount_in_yen: i32,
+        idempotency_key: &str,
+    ) -> Result<String, ErrResp> {
+        let (client, endpoint_uri, secret_key) = match &self.inner {
+            PaymentGatewayClientInner::Http {
+                client,
+                endpoint_uri,
+                secret_key,
+            } => (client, endpoint_uri, secret_key),
+            PaymentGatewayClientInner::Stub(stub) => {
+                return stub
+                    .refund(payment_id, amount_in_yen, idempotency_key)
+                    .await
+            }
+        };
+        let params = [
+            ("payment_id", payment_id.to_string()),
+            ("amount", amount_in_yen.to_string()),
+        ];
+        let resp = client
+            .post(format!("{}/v1/refunds", endpoint_uri))
+            .bearer_auth(secret_key)
+            .header("Idempotency-Key", idempotency_key)
+            .form(&params)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                error!(
+                    "failed to refund (payment_id: {}, amount_in_yen: {}, idempotency_key: {}): {}",
+                    payment_id, amount_in_yen, idempotency_key, e
+                );
+                unexpected_err_resp()
+            })?;
+        let refund = resp.json::<RefundResp>().await.map_err(|e| {
+            error!(
+                "failed to parse refund response (payment_id: {}): {}",
+                payment_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(refund.id)
+    }
+}
+
+/// ローカル環境での動作確認用の[PaymentGateway]の実装
+///
+/// 決済ページは作成せず、success_urlをそのまま決済ページのURLとして返す。
+/// 決済完了のWebhookは送られないため、動作確認時は[create_webhook_signature]で署名したリクエストを手動で送る。
+#[derive(Clone, Debug)]
+pub struct StubPaymentGateway;
+
+#[async_trait]
+impl PaymentGateway for StubPaymentGateway {
+    async fn create_checkout_session(
+        &self,
+        consultation_id: i64,
+        _amount_in_yen: i32,
+        success_url: &str,
+        _cancel_url: &str,
+    ) -> Result<CheckoutSession, ErrResp> {
+        Ok(CheckoutSession {
+            checkout_session_id: format!(
+                "cs_stub_{}_{}",
+                consultation_id,
+                Utc::now().timestamp_millis()
+            ),
+            url: success_url.to_string(),
+        })
+    }
+
+    async fn refund(
+        &self,
+        _payment_id: &str,
+        _amount_in_yen: i32,
+        idempotency_key: &str,
+    ) -> Result<String, ErrResp> {
+        Ok(format!("re_stub_{}", idempotency_key))
+    }
+}
+
+fn unexpected_err_resp() -> ErrResp {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiError {
+            code: err::Code::UnexpectedErr as u32,
+        }),
+    )
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebhookSignatureError {
+    InvalidFormat,
+    TimestampOutOfTolerance {
+        timestamp: i64,
+        current_timestamp: i64,
+    },
+    SignatureMismatch,
+}
+
+impl Display for WebhookSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookSignatureError::InvalidFormat => write!(f, "invalid signature header format"),
+            WebhookSignatureError::TimestampOutOfTolerance {
+                timestamp,
+                current_timestamp,
+            } => write!(
+                f,
+                "timestamp is out of tolerance (timestamp: {}, current_timestamp: {})",
+                timestamp, current_timestamp
+            ),
+            WebhookSignatureError::SignatureMismatch => write!(f, "signature mismatch"),
+        }
+    }
+}
+
+impl Error for WebhookSignatureError {}
+
+/// Webhookの署名ヘッダの値を生成する
+///
+/// 署名ヘッダの値は「t=(UNIX時間),v1=(署名)」の形式となる。署名は「(UNIX時間).(ペイロード)」をHMAC-SHA256で計算し、16進数で表したもの。
+pub fn create_webhook_signature(payload: &[u8], secret: &str, timestamp: i64) -> String {
+    let signature = hex::encode(
+        calculate_mac(payload, secret, timestamp)
+            .finalize()
+            .into_bytes(),
+    );
+    format!("t={},v1={}", timestamp, signature)
+}
+
+/// Webhookの署名ヘッダの値を検証する
+///
+/// 署名が一致しない場合に加え、署名に含まれるタイムスタンプと現在時刻の差が[WEBHOOK_TOLERANCE_IN_SECONDS]を超える場合もエラーとなる。
+pub fn verify_webhook_signature(
+    payload: &[u8],
+    signature_header: &str,
+    secret: &str,
+    current_timestamp: i64,
+) -> Result<(), WebhookSignatureError> {
+    let mut timestamp: Option<i64> = None;
+    let mut signatures = Vec::new();
+    for element in signature_header.split(',') {
+        let (key, value) = element
+            .trim()
+            .split_once('=')
+            .ok_or(WebhookSignatureError::InvalidFormat)?;
+        if key == "t" {
+            timestamp = Some(
+                value
+                    .parse::<i64>()
+                    .map_err(|_| WebhookSignatureError::InvalidFormat)?,
+            );
+        } else if key == "v1" {
+            signatures.push(hex::decode(value).map_err(|_| WebhookSignatureError::InvalidFormat)?);
+        }
+    }
+    let timestamp = timestamp.ok_or(WebhookSignatureError::InvalidFormat)?;
+    if signatures.is_empty() {
+        return Err(WebhookSignatureError::InvalidFormat);
+    }
+    if (current_timestamp - timestamp).abs() > WEBHOOK_TOLERANCE_IN_SECONDS {
+        return Err(WebhookSignatureError::TimestampOutOfTolerance {
+            timestamp,
+            current_timestamp,
+        });
+    }
+    // 秘密鍵の更新時等、署名が複数含まれることがあるため、いずれかが一致すれば正しい署名とみなす
+    let matched = signatures.iter().any(|signature| {
+        calculate_mac(payload, secret, timestamp)
+            .verify_slice(signature)
+            .is_ok()
+    });
+    if !matched {
+        return Err(WebhookSignatureError::SignatureMismatch);
+    }
+    Ok(())
+}
+
+fn calculate_mac(payload: &[u8], secret: &str, timestamp: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+    const PAYLOAD: &[u8] =
+        br#"{"id":"evt_1","type":"checkout.session.completed","data":{"checkout_session_id":"cs_1","payment_id":"pi_1","amount_in_yen":5000}}"#;
+
+    #[test]
+    fn verify_webhook_signature_success() {
+        let timestamp = 1693882800;
+        let header = create_webhook_signature(PAYLOAD, SECRET, timestamp);
+
+        let result = verify_webhook_signature(PAYLOAD, &header, SECRET, timestamp + 10);
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn verify_webhook_signature_success_one_of_multiple_signatures_matches() {
+        let timestamp = 1693882800;
+        let header = create_webhook_signature(PAYLOAD, SECRET, timestamp);
+        let old_header = create_webhook_signature(PAYLOAD, "whsec_old", timestamp);
+        let old_signature = old_header.split_once(",v1=").expect("failed to split").1;
+        let header = format!("{},v1={}", header, old_signature);
+
+        let result = verify_webhook_signature(PAYLOAD, &header, SECRET, timestamp);
+
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn verify_webhook_signature_fail_tampered_payload() {
+        let timestamp = 1693882800;
+        let header = create_webhook_signature(PAYLOAD, SECRET, timestamp);
+        let tampered = String::from_utf8(PAYLOAD.to_vec())
+            .expect("failed to get String")
+            .replace("5000", "1");
+
+        let result = verify_webhook_signature(tampered.as_bytes(), &header, SECRET, timestamp);
+
+        assert_eq!(Err(WebhookSignatureError::SignatureMismatch), result);
+    }
+
+    #[test]
+    fn verify_webhook_signature_fail_different_secret() {
+        let timestamp = 1693882800;
+        let header = create_webhook_signature(PAYLOAD, "whsec_other", timestamp);
+
+        let result = verify_webhook_signature(PAYLOAD, &header, SECRET, timestamp);
+
+        assert_eq!(Err(WebhookSignatureError::SignatureMismatch), result);
+    }
+
+    #[test]
+    fn verify_webhook_signature_fail_timestamp_out_of_tolerance() {
+        let timestamp = 1693882800;
+        let header = create_webhook_signature(PAYLOAD, SECRET, timestamp);
+        let current_timestamp = timestamp + WEBHOOK_TOLERANCE_IN_SECONDS + 1;
+
+        let result = verify_webhook_signature(PAYLOAD, &header, SECRET, current_timestamp);
+
+        assert_eq!(
+            Err(WebhookSignatureError::TimestampOutOfTolerance {
+                timestamp,
+                current_timestamp
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn verify_webhook_signature_fail_invalid_format() {
+        let timestamp = 1693882800;
+        let invalid_headers = [
+            "".to_string(),
+            format!("t={}", timestamp),
+            "v1=abcdef".to_string(),
+            format!("t=abc,v1={}", "00".repeat(32)),
+            format!("t={},v1=xyz", timestamp),
+        ];
+
+        for header in invalid_headers {
+            let result = verify_webhook_signature(PAYLOAD, &header, SECRET, timestamp);
+            assert_eq!(
+                Err(WebhookSignatureError::InvalidFormat),
+                result,
+                "header: {}",
+                header
+            );
+        }
+    }
+}
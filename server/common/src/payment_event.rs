@@ -0,0 +1,62 @@
+// Copyright 2023 Ken Miura
+
+//! 相談料の支払いに関する状態遷移の履歴（payment_event）を扱う定数、関数を集約するモジュール
+//!
+//! 支払いに関する状態は、状態ごとに別のテーブルで管理している。状態を遷移させるときは、同じトランザクション内で[insert_payment_event]を呼び出し、履歴を残す。
+
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, FixedOffset};
+use entity::sea_orm::{ActiveModelTrait, DatabaseTransaction, Set};
+use tracing::error;
+
+use crate::{err, ApiError, ErrRespStruct};
+
+/// 入金待ち（awaiting_paymentに存在する）状態
+pub const PAYMENT_STATE_AWAITING_PAYMENT: &str = "awaiting_payment";
+/// 出金待ち（awaiting_withdrawalに存在する）状態
+pub const PAYMENT_STATE_AWAITING_WITHDRAWAL: &str = "awaiting_withdrawal";
+/// 出金不可（left_awaiting_withdrawalに存在する）状態
+pub const PAYMENT_STATE_LEFT_AWAITING_WITHDRAWAL: &str = "left_awaiting_withdrawal";
+/// 入金なし（neglected_paymentに存在する）状態
+pub const PAYMENT_STATE_NEGLECTED_PAYMENT: &str = "neglected_payment";
+/// 出金済み（receipt_of_consultationに存在する）状態
+pub const PAYMENT_STATE_RECEIPT_OF_CONSULTATION: &str = "receipt_of_consultation";
+/// 返金済み（refunded_paymentに存在する）状態
+pub const PAYMENT_STATE_REFUNDED_PAYMENT: &str = "refunded_payment";
+
+/// 支払いに関する状態遷移の履歴を追加する
+///
+/// from_stateは、状態が新たに生成された（相談申し込みが承認された）場合、Noneとする。
+/// performed_byには状態を遷移させた操作者のメールアドレスを指定する。システムが自動で遷移させた場合、システムのメールアドレスを指定する。
+pub async fn insert_payment_event(
+    consultation_id: i64,
+    from_state: Option<&str>,
+    to_state: &str,
+    performed_by: &str,
+    created_at: DateTime<FixedOffset>,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let active_model = entity::payment_event::ActiveModel {
+        consultation_id: Set(consultation_id),
+        from_state: Set(from_state.map(|s| s.to_string())),
+        to_state: Set(to_state.to_string()),
+        performed_by: Set(performed_by.to_string()),
+        created_at: Set(created_at),
+        ..Default::default()
+    };
+    let _ = active_model.insert(txn).await.map_err(|e| {
+        error!(
+            "failed to insert payment_event (consultation_id: {}, from_state: {:?}, to_state: {}, performed_by: {}, created_at: {}): {}",
+            consultation_id, from_state, to_state, performed_by, created_at, e
+        );
+        ErrRespStruct {
+            err_resp: (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError {
+                    code: err::Code::UnexpectedErr as u32,
+                }),
+            ),
+        }
+    })?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// Copyright 2023 Ken Miura
+
+use image::{
+    imageops::{self, FilterType},
+    GrayImage,
+};
+
+/// 画像の知覚ハッシュ (dHash) を返す
+///
+/// 画像を9x8の大きさに縮小し、各行の隣り合う画素の輝度を比較した結果（左の画素の方が暗い場合に1）を64ビットに並べたもの。
+/// 再圧縮や多少の拡大縮小では値がほとんど変わらないため、ハミング距離で同じ画像かどうかを判定できる。
+pub fn calculate_perceptual_hash(gray_img: &GrayImage) -> u64 {
+    let small_img = imageops::resize(gray_img, 9, 8, FilterType::Triangle);
+    let mut hash = 0_u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small_img.get_pixel(x, y).0[0];
+            let right = small_img.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | u64::from(left < right);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+
+    use image::{ImageBuffer, Luma};
+
+    use super::*;
+
+    #[test]
+    fn calculate_perceptual_hash_returns_all_bits_set_for_image_getting_brighter_to_the_right() {
+        let gray_img = ImageBuffer::from_fn(90, 80, |x, _| Luma([(x * 2) as u8]));
+
+        let hash = calculate_perceptual_hash(&gray_img);
+
+        assert_eq!(u64::MAX, hash);
+    }
+}
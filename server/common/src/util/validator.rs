@@ -3,6 +3,8 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+pub mod corporate_number_validator;
+pub mod coupon_code_validator;
 pub mod email_address_validator;
 pub mod pass_code_validator;
 pub mod password_validator;
@@ -0,0 +1,132 @@
+// Copyright 2023 Ken Miura
+
+use std::{error::Error, fmt::Display};
+
+const CORPORATE_NUMBER_LENGTH: usize = 13;
+
+/// Validates corporate number (法人番号) format (13 digits) and its check digit (the first digit).
+///
+/// The check digit is calculated as described in the following link.
+/// https://www.houjin-bangou.nta.go.jp/documents/checkdigit.pdf
+pub fn validate_corporate_number(
+    corporate_number: &str,
+) -> Result<(), CorporateNumberValidationError> {
+    let digits: Vec<u32> = corporate_number
+        .chars()
+        .filter_map(|c| {
+            if c.is_ascii_digit() {
+                c.to_digit(10)
+            } else {
+                None
+            }
+        })
+        .collect();
+    if corporate_number.chars().count() != CORPORATE_NUMBER_LENGTH
+        || digits.len() != CORPORATE_NUMBER_LENGTH
+    {
+        return Err(CorporateNumberValidationError::InvalidFormat {
+            invalid_corporate_number: corporate_number.to_string(),
+        });
+    }
+    let check_digit = digits[0];
+    let sum: u32 = digits[1..]
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { *d * 2 })
+        .sum();
+    if check_digit != 9 - (sum % 9) {
+        return Err(CorporateNumberValidationError::InvalidCheckDigit {
+            invalid_corporate_number: corporate_number.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Error related to [validate_corporate_number()]
+#[derive(Debug, PartialEq)]
+pub enum CorporateNumberValidationError {
+    InvalidFormat { invalid_corporate_number: String },
+    InvalidCheckDigit { invalid_corporate_number: String },
+}
+
+impl Display for CorporateNumberValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorporateNumberValidationError::InvalidFormat {
+                invalid_corporate_number,
+            } => {
+                write!(f, "invalid corporate number: {}", invalid_corporate_number)
+            }
+            CorporateNumberValidationError::InvalidCheckDigit {
+                invalid_corporate_number,
+            } => {
+                write!(
+                    f,
+                    "invalid check digit in corporate number: {}",
+                    invalid_corporate_number
+                )
+            }
+        }
+    }
+}
+
+impl Error for CorporateNumberValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_corporate_number, CorporateNumberValidationError};
+
+    #[test]
+    fn validate_corporate_number_returns_ok_if_given_valid_corporate_number() {
+        let valid_corporate_numbers = vec!["1180301018771", "7000012050002", "9000000000000"];
+
+        for valid_corporate_number in valid_corporate_numbers {
+            let result = validate_corporate_number(valid_corporate_number);
+            assert!(
+                result.is_ok(),
+                "valid_corporate_number: {}",
+                valid_corporate_number
+            )
+        }
+    }
+
+    #[test]
+    fn validate_corporate_number_returns_invalid_format_if_given_invalid_format() {
+        let invalid_corporate_numbers = vec![
+            "",
+            "118030101877",
+            "11803010187712",
+            "118030101877a",
+            "１１８０３０１０１８７７１",
+            "1180-30101877",
+        ];
+
+        for corporate_number in invalid_corporate_numbers {
+            let result = validate_corporate_number(corporate_number);
+
+            let err = result.expect_err("failed to get Err");
+            assert_eq!(
+                CorporateNumberValidationError::InvalidFormat {
+                    invalid_corporate_number: corporate_number.to_string()
+                },
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn validate_corporate_number_returns_invalid_check_digit_if_given_wrong_check_digit() {
+        let corporate_number = "2180301018771";
+
+        let result = validate_corporate_number(corporate_number);
+
+        let err = result.expect_err("failed to get Err");
+        assert_eq!(
+            CorporateNumberValidationError::InvalidCheckDigit {
+                invalid_corporate_number: corporate_number.to_string()
+            },
+            err
+        );
+    }
+}
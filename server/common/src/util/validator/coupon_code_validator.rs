@@ -0,0 +1,83 @@
+// Copyright 2023 Ken Miura
+
+use std::{error::Error, fmt::Display};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const COUPON_CODE_REGEXP: &str = "^[A-Z0-9]{4,32}$";
+static COUPON_CODE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(COUPON_CODE_REGEXP).expect("failed to compile COUPON_CODE regexp"));
+
+/// Validates coupon code format (4 to 32 characters of uppercase alphabet and digit).
+pub fn validate_coupon_code(coupon_code: &str) -> Result<(), CouponCodeValidationError> {
+    if !COUPON_CODE_RE.is_match(coupon_code) {
+        return Err(CouponCodeValidationError::InvalidFormat {
+            invalid_coupon_code: coupon_code.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Error related to [validate_coupon_code()]
+#[derive(Debug)]
+pub enum CouponCodeValidationError {
+    InvalidFormat { invalid_coupon_code: String },
+}
+
+impl Display for CouponCodeValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CouponCodeValidationError::InvalidFormat {
+                invalid_coupon_code,
+            } => {
+                write!(f, "invalid coupon code: {}", invalid_coupon_code)
+            }
+        }
+    }
+}
+
+impl Error for CouponCodeValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_coupon_code, CouponCodeValidationError};
+
+    #[test]
+    fn validate_coupon_code_returns_ok_if_given_valid_coupon_code() {
+        let valid_coupon_codes = vec!["ABCD", "FIRST2023", "A1B2C3D4E5F6G7H8I9J0K1L2M3N4O5P6"];
+
+        for valid_coupon_code in valid_coupon_codes {
+            let result = validate_coupon_code(valid_coupon_code);
+            assert!(result.is_ok(), "valid_coupon_code: {}", valid_coupon_code)
+        }
+    }
+
+    #[test]
+    fn validate_coupon_code_returns_invalid_format_if_given_invalid_coupon_code() {
+        let invalid_coupon_codes = vec![
+            "",
+            "ABC",
+            "A1B2C3D4E5F6G7H8I9J0K1L2M3N4O5P6Q",
+            "first2023",
+            "FIRST-2023",
+            "FIRST 2023",
+            "ＦＩＲＳＴ",
+        ];
+
+        for coupon_code in invalid_coupon_codes {
+            let result = validate_coupon_code(coupon_code);
+
+            let err = result.expect_err("failed to get Err");
+            match err {
+                CouponCodeValidationError::InvalidFormat {
+                    invalid_coupon_code,
+                } => assert_eq!(
+                    invalid_coupon_code, coupon_code,
+                    "expect: {}, got: {}",
+                    invalid_coupon_code, coupon_code
+                ),
+            }
+        }
+    }
+}
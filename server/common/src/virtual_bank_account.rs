@@ -0,0 +1,136 @@
+// Copyright 2023 Ken Miura
+
+//! 銀行振込の入金先として相談に割り当てるバーチャル口座（virtual_bank_account）を扱う関数を集約するモジュール
+//!
+//! バーチャル口座は相談申し込みの承認時に[assign_virtual_bank_account]で割り当て、入金に関する最終的な状態が確定したとき（awaiting_paymentが削除されるとき）に[release_virtual_bank_account]で解放する。
+
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, FixedOffset};
+use entity::sea_orm::sea_query::{Expr, LockBehavior, LockType};
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, QueryTrait, Set, Value,
+};
+use entity::virtual_bank_account;
+use tracing::error;
+
+use crate::{err, ApiError, ErrResp, ErrRespStruct};
+
+/// 未割り当てのバーチャル口座を相談に割り当て、その口座番号を返す
+///
+/// 割り当て可能なバーチャル口座が存在しない場合、Noneを返す。その場合のエラーの扱いは呼び出し側で決定する。
+pub async fn assign_virtual_bank_account(
+    consultation_id: i64,
+    current_date_time: DateTime<FixedOffset>,
+    txn: &DatabaseTransaction,
+) -> Result<Option<String>, ErrRespStruct> {
+    // 複数の相談申し込みが同時に承認された場合に同じ口座を待ち合わせないように、ロック済の口座はスキップする
+    let mut query = virtual_bank_account::Entity::find()
+        .filter(virtual_bank_account::Column::ConsultationId.is_null())
+        .order_by_asc(virtual_bank_account::Column::VirtualBankAccountId)
+        .limit(1);
+    QueryTrait::query(&mut query).lock_with_behavior(LockType::Update, LockBehavior::SkipLocked);
+    let model = query.one(txn).await.map_err(|e| {
+        error!(
+            "failed to find unassigned virtual_bank_account (consultation_id: {}): {}",
+            consultation_id, e
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    let model = match model {
+        Some(m) => m,
+        None => return Ok(None),
+    };
+    let account_number = model.account_number.clone();
+    let mut active_model: virtual_bank_account::ActiveModel = model.into();
+    active_model.consultation_id = Set(Some(consultation_id));
+    active_model.assigned_at = Set(Some(current_date_time));
+    let _ = active_model.update(txn).await.map_err(|e| {
+        error!(
+            "failed to update virtual_bank_account (consultation_id: {}, account_number: {}, current_date_time: {}): {}",
+            consultation_id, account_number, current_date_time, e
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(Some(account_number))
+}
+
+/// バーチャル口座の登録数と未割り当ての口座の数
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualBankAccountPoolState {
+    pub total: u64,
+    pub unassigned: u64,
+}
+
+/// バーチャル口座の登録数と未割り当ての口座の数を返す
+///
+/// 割り当て可能なバーチャル口座が見つからなかった際、運用者が口座を補充できるようにログに出力するために利用する。
+/// 未割り当ての口座の数には、他のトランザクションが割り当てのためにロックしている口座も含まれる。
+pub async fn get_virtual_bank_account_pool_state(
+    txn: &DatabaseTransaction,
+) -> Result<VirtualBankAccountPoolState, ErrRespStruct> {
+    let total = virtual_bank_account::Entity::find()
+        .count(txn)
+        .await
+        .map_err(|e| {
+            error!("failed to count virtual_bank_account: {}", e);
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    let unassigned = virtual_bank_account::Entity::find()
+        .filter(virtual_bank_account::Column::ConsultationId.is_null())
+        .count(txn)
+        .await
+        .map_err(|e| {
+            error!("failed to count unassigned virtual_bank_account: {}", e);
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(VirtualBankAccountPoolState { total, unassigned })
+}
+
+/// 相談に割り当てていたバーチャル口座を解放する
+///
+/// 割り当てられているバーチャル口座が存在しない場合、何もしない。
+pub async fn release_virtual_bank_account(
+    consultation_id: i64,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let _ = virtual_bank_account::Entity::update_many()
+        .col_expr(
+            virtual_bank_account::Column::ConsultationId,
+            Expr::value(Value::BigInt(None)),
+        )
+        .col_expr(
+            virtual_bank_account::Column::AssignedAt,
+            Expr::value(Value::ChronoDateTimeWithTimeZone(None)),
+        )
+        .filter(virtual_bank_account::Column::ConsultationId.eq(consultation_id))
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to release virtual_bank_account (consultation_id: {}): {}",
+                consultation_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(())
+}
+
+fn unexpected_err_resp() -> ErrResp {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiError {
+            code: err::Code::UnexpectedErr as u32,
+        }),
+    )
+}
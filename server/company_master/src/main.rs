@@ -0,0 +1,388 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, FixedOffset};
+use common::admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD};
+use common::company::{insert_company, insert_company_alias_if_not_exists};
+use common::db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT};
+use common::smtp::{KEY_TO_SYSTEM_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
+use common::util::check_env_vars;
+use common::util::validator::corporate_number_validator::validate_corporate_number;
+use common::JAPANESE_TIME_ZONE;
+use dotenv::dotenv;
+use entity::sea_orm::{
+    ColumnTrait, ConnectOptions, Database, DatabaseConnection, EntityTrait, QueryFilter,
+    TransactionError, TransactionTrait,
+};
+use std::{collections::HashSet, env::args, error::Error, fmt, fs::read_to_string, process::exit};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const INVALID_ARG_LENGTH: i32 = 3;
+const INVALID_SUB_COMMAND: i32 = 4;
+const APPLICATION_ERR: i32 = 5;
+
+/// 1つのトランザクションで登録する法人の数
+const CHUNK_SIZE: usize = 1000;
+
+// 国税庁の法人番号公表サイトが提供する基本3情報のCSV（Unicode版）の列の位置
+const CORPORATE_NUMBER_COLUMN: usize = 1;
+const NAME_COLUMN: usize = 6;
+const CLOSE_DATE_COLUMN: usize = 18;
+const LATEST_COLUMN: usize = 23;
+const NUM_OF_COLUMNS: usize = 24;
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        KEY_TO_SYSTEM_EMAIL_ADDRESS.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    // 標準出力に出力される内容を見やすくしたい＋管理者向けの会社の登録しかしないのでログで見る必要のある重要な箇所もない
+    // 従ってログの初期化は行わない
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let conn = connect(&database_url).await.unwrap_or_else(|e| {
+        println!(
+            "failed to establish connection (database_url: {}): {}",
+            database_url, e
+        );
+        exit(CONNECTION_ERROR);
+    });
+
+    let args: Vec<String> = args().collect();
+    if args.len() < 2 {
+        println!("usage: {} [ load ] [SUB_COMMAND_ARGS...]", args[0]);
+        exit(INVALID_ARG_LENGTH);
+    }
+    let cmd = &args[1];
+    if cmd == "load" {
+        load(&conn, args).await;
+    } else {
+        println!("invalid subcommand: {}", cmd);
+        println!("valid subcommand [ load ]");
+        exit(INVALID_SUB_COMMAND);
+    }
+}
+
+async fn connect(database_url: &str) -> Result<DatabaseConnection, Box<dyn Error + Send + Sync>> {
+    let mut opt = ConnectOptions::new(database_url.to_string());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let conn = Database::connect(opt).await.map_err(Box::new)?;
+    Ok(conn)
+}
+
+async fn load(conn: &DatabaseConnection, args: Vec<String>) {
+    if args.len() != 3 {
+        println!("usage: {} load \"path_to_corporate_number_csv\"", args[0]);
+        println!("ex: {} load ./00_zenkoku_all_20231031.csv", args[0]);
+        exit(INVALID_ARG_LENGTH);
+    }
+    let contents = read_to_string(&args[2]).unwrap_or_else(|e| {
+        println!("application error: failed to read {}: {}", &args[2], e);
+        exit(APPLICATION_ERR);
+    });
+    let corporations = parse_corporations(&contents).unwrap_or_else(|e| {
+        println!("application error: {}", e);
+        exit(APPLICATION_ERR);
+    });
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let mut num_of_inserted = 0;
+    let mut num_of_aliases = 0;
+    for chunk in corporations.chunks(CHUNK_SIZE) {
+        match load_corporations(conn, chunk.to_vec(), current_date_time).await {
+            Ok((inserted, aliases)) => {
+                num_of_inserted += inserted;
+                num_of_aliases += aliases;
+            }
+            Err(e) => {
+                println!(
+                    "application error: {} ({} company(ies) loaded, {} alias(es) added before the error)",
+                    e, num_of_inserted, num_of_aliases
+                );
+                exit(APPLICATION_ERR);
+            }
+        }
+    }
+    println!(
+        "{} company(ies) loaded, {} alias(es) added to existing company(ies)",
+        num_of_inserted, num_of_aliases
+    );
+    exit(SUCCESS)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Corporation {
+    corporate_number: String,
+    name: String,
+}
+
+/// 国税庁の法人番号公表サイトが提供する基本3情報のCSV（Unicode版、ヘッダなし）を解析する。
+///
+/// 最新の履歴でない行、及び閉鎖された法人の行は無視する。空行は無視する。
+/// 列の数が足りない行、または法人番号が不正な行が含まれる場合、エラーを返す。
+fn parse_corporations(contents: &str) -> Result<Vec<Corporation>, InvalidLineError> {
+    let mut corporations: Vec<Corporation> = Vec::new();
+    let mut corporate_numbers = HashSet::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() {
+            continue;
+        }
+        let columns = split_csv_line(line);
+        if columns.len() < NUM_OF_COLUMNS {
+            return Err(InvalidLineError {
+                line_number: i + 1,
+                reason: format!("too few columns ({})", columns.len()),
+            });
+        }
+        if columns[LATEST_COLUMN] != "1" || !columns[CLOSE_DATE_COLUMN].is_empty() {
+            continue;
+        }
+        let corporate_number = columns[CORPORATE_NUMBER_COLUMN].clone();
+        validate_corporate_number(&corporate_number).map_err(|e| InvalidLineError {
+            line_number: i + 1,
+            reason: e.to_string(),
+        })?;
+        let name = columns[NAME_COLUMN].trim().to_string();
+        if name.is_empty() {
+            continue;
+        }
+        if !corporate_numbers.insert(corporate_number.clone()) {
+            continue;
+        }
+        corporations.push(Corporation {
+            corporate_number,
+            name,
+        });
+    }
+    Ok(corporations)
+}
+
+/// ダブルクォートで囲まれた値（値の中のダブルクォートは2つ重ねてエスケープされる）を含むCSVの1行を列に分割する
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut columns = Vec::new();
+    let mut column = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    column.push('"');
+                    let _ = chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' => in_quotes = true,
+            ',' if !in_quotes => columns.push(std::mem::take(&mut column)),
+            _ => column.push(c),
+        }
+    }
+    columns.push(column);
+    columns
+}
+
+/// 法人番号が未登録の法人は会社として登録し、登録済みの法人は名称を別名として追加する
+///
+/// 戻り値は（登録した会社の数、既存の会社に追加した別名の数）
+async fn load_corporations(
+    conn: &DatabaseConnection,
+    corporations: Vec<Corporation>,
+    current_date_time: DateTime<FixedOffset>,
+) -> Result<(usize, usize), Box<dyn Error + Send + Sync>> {
+    let result = conn
+        .transaction::<_, (usize, usize), TxErr>(|txn| {
+            Box::pin(async move {
+                let mut num_of_inserted = 0;
+                let mut num_of_aliases = 0;
+                for corporation in corporations {
+                    let company = entity::company::Entity::find()
+                        .filter(
+                            entity::company::Column::CorporateNumber
+                                .eq(corporation.corporate_number.clone()),
+                        )
+                        .one(txn)
+                        .await
+                        .map_err(|e| TxErr(Box::new(e)))?;
+                    if let Some(company) = company {
+                        let added = insert_company_alias_if_not_exists(
+                            company.company_id,
+                            &corporation.name,
+                            txn,
+                        )
+                        .await
+                        .map_err(|e| TxErr(Box::new(e)))?;
+                        if added {
+                            num_of_aliases += 1;
+                        }
+                        continue;
+                    }
+                    let _ = insert_company(
+                        &corporation.name,
+                        Some(corporation.corporate_number),
+                        SYSTEM_EMAIL_ADDRESS.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await
+                    .map_err(|e| TxErr(Box::new(e)))?;
+                    num_of_inserted += 1;
+                }
+                Ok((num_of_inserted, num_of_aliases))
+            })
+        })
+        .await
+        .map_err(|e| match e {
+            TransactionError::Connection(db_err) => Box::new(db_err),
+            TransactionError::Transaction(tx_err) => tx_err.0,
+        })?;
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct InvalidLineError {
+    line_number: usize,
+    reason: String,
+}
+
+impl fmt::Display for InvalidLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid line at {}: {}", self.line_number, self.reason)
+    }
+}
+
+impl Error for InvalidLineError {}
+
+#[derive(Debug)]
+struct TxErr(Box<dyn Error + Send + Sync>);
+
+impl fmt::Display for TxErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error in transaction: {}", self.0)
+    }
+}
+
+impl Error for TxErr {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_line(corporate_number: &str, name: &str, close_date: &str, latest: &str) -> String {
+        let mut columns = vec!["".to_string(); NUM_OF_COLUMNS];
+        columns[0] = "1".to_string();
+        columns[CORPORATE_NUMBER_COLUMN] = corporate_number.to_string();
+        columns[NAME_COLUMN] = name.to_string();
+        columns[CLOSE_DATE_COLUMN] = close_date.to_string();
+        columns[LATEST_COLUMN] = latest.to_string();
+        columns
+            .iter()
+            .map(|c| format!("\"{}\"", c.replace('"', "\"\"")))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    #[test]
+    fn parse_corporations_success() {
+        let contents = [
+            create_line("1180301018771", "トヨタ自動車株式会社", "", "1"),
+            "".to_string(),
+            create_line("7000012050002", "国税庁", "", "1"),
+            create_line("1180301018771", "トヨタ自動車株式会社", "", "1"),
+        ]
+        .join("\n");
+
+        let result = parse_corporations(&contents).expect("failed to get Ok");
+
+        assert_eq!(
+            vec![
+                Corporation {
+                    corporate_number: "1180301018771".to_string(),
+                    name: "トヨタ自動車株式会社".to_string()
+                },
+                Corporation {
+                    corporate_number: "7000012050002".to_string(),
+                    name: "国税庁".to_string()
+                }
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn parse_corporations_skips_closed_or_old_records() {
+        let contents = [
+            create_line("1180301018771", "トヨタ自動車株式会社", "2023-10-01", "1"),
+            create_line("7000012050002", "国税庁", "", "0"),
+        ]
+        .join("\n");
+
+        let result = parse_corporations(&contents).expect("failed to get Ok");
+
+        assert_eq!(Vec::<Corporation>::new(), result);
+    }
+
+    #[test]
+    fn parse_corporations_fail_invalid_corporate_number() {
+        let contents = [
+            create_line("1180301018771", "トヨタ自動車株式会社", "", "1"),
+            create_line("2180301018771", "テスト株式会社", "", "1"),
+        ]
+        .join("\n");
+
+        let result = parse_corporations(&contents).expect_err("failed to get Err");
+
+        assert_eq!(2, result.line_number);
+    }
+
+    #[test]
+    fn parse_corporations_fail_too_few_columns() {
+        let contents = "\"1\",\"1180301018771\"";
+
+        let result = parse_corporations(contents).expect_err("failed to get Err");
+
+        assert_eq!(1, result.line_number);
+    }
+
+    #[test]
+    fn split_csv_line_handles_quoted_values() {
+        let result = split_csv_line("\"a,b\",\"c\"\"d\",e,");
+
+        assert_eq!(
+            vec![
+                "a,b".to_string(),
+                "c\"d".to_string(),
+                "e".to_string(),
+                "".to_string()
+            ],
+            result
+        );
+    }
+}
@@ -9,6 +9,7 @@ pub struct Model {
     pub appr_cre_career_req_id: i64,
     pub user_account_id: i64,
     pub company_name: String,
+    pub company_id: i64,
     pub department_name: Option<String>,
     pub office: Option<String>,
     pub career_start_date: Date,
@@ -24,6 +25,7 @@ pub struct Model {
     pub image2_file_name_without_ext: Option<String>,
     pub approved_at: DateTimeWithTimeZone,
     pub approved_by: String,
+    pub verified_work_email_domain: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -9,6 +9,7 @@ pub struct Model {
     pub career_id: i64,
     pub user_account_id: i64,
     pub company_name: String,
+    pub company_id: i64,
     pub department_name: Option<String>,
     pub office: Option<String>,
     pub career_start_date: Date,
@@ -20,6 +21,7 @@ pub struct Model {
     pub position_name: Option<String>,
     pub is_new_graduate: bool,
     pub note: Option<String>,
+    pub verified_work_email_domain: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -14,6 +14,9 @@ pub struct Model {
     pub third_candidate_date_time: DateTimeWithTimeZone,
     pub latest_candidate_date_time: DateTimeWithTimeZone,
     pub fee_per_hour_in_yen: i32,
+    pub coupon_id: Option<i64>,
+    pub discount_in_yen: i32,
+    pub discount_borne_by: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +23,9 @@ pub struct Model {
     pub image1_file_name_without_ext: String,
     pub image2_file_name_without_ext: Option<String>,
     pub requested_at: DateTimeWithTimeZone,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<DateTimeWithTimeZone>,
+    pub verified_work_email_domain: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -20,6 +20,8 @@ pub struct Model {
     pub image1_file_name_without_ext: String,
     pub image2_file_name_without_ext: Option<String>,
     pub requested_at: DateTimeWithTimeZone,
+    pub claimed_by: Option<String>,
+    pub claimed_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.10
 
+pub mod approved_update_career_req;
 pub mod prelude;
 
 pub mod admin_account;
@@ -10,32 +11,50 @@ pub mod approved_update_identity_req;
 pub mod awaiting_payment;
 pub mod awaiting_withdrawal;
 pub mod bank_account;
+pub mod card_payment;
 pub mod career;
+pub mod company;
+pub mod company_alias;
 pub mod consultant_rating;
+pub mod consultant_search_click;
+pub mod consultant_search_log;
 pub mod consultation;
 pub mod consultation_req;
 pub mod consulting_fee;
+pub mod coupon;
+pub mod coupon_use;
 pub mod create_career_req;
 pub mod create_identity_req;
 pub mod deleted_user_account;
 pub mod document;
+pub mod favorite_consultant;
 pub mod identity;
+pub mod identity_image_hash;
 pub mod left_awaiting_withdrawal;
 pub mod maintenance;
 pub mod mfa_info;
 pub mod neglected_payment;
 pub mod news;
+pub mod payment_event;
+pub mod payment_reconciliation_snapshot;
 pub mod pwd_change_req;
 pub mod receipt_of_consultation;
 pub mod refunded_payment;
 pub mod rejected_create_career_req;
 pub mod rejected_create_identity_req;
+pub mod rejected_update_career_req;
 pub mod rejected_update_identity_req;
+pub mod rejection_reason_template;
+pub mod saved_search;
+pub mod saved_search_matched_consultant;
 pub mod temp_mfa_secret;
 pub mod terms_of_use;
+pub mod update_career_req;
 pub mod update_identity_req;
 pub mod user_account;
 pub mod user_rating;
 pub mod user_temp_account;
+pub mod virtual_bank_account;
+pub mod work_email_verification_code;
 
 pub use sea_orm;
@@ -11,6 +11,9 @@ pub struct Model {
     pub consultant_id: i64,
     pub meeting_at: DateTimeWithTimeZone,
     pub fee_per_hour_in_yen: i32,
+    pub coupon_id: Option<i64>,
+    pub discount_in_yen: i32,
+    pub discount_borne_by: Option<String>,
     pub neglect_confirmed_by: String,
     pub created_at: DateTimeWithTimeZone,
 }
@@ -4,34 +4,53 @@ pub use super::admin_account::Entity as AdminAccount;
 pub use super::admin_mfa_info::Entity as AdminMfaInfo;
 pub use super::approved_create_career_req::Entity as ApprovedCreateCareerReq;
 pub use super::approved_create_identity_req::Entity as ApprovedCreateIdentityReq;
+pub use super::approved_update_career_req::Entity as ApprovedUpdateCareerReq;
 pub use super::approved_update_identity_req::Entity as ApprovedUpdateIdentityReq;
 pub use super::awaiting_payment::Entity as AwaitingPayment;
 pub use super::awaiting_withdrawal::Entity as AwaitingWithdrawal;
 pub use super::bank_account::Entity as BankAccount;
+pub use super::card_payment::Entity as CardPayment;
 pub use super::career::Entity as Career;
+pub use super::company::Entity as Company;
+pub use super::company_alias::Entity as CompanyAlias;
 pub use super::consultant_rating::Entity as ConsultantRating;
+pub use super::consultant_search_click::Entity as ConsultantSearchClick;
+pub use super::consultant_search_log::Entity as ConsultantSearchLog;
 pub use super::consultation::Entity as Consultation;
 pub use super::consultation_req::Entity as ConsultationReq;
 pub use super::consulting_fee::Entity as ConsultingFee;
+pub use super::coupon::Entity as Coupon;
+pub use super::coupon_use::Entity as CouponUse;
 pub use super::create_career_req::Entity as CreateCareerReq;
 pub use super::create_identity_req::Entity as CreateIdentityReq;
 pub use super::deleted_user_account::Entity as DeletedUserAccount;
 pub use super::document::Entity as Document;
+pub use super::favorite_consultant::Entity as FavoriteConsultant;
 pub use super::identity::Entity as Identity;
+pub use super::identity_image_hash::Entity as IdentityImageHash;
 pub use super::left_awaiting_withdrawal::Entity as LeftAwaitingWithdrawal;
 pub use super::maintenance::Entity as Maintenance;
 pub use super::mfa_info::Entity as MfaInfo;
 pub use super::neglected_payment::Entity as NeglectedPayment;
 pub use super::news::Entity as News;
+pub use super::payment_event::Entity as PaymentEvent;
+pub use super::payment_reconciliation_snapshot::Entity as PaymentReconciliationSnapshot;
 pub use super::pwd_change_req::Entity as PwdChangeReq;
 pub use super::receipt_of_consultation::Entity as ReceiptOfConsultation;
 pub use super::refunded_payment::Entity as RefundedPayment;
 pub use super::rejected_create_career_req::Entity as RejectedCreateCareerReq;
 pub use super::rejected_create_identity_req::Entity as RejectedCreateIdentityReq;
+pub use super::rejected_update_career_req::Entity as RejectedUpdateCareerReq;
 pub use super::rejected_update_identity_req::Entity as RejectedUpdateIdentityReq;
+pub use super::rejection_reason_template::Entity as RejectionReasonTemplate;
+pub use super::saved_search::Entity as SavedSearch;
+pub use super::saved_search_matched_consultant::Entity as SavedSearchMatchedConsultant;
 pub use super::temp_mfa_secret::Entity as TempMfaSecret;
 pub use super::terms_of_use::Entity as TermsOfUse;
+pub use super::update_career_req::Entity as UpdateCareerReq;
 pub use super::update_identity_req::Entity as UpdateIdentityReq;
 pub use super::user_account::Entity as UserAccount;
 pub use super::user_rating::Entity as UserRating;
 pub use super::user_temp_account::Entity as UserTempAccount;
+pub use super::virtual_bank_account::Entity as VirtualBankAccount;
+pub use super::work_email_verification_code::Entity as WorkEmailVerificationCode;
@@ -21,6 +21,7 @@ pub struct Model {
     pub is_new_graduate: bool,
     pub note: Option<String>,
     pub reason: String,
+    pub reason_template_code: Option<String>,
     pub rejected_at: DateTimeWithTimeZone,
     pub rejected_by: String,
 }
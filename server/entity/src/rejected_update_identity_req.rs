@@ -22,6 +22,7 @@ pub struct Model {
     pub address_line2: Option<String>,
     pub telephone_number: String,
     pub reason: String,
+    pub reason_template_code: Option<String>,
     pub rejected_at: DateTimeWithTimeZone,
     pub rejected_by: String,
 }
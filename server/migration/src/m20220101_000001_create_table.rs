@@ -48,6 +48,34 @@ impl MigrationTrait for Migration {
             .execute(sql.stmt(r"CREATE DOMAIN ccs_schema.contract_type AS VARCHAR (8) CHECK (VALUE ~ 'regular' OR VALUE ~ 'contract' OR VALUE ~ 'other');"))
             .await
             .map(|_| ())?;
+        let _ = conn
+            /*
+             * percentage: 相談料の割合（パーセント）での割引、fixed_amount: 固定額（円）での割引
+             */
+            .execute(sql.stmt(r"CREATE DOMAIN ccs_schema.discount_type AS VARCHAR (12) CHECK (VALUE ~ 'percentage' OR VALUE ~ 'fixed_amount');"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            /*
+             * platform: 割引額をプラットフォームが負担、consultant: 割引額をコンサルタントが負担
+             */
+            .execute(sql.stmt(r"CREATE DOMAIN ccs_schema.discount_bearer AS VARCHAR (10) CHECK (VALUE ~ 'platform' OR VALUE ~ 'consultant');"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            /*
+             * 相談料の支払いに関する状態。状態と同名のテーブルにレコードが存在することを示す。
+             */
+            .execute(sql.stmt(r"CREATE DOMAIN ccs_schema.payment_state AS VARCHAR (24) CHECK (VALUE ~ 'awaiting_payment' OR VALUE ~ 'awaiting_withdrawal' OR VALUE ~ 'left_awaiting_withdrawal' OR VALUE ~ 'neglected_payment' OR VALUE ~ 'receipt_of_consultation' OR VALUE ~ 'refunded_payment');"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            /*
+             * 法人番号（国税庁が指定する13桁の番号）
+             */
+            .execute(sql.stmt(r"CREATE DOMAIN ccs_schema.corporate_number AS CHAR (13) CHECK ( VALUE ~ '^[0-9]{13}$' );"))
+            .await
+            .map(|_| ())?;
         // その他（TABLE、INDEX等）の定義
         let _ = conn
         /* ユーザーがアカウントを作成した際に生成される。ユーザーがアカウントを削除した際に削除される（情報は削除されたユーザーテーブルに移される） */
@@ -324,17 +352,95 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
 
+        let _ = conn
+            /*
+             * 会社のマスタ。同じ会社が異なる表記（例: 株式会社の有無、全角半角の違い）で職務経歴に登録されても同一の会社として扱うために利用する。
+             * 管理者が職務経歴確認依頼の承認時に作成するか、法人番号のデータ（国税庁の法人番号公表サイトから取得したファイル）を読み込んで作成する。サービスの運用期間を通じて存在し続ける。
+             *
+             * company_nameは画面に表示する正式な会社名。corporate_numberは法人番号が不明な場合（管理者が作成した場合）NULLとなる。
+             * created_byは作成した管理者のメールアドレス。法人番号のデータを読み込んで作成した場合、システムのメールアドレスとなる。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.company (
+                  company_id BIGSERIAL PRIMARY KEY,
+                  company_name VARCHAR (256) NOT NULL,
+                  corporate_number ccs_schema.corporate_number UNIQUE,
+                  created_by ccs_schema.email_address NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.company To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.company To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"GRANT USAGE ON SEQUENCE ccs_schema.company_company_id_seq TO admin_app;",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /*
+             * 会社の別名。会社の作成時（正式な会社名）と、職務経歴確認依頼の承認時（ユーザーが入力した会社名）に生成される。サービスの運用期間を通じて存在し続ける。
+             *
+             * normalized_nameは、common::opensearch::normalization::normalize_company_nameで正規化した会社名。
+             * 異なる会社が同じ名前を持つことがあるため、normalized_nameはUNIQUEとしない。
+             * companyは削除しないため、company_idは外部キーとしない。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.company_alias (
+                  company_alias_id BIGSERIAL PRIMARY KEY,
+                  company_id BIGINT NOT NULL,
+                  normalized_name VARCHAR (256) NOT NULL,
+                  UNIQUE(company_id, normalized_name)
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.company_alias To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.company_alias To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT USAGE ON SEQUENCE ccs_schema.company_alias_company_alias_id_seq TO admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX company_alias_normalized_name_idx ON ccs_schema.company_alias (normalized_name);",
+            ))
+            .await
+            .map(|_| ())?;
+
         let _ = conn
             .execute(
                 /* 管理者がユーザーの職歴確認依頼を承認したときに生成される。また、ユーザーが職歴を削除した際に削除される。
                  * ユーザーがアカウントを削除した後（削除されたユーザーテーブルに移された後）一定期間後、定期実行ツールにより削除される
                  */
                 /* annual_income_in_man_yen => 万円単位での年収 */
+                /* company_id => 承認時に管理者が選択した会社（company）のID。company_nameはユーザーが入力した会社名をそのまま保持する */
+                /* verified_work_email_domain => 職務経歴確認依頼（create_career_req）時に確認済みの勤務先メールアドレスのドメイン。会社が変わる更新が承認された場合、NULLとなる */
                 sql.stmt(
                     r"CREATE TABLE ccs_schema.career (
                     career_id BIGSERIAL PRIMARY KEY,
                     user_account_id BIGINT NOT NULL,
                     company_name VARCHAR (256) NOT NULL,
+                    company_id BIGINT NOT NULL,
                     department_name VARCHAR (256),
                     office VARCHAR (256),
                     career_start_date DATE NOT NULL,
@@ -345,7 +451,8 @@ impl MigrationTrait for Migration {
                     is_manager BOOLEAN NOT NULL,
                     position_name VARCHAR (128),
                     is_new_graduate BOOLEAN NOT NULL,
-                    note VARCHAR (2048)
+                    note VARCHAR (2048),
+                    verified_work_email_domain VARCHAR (253)
                   );",
                 ),
             )
@@ -439,6 +546,171 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
 
+        let _ = conn
+            /* ユーザーが検索条件を保存したときに生成される。ユーザーが削除したとき、またはアカウントを削除したときに削除される。
+             * search_paramは、コンサルタント検索で利用する条件（career_paramとfee_per_hour_in_yen_param）をバリデーション済のJSONとして保持する。
+             * alert_enabledがtrueの場合、定期実行ツール（notify_saved_search_matches）が新たに条件に一致したコンサルタントをメールで通知する。
+             * unsubscribe_tokenは、通知メールに記載する配信停止用URLに含めるトークン（ログインせずに配信を停止できるようにするため）
+             * last_checked_atは、定期実行ツールが最後に検索を実行した日時。一度も実行されていない場合はNULLとなる。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.saved_search (
+                  saved_search_id BIGSERIAL PRIMARY KEY,
+                  user_account_id BIGINT NOT NULL,
+                  search_param JSONB NOT NULL,
+                  alert_enabled BOOLEAN NOT NULL,
+                  unsubscribe_token ccs_schema.uuid_simple_form NOT NULL UNIQUE,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  last_checked_at TIMESTAMP WITH TIME ZONE
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE, DELETE ON ccs_schema.saved_search To user_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT USAGE ON SEQUENCE ccs_schema.saved_search_saved_search_id_seq TO user_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        // 定期実行ツールはadmin_appのロールを使う。そのため、定期実行ツールが検索の実行日時を更新できるようにUPDATE権限を保持させる
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT SELECT, UPDATE, DELETE ON ccs_schema.saved_search To admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX saved_search_user_account_id_idx ON ccs_schema.saved_search (user_account_id);",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* 定期実行ツール（notify_saved_search_matches）が、保存された検索条件に一致したコンサルタントを記録したときに生成される。
+             * 次回の実行時、記録済のコンサルタントを除くことで新たに条件に一致したコンサルタントを特定する。
+             * 対応するsaved_searchが削除されたとき、または条件に一致しなくなったときに削除される。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.saved_search_matched_consultant (
+                  saved_search_id BIGINT NOT NULL,
+                  consultant_id BIGINT NOT NULL,
+                  PRIMARY KEY (saved_search_id, consultant_id)
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, DELETE ON ccs_schema.saved_search_matched_consultant To user_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, DELETE ON ccs_schema.saved_search_matched_consultant To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* ユーザーがコンサルタントを検索したとき（ページングによる続きの検索を除く）に生成される。検索の傾向の分析に利用する。
+             * 匿名化のため、検索を行ったユーザーは記録しない。また、自由入力の検索条件は、会社名と職種以外は条件を指定したかどうかのみを記録する。
+             * criteriaは、指定された検索条件の名前（例: company_name、fee_per_hour_in_yen.equal_or_more）の配列
+             * num_of_hitsは検索条件に一致したコンサルタントの数、took_in_millisecondsは検索に要した時間（ミリ秒）
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.consultant_search_log (
+                  search_id ccs_schema.uuid_simple_form PRIMARY KEY,
+                  criteria JSONB NOT NULL,
+                  company_name VARCHAR (256),
+                  profession VARCHAR (128),
+                  sort_key VARCHAR (32),
+                  sort_order VARCHAR (4),
+                  num_of_hits BIGINT NOT NULL,
+                  took_in_milliseconds BIGINT NOT NULL,
+                  searched_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT INSERT ON ccs_schema.consultant_search_log To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.consultant_search_log To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX consultant_search_log_searched_at_idx ON ccs_schema.consultant_search_log (searched_at);",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* ユーザーが検索結果からコンサルタントの詳細を表示したときに生成される（同じ検索結果から同じコンサルタントを複数回表示した場合も一つのみ）。
+             * search_idは、詳細の表示元となった検索のconsultant_search_logのsearch_id
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.consultant_search_click (
+                  search_id ccs_schema.uuid_simple_form NOT NULL,
+                  consultant_id BIGINT NOT NULL,
+                  clicked_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  PRIMARY KEY (search_id, consultant_id)
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT INSERT ON ccs_schema.consultant_search_click To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.consultant_search_click To admin_app;"))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* ユーザーがコンサルタントをお気に入りに登録したときに生成される。ユーザーがお気に入りから外したときに削除される。
+             * コンサルタントのアカウントが無効化、または削除されたときにも削除される。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.favorite_consultant (
+                  user_account_id BIGINT NOT NULL,
+                  consultant_id BIGINT NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  PRIMARY KEY (user_account_id, consultant_id)
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, DELETE ON ccs_schema.favorite_consultant To user_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT SELECT, DELETE ON ccs_schema.favorite_consultant To admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX favorite_consultant_consultant_id_idx ON ccs_schema.favorite_consultant (consultant_id);",
+            ))
+            .await
+            .map(|_| ())?;
+
         let _ = conn
             /* ユーザーが相談申し込みをしたときに生成される。コンサルタントが相談申し込みを承認、または拒否したときに削除される。
              * 相談開始日時の候補すべてが現在時刻を超えている場合、定期実行ツールにより削除される
@@ -452,7 +724,11 @@ impl MigrationTrait for Migration {
                   second_candidate_date_time TIMESTAMP WITH TIME ZONE NOT NULL,
                   third_candidate_date_time TIMESTAMP WITH TIME ZONE NOT NULL,
                   latest_candidate_date_time TIMESTAMP WITH TIME ZONE NOT NULL,
-                  fee_per_hour_in_yen INTEGER NOT NULL
+                  fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
+                  CHECK (discount_in_yen >= 0 AND discount_in_yen <= fee_per_hour_in_yen)
                 );",
             ))
             .await
@@ -482,6 +758,102 @@ impl MigrationTrait for Migration {
             ))
             .await
             .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX consultation_req_coupon_id_idx ON ccs_schema.consultation_req (coupon_id);",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* 管理者がクーポンを作成したときに生成される。サービスの運用期間を通じて存在し続ける。不要なデータは有効期間でフィルタリングする
+             *
+             * max_num_of_uses、max_num_of_uses_per_userはNULLの場合、利用回数の制限がないことを示す。
+             * discount_typeがpercentageの場合、discount_valueは1から100までのパーセントを示す。fixed_amountの場合、円を示す。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.coupon (
+                  coupon_id BIGSERIAL PRIMARY KEY,
+                  code TEXT NOT NULL UNIQUE,
+                  discount_type ccs_schema.discount_type NOT NULL,
+                  discount_value INTEGER NOT NULL,
+                  max_num_of_uses INTEGER,
+                  max_num_of_uses_per_user INTEGER,
+                  valid_from TIMESTAMP WITH TIME ZONE NOT NULL,
+                  valid_until TIMESTAMP WITH TIME ZONE NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer NOT NULL,
+                  created_by ccs_schema.email_address NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  CHECK (discount_value > 0),
+                  CHECK (discount_type <> 'percentage' OR discount_value <= 100),
+                  CHECK (max_num_of_uses IS NULL OR max_num_of_uses > 0),
+                  CHECK (max_num_of_uses_per_user IS NULL OR max_num_of_uses_per_user > 0),
+                  CHECK (valid_until > valid_from)
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.coupon To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"GRANT SELECT, INSERT, UPDATE, DELETE ON ccs_schema.coupon To admin_app;",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT USAGE ON SEQUENCE ccs_schema.coupon_coupon_id_seq TO admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX coupon_valid_until_idx ON ccs_schema.coupon (valid_until);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* コンサルタントがクーポンを利用した相談申し込みを承認したときに生成される。サービスの運用期間を通じて存在し続ける。
+             * クーポンの利用回数は、このテーブルのレコード数と、クーポンを利用している相談申し込み（consultation_req）の数の合計とする。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.coupon_use (
+                  consultation_id BIGINT PRIMARY KEY,
+                  coupon_id BIGINT NOT NULL,
+                  user_account_id BIGINT NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.coupon_use To user_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT ON ccs_schema.coupon_use To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX coupon_use_coupon_id_idx ON ccs_schema.coupon_use (coupon_id);",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX coupon_use_user_account_id_idx ON ccs_schema.coupon_use (user_account_id);",
+            ))
+            .await
+            .map(|_| ())?;
 
         let _ = conn
             /* コンサルタントが相談申し込みを承認したときに生成される。サービスの運用期間を通じて存在し続ける。不要なデータは相談日時でフィルタリングして利用する */
@@ -539,12 +911,16 @@ impl MigrationTrait for Migration {
             /*
              * コンサルタントが相談申し込みを承認したときに生成される。
              * 管理者がユーザーからの支払いを確認したとき削除される。
+             * ユーザーがクレジットカードで支払い、決済代行サービスから決済完了の通知を受け取ったときに削除される。
              * 管理者がユーザーからの返金依頼を処理したときに削除される（返金を受け付けるのは、ユーザーが相談日時までに入金したにも関わらず、管理者が支払いの確認を出来なかった場合のみ）
              * 管理者が相談日時までにユーザーからの入金を確認できなかったときに削除される
              *
              * user_account_id、consultant_id、meeting_atは非正規化し、consultationと同じ値を保持する。
              * それらがないと仮定し、結合を使う場合、下記の点で問題があるため非正規化することしている。
              *  - このテーブルをconsultationと結合したとき、条件でのフィルタリングと取得件数制限の処理を同時に正しく処理する方法が煩雑
+             *
+             * fee_per_hour_in_yenは割引前の相談料。ユーザーが支払う金額は、fee_per_hour_in_yenからdiscount_in_yenを引いた値となる。
+             * coupon_id、discount_borne_byはクーポンを利用していない場合、NULLとなる（その場合、discount_in_yenは0となる）
              */
             .execute(sql.stmt(
                 r"CREATE TABLE ccs_schema.awaiting_payment (
@@ -553,51 +929,238 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"GRANT SELECT, INSERT, DELETE ON ccs_schema.awaiting_payment To user_app;",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ =
+            conn.execute(sql.stmt(
+                r"GRANT SELECT, UPDATE, DELETE ON ccs_schema.awaiting_payment To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX awaiting_payment_user_account_id_idx ON ccs_schema.awaiting_payment (user_account_id);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX awaiting_payment_consultant_id_idx ON ccs_schema.awaiting_payment (consultant_id);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX awaiting_payment_meeting_at_idx ON ccs_schema.awaiting_payment (meeting_at);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX awaiting_payment_created_at_idx ON ccs_schema.awaiting_payment (created_at);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /* 管理者が銀行から提供されたバーチャル口座の一覧を登録したときに生成される。サービスの運用期間を通じて存在し続ける。
+             * コンサルタントが相談申し込みを承認したとき（awaiting_paymentが生成されたとき）、未割り当てのバーチャル口座が一つ割り当てられる（consultation_id、assigned_atが設定される）
+             * awaiting_paymentが削除されたとき（入金確認、返金、入金なしのいずれかの最終状態となったとき）、割り当てが解除される（consultation_id、assigned_atがNULLとなる）
+             *
+             * 銀行コード、支店コード、口座種別、口座名義人は収納代行用の口座と共通のため、口座番号のみ保持する
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.virtual_bank_account (
+                  virtual_bank_account_id BIGSERIAL PRIMARY KEY,
+                  account_number TEXT NOT NULL UNIQUE,
+                  consultation_id BIGINT UNIQUE,
+                  assigned_at TIMESTAMP WITH TIME ZONE,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  CHECK ((consultation_id IS NULL AND assigned_at IS NULL) OR (consultation_id IS NOT NULL AND assigned_at IS NOT NULL))
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT SELECT, UPDATE ON ccs_schema.virtual_bank_account To user_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.virtual_bank_account To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT USAGE ON SEQUENCE ccs_schema.virtual_bank_account_virtual_bank_account_id_seq TO admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /*
+             * ユーザーがクレジットカードで相談料を支払うために決済ページ（チェックアウトセッション）を作成したときに生成される。サービスの運用期間を通じて存在し続ける。
+             * 決済代行サービスから決済完了のWebhookを受け取ったとき、payment_id、paid_atが設定される（同時にawaiting_paymentが削除され、awaiting_withdrawalが生成される）
+             * 管理者が返金を処理したとき、refund_id、refunded_atが設定される
+             *
+             * 決済ページは期限切れ等で作り直されることがあるため、一つの相談に対して複数存在し得る。
+             * 決済完了時に既にawaiting_paymentが存在しない場合（別の決済ページで支払い済みの場合等）、二重払いとしてその場で返金する。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.card_payment (
+                  checkout_session_id TEXT PRIMARY KEY,
+                  consultation_id BIGINT NOT NULL,
+                  user_account_id BIGINT NOT NULL,
+                  amount_in_yen INTEGER NOT NULL,
+                  payment_id TEXT UNIQUE,
+                  paid_at TIMESTAMP WITH TIME ZONE,
+                  refund_idempotency_key TEXT UNIQUE,
+                  refund_id TEXT UNIQUE,
+                  refunded_at TIMESTAMP WITH TIME ZONE,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  CHECK ((payment_id IS NULL AND paid_at IS NULL) OR (payment_id IS NOT NULL AND paid_at IS NOT NULL)),
+                  CHECK (refund_idempotency_key IS NULL OR payment_id IS NOT NULL),
+                  CHECK ((refund_id IS NULL AND refunded_at IS NULL) OR (refund_id IS NOT NULL AND refunded_at IS NOT NULL AND refund_idempotency_key IS NOT NULL))
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.card_payment To user_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, UPDATE ON ccs_schema.card_payment To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(
+                    r"CREATE INDEX card_payment_consultation_id_idx ON ccs_schema.card_payment (consultation_id);",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /*
+             * 相談料の支払いに関する状態（awaiting_payment、awaiting_withdrawal等）が遷移したときに、その遷移と同じトランザクション内で生成される。サービスの運用期間を通じて存在し続ける。
+             * 追記のみを行う履歴のため、UPDATE、DELETEの権限はどのロールにも付与しない。
+             *
+             * from_stateは、相談申し込みが承認され、最初の状態（awaiting_payment）が生成されたときにNULLとなる。
+             * performed_byは状態を遷移させた操作者（管理者、コンサルタント）のメールアドレス。決済代行サービスからのWebhook等、システムが遷移させた場合はシステムのメールアドレスとなる。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.payment_event (
+                  payment_event_id BIGSERIAL PRIMARY KEY,
+                  consultation_id BIGINT NOT NULL,
+                  from_state ccs_schema.payment_state,
+                  to_state ccs_schema.payment_state NOT NULL,
+                  performed_by ccs_schema.email_address NOT NULL,
                   created_at TIMESTAMP WITH TIME ZONE NOT NULL
                 );",
             ))
             .await
             .map(|_| ())?;
         let _ = conn
-            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.awaiting_payment To user_app;"))
+            .execute(sql.stmt(r"GRANT INSERT ON ccs_schema.payment_event To user_app;"))
             .await
             .map(|_| ())?;
-        let _ =
-            conn.execute(sql.stmt(
-                r"GRANT SELECT, UPDATE, DELETE ON ccs_schema.awaiting_payment To admin_app;",
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.payment_event To admin_app;"))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT USAGE ON SEQUENCE ccs_schema.payment_event_payment_event_id_seq TO user_app;",
             ))
             .await
             .map(|_| ())?;
         let _ = conn
-            .execute(
-                sql.stmt(
-                    r"CREATE INDEX awaiting_payment_user_account_id_idx ON ccs_schema.awaiting_payment (user_account_id);",
-                ),
-            )
+            .execute(sql.stmt(
+                r"GRANT USAGE ON SEQUENCE ccs_schema.payment_event_payment_event_id_seq TO admin_app;",
+            ))
             .await
             .map(|_| ())?;
         let _ = conn
             .execute(
                 sql.stmt(
-                    r"CREATE INDEX awaiting_payment_consultant_id_idx ON ccs_schema.awaiting_payment (consultant_id);",
+                    r"CREATE INDEX payment_event_consultation_id_idx ON ccs_schema.payment_event (consultation_id);",
                 ),
             )
             .await
             .map(|_| ())?;
+
         let _ = conn
-            .execute(
-                sql.stmt(
-                    r"CREATE INDEX awaiting_payment_meeting_at_idx ON ccs_schema.awaiting_payment (meeting_at);",
-                ),
-            )
+            /*
+             * 定期実行ツール（payment_reconciliation_report）が、前日分の入出金レポートを作成したときに生成される。サービスの運用期間を通じて存在し続ける。
+             * report_dateはレポートの対象日（日本時間）。同じ対象日でツールが再実行された場合、値は上書きされる。
+             *
+             * day_*は対象日の一日分、month_to_date_*は対象日を含む月の初日から対象日までの集計。
+             * 金額は、payout（receipt_of_consultation）は報酬（reward）の合計、それ以外は相談料（fee_per_hour_in_yen）の合計。
+             * unpaid_reward_*は、レポート作成時点で出金待ちの期間を過ぎているにも関わらず、まだ出金されていない（awaiting_withdrawalに存在する）ものの集計。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.payment_reconciliation_snapshot (
+                  report_date DATE PRIMARY KEY,
+                  day_new_awaiting_payment_count BIGINT NOT NULL,
+                  day_new_awaiting_payment_amount_in_yen BIGINT NOT NULL,
+                  day_confirmed_payment_count BIGINT NOT NULL,
+                  day_confirmed_payment_amount_in_yen BIGINT NOT NULL,
+                  day_payout_count BIGINT NOT NULL,
+                  day_payout_amount_in_yen BIGINT NOT NULL,
+                  day_refund_count BIGINT NOT NULL,
+                  day_refund_amount_in_yen BIGINT NOT NULL,
+                  day_neglected_payment_count BIGINT NOT NULL,
+                  day_neglected_payment_amount_in_yen BIGINT NOT NULL,
+                  month_to_date_new_awaiting_payment_count BIGINT NOT NULL,
+                  month_to_date_new_awaiting_payment_amount_in_yen BIGINT NOT NULL,
+                  month_to_date_confirmed_payment_count BIGINT NOT NULL,
+                  month_to_date_confirmed_payment_amount_in_yen BIGINT NOT NULL,
+                  month_to_date_payout_count BIGINT NOT NULL,
+                  month_to_date_payout_amount_in_yen BIGINT NOT NULL,
+                  month_to_date_refund_count BIGINT NOT NULL,
+                  month_to_date_refund_amount_in_yen BIGINT NOT NULL,
+                  month_to_date_neglected_payment_count BIGINT NOT NULL,
+                  month_to_date_neglected_payment_amount_in_yen BIGINT NOT NULL,
+                  unpaid_reward_count BIGINT NOT NULL,
+                  unpaid_reward_amount_in_yen BIGINT NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
             .await
             .map(|_| ())?;
         let _ = conn
-            .execute(
-                sql.stmt(
-                    r"CREATE INDEX awaiting_payment_created_at_idx ON ccs_schema.awaiting_payment (created_at);",
-                ),
-            )
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.payment_reconciliation_snapshot To admin_app;",
+            ))
             .await
             .map(|_| ())?;
 
@@ -623,7 +1186,9 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
         let _ = conn
-            .execute(sql.stmt(r"GRANT SELECT, UPDATE ON ccs_schema.user_rating To user_app;"))
+            .execute(
+                sql.stmt(r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.user_rating To user_app;"),
+            )
             .await
             .map(|_| ())?;
         let _ = conn
@@ -674,8 +1239,10 @@ impl MigrationTrait for Migration {
             ))
             .await
             .map(|_| ())?;
-        let _ = conn
-            .execute(sql.stmt(r"GRANT SELECT, UPDATE ON ccs_schema.consultant_rating To user_app;"))
+        let _ =
+            conn.execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.consultant_rating To user_app;",
+            ))
             .await
             .map(|_| ())?;
         let _ = conn
@@ -711,7 +1278,7 @@ impl MigrationTrait for Migration {
 
         let _ = conn
             /*
-             * 管理者がユーザーの入金を確認したときに生成される。
+             * 管理者がユーザーの入金を確認したとき、または決済代行サービスからクレジットカード決済完了の通知を受け取ったときに生成される。
              * 管理者がコンサルタントへプラットフォーム手数料と振込手数料を指し引いて出金したことを確認したときに削除される。
              * 管理者が、ユーザーから苦情を受け、客観的な証拠を確認し、返金した後に削除される。
              *
@@ -721,6 +1288,8 @@ impl MigrationTrait for Migration {
              *
              * sender_nameは入金時に確認できた振込依頼人（身分情報にある姓名）のこと。
              * 身分情報にある姓名はユーザーによって更新が可能なので確認時の情報は変更されても残るように別途保管しておく。
+             * クレジットカード決済の場合、振込依頼人は存在しないため、決済を識別する文字列を保管する。
+             * その場合、payment_confirmed_byにはシステムのメールアドレスを保管する。
              */
             .execute(sql.stmt(
                 r"CREATE TABLE ccs_schema.awaiting_withdrawal (
@@ -729,6 +1298,9 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
                   sender_name TEXT NOT NULL,
                   payment_confirmed_by ccs_schema.email_address NOT NULL,
                   created_at TIMESTAMP WITH TIME ZONE NOT NULL
@@ -736,6 +1308,10 @@ impl MigrationTrait for Migration {
             ))
             .await
             .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT INSERT ON ccs_schema.awaiting_withdrawal To user_app;"))
+            .await
+            .map(|_| ())?;
         let _ = conn
             .execute(
                 sql.stmt(r"GRANT SELECT, INSERT, UPDATE, DELETE ON ccs_schema.awaiting_withdrawal To admin_app;"),
@@ -790,6 +1366,9 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
                   sender_name TEXT NOT NULL,
                   confirmed_by ccs_schema.email_address NOT NULL,
                   created_at TIMESTAMP WITH TIME ZONE NOT NULL
@@ -851,6 +1430,9 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
                   neglect_confirmed_by ccs_schema.email_address NOT NULL,
                   created_at TIMESTAMP WITH TIME ZONE NOT NULL
                 );",
@@ -906,6 +1488,8 @@ impl MigrationTrait for Migration {
              *  - このテーブルをconsultationと結合したとき、条件でのフィルタリングと取得件数制限の処理を同時に正しく処理する方法が煩雑
              *
              * 口座情報はユーザーによって更新が可能なので、確認時の情報は変更されても残るように別途保管しておく。
+             *
+             * rewardは、discount_borne_byがconsultantの場合、割引額を差し引いた値となる。
              */
             .execute(sql.stmt(
                 r"CREATE TABLE ccs_schema.receipt_of_consultation (
@@ -914,6 +1498,9 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
                   platform_fee_rate_in_percentage TEXT NOT NULL,
                   transfer_fee_in_yen INTEGER NOT NULL,
                   reward INTEGER NOT NULL,
@@ -973,6 +1560,7 @@ impl MigrationTrait for Migration {
              * 1. 管理者が、ユーザーが相談日時までに入金したにも関わらず支払いの確認を出来なかった場合、返金した後生成される。
              * 2. 管理者が、ユーザーから苦情を受け、客観的な証拠を確認し、返金した後に生成される。
              * (振込手数料は、1の場合管理者が負担し、2の場合はコンサルタントに負担させる（次回コンサルタントに入金する際に損害を差し引く）)
+             * クレジットカードで支払われていた場合、決済代行サービスを通じて返金するため、振込手数料（transfer_fee_in_yen）は0となる。
              * サービスの運用期間を通じて存在し続ける。
              *
              * user_account_id、consultant_id、meeting_atは非正規化し、consultationと同じ値を保持する。
@@ -986,6 +1574,9 @@ impl MigrationTrait for Migration {
                   consultant_id BIGINT NOT NULL,
                   meeting_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   fee_per_hour_in_yen INTEGER NOT NULL,
+                  coupon_id BIGINT,
+                  discount_in_yen INTEGER NOT NULL,
+                  discount_borne_by ccs_schema.discount_bearer,
                   transfer_fee_in_yen INTEGER NOT NULL,
                   sender_name TEXT NOT NULL,
                   reason TEXT NOT NULL,
@@ -1038,6 +1629,11 @@ impl MigrationTrait for Migration {
              * 画像ファイル名は、user_account_idと組み合わせて外部に保存する。そのため、データベースに保管する値のUNIQUE指定は必須ではない
              * UNIQUEにしたときのNULLの扱いがデータベースごとに異なる可能性がある。従って、その点も考慮し、NULL利用があるカラムにUNIQUE付与を避ける
              */
+            /*
+             * claimed_by, claimed_at
+             * 確認作業中の管理者とその開始日時を示す。複数の管理者が同じ依頼を同時に扱わないようにするために利用する
+             * 確認作業の開始から一定時間経過したものは、確認作業中ではないものとして扱う（期限切れの判定はアプリケーションで行う）
+             */
             .execute(sql.stmt(
                 r"CREATE TABLE ccs_schema.create_identity_req (
                   user_account_id BIGINT PRIMARY KEY,
@@ -1053,7 +1649,9 @@ impl MigrationTrait for Migration {
                   telephone_number VARCHAR (13) NOT NULL,
                   image1_file_name_without_ext ccs_schema.uuid_simple_form NOT NULL,
                   image2_file_name_without_ext ccs_schema.uuid_simple_form,
-                  requested_at TIMESTAMP WITH TIME ZONE NOT NULL
+                  requested_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  claimed_by ccs_schema.email_address,
+                  claimed_at TIMESTAMP WITH TIME ZONE
                 );",
             ))
             .await
@@ -1116,6 +1714,41 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
 
+        let _ = conn
+            /* 管理者が本人確認、職務経歴確認の依頼を拒否する際に利用する拒否理由のテンプレート。管理者により作成、更新される */
+            /* 拒否した依頼の記録（rejected_*テーブル）からcodeで参照されるため、削除はしない */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.rejection_reason_template (
+                  code VARCHAR (32) PRIMARY KEY,
+                  title VARCHAR (64) NOT NULL,
+                  body VARCHAR (128) NOT NULL,
+                  updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                  updated_by ccs_schema.email_address
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE ON ccs_schema.rejection_reason_template To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            /* 拒否にはテンプレートの指定が必須のため、運用開始時点から拒否できるように基本的な拒否理由を登録しておく */
+            /* 管理者が作成、更新したテンプレートと区別できるように、updated_byはNULLとする（管理者が更新した時点で、その管理者のメールアドレスが入る） */
+            .execute(sql.stmt(
+                r"INSERT INTO ccs_schema.rejection_reason_template (code, title, body, updated_at, updated_by) VALUES
+                  ('blurred-image', '画像が不鮮明', '画像が不鮮明で、記載内容を確認できなかったため', CURRENT_TIMESTAMP, NULL),
+                  ('mismatched-information', '入力内容の不一致', '入力された内容と確認書類の記載内容が一致しなかったため', CURRENT_TIMESTAMP, NULL),
+                  ('unacceptable-document', '対象外の書類', '確認書類として受け付けていない書類が提出されたため', CURRENT_TIMESTAMP, NULL),
+                  ('expired-document', '有効期限切れ', '確認書類の有効期限が切れていたため', CURRENT_TIMESTAMP, NULL),
+                  ('missing-information', '記載箇所の不足', '確認に必要な記載箇所が画像に含まれていなかったため', CURRENT_TIMESTAMP, NULL),
+                  ('other', 'その他', '下記の理由により、提出された内容を確認できなかったため', CURRENT_TIMESTAMP, NULL);",
+            ))
+            .await
+            .map(|_| ())?;
+
         let _ = conn
             /* 管理者がユーザーの新規身分確認依頼を拒否したときに生成される。サービスの運用期間を通じて存在し続ける */
             /*
@@ -1147,6 +1780,7 @@ impl MigrationTrait for Migration {
                   address_line2 VARCHAR (128),
                   telephone_number VARCHAR (13) NOT NULL,
                   reason VARCHAR (256) NOT NULL,
+                  reason_template_code VARCHAR (32),
                   rejected_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   rejected_by ccs_schema.email_address NOT NULL
                 );",
@@ -1180,6 +1814,11 @@ impl MigrationTrait for Migration {
              * 画像ファイル名は、user_account_idと組み合わせて外部に保存する。そのため、データベースに保管する値のUNIQUE指定は必須ではない
              * UNIQUEにしたときのNULLの扱いがデータベースごとに異なる可能性がある。従って、その点も考慮し、NULL利用があるカラムにUNIQUE付与を避ける
              */
+            /*
+             * claimed_by, claimed_at
+             * 確認作業中の管理者とその開始日時を示す。複数の管理者が同じ依頼を同時に扱わないようにするために利用する
+             * 確認作業の開始から一定時間経過したものは、確認作業中ではないものとして扱う（期限切れの判定はアプリケーションで行う）
+             */
             .execute(sql.stmt(
                 r"CREATE TABLE ccs_schema.update_identity_req (
                     user_account_id BIGINT PRIMARY KEY,
@@ -1195,7 +1834,9 @@ impl MigrationTrait for Migration {
                     telephone_number VARCHAR (13) NOT NULL,
                     image1_file_name_without_ext ccs_schema.uuid_simple_form NOT NULL,
                     image2_file_name_without_ext ccs_schema.uuid_simple_form,
-                    requested_at TIMESTAMP WITH TIME ZONE NOT NULL
+                    requested_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                    claimed_by ccs_schema.email_address,
+                    claimed_at TIMESTAMP WITH TIME ZONE
                   );",
             ))
             .await
@@ -1295,6 +1936,7 @@ impl MigrationTrait for Migration {
                   address_line2 VARCHAR (128),
                   telephone_number VARCHAR (13) NOT NULL,
                   reason VARCHAR (256) NOT NULL,
+                  reason_template_code VARCHAR (32),
                   rejected_at TIMESTAMP WITH TIME ZONE NOT NULL,
                   rejected_by ccs_schema.email_address NOT NULL
                 );",
@@ -1315,6 +1957,55 @@ impl MigrationTrait for Migration {
             .map(|_| ())?;
 
         let _ = conn
+            /* ユーザーが本人確認依頼 (新規、更新) で身分証の画像をアップロードしたときに生成される。サービスの運用期間を通じて存在し続ける */
+            /*
+             * 同じ身分証の画像を別のユーザーが使い回すことを検出するために、画像の知覚ハッシュ (perceptual hash) を保持する。
+             * 依頼が拒否されて画像の実体が削除された後も、使い回しを検出できるようにレコードは削除しない。
+             * 画像ファイル名はuuidのため重複しない。そのため、image_file_name_without_extをPRIMARY KEYとして扱う。
+             */
+            /*
+             * user_account_idを外部キーにすると、user_accountの操作時に同時にこちらのテーブルのレコードも操作されて、
+             * 使い回しを検出できなくなる可能性がある。そのため、user_account_idは外部キーとしない
+             */
+            /*
+             * perceptual_hashは64ビットのハッシュ値をBIGINTとして保持する
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.identity_image_hash (
+                  image_file_name_without_ext ccs_schema.uuid_simple_form PRIMARY KEY,
+                  user_account_id BIGINT NOT NULL,
+                  perceptual_hash BIGINT NOT NULL,
+                  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+                );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.identity_image_hash To user_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            // INSERTは、既存の身分証の画像の知覚ハッシュを登録するツール（backfill_identity_image_hashes）で利用する
+            .execute(
+                sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.identity_image_hash To admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX identity_image_hash_user_account_id_idx ON ccs_schema.identity_image_hash (user_account_id);",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            /*
+             * claimed_by, claimed_at
+             * 確認作業中の管理者とその開始日時を示す。複数の管理者が同じ依頼を同時に扱わないようにするために利用する
+             * 確認作業の開始から一定時間経過したものは、確認作業中ではないものとして扱う（期限切れの判定はアプリケーションで行う）
+             */
             .execute(
                 /* ユーザーが管理者に職務経歴の確認を依頼したときに生成される。
                  * 管理者が職務経歴の内容を承認、または拒否したときに削除される。
@@ -1345,7 +2036,10 @@ impl MigrationTrait for Migration {
                     note VARCHAR (2048),
                     image1_file_name_without_ext ccs_schema.uuid_simple_form NOT NULL,
                     image2_file_name_without_ext ccs_schema.uuid_simple_form,
-                    requested_at TIMESTAMP WITH TIME ZONE NOT NULL
+                    requested_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                    claimed_by ccs_schema.email_address,
+                    claimed_at TIMESTAMP WITH TIME ZONE,
+                    verified_work_email_domain VARCHAR (253)
                   );",
                 ),
             )
@@ -1355,6 +2049,12 @@ impl MigrationTrait for Migration {
             .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.create_career_req To user_app;"))
             .await
             .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT UPDATE (verified_work_email_domain) ON ccs_schema.create_career_req To user_app;",
+            ))
+            .await
+            .map(|_| ())?;
         // 定期削除ツールはadmin_appのロールを使う。そのため、定期削除ツールが削除できるようにDELETE権限を保持させる
         let _ = conn
             .execute(sql.stmt(
@@ -1375,6 +2075,36 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
 
+        let _ = conn
+            /* ユーザーが職務経歴確認依頼について、勤務先のメールアドレスによる確認を試みたときに生成される。
+             * 確認に成功したとき、または職務経歴確認依頼が承認、拒否されたときに削除される。
+             * 勤務先のメールアドレスは長期間保存しないため、ドメインのみを保持する。
+             */
+            .execute(sql.stmt(
+                r"CREATE TABLE ccs_schema.work_email_verification_code (
+                create_career_req_id BIGINT PRIMARY KEY,
+                user_account_id BIGINT NOT NULL,
+                email_domain VARCHAR (253) NOT NULL,
+                hashed_code BYTEA NOT NULL,
+                num_of_attempts SMALLINT NOT NULL,
+                requested_at TIMESTAMP WITH TIME ZONE NOT NULL
+              );",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT, UPDATE, DELETE ON ccs_schema.work_email_verification_code To user_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, DELETE ON ccs_schema.work_email_verification_code To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+
         let _ = conn
             .execute(
                 /* 管理者がユーザーの職務経歴の内容を承認したときに生成される。サービスの運用期間を通じて存在し続ける */
@@ -1387,6 +2117,7 @@ impl MigrationTrait for Migration {
                     appr_cre_career_req_id BIGSERIAL PRIMARY KEY,
                     user_account_id BIGINT NOT NULL,
                     company_name VARCHAR (256) NOT NULL,
+                    company_id BIGINT NOT NULL,
                     department_name VARCHAR (256),
                     office VARCHAR (256),
                     career_start_date DATE NOT NULL,
@@ -1401,7 +2132,8 @@ impl MigrationTrait for Migration {
                     image1_file_name_without_ext ccs_schema.uuid_simple_form NOT NULL,
                     image2_file_name_without_ext ccs_schema.uuid_simple_form,
                     approved_at TIMESTAMP WITH TIME ZONE NOT NULL,
-                    approved_by ccs_schema.email_address NOT NULL
+                    approved_by ccs_schema.email_address NOT NULL,
+                    verified_work_email_domain VARCHAR (253)
                   );",
                 ),
             )
@@ -1485,6 +2217,7 @@ impl MigrationTrait for Migration {
                     is_new_graduate BOOLEAN NOT NULL,
                     note VARCHAR (2048),
                     reason VARCHAR (256) NOT NULL,
+                    reason_template_code VARCHAR (32),
                     rejected_at TIMESTAMP WITH TIME ZONE NOT NULL,
                     rejected_by ccs_schema.email_address NOT NULL
                   );",
@@ -1505,6 +2238,172 @@ impl MigrationTrait for Migration {
             .await
             .map(|_| ())?;
 
+        let _ = conn
+            /*
+             * claimed_by, claimed_at
+             * 確認作業中の管理者とその開始日時を示す。複数の管理者が同じ依頼を同時に扱わないようにするために利用する
+             * 確認作業の開始から一定時間経過したものは、確認作業中ではないものとして扱う（期限切れの判定はアプリケーションで行う）
+             */
+            .execute(
+                /* ユーザーが管理者に職務経歴の更新を依頼したときに生成される。
+                 * 管理者が職務経歴の更新依頼を承認、または拒否したときに削除される。
+                 */
+                /*
+                 * career一つに対して、update_career_req (職務経歴確認依頼 (更新)) は0もしくは1の関係とする。従って、career_idをPRIMARY KEYに指定する
+                 * career_idを外部キーにすると、careerの削除時に同時にこちらのテーブルのレコードも操作されて、
+                 * 画像の実体との紐づけが知らないうちに解除される可能性がある。そのため、career_idは外部キーとしない（user_account_idも同様の理由で外部キーとしない）
+                 */
+                /*
+                 * 職務経歴の各項目には、更新後の値（更新しない項目は現在の値）を保持する。
+                 * 退社日と備考のみを更新する場合、エビデンスとなる画像の提出は不要とするため、image1_file_name_without_extはNULLを許容する
+                 */
+                sql.stmt(
+                    r"CREATE TABLE ccs_schema.update_career_req (
+                    career_id BIGINT PRIMARY KEY,
+                    user_account_id BIGINT NOT NULL,
+                    company_name VARCHAR (256) NOT NULL,
+                    department_name VARCHAR (256),
+                    office VARCHAR (256),
+                    career_start_date DATE NOT NULL,
+                    career_end_date DATE,
+                    contract_type ccs_schema.contract_type NOT NULL,
+                    profession VARCHAR (128),
+                    annual_income_in_man_yen INTEGER,
+                    is_manager BOOLEAN NOT NULL,
+                    position_name VARCHAR (128),
+                    is_new_graduate BOOLEAN NOT NULL,
+                    note VARCHAR (2048),
+                    image1_file_name_without_ext ccs_schema.uuid_simple_form,
+                    image2_file_name_without_ext ccs_schema.uuid_simple_form,
+                    requested_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                    claimed_by ccs_schema.email_address,
+                    claimed_at TIMESTAMP WITH TIME ZONE
+                  );",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(r"GRANT SELECT, INSERT ON ccs_schema.update_career_req To user_app;"))
+            .await
+            .map(|_| ())?;
+        // 定期削除ツールはadmin_appのロールを使う。そのため、定期削除ツールが削除できるようにDELETE権限を保持させる
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, UPDATE, DELETE ON ccs_schema.update_career_req To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"CREATE INDEX update_career_req_requested_at_idx ON ccs_schema.update_career_req (requested_at);",
+            ))
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            .execute(
+                /* 管理者がユーザーの職務経歴の更新依頼を承認したときに生成される。サービスの運用期間を通じて存在し続ける */
+                /*
+                 * 同じ職務経歴に対して複数回更新の記録が残る可能性があるため、career_idをPRIMARY KEYとしては扱わない。
+                 */
+                /*
+                 * user_account_idを外部キーにすると、user_accountの操作時に同時にこちらのテーブルのレコードも操作されて、
+                 * 管理者の把握しないうちにレコードが消去される可能性がある。そのため、user_account_idは外部キーとしない
+                 */
+                /*
+                 * PRIMARY KEYはSEQUENCE名にしたときに識別子の63文字制限に引っかからないように命名する（appr_upd_career_req_id）
+                 */
+                sql.stmt(
+                    r"CREATE TABLE ccs_schema.approved_update_career_req (
+                    appr_upd_career_req_id BIGSERIAL PRIMARY KEY,
+                    career_id BIGINT NOT NULL,
+                    user_account_id BIGINT NOT NULL,
+                    company_name VARCHAR (256) NOT NULL,
+                    company_id BIGINT NOT NULL,
+                    department_name VARCHAR (256),
+                    office VARCHAR (256),
+                    career_start_date DATE NOT NULL,
+                    career_end_date DATE,
+                    contract_type ccs_schema.contract_type NOT NULL,
+                    profession VARCHAR (128),
+                    annual_income_in_man_yen INTEGER,
+                    is_manager BOOLEAN NOT NULL,
+                    position_name VARCHAR (128),
+                    is_new_graduate BOOLEAN NOT NULL,
+                    note VARCHAR (2048),
+                    image1_file_name_without_ext ccs_schema.uuid_simple_form,
+                    image2_file_name_without_ext ccs_schema.uuid_simple_form,
+                    approved_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                    approved_by ccs_schema.email_address NOT NULL
+                  );",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT ON ccs_schema.approved_update_career_req To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT USAGE ON SEQUENCE ccs_schema.approved_update_career_req_appr_upd_career_req_id_seq TO admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+
+        let _ = conn
+            .execute(
+                /* 管理者がユーザーの職務経歴の更新依頼を拒否したときに生成される。サービスの運用期間を通じて存在し続ける */
+                /*
+                 * user_account_idを外部キーにすると、user_accountの操作時に同時にこちらのテーブルのレコードも操作されて、
+                 * 管理者の把握しないうちにレコードが消去される可能性がある。そのため、user_account_idは外部キーとしない
+                 */
+                /*
+                 * アップロードされた画像は拒否時に削除するため、
+                 * image1_file_name_without_ext, image2_file_name_without_extは保持させない。
+                 */
+                sql.stmt(
+                    r"CREATE TABLE ccs_schema.rejected_update_career_req (
+                    rjd_upd_career_req_id BIGSERIAL PRIMARY KEY,
+                    career_id BIGINT NOT NULL,
+                    user_account_id BIGINT NOT NULL,
+                    company_name VARCHAR (256) NOT NULL,
+                    department_name VARCHAR (256),
+                    office VARCHAR (256),
+                    career_start_date DATE NOT NULL,
+                    career_end_date DATE,
+                    contract_type ccs_schema.contract_type NOT NULL,
+                    profession VARCHAR (128),
+                    annual_income_in_man_yen INTEGER,
+                    is_manager BOOLEAN NOT NULL,
+                    position_name VARCHAR (128),
+                    is_new_graduate BOOLEAN NOT NULL,
+                    note VARCHAR (2048),
+                    reason VARCHAR (256) NOT NULL,
+                    reason_template_code VARCHAR (32),
+                    rejected_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                    rejected_by ccs_schema.email_address NOT NULL
+                  );",
+                ),
+            )
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(sql.stmt(
+                r"GRANT SELECT, INSERT ON ccs_schema.rejected_update_career_req To admin_app;",
+            ))
+            .await
+            .map(|_| ())?;
+        let _ = conn
+            .execute(
+                sql.stmt(r"GRANT USAGE ON SEQUENCE ccs_schema.rejected_update_career_req_rjd_upd_career_req_id_seq TO admin_app;"),
+            )
+            .await
+            .map(|_| ())?;
+
         let _ = conn
             /* 管理者がメンテナンス期間を設定したときに生成される。
              * サービスの運用期間を通じて存在し続ける。不要なデータはメンテナンス日時でフィルタリングする */
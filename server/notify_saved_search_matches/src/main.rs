@@ -0,0 +1,1059 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, FixedOffset};
+use dotenv::dotenv;
+use entity::sea_orm::{
+    prelude::async_trait::async_trait, ActiveModelTrait, ColumnTrait, ConnectOptions, Database,
+    DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, Set, TransactionError,
+    TransactionTrait,
+};
+use opensearch::OpenSearch;
+use serde_json::{json, Value};
+use std::{
+    env::{set_var, var},
+    error::Error,
+    process::exit,
+};
+use tracing::{error, info};
+
+use common::{
+    admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD},
+    db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT},
+    log::{init_log, LOG_LEVEL},
+    opensearch::{
+        consultant_query::{create_query_json, SearchCondition},
+        create_client, search_documents, Sort, INDEX_NAME, KEY_TO_OPENSEARCH_AUTH,
+        KEY_TO_OPENSEARCH_ENDPOINT_URI, KEY_TO_OPENSEARCH_PASSWORD, KEY_TO_OPENSEARCH_USERNAME,
+    },
+    smtp::{
+        SendMail, SmtpClient, ADMIN_EMAIL_ADDRESS, AWS_SES_ACCESS_KEY_ID, AWS_SES_ENDPOINT_URI,
+        AWS_SES_REGION, AWS_SES_SECRET_ACCESS_KEY, INQUIRY_EMAIL_ADDRESS,
+        KEY_TO_ADMIN_EMAIL_ADDRESS, KEY_TO_AWS_SES_ENDPOINT_URI, KEY_TO_AWS_SES_REGION,
+        KEY_TO_INQUIRY_EMAIL_ADDRESS, KEY_TO_SYSTEM_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS,
+    },
+    util::check_env_vars,
+    JAPANESE_TIME_ZONE, KEY_TO_URL_FOR_FRONT_END, KEY_TO_USE_ECS_TASK_ROLE, URL_FOR_FRONT_END,
+    USE_ECS_TASK_ROLE, WEB_SITE_NAME,
+};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const APPLICATION_ERR: i32 = 3;
+
+/// 一つの検索条件で取得するコンサルタントの最大数
+///
+/// 新しく登録されたコンサルタントを優先して検出できるように、user_account_idの降順に取得する
+const MAX_NUM_OF_CONSULTANTS_PER_SAVED_SEARCH: i64 = 100;
+
+/// 一通のメールに記載する新たに一致したコンサルタントの最大数（一人のユーザーに対して一回の実行で送るメールは一通のみ）
+const MAX_NUM_OF_NEW_CONSULTANTS_PER_MAIL: usize = 20;
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        KEY_TO_OPENSEARCH_ENDPOINT_URI.to_string(),
+        KEY_TO_OPENSEARCH_AUTH.to_string(),
+        KEY_TO_OPENSEARCH_USERNAME.to_string(),
+        KEY_TO_OPENSEARCH_PASSWORD.to_string(),
+        KEY_TO_URL_FOR_FRONT_END.to_string(),
+        KEY_TO_ADMIN_EMAIL_ADDRESS.to_string(),
+        KEY_TO_SYSTEM_EMAIL_ADDRESS.to_string(),
+        KEY_TO_INQUIRY_EMAIL_ADDRESS.to_string(),
+        KEY_TO_AWS_SES_REGION.to_string(),
+        KEY_TO_AWS_SES_ENDPOINT_URI.to_string(),
+        KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    let log_conf = format!(
+        "notify_saved_search_matches={},common={},sea_orm={}",
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str()
+    );
+    set_var("RUST_LOG", log_conf);
+    init_log();
+
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let mut opt = ConnectOptions::new(database_url.clone());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let pool = Database::connect(opt).await.unwrap_or_else(|e| {
+        error!("failed to connect database: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let index_client = create_index_client().unwrap_or_else(|e| {
+        error!("failed to create OpenSearch client: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let op = NotifySavedSearchMatchesOperationImpl { pool, index_client };
+
+    let smtp_client = if *USE_ECS_TASK_ROLE {
+        SmtpClient::new_with_ecs_task_role(AWS_SES_REGION.as_str(), AWS_SES_ENDPOINT_URI.as_str())
+            .await
+    } else {
+        SmtpClient::new(
+            AWS_SES_REGION.as_str(),
+            AWS_SES_ACCESS_KEY_ID.as_str(),
+            AWS_SES_SECRET_ACCESS_KEY.as_str(),
+            AWS_SES_ENDPOINT_URI.as_str(),
+        )
+        .await
+    };
+
+    let result = notify_saved_search_matches(
+        current_date_time,
+        URL_FOR_FRONT_END.as_str(),
+        &op,
+        &smtp_client,
+    )
+    .await;
+
+    let notify_result = result.unwrap_or_else(|e| {
+        error!("failed to notify saved search matches: {}", e);
+        exit(APPLICATION_ERR)
+    });
+
+    info!(
+        "{} saved search(es) were (was) checked, {} user(s) were (was) notified successfully",
+        notify_result.num_of_saved_searches, notify_result.num_of_notified_users
+    );
+    exit(SUCCESS)
+}
+
+fn create_index_client() -> Result<OpenSearch, Box<dyn Error>> {
+    let opensearch_url = var(KEY_TO_OPENSEARCH_ENDPOINT_URI)?;
+    let opensearch_auth = var(KEY_TO_OPENSEARCH_AUTH)?.parse::<bool>()?;
+    let opensearch_username = var(KEY_TO_OPENSEARCH_USERNAME)?;
+    let opensearch_password = var(KEY_TO_OPENSEARCH_PASSWORD)?;
+    let index_client = create_client(
+        opensearch_url.as_str(),
+        opensearch_auth,
+        opensearch_username.as_str(),
+        opensearch_password.as_str(),
+    )?;
+    Ok(index_client)
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+struct NotifyResult {
+    num_of_saved_searches: usize,
+    num_of_notified_users: usize,
+}
+
+async fn notify_saved_search_matches(
+    current_date_time: DateTime<FixedOffset>,
+    url_for_front_end: &str,
+    op: &impl NotifySavedSearchMatchesOperation,
+    send_mail: &impl SendMail,
+) -> Result<NotifyResult, Box<dyn Error>> {
+    let saved_searches = op.get_alert_enabled_saved_searches().await?;
+    let mut user_account_ids: Vec<i64> = saved_searches.iter().map(|s| s.user_account_id).collect();
+    user_account_ids.dedup();
+
+    let mut notify_result = NotifyResult::default();
+    let mut notify_failed: Vec<(i64, String)> = Vec::new();
+    for user_account_id in user_account_ids {
+        let saved_searches_of_user: Vec<&SavedSearch> = saved_searches
+            .iter()
+            .filter(|s| s.user_account_id == user_account_id)
+            .collect();
+        let result = check_saved_searches_of_user(
+            user_account_id,
+            &saved_searches_of_user,
+            current_date_time,
+            url_for_front_end,
+            op,
+            send_mail,
+        )
+        .await;
+        match result {
+            Ok(notified) => {
+                notify_result.num_of_saved_searches += saved_searches_of_user.len();
+                if notified {
+                    notify_result.num_of_notified_users += 1;
+                }
+            }
+            Err(e) => {
+                error!(
+                    "failed check_saved_searches_of_user (user_account_id: {}): {}",
+                    user_account_id, e
+                );
+                notify_failed.push((user_account_id, e.to_string()));
+            }
+        }
+        op.wait_for_next_iteration().await;
+    }
+
+    if !notify_failed.is_empty() {
+        let subject = format!(
+            "[{}] 定期実行ツール (notify_saved_search_matches) 失敗通知",
+            WEB_SITE_NAME
+        );
+        let num_of_users = notify_result.num_of_notified_users + notify_failed.len();
+        let text = create_failure_text(num_of_users, &notify_failed);
+        let err_message = format!(
+            "{} processed, {} failed (detail: {:?})",
+            num_of_users,
+            notify_failed.len(),
+            notify_failed
+        );
+        send_mail
+            .send_mail(
+                ADMIN_EMAIL_ADDRESS.as_str(),
+                SYSTEM_EMAIL_ADDRESS.as_str(),
+                subject.as_str(),
+                text.as_str(),
+            )
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to send mail (status code: {}, response body: {:?}): {}",
+                    e.0, e.1, err_message
+                )
+            })?;
+        return Err(err_message.into());
+    }
+
+    Ok(notify_result)
+}
+
+/// ユーザーの保存した検索条件を実行し、新たに一致したコンサルタントがいればメールで通知する。通知した場合、trueを返す
+///
+/// 初回の実行時（last_checked_atがNone）は、その時点で一致しているコンサルタントを記録するのみで通知しない
+async fn check_saved_searches_of_user(
+    user_account_id: i64,
+    saved_searches: &[&SavedSearch],
+    current_date_time: DateTime<FixedOffset>,
+    url_for_front_end: &str,
+    op: &impl NotifySavedSearchMatchesOperation,
+    send_mail: &impl SendMail,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(email_address) = op
+        .find_email_address_of_enabled_user(user_account_id)
+        .await?
+    else {
+        info!(
+            "skip saved searches of disabled (or deleted) user (user_account_id: {})",
+            user_account_id
+        );
+        return Ok(false);
+    };
+
+    let mut checked_results = Vec::with_capacity(saved_searches.len());
+    let mut new_matches = Vec::new();
+    for saved_search in saved_searches {
+        let consultant_ids = op
+            .search_consultants(user_account_id, &saved_search.search_condition)
+            .await?;
+        if saved_search.last_checked_at.is_some() {
+            let matched_consultant_ids = op
+                .get_matched_consultant_ids(saved_search.saved_search_id)
+                .await?;
+            let new_consultant_ids: Vec<i64> = consultant_ids
+                .iter()
+                .filter(|id| !matched_consultant_ids.contains(id))
+                .copied()
+                .collect();
+            if !new_consultant_ids.is_empty() {
+                new_matches.push(NewMatches {
+                    saved_search_id: saved_search.saved_search_id,
+                    unsubscribe_token: saved_search.unsubscribe_token.clone(),
+                    consultant_ids: new_consultant_ids,
+                });
+            }
+        }
+        checked_results.push((saved_search.saved_search_id, consultant_ids));
+    }
+
+    let notified = !new_matches.is_empty();
+    if notified {
+        let subject = format!(
+            "[{}] 保存した検索条件に一致するコンサルタントのお知らせ",
+            WEB_SITE_NAME
+        );
+        let text = create_text(url_for_front_end, &new_matches);
+        send_mail
+            .send_mail(
+                email_address.as_str(),
+                SYSTEM_EMAIL_ADDRESS.as_str(),
+                subject.as_str(),
+                text.as_str(),
+            )
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to send mail (status code: {}, response body: {:?}, user_account_id: {})",
+                    e.0, e.1, user_account_id
+                )
+            })?;
+    }
+
+    // メールの送信に失敗した場合、次回の実行で改めて通知できるように、送信に成功した後に記録を更新する
+    for (saved_search_id, consultant_ids) in checked_results {
+        op.update_matched_consultants(saved_search_id, &consultant_ids, current_date_time)
+            .await?;
+    }
+
+    Ok(notified)
+}
+
+#[async_trait]
+trait NotifySavedSearchMatchesOperation {
+    /// alert_enabledがtrueの検索条件を、user_account_id、saved_search_idの昇順に取得する
+    async fn get_alert_enabled_saved_searches(&self) -> Result<Vec<SavedSearch>, Box<dyn Error>>;
+
+    /// アカウントが存在し、かつ無効化されていない場合、そのメールアドレスを返す
+    async fn find_email_address_of_enabled_user(
+        &self,
+        user_account_id: i64,
+    ) -> Result<Option<String>, Box<dyn Error>>;
+
+    /// 検索条件に一致するコンサルタントのuser_account_idを返す（検索を行うユーザー自身は含まない）
+    async fn search_consultants(
+        &self,
+        user_account_id: i64,
+        search_condition: &SearchCondition,
+    ) -> Result<Vec<i64>, Box<dyn Error>>;
+
+    /// 前回の実行時に検索条件に一致したとして記録したコンサルタントのuser_account_idを返す
+    async fn get_matched_consultant_ids(
+        &self,
+        saved_search_id: i64,
+    ) -> Result<Vec<i64>, Box<dyn Error>>;
+
+    /// 記録済のコンサルタントをconsultant_idsで置き換え、検索を実行した日時を更新する
+    async fn update_matched_consultants(
+        &self,
+        saved_search_id: i64,
+        consultant_ids: &[i64],
+        checked_at: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// 外部サービスに依存するアクションをする場合、その外部サービスのレートリミットにかからないように一定時間待つ
+    async fn wait_for_next_iteration(&self);
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct SavedSearch {
+    saved_search_id: i64,
+    user_account_id: i64,
+    search_condition: SearchCondition,
+    unsubscribe_token: String,
+    last_checked_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct NewMatches {
+    saved_search_id: i64,
+    unsubscribe_token: String,
+    consultant_ids: Vec<i64>,
+}
+
+struct NotifySavedSearchMatchesOperationImpl {
+    pool: DatabaseConnection,
+    index_client: OpenSearch,
+}
+
+#[async_trait]
+impl NotifySavedSearchMatchesOperation for NotifySavedSearchMatchesOperationImpl {
+    async fn get_alert_enabled_saved_searches(&self) -> Result<Vec<SavedSearch>, Box<dyn Error>> {
+        let models = entity::saved_search::Entity::find()
+            .filter(entity::saved_search::Column::AlertEnabled.eq(true))
+            .order_by_asc(entity::saved_search::Column::UserAccountId)
+            .order_by_asc(entity::saved_search::Column::SavedSearchId)
+            .all(&self.pool)
+            .await
+            .map_err(|e| format!("failed to get saved_search: {}", e))?;
+        let mut saved_searches = Vec::with_capacity(models.len());
+        for m in models {
+            let search_condition = serde_json::from_value::<SearchCondition>(m.search_param.clone())
+                .map_err(|e| {
+                    format!(
+                        "failed to deserialize search_param (saved_search_id: {}, search_param: {}): {}",
+                        m.saved_search_id, m.search_param, e
+                    )
+                })?;
+            saved_searches.push(SavedSearch {
+                saved_search_id: m.saved_search_id,
+                user_account_id: m.user_account_id,
+                search_condition,
+                unsubscribe_token: m.unsubscribe_token,
+                last_checked_at: m.last_checked_at,
+            });
+        }
+        Ok(saved_searches)
+    }
+
+    async fn find_email_address_of_enabled_user(
+        &self,
+        user_account_id: i64,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let model = entity::user_account::Entity::find_by_id(user_account_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to find user_account (user_account_id: {}): {}",
+                    user_account_id, e
+                )
+            })?;
+        Ok(model.and_then(|m| {
+            if m.disabled_at.is_some() {
+                None
+            } else {
+                Some(m.email_address)
+            }
+        }))
+    }
+
+    async fn search_consultants(
+        &self,
+        user_account_id: i64,
+        search_condition: &SearchCondition,
+    ) -> Result<Vec<i64>, Box<dyn Error>> {
+        let mut query = create_query_json(
+            user_account_id,
+            search_condition.career_param.clone(),
+            search_condition.fee_per_hour_in_yen_param.clone(),
+        );
+        query["_source"] = json!(["user_account_id"]);
+        let sort = Sort {
+            key: "user_account_id".to_string(),
+            order: "desc".to_string(),
+        };
+        let query_result = search_documents(
+            INDEX_NAME,
+            0,
+            MAX_NUM_OF_CONSULTANTS_PER_SAVED_SEARCH,
+            Some(sort),
+            &query,
+            &self.index_client,
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "failed to search documents (status code: {}, response body: {:?}, user_account_id: {})",
+                e.0, e.1, user_account_id
+            )
+        })?;
+        parse_consultant_ids(&query_result)
+    }
+
+    async fn get_matched_consultant_ids(
+        &self,
+        saved_search_id: i64,
+    ) -> Result<Vec<i64>, Box<dyn Error>> {
+        let models = entity::saved_search_matched_consultant::Entity::find()
+            .filter(
+                entity::saved_search_matched_consultant::Column::SavedSearchId.eq(saved_search_id),
+            )
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to get saved_search_matched_consultant (saved_search_id: {}): {}",
+                    saved_search_id, e
+                )
+            })?;
+        Ok(models.into_iter().map(|m| m.consultant_id).collect())
+    }
+
+    async fn update_matched_consultants(
+        &self,
+        saved_search_id: i64,
+        consultant_ids: &[i64],
+        checked_at: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>> {
+        let consultant_ids = consultant_ids.to_vec();
+        self.pool
+            .transaction::<_, (), DbErr>(|txn| {
+                Box::pin(async move {
+                    let _ = entity::saved_search_matched_consultant::Entity::delete_many()
+                        .filter(
+                            entity::saved_search_matched_consultant::Column::SavedSearchId
+                                .eq(saved_search_id),
+                        )
+                        .exec(txn)
+                        .await?;
+
+                    if !consultant_ids.is_empty() {
+                        let active_models = consultant_ids.into_iter().map(|consultant_id| {
+                            entity::saved_search_matched_consultant::ActiveModel {
+                                saved_search_id: Set(saved_search_id),
+                                consultant_id: Set(consultant_id),
+                            }
+                        });
+                        let _ = entity::saved_search_matched_consultant::Entity::insert_many(
+                            active_models,
+                        )
+                        .exec(txn)
+                        .await?;
+                    }
+
+                    let saved_search = entity::saved_search::ActiveModel {
+                        saved_search_id: Set(saved_search_id),
+                        last_checked_at: Set(Some(checked_at)),
+                        ..Default::default()
+                    };
+                    let _ = saved_search.update(txn).await?;
+
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    format!("connection error: {}", db_err)
+                }
+                TransactionError::Transaction(db_err) => {
+                    format!(
+                        "failed to update matched consultants (saved_search_id: {}): {}",
+                        saved_search_id, db_err
+                    )
+                }
+            })?;
+        Ok(())
+    }
+
+    async fn wait_for_next_iteration(&self) {
+        // SESのレートリミット（1秒あたりの送信数）にかからないように、ユーザーごとに少し待つ
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+fn parse_consultant_ids(query_result: &Value) -> Result<Vec<i64>, Box<dyn Error>> {
+    let hits = query_result["hits"]["hits"]
+        .as_array()
+        .ok_or_else(|| format!("failed to get hits: {}", query_result))?;
+    let mut consultant_ids = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let consultant_id = hit["_source"]["user_account_id"]
+            .as_i64()
+            .ok_or_else(|| format!("failed to find user_account_id: {}", hit))?;
+        consultant_ids.push(consultant_id);
+    }
+    Ok(consultant_ids)
+}
+
+fn create_text(url_for_front_end: &str, new_matches: &[NewMatches]) -> String {
+    let mut remaining = MAX_NUM_OF_NEW_CONSULTANTS_PER_MAIL;
+    let mut num_of_omitted = 0;
+    let mut sections = Vec::with_capacity(new_matches.len());
+    for new_match in new_matches {
+        let num_to_list = remaining.min(new_match.consultant_ids.len());
+        remaining -= num_to_list;
+        num_of_omitted += new_match.consultant_ids.len() - num_to_list;
+        let urls: Vec<String> = new_match.consultant_ids[..num_to_list]
+            .iter()
+            .map(|id| format!("{}/consultant-detail/{}", url_for_front_end, id))
+            .collect();
+        let urls = if urls.is_empty() {
+            "（記載できる件数を超えたため省略しました）".to_string()
+        } else {
+            urls.join("\n")
+        };
+        sections.push(format!(
+            r"【検索条件ID: {}】新たに一致したコンサルタント {}名
+{}
+
+この検索条件の通知を停止する場合は、下記URLにアクセスしてください。
+{}/saved-search-alert-unsubscribe?token={}",
+            new_match.saved_search_id,
+            new_match.consultant_ids.len(),
+            urls,
+            url_for_front_end,
+            new_match.unsubscribe_token
+        ));
+    }
+    let omitted = if num_of_omitted > 0 {
+        format!(
+            "\n\nほか{}名のコンサルタントが新たに一致しています。コンサルタント検索からご確認ください。",
+            num_of_omitted
+        )
+    } else {
+        "".to_string()
+    };
+    format!(
+        r"保存した検索条件に新たに一致したコンサルタントがいます。
+
+{}{}
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+        sections.join("\n\n"),
+        omitted,
+        INQUIRY_EMAIL_ADDRESS.as_str()
+    )
+}
+
+fn create_failure_text(num_of_users: usize, notify_failed: &[(i64, String)]) -> String {
+    format!(
+        r"保存された検索条件を確認したユーザー{}名の内、{}名の処理に失敗しました。
+
+【詳細】
+{:?}",
+        num_of_users,
+        notify_failed.len(),
+        notify_failed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Mutex;
+
+    use chrono::TimeZone;
+    use common::{
+        opensearch::consultant_query::{
+            AnnualInComeInManYenParam, CareerParam, FeePerHourInYenParam, YearsOfServiceParam,
+        },
+        ErrResp,
+    };
+
+    use super::*;
+
+    const URL_FOR_TEST: &str = "https://localhost:8080";
+
+    struct NotifySavedSearchMatchesOperationMock {
+        saved_searches: Vec<SavedSearch>,
+        /// user_account_idとメールアドレス（無効化されたユーザーは含めない）
+        enabled_users: Vec<(i64, String)>,
+        /// saved_search_idと検索結果
+        search_results: Vec<(i64, Vec<i64>)>,
+        /// saved_search_idと記録済のコンサルタント
+        matched_consultants: Vec<(i64, Vec<i64>)>,
+        /// update_matched_consultantsに渡されたsaved_search_idとconsultant_ids
+        updated: Mutex<Vec<(i64, Vec<i64>)>>,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl NotifySavedSearchMatchesOperation for NotifySavedSearchMatchesOperationMock {
+        async fn get_alert_enabled_saved_searches(
+            &self,
+        ) -> Result<Vec<SavedSearch>, Box<dyn Error>> {
+            Ok(self.saved_searches.clone())
+        }
+
+        async fn find_email_address_of_enabled_user(
+            &self,
+            user_account_id: i64,
+        ) -> Result<Option<String>, Box<dyn Error>> {
+            Ok(self
+                .enabled_users
+                .iter()
+                .find(|u| u.0 == user_account_id)
+                .map(|u| u.1.clone()))
+        }
+
+        async fn search_consultants(
+            &self,
+            user_account_id: i64,
+            search_condition: &SearchCondition,
+        ) -> Result<Vec<i64>, Box<dyn Error>> {
+            let saved_search = self
+                .saved_searches
+                .iter()
+                .find(|s| {
+                    s.user_account_id == user_account_id && s.search_condition == *search_condition
+                })
+                .expect("failed to get Some");
+            // 検索結果が用意されていない場合、検索に失敗したものとして扱う
+            let search_result = self
+                .search_results
+                .iter()
+                .find(|r| r.0 == saved_search.saved_search_id)
+                .ok_or("mock error message")?;
+            Ok(search_result.1.clone())
+        }
+
+        async fn get_matched_consultant_ids(
+            &self,
+            saved_search_id: i64,
+        ) -> Result<Vec<i64>, Box<dyn Error>> {
+            Ok(self
+                .matched_consultants
+                .iter()
+                .find(|m| m.0 == saved_search_id)
+                .map(|m| m.1.clone())
+                .unwrap_or_default())
+        }
+
+        async fn update_matched_consultants(
+            &self,
+            saved_search_id: i64,
+            consultant_ids: &[i64],
+            checked_at: DateTime<FixedOffset>,
+        ) -> Result<(), Box<dyn Error>> {
+            assert_eq!(self.current_date_time, checked_at);
+            self.updated
+                .lock()
+                .expect("failed to lock")
+                .push((saved_search_id, consultant_ids.to_vec()));
+            Ok(())
+        }
+
+        async fn wait_for_next_iteration(&self) {
+            // テストコードでは待つ必要はないので何もしない
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub(super) struct SendMailMock {
+        to: String,
+        from: String,
+        subject: String,
+        text_keywords: Vec<String>,
+    }
+
+    impl SendMailMock {
+        pub(super) fn new(
+            to: String,
+            from: String,
+            subject: String,
+            text_keywords: Vec<String>,
+        ) -> Self {
+            Self {
+                to,
+                from,
+                subject,
+                text_keywords,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SendMail for SendMailMock {
+        async fn send_mail(
+            &self,
+            to: &str,
+            from: &str,
+            subject: &str,
+            text: &str,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.to, to);
+            assert_eq!(self.from, from);
+            assert_eq!(self.subject, subject);
+            for text_keyword in self.text_keywords.clone() {
+                assert!(
+                    text.contains(&text_keyword),
+                    "{} not in {}",
+                    text_keyword,
+                    text
+                );
+            }
+            Ok(())
+        }
+    }
+
+    fn create_search_condition(equal_or_less: Option<i32>) -> SearchCondition {
+        SearchCondition {
+            career_param: CareerParam {
+                company_name: None,
+                company_id: None,
+                department_name: None,
+                office: None,
+                years_of_service: YearsOfServiceParam {
+                    equal_or_more: None,
+                    less_than: None,
+                },
+                employed: None,
+                contract_type: None,
+                profession: None,
+                annual_income_in_man_yen: AnnualInComeInManYenParam {
+                    equal_or_more: None,
+                    equal_or_less: None,
+                },
+                is_manager: None,
+                position_name: None,
+                is_new_graduate: None,
+                note: None,
+            },
+            fee_per_hour_in_yen_param: FeePerHourInYenParam {
+                equal_or_more: None,
+                equal_or_less,
+            },
+        }
+    }
+
+    fn create_saved_search(
+        saved_search_id: i64,
+        user_account_id: i64,
+        equal_or_less: Option<i32>,
+        last_checked_at: Option<DateTime<FixedOffset>>,
+    ) -> SavedSearch {
+        SavedSearch {
+            saved_search_id,
+            user_account_id,
+            search_condition: create_search_condition(equal_or_less),
+            unsubscribe_token: format!("{:0>32}", saved_search_id),
+            last_checked_at,
+        }
+    }
+
+    fn create_current_date_time() -> DateTime<FixedOffset> {
+        JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 20, 7, 0, 0)
+            .unwrap()
+    }
+
+    fn create_last_checked_at() -> Option<DateTime<FixedOffset>> {
+        Some(
+            JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2023, 9, 19, 7, 0, 0)
+                .unwrap(),
+        )
+    }
+
+    fn create_subject() -> String {
+        format!(
+            "[{}] 保存した検索条件に一致するコンサルタントのお知らせ",
+            WEB_SITE_NAME
+        )
+    }
+
+    #[tokio::test]
+    async fn notify_saved_search_matches_success_no_saved_searches() {
+        let current_date_time = create_current_date_time();
+        let op = NotifySavedSearchMatchesOperationMock {
+            saved_searches: vec![],
+            enabled_users: vec![],
+            search_results: vec![],
+            matched_consultants: vec![],
+            updated: Mutex::new(vec![]),
+            current_date_time,
+        };
+        // メールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            notify_saved_search_matches(current_date_time, URL_FOR_TEST, &op, &send_mail_mock)
+                .await;
+
+        let notify_result = result.expect("failed to get Ok");
+        assert_eq!(NotifyResult::default(), notify_result);
+    }
+
+    #[tokio::test]
+    async fn notify_saved_search_matches_success_first_check_records_baseline_without_mail() {
+        let current_date_time = create_current_date_time();
+        let op = NotifySavedSearchMatchesOperationMock {
+            saved_searches: vec![create_saved_search(1, 10, None, None)],
+            enabled_users: vec![(10, "test@test.com".to_string())],
+            search_results: vec![(1, vec![3, 2])],
+            matched_consultants: vec![],
+            updated: Mutex::new(vec![]),
+            current_date_time,
+        };
+        // メールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            notify_saved_search_matches(current_date_time, URL_FOR_TEST, &op, &send_mail_mock)
+                .await;
+
+        let notify_result = result.expect("failed to get Ok");
+        assert_eq!(
+            NotifyResult {
+                num_of_saved_searches: 1,
+                num_of_notified_users: 0
+            },
+            notify_result
+        );
+        assert_eq!(
+            vec![(1, vec![3, 2])],
+            *op.updated.lock().expect("failed to lock")
+        );
+    }
+
+    #[tokio::test]
+    async fn notify_saved_search_matches_success_notify_new_matches() {
+        let current_date_time = create_current_date_time();
+        let op = NotifySavedSearchMatchesOperationMock {
+            saved_searches: vec![
+                create_saved_search(1, 10, None, create_last_checked_at()),
+                create_saved_search(2, 10, Some(5000), create_last_checked_at()),
+            ],
+            enabled_users: vec![(10, "test@test.com".to_string())],
+            search_results: vec![(1, vec![4, 3, 2]), (2, vec![3])],
+            matched_consultants: vec![(1, vec![2, 1]), (2, vec![3])],
+            updated: Mutex::new(vec![]),
+            current_date_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            "test@test.com".to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            create_subject(),
+            vec![
+                "【検索条件ID: 1】新たに一致したコンサルタント 2名".to_string(),
+                format!("{}/consultant-detail/4", URL_FOR_TEST),
+                format!("{}/consultant-detail/3", URL_FOR_TEST),
+                format!(
+                    "{}/saved-search-alert-unsubscribe?token={:0>32}",
+                    URL_FOR_TEST, 1
+                ),
+            ],
+        );
+
+        let result =
+            notify_saved_search_matches(current_date_time, URL_FOR_TEST, &op, &send_mail_mock)
+                .await;
+
+        let notify_result = result.expect("failed to get Ok");
+        assert_eq!(
+            NotifyResult {
+                num_of_saved_searches: 2,
+                num_of_notified_users: 1
+            },
+            notify_result
+        );
+        assert_eq!(
+            vec![(1, vec![4, 3, 2]), (2, vec![3])],
+            *op.updated.lock().expect("failed to lock")
+        );
+    }
+
+    #[tokio::test]
+    async fn notify_saved_search_matches_success_skip_disabled_user() {
+        let current_date_time = create_current_date_time();
+        let op = NotifySavedSearchMatchesOperationMock {
+            saved_searches: vec![create_saved_search(1, 10, None, create_last_checked_at())],
+            enabled_users: vec![],
+            search_results: vec![(1, vec![4])],
+            matched_consultants: vec![],
+            updated: Mutex::new(vec![]),
+            current_date_time,
+        };
+        // メールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            notify_saved_search_matches(current_date_time, URL_FOR_TEST, &op, &send_mail_mock)
+                .await;
+
+        let notify_result = result.expect("failed to get Ok");
+        assert_eq!(
+            NotifyResult {
+                num_of_saved_searches: 1,
+                num_of_notified_users: 0
+            },
+            notify_result
+        );
+        assert!(op.updated.lock().expect("failed to lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn notify_saved_search_matches_fail_search_consultants() {
+        let current_date_time = create_current_date_time();
+        let op = NotifySavedSearchMatchesOperationMock {
+            saved_searches: vec![
+                create_saved_search(1, 10, None, create_last_checked_at()),
+                create_saved_search(2, 10, Some(5000), create_last_checked_at()),
+            ],
+            enabled_users: vec![(10, "test@test.com".to_string())],
+            search_results: vec![(1, vec![4])],
+            matched_consultants: vec![],
+            updated: Mutex::new(vec![]),
+            current_date_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            ADMIN_EMAIL_ADDRESS.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            format!(
+                "[{}] 定期実行ツール (notify_saved_search_matches) 失敗通知",
+                WEB_SITE_NAME
+            ),
+            vec!["10".to_string()],
+        );
+
+        let result =
+            notify_saved_search_matches(current_date_time, URL_FOR_TEST, &op, &send_mail_mock)
+                .await;
+
+        assert!(result.is_err());
+        // 一部の検索条件の実行に失敗した場合、次回の実行で改めて通知できるように記録は更新しない
+        assert!(op.updated.lock().expect("failed to lock").is_empty());
+    }
+
+    #[test]
+    fn create_text_limits_num_of_consultants_per_mail() {
+        let new_matches = vec![
+            NewMatches {
+                saved_search_id: 1,
+                unsubscribe_token: format!("{:0>32}", 1),
+                consultant_ids: (1..=(MAX_NUM_OF_NEW_CONSULTANTS_PER_MAIL as i64)).collect(),
+            },
+            NewMatches {
+                saved_search_id: 2,
+                unsubscribe_token: format!("{:0>32}", 2),
+                consultant_ids: vec![100, 101],
+            },
+        ];
+
+        let text = create_text(URL_FOR_TEST, &new_matches);
+
+        assert!(text.contains(&format!(
+            "{}/consultant-detail/{}",
+            URL_FOR_TEST, MAX_NUM_OF_NEW_CONSULTANTS_PER_MAIL
+        )));
+        assert!(!text.contains(&format!("{}/consultant-detail/100", URL_FOR_TEST)));
+        assert!(text.contains("ほか2名"));
+        assert!(text.contains(&format!(
+            "{}/saved-search-alert-unsubscribe?token={:0>32}",
+            URL_FOR_TEST, 2
+        )));
+    }
+
+    #[test]
+    fn parse_consultant_ids_returns_user_account_ids() {
+        let query_result = json!({
+            "hits": {
+                "hits": [
+                    { "_id": "5", "_source": { "user_account_id": 3 } },
+                    { "_id": "2", "_source": { "user_account_id": 1 } }
+                ]
+            }
+        });
+
+        let consultant_ids = parse_consultant_ids(&query_result).expect("failed to get Ok");
+
+        assert_eq!(vec![3, 1], consultant_ids);
+    }
+}
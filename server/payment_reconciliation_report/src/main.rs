@@ -0,0 +1,1101 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone};
+use dotenv::dotenv;
+use entity::sea_orm::{
+    prelude::async_trait::async_trait, sea_query::OnConflict, ConnectOptions, ConnectionTrait,
+    Database, DatabaseBackend, DatabaseConnection, EntityTrait, Set, Statement, Value,
+};
+use once_cell::sync::Lazy;
+use std::{
+    env::{set_var, var},
+    error::Error,
+    process::exit,
+};
+use tracing::{error, info};
+
+use common::{
+    admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD},
+    coupon::DISCOUNT_BORNE_BY_CONSULTANT,
+    db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT},
+    log::{init_log, LOG_LEVEL},
+    payment::WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS,
+    payment_event::{PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_AWAITING_WITHDRAWAL},
+    smtp::{
+        SendMail, SmtpClient, ADMIN_EMAIL_ADDRESS, AWS_SES_ACCESS_KEY_ID, AWS_SES_ENDPOINT_URI,
+        AWS_SES_REGION, AWS_SES_SECRET_ACCESS_KEY, KEY_TO_ADMIN_EMAIL_ADDRESS,
+        KEY_TO_AWS_SES_ENDPOINT_URI, KEY_TO_AWS_SES_REGION, KEY_TO_SYSTEM_EMAIL_ADDRESS,
+        SYSTEM_EMAIL_ADDRESS,
+    },
+    util::check_env_vars,
+    JAPANESE_TIME_ZONE, KEY_TO_USE_ECS_TASK_ROLE, LENGTH_OF_MEETING_IN_MINUTE, USE_ECS_TASK_ROLE,
+    WEB_SITE_NAME,
+};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const APPLICATION_ERR: i32 = 3;
+
+/// 指定した状態へ遷移した相談の件数と（割引後の）相談料の合計を、payment_eventから集計するSQL
+///
+/// 状態を遷移したあとの相談は別のテーブルに移動しているため、相談料は全ての状態のテーブルから探す。
+/// 状態の遷移中に集計した場合等、同じ相談が複数のテーブルに存在しても二重に計上しないように、相談ごとに一つに絞り込む。
+const SUMMARIZE_PAYMENT_EVENTS_SQL: &str = r"SELECT COUNT(*) AS count, COALESCE(SUM(f.fee_per_hour_in_yen - f.discount_in_yen), 0)::BIGINT AS amount_in_yen
+FROM ccs_schema.payment_event e
+LEFT JOIN (
+  SELECT DISTINCT ON (consultation_id) consultation_id, fee_per_hour_in_yen, discount_in_yen FROM (
+    SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.awaiting_payment
+    UNION ALL SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.awaiting_withdrawal
+    UNION ALL SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.left_awaiting_withdrawal
+    UNION ALL SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.neglected_payment
+    UNION ALL SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.receipt_of_consultation
+    UNION ALL SELECT consultation_id, fee_per_hour_in_yen, discount_in_yen FROM ccs_schema.refunded_payment
+  ) s ORDER BY consultation_id
+) f ON e.consultation_id = f.consultation_id
+WHERE e.to_state = $1 AND e.created_at >= $2 AND e.created_at < $3";
+
+const SUMMARIZE_PAYOUTS_SQL: &str = r"SELECT COUNT(*) AS count, COALESCE(SUM(reward), 0)::BIGINT AS amount_in_yen
+FROM ccs_schema.receipt_of_consultation WHERE created_at >= $1 AND created_at < $2";
+
+const SUMMARIZE_REFUNDS_SQL: &str = r"SELECT COUNT(*) AS count, COALESCE(SUM(fee_per_hour_in_yen - discount_in_yen), 0)::BIGINT AS amount_in_yen
+FROM ccs_schema.refunded_payment WHERE created_at >= $1 AND created_at < $2";
+
+const SUMMARIZE_NEGLECTED_PAYMENTS_SQL: &str = r"SELECT COUNT(*) AS count, COALESCE(SUM(fee_per_hour_in_yen - discount_in_yen), 0)::BIGINT AS amount_in_yen
+FROM ccs_schema.neglected_payment WHERE created_at >= $1 AND created_at < $2";
+
+/// 未出金の報酬を集計するSQL
+///
+/// 報酬は、admin_serviceで出金時に計算する値（calculate_reward）と同じ方法で計算する。
+/// つまり、割引前の相談料からプラットフォーム手数料（小数点以下切り捨て）と振込手数料を差し引き、割引額をコンサルタントが負担する場合はさらに割引額を差し引く（負となる場合は0とする）。
+const SUMMARIZE_UNPAID_REWARDS_SQL: &str = r"SELECT COUNT(*) AS count, COALESCE(SUM(
+  CASE WHEN discount_borne_by::TEXT = $4
+    THEN GREATEST(fee_per_hour_in_yen - TRUNC(fee_per_hour_in_yen * $2::NUMERIC / 100) - $3 - discount_in_yen, 0)
+    ELSE fee_per_hour_in_yen - TRUNC(fee_per_hour_in_yen * $2::NUMERIC / 100) - $3
+  END), 0)::BIGINT AS amount_in_yen
+FROM ccs_schema.awaiting_withdrawal WHERE meeting_at < $1";
+
+const KEY_TO_TRANSFER_FEE_IN_YEN: &str = "TRANSFER_FEE_IN_YEN";
+/// 報酬の出金時にかかる振込手数料。admin_serviceと同じ値を指定する
+static TRANSFER_FEE_IN_YEN: Lazy<i32> = Lazy::new(|| {
+    var(KEY_TO_TRANSFER_FEE_IN_YEN)
+        .unwrap_or_else(|_| {
+            panic!(
+                "Not environment variable found: environment variable \"{}\" (example value: \"300\") must be set",
+                KEY_TO_TRANSFER_FEE_IN_YEN
+            );
+        })
+        .parse()
+        .expect("failed to parse TRANSFER_FEE_IN_YEN")
+});
+
+const KEY_TO_PLATFORM_FEE_RATE_IN_PERCENTAGE: &str = "PLATFORM_FEE_RATE_IN_PERCENTAGE";
+/// 報酬の出金時に差し引くプラットフォーム手数料の割合（パーセント）。admin_serviceと同じ値を指定する
+static PLATFORM_FEE_RATE_IN_PERCENTAGE: Lazy<String> = Lazy::new(|| {
+    var(KEY_TO_PLATFORM_FEE_RATE_IN_PERCENTAGE).unwrap_or_else(|_| {
+        panic!(
+            "Not environment variable found: environment variable \"{}\" (example value: \"50.0\") must be set",
+            KEY_TO_PLATFORM_FEE_RATE_IN_PERCENTAGE
+        );
+    })
+});
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        KEY_TO_ADMIN_EMAIL_ADDRESS.to_string(),
+        KEY_TO_SYSTEM_EMAIL_ADDRESS.to_string(),
+        KEY_TO_AWS_SES_REGION.to_string(),
+        KEY_TO_AWS_SES_ENDPOINT_URI.to_string(),
+        KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+        KEY_TO_TRANSFER_FEE_IN_YEN.to_string(),
+        KEY_TO_PLATFORM_FEE_RATE_IN_PERCENTAGE.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    let log_conf = format!(
+        "payment_reconciliation_report={},common={},sea_orm={}",
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str()
+    );
+    set_var("RUST_LOG", log_conf);
+    init_log();
+
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let mut opt = ConnectOptions::new(database_url.clone());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let pool = Database::connect(opt).await.unwrap_or_else(|e| {
+        error!("failed to connect database: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let op = PaymentReconciliationReportOperationImpl { pool };
+
+    let smtp_client = if *USE_ECS_TASK_ROLE {
+        SmtpClient::new_with_ecs_task_role(AWS_SES_REGION.as_str(), AWS_SES_ENDPOINT_URI.as_str())
+            .await
+    } else {
+        SmtpClient::new(
+            AWS_SES_REGION.as_str(),
+            AWS_SES_ACCESS_KEY_ID.as_str(),
+            AWS_SES_SECRET_ACCESS_KEY.as_str(),
+            AWS_SES_ENDPOINT_URI.as_str(),
+        )
+        .await
+    };
+
+    let result = report_payment_reconciliation(current_date_time, &op, &smtp_client).await;
+
+    let report = result.unwrap_or_else(|e| {
+        error!("failed to report payment reconciliation: {}", e);
+        exit(APPLICATION_ERR)
+    });
+
+    info!(
+        "payment reconciliation report (report_date: {}) was created and sent successfully: {:?}",
+        report.report_date, report
+    );
+    exit(SUCCESS)
+}
+
+/// 件数と金額（円）の集計結果
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+struct Summary {
+    count: i64,
+    amount_in_yen: i64,
+}
+
+/// ある期間に発生した支払いに関する状態遷移の集計結果
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+struct PeriodSummary {
+    new_awaiting_payment: Summary,
+    confirmed_payment: Summary,
+    payout: Summary,
+    refund: Summary,
+    neglected_payment: Summary,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct PaymentReconciliationReport {
+    report_date: NaiveDate,
+    day: PeriodSummary,
+    month_to_date: PeriodSummary,
+    unpaid_reward: Summary,
+}
+
+async fn report_payment_reconciliation(
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl PaymentReconciliationReportOperation,
+    send_mail: &impl SendMail,
+) -> Result<PaymentReconciliationReport, Box<dyn Error>> {
+    let report_date = current_date_time.date_naive() - Duration::days(1);
+    let end = start_of_date_in_jst(current_date_time.date_naive())?;
+    let day_start = start_of_date_in_jst(report_date)?;
+    let month_start = start_of_date_in_jst(
+        report_date
+            .with_day(1)
+            .ok_or_else(|| format!("failed to get first day of month ({})", report_date))?,
+    )?;
+
+    let day = summarize_period(day_start, end, op).await?;
+    let month_to_date = summarize_period(month_start, end, op).await?;
+    let criteria = current_date_time
+        - Duration::days(WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS)
+        - Duration::minutes(LENGTH_OF_MEETING_IN_MINUTE as i64);
+    let unpaid_reward = op.summarize_unpaid_rewards(criteria).await?;
+
+    let report = PaymentReconciliationReport {
+        report_date,
+        day,
+        month_to_date,
+        unpaid_reward,
+    };
+
+    op.store_snapshot(&report, current_date_time).await?;
+
+    let subject = format!(
+        "[{}] 定期実行ツール (payment_reconciliation_report) 入出金レポート ({})",
+        WEB_SITE_NAME, report_date
+    );
+    let text = create_text(&report, current_date_time);
+    send_mail
+        .send_mail(
+            ADMIN_EMAIL_ADDRESS.as_str(),
+            SYSTEM_EMAIL_ADDRESS.as_str(),
+            subject.as_str(),
+            text.as_str(),
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "failed to send mail (status code: {}, response body: {:?}): {:?}",
+                e.0, e.1, report
+            )
+        })?;
+
+    Ok(report)
+}
+
+fn start_of_date_in_jst(date: NaiveDate) -> Result<DateTime<FixedOffset>, Box<dyn Error>> {
+    let naive_date_time = date
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| format!("failed to create start of date ({})", date))?;
+    let date_time = JAPANESE_TIME_ZONE
+        .from_local_datetime(&naive_date_time)
+        .single()
+        .ok_or_else(|| {
+            format!(
+                "failed to convert to date time in JST ({})",
+                naive_date_time
+            )
+        })?;
+    Ok(date_time)
+}
+
+async fn summarize_period(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    op: &impl PaymentReconciliationReportOperation,
+) -> Result<PeriodSummary, Box<dyn Error>> {
+    Ok(PeriodSummary {
+        new_awaiting_payment: op.summarize_new_awaiting_payments(start, end).await?,
+        confirmed_payment: op.summarize_confirmed_payments(start, end).await?,
+        payout: op.summarize_payouts(start, end).await?,
+        refund: op.summarize_refunds(start, end).await?,
+        neglected_payment: op.summarize_neglected_payments(start, end).await?,
+    })
+}
+
+/// 期間を受け取るメソッドは、start以上end未満の期間を対象とする
+#[async_trait]
+trait PaymentReconciliationReportOperation {
+    /// 相談申し込みが承認され、入金待ちとなったものを集計する（金額は割引後の相談料）
+    async fn summarize_new_awaiting_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    /// 入金が確認され、出金待ちとなったものを集計する（金額は割引後の相談料）
+    async fn summarize_confirmed_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    /// コンサルタントへ出金したもの（receipt_of_consultation）を集計する（金額は報酬）
+    async fn summarize_payouts(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    /// 返金したもの（refunded_payment）を集計する（金額は割引後の相談料）
+    async fn summarize_refunds(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    /// 入金がなかったもの（neglected_payment）を集計する（金額は割引後の相談料）
+    async fn summarize_neglected_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    /// 相談日時がcriteriaより前で、まだ出金されていないもの（awaiting_withdrawal）を集計する（金額は報酬）
+    async fn summarize_unpaid_rewards(
+        &self,
+        criteria: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>>;
+
+    async fn store_snapshot(
+        &self,
+        report: &PaymentReconciliationReport,
+        created_at: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+struct PaymentReconciliationReportOperationImpl {
+    pool: DatabaseConnection,
+}
+
+impl PaymentReconciliationReportOperationImpl {
+    async fn summarize(&self, sql: &str, values: Vec<Value>) -> Result<Summary, Box<dyn Error>> {
+        let stmt = Statement::from_sql_and_values(DatabaseBackend::Postgres, sql, values.clone());
+        let row = self
+            .pool
+            .query_one(stmt)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to summarize (sql: {}, values: {:?}): {}",
+                    sql, values, e
+                )
+            })?
+            .ok_or_else(|| format!("no result found (sql: {}, values: {:?})", sql, values))?;
+        let count: i64 = row
+            .try_get("", "count")
+            .map_err(|e| format!("failed to get count: {}", e))?;
+        let amount_in_yen: i64 = row
+            .try_get("", "amount_in_yen")
+            .map_err(|e| format!("failed to get amount_in_yen: {}", e))?;
+        Ok(Summary {
+            count,
+            amount_in_yen,
+        })
+    }
+}
+
+#[async_trait]
+impl PaymentReconciliationReportOperation for PaymentReconciliationReportOperationImpl {
+    async fn summarize_new_awaiting_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(
+            SUMMARIZE_PAYMENT_EVENTS_SQL,
+            vec![
+                PAYMENT_STATE_AWAITING_PAYMENT.into(),
+                start.into(),
+                end.into(),
+            ],
+        )
+        .await
+    }
+
+    async fn summarize_confirmed_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(
+            SUMMARIZE_PAYMENT_EVENTS_SQL,
+            vec![
+                PAYMENT_STATE_AWAITING_WITHDRAWAL.into(),
+                start.into(),
+                end.into(),
+            ],
+        )
+        .await
+    }
+
+    async fn summarize_payouts(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(SUMMARIZE_PAYOUTS_SQL, vec![start.into(), end.into()])
+            .await
+    }
+
+    async fn summarize_refunds(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(SUMMARIZE_REFUNDS_SQL, vec![start.into(), end.into()])
+            .await
+    }
+
+    async fn summarize_neglected_payments(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(
+            SUMMARIZE_NEGLECTED_PAYMENTS_SQL,
+            vec![start.into(), end.into()],
+        )
+        .await
+    }
+
+    async fn summarize_unpaid_rewards(
+        &self,
+        criteria: DateTime<FixedOffset>,
+    ) -> Result<Summary, Box<dyn Error>> {
+        self.summarize(
+            SUMMARIZE_UNPAID_REWARDS_SQL,
+            vec![
+                criteria.into(),
+                PLATFORM_FEE_RATE_IN_PERCENTAGE.as_str().into(),
+                (*TRANSFER_FEE_IN_YEN).into(),
+                DISCOUNT_BORNE_BY_CONSULTANT.into(),
+            ],
+        )
+        .await
+    }
+
+    async fn store_snapshot(
+        &self,
+        report: &PaymentReconciliationReport,
+        created_at: DateTime<FixedOffset>,
+    ) -> Result<(), Box<dyn Error>> {
+        use entity::payment_reconciliation_snapshot::{ActiveModel, Column, Entity};
+        let active_model = ActiveModel {
+            report_date: Set(report.report_date),
+            day_new_awaiting_payment_count: Set(report.day.new_awaiting_payment.count),
+            day_new_awaiting_payment_amount_in_yen: Set(report
+                .day
+                .new_awaiting_payment
+                .amount_in_yen),
+            day_confirmed_payment_count: Set(report.day.confirmed_payment.count),
+            day_confirmed_payment_amount_in_yen: Set(report.day.confirmed_payment.amount_in_yen),
+            day_payout_count: Set(report.day.payout.count),
+            day_payout_amount_in_yen: Set(report.day.payout.amount_in_yen),
+            day_refund_count: Set(report.day.refund.count),
+            day_refund_amount_in_yen: Set(report.day.refund.amount_in_yen),
+            day_neglected_payment_count: Set(report.day.neglected_payment.count),
+            day_neglected_payment_amount_in_yen: Set(report.day.neglected_payment.amount_in_yen),
+            month_to_date_new_awaiting_payment_count: Set(report
+                .month_to_date
+                .new_awaiting_payment
+                .count),
+            month_to_date_new_awaiting_payment_amount_in_yen: Set(report
+                .month_to_date
+                .new_awaiting_payment
+                .amount_in_yen),
+            month_to_date_confirmed_payment_count: Set(report
+                .month_to_date
+                .confirmed_payment
+                .count),
+            month_to_date_confirmed_payment_amount_in_yen: Set(report
+                .month_to_date
+                .confirmed_payment
+                .amount_in_yen),
+            month_to_date_payout_count: Set(report.month_to_date.payout.count),
+            month_to_date_payout_amount_in_yen: Set(report.month_to_date.payout.amount_in_yen),
+            month_to_date_refund_count: Set(report.month_to_date.refund.count),
+            month_to_date_refund_amount_in_yen: Set(report.month_to_date.refund.amount_in_yen),
+            month_to_date_neglected_payment_count: Set(report
+                .month_to_date
+                .neglected_payment
+                .count),
+            month_to_date_neglected_payment_amount_in_yen: Set(report
+                .month_to_date
+                .neglected_payment
+                .amount_in_yen),
+            unpaid_reward_count: Set(report.unpaid_reward.count),
+            unpaid_reward_amount_in_yen: Set(report.unpaid_reward.amount_in_yen),
+            created_at: Set(created_at),
+        };
+        // 同じ対象日で再実行された場合（メール送信に失敗した場合等）、最新の集計結果で上書きする
+        let on_conflict = OnConflict::column(Column::ReportDate)
+            .update_columns([
+                Column::DayNewAwaitingPaymentCount,
+                Column::DayNewAwaitingPaymentAmountInYen,
+                Column::DayConfirmedPaymentCount,
+                Column::DayConfirmedPaymentAmountInYen,
+                Column::DayPayoutCount,
+                Column::DayPayoutAmountInYen,
+                Column::DayRefundCount,
+                Column::DayRefundAmountInYen,
+                Column::DayNeglectedPaymentCount,
+                Column::DayNeglectedPaymentAmountInYen,
+                Column::MonthToDateNewAwaitingPaymentCount,
+                Column::MonthToDateNewAwaitingPaymentAmountInYen,
+                Column::MonthToDateConfirmedPaymentCount,
+                Column::MonthToDateConfirmedPaymentAmountInYen,
+                Column::MonthToDatePayoutCount,
+                Column::MonthToDatePayoutAmountInYen,
+                Column::MonthToDateRefundCount,
+                Column::MonthToDateRefundAmountInYen,
+                Column::MonthToDateNeglectedPaymentCount,
+                Column::MonthToDateNeglectedPaymentAmountInYen,
+                Column::UnpaidRewardCount,
+                Column::UnpaidRewardAmountInYen,
+                Column::CreatedAt,
+            ])
+            .to_owned();
+        let _ = Entity::insert(active_model)
+            .on_conflict(on_conflict)
+            .exec(&self.pool)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to insert payment_reconciliation_snapshot (report: {:?}, created_at: {}): {}",
+                    report, created_at, e
+                )
+            })?;
+        Ok(())
+    }
+}
+
+fn create_text(
+    report: &PaymentReconciliationReport,
+    current_date_time: DateTime<FixedOffset>,
+) -> String {
+    format!(
+        r"{}の入出金レポートです。
+
+【{}の集計】
+{}
+
+【{}月1日から{}までの集計】
+{}
+
+【{}時点で出金待ちの期間を過ぎている未出金の相談】
+{}件（報酬合計 {}円）
+
+※報酬の出金と未出金の相談は報酬の合計、それ以外は割引後の相談料の合計を記載しています。",
+        report.report_date,
+        report.report_date,
+        create_period_text(&report.day),
+        report.report_date.month(),
+        report.report_date,
+        create_period_text(&report.month_to_date),
+        current_date_time.format("%Y-%m-%d %H:%M"),
+        report.unpaid_reward.count,
+        report.unpaid_reward.amount_in_yen
+    )
+}
+
+fn create_period_text(period_summary: &PeriodSummary) -> String {
+    format!(
+        r"新規入金待ち: {}件（{}円）
+入金確認: {}件（{}円）
+報酬の出金: {}件（{}円）
+返金: {}件（{}円）
+入金なし: {}件（{}円）",
+        period_summary.new_awaiting_payment.count,
+        period_summary.new_awaiting_payment.amount_in_yen,
+        period_summary.confirmed_payment.count,
+        period_summary.confirmed_payment.amount_in_yen,
+        period_summary.payout.count,
+        period_summary.payout.amount_in_yen,
+        period_summary.refund.count,
+        period_summary.refund.amount_in_yen,
+        period_summary.neglected_payment.count,
+        period_summary.neglected_payment.amount_in_yen
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+    use common::ErrResp;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+    enum Kind {
+        NewAwaitingPayment,
+        ConfirmedPayment,
+        Payout,
+        Refund,
+        NeglectedPayment,
+    }
+
+    /// 集計対象のレコード（種類、状態遷移した日時、金額）
+    #[derive(Clone, Debug)]
+    struct Record {
+        kind: Kind,
+        created_at: DateTime<FixedOffset>,
+        amount_in_yen: i64,
+    }
+
+    /// 出金待ちのレコード（相談日時、金額）
+    #[derive(Clone, Debug)]
+    struct AwaitingWithdrawal {
+        meeting_at: DateTime<FixedOffset>,
+        amount_in_yen: i64,
+    }
+
+    struct PaymentReconciliationReportOperationMock {
+        current_date_time: DateTime<FixedOffset>,
+        records: Vec<Record>,
+        awaiting_withdrawals: Vec<AwaitingWithdrawal>,
+        fail_to_store_snapshot: bool,
+    }
+
+    impl PaymentReconciliationReportOperationMock {
+        fn summarize(
+            &self,
+            kind: Kind,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Summary {
+            let today = JAPANESE_TIME_ZONE
+                .from_local_datetime(
+                    &self
+                        .current_date_time
+                        .date_naive()
+                        .and_hms_opt(0, 0, 0)
+                        .expect("failed to get Ok"),
+                )
+                .unwrap();
+            assert_eq!(today, end);
+            assert!(start < end);
+            self.records
+                .iter()
+                .filter(|r| r.kind == kind && start <= r.created_at && r.created_at < end)
+                .fold(Summary::default(), |acc, r| Summary {
+                    count: acc.count + 1,
+                    amount_in_yen: acc.amount_in_yen + r.amount_in_yen,
+                })
+        }
+    }
+
+    #[async_trait]
+    impl PaymentReconciliationReportOperation for PaymentReconciliationReportOperationMock {
+        async fn summarize_new_awaiting_payments(
+            &self,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            Ok(self.summarize(Kind::NewAwaitingPayment, start, end))
+        }
+
+        async fn summarize_confirmed_payments(
+            &self,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            Ok(self.summarize(Kind::ConfirmedPayment, start, end))
+        }
+
+        async fn summarize_payouts(
+            &self,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            Ok(self.summarize(Kind::Payout, start, end))
+        }
+
+        async fn summarize_refunds(
+            &self,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            Ok(self.summarize(Kind::Refund, start, end))
+        }
+
+        async fn summarize_neglected_payments(
+            &self,
+            start: DateTime<FixedOffset>,
+            end: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            Ok(self.summarize(Kind::NeglectedPayment, start, end))
+        }
+
+        async fn summarize_unpaid_rewards(
+            &self,
+            criteria: DateTime<FixedOffset>,
+        ) -> Result<Summary, Box<dyn Error>> {
+            assert_eq!(
+                self.current_date_time
+                    - Duration::days(WAITING_PERIOD_BEFORE_WITHDRAWAL_TO_CONSULTANT_IN_DAYS)
+                    - Duration::minutes(LENGTH_OF_MEETING_IN_MINUTE as i64),
+                criteria
+            );
+            Ok(self
+                .awaiting_withdrawals
+                .iter()
+                .filter(|aw| aw.meeting_at < criteria)
+                .fold(Summary::default(), |acc, aw| Summary {
+                    count: acc.count + 1,
+                    amount_in_yen: acc.amount_in_yen + aw.amount_in_yen,
+                }))
+        }
+
+        async fn store_snapshot(
+            &self,
+            report: &PaymentReconciliationReport,
+            created_at: DateTime<FixedOffset>,
+        ) -> Result<(), Box<dyn Error>> {
+            assert_eq!(
+                self.current_date_time.date_naive() - Duration::days(1),
+                report.report_date
+            );
+            assert_eq!(self.current_date_time, created_at);
+            if self.fail_to_store_snapshot {
+                return Err("mock error message".into());
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct SendMailMock {
+        to: String,
+        from: String,
+        subject: String,
+        text_keywords: Vec<String>,
+    }
+
+    #[async_trait]
+    impl SendMail for SendMailMock {
+        async fn send_mail(
+            &self,
+            to: &str,
+            from: &str,
+            subject: &str,
+            text: &str,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.to, to);
+            assert_eq!(self.from, from);
+            assert_eq!(self.subject, subject);
+            for text_keyword in self.text_keywords.clone() {
+                assert!(text.contains(&text_keyword));
+            }
+            Ok(())
+        }
+    }
+
+    fn create_subject(report_date: &str) -> String {
+        format!(
+            "[{}] 定期実行ツール (payment_reconciliation_report) 入出金レポート ({})",
+            WEB_SITE_NAME, report_date
+        )
+    }
+
+    fn create_dummy_records() -> Vec<Record> {
+        vec![
+            // 前月分のため集計対象外
+            Record {
+                kind: Kind::NewAwaitingPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 8, 31, 23, 59, 59)
+                    .unwrap(),
+                amount_in_yen: 3000,
+            },
+            Record {
+                kind: Kind::NewAwaitingPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 1, 0, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 4000,
+            },
+            Record {
+                kind: Kind::NewAwaitingPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 14, 0, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 5000,
+            },
+            Record {
+                kind: Kind::NewAwaitingPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 14, 23, 59, 59)
+                    .unwrap(),
+                amount_in_yen: 6000,
+            },
+            Record {
+                kind: Kind::ConfirmedPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 10, 12, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 4000,
+            },
+            Record {
+                kind: Kind::ConfirmedPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 14, 12, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 5000,
+            },
+            Record {
+                kind: Kind::Payout,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 14, 15, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 2500,
+            },
+            Record {
+                kind: Kind::Refund,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 5, 15, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 7000,
+            },
+            Record {
+                kind: Kind::NeglectedPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 14, 18, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 3500,
+            },
+            // 当日分のため集計対象外
+            Record {
+                kind: Kind::NeglectedPayment,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 15, 0, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 3500,
+            },
+        ]
+    }
+
+    fn create_dummy_awaiting_withdrawals() -> Vec<AwaitingWithdrawal> {
+        vec![
+            AwaitingWithdrawal {
+                meeting_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 1, 21, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 4000,
+            },
+            // 出金待ちの期間を過ぎていないため集計対象外
+            AwaitingWithdrawal {
+                meeting_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 9, 10, 21, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 5000,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn report_payment_reconciliation_success_no_record() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 0, 30, 0)
+            .unwrap();
+        let op = PaymentReconciliationReportOperationMock {
+            current_date_time,
+            records: vec![],
+            awaiting_withdrawals: vec![],
+            fail_to_store_snapshot: false,
+        };
+        let send_mail_mock = SendMailMock {
+            to: ADMIN_EMAIL_ADDRESS.to_string(),
+            from: SYSTEM_EMAIL_ADDRESS.to_string(),
+            subject: create_subject("2023-09-14"),
+            text_keywords: vec!["新規入金待ち: 0件（0円）".to_string()],
+        };
+
+        let result = report_payment_reconciliation(current_date_time, &op, &send_mail_mock).await;
+
+        let report = result.expect("failed to get Ok");
+        assert_eq!(
+            PaymentReconciliationReport {
+                report_date: NaiveDate::from_ymd_opt(2023, 9, 14).expect("failed to get Ok"),
+                day: PeriodSummary::default(),
+                month_to_date: PeriodSummary::default(),
+                unpaid_reward: Summary::default(),
+            },
+            report
+        );
+    }
+
+    #[tokio::test]
+    async fn report_payment_reconciliation_success_some_records() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 0, 30, 0)
+            .unwrap();
+        let op = PaymentReconciliationReportOperationMock {
+            current_date_time,
+            records: create_dummy_records(),
+            awaiting_withdrawals: create_dummy_awaiting_withdrawals(),
+            fail_to_store_snapshot: false,
+        };
+        let send_mail_mock = SendMailMock {
+            to: ADMIN_EMAIL_ADDRESS.to_string(),
+            from: SYSTEM_EMAIL_ADDRESS.to_string(),
+            subject: create_subject("2023-09-14"),
+            text_keywords: vec![
+                "【2023-09-14の集計】".to_string(),
+                "新規入金待ち: 2件（11000円）".to_string(),
+                "【9月1日から2023-09-14までの集計】".to_string(),
+                "新規入金待ち: 3件（15000円）".to_string(),
+                "返金: 1件（7000円）".to_string(),
+                "1件（報酬合計 4000円）".to_string(),
+            ],
+        };
+
+        let result = report_payment_reconciliation(current_date_time, &op, &send_mail_mock).await;
+
+        let report = result.expect("failed to get Ok");
+        assert_eq!(
+            PaymentReconciliationReport {
+                report_date: NaiveDate::from_ymd_opt(2023, 9, 14).expect("failed to get Ok"),
+                day: PeriodSummary {
+                    new_awaiting_payment: Summary {
+                        count: 2,
+                        amount_in_yen: 11000
+                    },
+                    confirmed_payment: Summary {
+                        count: 1,
+                        amount_in_yen: 5000
+                    },
+                    payout: Summary {
+                        count: 1,
+                        amount_in_yen: 2500
+                    },
+                    refund: Summary::default(),
+                    neglected_payment: Summary {
+                        count: 1,
+                        amount_in_yen: 3500
+                    },
+                },
+                month_to_date: PeriodSummary {
+                    new_awaiting_payment: Summary {
+                        count: 3,
+                        amount_in_yen: 15000
+                    },
+                    confirmed_payment: Summary {
+                        count: 2,
+                        amount_in_yen: 9000
+                    },
+                    payout: Summary {
+                        count: 1,
+                        amount_in_yen: 2500
+                    },
+                    refund: Summary {
+                        count: 1,
+                        amount_in_yen: 7000
+                    },
+                    neglected_payment: Summary {
+                        count: 1,
+                        amount_in_yen: 3500
+                    },
+                },
+                unpaid_reward: Summary {
+                    count: 1,
+                    amount_in_yen: 4000
+                },
+            },
+            report
+        );
+    }
+
+    #[tokio::test]
+    async fn report_payment_reconciliation_success_first_day_of_month() {
+        // 対象日が月初の場合、月初からの集計は対象日の集計と一致する
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 2, 0, 30, 0)
+            .unwrap();
+        let op = PaymentReconciliationReportOperationMock {
+            current_date_time,
+            records: create_dummy_records(),
+            awaiting_withdrawals: vec![],
+            fail_to_store_snapshot: false,
+        };
+        let send_mail_mock = SendMailMock {
+            to: ADMIN_EMAIL_ADDRESS.to_string(),
+            from: SYSTEM_EMAIL_ADDRESS.to_string(),
+            subject: create_subject("2023-09-01"),
+            text_keywords: vec!["新規入金待ち: 1件（4000円）".to_string()],
+        };
+
+        let result = report_payment_reconciliation(current_date_time, &op, &send_mail_mock).await;
+
+        let report = result.expect("failed to get Ok");
+        let expected = PeriodSummary {
+            new_awaiting_payment: Summary {
+                count: 1,
+                amount_in_yen: 4000,
+            },
+            ..Default::default()
+        };
+        assert_eq!(expected, report.day);
+        assert_eq!(expected, report.month_to_date);
+    }
+
+    #[tokio::test]
+    async fn report_payment_reconciliation_success_first_day_of_year() {
+        // 対象日が前年の大晦日の場合、前年の12月の集計となる
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2024, 1, 1, 0, 30, 0)
+            .unwrap();
+        let records = vec![
+            Record {
+                kind: Kind::Payout,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 12, 1, 10, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 2500,
+            },
+            Record {
+                kind: Kind::Payout,
+                created_at: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2023, 12, 31, 10, 0, 0)
+                    .unwrap(),
+                amount_in_yen: 3000,
+            },
+        ];
+        let op = PaymentReconciliationReportOperationMock {
+            current_date_time,
+            records,
+            awaiting_withdrawals: vec![],
+            fail_to_store_snapshot: false,
+        };
+        let send_mail_mock = SendMailMock {
+            to: ADMIN_EMAIL_ADDRESS.to_string(),
+            from: SYSTEM_EMAIL_ADDRESS.to_string(),
+            subject: create_subject("2023-12-31"),
+            text_keywords: vec!["【12月1日から2023-12-31までの集計】".to_string()],
+        };
+
+        let result = report_payment_reconciliation(current_date_time, &op, &send_mail_mock).await;
+
+        let report = result.expect("failed to get Ok");
+        assert_eq!(
+            Summary {
+                count: 1,
+                amount_in_yen: 3000
+            },
+            report.day.payout
+        );
+        assert_eq!(
+            Summary {
+                count: 2,
+                amount_in_yen: 5500
+            },
+            report.month_to_date.payout
+        );
+    }
+
+    #[tokio::test]
+    async fn report_payment_reconciliation_fail_store_snapshot() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 0, 30, 0)
+            .unwrap();
+        let op = PaymentReconciliationReportOperationMock {
+            current_date_time,
+            records: create_dummy_records(),
+            awaiting_withdrawals: create_dummy_awaiting_withdrawals(),
+            fail_to_store_snapshot: true,
+        };
+        // スナップショットの保存に失敗した場合はメールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock = SendMailMock {
+            to: "".to_string(),
+            from: "".to_string(),
+            subject: "".to_string(),
+            text_keywords: vec![],
+        };
+
+        let result = report_payment_reconciliation(current_date_time, &op, &send_mail_mock).await;
+
+        let err = result.expect_err("failed to get Err");
+        assert_eq!("mock error message", err.to_string());
+    }
+}
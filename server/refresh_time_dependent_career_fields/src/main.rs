@@ -0,0 +1,935 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use dotenv::dotenv;
+use entity::sea_orm::{
+    prelude::async_trait::async_trait, ColumnTrait, ConnectOptions, Database, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use opensearch::OpenSearch;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashMap,
+    env::{set_var, var},
+    error::Error,
+    process::exit,
+};
+use tracing::{error, info};
+
+use common::{
+    admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD},
+    db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT},
+    log::{init_log, LOG_LEVEL},
+    opensearch::{
+        bulk_update_documents, calculate_time_dependent_career_fields, create_client,
+        search_documents, TimeDependentCareerFields, INDEX_NAME, KEY_TO_OPENSEARCH_AUTH,
+        KEY_TO_OPENSEARCH_ENDPOINT_URI, KEY_TO_OPENSEARCH_PASSWORD, KEY_TO_OPENSEARCH_USERNAME,
+    },
+    smtp::{
+        SendMail, SmtpClient, ADMIN_EMAIL_ADDRESS, AWS_SES_ACCESS_KEY_ID, AWS_SES_ENDPOINT_URI,
+        AWS_SES_REGION, AWS_SES_SECRET_ACCESS_KEY, KEY_TO_ADMIN_EMAIL_ADDRESS,
+        KEY_TO_AWS_SES_ENDPOINT_URI, KEY_TO_AWS_SES_REGION, KEY_TO_SYSTEM_EMAIL_ADDRESS,
+        SYSTEM_EMAIL_ADDRESS,
+    },
+    util::check_env_vars,
+    JAPANESE_TIME_ZONE, KEY_TO_USE_ECS_TASK_ROLE, USE_ECS_TASK_ROLE, WEB_SITE_NAME,
+};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const APPLICATION_ERR: i32 = 3;
+
+/// 一度に処理する（DBから取得し、インデックスと比較、更新する）ドキュメントの数
+const NUM_OF_DOCUMENTS_PER_CHUNK: u64 = 100;
+
+/// params.careersに含まれる職務経歴の在籍年数と在籍中かどうかを更新するスクリプト
+///
+/// params.careersは、career_idの文字列をキー、更新後の値を値とするマップ
+const REFRESH_CAREERS_SCRIPT: &str = "for (career in ctx._source.careers) { String key = String.valueOf(career.career_id); if (params.careers.containsKey(key)) { career.years_of_service = params.careers[key].years_of_service; career.employed = params.careers[key].employed; } }";
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        KEY_TO_OPENSEARCH_ENDPOINT_URI.to_string(),
+        KEY_TO_OPENSEARCH_AUTH.to_string(),
+        KEY_TO_OPENSEARCH_USERNAME.to_string(),
+        KEY_TO_OPENSEARCH_PASSWORD.to_string(),
+        KEY_TO_ADMIN_EMAIL_ADDRESS.to_string(),
+        KEY_TO_SYSTEM_EMAIL_ADDRESS.to_string(),
+        KEY_TO_AWS_SES_REGION.to_string(),
+        KEY_TO_AWS_SES_ENDPOINT_URI.to_string(),
+        KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    let log_conf = format!(
+        "refresh_time_dependent_career_fields={},common={},sea_orm={}",
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str(),
+        LOG_LEVEL.as_str()
+    );
+    set_var("RUST_LOG", log_conf);
+    init_log();
+
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let mut opt = ConnectOptions::new(database_url.clone());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let pool = Database::connect(opt).await.unwrap_or_else(|e| {
+        error!("failed to connect database: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let index_client = create_index_client().unwrap_or_else(|e| {
+        error!("failed to create OpenSearch client: {}", e);
+        exit(CONNECTION_ERROR)
+    });
+
+    let op = RefreshTimeDependentCareerFieldsOperationImpl { pool, index_client };
+
+    let smtp_client = if *USE_ECS_TASK_ROLE {
+        SmtpClient::new_with_ecs_task_role(AWS_SES_REGION.as_str(), AWS_SES_ENDPOINT_URI.as_str())
+            .await
+    } else {
+        SmtpClient::new(
+            AWS_SES_REGION.as_str(),
+            AWS_SES_ACCESS_KEY_ID.as_str(),
+            AWS_SES_SECRET_ACCESS_KEY.as_str(),
+            AWS_SES_ENDPOINT_URI.as_str(),
+        )
+        .await
+    };
+
+    let result = refresh_time_dependent_career_fields(current_date_time, &op, &smtp_client).await;
+
+    let refresh_result = result.unwrap_or_else(|e| {
+        error!("failed to refresh time dependent career fields: {}", e);
+        exit(APPLICATION_ERR)
+    });
+
+    info!(
+        "{} document(s) were (was) checked, {} document(s) ({} career(s)) drifted and were (was) refreshed successfully",
+        refresh_result.num_of_documents,
+        refresh_result.num_of_drifted_documents,
+        refresh_result.num_of_drifted_careers
+    );
+    exit(SUCCESS)
+}
+
+fn create_index_client() -> Result<OpenSearch, Box<dyn Error>> {
+    let opensearch_url = var(KEY_TO_OPENSEARCH_ENDPOINT_URI)?;
+    let opensearch_auth = var(KEY_TO_OPENSEARCH_AUTH)?.parse::<bool>()?;
+    let opensearch_username = var(KEY_TO_OPENSEARCH_USERNAME)?;
+    let opensearch_password = var(KEY_TO_OPENSEARCH_PASSWORD)?;
+    let index_client = create_client(
+        opensearch_url.as_str(),
+        opensearch_auth,
+        opensearch_username.as_str(),
+        opensearch_password.as_str(),
+    )?;
+    Ok(index_client)
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+struct RefreshResult {
+    num_of_documents: usize,
+    /// インデックスに格納されている値が、DBから計算した値と異なっていたドキュメントの数
+    num_of_drifted_documents: usize,
+    /// インデックスに格納されている値が、DBから計算した値と異なっていた職務経歴の数
+    num_of_drifted_careers: usize,
+}
+
+async fn refresh_time_dependent_career_fields(
+    current_date_time: DateTime<FixedOffset>,
+    op: &impl RefreshTimeDependentCareerFieldsOperation,
+    send_mail: &impl SendMail,
+) -> Result<RefreshResult, Box<dyn Error>> {
+    let current_date = current_date_time.date_naive();
+    let mut refresh_result = RefreshResult::default();
+    let mut refresh_failed: Vec<Document> = Vec::new();
+    let mut last_user_account_id = None;
+
+    loop {
+        let documents = op
+            .get_documents(last_user_account_id, NUM_OF_DOCUMENTS_PER_CHUNK)
+            .await?;
+        let Some(last_document) = documents.last() else {
+            break;
+        };
+        last_user_account_id = Some(last_document.user_account_id);
+        refresh_result.num_of_documents += documents.len();
+
+        let user_account_ids: Vec<i64> = documents.iter().map(|d| d.user_account_id).collect();
+        let careers = op.get_careers(&user_account_ids).await?;
+        let document_ids: Vec<i64> = documents.iter().map(|d| d.document_id).collect();
+        let indexed_careers = op.get_indexed_careers(&document_ids).await?;
+
+        let mut document_updates = Vec::with_capacity(documents.len());
+        for document in documents.iter() {
+            let expected: Vec<CareerFields> = careers
+                .iter()
+                .filter(|c| c.user_account_id == document.user_account_id)
+                .map(|c| CareerFields {
+                    career_id: c.career_id,
+                    fields: calculate_time_dependent_career_fields(
+                        c.career_start_date,
+                        c.career_end_date,
+                        current_date,
+                    ),
+                })
+                .collect();
+            let indexed = indexed_careers
+                .get(&document.document_id)
+                .map(|v| v.as_slice())
+                .unwrap_or_default();
+            let drifted_careers = find_drifted_careers(&expected, indexed);
+            if !drifted_careers.is_empty() {
+                refresh_result.num_of_drifted_documents += 1;
+                refresh_result.num_of_drifted_careers += drifted_careers.len();
+                document_updates.push((document.clone(), drifted_careers));
+            }
+        }
+
+        if !document_updates.is_empty() {
+            info!("refresh drifted documents: {:?}", document_updates);
+            let result = op.refresh_documents(&document_updates).await;
+            if result.is_err() {
+                error!("failed refresh_documents: {:?}", result);
+                refresh_failed.extend(document_updates.into_iter().map(|d| d.0));
+            }
+            op.wait_for_next_iteration().await;
+        }
+    }
+
+    if !refresh_failed.is_empty() {
+        let subject = format!(
+            "[{}] 定期実行ツール (refresh_time_dependent_career_fields) 失敗通知",
+            WEB_SITE_NAME
+        );
+        let num_of_refresh_failed = refresh_failed.len();
+        let text = create_text(
+            refresh_result.num_of_drifted_documents,
+            num_of_refresh_failed,
+            &refresh_failed,
+        );
+        let err_message = format!(
+            "{} drifted, {} failed (detail: {:?})",
+            refresh_result.num_of_drifted_documents, num_of_refresh_failed, refresh_failed
+        );
+        send_mail
+            .send_mail(
+                ADMIN_EMAIL_ADDRESS.as_str(),
+                SYSTEM_EMAIL_ADDRESS.as_str(),
+                subject.as_str(),
+                text.as_str(),
+            )
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to send mail (status code: {}, response body: {:?}): {}",
+                    e.0, e.1, err_message
+                )
+            })?;
+        return Err(err_message.into());
+    }
+
+    Ok(refresh_result)
+}
+
+/// expectedのうち、indexedと値が異なるもの（またはindexedに存在しないもの）を返す
+///
+/// indexedにのみ存在する職務経歴は、職務経歴の削除時にドキュメントから取り除かれるため、この処理の対象としない
+fn find_drifted_careers(expected: &[CareerFields], indexed: &[CareerFields]) -> Vec<CareerFields> {
+    expected
+        .iter()
+        .filter(|e| {
+            !indexed
+                .iter()
+                .any(|i| i.career_id == e.career_id && i.fields == e.fields)
+        })
+        .cloned()
+        .collect()
+}
+
+#[async_trait]
+trait RefreshTimeDependentCareerFieldsOperation {
+    /// user_account_idがlast_user_account_idより大きいドキュメントを、user_account_idの昇順にlimit件取得する
+    async fn get_documents(
+        &self,
+        last_user_account_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<Document>, Box<dyn Error>>;
+
+    async fn get_careers(&self, user_account_ids: &[i64]) -> Result<Vec<Career>, Box<dyn Error>>;
+
+    /// インデックスに格納されている職務経歴の在籍年数と在籍中かどうかを、ドキュメントIDごとに取得する
+    async fn get_indexed_careers(
+        &self,
+        document_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<CareerFields>>, Box<dyn Error>>;
+
+    async fn refresh_documents(
+        &self,
+        document_updates: &[(Document, Vec<CareerFields>)],
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// 外部サービスに依存するアクションをする場合、その外部サービスのレートリミットにかからないように一定時間待つ
+    async fn wait_for_next_iteration(&self);
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Document {
+    user_account_id: i64,
+    document_id: i64,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Career {
+    career_id: i64,
+    user_account_id: i64,
+    career_start_date: NaiveDate,
+    career_end_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct CareerFields {
+    career_id: i64,
+    fields: TimeDependentCareerFields,
+}
+
+struct RefreshTimeDependentCareerFieldsOperationImpl {
+    pool: DatabaseConnection,
+    index_client: OpenSearch,
+}
+
+#[async_trait]
+impl RefreshTimeDependentCareerFieldsOperation for RefreshTimeDependentCareerFieldsOperationImpl {
+    async fn get_documents(
+        &self,
+        last_user_account_id: Option<i64>,
+        limit: u64,
+    ) -> Result<Vec<Document>, Box<dyn Error>> {
+        let mut query = entity::document::Entity::find();
+        if let Some(last_user_account_id) = last_user_account_id {
+            query = query.filter(entity::document::Column::UserAccountId.gt(last_user_account_id));
+        }
+        let models = query
+            .order_by_asc(entity::document::Column::UserAccountId)
+            .limit(limit)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to get document (last_user_account_id: {:?}, limit: {}): {}",
+                    last_user_account_id, limit, e
+                )
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| Document {
+                user_account_id: m.user_account_id,
+                document_id: m.document_id,
+            })
+            .collect())
+    }
+
+    async fn get_careers(&self, user_account_ids: &[i64]) -> Result<Vec<Career>, Box<dyn Error>> {
+        let models = entity::career::Entity::find()
+            .filter(entity::career::Column::UserAccountId.is_in(user_account_ids.to_vec()))
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to get career (user_account_ids: {:?}): {}",
+                    user_account_ids, e
+                )
+            })?;
+        Ok(models
+            .into_iter()
+            .map(|m| Career {
+                career_id: m.career_id,
+                user_account_id: m.user_account_id,
+                career_start_date: m.career_start_date,
+                career_end_date: m.career_end_date,
+            })
+            .collect())
+    }
+
+    async fn get_indexed_careers(
+        &self,
+        document_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<CareerFields>>, Box<dyn Error>> {
+        let ids: Vec<String> = document_ids.iter().map(|id| id.to_string()).collect();
+        let query = json!({
+            "query": {
+                "ids": {
+                    "values": ids
+                }
+            },
+            "_source": ["careers.career_id", "careers.years_of_service", "careers.employed"]
+        });
+        let query_result = search_documents(
+            INDEX_NAME,
+            0,
+            document_ids.len() as i64,
+            None,
+            &query,
+            &self.index_client,
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "failed to search documents (status code: {}, response body: {:?}, document_ids: {:?})",
+                e.0, e.1, document_ids
+            )
+        })?;
+        parse_indexed_careers(&query_result)
+    }
+
+    async fn refresh_documents(
+        &self,
+        document_updates: &[(Document, Vec<CareerFields>)],
+    ) -> Result<(), Box<dyn Error>> {
+        let updates: Vec<(String, Value)> = document_updates
+            .iter()
+            .map(|(document, careers)| {
+                (
+                    document.document_id.to_string(),
+                    create_refresh_careers_script(careers),
+                )
+            })
+            .collect();
+        bulk_update_documents(INDEX_NAME, &updates, &self.index_client)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to bulk update documents (status code: {}, response body: {:?})",
+                    e.0, e.1
+                )
+            })?;
+        Ok(())
+    }
+
+    async fn wait_for_next_iteration(&self) {
+        // 一度に更新するドキュメントの数をNUM_OF_DOCUMENTS_PER_CHUNKで制限しているため、特に待つ必要はない
+    }
+}
+
+fn parse_indexed_careers(
+    query_result: &Value,
+) -> Result<HashMap<i64, Vec<CareerFields>>, Box<dyn Error>> {
+    let hits = query_result["hits"]["hits"]
+        .as_array()
+        .ok_or_else(|| format!("failed to get hits: {}", query_result))?;
+    let mut results = HashMap::with_capacity(hits.len());
+    for hit in hits {
+        let document_id = hit["_id"]
+            .as_str()
+            .ok_or_else(|| format!("failed to find _id: {}", hit))?
+            .parse::<i64>()?;
+        // 職務経歴が一度も登録されていないドキュメントにはcareersが存在しない
+        let careers = match hit["_source"]["careers"].as_array() {
+            Some(careers) => careers.clone(),
+            None => vec![],
+        };
+        let mut career_fields = Vec::with_capacity(careers.len());
+        for career in careers {
+            let career_id = career["career_id"]
+                .as_i64()
+                .ok_or_else(|| format!("failed to find career_id in career: {}", career))?;
+            let years_of_service = career["years_of_service"]
+                .as_i64()
+                .ok_or_else(|| format!("failed to find years_of_service in career: {}", career))?;
+            let employed = career["employed"]
+                .as_bool()
+                .ok_or_else(|| format!("failed to find employed in career: {}", career))?;
+            career_fields.push(CareerFields {
+                career_id,
+                fields: TimeDependentCareerFields {
+                    years_of_service,
+                    employed,
+                },
+            });
+        }
+        results.insert(document_id, career_fields);
+    }
+    Ok(results)
+}
+
+fn create_refresh_careers_script(careers: &[CareerFields]) -> Value {
+    let mut params = Map::with_capacity(careers.len());
+    for career in careers {
+        params.insert(
+            career.career_id.to_string(),
+            json!({
+                "years_of_service": career.fields.years_of_service,
+                "employed": career.fields.employed
+            }),
+        );
+    }
+    json!({
+        "script": {
+            "source": REFRESH_CAREERS_SCRIPT,
+            "params": {
+                "careers": params
+            }
+        }
+    })
+}
+
+fn create_text(
+    num_of_drifted_documents: usize,
+    num_of_refresh_failed: usize,
+    refresh_failed: &[Document],
+) -> String {
+    format!(
+        r"職務経歴の在籍年数、在籍中かどうかの値がずれていたドキュメント{}個の内、{}個の更新に失敗しました。
+
+【詳細】
+{:?}",
+        num_of_drifted_documents, num_of_refresh_failed, refresh_failed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cmp::min;
+
+    use chrono::TimeZone;
+    use common::ErrResp;
+
+    use super::*;
+
+    struct RefreshTimeDependentCareerFieldsOperationMock {
+        documents: Vec<Document>,
+        careers: Vec<Career>,
+        indexed_careers: HashMap<i64, Vec<CareerFields>>,
+        expected_document_updates: Vec<(Document, Vec<CareerFields>)>,
+        refresh_documents_result: bool,
+    }
+
+    #[async_trait]
+    impl RefreshTimeDependentCareerFieldsOperation for RefreshTimeDependentCareerFieldsOperationMock {
+        async fn get_documents(
+            &self,
+            last_user_account_id: Option<i64>,
+            limit: u64,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            assert_eq!(NUM_OF_DOCUMENTS_PER_CHUNK, limit);
+            let documents: Vec<Document> = self
+                .documents
+                .iter()
+                .filter(|d| match last_user_account_id {
+                    Some(id) => d.user_account_id > id,
+                    None => true,
+                })
+                .cloned()
+                .collect();
+            let limit = min(limit as usize, documents.len());
+            Ok(documents[..limit].to_vec())
+        }
+
+        async fn get_careers(
+            &self,
+            user_account_ids: &[i64],
+        ) -> Result<Vec<Career>, Box<dyn Error>> {
+            Ok(self
+                .careers
+                .iter()
+                .filter(|c| user_account_ids.contains(&c.user_account_id))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_indexed_careers(
+            &self,
+            document_ids: &[i64],
+        ) -> Result<HashMap<i64, Vec<CareerFields>>, Box<dyn Error>> {
+            Ok(self
+                .indexed_careers
+                .iter()
+                .filter(|(id, _)| document_ids.contains(id))
+                .map(|(id, careers)| (*id, careers.clone()))
+                .collect())
+        }
+
+        async fn refresh_documents(
+            &self,
+            document_updates: &[(Document, Vec<CareerFields>)],
+        ) -> Result<(), Box<dyn Error>> {
+            assert_eq!(self.expected_document_updates, document_updates);
+            if !self.refresh_documents_result {
+                return Err("mock error message".into());
+            }
+            Ok(())
+        }
+
+        async fn wait_for_next_iteration(&self) {
+            // テストコードでは待つ必要はないので何もしない
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub(super) struct SendMailMock {
+        to: String,
+        from: String,
+        subject: String,
+        text_keywords: Vec<String>,
+    }
+
+    impl SendMailMock {
+        pub(super) fn new(
+            to: String,
+            from: String,
+            subject: String,
+            text_keywords: Vec<String>,
+        ) -> Self {
+            Self {
+                to,
+                from,
+                subject,
+                text_keywords,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SendMail for SendMailMock {
+        async fn send_mail(
+            &self,
+            to: &str,
+            from: &str,
+            subject: &str,
+            text: &str,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.to, to);
+            assert_eq!(self.from, from);
+            assert_eq!(self.subject, subject);
+            for text_keyword in self.text_keywords.clone() {
+                assert!(text.contains(&text_keyword));
+            }
+            Ok(())
+        }
+    }
+
+    fn create_career(
+        career_id: i64,
+        user_account_id: i64,
+        career_start_date: NaiveDate,
+        career_end_date: Option<NaiveDate>,
+    ) -> Career {
+        Career {
+            career_id,
+            user_account_id,
+            career_start_date,
+            career_end_date,
+        }
+    }
+
+    fn create_career_fields(career_id: i64, years_of_service: i64, employed: bool) -> CareerFields {
+        CareerFields {
+            career_id,
+            fields: TimeDependentCareerFields {
+                years_of_service,
+                employed,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_time_dependent_career_fields_success_no_documents() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 3, 0, 40)
+            .unwrap();
+        let op = RefreshTimeDependentCareerFieldsOperationMock {
+            documents: vec![],
+            careers: vec![],
+            indexed_careers: HashMap::new(),
+            expected_document_updates: vec![],
+            refresh_documents_result: true,
+        };
+        // 成功時はメールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            refresh_time_dependent_career_fields(current_date_time, &op, &send_mail_mock).await;
+
+        let refresh_result = result.expect("failed to get Ok");
+        assert_eq!(RefreshResult::default(), refresh_result);
+    }
+
+    #[tokio::test]
+    async fn refresh_time_dependent_career_fields_success_no_drift() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 3, 0, 40)
+            .unwrap();
+        let op = RefreshTimeDependentCareerFieldsOperationMock {
+            documents: vec![Document {
+                user_account_id: 1,
+                document_id: 1,
+            }],
+            careers: vec![
+                create_career(
+                    10,
+                    1,
+                    NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok"),
+                    None,
+                ),
+                create_career(
+                    11,
+                    1,
+                    NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get Ok"),
+                    Some(NaiveDate::from_ymd_opt(2015, 3, 31).expect("failed to get Ok")),
+                ),
+            ],
+            indexed_careers: HashMap::from([(
+                1,
+                vec![
+                    create_career_fields(10, 8, true),
+                    create_career_fields(11, 5, false),
+                ],
+            )]),
+            expected_document_updates: vec![],
+            refresh_documents_result: true,
+        };
+        // 成功時はメールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            refresh_time_dependent_career_fields(current_date_time, &op, &send_mail_mock).await;
+
+        let refresh_result = result.expect("failed to get Ok");
+        assert_eq!(
+            RefreshResult {
+                num_of_documents: 1,
+                num_of_drifted_documents: 0,
+                num_of_drifted_careers: 0
+            },
+            refresh_result
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_time_dependent_career_fields_success_drifted() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 3, 0, 40)
+            .unwrap();
+        let op = RefreshTimeDependentCareerFieldsOperationMock {
+            documents: vec![
+                Document {
+                    user_account_id: 1,
+                    document_id: 1,
+                },
+                Document {
+                    user_account_id: 2,
+                    document_id: 2,
+                },
+            ],
+            careers: vec![
+                // 在籍年数が増えた
+                create_career(
+                    10,
+                    1,
+                    NaiveDate::from_ymd_opt(2015, 9, 15).expect("failed to get Ok"),
+                    None,
+                ),
+                create_career(
+                    11,
+                    1,
+                    NaiveDate::from_ymd_opt(2010, 4, 1).expect("failed to get Ok"),
+                    Some(NaiveDate::from_ymd_opt(2015, 3, 31).expect("failed to get Ok")),
+                ),
+                // 入社日からちょうど5年（365日 x 5）が経過した
+                create_career(
+                    20,
+                    2,
+                    NaiveDate::from_ymd_opt(2018, 9, 16).expect("failed to get Ok"),
+                    None,
+                ),
+            ],
+            indexed_careers: HashMap::from([
+                (
+                    1,
+                    vec![
+                        create_career_fields(10, 7, true),
+                        create_career_fields(11, 5, false),
+                    ],
+                ),
+                (2, vec![create_career_fields(20, 4, true)]),
+            ]),
+            expected_document_updates: vec![
+                (
+                    Document {
+                        user_account_id: 1,
+                        document_id: 1,
+                    },
+                    vec![create_career_fields(10, 8, true)],
+                ),
+                (
+                    Document {
+                        user_account_id: 2,
+                        document_id: 2,
+                    },
+                    vec![create_career_fields(20, 5, true)],
+                ),
+            ],
+            refresh_documents_result: true,
+        };
+        // 成功時はメールを送らないので、わざと失敗するような内容でモックを生成する
+        let send_mail_mock =
+            SendMailMock::new("".to_string(), "".to_string(), "".to_string(), vec![]);
+
+        let result =
+            refresh_time_dependent_career_fields(current_date_time, &op, &send_mail_mock).await;
+
+        let refresh_result = result.expect("failed to get Ok");
+        assert_eq!(
+            RefreshResult {
+                num_of_documents: 2,
+                num_of_drifted_documents: 2,
+                num_of_drifted_careers: 2
+            },
+            refresh_result
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_time_dependent_career_fields_fail_refresh_documents() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 15, 3, 0, 40)
+            .unwrap();
+        let document = Document {
+            user_account_id: 1,
+            document_id: 1,
+        };
+        let op = RefreshTimeDependentCareerFieldsOperationMock {
+            documents: vec![document.clone()],
+            careers: vec![create_career(
+                10,
+                1,
+                NaiveDate::from_ymd_opt(2015, 9, 15).expect("failed to get Ok"),
+                None,
+            )],
+            indexed_careers: HashMap::from([(1, vec![create_career_fields(10, 7, true)])]),
+            expected_document_updates: vec![(
+                document.clone(),
+                vec![create_career_fields(10, 8, true)],
+            )],
+            refresh_documents_result: false,
+        };
+        let send_mail_mock = SendMailMock::new(
+            ADMIN_EMAIL_ADDRESS.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            format!(
+                "[{}] 定期実行ツール (refresh_time_dependent_career_fields) 失敗通知",
+                WEB_SITE_NAME
+            ),
+            vec![
+                "職務経歴の在籍年数、在籍中かどうかの値がずれていたドキュメント1個の内、1個の更新に失敗しました。".to_string(),
+                format!("{:?}", document),
+            ],
+        );
+
+        let result =
+            refresh_time_dependent_career_fields(current_date_time, &op, &send_mail_mock).await;
+
+        let err = result.expect_err("failed to get Err");
+        assert_eq!(
+            format!("1 drifted, 1 failed (detail: {:?})", vec![document]),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_indexed_careers_success() {
+        let query_result = json!({
+            "hits": {
+                "hits": [
+                    {
+                        "_id": "1",
+                        "_source": {
+                            "careers": [
+                                {"career_id": 10, "years_of_service": 7, "employed": true},
+                                {"career_id": 11, "years_of_service": 4, "employed": false}
+                            ]
+                        }
+                    },
+                    {
+                        "_id": "2",
+                        "_source": {}
+                    }
+                ]
+            }
+        });
+
+        let result = parse_indexed_careers(&query_result).expect("failed to get Ok");
+
+        assert_eq!(
+            HashMap::from([
+                (
+                    1,
+                    vec![
+                        create_career_fields(10, 7, true),
+                        create_career_fields(11, 4, false)
+                    ]
+                ),
+                (2, vec![])
+            ]),
+            result
+        );
+    }
+
+    #[test]
+    fn create_refresh_careers_script_success() {
+        let careers = vec![
+            create_career_fields(10, 8, true),
+            create_career_fields(11, 5, false),
+        ];
+
+        let result = create_refresh_careers_script(&careers);
+
+        assert_eq!(
+            json!({
+                "script": {
+                    "source": REFRESH_CAREERS_SCRIPT,
+                    "params": {
+                        "careers": {
+                            "10": {"years_of_service": 8, "employed": true},
+                            "11": {"years_of_service": 5, "employed": false}
+                        }
+                    }
+                }
+            }),
+            result
+        );
+    }
+}
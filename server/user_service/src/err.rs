@@ -47,7 +47,6 @@ pub(crate) enum Code {
     InvalidNameInField = 20036,
     InvalidUtf8Sequence = 20037,
     InvalidIdentityJson = 20038,
-    NotJpegExtension = 20039,
     ExceedMaxIdentityImageSizeLimit = 20040,
     InvalidJpegImage = 20041,
     NoIdentityFound = 20042,
@@ -140,6 +139,48 @@ pub(crate) enum Code {
     NoEnoughSpareTimeBeforeMeeting = 20143,
     ConsultationHasNotBeenFinished = 20144,
     PaymentIsNotDoneYet = 20145,
+    InvalidCouponCodeFormat = 20146,
+    NoCouponFound = 20147,
+    CouponIsOutOfValidPeriod = 20148,
+    ReachCouponUsageLimit = 20149,
+    ReachCouponUsageLimitPerUser = 20150,
+    NoVirtualBankAccountAvailable = 20151,
+    NoAwaitingPaymentFound = 20152,
+    NoCardPaymentRequired = 20153,
+    InvalidWebhookSignature = 20154,
+    InvalidWebhookPayload = 20155,
+    NonPositiveCompanyId = 20156,
+    NonPositiveSavedSearchId = 20157,
+    NoSavedSearchFound = 20158,
+    ReachSavedSearchesLimit = 20159,
+    FavoriteConsultantIsSelf = 20160,
+    ReachFavoriteConsultantsLimit = 20161,
+    NoFavoriteConsultantFound = 20162,
+    InvalidConsultantSearchCursor = 20163,
+    ConsultantSearchCursorExpired = 20164,
+    InvalidConsultantsSearchSuggestionPrefix = 20165,
+    TooManyConsultantsSearchSuggestionsRequests = 20166,
+    ImageResolutionTooLow = 20167,
+    ImageTooBlurry = 20168,
+    ImageIsBlank = 20169,
+    DuplicateIdentityImage = 20170,
+    UnsupportedFileFormat = 20171,
+    InvalidPngImage = 20172,
+    InvalidHeicImage = 20173,
+    InvalidPdf = 20174,
+    NoCareerUpdated = 20175,
+    UpdateCareerReqAlreadyExists = 20176,
+    NoCreateCareerReqForWorkEmailVerificationFound = 20177,
+    WorkEmailVerificationForEndedCareer = 20178,
+    FreeEmailDomainNotAllowed = 20179,
+    WorkEmailVerificationCodeReqTooFrequent = 20180,
+    InvalidWorkEmailVerificationCodeFormat = 20181,
+    NoWorkEmailVerificationCodeFound = 20182,
+    WorkEmailVerificationCodeExpired = 20183,
+    ReachWorkEmailVerificationAttemptLimit = 20184,
+    WorkEmailVerificationCodeMismatch = 20185,
+    CouponDiscountReachesFee = 20186,
+    MultiPagePdf = 20187,
 }
 
 pub(crate) fn unexpected_err_resp() -> ErrResp {
@@ -3,6 +3,8 @@
 pub(crate) mod account_creation;
 pub(crate) mod health;
 pub(crate) mod news;
+pub(crate) mod payment_webhook;
+pub(crate) mod saved_search_alert_unsubscribe;
 pub(crate) mod session;
 
 pub(super) const ROOT_PATH: &str = "/api";
@@ -0,0 +1,647 @@
+// Copyright 2023 Ken Miura
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{async_trait, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::payment::{
+    create_sender_name_for_card_payment, execute_card_payment_refund,
+    record_card_payment_refund_request, verify_webhook_signature, CardPaymentRefund,
+    PaymentGatewayClient, EVENT_TYPE_CHECKOUT_SESSION_COMPLETED, PAYMENT_GATEWAY_SIGNATURE_HEADER,
+    PAYMENT_GATEWAY_WEBHOOK_SECRET,
+};
+use common::payment_event::{
+    insert_payment_event, PAYMENT_STATE_AWAITING_PAYMENT, PAYMENT_STATE_AWAITING_WITHDRAWAL,
+};
+use common::smtp::SYSTEM_EMAIL_ADDRESS;
+use common::virtual_bank_account::release_virtual_bank_account;
+use common::{ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{
+    ActiveModelTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QuerySelect, Set,
+    TransactionError, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::err::{unexpected_err_resp, Code};
+
+/// 決済代行サービスからの通知（Webhook）を受け付ける
+///
+/// 認証はセッションではなく、リクエストに含まれる署名の検証により行う。
+/// 決済完了の通知を受け取った場合、該当する相談を入金待ちから出金待ちの状態へ移す。
+/// 決済代行サービスは2xx以外のレスポンスを受け取った場合、通知を再送するため、同じ通知を複数回処理しても問題ないように実装する。
+pub(crate) async fn post_payment_webhook(
+    State(pool): State<DatabaseConnection>,
+    State(payment_gateway_client): State<PaymentGatewayClient>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> RespResult<PaymentWebhookResult> {
+    let signature = headers
+        .get(PAYMENT_GATEWAY_SIGNATURE_HEADER)
+        .and_then(|hv| hv.to_str().ok())
+        .map(|s| s.to_string());
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = PaymentWebhookOperationImpl {
+        pool,
+        payment_gateway_client,
+    };
+    handle_payment_webhook(
+        signature,
+        &body,
+        PAYMENT_GATEWAY_WEBHOOK_SECRET.as_str(),
+        current_date_time,
+        op,
+    )
+    .await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct PaymentWebhookResult {}
+
+#[derive(Deserialize, Debug)]
+struct WebhookEvent {
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    data: Option<CheckoutSessionCompleted>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct CheckoutSessionCompleted {
+    checkout_session_id: String,
+    payment_id: String,
+    amount_in_yen: i32,
+}
+
+async fn handle_payment_webhook(
+    signature: Option<String>,
+    payload: &[u8],
+    webhook_secret: &str,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl PaymentWebhookOperation,
+) -> RespResult<PaymentWebhookResult> {
+    let signature = signature.ok_or_else(|| {
+        error!("no {} header found", PAYMENT_GATEWAY_SIGNATURE_HEADER);
+        invalid_webhook_signature_err_resp()
+    })?;
+    verify_webhook_signature(
+        payload,
+        &signature,
+        webhook_secret,
+        current_date_time.timestamp(),
+    )
+    .map_err(|e| {
+        error!(
+            "failed to verify webhook signature (signature: {}): {}",
+            signature, e
+        );
+        invalid_webhook_signature_err_resp()
+    })?;
+
+    let event = serde_json::from_slice::<WebhookEvent>(payload).map_err(|e| {
+        error!("failed to parse webhook payload: {}", e);
+        invalid_webhook_payload_err_resp()
+    })?;
+    if event.event_type != EVENT_TYPE_CHECKOUT_SESSION_COMPLETED {
+        // 決済完了以外の通知は利用しないため、受け取ったことだけ応答する
+        info!(
+            "ignored webhook event (id: {}, type: {})",
+            event.id, event.event_type
+        );
+        return Ok((StatusCode::OK, Json(PaymentWebhookResult {})));
+    }
+    let data = event.data.ok_or_else(|| {
+        error!("no data found in webhook event (id: {})", event.id);
+        invalid_webhook_payload_err_resp()
+    })?;
+
+    op.confirm_card_payment(data, current_date_time).await?;
+
+    Ok((StatusCode::OK, Json(PaymentWebhookResult {})))
+}
+
+fn invalid_webhook_signature_err_resp() -> ErrResp {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiError {
+            code: Code::InvalidWebhookSignature as u32,
+        }),
+    )
+}
+
+fn invalid_webhook_payload_err_resp() -> ErrResp {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiError {
+            code: Code::InvalidWebhookPayload as u32,
+        }),
+    )
+}
+
+#[async_trait]
+trait PaymentWebhookOperation {
+    /// クレジットカード決済の完了を記録し、相談を入金待ちから出金待ちの状態へ移す
+    ///
+    /// 既に別の決済で支払い済みの場合（入金待ちの状態でない場合）、二重払いとして返金する。
+    async fn confirm_card_payment(
+        &self,
+        data: CheckoutSessionCompleted,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp>;
+}
+
+struct PaymentWebhookOperationImpl {
+    pool: DatabaseConnection,
+    payment_gateway_client: PaymentGatewayClient,
+}
+
+#[async_trait]
+impl PaymentWebhookOperation for PaymentWebhookOperationImpl {
+    async fn confirm_card_payment(
+        &self,
+        data: CheckoutSessionCompleted,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp> {
+        let card_payment_refund = self
+            .pool
+            .transaction::<_, Option<CardPaymentRefund>, ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let cp = entity::card_payment::Entity::find_by_id(data.checkout_session_id.clone())
+                        .lock_exclusive()
+                        .one(txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to find card_payment (checkout_session_id: {}): {}",
+                                data.checkout_session_id, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+                    let cp = cp.ok_or_else(|| {
+                        error!(
+                            "no card_payment (checkout_session_id: {}) found",
+                            data.checkout_session_id
+                        );
+                        ErrRespStruct {
+                            err_resp: invalid_webhook_payload_err_resp(),
+                        }
+                    })?;
+                    if cp.paid_at.is_some() {
+                        info!(
+                            "card_payment (checkout_session_id: {}) has already been paid",
+                            data.checkout_session_id
+                        );
+                        // 二重払いの返金依頼に失敗していた場合、Webhookの再送を契機に同じ冪等キーで返金を再試行する
+                        return Ok(find_unfinished_card_payment_refund(cp));
+                    }
+                    if cp.amount_in_yen != data.amount_in_yen {
+                        error!(
+                            "amount_in_yen does not match (card_payment: {:?}, data: {:?})",
+                            cp, data
+                        );
+                        return Err(ErrRespStruct {
+                            err_resp: invalid_webhook_payload_err_resp(),
+                        });
+                    }
+
+                    let ap = entity::awaiting_payment::Entity::find_by_id(cp.consultation_id)
+                        .lock_exclusive()
+                        .one(txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to find awaiting_payment (consultation_id: {}): {}",
+                                cp.consultation_id, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+
+                    let consultation_id = cp.consultation_id;
+                    let mut active_model: entity::card_payment::ActiveModel = cp.into();
+                    active_model.payment_id = Set(Some(data.payment_id.clone()));
+                    active_model.paid_at = Set(Some(current_date_time));
+
+                    let ap = match ap {
+                        Some(ap) => ap,
+                        None => {
+                            // 別の決済ページで既に支払われている、または管理者により返金、入金なしの処理がされている
+                            warn!(
+                                "no awaiting_payment (consultation_id: {}) found, refund duplicate payment (payment_id: {})",
+                                consultation_id, data.payment_id
+                            );
+                            let cp = update_card_payment(active_model, txn).await?;
+                            let card_payment_refund = record_card_payment_refund_request(
+                                cp,
+                                format!("duplicate-{}", data.checkout_session_id),
+                                txn,
+                            )
+                            .await?;
+                            return Ok(Some(card_payment_refund));
+                        }
+                    };
+                    let _ = update_card_payment(active_model, txn).await?;
+
+                    insert_user_rating(&ap, txn).await?;
+                    insert_consultant_rating(&ap, txn).await?;
+                    let sender_name = create_sender_name_for_card_payment(&data.payment_id);
+                    insert_awaiting_withdrawal(&ap, sender_name, current_date_time, txn).await?;
+                    insert_payment_event(
+                        consultation_id,
+                        Some(PAYMENT_STATE_AWAITING_PAYMENT),
+                        PAYMENT_STATE_AWAITING_WITHDRAWAL,
+                        SYSTEM_EMAIL_ADDRESS.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+                    delete_awaiting_payment(consultation_id, txn).await?;
+
+                    Ok(None)
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!("failed to confirm_card_payment: {}", err_resp_struct);
+                    err_resp_struct.err_resp
+                }
+            })?;
+        // 二重払いを記録したトランザクションをコミットした後に決済代行サービスへ返金を依頼する。
+        // 返金依頼に失敗した場合、エラーを返すことでWebhookが再送され、その際に同じ冪等キーで再試行される。
+        if let Some(card_payment_refund) = card_payment_refund {
+            execute_card_payment_refund(
+                &card_payment_refund,
+                current_date_time,
+                &self.payment_gateway_client,
+                &self.pool,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+fn find_unfinished_card_payment_refund(
+    cp: entity::card_payment::Model,
+) -> Option<CardPaymentRefund> {
+    if cp.refund_id.is_some() {
+        return None;
+    }
+    match (cp.payment_id, cp.refund_idempotency_key) {
+        (Some(payment_id), Some(idempotency_key)) => Some(CardPaymentRefund {
+            checkout_session_id: cp.checkout_session_id,
+            payment_id,
+            amount_in_yen: cp.amount_in_yen,
+            idempotency_key,
+        }),
+        _ => None,
+    }
+}
+
+async fn update_card_payment(
+    active_model: entity::card_payment::ActiveModel,
+    txn: &DatabaseTransaction,
+) -> Result<entity::card_payment::Model, ErrRespStruct> {
+    let model = active_model.update(txn).await.map_err(|e| {
+        error!("failed to update card_payment: {}", e);
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(model)
+}
+
+async fn insert_user_rating(
+    ap: &entity::awaiting_payment::Model,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let ur = entity::user_rating::ActiveModel {
+        consultation_id: Set(ap.consultation_id),
+        user_account_id: Set(ap.user_account_id),
+        consultant_id: Set(ap.consultant_id),
+        meeting_at: Set(ap.meeting_at),
+        rating: Set(None),
+        rated_at: Set(None),
+    };
+    let _ = ur.insert(txn).await.map_err(|e| {
+        error!(
+            "failed to insert user_rating (awaiting_payment: {:?}): {}",
+            ap, e
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(())
+}
+
+async fn insert_consultant_rating(
+    ap: &entity::awaiting_payment::Model,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let cr = entity::consultant_rating::ActiveModel {
+        consultation_id: Set(ap.consultation_id),
+        user_account_id: Set(ap.user_account_id),
+        consultant_id: Set(ap.consultant_id),
+        meeting_at: Set(ap.meeting_at),
+        rating: Set(None),
+        rated_at: Set(None),
+    };
+    let _ = cr.insert(txn).await.map_err(|e| {
+        error!(
+            "failed to insert consultant_rating (awaiting_payment: {:?}): {}",
+            ap, e
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(())
+}
+
+async fn insert_awaiting_withdrawal(
+    ap: &entity::awaiting_payment::Model,
+    sender_name: String,
+    created_at: DateTime<FixedOffset>,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    // 決済代行サービスにより入金が確認されているため、確認者としてシステムのメールアドレスを記録する
+    let payment_confirmed_by = SYSTEM_EMAIL_ADDRESS.to_string();
+    let aw = entity::awaiting_withdrawal::ActiveModel {
+        consultation_id: Set(ap.consultation_id),
+        user_account_id: Set(ap.user_account_id),
+        consultant_id: Set(ap.consultant_id),
+        meeting_at: Set(ap.meeting_at),
+        fee_per_hour_in_yen: Set(ap.fee_per_hour_in_yen),
+        coupon_id: Set(ap.coupon_id),
+        discount_in_yen: Set(ap.discount_in_yen),
+        discount_borne_by: Set(ap.discount_borne_by.clone()),
+        sender_name: Set(sender_name),
+        payment_confirmed_by: Set(payment_confirmed_by.clone()),
+        created_at: Set(created_at),
+    };
+    let _ = aw.insert(txn).await.map_err(|e| {
+        error!("failed to insert awaiting_withdrawal (awaiting_payment: {:?}, payment_confirmed_by: {}, created_at: {}): {}",
+            ap, payment_confirmed_by, created_at, e);
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(())
+}
+
+async fn delete_awaiting_payment(
+    consultation_id: i64,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let _ = entity::awaiting_payment::Entity::delete_by_id(consultation_id)
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete awaiting_payment (consultation_id: {}): {}",
+                consultation_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    // 入金が確定したため、割り当てていたバーチャル口座を解放する
+    release_virtual_bank_account(consultation_id, txn).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use common::payment::create_webhook_signature;
+
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+
+    struct PaymentWebhookOperationMock {
+        data: Option<CheckoutSessionCompleted>,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl PaymentWebhookOperation for PaymentWebhookOperationMock {
+        async fn confirm_card_payment(
+            &self,
+            data: CheckoutSessionCompleted,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<(), ErrResp> {
+            let expected = self
+                .data
+                .clone()
+                .expect("confirm_card_payment must not be called");
+            assert_eq!(expected, data);
+            assert_eq!(self.current_date_time, current_date_time);
+            Ok(())
+        }
+    }
+
+    fn create_completed_payload() -> String {
+        r#"{"id":"evt_1","type":"checkout.session.completed","data":{"checkout_session_id":"cs_test_a1b2c3","payment_id":"pi_test_d4e5f6","amount_in_yen":4500}}"#.to_string()
+    }
+
+    #[tokio::test]
+    async fn handle_payment_webhook_success() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let payload = create_completed_payload();
+        let signature =
+            create_webhook_signature(payload.as_bytes(), SECRET, current_date_time.timestamp());
+        let op = PaymentWebhookOperationMock {
+            data: Some(CheckoutSessionCompleted {
+                checkout_session_id: "cs_test_a1b2c3".to_string(),
+                payment_id: "pi_test_d4e5f6".to_string(),
+                amount_in_yen: 4500,
+            }),
+            current_date_time,
+        };
+
+        let result = handle_payment_webhook(
+            Some(signature),
+            payload.as_bytes(),
+            SECRET,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(PaymentWebhookResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_payment_webhook_success_ignore_other_event() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let payload = r#"{"id":"evt_2","type":"checkout.session.expired"}"#;
+        let signature =
+            create_webhook_signature(payload.as_bytes(), SECRET, current_date_time.timestamp());
+        let op = PaymentWebhookOperationMock {
+            data: None,
+            current_date_time,
+        };
+
+        let result = handle_payment_webhook(
+            Some(signature),
+            payload.as_bytes(),
+            SECRET,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(PaymentWebhookResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_payment_webhook_fail_no_signature() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let payload = create_completed_payload();
+        let op = PaymentWebhookOperationMock {
+            data: None,
+            current_date_time,
+        };
+
+        let result =
+            handle_payment_webhook(None, payload.as_bytes(), SECRET, current_date_time, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidWebhookSignature as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_payment_webhook_fail_invalid_signature() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let payload = create_completed_payload();
+        let signature = create_webhook_signature(
+            payload.as_bytes(),
+            "whsec_other",
+            current_date_time.timestamp(),
+        );
+        let op = PaymentWebhookOperationMock {
+            data: None,
+            current_date_time,
+        };
+
+        let result = handle_payment_webhook(
+            Some(signature),
+            payload.as_bytes(),
+            SECRET,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidWebhookSignature as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_payment_webhook_fail_invalid_payload() {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let payload = r#"{"id":"evt_3","type":"checkout.session.completed"}"#;
+        let signature =
+            create_webhook_signature(payload.as_bytes(), SECRET, current_date_time.timestamp());
+        let op = PaymentWebhookOperationMock {
+            data: None,
+            current_date_time,
+        };
+
+        let result = handle_payment_webhook(
+            Some(signature),
+            payload.as_bytes(),
+            SECRET,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidWebhookPayload as u32, resp.1 .0.code);
+    }
+
+    fn create_card_payment(
+        refund_idempotency_key: Option<String>,
+        refund_id: Option<String>,
+    ) -> entity::card_payment::Model {
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        entity::card_payment::Model {
+            checkout_session_id: "cs_1".to_string(),
+            consultation_id: 1,
+            user_account_id: 2,
+            amount_in_yen: 5000,
+            payment_id: Some("pi_1".to_string()),
+            paid_at: Some(current_date_time),
+            refund_idempotency_key,
+            refunded_at: refund_id.as_ref().map(|_| current_date_time),
+            refund_id,
+            created_at: current_date_time,
+        }
+    }
+
+    #[test]
+    fn find_unfinished_card_payment_refund_returns_refund_if_not_refunded_yet() {
+        let cp = create_card_payment(Some("duplicate-cs_1".to_string()), None);
+
+        let result = find_unfinished_card_payment_refund(cp);
+
+        assert_eq!(
+            Some(CardPaymentRefund {
+                checkout_session_id: "cs_1".to_string(),
+                payment_id: "pi_1".to_string(),
+                amount_in_yen: 5000,
+                idempotency_key: "duplicate-cs_1".to_string(),
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn find_unfinished_card_payment_refund_returns_none_if_already_refunded() {
+        let cp = create_card_payment(Some("duplicate-cs_1".to_string()), Some("re_1".to_string()));
+
+        let result = find_unfinished_card_payment_refund(cp);
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn find_unfinished_card_payment_refund_returns_none_if_no_refund_requested() {
+        let cp = create_card_payment(None, None);
+
+        let result = find_unfinished_card_payment_refund(cp);
+
+        assert_eq!(None, result);
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2023 Ken Miura
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{async_trait, Json};
+use common::util::validator::uuid_validator::validate_uuid;
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::err::{unexpected_err_resp, Code};
+
+/// 保存した検索条件に新たに一致したコンサルタントの通知を停止する
+///
+/// 通知メールに記載したURLからログインせずに停止できるように、認証は行わずにトークン（saved_searchのunsubscribe_token）で対象を特定する
+pub(crate) async fn post_saved_search_alert_unsubscribe(
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<SavedSearchAlertUnsubscribeReq>,
+) -> RespResult<SavedSearchAlertUnsubscribeResult> {
+    let op = SavedSearchAlertUnsubscribeOperationImpl { pool };
+    handle_saved_search_alert_unsubscribe(req.token.as_str(), op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SavedSearchAlertUnsubscribeReq {
+    token: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct SavedSearchAlertUnsubscribeResult {}
+
+#[async_trait]
+trait SavedSearchAlertUnsubscribeOperation {
+    /// トークンに対応するsaved_searchの通知を無効にする。対応するsaved_searchが存在しない場合、falseを返す
+    async fn disable_alert_by_unsubscribe_token(&self, token: &str) -> Result<bool, ErrResp>;
+}
+
+struct SavedSearchAlertUnsubscribeOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SavedSearchAlertUnsubscribeOperation for SavedSearchAlertUnsubscribeOperationImpl {
+    async fn disable_alert_by_unsubscribe_token(&self, token: &str) -> Result<bool, ErrResp> {
+        let model_option = entity::saved_search::Entity::find()
+            .filter(entity::saved_search::Column::UnsubscribeToken.eq(token))
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find saved_search (unsubscribe_token: {}): {}",
+                    token, e
+                );
+                unexpected_err_resp()
+            })?;
+        let model = match model_option {
+            Some(m) => m,
+            None => return Ok(false),
+        };
+        let saved_search_id = model.saved_search_id;
+        let mut active_model: entity::saved_search::ActiveModel = model.into();
+        active_model.alert_enabled = Set(false);
+        let _ = active_model.update(&self.pool).await.map_err(|e| {
+            error!(
+                "failed to update saved_search (saved_search_id: {}): {}",
+                saved_search_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(true)
+    }
+}
+
+async fn handle_saved_search_alert_unsubscribe(
+    token: &str,
+    op: impl SavedSearchAlertUnsubscribeOperation,
+) -> RespResult<SavedSearchAlertUnsubscribeResult> {
+    validate_uuid(token).map_err(|e| {
+        error!("failed to validate token ({}): {}", token, e);
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: common::err::Code::InvalidUuidFormat as u32,
+            }),
+        )
+    })?;
+    let disabled = op.disable_alert_by_unsubscribe_token(token).await?;
+    if !disabled {
+        error!("no saved_search found (unsubscribe_token: {})", token);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoSavedSearchFound as u32,
+            }),
+        ));
+    }
+    info!("disabled alert (unsubscribe_token: {})", token);
+    Ok((StatusCode::OK, Json(SavedSearchAlertUnsubscribeResult {})))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct SavedSearchAlertUnsubscribeOperationMock {
+        token: String,
+    }
+
+    #[async_trait]
+    impl SavedSearchAlertUnsubscribeOperation for SavedSearchAlertUnsubscribeOperationMock {
+        async fn disable_alert_by_unsubscribe_token(&self, token: &str) -> Result<bool, ErrResp> {
+            Ok(self.token == token)
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_alert_unsubscribe_success() {
+        let token = "b860dc5138d146ac8127b0780fabce7d";
+        let op = SavedSearchAlertUnsubscribeOperationMock {
+            token: token.to_string(),
+        };
+
+        let result = handle_saved_search_alert_unsubscribe(token, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(SavedSearchAlertUnsubscribeResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_alert_unsubscribe_fail_no_saved_search_found() {
+        let op = SavedSearchAlertUnsubscribeOperationMock {
+            token: "b860dc5138d146ac8127b0780fabce7d".to_string(),
+        };
+
+        let result =
+            handle_saved_search_alert_unsubscribe("a860dc5138d146ac8127b0780fabce7d", op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoSavedSearchFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_alert_unsubscribe_fail_invalid_token() {
+        let op = SavedSearchAlertUnsubscribeOperationMock {
+            token: "b860dc5138d146ac8127b0780fabce7d".to_string(),
+        };
+
+        let result = handle_saved_search_alert_unsubscribe("' OR '1'='1", op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(common::err::Code::InvalidUuidFormat as u32, resp.1 .0.code);
+    }
+}
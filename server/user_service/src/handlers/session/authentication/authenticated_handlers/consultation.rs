@@ -1,5 +1,6 @@
 // Copyright 2023 Ken Miura
 
+pub(crate) mod card_checkout_session;
 pub(crate) mod consultant;
 pub(crate) mod consultation_request;
 pub(crate) mod consultation_room;
@@ -140,6 +141,21 @@ fn validate_consultation_id_is_positive(consultation_id: i64) -> Result<(), ErrR
     Ok(())
 }
 
+/// メールに記載する相談料金の文字列表現を返す
+///
+/// 割引がある場合、支払う金額に加えてクーポン適用前の相談料金と割引額を併記する
+fn create_fee_description(fee_per_hour_in_yen: i32, discount_in_yen: i32) -> String {
+    if discount_in_yen == 0 {
+        return format!("{} 円", fee_per_hour_in_yen);
+    }
+    format!(
+        "{} 円（クーポン適用前の相談料金: {} 円、割引額: {} 円）",
+        fee_per_hour_in_yen - discount_in_yen,
+        fee_per_hour_in_yen,
+        discount_in_yen
+    )
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,501 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset, Utc};
+use common::payment::{PaymentGateway, PaymentGatewayClient};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE, URL_FOR_FRONT_END};
+use entity::sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+use super::validate_consultation_id_is_positive;
+
+pub(crate) async fn post_card_checkout_session(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+    State(payment_gateway_client): State<PaymentGatewayClient>,
+    Json(req): Json<CardCheckoutSessionParam>,
+) -> RespResult<CardCheckoutSessionResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = CardCheckoutSessionOperationImpl { pool };
+    handle_card_checkout_session(
+        user_info.account_id,
+        req.consultation_id,
+        current_date_time,
+        URL_FOR_FRONT_END.as_str(),
+        &payment_gateway_client,
+        op,
+    )
+    .await
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CardCheckoutSessionParam {
+    consultation_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct CardCheckoutSessionResult {
+    checkout_session_id: String,
+    url: String,
+}
+
+#[derive(Clone, Debug)]
+struct AwaitingPayment {
+    user_account_id: i64,
+    fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
+}
+
+#[async_trait]
+trait CardCheckoutSessionOperation {
+    async fn find_awaiting_payment(
+        &self,
+        consultation_id: i64,
+    ) -> Result<Option<AwaitingPayment>, ErrResp>;
+
+    async fn create_card_payment(
+        &self,
+        checkout_session_id: String,
+        consultation_id: i64,
+        user_account_id: i64,
+        amount_in_yen: i32,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp>;
+}
+
+struct CardCheckoutSessionOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl CardCheckoutSessionOperation for CardCheckoutSessionOperationImpl {
+    async fn find_awaiting_payment(
+        &self,
+        consultation_id: i64,
+    ) -> Result<Option<AwaitingPayment>, ErrResp> {
+        let model = entity::awaiting_payment::Entity::find_by_id(consultation_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find awaiting_payment (consultation_id: {}): {}",
+                    consultation_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.map(|m| AwaitingPayment {
+            user_account_id: m.user_account_id,
+            fee_per_hour_in_yen: m.fee_per_hour_in_yen,
+            discount_in_yen: m.discount_in_yen,
+        }))
+    }
+
+    async fn create_card_payment(
+        &self,
+        checkout_session_id: String,
+        consultation_id: i64,
+        user_account_id: i64,
+        amount_in_yen: i32,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp> {
+        let active_model = entity::card_payment::ActiveModel {
+            checkout_session_id: Set(checkout_session_id.clone()),
+            consultation_id: Set(consultation_id),
+            user_account_id: Set(user_account_id),
+            amount_in_yen: Set(amount_in_yen),
+            payment_id: Set(None),
+            paid_at: Set(None),
+            refund_idempotency_key: Set(None),
+            refund_id: Set(None),
+            refunded_at: Set(None),
+            created_at: Set(current_date_time),
+        };
+        let _ = active_model.insert(&self.pool).await.map_err(|e| {
+            error!(
+                "failed to insert card_payment (checkout_session_id: {}, consultation_id: {}, user_account_id: {}, amount_in_yen: {}): {}",
+                checkout_session_id, consultation_id, user_account_id, amount_in_yen, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(())
+    }
+}
+
+async fn handle_card_checkout_session(
+    user_account_id: i64,
+    consultation_id: i64,
+    current_date_time: DateTime<FixedOffset>,
+    url_for_front_end: &str,
+    payment_gateway: &impl PaymentGateway,
+    op: impl CardCheckoutSessionOperation,
+) -> RespResult<CardCheckoutSessionResult> {
+    validate_consultation_id_is_positive(consultation_id)?;
+
+    let ap = op
+        .find_awaiting_payment(consultation_id)
+        .await?
+        .ok_or_else(|| {
+            error!(
+                "no awaiting_payment (consultation_id: {}) found",
+                consultation_id
+            );
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::NoAwaitingPaymentFound as u32,
+                }),
+            )
+        })?;
+    // 他のユーザーの支払いの存在を推測されないように、存在しない場合と同じエラーを返す
+    if ap.user_account_id != user_account_id {
+        error!(
+            "user_account_id ({}) does not match that of awaiting_payment (consultation_id: {}, user_account_id: {})",
+            user_account_id, consultation_id, ap.user_account_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoAwaitingPaymentFound as u32,
+            }),
+        ));
+    }
+
+    let amount_in_yen = ap.fee_per_hour_in_yen - ap.discount_in_yen;
+    if amount_in_yen <= 0 {
+        error!(
+            "no card payment required (consultation_id: {}, fee_per_hour_in_yen: {}, discount_in_yen: {})",
+            consultation_id, ap.fee_per_hour_in_yen, ap.discount_in_yen
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCardPaymentRequired as u32,
+            }),
+        ));
+    }
+
+    let return_url = format!("{}/schedule", url_for_front_end);
+    let session = payment_gateway
+        .create_checkout_session(consultation_id, amount_in_yen, &return_url, &return_url)
+        .await?;
+
+    op.create_card_payment(
+        session.checkout_session_id.clone(),
+        consultation_id,
+        user_account_id,
+        amount_in_yen,
+        current_date_time,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(CardCheckoutSessionResult {
+            checkout_session_id: session.checkout_session_id,
+            url: session.url,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use common::payment::CheckoutSession;
+
+    use super::*;
+
+    struct CardCheckoutSessionOperationMock {
+        consultation_id: i64,
+        awaiting_payment: Option<AwaitingPayment>,
+        checkout_session_id: String,
+        user_account_id: i64,
+        amount_in_yen: i32,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl CardCheckoutSessionOperation for CardCheckoutSessionOperationMock {
+        async fn find_awaiting_payment(
+            &self,
+            consultation_id: i64,
+        ) -> Result<Option<AwaitingPayment>, ErrResp> {
+            assert_eq!(self.consultation_id, consultation_id);
+            Ok(self.awaiting_payment.clone())
+        }
+
+        async fn create_card_payment(
+            &self,
+            checkout_session_id: String,
+            consultation_id: i64,
+            user_account_id: i64,
+            amount_in_yen: i32,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.checkout_session_id, checkout_session_id);
+            assert_eq!(self.consultation_id, consultation_id);
+            assert_eq!(self.user_account_id, user_account_id);
+            assert_eq!(self.amount_in_yen, amount_in_yen);
+            assert_eq!(self.current_date_time, current_date_time);
+            Ok(())
+        }
+    }
+
+    struct PaymentGatewayMock {
+        consultation_id: i64,
+        amount_in_yen: i32,
+        return_url: String,
+        session: CheckoutSession,
+    }
+
+    #[async_trait]
+    impl PaymentGateway for PaymentGatewayMock {
+        async fn create_checkout_session(
+            &self,
+            consultation_id: i64,
+            amount_in_yen: i32,
+            success_url: &str,
+            cancel_url: &str,
+        ) -> Result<CheckoutSession, ErrResp> {
+            assert_eq!(self.consultation_id, consultation_id);
+            assert_eq!(self.amount_in_yen, amount_in_yen);
+            assert_eq!(self.return_url, success_url);
+            assert_eq!(self.return_url, cancel_url);
+            Ok(self.session.clone())
+        }
+
+        async fn refund(
+            &self,
+            _payment_id: &str,
+            _amount_in_yen: i32,
+            _idempotency_key: &str,
+        ) -> Result<String, ErrResp> {
+            panic!("refund must not be called")
+        }
+    }
+
+    fn create_dummy_session() -> CheckoutSession {
+        CheckoutSession {
+            checkout_session_id: "cs_test_a1b2c3".to_string(),
+            url: "https://checkout.example.com/cs_test_a1b2c3".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_card_checkout_session_success() {
+        let user_account_id = 53;
+        let consultation_id = 1241;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let url_for_front_end = "https://localhost:8080";
+        let session = create_dummy_session();
+        let gateway = PaymentGatewayMock {
+            consultation_id,
+            amount_in_yen: 4500,
+            return_url: format!("{}/schedule", url_for_front_end),
+            session: session.clone(),
+        };
+        let op = CardCheckoutSessionOperationMock {
+            consultation_id,
+            awaiting_payment: Some(AwaitingPayment {
+                user_account_id,
+                fee_per_hour_in_yen: 5000,
+                discount_in_yen: 500,
+            }),
+            checkout_session_id: session.checkout_session_id.clone(),
+            user_account_id,
+            amount_in_yen: 4500,
+            current_date_time,
+        };
+
+        let result = handle_card_checkout_session(
+            user_account_id,
+            consultation_id,
+            current_date_time,
+            url_for_front_end,
+            &gateway,
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            CardCheckoutSessionResult {
+                checkout_session_id: session.checkout_session_id,
+                url: session.url
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_card_checkout_session_fail_non_positive_consultation_id() {
+        let user_account_id = 53;
+        let consultation_id = 0;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let url_for_front_end = "https://localhost:8080";
+        let session = create_dummy_session();
+        let gateway = PaymentGatewayMock {
+            consultation_id,
+            amount_in_yen: 5000,
+            return_url: format!("{}/schedule", url_for_front_end),
+            session: session.clone(),
+        };
+        let op = CardCheckoutSessionOperationMock {
+            consultation_id,
+            awaiting_payment: None,
+            checkout_session_id: session.checkout_session_id,
+            user_account_id,
+            amount_in_yen: 5000,
+            current_date_time,
+        };
+
+        let result = handle_card_checkout_session(
+            user_account_id,
+            consultation_id,
+            current_date_time,
+            url_for_front_end,
+            &gateway,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveConsultationId as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_card_checkout_session_fail_no_awaiting_payment_found() {
+        let user_account_id = 53;
+        let consultation_id = 1241;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let url_for_front_end = "https://localhost:8080";
+        let session = create_dummy_session();
+        let gateway = PaymentGatewayMock {
+            consultation_id,
+            amount_in_yen: 5000,
+            return_url: format!("{}/schedule", url_for_front_end),
+            session: session.clone(),
+        };
+        let op = CardCheckoutSessionOperationMock {
+            consultation_id,
+            awaiting_payment: None,
+            checkout_session_id: session.checkout_session_id,
+            user_account_id,
+            amount_in_yen: 5000,
+            current_date_time,
+        };
+
+        let result = handle_card_checkout_session(
+            user_account_id,
+            consultation_id,
+            current_date_time,
+            url_for_front_end,
+            &gateway,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoAwaitingPaymentFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_card_checkout_session_fail_awaiting_payment_of_other_user() {
+        let user_account_id = 53;
+        let consultation_id = 1241;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let url_for_front_end = "https://localhost:8080";
+        let session = create_dummy_session();
+        let gateway = PaymentGatewayMock {
+            consultation_id,
+            amount_in_yen: 5000,
+            return_url: format!("{}/schedule", url_for_front_end),
+            session: session.clone(),
+        };
+        let op = CardCheckoutSessionOperationMock {
+            consultation_id,
+            awaiting_payment: Some(AwaitingPayment {
+                user_account_id: user_account_id + 1,
+                fee_per_hour_in_yen: 5000,
+                discount_in_yen: 0,
+            }),
+            checkout_session_id: session.checkout_session_id,
+            user_account_id,
+            amount_in_yen: 5000,
+            current_date_time,
+        };
+
+        let result = handle_card_checkout_session(
+            user_account_id,
+            consultation_id,
+            current_date_time,
+            url_for_front_end,
+            &gateway,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoAwaitingPaymentFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_card_checkout_session_fail_no_card_payment_required() {
+        let user_account_id = 53;
+        let consultation_id = 1241;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let url_for_front_end = "https://localhost:8080";
+        let session = create_dummy_session();
+        let gateway = PaymentGatewayMock {
+            consultation_id,
+            amount_in_yen: 0,
+            return_url: format!("{}/schedule", url_for_front_end),
+            session: session.clone(),
+        };
+        let op = CardCheckoutSessionOperationMock {
+            consultation_id,
+            awaiting_payment: Some(AwaitingPayment {
+                user_account_id,
+                fee_per_hour_in_yen: 5000,
+                discount_in_yen: 5000,
+            }),
+            checkout_session_id: session.checkout_session_id,
+            user_account_id,
+            amount_in_yen: 0,
+            current_date_time,
+        };
+
+        let result = handle_card_checkout_session(
+            user_account_id,
+            consultation_id,
+            current_date_time,
+            url_for_front_end,
+            &gateway,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCardPaymentRequired as u32, resp.1 .0.code);
+    }
+}
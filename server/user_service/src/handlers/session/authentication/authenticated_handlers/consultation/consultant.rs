@@ -2,8 +2,13 @@
 
 mod career_param_validator;
 pub(crate) mod detail;
+pub(crate) mod favorite;
 mod fee_per_hour_in_yen_param_validator;
+pub(crate) mod saved_search;
 pub(crate) mod search;
+mod search_log;
+pub(crate) mod search_suggestions;
+pub(crate) mod similar;
 mod sort_param_validator;
 
 const VALID_YEARS_OF_SERVICE_PERIOD_THREE: i32 = 3;
@@ -6,8 +6,11 @@ use once_cell::sync::Lazy;
 
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::MAX_ANNUAL_INCOME_IN_MAN_YEN;
 
+use common::opensearch::consultant_query::{
+    AnnualInComeInManYenParam, CareerParam, YearsOfServiceParam,
+};
+
 use super::{
-    search::{AnnualInComeInManYenParam, CareerParam, YearsOfServiceParam},
     VALID_YEARS_OF_SERVICE_PERIOD_FIFTEEN, VALID_YEARS_OF_SERVICE_PERIOD_FIVE,
     VALID_YEARS_OF_SERVICE_PERIOD_TEN, VALID_YEARS_OF_SERVICE_PERIOD_THREE,
     VALID_YEARS_OF_SERVICE_PERIOD_TWENTY,
@@ -29,6 +32,11 @@ pub(super) fn validate_career_param(
     if let Some(company_name) = &career_param.company_name {
         validate_company(company_name.as_str())?;
     };
+    if let Some(company_id) = career_param.company_id {
+        if company_id <= 0 {
+            return Err(CareerParamValidationError::NonPositiveCompanyId(company_id));
+        }
+    };
     if let Some(department_name) = &career_param.department_name {
         validate_department_name(department_name.as_str())?;
     };
@@ -260,6 +268,7 @@ pub(super) enum CareerParamValidationError {
         max_length: usize,
     },
     IllegalCharInCompanyName(String),
+    NonPositiveCompanyId(i64),
     InvalidDepartmentNameLength {
         length: usize,
         min_length: usize,
@@ -332,6 +341,9 @@ impl Display for CareerParamValidationError {
                     company_name.as_bytes().to_vec()
                 )
             }
+            CareerParamValidationError::NonPositiveCompanyId(company_id) => {
+                write!(f, "company_id is not positive: {}", company_id)
+            }
             CareerParamValidationError::InvalidDepartmentNameLength {
                 length,
                 min_length,
@@ -489,6 +501,7 @@ mod tests {
                 name: "no parameters specified".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -510,6 +523,7 @@ mod tests {
                 name: "all parameters specified".to_string(),
                 input: CareerParam {
                     company_name: Some("テスト株式会社".to_string()),
+                    company_id: Some(1),
                     department_name: Some("開発部".to_string()),
                     office: Some("山梨事業所".to_string()),
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(3), less_than: None },
@@ -531,6 +545,7 @@ mod tests {
                 name: "invalid length company_name".to_string(),
                 input: CareerParam {
                     company_name: Some("".to_string()),
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -556,6 +571,7 @@ mod tests {
                 name: "illegal char company_name".to_string(),
                 input: CareerParam {
                     company_name: Some("’ or ‘A’=‘A".to_string()),
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -575,10 +591,55 @@ mod tests {
                     "’ or ‘A’=‘A".to_string(),
                 )),
             },
+            TestCase {
+                name: "zero company_id".to_string(),
+                input: CareerParam {
+                    company_name: None,
+                    company_id: Some(0),
+                    department_name: None,
+                    office: None,
+                    years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
+                    employed: None,
+                    contract_type: None,
+                    profession: None,
+                    annual_income_in_man_yen: AnnualInComeInManYenParam {
+                        equal_or_more: None,
+                        equal_or_less: None,
+                    },
+                    is_manager: None,
+                    position_name: None,
+                    is_new_graduate: None,
+                    note: None,
+                },
+                expected: Err(CareerParamValidationError::NonPositiveCompanyId(0)),
+            },
+            TestCase {
+                name: "negative company_id".to_string(),
+                input: CareerParam {
+                    company_name: None,
+                    company_id: Some(-1),
+                    department_name: None,
+                    office: None,
+                    years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
+                    employed: None,
+                    contract_type: None,
+                    profession: None,
+                    annual_income_in_man_yen: AnnualInComeInManYenParam {
+                        equal_or_more: None,
+                        equal_or_less: None,
+                    },
+                    is_manager: None,
+                    position_name: None,
+                    is_new_graduate: None,
+                    note: None,
+                },
+                expected: Err(CareerParamValidationError::NonPositiveCompanyId(-1)),
+            },
             TestCase {
                 name: "invalid length department_name".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: Some("".to_string()),
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -604,6 +665,7 @@ mod tests {
                 name: "illegal char department_name".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: Some("’ or ‘A’=‘A".to_string()),
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -627,6 +689,7 @@ mod tests {
                 name: "invalid length office".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: Some("".to_string()),
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -652,6 +715,7 @@ mod tests {
                 name: "illegal char office".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: Some("’ or ‘A’=‘A".to_string()),
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -675,6 +739,7 @@ mod tests {
                 name: "valid years_of_service THREE_YEARS_OR_MORE".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(3), less_than: None },
@@ -696,6 +761,7 @@ mod tests {
                 name: "valid years_of_service FIVE_YEARS_OR_MORE".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(5), less_than: None },
@@ -717,6 +783,7 @@ mod tests {
                 name: "valid years_of_service TEN_YEARS_OR_MORE".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(10), less_than: None },
@@ -738,6 +805,7 @@ mod tests {
                 name: "valid years_of_service FIFTEEN_YEARS_OR_MORE".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(15), less_than: None },
@@ -759,6 +827,7 @@ mod tests {
                 name: "valid years_of_service TWENTY_YEARS_OR_MORE".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(20), less_than: None },
@@ -780,6 +849,7 @@ mod tests {
                 name: "invalid years_of_service 1".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: Some(1), less_than: None },
@@ -803,6 +873,7 @@ mod tests {
                 name: "invalid years_of_service 2".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: Some(4) },
@@ -826,6 +897,7 @@ mod tests {
                 name: "valid contract type regular".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -847,6 +919,7 @@ mod tests {
                 name: "valid contract type contract".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -868,6 +941,7 @@ mod tests {
                 name: "valid contract type other".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -889,6 +963,7 @@ mod tests {
                 name: "invalid contract type".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -912,6 +987,7 @@ mod tests {
                 name: "invalid length profession".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -937,6 +1013,7 @@ mod tests {
                 name: "illegal char profession".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -960,6 +1037,7 @@ mod tests {
                 name: "valid equal_or_more in annual_income_in_man_yen 0".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -981,6 +1059,7 @@ mod tests {
                 name: "valid equal_or_more in annual_income_in_man_yen max value".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1003,6 +1082,7 @@ mod tests {
                     .to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1030,6 +1110,7 @@ mod tests {
                 name: "valid equal_or_more in annual_income_in_man_yen max value".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1057,6 +1138,7 @@ mod tests {
                 name: "valid equal_or_less in annual_income_in_man_yen 0".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1078,6 +1160,7 @@ mod tests {
                 name: "valid equal_or_less in annual_income_in_man_yen max value".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1100,6 +1183,7 @@ mod tests {
                     .to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1127,6 +1211,7 @@ mod tests {
                 name: "valid equal_or_less in annual_income_in_man_yen max value".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1154,6 +1239,7 @@ mod tests {
                 name: "valid equal_or_less == equal_or_more".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1175,6 +1261,7 @@ mod tests {
                 name: "invalid equal_or_less exceeds equal_or_more".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1196,6 +1283,7 @@ mod tests {
                 name: "invalid length position name".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1221,6 +1309,7 @@ mod tests {
                 name: "illegal char position name".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1244,6 +1333,7 @@ mod tests {
                 name: "invalid length note".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
@@ -1269,6 +1359,7 @@ mod tests {
                 name: "illegal char position name".to_string(),
                 input: CareerParam {
                     company_name: None,
+                    company_id: None,
                     department_name: None,
                     office: None,
                     years_of_service: YearsOfServiceParam { equal_or_more: None, less_than: None },
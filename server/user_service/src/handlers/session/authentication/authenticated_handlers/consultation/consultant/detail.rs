@@ -1,14 +1,14 @@
 // Copyright 2022 Ken Miura
 
-use async_session::serde_json::{json, Value};
+use async_session::serde_json::Value;
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::{async_trait, Json};
-use common::opensearch::{search_documents, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
 use common::rating::round_rating_to_one_decimal_places;
+use common::util::validator::uuid_validator::validate_uuid;
 use common::{ApiError, ErrResp, RespResult, MAX_NUM_OF_CAREER_PER_USER_ACCOUNT};
 use entity::sea_orm::DatabaseConnection;
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -16,6 +16,7 @@ use crate::err::Code;
 use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
 use crate::handlers::session::authentication::user_operation::{FindUserInfoOperationImpl};
 
+use super::search_log::save_consultant_search_click;
 use super::{
     VALID_YEARS_OF_SERVICE_PERIOD_FIFTEEN, VALID_YEARS_OF_SERVICE_PERIOD_FIVE,
     VALID_YEARS_OF_SERVICE_PERIOD_TEN, VALID_YEARS_OF_SERVICE_PERIOD_THREE,
@@ -37,16 +38,27 @@ pub(crate) async fn get_consultant_detail(
     VerifiedUser { user_info }: VerifiedUser,
     query: Query<ConsultantDetailQuery>,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
 ) -> RespResult<ConsultantDetail> {
     let query = query.0;
-    let op = ConsultantDetailOperationImpl { pool, index_client };
-    handle_consultant_detail(user_info.account_id, query.consultant_id, op).await
+    let op = ConsultantDetailOperationImpl {
+        pool,
+        consultant_search_client,
+    };
+    handle_consultant_detail(
+        user_info.account_id,
+        query.consultant_id,
+        query.search_id,
+        op,
+    )
+    .await
 }
 
 #[derive(Deserialize)]
 pub(crate) struct ConsultantDetailQuery {
     consultant_id: i64,
+    /// 検索結果から詳細を表示する場合、検索結果と合わせて返されたsearch_idを指定する
+    search_id: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, PartialEq)]
@@ -77,12 +89,18 @@ pub(crate) struct ConsultantCareerDetail {
 #[async_trait]
 trait ConsultantDetailOperation {
     async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp>;
-    async fn search_consultant(&self, index_name: &str, query: &Value) -> Result<Value, ErrResp>;
+    /// 検索結果からコンサルタントの詳細を表示したことを非同期に保存する
+    fn save_search_click(&self, search_id: String, consultant_id: i64);
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp>;
 }
 
 struct ConsultantDetailOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -92,15 +110,25 @@ impl ConsultantDetailOperation for ConsultantDetailOperationImpl {
         super::super::check_if_consultant_is_available(consultant_id, &op).await
     }
 
-    async fn search_consultant(&self, index_name: &str, query: &Value) -> Result<Value, ErrResp> {
-        let result = search_documents(index_name, 0, 1, None, query, &self.index_client).await?;
-        Ok(result)
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp> {
+        self.consultant_search_client
+            .search_consultant(consultant_id, account_id)
+            .await
+    }
+
+    fn save_search_click(&self, search_id: String, consultant_id: i64) {
+        save_consultant_search_click(&self.pool, search_id, consultant_id)
     }
 }
 
 async fn handle_consultant_detail(
     account_id: i64,
     consultant_id: i64,
+    search_id: Option<String>,
     op: impl ConsultantDetailOperation,
 ) -> RespResult<ConsultantDetail> {
     if !consultant_id.is_positive() {
@@ -112,6 +140,17 @@ async fn handle_consultant_detail(
             }),
         ));
     }
+    if let Some(search_id) = search_id.as_ref() {
+        validate_uuid(search_id).map_err(|e| {
+            error!("failed to validate search_id ({}): {}", search_id, e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: common::err::Code::InvalidUuidFormat as u32,
+                }),
+            )
+        })?;
+    }
     let consultant_available = op.check_if_consultant_is_available(consultant_id).await?;
     if !consultant_available {
         error!(
@@ -129,56 +168,12 @@ async fn handle_consultant_detail(
         "query param (account_id (for consultant): {}, account_id: {})",
         consultant_id, account_id
     );
-    let query = create_query_json(consultant_id, account_id);
-    let result = op.search_consultant(INDEX_NAME, &query).await?;
-    parse_query_result(result)
-}
-
-fn create_query_json(account_id_for_consultant: i64, account_id: i64) -> Value {
-    json!({
-        "query": {
-            "bool": {
-                "must": [
-                    {
-                        "term": {
-                            "user_account_id": account_id_for_consultant
-                        }
-                    }
-                ],
-                "filter": [
-                    {
-                        "range": {
-                            "num_of_careers": {
-                                "gt": 0
-                            }
-                        }
-                    },
-                    {
-                        "exists": {
-                            "field": "fee_per_hour_in_yen"
-                        }
-                    },
-                    {
-                        "term": {
-                            "is_bank_account_registered": true
-                        }
-                    },
-                    {
-                        "term": {
-                            "disabled": false
-                        }
-                    }
-                ],
-                "must_not": [
-                    {
-                        "term": {
-                            "user_account_id": account_id
-                        }
-                    }
-                ]
-            }
-        }
-    })
+    let result = op.search_consultant(consultant_id, account_id).await?;
+    let consultant_detail = parse_query_result(result)?;
+    if let Some(search_id) = search_id {
+        op.save_search_click(search_id, consultant_id);
+    }
+    Ok(consultant_detail)
 }
 
 fn parse_query_result(query_result: Value) -> RespResult<ConsultantDetail> {
@@ -407,10 +402,13 @@ fn convert_years_of_service(years_of_service: i64) -> Result<String, ErrResp> {
 #[cfg(test)]
 mod tests {
 
+    use async_session::serde_json::json;
     use once_cell::sync::Lazy;
 
     use super::*;
 
+    const SEARCH_ID: &str = "b7a3c1e0f2d94a6b8c5e1f0a2b3c4d5e";
+
     #[derive(Clone, Debug)]
     struct ConsultantDetailOperationMock {
         consultant_id: i64,
@@ -431,11 +429,17 @@ mod tests {
 
         async fn search_consultant(
             &self,
-            _index_name: &str,
-            _query: &Value,
+            consultant_id: i64,
+            _account_id: i64,
         ) -> Result<Value, ErrResp> {
+            assert_eq!(self.consultant_id, consultant_id);
             Ok(self.query_result.clone())
         }
+
+        fn save_search_click(&self, search_id: String, consultant_id: i64) {
+            assert_eq!(SEARCH_ID, search_id);
+            assert_eq!(self.consultant_id, consultant_id);
+        }
     }
 
     #[derive(Debug)]
@@ -1215,6 +1219,7 @@ mod tests {
             let resp = handle_consultant_detail(
                 test_case.input.account_id,
                 test_case.input.consultant_id,
+                None,
                 test_case.input.op.clone(),
             )
             .await;
@@ -1233,4 +1238,48 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn handle_consultant_detail_succeeds_with_search_id() {
+        let test_case = TEST_CASE_SET
+            .iter()
+            .find(|t| t.expected.is_ok())
+            .expect("failed to get test case");
+
+        let resp = handle_consultant_detail(
+            test_case.input.account_id,
+            test_case.input.consultant_id,
+            Some(SEARCH_ID.to_string()),
+            test_case.input.op.clone(),
+        )
+        .await;
+
+        let result = resp.expect("failed to get Ok");
+        let expected_result = test_case.expected.as_ref().expect("failed to get Ok");
+        assert_eq!(expected_result.0, result.0);
+        assert_eq!(expected_result.1 .0, result.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_consultant_detail_fails_if_search_id_is_invalid() {
+        let test_case = TEST_CASE_SET
+            .iter()
+            .find(|t| t.expected.is_ok())
+            .expect("failed to get test case");
+
+        let resp = handle_consultant_detail(
+            test_case.input.account_id,
+            test_case.input.consultant_id,
+            Some("invalid-search-id".to_string()),
+            test_case.input.op.clone(),
+        )
+        .await;
+
+        let result = resp.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(
+            common::err::Code::InvalidUuidFormat as u32,
+            result.1 .0.code
+        );
+    }
 }
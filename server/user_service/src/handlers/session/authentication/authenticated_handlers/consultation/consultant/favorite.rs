@@ -0,0 +1,12 @@
+// Copyright 2023 Ken Miura
+
+//! コンサルタントのお気に入り登録、一覧、削除を扱うモジュール
+//!
+//! お気に入りに登録されたコンサルタントのアカウントが無効化、または削除された場合、そのコンサルタントは全ユーザーのお気に入りから削除される。
+
+pub(crate) mod delete;
+pub(crate) mod list;
+pub(crate) mod post;
+
+/// 一つのアカウントがお気に入りに登録できるコンサルタントの最大数
+const MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT: u64 = 100;
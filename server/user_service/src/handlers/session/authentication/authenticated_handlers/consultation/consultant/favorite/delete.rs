@@ -0,0 +1,176 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::{DatabaseConnection, EntityTrait};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+pub(crate) async fn delete_favorite_consultant(
+    VerifiedUser { user_info }: VerifiedUser,
+    query: Query<DeleteFavoriteConsultantQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<DeleteFavoriteConsultantResult> {
+    let query = query.0;
+    let op = DeleteFavoriteConsultantOperationImpl { pool };
+    handle_delete_favorite_consultant(user_info.account_id, query.consultant_id, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DeleteFavoriteConsultantQuery {
+    consultant_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct DeleteFavoriteConsultantResult {}
+
+#[async_trait]
+trait DeleteFavoriteConsultantOperation {
+    /// 削除したレコードの数を返す
+    async fn delete_favorite_consultant(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+    ) -> Result<u64, ErrResp>;
+}
+
+struct DeleteFavoriteConsultantOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl DeleteFavoriteConsultantOperation for DeleteFavoriteConsultantOperationImpl {
+    async fn delete_favorite_consultant(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+    ) -> Result<u64, ErrResp> {
+        let result =
+            entity::favorite_consultant::Entity::delete_by_id((account_id, consultant_id))
+                .exec(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!(
+                        "failed to delete favorite_consultant (user_account_id: {}, consultant_id: {}): {}",
+                        account_id, consultant_id, e
+                    );
+                    unexpected_err_resp()
+                })?;
+        Ok(result.rows_affected)
+    }
+}
+
+async fn handle_delete_favorite_consultant(
+    account_id: i64,
+    consultant_id: i64,
+    op: impl DeleteFavoriteConsultantOperation,
+) -> RespResult<DeleteFavoriteConsultantResult> {
+    if !consultant_id.is_positive() {
+        error!("consultant_id ({}) is not positive", consultant_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NonPositiveConsultantId as u32,
+            }),
+        ));
+    }
+    // 主キーにログインユーザーのアカウントIDを含めて削除するため、他のユーザーのお気に入りが削除されることはない
+    let num = op
+        .delete_favorite_consultant(account_id, consultant_id)
+        .await?;
+    if num == 0 {
+        error!(
+            "no favorite_consultant found (account_id: {}, consultant_id: {})",
+            account_id, consultant_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoFavoriteConsultantFound as u32,
+            }),
+        ));
+    }
+    info!(
+        "deleted favorite_consultant (account_id: {}, consultant_id: {})",
+        account_id, consultant_id
+    );
+    Ok((StatusCode::OK, Json(DeleteFavoriteConsultantResult {})))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct DeleteFavoriteConsultantOperationMock {
+        account_id: i64,
+        favorite_consultant_ids: Vec<i64>,
+    }
+
+    #[async_trait]
+    impl DeleteFavoriteConsultantOperation for DeleteFavoriteConsultantOperationMock {
+        async fn delete_favorite_consultant(
+            &self,
+            account_id: i64,
+            consultant_id: i64,
+        ) -> Result<u64, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            if self.favorite_consultant_ids.contains(&consultant_id) {
+                Ok(1)
+            } else {
+                Ok(0)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_delete_favorite_consultant_success() {
+        let account_id = 1;
+        let op = DeleteFavoriteConsultantOperationMock {
+            account_id,
+            favorite_consultant_ids: vec![2, 5],
+        };
+
+        let result = handle_delete_favorite_consultant(account_id, 5, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(DeleteFavoriteConsultantResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_delete_favorite_consultant_fail_no_favorite_consultant_found() {
+        let account_id = 1;
+        let op = DeleteFavoriteConsultantOperationMock {
+            account_id,
+            favorite_consultant_ids: vec![2],
+        };
+
+        let result = handle_delete_favorite_consultant(account_id, 3, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoFavoriteConsultantFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_delete_favorite_consultant_fail_non_positive_consultant_id() {
+        let account_id = 1;
+        let op = DeleteFavoriteConsultantOperationMock {
+            account_id,
+            favorite_consultant_ids: vec![],
+        };
+
+        let result = handle_delete_favorite_consultant(account_id, 0, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveConsultantId as u32, resp.1 .0.code);
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright 2023 Ken Miura
+
+use async_session::serde_json::Value;
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use tracing::{error, info};
+
+use super::super::search::{create_consultant_description, ConsultantDescription};
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+pub(crate) async fn get_favorite_consultants(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
+) -> RespResult<FavoriteConsultantsResult> {
+    let op = FavoriteConsultantsOperationImpl {
+        pool,
+        consultant_search_client,
+    };
+    handle_favorite_consultants(user_info.account_id, op).await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct FavoriteConsultantsResult {
+    consultants: Vec<ConsultantDescription>,
+}
+
+#[async_trait]
+trait FavoriteConsultantsOperation {
+    /// お気に入りに登録した日時の新しい順にコンサルタントのIDを返す
+    async fn filter_favorite_consultant_ids_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<i64>, ErrResp>;
+
+    /// 検索結果と同じ条件（職務経歴、相談料及び口座が登録済で、無効化されていない）を満たすコンサルタントのみを返す
+    async fn search_consultants(
+        &self,
+        consultant_ids: &[i64],
+        account_id: i64,
+    ) -> Result<Value, ErrResp>;
+}
+
+struct FavoriteConsultantsOperationImpl {
+    pool: DatabaseConnection,
+    consultant_search_client: ConsultantSearchClient,
+}
+
+#[async_trait]
+impl FavoriteConsultantsOperation for FavoriteConsultantsOperationImpl {
+    async fn filter_favorite_consultant_ids_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<i64>, ErrResp> {
+        let models = entity::favorite_consultant::Entity::find()
+            .filter(entity::favorite_consultant::Column::UserAccountId.eq(account_id))
+            .order_by_desc(entity::favorite_consultant::Column::CreatedAt)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to filter favorite_consultant (user_account_id: {}): {}",
+                    account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models.into_iter().map(|m| m.consultant_id).collect())
+    }
+
+    async fn search_consultants(
+        &self,
+        consultant_ids: &[i64],
+        account_id: i64,
+    ) -> Result<Value, ErrResp> {
+        self.consultant_search_client
+            .search_consultants_by_ids(consultant_ids, account_id)
+            .await
+    }
+}
+
+async fn handle_favorite_consultants(
+    account_id: i64,
+    op: impl FavoriteConsultantsOperation,
+) -> RespResult<FavoriteConsultantsResult> {
+    let consultant_ids = op
+        .filter_favorite_consultant_ids_by_account_id(account_id)
+        .await?;
+    if consultant_ids.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(FavoriteConsultantsResult {
+                consultants: vec![],
+            }),
+        ));
+    }
+    info!(
+        "favorite consultant ids (account_id: {}, consultant_ids: {:?})",
+        account_id, consultant_ids
+    );
+    let result = op.search_consultants(&consultant_ids, account_id).await?;
+    let consultants = parse_query_result(result, &consultant_ids)?;
+    Ok((
+        StatusCode::OK,
+        Json(FavoriteConsultantsResult { consultants }),
+    ))
+}
+
+/// 検索結果をお気に入りに登録した順（consultant_idsの順）に並べて返す
+fn parse_query_result(
+    query_result: Value,
+    consultant_ids: &[i64],
+) -> Result<Vec<ConsultantDescription>, ErrResp> {
+    let took = query_result["took"].as_i64().ok_or_else(|| {
+        error!("failed to get processing time: {}", query_result);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    info!("opensearch took {} milliseconds", took);
+
+    let hits = query_result["hits"]["hits"].as_array().ok_or_else(|| {
+        error!("failed to get hits: {}", query_result);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError {
+                code: Code::UnexpectedErr as u32,
+            }),
+        )
+    })?;
+    let mut consultants = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let consultant_description = create_consultant_description(hit)?;
+        consultants.push(consultant_description);
+    }
+    consultants.sort_by_key(|c| {
+        consultant_ids
+            .iter()
+            .position(|id| *id == c.consultant_id)
+            .unwrap_or(consultant_ids.len())
+    });
+    Ok(consultants)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use async_session::serde_json::json;
+
+    use super::super::super::search::ConsultantCareerDescription;
+    use super::*;
+
+    struct FavoriteConsultantsOperationMock {
+        account_id: i64,
+        consultant_ids: Vec<i64>,
+        query_result: Value,
+    }
+
+    #[async_trait]
+    impl FavoriteConsultantsOperation for FavoriteConsultantsOperationMock {
+        async fn filter_favorite_consultant_ids_by_account_id(
+            &self,
+            account_id: i64,
+        ) -> Result<Vec<i64>, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            Ok(self.consultant_ids.clone())
+        }
+
+        async fn search_consultants(
+            &self,
+            consultant_ids: &[i64],
+            account_id: i64,
+        ) -> Result<Value, ErrResp> {
+            assert!(!self.consultant_ids.is_empty());
+            assert_eq!(self.consultant_ids.as_slice(), consultant_ids);
+            assert_eq!(self.account_id, account_id);
+            Ok(self.query_result.clone())
+        }
+    }
+
+    fn create_hit(consultant_id: i64, fee_per_hour_in_yen: i32, company_name: &str) -> Value {
+        json!({
+            "_index" : "users",
+            "_id" : consultant_id.to_string(),
+            "_score" : 1.0,
+            "_source" : {
+              "careers" : [
+                {
+                  "annual_income_in_man_yen" : null,
+                  "career_id" : consultant_id,
+                  "company_name" : company_name,
+                  "contract_type" : "regular",
+                  "department_name" : null,
+                  "employed" : true,
+                  "is_manager" : false,
+                  "is_new_graduate" : true,
+                  "note" : null,
+                  "office" : null,
+                  "position_name" : null,
+                  "profession" : null,
+                  "years_of_service" : 3
+                }
+              ],
+              "fee_per_hour_in_yen" : fee_per_hour_in_yen,
+              "is_bank_account_registered" : true,
+              "disabled" : false,
+              "num_of_careers" : 1,
+              "rating" : null,
+              "num_of_rated": 0,
+              "user_account_id" : consultant_id
+            }
+        })
+    }
+
+    fn create_expected_consultant_description(
+        consultant_id: i64,
+        fee_per_hour_in_yen: i32,
+        company_name: &str,
+    ) -> ConsultantDescription {
+        ConsultantDescription {
+            consultant_id,
+            fee_per_hour_in_yen,
+            rating: None,
+            num_of_rated: 0,
+            careers: vec![ConsultantCareerDescription {
+                company_name: company_name.to_string(),
+                profession: None,
+                office: None,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultants_success() {
+        let account_id = 1;
+        // お気に入りに登録した日時の新しい順
+        let consultant_ids = vec![5, 2, 3];
+        // consultant_id 3は無効化された等の理由で検索結果に含まれない
+        let op = FavoriteConsultantsOperationMock {
+            account_id,
+            consultant_ids,
+            query_result: json!({
+              "took" : 2,
+              "timed_out" : false,
+              "_shards" : {
+                "total" : 1,
+                "successful" : 1,
+                "skipped" : 0,
+                "failed" : 0
+              },
+              "hits" : {
+                "total" : {
+                  "value" : 2,
+                  "relation" : "eq"
+                },
+                "max_score" : 1.0,
+                "hits" : [
+                  create_hit(2, 4500, "テスト１"),
+                  create_hit(5, 6000, "テスト２")
+                ]
+              }
+            }),
+        };
+
+        let result = handle_favorite_consultants(account_id, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            FavoriteConsultantsResult {
+                consultants: vec![
+                    create_expected_consultant_description(5, 6000, "テスト２"),
+                    create_expected_consultant_description(2, 4500, "テスト１"),
+                ]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultants_success_no_favorite_consultants() {
+        let account_id = 1;
+        let op = FavoriteConsultantsOperationMock {
+            account_id,
+            consultant_ids: vec![],
+            query_result: json!({}),
+        };
+
+        let result = handle_favorite_consultants(account_id, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            FavoriteConsultantsResult {
+                consultants: vec![]
+            },
+            resp.1 .0
+        );
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT;
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+use crate::handlers::session::authentication::user_operation::FindUserInfoOperationImpl;
+
+pub(crate) async fn post_favorite_consultant(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<FavoriteConsultantParam>,
+) -> RespResult<FavoriteConsultantResult> {
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = FavoriteConsultantOperationImpl { pool };
+    handle_favorite_consultant(
+        user_info.account_id,
+        req.consultant_id,
+        current_date_time,
+        op,
+    )
+    .await
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct FavoriteConsultantParam {
+    consultant_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct FavoriteConsultantResult {}
+
+#[async_trait]
+trait FavoriteConsultantOperation {
+    async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp>;
+
+    async fn check_if_favorite_consultant_exists(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+    ) -> Result<bool, ErrResp>;
+
+    async fn count_favorite_consultants(&self, account_id: i64) -> Result<u64, ErrResp>;
+
+    async fn create_favorite_consultant(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp>;
+}
+
+struct FavoriteConsultantOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl FavoriteConsultantOperation for FavoriteConsultantOperationImpl {
+    async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp> {
+        let op = FindUserInfoOperationImpl::new(&self.pool);
+        super::super::super::check_if_consultant_is_available(consultant_id, &op).await
+    }
+
+    async fn check_if_favorite_consultant_exists(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+    ) -> Result<bool, ErrResp> {
+        let model = entity::favorite_consultant::Entity::find_by_id((account_id, consultant_id))
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find favorite_consultant (user_account_id: {}, consultant_id: {}): {}",
+                    account_id, consultant_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.is_some())
+    }
+
+    async fn count_favorite_consultants(&self, account_id: i64) -> Result<u64, ErrResp> {
+        let num = entity::favorite_consultant::Entity::find()
+            .filter(entity::favorite_consultant::Column::UserAccountId.eq(account_id))
+            .count(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to count favorite_consultant (user_account_id: {}): {}",
+                    account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(num)
+    }
+
+    async fn create_favorite_consultant(
+        &self,
+        account_id: i64,
+        consultant_id: i64,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp> {
+        let active_model = entity::favorite_consultant::ActiveModel {
+            user_account_id: Set(account_id),
+            consultant_id: Set(consultant_id),
+            created_at: Set(current_date_time),
+        };
+        let _ = active_model.insert(&self.pool).await.map_err(|e| {
+            error!(
+                "failed to insert favorite_consultant (user_account_id: {}, consultant_id: {}): {}",
+                account_id, consultant_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(())
+    }
+}
+
+async fn handle_favorite_consultant(
+    account_id: i64,
+    consultant_id: i64,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl FavoriteConsultantOperation,
+) -> RespResult<FavoriteConsultantResult> {
+    if !consultant_id.is_positive() {
+        error!("consultant_id ({}) is not positive", consultant_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NonPositiveConsultantId as u32,
+            }),
+        ));
+    }
+    if account_id == consultant_id {
+        error!(
+            "account_id ({}) is same as consultant_id ({})",
+            account_id, consultant_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::FavoriteConsultantIsSelf as u32,
+            }),
+        ));
+    }
+    let consultant_available = op.check_if_consultant_is_available(consultant_id).await?;
+    if !consultant_available {
+        error!(
+            "consultant is not available (consultant_id: {})",
+            consultant_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ConsultantIsNotAvailable as u32,
+            }),
+        ));
+    }
+
+    // 既に登録済の場合は何もせずに成功として扱う（ボタンの連打等による重複リクエストを想定）
+    let exists = op
+        .check_if_favorite_consultant_exists(account_id, consultant_id)
+        .await?;
+    if exists {
+        info!(
+            "favorite_consultant already exists (account_id: {}, consultant_id: {})",
+            account_id, consultant_id
+        );
+        return Ok((StatusCode::OK, Json(FavoriteConsultantResult {})));
+    }
+
+    let num = op.count_favorite_consultants(account_id).await?;
+    // DBの分離レベルがSerializeでないため、MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNTを超える可能性を考慮し、">="とする
+    if num >= MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT {
+        error!(
+            "reach max favorite consultants limit (account id: {}, num: {}, max: {})",
+            account_id, num, MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ReachFavoriteConsultantsLimit as u32,
+            }),
+        ));
+    }
+
+    op.create_favorite_consultant(account_id, consultant_id, current_date_time)
+        .await?;
+    info!(
+        "added favorite consultant (account_id: {}, consultant_id: {}) at {}",
+        account_id, consultant_id, current_date_time
+    );
+
+    Ok((StatusCode::OK, Json(FavoriteConsultantResult {})))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    struct FavoriteConsultantOperationMock {
+        account_id: i64,
+        consultant_id: i64,
+        consultant_available: bool,
+        already_exists: bool,
+        num_of_favorite_consultants: u64,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl FavoriteConsultantOperation for FavoriteConsultantOperationMock {
+        async fn check_if_consultant_is_available(
+            &self,
+            consultant_id: i64,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.consultant_id, consultant_id);
+            Ok(self.consultant_available)
+        }
+
+        async fn check_if_favorite_consultant_exists(
+            &self,
+            account_id: i64,
+            consultant_id: i64,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            assert_eq!(self.consultant_id, consultant_id);
+            Ok(self.already_exists)
+        }
+
+        async fn count_favorite_consultants(&self, account_id: i64) -> Result<u64, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            Ok(self.num_of_favorite_consultants)
+        }
+
+        async fn create_favorite_consultant(
+            &self,
+            account_id: i64,
+            consultant_id: i64,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<(), ErrResp> {
+            assert!(!self.already_exists);
+            assert_eq!(self.account_id, account_id);
+            assert_eq!(self.consultant_id, consultant_id);
+            assert_eq!(self.current_date_time, current_date_time);
+            Ok(())
+        }
+    }
+
+    fn create_dummy_current_date_time() -> DateTime<FixedOffset> {
+        JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 10, 12, 0, 0)
+            .unwrap()
+    }
+
+    fn create_default_op_mock(
+        account_id: i64,
+        consultant_id: i64,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> FavoriteConsultantOperationMock {
+        FavoriteConsultantOperationMock {
+            account_id,
+            consultant_id,
+            consultant_available: true,
+            already_exists: false,
+            num_of_favorite_consultants: 0,
+            current_date_time,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_success() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let current_date_time = create_dummy_current_date_time();
+        let mut op = create_default_op_mock(account_id, consultant_id, current_date_time);
+        op.num_of_favorite_consultants = MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT - 1;
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(FavoriteConsultantResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_success_already_exists() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let current_date_time = create_dummy_current_date_time();
+        let mut op = create_default_op_mock(account_id, consultant_id, current_date_time);
+        op.already_exists = true;
+        op.num_of_favorite_consultants = MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT;
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(FavoriteConsultantResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_fail_non_positive_consultant_id() {
+        let account_id = 1;
+        let consultant_id = 0;
+        let current_date_time = create_dummy_current_date_time();
+        let op = create_default_op_mock(account_id, consultant_id, current_date_time);
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveConsultantId as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_fail_self() {
+        let account_id = 1;
+        let consultant_id = account_id;
+        let current_date_time = create_dummy_current_date_time();
+        let op = create_default_op_mock(account_id, consultant_id, current_date_time);
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::FavoriteConsultantIsSelf as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_fail_consultant_is_not_available() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let current_date_time = create_dummy_current_date_time();
+        let mut op = create_default_op_mock(account_id, consultant_id, current_date_time);
+        op.consultant_available = false;
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ConsultantIsNotAvailable as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_favorite_consultant_fail_reach_limit() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let current_date_time = create_dummy_current_date_time();
+        let mut op = create_default_op_mock(account_id, consultant_id, current_date_time);
+        op.num_of_favorite_consultants = MAX_NUM_OF_FAVORITE_CONSULTANTS_PER_USER_ACCOUNT;
+
+        let result =
+            handle_favorite_consultant(account_id, consultant_id, current_date_time, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ReachFavoriteConsultantsLimit as u32, resp.1 .0.code);
+    }
+}
@@ -6,7 +6,7 @@ use crate::handlers::session::authentication::authenticated_handlers::fee_per_ho
     MAX_FEE_PER_HOUR_IN_YEN, MIN_FEE_PER_HOUR_IN_YEN,
 };
 
-use super::search::FeePerHourInYenParam;
+use common::opensearch::consultant_query::FeePerHourInYenParam;
 
 pub(super) fn validate_fee_per_hour_in_yen_param(
     fee_per_hour_in_yen: &FeePerHourInYenParam,
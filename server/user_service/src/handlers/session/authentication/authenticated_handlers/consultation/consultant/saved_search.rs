@@ -0,0 +1,12 @@
+// Copyright 2023 Ken Miura
+
+//! コンサルタントの検索条件の保存、一覧、削除を扱うモジュール
+//!
+//! 新たに条件に一致したコンサルタントの通知（alert_enabledがtrueの場合）は、定期実行ツール（notify_saved_search_matches）が行う。
+
+pub(crate) mod delete;
+pub(crate) mod list;
+pub(crate) mod post;
+
+/// 一つのアカウントが保存できる検索条件の最大数
+const MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT: u64 = 5;
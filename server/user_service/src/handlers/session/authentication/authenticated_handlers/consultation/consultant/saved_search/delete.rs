@@ -0,0 +1,229 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use common::{ApiError, ErrResp, ErrRespStruct, RespResult};
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionError, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+pub(crate) async fn delete_saved_search(
+    VerifiedUser { user_info }: VerifiedUser,
+    query: Query<DeleteSavedSearchQuery>,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<DeleteSavedSearchResult> {
+    let query = query.0;
+    let op = DeleteSavedSearchOperationImpl { pool };
+    handle_delete_saved_search(user_info.account_id, query.saved_search_id, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DeleteSavedSearchQuery {
+    saved_search_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct DeleteSavedSearchResult {}
+
+#[async_trait]
+trait DeleteSavedSearchOperation {
+    /// ユーザーが保存した検索条件のsaved_search_idを返す
+    async fn filter_saved_search_ids_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<i64>, ErrResp>;
+
+    /// 検索条件と、その検索条件に一致したとして記録済のコンサルタントを削除する
+    async fn delete_saved_search(&self, saved_search_id: i64) -> Result<(), ErrResp>;
+}
+
+struct DeleteSavedSearchOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl DeleteSavedSearchOperation for DeleteSavedSearchOperationImpl {
+    async fn filter_saved_search_ids_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<i64>, ErrResp> {
+        let models = entity::saved_search::Entity::find()
+            .filter(entity::saved_search::Column::UserAccountId.eq(account_id))
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to filter saved_search (user_account_id: {}): {}",
+                    account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(models.into_iter().map(|m| m.saved_search_id).collect())
+    }
+
+    async fn delete_saved_search(&self, saved_search_id: i64) -> Result<(), ErrResp> {
+        self.pool
+            .transaction::<_, (), ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let _ = entity::saved_search_matched_consultant::Entity::delete_many()
+                        .filter(
+                            entity::saved_search_matched_consultant::Column::SavedSearchId
+                                .eq(saved_search_id),
+                        )
+                        .exec(txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to delete saved_search_matched_consultant (saved_search_id: {}): {}",
+                                saved_search_id, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+
+                    let _ = entity::saved_search::Entity::delete_by_id(saved_search_id)
+                        .exec(txn)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "failed to delete saved_search (saved_search_id: {}): {}",
+                                saved_search_id, e
+                            );
+                            ErrRespStruct {
+                                err_resp: unexpected_err_resp(),
+                            }
+                        })?;
+
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!("failed to delete saved_search: {}", err_resp_struct);
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(())
+    }
+}
+
+async fn handle_delete_saved_search(
+    account_id: i64,
+    saved_search_id: i64,
+    op: impl DeleteSavedSearchOperation,
+) -> RespResult<DeleteSavedSearchResult> {
+    if !saved_search_id.is_positive() {
+        error!("saved_search_id ({}) is not positive", saved_search_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NonPositiveSavedSearchId as u32,
+            }),
+        ));
+    }
+    // 他のユーザーが保存した検索条件の削除を防ぐため、必ずログインユーザーのアカウントIDに紐付いた検索条件かチェック
+    let saved_search_ids = op.filter_saved_search_ids_by_account_id(account_id).await?;
+    if !saved_search_ids.contains(&saved_search_id) {
+        error!(
+            "no saved_search associated with user account found (account_id: {}, saved_search_id: {})",
+            account_id, saved_search_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoSavedSearchFound as u32,
+            }),
+        ));
+    }
+    op.delete_saved_search(saved_search_id).await?;
+    info!(
+        "deleted saved_search (account_id: {}, saved_search_id: {})",
+        account_id, saved_search_id
+    );
+    Ok((StatusCode::OK, Json(DeleteSavedSearchResult {})))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct DeleteSavedSearchOperationMock {
+        account_id: i64,
+        saved_search_ids: Vec<i64>,
+    }
+
+    #[async_trait]
+    impl DeleteSavedSearchOperation for DeleteSavedSearchOperationMock {
+        async fn filter_saved_search_ids_by_account_id(
+            &self,
+            account_id: i64,
+        ) -> Result<Vec<i64>, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            Ok(self.saved_search_ids.clone())
+        }
+
+        async fn delete_saved_search(&self, saved_search_id: i64) -> Result<(), ErrResp> {
+            assert!(self.saved_search_ids.contains(&saved_search_id));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_delete_saved_search_success() {
+        let account_id = 1;
+        let op = DeleteSavedSearchOperationMock {
+            account_id,
+            saved_search_ids: vec![2, 5],
+        };
+
+        let result = handle_delete_saved_search(account_id, 5, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(DeleteSavedSearchResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_delete_saved_search_fail_other_users_saved_search() {
+        let account_id = 1;
+        let op = DeleteSavedSearchOperationMock {
+            account_id,
+            saved_search_ids: vec![2],
+        };
+
+        let result = handle_delete_saved_search(account_id, 3, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoSavedSearchFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_delete_saved_search_fail_non_positive_saved_search_id() {
+        let account_id = 1;
+        let op = DeleteSavedSearchOperationMock {
+            account_id,
+            saved_search_ids: vec![],
+        };
+
+        let result = handle_delete_saved_search(account_id, 0, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveSavedSearchId as u32, resp.1 .0.code);
+    }
+}
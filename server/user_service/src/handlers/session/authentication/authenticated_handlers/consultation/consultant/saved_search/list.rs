@@ -0,0 +1,185 @@
+// Copyright 2023 Ken Miura
+
+use async_session::serde_json;
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::Datelike;
+use common::opensearch::consultant_query::{CareerParam, FeePerHourInYenParam, SearchCondition};
+use common::util::Ymd;
+use common::{ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+pub(crate) async fn get_saved_searches(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+) -> RespResult<SavedSearchesResult> {
+    let op = SavedSearchesOperationImpl { pool };
+    handle_saved_searches(user_info.account_id, op).await
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct SavedSearchesResult {
+    saved_searches: Vec<SavedSearch>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq)]
+struct SavedSearch {
+    saved_search_id: i64,
+    career_param: CareerParam,
+    fee_per_hour_in_yen_param: FeePerHourInYenParam,
+    alert_enabled: bool,
+    created_date_in_jst: Ymd,
+}
+
+#[async_trait]
+trait SavedSearchesOperation {
+    /// 保存した日時の古い順に返す
+    async fn filter_saved_searches_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<SavedSearch>, ErrResp>;
+}
+
+struct SavedSearchesOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SavedSearchesOperation for SavedSearchesOperationImpl {
+    async fn filter_saved_searches_by_account_id(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<SavedSearch>, ErrResp> {
+        let models = entity::saved_search::Entity::find()
+            .filter(entity::saved_search::Column::UserAccountId.eq(account_id))
+            .order_by_asc(entity::saved_search::Column::SavedSearchId)
+            .all(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to filter saved_search (user_account_id: {}): {}",
+                    account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        models
+            .into_iter()
+            .map(|m| {
+                let search_condition = serde_json::from_value::<SearchCondition>(
+                    m.search_param.clone(),
+                )
+                .map_err(|e| {
+                    error!(
+                        "failed to deserialize search_param (saved_search_id: {}, search_param: {}): {}",
+                        m.saved_search_id, m.search_param, e
+                    );
+                    unexpected_err_resp()
+                })?;
+                let created_at = m.created_at.with_timezone(&(*JAPANESE_TIME_ZONE));
+                Ok(SavedSearch {
+                    saved_search_id: m.saved_search_id,
+                    career_param: search_condition.career_param,
+                    fee_per_hour_in_yen_param: search_condition.fee_per_hour_in_yen_param,
+                    alert_enabled: m.alert_enabled,
+                    created_date_in_jst: Ymd {
+                        year: created_at.year(),
+                        month: created_at.month(),
+                        day: created_at.day(),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+async fn handle_saved_searches(
+    account_id: i64,
+    op: impl SavedSearchesOperation,
+) -> RespResult<SavedSearchesResult> {
+    let saved_searches = op.filter_saved_searches_by_account_id(account_id).await?;
+    Ok((StatusCode::OK, Json(SavedSearchesResult { saved_searches })))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use common::opensearch::consultant_query::{AnnualInComeInManYenParam, YearsOfServiceParam};
+
+    use super::*;
+
+    struct SavedSearchesOperationMock {
+        account_id: i64,
+        saved_searches: Vec<SavedSearch>,
+    }
+
+    #[async_trait]
+    impl SavedSearchesOperation for SavedSearchesOperationMock {
+        async fn filter_saved_searches_by_account_id(
+            &self,
+            account_id: i64,
+        ) -> Result<Vec<SavedSearch>, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            Ok(self.saved_searches.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_saved_searches_success() {
+        let account_id = 1;
+        let saved_search = SavedSearch {
+            saved_search_id: 3,
+            career_param: CareerParam {
+                company_name: None,
+                company_id: Some(12),
+                department_name: None,
+                office: None,
+                years_of_service: YearsOfServiceParam {
+                    equal_or_more: None,
+                    less_than: None,
+                },
+                employed: None,
+                contract_type: None,
+                profession: None,
+                annual_income_in_man_yen: AnnualInComeInManYenParam {
+                    equal_or_more: None,
+                    equal_or_less: None,
+                },
+                is_manager: None,
+                position_name: None,
+                is_new_graduate: None,
+                note: None,
+            },
+            fee_per_hour_in_yen_param: FeePerHourInYenParam {
+                equal_or_more: None,
+                equal_or_less: Some(5000),
+            },
+            alert_enabled: false,
+            created_date_in_jst: Ymd {
+                year: 2023,
+                month: 9,
+                day: 5,
+            },
+        };
+        let op = SavedSearchesOperationMock {
+            account_id,
+            saved_searches: vec![saved_search.clone()],
+        };
+
+        let result = handle_saved_searches(account_id, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            SavedSearchesResult {
+                saved_searches: vec![saved_search]
+            },
+            resp.1 .0
+        );
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright 2023 Ken Miura
+
+use async_session::serde_json;
+use axum::async_trait;
+use axum::http::StatusCode;
+use axum::{extract::State, Json};
+use chrono::{DateTime, FixedOffset};
+use common::opensearch::consultant_query::{CareerParam, FeePerHourInYenParam, SearchCondition};
+use common::{ApiError, ErrResp, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    Set,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use super::super::career_param_validator::validate_career_param;
+use super::super::fee_per_hour_in_yen_param_validator::validate_fee_per_hour_in_yen_param;
+use super::super::search::{
+    create_invalid_career_param_err, create_invalid_fee_per_hour_in_yen_param_err,
+};
+use super::MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT;
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+pub(crate) async fn post_saved_search(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<SavedSearchParam>,
+) -> RespResult<SavedSearchResult> {
+    let unsubscribe_token = Uuid::new_v4().simple().to_string();
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = SavedSearchOperationImpl { pool };
+    handle_saved_search(
+        user_info.account_id,
+        req,
+        unsubscribe_token,
+        current_date_time,
+        op,
+    )
+    .await
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct SavedSearchParam {
+    career_param: CareerParam,
+    fee_per_hour_in_yen_param: FeePerHourInYenParam,
+    /// trueの場合、新たに条件に一致したコンサルタントをメールで通知する
+    alert_enabled: bool,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct SavedSearchResult {
+    saved_search_id: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SavedSearch {
+    user_account_id: i64,
+    search_condition: SearchCondition,
+    alert_enabled: bool,
+    unsubscribe_token: String,
+    created_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait SavedSearchOperation {
+    async fn count_saved_searches(&self, account_id: i64) -> Result<u64, ErrResp>;
+
+    /// 検索条件を保存し、saved_search_idを返す
+    async fn create_saved_search(&self, saved_search: SavedSearch) -> Result<i64, ErrResp>;
+}
+
+struct SavedSearchOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl SavedSearchOperation for SavedSearchOperationImpl {
+    async fn count_saved_searches(&self, account_id: i64) -> Result<u64, ErrResp> {
+        let num = entity::saved_search::Entity::find()
+            .filter(entity::saved_search::Column::UserAccountId.eq(account_id))
+            .count(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to count saved_search (user_account_id: {}): {}",
+                    account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(num)
+    }
+
+    async fn create_saved_search(&self, saved_search: SavedSearch) -> Result<i64, ErrResp> {
+        let search_param = serde_json::to_value(&saved_search.search_condition).map_err(|e| {
+            error!(
+                "failed to serialize search condition ({:?}): {}",
+                saved_search.search_condition, e
+            );
+            unexpected_err_resp()
+        })?;
+        let active_model = entity::saved_search::ActiveModel {
+            user_account_id: Set(saved_search.user_account_id),
+            search_param: Set(search_param),
+            alert_enabled: Set(saved_search.alert_enabled),
+            unsubscribe_token: Set(saved_search.unsubscribe_token.clone()),
+            created_at: Set(saved_search.created_at),
+            last_checked_at: Set(None),
+            ..Default::default()
+        };
+        let result = active_model.insert(&self.pool).await.map_err(|e| {
+            error!(
+                "failed to insert saved_search (user_account_id: {}): {}",
+                saved_search.user_account_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(result.saved_search_id)
+    }
+}
+
+async fn handle_saved_search(
+    account_id: i64,
+    param: SavedSearchParam,
+    unsubscribe_token: String,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl SavedSearchOperation,
+) -> RespResult<SavedSearchResult> {
+    validate_career_param(&param.career_param).map_err(|e| {
+        error!("invalid career_param: {} (account id: {})", e, account_id);
+        create_invalid_career_param_err(&e)
+    })?;
+    validate_fee_per_hour_in_yen_param(&param.fee_per_hour_in_yen_param).map_err(|e| {
+        error!(
+            "invalid fee_per_hour_in_yen_param: {} (account id: {})",
+            e, account_id
+        );
+        create_invalid_fee_per_hour_in_yen_param_err(&e)
+    })?;
+
+    let num = op.count_saved_searches(account_id).await?;
+    // DBの分離レベルがSerializeでないため、MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNTを超える可能性を考慮し、">="とする
+    if num >= MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT {
+        error!(
+            "reach max saved searches limit (account id: {}, num: {}, max: {})",
+            account_id, num, MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ReachSavedSearchesLimit as u32,
+            }),
+        ));
+    }
+
+    let saved_search = SavedSearch {
+        user_account_id: account_id,
+        search_condition: SearchCondition {
+            career_param: param.career_param,
+            fee_per_hour_in_yen_param: param.fee_per_hour_in_yen_param,
+        },
+        alert_enabled: param.alert_enabled,
+        unsubscribe_token,
+        created_at: current_date_time,
+    };
+    let saved_search_id = op.create_saved_search(saved_search).await?;
+    info!(
+        "saved search (saved_search_id: {}, account id: {}) at {}",
+        saved_search_id, account_id, current_date_time
+    );
+
+    Ok((StatusCode::OK, Json(SavedSearchResult { saved_search_id })))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::TimeZone;
+    use common::opensearch::consultant_query::{AnnualInComeInManYenParam, YearsOfServiceParam};
+
+    use super::*;
+
+    struct SavedSearchOperationMock {
+        num_of_saved_searches: u64,
+        saved_search: SavedSearch,
+        saved_search_id: i64,
+    }
+
+    #[async_trait]
+    impl SavedSearchOperation for SavedSearchOperationMock {
+        async fn count_saved_searches(&self, account_id: i64) -> Result<u64, ErrResp> {
+            assert_eq!(self.saved_search.user_account_id, account_id);
+            Ok(self.num_of_saved_searches)
+        }
+
+        async fn create_saved_search(&self, saved_search: SavedSearch) -> Result<i64, ErrResp> {
+            assert_eq!(self.saved_search, saved_search);
+            Ok(self.saved_search_id)
+        }
+    }
+
+    fn create_empty_career_param() -> CareerParam {
+        CareerParam {
+            company_name: None,
+            company_id: None,
+            department_name: None,
+            office: None,
+            years_of_service: YearsOfServiceParam {
+                equal_or_more: None,
+                less_than: None,
+            },
+            employed: None,
+            contract_type: None,
+            profession: None,
+            annual_income_in_man_yen: AnnualInComeInManYenParam {
+                equal_or_more: None,
+                equal_or_less: None,
+            },
+            is_manager: None,
+            position_name: None,
+            is_new_graduate: None,
+            note: None,
+        }
+    }
+
+    fn create_dummy_saved_search(
+        account_id: i64,
+        career_param: CareerParam,
+        fee_per_hour_in_yen_param: FeePerHourInYenParam,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> SavedSearch {
+        SavedSearch {
+            user_account_id: account_id,
+            search_condition: SearchCondition {
+                career_param,
+                fee_per_hour_in_yen_param,
+            },
+            alert_enabled: true,
+            unsubscribe_token: "b860dc5138d146ac8127b0780fabce7d".to_string(),
+            created_at: current_date_time,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_success() {
+        let account_id = 1;
+        let mut career_param = create_empty_career_param();
+        career_param.company_name = Some("テスト株式会社".to_string());
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: Some(3000),
+            equal_or_less: None,
+        };
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let saved_search = create_dummy_saved_search(
+            account_id,
+            career_param.clone(),
+            fee_per_hour_in_yen_param.clone(),
+            current_date_time,
+        );
+        let op = SavedSearchOperationMock {
+            num_of_saved_searches: MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT - 1,
+            saved_search: saved_search.clone(),
+            saved_search_id: 10,
+        };
+        let param = SavedSearchParam {
+            career_param,
+            fee_per_hour_in_yen_param,
+            alert_enabled: true,
+        };
+
+        let result = handle_saved_search(
+            account_id,
+            param,
+            saved_search.unsubscribe_token,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            SavedSearchResult {
+                saved_search_id: 10
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_fail_reach_limit() {
+        let account_id = 1;
+        let career_param = create_empty_career_param();
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: None,
+            equal_or_less: None,
+        };
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let saved_search = create_dummy_saved_search(
+            account_id,
+            career_param.clone(),
+            fee_per_hour_in_yen_param.clone(),
+            current_date_time,
+        );
+        let op = SavedSearchOperationMock {
+            num_of_saved_searches: MAX_NUM_OF_SAVED_SEARCHES_PER_USER_ACCOUNT,
+            saved_search: saved_search.clone(),
+            saved_search_id: 10,
+        };
+        let param = SavedSearchParam {
+            career_param,
+            fee_per_hour_in_yen_param,
+            alert_enabled: true,
+        };
+
+        let result = handle_saved_search(
+            account_id,
+            param,
+            saved_search.unsubscribe_token,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ReachSavedSearchesLimit as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_fail_invalid_fee_per_hour_in_yen_param() {
+        let account_id = 1;
+        let career_param = create_empty_career_param();
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: Some(10000),
+            equal_or_less: Some(5000),
+        };
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let saved_search = create_dummy_saved_search(
+            account_id,
+            career_param.clone(),
+            fee_per_hour_in_yen_param.clone(),
+            current_date_time,
+        );
+        let op = SavedSearchOperationMock {
+            num_of_saved_searches: 0,
+            saved_search: saved_search.clone(),
+            saved_search_id: 10,
+        };
+        let param = SavedSearchParam {
+            career_param,
+            fee_per_hour_in_yen_param,
+            alert_enabled: true,
+        };
+
+        let result = handle_saved_search(
+            account_id,
+            param,
+            saved_search.unsubscribe_token,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::EqualOrMoreExceedsEqualOrLessInFeePerHourInYen as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_saved_search_fail_invalid_career_param() {
+        let account_id = 1;
+        let mut career_param = create_empty_career_param();
+        career_param.company_id = Some(0);
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: None,
+            equal_or_less: None,
+        };
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 9, 5, 21, 0, 40)
+            .unwrap();
+        let saved_search = create_dummy_saved_search(
+            account_id,
+            career_param.clone(),
+            fee_per_hour_in_yen_param.clone(),
+            current_date_time,
+        );
+        let op = SavedSearchOperationMock {
+            num_of_saved_searches: 0,
+            saved_search: saved_search.clone(),
+            saved_search_id: 10,
+        };
+        let param = SavedSearchParam {
+            career_param,
+            fee_per_hour_in_yen_param,
+            alert_enabled: true,
+        };
+
+        let result = handle_saved_search(
+            account_id,
+            param,
+            saved_search.unsubscribe_token,
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveCompanyId as u32, resp.1 .0.code);
+    }
+}
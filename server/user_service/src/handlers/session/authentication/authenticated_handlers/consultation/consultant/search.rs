@@ -1,20 +1,27 @@
 // Copyright 2022 Ken Miura
 
 use async_session::async_trait;
-use async_session::serde_json::{json, Value};
+use async_session::serde_json::Value;
 use axum::http::StatusCode;
 use axum::{extract::State, Json};
-use common::opensearch::{search_documents, Sort, INDEX_NAME};
+use common::consultant_search::{
+    ConsultantSearchBackend, ConsultantSearchClient, ConsultantSearchRequest,
+};
+use common::opensearch::consultant_query::{CareerParam, FeePerHourInYenParam};
+use common::opensearch::Sort;
 use common::rating::round_rating_to_one_decimal_places;
 use common::{ApiError, ErrResp, RespResult, MAX_NUM_OF_CAREER_PER_USER_ACCOUNT};
-use opensearch::OpenSearch;
+use entity::sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use super::career_param_validator::validate_career_param;
 use super::fee_per_hour_in_yen_param_validator::FeePerHourInYenParamError;
+use super::search_log::{
+    create_consultant_search_log, save_consultant_search_log, ConsultantSearchLog,
+};
 use super::sort_param_validator::SortParamError;
-use crate::err::Code;
+use crate::err::{unexpected_err_resp, Code};
 use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::fee_per_hour_in_yen_param_validator::validate_fee_per_hour_in_yen_param;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::sort_param_validator::validate_sort_param;
@@ -23,12 +30,24 @@ use super::career_param_validator::CareerParamValidationError;
 
 const VALID_SIZE: i64 = 20;
 
+/// カーソルを利用した検索でPoint in Timeを保持する期間（検索する度に延長される）
+///
+/// Point in Timeは保持している間OpenSearchのリソースを消費するため、次のページの取得に必要な程度の短い期間とする。
+/// 続きの検索結果が存在しない場合、期限を待たずに削除する。
+const POINT_IN_TIME_KEEP_ALIVE: &str = "1m";
+/// カーソルに含まれるPoint in TimeのIDとして受け付ける最大の長さ
+const MAX_PIT_ID_LENGTH: usize = 2048;
+
 pub(crate) async fn post_consultants_search(
     VerifiedUser { user_info }: VerifiedUser,
-    State(index_client): State<OpenSearch>,
+    State(pool): State<DatabaseConnection>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(req): Json<ConsultantSearchParam>,
 ) -> RespResult<ConsultantsSearchResult> {
-    let op = ConsultantsSearchOperationImpl { index_client };
+    let op = ConsultantsSearchOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     handle_consultants_search(user_info.account_id, req, op).await
 }
 
@@ -39,40 +58,21 @@ pub(crate) struct ConsultantSearchParam {
     sort_param: Option<SortParam>,
     from: i64,
     size: i64,
+    /// trueの場合、検索結果の件数を職種、雇用形態、管理職か否か、相談料及び評価ごとに集計した結果を返す
+    include_aggregations: Option<bool>,
+    /// trueの場合、fromによるページングではなく、検索結果と合わせて返すカーソルによるページングを行う（fromは0を指定する）
+    use_cursor: Option<bool>,
+    /// 前回の検索結果と合わせて返したカーソル。指定した場合、前回の検索結果の続きを返す（fromは0を指定する）
+    cursor: Option<ConsultantSearchCursor>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-pub(crate) struct CareerParam {
-    pub(super) company_name: Option<String>,
-    pub(super) department_name: Option<String>,
-    pub(super) office: Option<String>,
-    pub(super) years_of_service: YearsOfServiceParam,
-    pub(super) employed: Option<bool>,
-    pub(super) contract_type: Option<String>,
-    pub(super) profession: Option<String>,
-    pub(super) annual_income_in_man_yen: AnnualInComeInManYenParam,
-    pub(super) is_manager: Option<bool>,
-    pub(super) position_name: Option<String>,
-    pub(super) is_new_graduate: Option<bool>,
-    pub(super) note: Option<String>,
-}
-
-#[derive(Clone, Debug, Deserialize)]
-pub(crate) struct YearsOfServiceParam {
-    pub(super) equal_or_more: Option<i32>,
-    pub(super) less_than: Option<i32>,
-}
-
-#[derive(Clone, Debug, Deserialize)]
-pub(crate) struct AnnualInComeInManYenParam {
-    pub(super) equal_or_more: Option<i32>,
-    pub(super) equal_or_less: Option<i32>,
-}
-
-#[derive(Clone, Debug, Deserialize)]
-pub(crate) struct FeePerHourInYenParam {
-    pub(super) equal_or_more: Option<i32>,
-    pub(super) equal_or_less: Option<i32>,
+/// search_afterによるページングで利用するカーソル
+///
+/// 一連のページングの間、検索対象は最初の検索時点のPoint in Timeに固定されるため、ドキュメントの更新により検索結果がずれることはない
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct ConsultantSearchCursor {
+    pit_id: String,
+    search_after: Vec<Value>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -85,22 +85,55 @@ pub(crate) struct SortParam {
 pub(crate) struct ConsultantsSearchResult {
     total: i64,
     consultants: Vec<ConsultantDescription>,
+    aggregations: Option<ConsultantsSearchAggregations>,
+    /// カーソルを利用した検索で、続きの検索結果が存在する可能性がある場合に返す
+    next_cursor: Option<ConsultantSearchCursor>,
+    /// 新しい検索（fromが0で、カーソルを指定していない検索）の場合に返す
+    ///
+    /// 検索結果からコンサルタントの詳細を表示する際に指定すると、検索結果からの表示（クリック）として記録される
+    search_id: Option<String>,
+}
+
+/// 検索条件に一致したコンサルタントの数の集計結果
+///
+/// 各バケットのcountはコンサルタントの数（職務経歴の数ではない）
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct ConsultantsSearchAggregations {
+    professions: Vec<TermBucket>,
+    contract_types: Vec<TermBucket>,
+    is_manager: Vec<TermBucket>,
+    fee_per_hour_in_yen: Vec<RangeBucket>,
+    rating: Vec<RangeBucket>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct TermBucket {
+    key: String,
+    count: i64,
+}
+
+/// fromは範囲に含み、toは範囲に含まない（どちらもNoneの場合、その方向に上限（下限）がないことを示す）
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct RangeBucket {
+    from: Option<f64>,
+    to: Option<f64>,
+    count: i64,
 }
 
 #[derive(Clone, Serialize, Debug, PartialEq)]
 pub(crate) struct ConsultantDescription {
-    consultant_id: i64,
-    fee_per_hour_in_yen: i32,
-    rating: Option<String>, // 適切な型は浮動少数だが、PartialEqの==を正しく動作させるために文字列として処理する
-    num_of_rated: i32,
-    careers: Vec<ConsultantCareerDescription>,
+    pub(super) consultant_id: i64,
+    pub(super) fee_per_hour_in_yen: i32,
+    pub(super) rating: Option<String>, // 適切な型は浮動少数だが、PartialEqの==を正しく動作させるために文字列として処理する
+    pub(super) num_of_rated: i32,
+    pub(super) careers: Vec<ConsultantCareerDescription>,
 }
 
 #[derive(Clone, Serialize, Debug, PartialEq)]
 pub(crate) struct ConsultantCareerDescription {
-    company_name: String,
-    profession: Option<String>,
-    office: Option<String>,
+    pub(super) company_name: String,
+    pub(super) profession: Option<String>,
+    pub(super) office: Option<String>,
 }
 
 async fn handle_consultants_search(
@@ -146,60 +179,242 @@ async fn handle_consultants_search(
             }),
         ));
     }
+    let use_cursor = param.use_cursor.unwrap_or(false) || param.cursor.is_some();
+    // ページングによる続きの検索は、検索の記録の対象としない
+    let is_new_search = param.from == 0 && param.cursor.is_none();
+    // search_afterを利用する場合、fromは0でなければならない
+    if use_cursor && param.from != 0 {
+        error!(
+            "from must be 0 when using cursor: {} (account id: {})",
+            param.from, account_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidConsultantSearchParamFrom as u32,
+            }),
+        ));
+    }
+    let include_aggregations = param.include_aggregations.unwrap_or(false);
+    let request = ConsultantSearchRequest {
+        account_id,
+        career_param: param.career_param.clone(),
+        fee_per_hour_in_yen_param: param.fee_per_hour_in_yen_param.clone(),
+        sort: param.sort_param.as_ref().map(|s| Sort {
+            key: s.key.clone(),
+            order: s.order.clone(),
+        }),
+        include_aggregations,
+    };
+    if let Some(cursor) = param.cursor.as_ref() {
+        if !validate_cursor(cursor, request.num_of_sort_criteria()) {
+            error!("invalid cursor: {:?} (account id: {})", cursor, account_id);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::InvalidConsultantSearchCursor as u32,
+                }),
+            ));
+        }
+    }
 
     info!(
         "query param (account_id: {}, career_param: {:?}, fee_per_hour_in_yen_param: {:?}, sort_param: {:?})",
         account_id, param.career_param, param.fee_per_hour_in_yen_param, param.sort_param
     );
-    let query = create_query_json(
-        account_id,
-        param.career_param,
-        param.fee_per_hour_in_yen_param,
-    )?;
-    let sort = param.sort_param.map(|s| Sort {
-        key: s.key,
-        order: s.order,
-    });
+
+    if !use_cursor {
+        let query_result = op
+            .search_consultants(&request, param.from, param.size)
+            .await?;
+        let (mut result, took) = parse_query_result(query_result, include_aggregations, None)?;
+        if is_new_search {
+            result.search_id = Some(save_search_log(&param, &result, took, &op));
+        }
+        return Ok((StatusCode::OK, Json(result)));
+    }
+
+    let (pit_id, search_after) = match param.cursor.clone() {
+        Some(cursor) => (cursor.pit_id, Some(cursor.search_after)),
+        None => (op.create_point_in_time().await?, None),
+    };
     let query_result = op
-        .search_documents(INDEX_NAME, param.from, param.size, sort, &query)
+        .search_consultants_with_point_in_time(&pit_id, &request, param.size, search_after)
         .await?;
+    let query_result = query_result.ok_or_else(|| {
+        error!(
+            "point in time has expired (pit_id: {}, account id: {})",
+            pit_id, account_id
+        );
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ConsultantSearchCursorExpired as u32,
+            }),
+        )
+    })?;
+    // Point in Timeを指定した検索のレスポンスには、（更新されている可能性のある）Point in TimeのIDが含まれる
+    let pit_id = query_result["pit_id"]
+        .as_str()
+        .map(|id| id.to_string())
+        .unwrap_or(pit_id);
+    let next_cursor = create_next_cursor(&query_result, &pit_id, param.size)?;
+    if next_cursor.is_none() {
+        // 続きの検索結果が存在しないため、以降使われることのないPoint in Timeを削除する
+        // 削除に失敗しても期限切れで自動的に削除されるため、検索結果は返す
+        if let Err(e) = op.delete_point_in_time(&pit_id).await {
+            error!(
+                "failed to delete point in time (pit_id: {}, account id: {}): {:?}",
+                pit_id, account_id, e
+            );
+        }
+    }
+
+    let (mut result, took) = parse_query_result(query_result, include_aggregations, next_cursor)?;
+    if is_new_search {
+        result.search_id = Some(save_search_log(&param, &result, took, &op));
+    }
+    Ok((StatusCode::OK, Json(result)))
+}
+
+fn save_search_log(
+    param: &ConsultantSearchParam,
+    result: &ConsultantsSearchResult,
+    took: i64,
+    op: &impl ConsultantsSearchOperation,
+) -> String {
+    let log = create_consultant_search_log(
+        &param.career_param,
+        &param.fee_per_hour_in_yen_param,
+        param.sort_param.as_ref(),
+        result.total,
+        took,
+    );
+    op.save_search_log(log)
+}
+
+fn validate_cursor(cursor: &ConsultantSearchCursor, num_of_sort_criteria: usize) -> bool {
+    if cursor.pit_id.is_empty() || cursor.pit_id.len() > MAX_PIT_ID_LENGTH {
+        return false;
+    }
+    if cursor.search_after.len() != num_of_sort_criteria {
+        return false;
+    }
+    cursor
+        .search_after
+        .iter()
+        .all(|v| v.is_number() || v.is_string() || v.is_null())
+}
 
-    parse_query_result(query_result)
+/// 取得した件数がsizeに満たない場合、続きの検索結果は存在しないためNoneを返す
+fn create_next_cursor(
+    query_result: &Value,
+    pit_id: &str,
+    size: i64,
+) -> Result<Option<ConsultantSearchCursor>, ErrResp> {
+    let hits = query_result["hits"]["hits"].as_array().ok_or_else(|| {
+        error!("failed to get hits: {}", query_result);
+        unexpected_err_resp()
+    })?;
+    if (hits.len() as i64) < size {
+        return Ok(None);
+    }
+    let last_hit = hits.last().ok_or_else(|| {
+        error!("failed to get last hit: {}", query_result);
+        unexpected_err_resp()
+    })?;
+    let search_after = last_hit["sort"].as_array().ok_or_else(|| {
+        error!("failed to find sort in hit: {}", last_hit);
+        unexpected_err_resp()
+    })?;
+    Ok(Some(ConsultantSearchCursor {
+        pit_id: pit_id.to_string(),
+        search_after: search_after.clone(),
+    }))
 }
 
 #[async_trait]
 trait ConsultantsSearchOperation {
-    async fn search_documents(
+    async fn search_consultants(
         &self,
-        index_name: &str,
+        request: &ConsultantSearchRequest,
         from: i64,
         size: i64,
-        sort: Option<Sort>,
-        query: &Value,
     ) -> Result<Value, ErrResp>;
+
+    /// Point in Timeを作成し、そのIDを返す
+    async fn create_point_in_time(&self) -> Result<String, ErrResp>;
+
+    /// Point in Timeを削除する
+    async fn delete_point_in_time(&self, pit_id: &str) -> Result<(), ErrResp>;
+
+    /// Point in Timeの期限が切れている場合、Noneを返す
+    async fn search_consultants_with_point_in_time(
+        &self,
+        pit_id: &str,
+        request: &ConsultantSearchRequest,
+        size: i64,
+        search_after: Option<Vec<Value>>,
+    ) -> Result<Option<Value>, ErrResp>;
+
+    /// 検索の記録を非同期に保存し、記録を識別するID（search_id）を返す
+    fn save_search_log(&self, log: ConsultantSearchLog) -> String;
 }
 
 struct ConsultantsSearchOperationImpl {
-    index_client: OpenSearch,
+    pool: DatabaseConnection,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
 impl ConsultantsSearchOperation for ConsultantsSearchOperationImpl {
-    async fn search_documents(
+    async fn search_consultants(
         &self,
-        index_name: &str,
+        request: &ConsultantSearchRequest,
         from: i64,
         size: i64,
-        sort: Option<Sort>,
-        query: &Value,
     ) -> Result<Value, ErrResp> {
-        let result =
-            search_documents(index_name, from, size, sort, query, &self.index_client).await?;
-        Ok(result)
+        self.consultant_search_client
+            .search_consultants(request, from, size)
+            .await
+    }
+
+    async fn create_point_in_time(&self) -> Result<String, ErrResp> {
+        self.consultant_search_client
+            .create_point_in_time(POINT_IN_TIME_KEEP_ALIVE)
+            .await
+    }
+
+    async fn delete_point_in_time(&self, pit_id: &str) -> Result<(), ErrResp> {
+        self.consultant_search_client
+            .delete_point_in_time(pit_id)
+            .await
+    }
+
+    async fn search_consultants_with_point_in_time(
+        &self,
+        pit_id: &str,
+        request: &ConsultantSearchRequest,
+        size: i64,
+        search_after: Option<Vec<Value>>,
+    ) -> Result<Option<Value>, ErrResp> {
+        self.consultant_search_client
+            .search_consultants_with_point_in_time(
+                pit_id,
+                POINT_IN_TIME_KEEP_ALIVE,
+                request,
+                size,
+                search_after.as_deref(),
+            )
+            .await
+    }
+
+    fn save_search_log(&self, log: ConsultantSearchLog) -> String {
+        save_consultant_search_log(&self.pool, log)
     }
 }
 
-fn create_invalid_career_param_err(e: &CareerParamValidationError) -> ErrResp {
+pub(super) fn create_invalid_career_param_err(e: &CareerParamValidationError) -> ErrResp {
     let code = match e {
         CareerParamValidationError::InvalidCompanyNameLength {
             length: _,
@@ -207,6 +422,7 @@ fn create_invalid_career_param_err(e: &CareerParamValidationError) -> ErrResp {
             max_length: _,
         } => Code::InvalidCompanyNameLength,
         CareerParamValidationError::IllegalCharInCompanyName(_) => Code::IllegalCharInCompanyName,
+        CareerParamValidationError::NonPositiveCompanyId(_) => Code::NonPositiveCompanyId,
         CareerParamValidationError::InvalidDepartmentNameLength {
             length: _,
             min_length: _,
@@ -266,7 +482,9 @@ fn create_invalid_career_param_err(e: &CareerParamValidationError) -> ErrResp {
     )
 }
 
-fn create_invalid_fee_per_hour_in_yen_param_err(e: &FeePerHourInYenParamError) -> ErrResp {
+pub(super) fn create_invalid_fee_per_hour_in_yen_param_err(
+    e: &FeePerHourInYenParamError,
+) -> ErrResp {
     let code = match e {
         FeePerHourInYenParamError::InvalidEqualOrMore {
             value: _,
@@ -300,526 +518,12 @@ fn create_invalid_sort_param_err(e: &SortParamError) -> ErrResp {
     )
 }
 
-fn create_query_json(
-    account_id: i64,
-    career_param: CareerParam,
-    fee_per_hour_in_yen_param: FeePerHourInYenParam,
-) -> Result<Value, ErrResp> {
-    let mut params = Vec::<Value>::new();
-    if let Some(company_name) = career_param.company_name {
-        let company_name_criteria = create_company_name_criteria(company_name.as_str());
-        params.push(company_name_criteria);
-    }
-    if let Some(department_name) = career_param.department_name {
-        let department_name_criteria = create_department_name_criteria(department_name.as_str());
-        params.push(department_name_criteria);
-    }
-    if let Some(office) = career_param.office {
-        let office_criteria = create_office_criteria(office.as_str());
-        params.push(office_criteria);
-    }
-    if let Some(equal_or_more) = career_param.years_of_service.equal_or_more {
-        let years_of_service_equal_or_more_criteria =
-            create_years_of_service_equal_or_more_criteria(equal_or_more);
-        params.push(years_of_service_equal_or_more_criteria);
-    }
-    if let Some(less_than) = career_param.years_of_service.less_than {
-        let years_of_service_less_than_criteria =
-            create_years_of_service_less_than_criteria(less_than);
-        params.push(years_of_service_less_than_criteria);
-    }
-    if let Some(employed) = career_param.employed {
-        let employed_criteria = create_employed_criteria(employed);
-        params.push(employed_criteria);
-    }
-    if let Some(contract_type) = career_param.contract_type {
-        let contract_type_criteria = create_contract_type_criteria(contract_type.as_str());
-        params.push(contract_type_criteria);
-    }
-    if let Some(profession) = career_param.profession {
-        let profession_criteria = create_profession_criteria(profession.as_str());
-        params.push(profession_criteria);
-    }
-    if let Some(equal_or_more) = career_param.annual_income_in_man_yen.equal_or_more {
-        let equal_or_more_criteria =
-            create_annual_income_in_man_yen_equal_or_more_criteria(equal_or_more);
-        params.push(equal_or_more_criteria);
-    }
-    if let Some(equal_or_less) = career_param.annual_income_in_man_yen.equal_or_less {
-        let equal_or_less_criteria =
-            create_annual_income_in_man_yen_equal_or_less_criteria(equal_or_less);
-        params.push(equal_or_less_criteria);
-    }
-    if let Some(is_manager) = career_param.is_manager {
-        let is_manager_criteria = create_is_manager_criteria(is_manager);
-        params.push(is_manager_criteria);
-    }
-    if let Some(position_name) = career_param.position_name {
-        let position_name_criteria = create_position_name_criteria(position_name.as_str());
-        params.push(position_name_criteria);
-    }
-    if let Some(is_new_graduate) = career_param.is_new_graduate {
-        let is_new_graduate_criteria = create_is_new_graduate_criteria(is_new_graduate);
-        params.push(is_new_graduate_criteria);
-    }
-    if let Some(note) = career_param.note {
-        let note_criteria = create_note_criteria(note.as_str());
-        params.push(note_criteria);
-    }
-    if let Some(equal_or_more) = fee_per_hour_in_yen_param.equal_or_more {
-        let equal_or_more_criteria =
-            create_fee_per_hour_in_yen_equal_or_more_criteria(equal_or_more);
-        params.push(equal_or_more_criteria);
-    }
-    if let Some(equal_or_less) = fee_per_hour_in_yen_param.equal_or_less {
-        let equal_or_less_criteria =
-            create_fee_per_hour_in_yen_equal_or_less_criteria(equal_or_less);
-        params.push(equal_or_less_criteria);
-    }
-    Ok(generate_query_json(account_id, params))
-}
-
-fn create_company_name_criteria(company_name: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": company_name,
-                                "fields": [
-                                    "careers.company_name.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": company_name,
-                                "fields": [
-                                    "careers.company_name^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_department_name_criteria(department_name: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": department_name,
-                                "fields": [
-                                    "careers.department_name.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": department_name,
-                                "fields": [
-                                    "careers.department_name^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_office_criteria(office: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": office,
-                                "fields": [
-                                    "careers.office.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": office,
-                                "fields": [
-                                    "careers.office^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_years_of_service_equal_or_more_criteria(years_of_service: i32) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "range": {
-                                "careers.years_of_service": {
-                                    "gte": years_of_service
-                                }
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_years_of_service_less_than_criteria(years_of_service: i32) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "range": {
-                                "careers.years_of_service": {
-                                    "lt": years_of_service
-                                }
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_employed_criteria(employed: bool) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "term": {
-                                "careers.employed": employed
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_contract_type_criteria(contract_type: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": contract_type,
-                                "fields": [
-                                    "careers.contract_type.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": contract_type,
-                                "fields": [
-                                    "careers.contract_type^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_profession_criteria(profession: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": profession,
-                                "fields": [
-                                    "careers.profession.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": profession,
-                                "fields": [
-                                    "careers.profession^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_annual_income_in_man_yen_equal_or_more_criteria(equal_or_more: i32) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "range": {
-                                "careers.years_of_service": {
-                                    "gte": equal_or_more
-                                }
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_annual_income_in_man_yen_equal_or_less_criteria(equal_or_less: i32) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "range": {
-                                "careers.years_of_service": {
-                                    "lte": equal_or_less
-                                }
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_is_manager_criteria(is_manager: bool) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "term": {
-                                "careers.is_manager": is_manager
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_position_name_criteria(position_name: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": position_name,
-                                "fields": [
-                                    "careers.position_name.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": position_name,
-                                "fields": [
-                                    "careers.position_name^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_is_new_graduate_criteria(is_new_graduate: bool) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "term": {
-                                "careers.is_new_graduate": is_new_graduate
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_note_criteria(note: &str) -> Value {
-    json!({
-        "nested": {
-            "path": "careers",
-            "query": {
-                "bool": {
-                    "must": [
-                        {
-                            "multi_match": {
-                                "query": note,
-                                "fields": [
-                                    "careers.note.ngram^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ],
-                    "should": [
-                        {
-                            "multi_match": {
-                                "query": note,
-                                "fields": [
-                                    "careers.note^1"
-                                ],
-                                "type": "phrase"
-                            }
-                        }
-                    ]
-                }
-            }
-        }
-    })
-}
-
-fn create_fee_per_hour_in_yen_equal_or_more_criteria(equal_or_more: i32) -> Value {
-    json!({
-        "range": {
-            "fee_per_hour_in_yen": {
-                "gte": equal_or_more
-            }
-        }
-    })
-}
-
-fn create_fee_per_hour_in_yen_equal_or_less_criteria(equal_or_less: i32) -> Value {
-    json!({
-        "range": {
-            "fee_per_hour_in_yen": {
-                "lte": equal_or_less
-            }
-        }
-    })
-}
-
-fn generate_query_json(account_id: i64, params: Vec<Value>) -> Value {
-    json!({
-        "query": {
-            "bool": {
-                "must": params,
-                "filter": [
-                    {
-                        "range": {
-                            "num_of_careers": {
-                                "gt": 0
-                            }
-                        }
-                    },
-                    {
-                        "exists": {
-                            "field": "fee_per_hour_in_yen"
-                        }
-                    },
-                    {
-                        "term": {
-                            "is_bank_account_registered": true
-                        }
-                    },
-                    {
-                        "term": {
-                            "disabled": false
-                        }
-                    }
-                ],
-                "must_not": [
-                    {
-                        "term": {
-                            "user_account_id": account_id
-                        }
-                    }
-                ]
-            }
-        }
-    })
-}
-
-fn parse_query_result(query_result: Value) -> RespResult<ConsultantsSearchResult> {
+/// 検索結果と、検索に要した時間（ミリ秒）を返す
+fn parse_query_result(
+    query_result: Value,
+    include_aggregations: bool,
+    next_cursor: Option<ConsultantSearchCursor>,
+) -> Result<(ConsultantsSearchResult, i64), ErrResp> {
     let took = query_result["took"].as_i64().ok_or_else(|| {
         error!("failed to get processing time: {}", query_result);
         (
@@ -858,11 +562,81 @@ fn parse_query_result(query_result: Value) -> RespResult<ConsultantsSearchResult
     }
     // NOTE: 将来的にパフォーマンスに影響する場合、ログに出力する内容を制限する
     info!("total: {}, consultants: {:?}", total, consultants);
-    let results = ConsultantsSearchResult { total, consultants };
-    Ok((StatusCode::OK, Json(results)))
+    let aggregations = if include_aggregations {
+        Some(parse_aggregations(&query_result["aggregations"])?)
+    } else {
+        None
+    };
+    let results = ConsultantsSearchResult {
+        total,
+        consultants,
+        aggregations,
+        next_cursor,
+        search_id: None,
+    };
+    Ok((results, took))
+}
+
+fn parse_aggregations(aggregations: &Value) -> Result<ConsultantsSearchAggregations, ErrResp> {
+    let careers = &aggregations["careers"];
+    Ok(ConsultantsSearchAggregations {
+        professions: parse_term_buckets(&careers["professions"])?,
+        contract_types: parse_term_buckets(&careers["contract_types"])?,
+        is_manager: parse_term_buckets(&careers["is_manager"])?,
+        fee_per_hour_in_yen: parse_range_buckets(&aggregations["fee_per_hour_in_yen"])?,
+        rating: parse_range_buckets(&aggregations["rating"])?,
+    })
 }
 
-fn create_consultant_description(hit: &Value) -> Result<ConsultantDescription, ErrResp> {
+fn parse_term_buckets(aggregation: &Value) -> Result<Vec<TermBucket>, ErrResp> {
+    let buckets = get_buckets(aggregation)?;
+    let mut results = Vec::with_capacity(buckets.len());
+    for bucket in buckets {
+        // booleanのフィールドのkeyは1または0のため、文字列表現（key_as_string）があればそちらを利用する
+        let key = bucket["key_as_string"]
+            .as_str()
+            .or_else(|| bucket["key"].as_str())
+            .ok_or_else(|| {
+                error!("failed to get key in bucket: {}", bucket);
+                unexpected_err_resp()
+            })?;
+        let count = bucket["consultants"]["doc_count"].as_i64().ok_or_else(|| {
+            error!("failed to get consultants.doc_count in bucket: {}", bucket);
+            unexpected_err_resp()
+        })?;
+        results.push(TermBucket {
+            key: key.to_string(),
+            count,
+        });
+    }
+    Ok(results)
+}
+
+fn parse_range_buckets(aggregation: &Value) -> Result<Vec<RangeBucket>, ErrResp> {
+    let buckets = get_buckets(aggregation)?;
+    let mut results = Vec::with_capacity(buckets.len());
+    for bucket in buckets {
+        let count = bucket["doc_count"].as_i64().ok_or_else(|| {
+            error!("failed to get doc_count in bucket: {}", bucket);
+            unexpected_err_resp()
+        })?;
+        results.push(RangeBucket {
+            from: bucket["from"].as_f64(),
+            to: bucket["to"].as_f64(),
+            count,
+        });
+    }
+    Ok(results)
+}
+
+fn get_buckets(aggregation: &Value) -> Result<&Vec<Value>, ErrResp> {
+    aggregation["buckets"].as_array().ok_or_else(|| {
+        error!("failed to get buckets: {}", aggregation);
+        unexpected_err_resp()
+    })
+}
+
+pub(super) fn create_consultant_description(hit: &Value) -> Result<ConsultantDescription, ErrResp> {
     let account_id = hit["_source"]["user_account_id"].as_i64().ok_or_else(|| {
         error!("failed to find account id in _source: {:?}", hit);
         (
@@ -937,29 +711,73 @@ fn create_consultant_career_description(
 #[cfg(test)]
 mod tests {
 
+    use std::sync::{Arc, Mutex};
+
+    use async_session::serde_json::json;
+    use common::opensearch::consultant_query::{AnnualInComeInManYenParam, YearsOfServiceParam};
     use once_cell::sync::Lazy;
 
     use crate::handlers::session::authentication::authenticated_handlers::fee_per_hour_in_yen_range::MIN_FEE_PER_HOUR_IN_YEN;
 
     use super::*;
 
+    const SEARCH_ID: &str = "b7a3c1e0f2d94a6b8c5e1f0a2b3c4d5e";
+    const PIT_ID: &str = "46ToAwMDaWR5BXV1aWQyKwZub2RlXzMAAAAAAAAAACoBYwADaWR4BXV1aWQxAgZub2RlXzEAAAAAAAAAAAEBYQADaWR5BXV1aWQyKgZub2RlXzIAAAAAAAAAAAwBYgACBXV1aWQyAAAFdXVpZDEAAQltYXRjaF9hbGw_gAAAAA==";
+
     #[derive(Clone, Debug)]
     struct ConsultantsSearchOperationMock {
         query_result: Value,
+        /// Noneの場合、Point in Timeの期限が切れているものとして扱う
+        pit_id: Option<String>,
+        search_after: Option<Vec<Value>>,
+        /// delete_point_in_timeで削除されたPoint in TimeのID
+        deleted_pit_ids: Arc<Mutex<Vec<String>>>,
     }
 
     #[async_trait]
     impl ConsultantsSearchOperation for ConsultantsSearchOperationMock {
-        async fn search_documents(
+        async fn search_consultants(
             &self,
-            _index_name: &str,
+            _request: &ConsultantSearchRequest,
             _from: i64,
             _size: i64,
-            _sort: Option<Sort>,
-            _query: &Value,
         ) -> Result<Value, ErrResp> {
             Ok(self.query_result.clone())
         }
+
+        async fn create_point_in_time(&self) -> Result<String, ErrResp> {
+            assert_eq!(None, self.search_after);
+            Ok(self.pit_id.clone().expect("failed to get Ok"))
+        }
+
+        async fn delete_point_in_time(&self, pit_id: &str) -> Result<(), ErrResp> {
+            self.deleted_pit_ids
+                .lock()
+                .expect("failed to lock")
+                .push(pit_id.to_string());
+            Ok(())
+        }
+
+        async fn search_consultants_with_point_in_time(
+            &self,
+            pit_id: &str,
+            _request: &ConsultantSearchRequest,
+            _size: i64,
+            search_after: Option<Vec<Value>>,
+        ) -> Result<Option<Value>, ErrResp> {
+            assert_eq!(self.search_after, search_after);
+            match self.pit_id.as_ref() {
+                Some(id) => {
+                    assert_eq!(id, pit_id);
+                    Ok(Some(self.query_result.clone()))
+                }
+                None => Ok(None),
+            }
+        }
+
+        fn save_search_log(&self, _log: ConsultantSearchLog) -> String {
+            SEARCH_ID.to_string()
+        }
     }
 
     #[derive(Debug)]
@@ -985,6 +803,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: Some("テスト１".to_string()),
+                            company_id: None,
                             department_name: Some("テスト２".to_string()),
                             office: Some("テスト３".to_string()),
                             years_of_service: YearsOfServiceParam {
@@ -1013,8 +832,13 @@ mod tests {
                         }),
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: json!({
                           "took" : 2,
                           "timed_out" : false,
@@ -1065,12 +889,16 @@ mod tests {
                             ]
                           }
                         }),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Ok((
                     StatusCode::OK,
                     Json(ConsultantsSearchResult {
                         total: 1,
+                        aggregations: None,
+                        next_cursor: None,
+                        search_id: Some(SEARCH_ID.to_string()),
                         consultants: vec![ConsultantDescription {
                             consultant_id: 2,
                             fee_per_hour_in_yen: 4500,
@@ -1092,6 +920,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1117,8 +946,13 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: json!({
                           "took" : 2,
                           "timed_out" : false,
@@ -1169,12 +1003,16 @@ mod tests {
                             ]
                           }
                         }),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Ok((
                     StatusCode::OK,
                     Json(ConsultantsSearchResult {
                         total: 1,
+                        aggregations: None,
+                        next_cursor: None,
+                        search_id: Some(SEARCH_ID.to_string()),
                         consultants: vec![ConsultantDescription {
                             consultant_id: 2,
                             fee_per_hour_in_yen: 4500,
@@ -1196,6 +1034,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: Some("".to_string()),
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1221,9 +1060,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1240,6 +1085,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: Some("*".to_string()),
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1265,9 +1111,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1284,6 +1136,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: Some("".to_string()),
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1309,9 +1162,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1328,6 +1187,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: Some("*".to_string()),
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1353,9 +1213,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1372,6 +1238,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: Some("".to_string()),
                             years_of_service: YearsOfServiceParam {
@@ -1397,9 +1264,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1416,6 +1289,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: Some("*".to_string()),
                             years_of_service: YearsOfServiceParam {
@@ -1441,9 +1315,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1460,6 +1340,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1485,9 +1366,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1504,6 +1391,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1529,9 +1417,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1548,6 +1442,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1573,9 +1468,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1592,6 +1493,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1617,9 +1519,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1636,6 +1544,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1661,9 +1570,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1680,6 +1595,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1705,9 +1621,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1724,6 +1646,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1749,9 +1672,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1768,6 +1697,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1793,9 +1723,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1812,6 +1748,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1837,9 +1774,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1856,6 +1799,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1881,9 +1825,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1900,6 +1850,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1925,9 +1876,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1944,6 +1901,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -1969,9 +1927,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -1988,6 +1952,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2013,9 +1978,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2032,6 +2003,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2057,9 +2029,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2076,6 +2054,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2104,9 +2083,15 @@ mod tests {
                         }),
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2123,6 +2108,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2151,9 +2137,15 @@ mod tests {
                         }),
                         from: 0,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2170,6 +2162,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2195,9 +2188,15 @@ mod tests {
                         sort_param: None,
                         from: -1,
                         size: 20,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2214,6 +2213,7 @@ mod tests {
                     param: ConsultantSearchParam {
                         career_param: CareerParam {
                             company_name: None,
+                            company_id: None,
                             department_name: None,
                             office: None,
                             years_of_service: YearsOfServiceParam {
@@ -2239,9 +2239,15 @@ mod tests {
                         sort_param: None,
                         from: 0,
                         size: 21,
+                        include_aggregations: None,
+                        use_cursor: None,
+                        cursor: None,
                     },
                     op: ConsultantsSearchOperationMock {
+                        pit_id: None,
+                        search_after: None,
                         query_result: create_empty_result(),
+                        deleted_pit_ids: Arc::new(Mutex::new(vec![])),
                     },
                 },
                 expected: Err((
@@ -2299,4 +2305,385 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn handle_consultants_search_returns_aggregations_if_requested() {
+        let param = ConsultantSearchParam {
+            career_param: CareerParam {
+                company_name: None,
+                company_id: None,
+                department_name: None,
+                office: None,
+                years_of_service: YearsOfServiceParam {
+                    equal_or_more: None,
+                    less_than: None,
+                },
+                employed: None,
+                contract_type: None,
+                profession: None,
+                annual_income_in_man_yen: AnnualInComeInManYenParam {
+                    equal_or_more: None,
+                    equal_or_less: None,
+                },
+                is_manager: None,
+                position_name: None,
+                is_new_graduate: None,
+                note: None,
+            },
+            fee_per_hour_in_yen_param: FeePerHourInYenParam {
+                equal_or_more: None,
+                equal_or_less: None,
+            },
+            sort_param: None,
+            from: 0,
+            size: 20,
+            include_aggregations: Some(true),
+            use_cursor: None,
+            cursor: None,
+        };
+        let op = ConsultantsSearchOperationMock {
+            pit_id: None,
+            search_after: None,
+            query_result: json!({
+              "took": 5,
+              "timed_out": false,
+              "hits": {
+                "total": { "value": 0, "relation": "eq" },
+                "max_score": null,
+                "hits": []
+              },
+              "aggregations": {
+                "careers": {
+                  "doc_count": 3,
+                  "professions": {
+                    "buckets": [
+                      { "key": "エンジニア", "doc_count": 2, "consultants": { "doc_count": 1 } }
+                    ]
+                  },
+                  "contract_types": {
+                    "buckets": [
+                      { "key": "regular", "doc_count": 3, "consultants": { "doc_count": 2 } }
+                    ]
+                  },
+                  "is_manager": {
+                    "buckets": [
+                      { "key": 0, "key_as_string": "false", "doc_count": 3, "consultants": { "doc_count": 2 } }
+                    ]
+                  }
+                },
+                "fee_per_hour_in_yen": {
+                  "buckets": [
+                    { "key": "*-5000.0", "to": 5000.0, "doc_count": 1 },
+                    { "key": "5000.0-*", "from": 5000.0, "doc_count": 1 }
+                  ]
+                },
+                "rating": {
+                  "buckets": [
+                    { "key": "*-4.0", "to": 4.0, "doc_count": 0 },
+                    { "key": "4.0-*", "from": 4.0, "doc_count": 1 }
+                  ]
+                }
+              }
+            }),
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect("failed to get Ok");
+
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            Some(ConsultantsSearchAggregations {
+                professions: vec![TermBucket {
+                    key: "エンジニア".to_string(),
+                    count: 1
+                }],
+                contract_types: vec![TermBucket {
+                    key: "regular".to_string(),
+                    count: 2
+                }],
+                is_manager: vec![TermBucket {
+                    key: "false".to_string(),
+                    count: 2
+                }],
+                fee_per_hour_in_yen: vec![
+                    RangeBucket {
+                        from: None,
+                        to: Some(5000.0),
+                        count: 1
+                    },
+                    RangeBucket {
+                        from: Some(5000.0),
+                        to: None,
+                        count: 1
+                    }
+                ],
+                rating: vec![
+                    RangeBucket {
+                        from: None,
+                        to: Some(4.0),
+                        count: 0
+                    },
+                    RangeBucket {
+                        from: Some(4.0),
+                        to: None,
+                        count: 1
+                    }
+                ],
+            }),
+            resp.1 .0.aggregations
+        );
+    }
+
+    fn create_param_without_conditions(
+        use_cursor: Option<bool>,
+        cursor: Option<ConsultantSearchCursor>,
+    ) -> ConsultantSearchParam {
+        ConsultantSearchParam {
+            career_param: CareerParam {
+                company_name: None,
+                company_id: None,
+                department_name: None,
+                office: None,
+                years_of_service: YearsOfServiceParam {
+                    equal_or_more: None,
+                    less_than: None,
+                },
+                employed: None,
+                contract_type: None,
+                profession: None,
+                annual_income_in_man_yen: AnnualInComeInManYenParam {
+                    equal_or_more: None,
+                    equal_or_less: None,
+                },
+                is_manager: None,
+                position_name: None,
+                is_new_graduate: None,
+                note: None,
+            },
+            fee_per_hour_in_yen_param: FeePerHourInYenParam {
+                equal_or_more: None,
+                equal_or_less: None,
+            },
+            sort_param: None,
+            from: 0,
+            size: VALID_SIZE,
+            include_aggregations: None,
+            use_cursor,
+            cursor,
+        }
+    }
+
+    /// user_account_idの降順にnum_of_hits件のヒットを含む検索結果を返す
+    fn create_result_with_point_in_time(num_of_hits: usize, pit_id: &str) -> Value {
+        let hits = (0..num_of_hits)
+            .map(|i| {
+                let user_account_id = 100 - i as i64;
+                json!({
+                    "_index": "users",
+                    "_id": user_account_id.to_string(),
+                    "_score": 1.0,
+                    "_source": {
+                        "careers": [
+                            {
+                                "annual_income_in_man_yen": null,
+                                "career_id": user_account_id,
+                                "company_name": "テスト１",
+                                "contract_type": "regular",
+                                "department_name": null,
+                                "employed": true,
+                                "is_manager": false,
+                                "is_new_graduate": true,
+                                "note": null,
+                                "office": null,
+                                "position_name": null,
+                                "profession": null,
+                                "years_of_service": 3
+                            }
+                        ],
+                        "fee_per_hour_in_yen": 5000,
+                        "is_bank_account_registered": true,
+                        "disabled": false,
+                        "num_of_careers": 1,
+                        "rating": null,
+                        "num_of_rated": 0,
+                        "user_account_id": user_account_id
+                    },
+                    "sort": [1.0, user_account_id]
+                })
+            })
+            .collect::<Vec<Value>>();
+        json!({
+            "pit_id": pit_id,
+            "took": 3,
+            "timed_out": false,
+            "hits": {
+                "total": { "value": 100, "relation": "eq" },
+                "max_score": null,
+                "hits": hits
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_returns_next_cursor_if_use_cursor_specified() {
+        let param = create_param_without_conditions(Some(true), None);
+        let op = ConsultantsSearchOperationMock {
+            query_result: create_result_with_point_in_time(VALID_SIZE as usize, PIT_ID),
+            pit_id: Some(PIT_ID.to_string()),
+            search_after: None,
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+        let deleted_pit_ids = op.deleted_pit_ids.clone();
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect("failed to get Ok");
+
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(VALID_SIZE as usize, resp.1 .0.consultants.len());
+        assert_eq!(
+            Some(ConsultantSearchCursor {
+                pit_id: PIT_ID.to_string(),
+                search_after: vec![json!(1.0), json!(81)],
+            }),
+            resp.1 .0.next_cursor
+        );
+        assert_eq!(Some(SEARCH_ID.to_string()), resp.1 .0.search_id);
+        // 続きの検索で使うため、Point in Timeは削除しない
+        assert!(deleted_pit_ids.lock().expect("failed to lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_deletes_point_in_time_if_first_page_is_last_page() {
+        let param = create_param_without_conditions(Some(true), None);
+        let op = ConsultantsSearchOperationMock {
+            query_result: create_result_with_point_in_time(3, PIT_ID),
+            pit_id: Some(PIT_ID.to_string()),
+            search_after: None,
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+        let deleted_pit_ids = op.deleted_pit_ids.clone();
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect("failed to get Ok");
+
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(3, resp.1 .0.consultants.len());
+        assert_eq!(None, resp.1 .0.next_cursor);
+        assert_eq!(
+            vec![PIT_ID.to_string()],
+            *deleted_pit_ids.lock().expect("failed to lock")
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_continues_from_cursor() {
+        let search_after = vec![json!(1.0), json!(81)];
+        let param = create_param_without_conditions(
+            None,
+            Some(ConsultantSearchCursor {
+                pit_id: PIT_ID.to_string(),
+                search_after: search_after.clone(),
+            }),
+        );
+        let op = ConsultantsSearchOperationMock {
+            // 残りの件数がsizeに満たない（最後のページ）
+            query_result: create_result_with_point_in_time(5, PIT_ID),
+            pit_id: Some(PIT_ID.to_string()),
+            search_after: Some(search_after),
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+        let deleted_pit_ids = op.deleted_pit_ids.clone();
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect("failed to get Ok");
+
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(5, resp.1 .0.consultants.len());
+        assert_eq!(None, resp.1 .0.next_cursor);
+        // ページングによる続きの検索は記録しない
+        assert_eq!(None, resp.1 .0.search_id);
+        // 続きの検索結果は存在しないため、Point in Timeを削除する
+        assert_eq!(
+            vec![PIT_ID.to_string()],
+            *deleted_pit_ids.lock().expect("failed to lock")
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_fails_if_point_in_time_has_expired() {
+        let search_after = vec![json!(1.0), json!(81)];
+        let param = create_param_without_conditions(
+            None,
+            Some(ConsultantSearchCursor {
+                pit_id: PIT_ID.to_string(),
+                search_after: search_after.clone(),
+            }),
+        );
+        let op = ConsultantsSearchOperationMock {
+            query_result: create_empty_result(),
+            pit_id: None,
+            search_after: Some(search_after),
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect_err("failed to get Err");
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ConsultantSearchCursorExpired as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_fails_if_from_is_not_zero_with_cursor() {
+        let mut param = create_param_without_conditions(Some(true), None);
+        param.from = VALID_SIZE;
+        let op = ConsultantsSearchOperationMock {
+            query_result: create_empty_result(),
+            pit_id: Some(PIT_ID.to_string()),
+            search_after: None,
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect_err("failed to get Err");
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::InvalidConsultantSearchParamFrom as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_fails_if_search_after_does_not_match_sort() {
+        // ソートの条件（_scoreとuser_account_id）の数と一致しない
+        let search_after = vec![json!(81)];
+        let param = create_param_without_conditions(
+            None,
+            Some(ConsultantSearchCursor {
+                pit_id: PIT_ID.to_string(),
+                search_after: search_after.clone(),
+            }),
+        );
+        let op = ConsultantsSearchOperationMock {
+            query_result: create_empty_result(),
+            pit_id: Some(PIT_ID.to_string()),
+            search_after: Some(search_after),
+            deleted_pit_ids: Arc::new(Mutex::new(vec![])),
+        };
+
+        let resp = handle_consultants_search(1, param, op)
+            .await
+            .expect_err("failed to get Err");
+
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidConsultantSearchCursor as u32, resp.1 .0.code);
+    }
 }
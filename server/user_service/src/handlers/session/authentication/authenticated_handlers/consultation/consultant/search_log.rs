@@ -0,0 +1,300 @@
+// Copyright 2023 Ken Miura
+
+//! コンサルタントの検索と、検索結果からのコンサルタントの詳細の表示を記録するモジュール
+//!
+//! 記録は検索の傾向の分析（管理者向けのレポート）のみに利用するため、記録に失敗した場合でも検索や詳細の表示は失敗させない。
+//! そのため、記録は応答を返す処理とは別のタスクで非同期に行い、失敗した場合はログに出力するのみとする。
+
+use async_session::serde_json::json;
+use chrono::{DateTime, FixedOffset, Utc};
+use common::opensearch::consultant_query::{CareerParam, FeePerHourInYenParam};
+use common::JAPANESE_TIME_ZONE;
+use entity::sea_orm::sea_query::OnConflict;
+use entity::sea_orm::{ActiveValue::Set, DatabaseConnection, EntityTrait};
+use tracing::error;
+use uuid::Uuid;
+
+use super::search::SortParam;
+
+/// 匿名化した検索の記録
+///
+/// 検索を行ったユーザーは含まない。また、自由入力の検索条件は、会社名と職種以外は条件を指定したかどうか（criteria）のみを含む。
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ConsultantSearchLog {
+    pub(super) criteria: Vec<String>,
+    pub(super) company_name: Option<String>,
+    pub(super) profession: Option<String>,
+    pub(super) sort_key: Option<String>,
+    pub(super) sort_order: Option<String>,
+    pub(super) num_of_hits: i64,
+    pub(super) took_in_milliseconds: i64,
+}
+
+pub(super) fn create_consultant_search_log(
+    career_param: &CareerParam,
+    fee_per_hour_in_yen_param: &FeePerHourInYenParam,
+    sort_param: Option<&SortParam>,
+    num_of_hits: i64,
+    took_in_milliseconds: i64,
+) -> ConsultantSearchLog {
+    let criteria = [
+        ("company_name", career_param.company_name.is_some()),
+        ("company_id", career_param.company_id.is_some()),
+        ("department_name", career_param.department_name.is_some()),
+        ("office", career_param.office.is_some()),
+        (
+            "years_of_service.equal_or_more",
+            career_param.years_of_service.equal_or_more.is_some(),
+        ),
+        (
+            "years_of_service.less_than",
+            career_param.years_of_service.less_than.is_some(),
+        ),
+        ("employed", career_param.employed.is_some()),
+        ("contract_type", career_param.contract_type.is_some()),
+        ("profession", career_param.profession.is_some()),
+        (
+            "annual_income_in_man_yen.equal_or_more",
+            career_param
+                .annual_income_in_man_yen
+                .equal_or_more
+                .is_some(),
+        ),
+        (
+            "annual_income_in_man_yen.equal_or_less",
+            career_param
+                .annual_income_in_man_yen
+                .equal_or_less
+                .is_some(),
+        ),
+        ("is_manager", career_param.is_manager.is_some()),
+        ("position_name", career_param.position_name.is_some()),
+        ("is_new_graduate", career_param.is_new_graduate.is_some()),
+        ("note", career_param.note.is_some()),
+        (
+            "fee_per_hour_in_yen.equal_or_more",
+            fee_per_hour_in_yen_param.equal_or_more.is_some(),
+        ),
+        (
+            "fee_per_hour_in_yen.equal_or_less",
+            fee_per_hour_in_yen_param.equal_or_less.is_some(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, specified)| *specified)
+    .map(|(name, _)| name.to_string())
+    .collect();
+    ConsultantSearchLog {
+        criteria,
+        company_name: career_param
+            .company_name
+            .as_ref()
+            .map(|c| c.trim().to_string()),
+        profession: career_param
+            .profession
+            .as_ref()
+            .map(|p| p.trim().to_string()),
+        sort_key: sort_param.map(|s| s.key.clone()),
+        sort_order: sort_param.map(|s| s.order.clone()),
+        num_of_hits,
+        took_in_milliseconds,
+    }
+}
+
+/// 検索の記録を非同期に保存し、記録を識別するID（search_id）を返す
+pub(super) fn save_consultant_search_log(
+    pool: &DatabaseConnection,
+    log: ConsultantSearchLog,
+) -> String {
+    let search_id = Uuid::new_v4().simple().to_string();
+    let searched_at = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let pool = pool.clone();
+    let id = search_id.clone();
+    tokio::spawn(async move {
+        insert_consultant_search_log(&pool, id, log, searched_at).await;
+    });
+    search_id
+}
+
+async fn insert_consultant_search_log(
+    pool: &DatabaseConnection,
+    search_id: String,
+    log: ConsultantSearchLog,
+    searched_at: DateTime<FixedOffset>,
+) {
+    let active_model = entity::consultant_search_log::ActiveModel {
+        search_id: Set(search_id.clone()),
+        criteria: Set(json!(log.criteria)),
+        company_name: Set(log.company_name.clone()),
+        profession: Set(log.profession.clone()),
+        sort_key: Set(log.sort_key.clone()),
+        sort_order: Set(log.sort_order.clone()),
+        num_of_hits: Set(log.num_of_hits),
+        took_in_milliseconds: Set(log.took_in_milliseconds),
+        searched_at: Set(searched_at),
+    };
+    if let Err(e) = entity::consultant_search_log::Entity::insert(active_model)
+        .exec(pool)
+        .await
+    {
+        error!(
+            "failed to insert consultant_search_log (search_id: {}, log: {:?}, searched_at: {}): {}",
+            search_id, log, searched_at, e
+        );
+    }
+}
+
+/// 検索結果からコンサルタントの詳細を表示したことを非同期に保存する
+pub(super) fn save_consultant_search_click(
+    pool: &DatabaseConnection,
+    search_id: String,
+    consultant_id: i64,
+) {
+    let clicked_at = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        insert_consultant_search_click(&pool, search_id, consultant_id, clicked_at).await;
+    });
+}
+
+async fn insert_consultant_search_click(
+    pool: &DatabaseConnection,
+    search_id: String,
+    consultant_id: i64,
+    clicked_at: DateTime<FixedOffset>,
+) {
+    let active_model = entity::consultant_search_click::ActiveModel {
+        search_id: Set(search_id.clone()),
+        consultant_id: Set(consultant_id),
+        clicked_at: Set(clicked_at),
+    };
+    // 同じ検索結果から同じコンサルタントの詳細を複数回表示した場合、最初の一回のみを記録する
+    let on_conflict = OnConflict::columns([
+        entity::consultant_search_click::Column::SearchId,
+        entity::consultant_search_click::Column::ConsultantId,
+    ])
+    .do_nothing()
+    .to_owned();
+    if let Err(e) = entity::consultant_search_click::Entity::insert(active_model)
+        .on_conflict(on_conflict)
+        .exec_without_returning(pool)
+        .await
+    {
+        error!(
+            "failed to insert consultant_search_click (search_id: {}, consultant_id: {}, clicked_at: {}): {}",
+            search_id, consultant_id, clicked_at, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use common::opensearch::consultant_query::{AnnualInComeInManYenParam, YearsOfServiceParam};
+
+    use super::*;
+
+    fn create_empty_career_param() -> CareerParam {
+        CareerParam {
+            company_name: None,
+            company_id: None,
+            department_name: None,
+            office: None,
+            years_of_service: YearsOfServiceParam {
+                equal_or_more: None,
+                less_than: None,
+            },
+            employed: None,
+            contract_type: None,
+            profession: None,
+            annual_income_in_man_yen: AnnualInComeInManYenParam {
+                equal_or_more: None,
+                equal_or_less: None,
+            },
+            is_manager: None,
+            position_name: None,
+            is_new_graduate: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn create_consultant_search_log_records_only_names_of_free_text_criteria() {
+        let career_param = CareerParam {
+            company_name: Some(" テスト株式会社 ".to_string()),
+            department_name: Some("開発部".to_string()),
+            profession: Some("エンジニア".to_string()),
+            years_of_service: YearsOfServiceParam {
+                equal_or_more: Some(3),
+                less_than: None,
+            },
+            note: Some("山田太郎".to_string()),
+            ..create_empty_career_param()
+        };
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: None,
+            equal_or_less: Some(5000),
+        };
+        let sort_param = SortParam {
+            key: "rating".to_string(),
+            order: "desc".to_string(),
+        };
+
+        let log = create_consultant_search_log(
+            &career_param,
+            &fee_per_hour_in_yen_param,
+            Some(&sort_param),
+            0,
+            12,
+        );
+
+        assert_eq!(
+            ConsultantSearchLog {
+                criteria: vec![
+                    "company_name".to_string(),
+                    "department_name".to_string(),
+                    "years_of_service.equal_or_more".to_string(),
+                    "profession".to_string(),
+                    "note".to_string(),
+                    "fee_per_hour_in_yen.equal_or_less".to_string(),
+                ],
+                company_name: Some("テスト株式会社".to_string()),
+                profession: Some("エンジニア".to_string()),
+                sort_key: Some("rating".to_string()),
+                sort_order: Some("desc".to_string()),
+                num_of_hits: 0,
+                took_in_milliseconds: 12,
+            },
+            log
+        );
+    }
+
+    #[test]
+    fn create_consultant_search_log_returns_empty_criteria_if_no_criteria_specified() {
+        let fee_per_hour_in_yen_param = FeePerHourInYenParam {
+            equal_or_more: None,
+            equal_or_less: None,
+        };
+
+        let log = create_consultant_search_log(
+            &create_empty_career_param(),
+            &fee_per_hour_in_yen_param,
+            None,
+            25,
+            3,
+        );
+
+        assert_eq!(
+            ConsultantSearchLog {
+                criteria: vec![],
+                company_name: None,
+                profession: None,
+                sort_key: None,
+                sort_order: None,
+                num_of_hits: 25,
+                took_in_milliseconds: 3,
+            },
+            log
+        );
+    }
+}
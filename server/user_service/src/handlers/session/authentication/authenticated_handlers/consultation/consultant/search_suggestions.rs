@@ -0,0 +1,391 @@
+// Copyright 2023 Ken Miura
+
+use async_fred_session::fred::pool::RedisPool;
+use async_fred_session::fred::prelude::{KeysInterface, TransactionInterface};
+use async_fred_session::fred::types::{Expiration, RedisValue, SetOptions};
+use async_session::serde_json::Value;
+use async_session::Session;
+use axum::async_trait;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use axum_extra::extract::SignedCookieJar;
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::util::validator::has_control_char;
+use common::{ApiError, ErrResp, RespResult};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+use crate::handlers::session::SESSION_ID_COOKIE_NAME;
+
+/// 候補として扱う文字列（前方一致の対象）の最大長（インデックスのja_edge_ngram_tokenizerのmax_gramと合わせる）
+const MAX_PREFIX_LENGTH: usize = 20;
+
+/// リクエスト数を記録するRedisのキーの接頭辞（接頭辞の後ろにセッションIDを付与する）
+const KEY_PREFIX_TO_SUGGESTIONS_RATE_LIMIT: &str = "consultants_search_suggestions_rate_limit:";
+/// リクエスト数を数える期間（秒）
+const RATE_LIMIT_WINDOW_IN_SECONDS: i64 = 60;
+/// [RATE_LIMIT_WINDOW_IN_SECONDS]の間に受け付けるリクエストの最大数（入力の度にリクエストされることを想定した値）
+const MAX_NUM_OF_REQUESTS_PER_WINDOW: i64 = 60;
+
+pub(crate) async fn get_consultants_search_suggestions(
+    VerifiedUser { user_info }: VerifiedUser,
+    jar: SignedCookieJar,
+    query: Query<ConsultantsSearchSuggestionsQuery>,
+    State(redis_pool): State<RedisPool>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
+) -> RespResult<ConsultantsSearchSuggestionsResult> {
+    let session_id = match jar.get(SESSION_ID_COOKIE_NAME) {
+        Some(c) => c.value().to_string(),
+        None => {
+            // VerifiedUserの取得時にセッションの存在を確認済のため、ここに到達することはない
+            error!("no session cookie found");
+            return Err(unexpected_err_resp());
+        }
+    };
+    let query = query.0;
+    let op = ConsultantsSearchSuggestionsOperationImpl {
+        consultant_search_client,
+        redis_pool,
+    };
+    handle_consultants_search_suggestions(user_info.account_id, &session_id, query.prefix, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ConsultantsSearchSuggestionsQuery {
+    prefix: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct ConsultantsSearchSuggestionsResult {
+    company_names: Vec<Suggestion>,
+    professions: Vec<Suggestion>,
+}
+
+/// countは候補の文字列を職務経歴に含むコンサルタントの数（職務経歴の数ではない）
+#[derive(Serialize, Debug, PartialEq)]
+struct Suggestion {
+    value: String,
+    count: i64,
+}
+
+#[async_trait]
+trait ConsultantsSearchSuggestionsOperation {
+    async fn search_suggestions(&self, account_id: i64, prefix: &str) -> Result<Value, ErrResp>;
+
+    /// セッション（session_id）の[RATE_LIMIT_WINDOW_IN_SECONDS]の間のリクエスト数を1増やし、増やした後のリクエスト数を返す
+    async fn increment_request_count(&self, session_id: &str) -> Result<i64, ErrResp>;
+}
+
+struct ConsultantsSearchSuggestionsOperationImpl {
+    consultant_search_client: ConsultantSearchClient,
+    redis_pool: RedisPool,
+}
+
+#[async_trait]
+impl ConsultantsSearchSuggestionsOperation for ConsultantsSearchSuggestionsOperationImpl {
+    async fn search_suggestions(&self, account_id: i64, prefix: &str) -> Result<Value, ErrResp> {
+        self.consultant_search_client
+            .search_suggestions(account_id, prefix)
+            .await
+    }
+
+    async fn increment_request_count(&self, session_id: &str) -> Result<i64, ErrResp> {
+        let id = Session::id_from_cookie_value(session_id).map_err(|e| {
+            error!("failed to get session id from cookie value: {}", e);
+            unexpected_err_resp()
+        })?;
+        let key = format!("{}{}", KEY_PREFIX_TO_SUGGESTIONS_RATE_LIMIT, id);
+        // 期間の最初のリクエストでのみ有効期限付きのキーを作成（SET NX EX）し、その後リクエスト数を増やす（INCR）。
+        // 同一セッションから同時にリクエストがあっても数え漏れや有効期限の無いキーが発生しないように、二つのコマンドはMULTIで実行する
+        let trx = self.redis_pool.multi();
+        let _: () = trx
+            .set(
+                key.as_str(),
+                0,
+                Some(Expiration::EX(RATE_LIMIT_WINDOW_IN_SECONDS)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await
+            .map_err(|e| {
+                error!("failed to queue SET (key: {}): {}", key, e);
+                unexpected_err_resp()
+            })?;
+        let _: () = trx.incr(key.as_str()).await.map_err(|e| {
+            error!("failed to queue INCR (key: {}): {}", key, e);
+            unexpected_err_resp()
+        })?;
+        let (_, count): (RedisValue, i64) = trx.exec(true).await.map_err(|e| {
+            error!("failed to count request (key: {}): {}", key, e);
+            unexpected_err_resp()
+        })?;
+        Ok(count)
+    }
+}
+
+async fn handle_consultants_search_suggestions(
+    account_id: i64,
+    session_id: &str,
+    prefix: String,
+    op: impl ConsultantsSearchSuggestionsOperation,
+) -> RespResult<ConsultantsSearchSuggestionsResult> {
+    let prefix = prefix.trim().to_string();
+    let length = prefix.chars().count();
+    if length == 0 || length > MAX_PREFIX_LENGTH || has_control_char(&prefix) {
+        error!(
+            "invalid prefix (length: {}, account id: {})",
+            length, account_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidConsultantsSearchSuggestionPrefix as u32,
+            }),
+        ));
+    }
+    let count = op.increment_request_count(session_id).await?;
+    if count > MAX_NUM_OF_REQUESTS_PER_WINDOW {
+        error!(
+            "too many suggestions requests (account id: {}, count: {})",
+            account_id, count
+        );
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ApiError {
+                code: Code::TooManyConsultantsSearchSuggestionsRequests as u32,
+            }),
+        ));
+    }
+
+    info!(
+        "query param (account_id: {}, prefix: {})",
+        account_id, prefix
+    );
+    let result = op.search_suggestions(account_id, &prefix).await?;
+    let aggregations = &result["aggregations"]["careers"];
+    let company_names = parse_suggestions(&aggregations["company_names"]["values"])?;
+    let professions = parse_suggestions(&aggregations["professions"]["values"])?;
+    Ok((
+        StatusCode::OK,
+        Json(ConsultantsSearchSuggestionsResult {
+            company_names,
+            professions,
+        }),
+    ))
+}
+
+fn parse_suggestions(aggregation: &Value) -> Result<Vec<Suggestion>, ErrResp> {
+    let buckets = aggregation["buckets"].as_array().ok_or_else(|| {
+        error!("failed to get buckets: {}", aggregation);
+        unexpected_err_resp()
+    })?;
+    let mut suggestions = Vec::with_capacity(buckets.len());
+    for bucket in buckets {
+        let value = bucket["key"].as_str().ok_or_else(|| {
+            error!("failed to get key: {}", bucket);
+            unexpected_err_resp()
+        })?;
+        let count = bucket["consultants"]["doc_count"].as_i64().ok_or_else(|| {
+            error!("failed to get doc_count of consultants: {}", bucket);
+            unexpected_err_resp()
+        })?;
+        suggestions.push(Suggestion {
+            value: value.to_string(),
+            count,
+        });
+    }
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use async_session::serde_json::json;
+
+    use super::*;
+
+    const SESSION_ID: &str = "b3BlbnNlc2FtZXNlc3Npb25pZGZvcnRlc3RpbmdwdXJwb3Nlcw==";
+
+    struct ConsultantsSearchSuggestionsOperationMock {
+        account_id: i64,
+        prefix: String,
+        result: Value,
+        /// リクエスト数を増やした後の値として返す値
+        request_count: i64,
+    }
+
+    #[async_trait]
+    impl ConsultantsSearchSuggestionsOperation for ConsultantsSearchSuggestionsOperationMock {
+        async fn search_suggestions(
+            &self,
+            account_id: i64,
+            prefix: &str,
+        ) -> Result<Value, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            assert_eq!(self.prefix, prefix);
+            Ok(self.result.clone())
+        }
+
+        async fn increment_request_count(&self, session_id: &str) -> Result<i64, ErrResp> {
+            assert_eq!(SESSION_ID, session_id);
+            Ok(self.request_count)
+        }
+    }
+
+    fn create_dummy_result() -> Value {
+        json!({
+          "took": 4,
+          "timed_out": false,
+          "hits": {
+            "total": { "value": 3, "relation": "eq" },
+            "max_score": null,
+            "hits": []
+          },
+          "aggregations": {
+            "careers": {
+              "doc_count": 5,
+              "company_names": {
+                "doc_count": 3,
+                "values": {
+                  "buckets": [
+                    { "key": "テスト株式会社", "doc_count": 2, "consultants": { "doc_count": 2 } },
+                    { "key": "テスト工業", "doc_count": 1, "consultants": { "doc_count": 1 } }
+                  ]
+                }
+              },
+              "professions": {
+                "doc_count": 0,
+                "values": {
+                  "buckets": []
+                }
+              }
+            }
+          }
+        })
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_suggestions_success() {
+        let account_id = 1;
+        let op = ConsultantsSearchSuggestionsOperationMock {
+            account_id,
+            prefix: "テスト".to_string(),
+            result: create_dummy_result(),
+            request_count: 1,
+        };
+
+        let result = handle_consultants_search_suggestions(
+            account_id,
+            SESSION_ID,
+            " テスト ".to_string(),
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            ConsultantsSearchSuggestionsResult {
+                company_names: vec![
+                    Suggestion {
+                        value: "テスト株式会社".to_string(),
+                        count: 2
+                    },
+                    Suggestion {
+                        value: "テスト工業".to_string(),
+                        count: 1
+                    }
+                ],
+                professions: vec![],
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_suggestions_fail_empty_prefix() {
+        let account_id = 1;
+        let op = ConsultantsSearchSuggestionsOperationMock {
+            account_id,
+            prefix: "".to_string(),
+            result: create_dummy_result(),
+            request_count: 1,
+        };
+
+        let result =
+            handle_consultants_search_suggestions(account_id, SESSION_ID, "　".to_string(), op)
+                .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::InvalidConsultantsSearchSuggestionPrefix as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_suggestions_fail_too_long_prefix() {
+        let account_id = 1;
+        let prefix = "あ".repeat(MAX_PREFIX_LENGTH + 1);
+        let op = ConsultantsSearchSuggestionsOperationMock {
+            account_id,
+            prefix: prefix.clone(),
+            result: create_dummy_result(),
+            request_count: 1,
+        };
+
+        let result =
+            handle_consultants_search_suggestions(account_id, SESSION_ID, prefix, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::InvalidConsultantsSearchSuggestionPrefix as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_suggestions_fail_too_many_requests() {
+        let account_id = 1;
+        let op = ConsultantsSearchSuggestionsOperationMock {
+            account_id,
+            prefix: "テスト".to_string(),
+            result: create_dummy_result(),
+            request_count: MAX_NUM_OF_REQUESTS_PER_WINDOW + 1,
+        };
+
+        let result =
+            handle_consultants_search_suggestions(account_id, SESSION_ID, "テスト".to_string(), op)
+                .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, resp.0);
+        assert_eq!(
+            Code::TooManyConsultantsSearchSuggestionsRequests as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_consultants_search_suggestions_success_up_to_max_num_of_requests() {
+        let account_id = 1;
+        let op = ConsultantsSearchSuggestionsOperationMock {
+            account_id,
+            prefix: "テスト".to_string(),
+            result: create_dummy_result(),
+            request_count: MAX_NUM_OF_REQUESTS_PER_WINDOW,
+        };
+
+        let result =
+            handle_consultants_search_suggestions(account_id, SESSION_ID, "テスト".to_string(), op)
+                .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright 2023 Ken Miura
+
+use async_session::serde_json::Value;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::{async_trait, Json};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::consultant_query::CareerFeature;
+use common::{ApiError, ErrResp, RespResult};
+use entity::sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::search::{create_consultant_description, ConsultantDescription};
+use super::{
+    VALID_YEARS_OF_SERVICE_PERIOD_FIFTEEN, VALID_YEARS_OF_SERVICE_PERIOD_FIVE,
+    VALID_YEARS_OF_SERVICE_PERIOD_TEN, VALID_YEARS_OF_SERVICE_PERIOD_THREE,
+    VALID_YEARS_OF_SERVICE_PERIOD_TWENTY,
+};
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+use crate::handlers::session::authentication::user_operation::FindUserInfoOperationImpl;
+
+/// 返す似たコンサルタントの最大数
+const MAX_NUM_OF_SIMILAR_CONSULTANTS: i64 = 5;
+
+pub(crate) async fn get_similar_consultants(
+    VerifiedUser { user_info }: VerifiedUser,
+    query: Query<SimilarConsultantsQuery>,
+    State(pool): State<DatabaseConnection>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
+) -> RespResult<SimilarConsultantsResult> {
+    let query = query.0;
+    let op = SimilarConsultantsOperationImpl {
+        pool,
+        consultant_search_client,
+    };
+    handle_similar_consultants(user_info.account_id, query.consultant_id, op).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SimilarConsultantsQuery {
+    consultant_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct SimilarConsultantsResult {
+    consultants: Vec<ConsultantDescription>,
+}
+
+#[async_trait]
+trait SimilarConsultantsOperation {
+    async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp>;
+
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp>;
+
+    async fn search_similar_consultants(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+        career_features: &[CareerFeature],
+        size: i64,
+    ) -> Result<Value, ErrResp>;
+}
+
+struct SimilarConsultantsOperationImpl {
+    pool: DatabaseConnection,
+    consultant_search_client: ConsultantSearchClient,
+}
+
+#[async_trait]
+impl SimilarConsultantsOperation for SimilarConsultantsOperationImpl {
+    async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp> {
+        let op = FindUserInfoOperationImpl::new(&self.pool);
+        super::super::check_if_consultant_is_available(consultant_id, &op).await
+    }
+
+    async fn search_consultant(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+    ) -> Result<Value, ErrResp> {
+        self.consultant_search_client
+            .search_consultant(consultant_id, account_id)
+            .await
+    }
+
+    async fn search_similar_consultants(
+        &self,
+        consultant_id: i64,
+        account_id: i64,
+        career_features: &[CareerFeature],
+        size: i64,
+    ) -> Result<Value, ErrResp> {
+        self.consultant_search_client
+            .search_similar_consultants(consultant_id, account_id, career_features, size)
+            .await
+    }
+}
+
+async fn handle_similar_consultants(
+    account_id: i64,
+    consultant_id: i64,
+    op: impl SimilarConsultantsOperation,
+) -> RespResult<SimilarConsultantsResult> {
+    if !consultant_id.is_positive() {
+        error!("consultant_id ({}) is not positive", consultant_id);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NonPositiveConsultantId as u32,
+            }),
+        ));
+    }
+    let consultant_available = op.check_if_consultant_is_available(consultant_id).await?;
+    if !consultant_available {
+        error!(
+            "consultant is not available (consultant_id: {})",
+            consultant_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ConsultantIsNotAvailable as u32,
+            }),
+        ));
+    }
+
+    let result = op.search_consultant(consultant_id, account_id).await?;
+    let career_features = extract_career_features(&result)?;
+
+    info!(
+        "query param (account_id (for consultant): {}, account_id: {}, career_features: {:?})",
+        consultant_id, account_id, career_features
+    );
+    let result = op
+        .search_similar_consultants(
+            consultant_id,
+            account_id,
+            &career_features,
+            MAX_NUM_OF_SIMILAR_CONSULTANTS,
+        )
+        .await?;
+    let hits = result["hits"]["hits"].as_array().ok_or_else(|| {
+        error!("failed to get hits: {}", result);
+        unexpected_err_resp()
+    })?;
+    let mut consultants = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let consultant_description = create_consultant_description(hit)?;
+        consultants.push(consultant_description);
+    }
+    Ok((
+        StatusCode::OK,
+        Json(SimilarConsultantsResult { consultants }),
+    ))
+}
+
+fn extract_career_features(query_result: &Value) -> Result<Vec<CareerFeature>, ErrResp> {
+    let hits = query_result["hits"]["hits"].as_array().ok_or_else(|| {
+        error!("failed to get hits: {}", query_result);
+        unexpected_err_resp()
+    })?;
+    if hits.len() != 1 {
+        error!("no consultant found (hit_num: {})", hits.len());
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ConsultantDoesNotExist as u32,
+            }),
+        ));
+    }
+    let careers = hits[0]["_source"]["careers"].as_array().ok_or_else(|| {
+        error!("failed to find careers in _source: {:?}", hits[0]);
+        unexpected_err_resp()
+    })?;
+    let mut career_features = Vec::with_capacity(careers.len());
+    for career in careers {
+        let contract_type = career["contract_type"].as_str().ok_or_else(|| {
+            error!("failed to find contract_type in career: {:?}", career);
+            unexpected_err_resp()
+        })?;
+        let is_manager = career["is_manager"].as_bool().ok_or_else(|| {
+            error!("failed to find is_manager in career: {:?}", career);
+            unexpected_err_resp()
+        })?;
+        let years_of_service = career["years_of_service"].as_i64().ok_or_else(|| {
+            error!("failed to find years_of_service in career: {:?}", career);
+            unexpected_err_resp()
+        })?;
+        let (years_of_service_equal_or_more, years_of_service_less_than) =
+            find_years_of_service_band(years_of_service);
+        career_features.push(CareerFeature {
+            company_id: career["company_id"].as_i64(),
+            profession: career["profession"].as_str().map(|s| s.to_string()),
+            contract_type: contract_type.to_string(),
+            is_manager,
+            years_of_service_equal_or_more,
+            years_of_service_less_than,
+        });
+    }
+    Ok(career_features)
+}
+
+/// 在籍年数が含まれる区分（コンサルタントの詳細で表示する区分と同じ）の下限（以上）と上限（未満）を返す
+fn find_years_of_service_band(years_of_service: i64) -> (i64, Option<i64>) {
+    let boundaries = [
+        VALID_YEARS_OF_SERVICE_PERIOD_THREE as i64,
+        VALID_YEARS_OF_SERVICE_PERIOD_FIVE as i64,
+        VALID_YEARS_OF_SERVICE_PERIOD_TEN as i64,
+        VALID_YEARS_OF_SERVICE_PERIOD_FIFTEEN as i64,
+        VALID_YEARS_OF_SERVICE_PERIOD_TWENTY as i64,
+    ];
+    let mut lower = 0;
+    for boundary in boundaries {
+        if years_of_service < boundary {
+            return (lower, Some(boundary));
+        }
+        lower = boundary;
+    }
+    (lower, None)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use async_session::serde_json::json;
+
+    use super::super::search::ConsultantCareerDescription;
+    use super::*;
+
+    struct SimilarConsultantsOperationMock {
+        account_id: i64,
+        consultant_id: i64,
+        consultant_available: bool,
+        consultant_result: Value,
+        career_features: Vec<CareerFeature>,
+        similar_consultants_result: Value,
+    }
+
+    #[async_trait]
+    impl SimilarConsultantsOperation for SimilarConsultantsOperationMock {
+        async fn check_if_consultant_is_available(
+            &self,
+            consultant_id: i64,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.consultant_id, consultant_id);
+            Ok(self.consultant_available)
+        }
+
+        async fn search_consultant(
+            &self,
+            consultant_id: i64,
+            account_id: i64,
+        ) -> Result<Value, ErrResp> {
+            assert_eq!(self.consultant_id, consultant_id);
+            assert_eq!(self.account_id, account_id);
+            Ok(self.consultant_result.clone())
+        }
+
+        async fn search_similar_consultants(
+            &self,
+            consultant_id: i64,
+            account_id: i64,
+            career_features: &[CareerFeature],
+            size: i64,
+        ) -> Result<Value, ErrResp> {
+            assert_eq!(self.consultant_id, consultant_id);
+            assert_eq!(self.account_id, account_id);
+            assert_eq!(self.career_features.as_slice(), career_features);
+            assert_eq!(MAX_NUM_OF_SIMILAR_CONSULTANTS, size);
+            Ok(self.similar_consultants_result.clone())
+        }
+    }
+
+    fn create_hit(consultant_id: i64, company_id: i64, company_name: &str) -> Value {
+        json!({
+            "_index": "users",
+            "_id": consultant_id.to_string(),
+            "_score": 1.0,
+            "_source": {
+                "careers": [
+                    {
+                        "annual_income_in_man_yen": null,
+                        "career_id": consultant_id,
+                        "company_name": company_name,
+                        "normalized_company_name": company_name,
+                        "company_id": company_id,
+                        "contract_type": "regular",
+                        "department_name": null,
+                        "employed": true,
+                        "is_manager": false,
+                        "is_new_graduate": true,
+                        "note": null,
+                        "office": null,
+                        "position_name": null,
+                        "profession": "エンジニア",
+                        "years_of_service": 7
+                    }
+                ],
+                "fee_per_hour_in_yen": 5000,
+                "is_bank_account_registered": true,
+                "disabled": false,
+                "num_of_careers": 1,
+                "rating": null,
+                "num_of_rated": 0,
+                "user_account_id": consultant_id
+            }
+        })
+    }
+
+    fn create_result(hits: Vec<Value>) -> Value {
+        json!({
+            "took": 3,
+            "timed_out": false,
+            "hits": {
+                "total": { "value": hits.len(), "relation": "eq" },
+                "max_score": 1.0,
+                "hits": hits
+            }
+        })
+    }
+
+    fn create_default_op_mock(
+        account_id: i64,
+        consultant_id: i64,
+    ) -> SimilarConsultantsOperationMock {
+        SimilarConsultantsOperationMock {
+            account_id,
+            consultant_id,
+            consultant_available: true,
+            consultant_result: create_result(vec![create_hit(consultant_id, 10, "テスト１")]),
+            career_features: vec![CareerFeature {
+                company_id: Some(10),
+                profession: Some("エンジニア".to_string()),
+                contract_type: "regular".to_string(),
+                is_manager: false,
+                years_of_service_equal_or_more: 5,
+                years_of_service_less_than: Some(10),
+            }],
+            similar_consultants_result: create_result(vec![
+                create_hit(4, 10, "テスト１"),
+                create_hit(5, 11, "テスト２"),
+            ]),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_similar_consultants_success() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let op = create_default_op_mock(account_id, consultant_id);
+
+        let result = handle_similar_consultants(account_id, consultant_id, op).await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            SimilarConsultantsResult {
+                consultants: vec![
+                    ConsultantDescription {
+                        consultant_id: 4,
+                        fee_per_hour_in_yen: 5000,
+                        rating: None,
+                        num_of_rated: 0,
+                        careers: vec![ConsultantCareerDescription {
+                            company_name: "テスト１".to_string(),
+                            profession: Some("エンジニア".to_string()),
+                            office: None,
+                        }],
+                    },
+                    ConsultantDescription {
+                        consultant_id: 5,
+                        fee_per_hour_in_yen: 5000,
+                        rating: None,
+                        num_of_rated: 0,
+                        careers: vec![ConsultantCareerDescription {
+                            company_name: "テスト２".to_string(),
+                            profession: Some("エンジニア".to_string()),
+                            office: None,
+                        }],
+                    }
+                ]
+            },
+            resp.1 .0
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_similar_consultants_fail_non_positive_consultant_id() {
+        let account_id = 1;
+        let consultant_id = 0;
+        let op = create_default_op_mock(account_id, consultant_id);
+
+        let result = handle_similar_consultants(account_id, consultant_id, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NonPositiveConsultantId as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_similar_consultants_fail_consultant_is_not_available() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let mut op = create_default_op_mock(account_id, consultant_id);
+        op.consultant_available = false;
+
+        let result = handle_similar_consultants(account_id, consultant_id, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ConsultantIsNotAvailable as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_similar_consultants_fail_consultant_does_not_exist() {
+        let account_id = 1;
+        let consultant_id = 2;
+        let mut op = create_default_op_mock(account_id, consultant_id);
+        // 職務経歴や相談料が未登録の場合等、相談を受け付けられないコンサルタントは検索でヒットしない
+        op.consultant_result = create_result(vec![]);
+
+        let result = handle_similar_consultants(account_id, consultant_id, op).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ConsultantDoesNotExist as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn find_years_of_service_band_returns_band_including_years_of_service() {
+        assert_eq!((0, Some(3)), find_years_of_service_band(0));
+        assert_eq!((0, Some(3)), find_years_of_service_band(2));
+        assert_eq!((3, Some(5)), find_years_of_service_band(3));
+        assert_eq!((5, Some(10)), find_years_of_service_band(9));
+        assert_eq!((10, Some(15)), find_years_of_service_band(10));
+        assert_eq!((15, Some(20)), find_years_of_service_band(19));
+        assert_eq!((20, None), find_years_of_service_band(20));
+        assert_eq!((20, None), find_years_of_service_band(35));
+    }
+}
@@ -7,9 +7,13 @@ use once_cell::sync::Lazy;
 use super::search::SortParam;
 
 static KEY_SET: Lazy<HashSet<String>> = Lazy::new(|| {
-    let mut set: HashSet<String> = HashSet::with_capacity(2);
+    let mut set: HashSet<String> = HashSet::with_capacity(6);
     set.insert("fee_per_hour_in_yen".to_string());
     set.insert("rating".to_string());
+    set.insert("num_of_rated".to_string());
+    set.insert("max_years_of_service".to_string());
+    set.insert("joined_at".to_string());
+    set.insert("relevance".to_string());
     set
 });
 
@@ -98,6 +102,70 @@ mod tests {
                 },
                 expected: Ok(()),
             },
+            TestCase {
+                name: "num_of_rated asc".to_string(),
+                input: SortParam {
+                    key: "num_of_rated".to_string(),
+                    order: "asc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "num_of_rated desc".to_string(),
+                input: SortParam {
+                    key: "num_of_rated".to_string(),
+                    order: "desc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "max_years_of_service asc".to_string(),
+                input: SortParam {
+                    key: "max_years_of_service".to_string(),
+                    order: "asc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "max_years_of_service desc".to_string(),
+                input: SortParam {
+                    key: "max_years_of_service".to_string(),
+                    order: "desc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "joined_at asc".to_string(),
+                input: SortParam {
+                    key: "joined_at".to_string(),
+                    order: "asc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "joined_at desc".to_string(),
+                input: SortParam {
+                    key: "joined_at".to_string(),
+                    order: "desc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "relevance asc".to_string(),
+                input: SortParam {
+                    key: "relevance".to_string(),
+                    order: "asc".to_string(),
+                },
+                expected: Ok(()),
+            },
+            TestCase {
+                name: "relevance desc".to_string(),
+                input: SortParam {
+                    key: "relevance".to_string(),
+                    order: "desc".to_string(),
+                },
+                expected: Ok(()),
+            },
             TestCase {
                 name: "invalid key".to_string(),
                 input: SortParam {
@@ -4,9 +4,13 @@ use axum::async_trait;
 use axum::http::StatusCode;
 use axum::{extract::State, Json};
 use chrono::{DateTime, Datelike, Duration, FixedOffset, Timelike, Utc};
+use common::payment_event::{insert_payment_event, PAYMENT_STATE_AWAITING_PAYMENT};
 use common::smtp::{SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
 use common::util::validator::uuid_validator::validate_uuid;
 use common::util::Maintenance;
+use common::virtual_bank_account::{
+    assign_virtual_bank_account, get_virtual_bank_account_pool_state,
+};
 use common::{smtp::SendMail, RespResult, JAPANESE_TIME_ZONE};
 use common::{ApiError, ErrResp, ErrRespStruct, WEB_SITE_NAME};
 use entity::prelude::ConsultationReq;
@@ -77,16 +81,6 @@ static BANK_BRANCH_NAME: Lazy<String> = Lazy::new(|| {
 // 普通のみ利用する想定。普通以外が必要になったときに環境変数として作成し直す。
 const BANK_ACCOUNT_TYPE: &str = "普通";
 
-pub(crate) const KEY_TO_BANK_ACCOUNT_NUMBER: &str = "BANK_ACCOUNT_NUMBER";
-/// 収納代行として相談料金を預かる口座の銀行口座番号
-static BANK_ACCOUNT_NUMBER: Lazy<String> = Lazy::new(|| {
-    std::env::var(KEY_TO_BANK_ACCOUNT_NUMBER).unwrap_or_else(|_| {
-        // UTのためにダミー用の初期値を設定しているだけ
-        // 実行時には環境変数を指定して適切なものに入れ替える
-        "zzzzzzz".to_string()
-    })
-});
-
 pub(crate) const KEY_TO_BANK_ACCOUNT_HOLDER_NAME: &str = "BANK_ACCOUNT_HOLDER_NAME";
 /// 収納代行として相談料金を預かる口座の銀行口座名義名
 static BANK_ACCOUNT_HOLDER_NAME: Lazy<String> = Lazy::new(|| {
@@ -197,6 +191,7 @@ async fn handle_consultation_request_acceptance(
             room_name,
             *current_date_time,
             req.fee_per_hour_in_yen,
+            consultant_email_address.as_str(),
         )
         .await?;
 
@@ -281,6 +276,7 @@ trait ConsultationRequestAcceptanceOperation {
         room_name: String,
         current_date_time: DateTime<FixedOffset>,
         fee_per_hour_in_yen: i32,
+        consultant_email_address: &str,
     ) -> Result<AcceptedConsultation, ErrResp>;
 }
 
@@ -289,7 +285,9 @@ struct AcceptedConsultation {
     user_account_id: i64,
     consultant_id: i64,
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
     consultation_date_time_in_jst: DateTime<FixedOffset>,
+    virtual_bank_account_number: String,
 }
 
 struct ConsultationRequestAcceptanceOperationImpl {
@@ -432,7 +430,9 @@ impl ConsultationRequestAcceptanceOperation for ConsultationRequestAcceptanceOpe
         room_name: String,
         current_date_time: DateTime<FixedOffset>,
         fee_per_hour_in_yen: i32,
+        consultant_email_address: &str,
     ) -> Result<AcceptedConsultation, ErrResp> {
+        let consultant_email_address = consultant_email_address.to_string();
         let consultation = self
             .pool
             .transaction::<_, AcceptedConsultation, ErrRespStruct>(|txn| {
@@ -444,15 +444,56 @@ impl ConsultationRequestAcceptanceOperation for ConsultationRequestAcceptanceOpe
                         .await?;
                     create_awaiting_payment(
                         c.consultation_id,
-                        c.user_account_id,
-                        c.consultant_id,
                         c.meeting_at,
                         current_date_time,
                         fee_per_hour_in_yen,
+                        &req,
                         txn,
                     )
                     .await?;
 
+                    insert_payment_event(
+                        c.consultation_id,
+                        None,
+                        PAYMENT_STATE_AWAITING_PAYMENT,
+                        consultant_email_address.as_str(),
+                        current_date_time,
+                        txn,
+                    )
+                    .await?;
+
+                    let virtual_bank_account_number = match assign_virtual_bank_account(
+                        c.consultation_id,
+                        current_date_time,
+                        txn,
+                    )
+                    .await?
+                    {
+                        Some(account_number) => account_number,
+                        None => {
+                            // 運用者が口座を補充できるように、口座の残数をエラーとして記録する
+                            let pool_state = get_virtual_bank_account_pool_state(txn).await?;
+                            error!(
+                                "no virtual_bank_account available, register new accounts (consultation_id: {}, total: {}, unassigned (including accounts locked by other transactions): {})",
+                                c.consultation_id, pool_state.total, pool_state.unassigned
+                            );
+                            return Err(ErrRespStruct {
+                                err_resp: no_virtual_bank_account_available_err_resp(),
+                            });
+                        }
+                    };
+
+                    if let Some(coupon_id) = req.coupon_id {
+                        create_coupon_use(
+                            c.consultation_id,
+                            coupon_id,
+                            c.user_account_id,
+                            current_date_time,
+                            txn,
+                        )
+                        .await?;
+                    }
+
                     delete_consultation_req_by_consultation_req_id(req.consultation_req_id, txn)
                         .await?;
 
@@ -460,8 +501,10 @@ impl ConsultationRequestAcceptanceOperation for ConsultationRequestAcceptanceOpe
                         user_account_id: req.user_account_id,
                         consultant_id: req.consultant_id,
                         fee_per_hour_in_yen: req.fee_per_hour_in_yen,
+                        discount_in_yen: req.discount_in_yen,
                         consultation_date_time_in_jst: meeting_date_time
                             .with_timezone(&(*JAPANESE_TIME_ZONE)),
+                        virtual_bank_account_number,
                     })
                 })
             })
@@ -480,6 +523,18 @@ impl ConsultationRequestAcceptanceOperation for ConsultationRequestAcceptanceOpe
     }
 }
 
+/// 割り当て可能なバーチャル口座がない場合のレスポンス
+///
+/// 口座が補充されるまで承認できない一時的な状態のため、503を返す。
+fn no_virtual_bank_account_available_err_resp() -> ErrResp {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ApiError {
+            code: Code::NoVirtualBankAccountAvailable as u32,
+        }),
+    )
+}
+
 async fn count_consultation_filtered_by_consultant_id_and_meeting_at(
     consultant_id: i64,
     meeting_date_time: DateTime<FixedOffset>,
@@ -561,24 +616,50 @@ async fn create_consultation(
 
 async fn create_awaiting_payment(
     consultation_id: i64,
-    user_account_id: i64,
-    consultant_id: i64,
     meeting_at: DateTime<FixedOffset>,
     current_date_time: DateTime<FixedOffset>,
     fee_per_hour_in_yen: i32,
+    req: &consultation_req::Model,
     txn: &DatabaseTransaction,
 ) -> Result<(), ErrRespStruct> {
+    // 割引に関する情報は、相談申し込み時に確定した値を引き継ぐ
     let active_model = entity::awaiting_payment::ActiveModel {
         consultation_id: Set(consultation_id),
-        user_account_id: Set(user_account_id),
-        consultant_id: Set(consultant_id),
+        user_account_id: Set(req.user_account_id),
+        consultant_id: Set(req.consultant_id),
         meeting_at: Set(meeting_at),
         fee_per_hour_in_yen: Set(fee_per_hour_in_yen),
+        coupon_id: Set(req.coupon_id),
+        discount_in_yen: Set(req.discount_in_yen),
+        discount_borne_by: Set(req.discount_borne_by.clone()),
         created_at: Set(current_date_time),
     };
     let _ = active_model.insert(txn).await.map_err(|e|{
-        error!("failed to insert awaiting_payment (consultation_id: {}, current_date_time: {}, fee_per_hour_in_yen: {}): {}", 
-            consultation_id, current_date_time, fee_per_hour_in_yen, e);
+        error!("failed to insert awaiting_payment (consultation_id: {}, current_date_time: {}, fee_per_hour_in_yen: {}, consultation_req: {:?}): {}", 
+            consultation_id, current_date_time, fee_per_hour_in_yen, req, e);
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    Ok(())
+}
+
+async fn create_coupon_use(
+    consultation_id: i64,
+    coupon_id: i64,
+    user_account_id: i64,
+    current_date_time: DateTime<FixedOffset>,
+    txn: &DatabaseTransaction,
+) -> Result<(), ErrRespStruct> {
+    let active_model = entity::coupon_use::ActiveModel {
+        consultation_id: Set(consultation_id),
+        coupon_id: Set(coupon_id),
+        user_account_id: Set(user_account_id),
+        created_at: Set(current_date_time),
+    };
+    let _ = active_model.insert(txn).await.map_err(|e| {
+        error!("failed to insert coupon_use (consultation_id: {}, coupon_id: {}, user_account_id: {}, current_date_time: {}): {}",
+            consultation_id, coupon_id, user_account_id, current_date_time, e);
         ErrRespStruct {
             err_resp: unexpected_err_resp(),
         }
@@ -850,7 +931,9 @@ async fn send_mail_to_user(
         consultation_req_id,
         consultation.consultant_id,
         consultation.fee_per_hour_in_yen,
+        consultation.discount_in_yen,
         date_time.as_str(),
+        consultation.virtual_bank_account_number.as_str(),
     );
     send_mail
         .send_mail(
@@ -875,8 +958,11 @@ fn create_text_for_user(
     consultation_req_id: i64,
     consultant_id: i64,
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
     consultation_date_time: &str,
+    virtual_bank_account_number: &str,
 ) -> String {
+    let fee = super::super::create_fee_description(fee_per_hour_in_yen, discount_in_yen);
     format!(
         r"相談申し込み（相談申し込み番号: {}）が成立しました。ログイン後、スケジュールに相談室が作られていることをご確認下さい。下記に成立した相談申し込みの詳細を記載いたします。
 
@@ -884,7 +970,7 @@ fn create_text_for_user(
   コンサルタントID: {}
 
 相談料金
-  {} 円
+  {}
 
 相談開始日時
   {}
@@ -897,15 +983,13 @@ fn create_text_for_user(
   口座番号: {}
   口座名義人: {}
 
-入金の際、依頼人名は姓名、空白、相談日時（6桁の数字）として下さい（例 姓名がタナカ　タロウ、相談日時が9月29日19時のとき、依頼人名は「タナカ　タロウ　０９２９１９」となります（※））お名前に加えて相談日時を確認できない場合、正しく入金確認出来ないことがありますので必ず前述の通りご入力をお願いします。
-
-（※）依頼人名に入力可能な文字数制限に達して全て入力出来ない場合、可能なところまで入力して振り込みを行って下さい。
+上記の口座番号は、この相談の入金確認のためだけに割り当てられたものです。他の相談の入金には利用せず、相談毎に記載された口座番号へ入金をお願いいたします。
 
 【お問い合わせ先】
 Email: {}",
         consultation_req_id,
         consultant_id,
-        fee_per_hour_in_yen,
+        fee,
         consultation_date_time,
         DEADLINE_OF_PAYMENT_IN_DAYS,
         *BANK_NAME,
@@ -913,7 +997,7 @@ Email: {}",
         *BANK_BRANCH_NAME,
         *BANK_BRANCH_CODE,
         BANK_ACCOUNT_TYPE,
-        *BANK_ACCOUNT_NUMBER,
+        virtual_bank_account_number,
         *BANK_ACCOUNT_HOLDER_NAME,
         INQUIRY_EMAIL_ADDRESS.as_str()
     )
@@ -1010,6 +1094,7 @@ mod tests {
         maintenance_info: Vec<Maintenance>,
         consultation: AcceptedConsultation,
         room_name: String,
+        virtual_bank_account_available: bool,
     }
 
     #[async_trait]
@@ -1087,6 +1172,7 @@ mod tests {
             room_name: String,
             current_date_time: DateTime<FixedOffset>,
             fee_per_hour_in_yen: i32,
+            _consultant_email_address: &str,
         ) -> Result<AcceptedConsultation, ErrResp> {
             assert_eq!(
                 self.consultation_req.consultation_req_id,
@@ -1099,6 +1185,9 @@ mod tests {
                 self.consultation_req.fee_per_hour_in_yen,
                 fee_per_hour_in_yen
             );
+            if !self.virtual_bank_account_available {
+                return Err(no_virtual_bank_account_available_err_resp());
+            }
             Ok(self.consultation.clone())
         }
     }
@@ -1191,11 +1280,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1251,11 +1343,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 6, 15, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1311,11 +1406,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 7, 7, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1371,11 +1469,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: SendMailMock { fail: true },
                 },
@@ -1439,11 +1540,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1507,11 +1611,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 22, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1575,11 +1682,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 22, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1643,11 +1753,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1717,11 +1830,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1777,11 +1893,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1842,11 +1961,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1907,11 +2029,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -1972,11 +2097,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2037,11 +2165,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2102,11 +2233,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2167,11 +2301,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2240,14 +2377,17 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 7, 0, 0)
                                 .unwrap()
                                 + Duration::seconds(
                                     *MIN_DURATION_BEFORE_CONSULTATION_ACCEPTANCE_IN_SECONDS as i64,
                                 ),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2316,14 +2456,17 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 7, 0, 0)
                                 .unwrap()
                                 + Duration::seconds(
                                     *MIN_DURATION_BEFORE_CONSULTATION_ACCEPTANCE_IN_SECONDS as i64,
                                 ),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2379,11 +2522,14 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE
                                 .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
                                 .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2434,9 +2580,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2487,9 +2636,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2540,9 +2692,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2593,9 +2748,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2650,9 +2808,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2707,9 +2868,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 19, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2764,9 +2928,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 19, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2821,9 +2988,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 19, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2878,9 +3048,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 19, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail: send_mail.clone(),
                 },
@@ -2931,9 +3104,12 @@ mod tests {
                             user_account_id,
                             consultant_id: user_account_id_of_consultant,
                             fee_per_hour_in_yen,
+                            discount_in_yen: 0,
                             consultation_date_time_in_jst: JAPANESE_TIME_ZONE.with_ymd_and_hms(2023, 1, 5, 23, 0, 0).unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
                         },
                         room_name: room_name.to_string(),
+                        virtual_bank_account_available: true,
                     },
                     send_mail,
                 },
@@ -2944,6 +3120,74 @@ mod tests {
                     }),
                 )),
             },
+            TestCase {
+                name: "fail NoVirtualBankAccountAvailable".to_string(),
+                input: Input {
+                    user_account_id: user_account_id_of_consultant,
+                    email_address: consultant_email_address.to_string(),
+                    param: ConsultationRequestAcceptanceParam {
+                        consultation_req_id,
+                        picked_candidate,
+                        user_checked,
+                    },
+                    current_date_time,
+                    room_name: room_name.to_string(),
+                    op: ConsultationRequestAcceptanceOperationMock {
+                        consultation_req: ConsultationRequest {
+                            consultation_req_id,
+                            user_account_id,
+                            consultant_id: user_account_id_of_consultant,
+                            fee_per_hour_in_yen,
+                            first_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                                .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
+                                .unwrap(),
+                            second_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                                .with_ymd_and_hms(2023, 1, 6, 15, 0, 0)
+                                .unwrap(),
+                            third_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                                .with_ymd_and_hms(2023, 1, 7, 7, 0, 0)
+                                .unwrap(),
+                            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                                .with_ymd_and_hms(2023, 1, 7, 7, 0, 0)
+                                .unwrap(),
+                        },
+                        user: Some(UserInfo {
+                            account_id: user_account_id,
+                            email_address: user_email_address.to_string(),
+                            mfa_enabled_at: None,
+                            disabled_at: None,
+                        }),
+                        meeting_date_time: JAPANESE_TIME_ZONE
+                            .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
+                            .unwrap(),
+                        cnt_user_side_consultation_by_user_account_id: 0,
+                        cnt_consultant_side_consultation_by_user_account_id: 0,
+                        cnt_consultant_side_consultation_by_consultant_id: 0,
+                        cnt_user_side_consultation_by_consultant_id: 0,
+                        current_date_time,
+                        maintenance_info: vec![],
+                        consultation: AcceptedConsultation {
+                            user_account_id,
+                            consultant_id: user_account_id_of_consultant,
+                            fee_per_hour_in_yen,
+                            discount_in_yen: 0,
+                            consultation_date_time_in_jst: JAPANESE_TIME_ZONE
+                                .with_ymd_and_hms(2023, 1, 5, 23, 0, 0)
+                                .unwrap(),
+                            virtual_bank_account_number: "1234567".to_string(),
+                        },
+                        room_name: room_name.to_string(),
+                        virtual_bank_account_available: false,
+                    },
+                    send_mail: SendMailMock { fail: false },
+                },
+                expected: Err((
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ApiError {
+                        code: Code::NoVirtualBankAccountAvailable as u32,
+                    }),
+                )),
+            },
         ]
     });
 
@@ -2990,12 +3234,15 @@ mod tests {
         let consultant_id = 53;
         let fee_per_hour_in_yen = 5000;
         let consultation_date_time = "2022年 11月 12日 7時00分";
+        let virtual_bank_account_number = "1234567";
 
         let result = create_text_for_user(
             consultation_req_id,
             consultant_id,
             fee_per_hour_in_yen,
+            0,
             consultation_date_time,
+            virtual_bank_account_number,
         );
 
         let expected = format!(
@@ -3018,15 +3265,75 @@ mod tests {
   口座番号: {}
   口座名義人: {}
 
-入金の際、依頼人名は姓名、空白、相談日時（6桁の数字）として下さい（例 姓名がタナカ　タロウ、相談日時が9月29日19時のとき、依頼人名は「タナカ　タロウ　０９２９１９」となります（※））お名前に加えて相談日時を確認できない場合、正しく入金確認出来ないことがありますので必ず前述の通りご入力をお願いします。
+上記の口座番号は、この相談の入金確認のためだけに割り当てられたものです。他の相談の入金には利用せず、相談毎に記載された口座番号へ入金をお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+            consultation_req_id,
+            consultant_id,
+            fee_per_hour_in_yen,
+            consultation_date_time,
+            DEADLINE_OF_PAYMENT_IN_DAYS,
+            *BANK_NAME,
+            *BANK_CODE,
+            *BANK_BRANCH_NAME,
+            *BANK_BRANCH_CODE,
+            BANK_ACCOUNT_TYPE,
+            virtual_bank_account_number,
+            *BANK_ACCOUNT_HOLDER_NAME,
+            INQUIRY_EMAIL_ADDRESS.as_str()
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_create_text_for_user_with_discount() {
+        let consultation_req_id = 1312;
+        let consultant_id = 53;
+        let fee_per_hour_in_yen = 5000;
+        let discount_in_yen = 1000;
+        let consultation_date_time = "2022年 11月 12日 7時00分";
+        let virtual_bank_account_number = "1234567";
+
+        let result = create_text_for_user(
+            consultation_req_id,
+            consultant_id,
+            fee_per_hour_in_yen,
+            discount_in_yen,
+            consultation_date_time,
+            virtual_bank_account_number,
+        );
+
+        let expected = format!(
+            r"相談申し込み（相談申し込み番号: {}）が成立しました。ログイン後、スケジュールに相談室が作られていることをご確認下さい。下記に成立した相談申し込みの詳細を記載いたします。
+
+相談相手
+  コンサルタントID: {}
+
+相談料金
+  {} 円（クーポン適用前の相談料金: {} 円、割引額: {} 円）
+
+相談開始日時
+  {}
+
+相談開始日時の{}日前までに下記の口座に入金をお願いいたします（入金の際にかかる振込手数料はユーザーのご負担となります）入金が確認できない場合、相談を行うことは出来ないため、期日に余裕を持って入金をするようお願いいたします。
+
+  銀行名: {} (銀行コード: {})
+  支店名: {} (支店コード: {})
+  口座種別: {}
+  口座番号: {}
+  口座名義人: {}
 
-（※）依頼人名に入力可能な文字数制限に達して全て入力出来ない場合、可能なところまで入力して振り込みを行って下さい。
+上記の口座番号は、この相談の入金確認のためだけに割り当てられたものです。他の相談の入金には利用せず、相談毎に記載された口座番号へ入金をお願いいたします。
 
 【お問い合わせ先】
 Email: {}",
             consultation_req_id,
             consultant_id,
+            fee_per_hour_in_yen - discount_in_yen,
             fee_per_hour_in_yen,
+            discount_in_yen,
             consultation_date_time,
             DEADLINE_OF_PAYMENT_IN_DAYS,
             *BANK_NAME,
@@ -3034,7 +3341,7 @@ Email: {}",
             *BANK_BRANCH_NAME,
             *BANK_BRANCH_CODE,
             BANK_ACCOUNT_TYPE,
-            *BANK_ACCOUNT_NUMBER,
+            virtual_bank_account_number,
             *BANK_ACCOUNT_HOLDER_NAME,
             INQUIRY_EMAIL_ADDRESS.as_str()
         );
@@ -5,14 +5,14 @@ use axum::async_trait;
 use axum::http::StatusCode;
 use axum::{extract::State, Json};
 use chrono::{DateTime, FixedOffset, Utc};
-use common::opensearch::{update_document, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::INDEX_NAME;
 use common::rating::calculate_average_rating;
 use common::{ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE};
 use entity::sea_orm::{
     ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
     QueryFilter, Set, TransactionError, TransactionTrait,
 };
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -30,10 +30,13 @@ use super::{
 pub(crate) async fn post_consultant_rating(
     VerifiedUser { user_info }: VerifiedUser,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(req): Json<ConsultantRatingParam>,
 ) -> RespResult<ConsultantRatingResult> {
-    let op = ConsultantRatingOperationImpl { pool, index_client };
+    let op = ConsultantRatingOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
     handle_consultant_rating(
         user_info.account_id,
@@ -84,7 +87,7 @@ trait ConsultantRatingOperation {
 
 struct ConsultantRatingOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -215,7 +218,7 @@ impl ConsultantRatingOperation for ConsultantRatingOperationImpl {
         averate_rating: f64,
         num_of_rated: i32,
     ) -> Result<(), ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         self.pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
                 Box::pin(async move {
@@ -257,7 +260,7 @@ impl ConsultantRatingOperation for ConsultantRatingOperationImpl {
                             return Ok(());
                         }
                     };
-                    update_rating_info_on_document(INDEX_NAME, doc.document_id.to_string().as_str(), averate_rating, num_of_rated, index_client).await?;
+                    update_rating_info_on_document(INDEX_NAME, doc.document_id.to_string().as_str(), averate_rating, num_of_rated, consultant_search_client).await?;
                     Ok(())
                 })
             })
@@ -306,7 +309,7 @@ async fn update_rating_info_on_document(
     document_id: &str,
     averate_rating: f64,
     num_of_rated: i32,
-    client: OpenSearch,
+    client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let script = json!({
         "doc": {
@@ -314,7 +317,7 @@ async fn update_rating_info_on_document(
             "num_of_rated": num_of_rated
         }
     });
-    update_document(index_name, document_id, &script, &client)
+    client.update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
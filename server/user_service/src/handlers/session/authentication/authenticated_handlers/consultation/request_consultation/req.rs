@@ -5,11 +5,13 @@ use axum::extract::State;
 use axum::http::StatusCode;
 use axum::Json;
 use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Timelike, Utc};
+use common::coupon::calculate_discount_in_yen;
 use common::smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
+use common::util::validator::coupon_code_validator::validate_coupon_code;
 use common::{ApiError, JAPANESE_TIME_ZONE, WEB_SITE_NAME};
 use common::{ErrResp, RespResult};
 use entity::sea_orm::ActiveValue::NotSet;
-use entity::sea_orm::{ActiveModelTrait, Set};
+use entity::sea_orm::{ActiveModelTrait, ColumnTrait, PaginatorTrait, QueryFilter, Set};
 use entity::sea_orm::{DatabaseConnection, EntityTrait};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -60,6 +62,7 @@ pub(crate) struct RequestConsultationParam {
     first_candidate_in_jst: ConsultationDateTime,
     second_candidate_in_jst: ConsultationDateTime,
     third_candidate_in_jst: ConsultationDateTime,
+    coupon_code: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, PartialEq)]
@@ -72,6 +75,25 @@ struct Candidates {
     third_candidate_in_jst: DateTime<FixedOffset>,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+struct Coupon {
+    coupon_id: i64,
+    discount_type: String,
+    discount_value: i32,
+    max_num_of_uses: Option<i32>,
+    max_num_of_uses_per_user: Option<i32>,
+    valid_from: DateTime<FixedOffset>,
+    valid_until: DateTime<FixedOffset>,
+    discount_borne_by: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Discount {
+    coupon_id: i64,
+    discount_in_yen: i32,
+    discount_borne_by: String,
+}
+
 #[async_trait]
 trait RequestConsultationOperation {
     async fn check_if_consultant_is_available(&self, consultant_id: i64) -> Result<bool, ErrResp>;
@@ -81,6 +103,17 @@ trait RequestConsultationOperation {
         consultant_id: i64,
     ) -> Result<Option<i32>, ErrResp>;
 
+    async fn find_coupon_by_code(&self, code: String) -> Result<Option<Coupon>, ErrResp>;
+
+    /// クーポンを利用して承認された相談の数と、クーポンを利用している未処理の相談申し込みの数の合計を返す
+    ///
+    /// user_account_idを指定した場合、そのユーザーによる利用のみを数える
+    async fn count_coupon_uses(
+        &self,
+        coupon_id: i64,
+        user_account_id: Option<i64>,
+    ) -> Result<u64, ErrResp>;
+
     async fn create_request_consultation(
         &self,
         user_account_id: i64,
@@ -88,6 +121,7 @@ trait RequestConsultationOperation {
         candidates: &Candidates,
         latest_candidate_date_time_in_jst: DateTime<FixedOffset>,
         fee_per_hour_in_yen: i32,
+        discount: Option<Discount>,
     ) -> Result<i64, ErrResp>;
 
     async fn get_consultant_email_address_by_consultant_id(
@@ -124,6 +158,63 @@ impl RequestConsultationOperation for RequestConsultationOperationImpl {
         Ok(model.map(|m| m.fee_per_hour_in_yen))
     }
 
+    async fn find_coupon_by_code(&self, code: String) -> Result<Option<Coupon>, ErrResp> {
+        let model = entity::coupon::Entity::find()
+            .filter(entity::coupon::Column::Code.eq(code.clone()))
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!("failed to find coupon (code: {}): {}", code, e);
+                unexpected_err_resp()
+            })?;
+        Ok(model.map(|m| Coupon {
+            coupon_id: m.coupon_id,
+            discount_type: m.discount_type,
+            discount_value: m.discount_value,
+            max_num_of_uses: m.max_num_of_uses,
+            max_num_of_uses_per_user: m.max_num_of_uses_per_user,
+            valid_from: m.valid_from,
+            valid_until: m.valid_until,
+            discount_borne_by: m.discount_borne_by,
+        }))
+    }
+
+    async fn count_coupon_uses(
+        &self,
+        coupon_id: i64,
+        user_account_id: Option<i64>,
+    ) -> Result<u64, ErrResp> {
+        let mut coupon_use_query = entity::coupon_use::Entity::find()
+            .filter(entity::coupon_use::Column::CouponId.eq(coupon_id));
+        let mut consultation_req_query = entity::consultation_req::Entity::find()
+            .filter(entity::consultation_req::Column::CouponId.eq(coupon_id));
+        if let Some(user_account_id) = user_account_id {
+            coupon_use_query = coupon_use_query
+                .filter(entity::coupon_use::Column::UserAccountId.eq(user_account_id));
+            consultation_req_query = consultation_req_query
+                .filter(entity::consultation_req::Column::UserAccountId.eq(user_account_id));
+        }
+        let num_of_coupon_uses = coupon_use_query.count(&self.pool).await.map_err(|e| {
+            error!(
+                "failed to count coupon_use (coupon_id: {}, user_account_id: {:?}): {}",
+                coupon_id, user_account_id, e
+            );
+            unexpected_err_resp()
+        })?;
+        let num_of_consultation_reqs =
+            consultation_req_query
+                .count(&self.pool)
+                .await
+                .map_err(|e| {
+                    error!(
+                "failed to count consultation_req (coupon_id: {}, user_account_id: {:?}): {}",
+                coupon_id, user_account_id, e
+            );
+                    unexpected_err_resp()
+                })?;
+        Ok(num_of_coupon_uses + num_of_consultation_reqs)
+    }
+
     async fn create_request_consultation(
         &self,
         user_account_id: i64,
@@ -131,6 +222,7 @@ impl RequestConsultationOperation for RequestConsultationOperationImpl {
         candidates: &Candidates,
         latest_candidate_date_time_in_jst: DateTime<FixedOffset>,
         fee_per_hour_in_yen: i32,
+        discount: Option<Discount>,
     ) -> Result<i64, ErrResp> {
         let active_model = entity::consultation_req::ActiveModel {
             consultation_req_id: NotSet,
@@ -141,11 +233,14 @@ impl RequestConsultationOperation for RequestConsultationOperationImpl {
             third_candidate_date_time: Set(candidates.third_candidate_in_jst),
             latest_candidate_date_time: Set(latest_candidate_date_time_in_jst),
             fee_per_hour_in_yen: Set(fee_per_hour_in_yen),
+            coupon_id: Set(discount.as_ref().map(|d| d.coupon_id)),
+            discount_in_yen: Set(discount.as_ref().map(|d| d.discount_in_yen).unwrap_or(0)),
+            discount_borne_by: Set(discount.as_ref().map(|d| d.discount_borne_by.clone())),
         };
         let result = active_model.insert(&self.pool).await.map_err(|e| {
             error!(
-                "failed to insert consultation_req (user_account_id: {}, consultant_id: {}, candidates: {:?}, latest_candidate_date_time_in_jst: {}, fee_per_hour_in_yen: {}): {}",
-                user_account_id, consultant_id, candidates, latest_candidate_date_time_in_jst, fee_per_hour_in_yen, e
+                "failed to insert consultation_req (user_account_id: {}, consultant_id: {}, candidates: {:?}, latest_candidate_date_time_in_jst: {}, fee_per_hour_in_yen: {}, discount: {:?}): {}",
+                user_account_id, consultant_id, candidates, latest_candidate_date_time_in_jst, fee_per_hour_in_yen, discount, e
             );
             unexpected_err_resp()
         })?;
@@ -218,6 +313,21 @@ async fn handle_request_consultation(
     )?;
     let latest_candiate_in_jst = extract_latest_candidate_date_time_in_jst(&candidates)?;
 
+    let discount = match request_consultation_param.coupon_code {
+        Some(coupon_code) => Some(
+            apply_coupon(
+                coupon_code,
+                user_account_id,
+                fee_per_hour_in_yen,
+                current_date_time,
+                &op,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let discount_in_yen = discount.as_ref().map(|d| d.discount_in_yen).unwrap_or(0);
+
     let consultation_req_id = op
         .create_request_consultation(
             user_account_id,
@@ -225,6 +335,7 @@ async fn handle_request_consultation(
             &candidates,
             latest_candiate_in_jst,
             fee_per_hour_in_yen,
+            discount,
         )
         .await?;
 
@@ -249,6 +360,7 @@ async fn handle_request_consultation(
         consultation_req_id,
         &candidates_in_string,
         fee_per_hour_in_yen,
+        discount_in_yen,
         &send_mail,
     )
     .await?;
@@ -256,6 +368,118 @@ async fn handle_request_consultation(
     Ok((StatusCode::OK, Json(RequestConsultationResult {})))
 }
 
+// NOTE:
+// 利用回数の確認と相談申し込みの作成は同一トランザクションで行っていない。
+// そのため、同時に申し込みが行われた場合、利用回数の上限をわずかに超えて利用される可能性がある。
+// 割引の範囲でしか損失は発生しないため、現時点では厳密な制御は行わない。
+async fn apply_coupon(
+    coupon_code: String,
+    user_account_id: i64,
+    fee_per_hour_in_yen: i32,
+    current_date_time: &DateTime<FixedOffset>,
+    op: &impl RequestConsultationOperation,
+) -> Result<Discount, ErrResp> {
+    validate_coupon_code(coupon_code.as_str()).map_err(|e| {
+        error!("invalid coupon code: {}", e);
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidCouponCodeFormat as u32,
+            }),
+        )
+    })?;
+
+    let coupon = op.find_coupon_by_code(coupon_code.clone()).await?;
+    let coupon = coupon.ok_or_else(|| {
+        error!("no coupon (code: {}) found", coupon_code);
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCouponFound as u32,
+            }),
+        )
+    })?;
+
+    if *current_date_time < coupon.valid_from || *current_date_time >= coupon.valid_until {
+        error!(
+            "coupon is out of valid period (coupon: {:?}, current_date_time: {})",
+            coupon, current_date_time
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::CouponIsOutOfValidPeriod as u32,
+            }),
+        ));
+    }
+
+    if let Some(max_num_of_uses) = coupon.max_num_of_uses {
+        let num = op.count_coupon_uses(coupon.coupon_id, None).await?;
+        if num >= max_num_of_uses as u64 {
+            error!(
+                "already reach max num of coupon uses (num: {}, coupon: {:?})",
+                num, coupon
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::ReachCouponUsageLimit as u32,
+                }),
+            ));
+        }
+    }
+
+    if let Some(max_num_of_uses_per_user) = coupon.max_num_of_uses_per_user {
+        let num = op
+            .count_coupon_uses(coupon.coupon_id, Some(user_account_id))
+            .await?;
+        if num >= max_num_of_uses_per_user as u64 {
+            error!(
+                "already reach max num of coupon uses per user (num: {}, user_account_id: {}, coupon: {:?})",
+                num, user_account_id, coupon
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::ReachCouponUsageLimitPerUser as u32,
+                }),
+            ));
+        }
+    }
+
+    let discount_in_yen = calculate_discount_in_yen(
+        fee_per_hour_in_yen,
+        coupon.discount_type.as_str(),
+        coupon.discount_value,
+    )
+    .ok_or_else(|| {
+        error!(
+            "failed to calculate discount (fee_per_hour_in_yen: {}, coupon: {:?})",
+            fee_per_hour_in_yen, coupon
+        );
+        unexpected_err_resp()
+    })?;
+    // 支払額が0円以下となる相談は、カード決済、銀行振込のいずれでも支払うことができないため受け付けない
+    if discount_in_yen >= fee_per_hour_in_yen {
+        error!(
+            "discount reaches fee (discount_in_yen: {}, fee_per_hour_in_yen: {}, coupon: {:?})",
+            discount_in_yen, fee_per_hour_in_yen, coupon
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::CouponDiscountReachesFee as u32,
+            }),
+        ));
+    }
+
+    Ok(Discount {
+        coupon_id: coupon.coupon_id,
+        discount_in_yen,
+        discount_borne_by: coupon.discount_borne_by,
+    })
+}
+
 fn validate_consultant_id_is_positive(consultant_id: i64) -> Result<(), ErrResp> {
     if !consultant_id.is_positive() {
         error!("consultant_id ({}) is not positive", consultant_id);
@@ -528,12 +752,14 @@ async fn send_mail_to_user(
     consultation_req_id: i64,
     candidates_in_string: &(String, String, String),
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
     send_mail: &impl SendMail,
 ) -> Result<(), ErrResp> {
     let text = create_text_for_user_mail(
         consultant_id,
         consultation_req_id,
         fee_per_hour_in_yen,
+        discount_in_yen,
         candidates_in_string,
     );
     send_mail
@@ -551,8 +777,10 @@ fn create_text_for_user_mail(
     consultant_id: i64,
     consultation_req_id: i64,
     fee_per_hour_in_yen: i32,
+    discount_in_yen: i32,
     candidates: &(String, String, String),
 ) -> String {
+    let fee = super::super::create_fee_description(fee_per_hour_in_yen, discount_in_yen);
     format!(
         r"下記の内容で相談申し込み（相談申し込み番号: {}）を行いました。
 
@@ -560,7 +788,7 @@ fn create_text_for_user_mail(
   コンサルタントID: {}
 
 相談料金
-  {} 円
+  {}
 
 希望相談開始日時
   第一希望: {}
@@ -577,7 +805,7 @@ fn create_text_for_user_mail(
 Email: {}",
         consultation_req_id,
         consultant_id,
-        fee_per_hour_in_yen,
+        fee,
         candidates.0,
         candidates.1,
         candidates.2,
@@ -591,6 +819,11 @@ Email: {}",
 #[cfg(test)]
 mod tests {
 
+    use common::coupon::{
+        DISCOUNT_BORNE_BY_CONSULTANT, DISCOUNT_BORNE_BY_PLATFORM, DISCOUNT_TYPE_FIXED_AMOUNT,
+        DISCOUNT_TYPE_PERCENTAGE,
+    };
+
     use super::*;
 
     #[derive(Clone, Debug)]
@@ -603,6 +836,10 @@ mod tests {
         latest_candidate_date_time_in_jst: DateTime<FixedOffset>,
         consultation_req_id: i64,
         consultant_email_address: String,
+        coupon: Option<Coupon>,
+        num_of_coupon_uses: u64,
+        num_of_coupon_uses_by_user: u64,
+        discount: Option<Discount>,
     }
 
     #[async_trait]
@@ -623,6 +860,27 @@ mod tests {
             Ok(self.fee_per_hour_in_yen)
         }
 
+        async fn find_coupon_by_code(&self, code: String) -> Result<Option<Coupon>, ErrResp> {
+            assert!(!code.is_empty());
+            Ok(self.coupon.clone())
+        }
+
+        async fn count_coupon_uses(
+            &self,
+            coupon_id: i64,
+            user_account_id: Option<i64>,
+        ) -> Result<u64, ErrResp> {
+            let coupon = self.coupon.clone().expect("failed to get coupon");
+            assert_eq!(coupon_id, coupon.coupon_id);
+            match user_account_id {
+                Some(user_account_id) => {
+                    assert_eq!(user_account_id, self.user_account_id);
+                    Ok(self.num_of_coupon_uses_by_user)
+                }
+                None => Ok(self.num_of_coupon_uses),
+            }
+        }
+
         async fn create_request_consultation(
             &self,
             user_account_id: i64,
@@ -630,6 +888,7 @@ mod tests {
             candidates: &Candidates,
             latest_candidate_date_time_in_jst: DateTime<FixedOffset>,
             fee_per_hour_in_yen: i32,
+            discount: Option<Discount>,
         ) -> Result<i64, ErrResp> {
             assert_eq!(user_account_id, self.user_account_id);
             assert_eq!(consultant_id, self.consultant_id);
@@ -639,6 +898,7 @@ mod tests {
                 self.latest_candidate_date_time_in_jst
             );
             assert_eq!(Some(fee_per_hour_in_yen), self.fee_per_hour_in_yen);
+            assert_eq!(discount, self.discount);
             Ok(self.consultation_req_id)
         }
 
@@ -696,6 +956,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -718,6 +979,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -765,6 +1030,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -787,6 +1053,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -834,6 +1104,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -856,6 +1127,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -903,6 +1178,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -925,6 +1201,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -973,6 +1253,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -995,6 +1276,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1043,6 +1328,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1065,6 +1351,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1112,6 +1402,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1134,6 +1425,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1182,6 +1477,7 @@ mod tests {
                 day: 21,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1204,6 +1500,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1252,6 +1552,7 @@ mod tests {
                 day: 22,
                 hour: 24,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1274,6 +1575,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1321,6 +1626,7 @@ mod tests {
                 day: 22,
                 hour: 6,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1343,6 +1649,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1391,6 +1701,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1413,6 +1724,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1461,6 +1776,7 @@ mod tests {
                 day: 12,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1483,6 +1799,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1531,6 +1851,7 @@ mod tests {
                 day: 11,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1553,6 +1874,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1601,6 +1926,7 @@ mod tests {
                 day: 11,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1623,6 +1949,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1670,6 +2000,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1692,6 +2023,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1739,6 +2074,7 @@ mod tests {
                 day: 22,
                 hour: 7,
             },
+            coupon_code: None,
         };
         let op = RequestConsultationOperationMock {
             consultant_id: user_account_id + 67,
@@ -1761,6 +2097,10 @@ mod tests {
                 .unwrap(),
             consultation_req_id: 3,
             consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
         };
         let send_mail = SendMailMock {};
 
@@ -1779,43 +2119,717 @@ mod tests {
         assert_eq!(Code::FeePerHourInYenWasUpdated as u32, resp.1 .0.code);
     }
 
-    #[test]
-    fn test_create_text_for_consultant_mail() {
-        let user_account_id = 1;
-        let consultation_req_id = 1;
-        let first_candidate = "2022年 11月 12日 7時00分";
-        let second_candidate = "2022年 11月 12日 23時00分";
-        let third_candidate = "2022年 11月 22日 7時00分";
+    #[tokio::test]
+    async fn test_handle_request_consultation_success_with_percentage_coupon() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+                discount_value: 10,
+                max_num_of_uses: Some(100),
+                max_num_of_uses_per_user: Some(1),
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 10, 1, 0, 0, 0)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 12, 1, 0, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+            num_of_coupon_uses: 99,
+            num_of_coupon_uses_by_user: 0,
+            discount: Some(Discount {
+                coupon_id: 1,
+                discount_in_yen: 400,
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+        };
+        let send_mail = SendMailMock {};
 
-        let result = create_text_for_consultant_mail(
+        let result = handle_request_consultation(
             user_account_id,
-            consultation_req_id,
-            &(
-                first_candidate.to_string(),
-                second_candidate.to_string(),
-                third_candidate.to_string(),
-            ),
-        );
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
 
-        let expected = format!(
-            r"ユーザーID ({}) から相談申し込み（相談申し込み番号: {}）が届きました。相談者からの希望相談開始日時を下記に記載します。{}へログインし、相談受け付けのページから該当の申し込みの詳細を確認し、了承する、または拒否するをご選択下さい。
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(RequestConsultationResult {}, resp.1 .0);
+    }
 
-希望相談開始日時
-  第一希望: {}
-  第二希望: {}
-  第三希望: {}
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_fixed_amount_coupon_exceeding_fee() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("PARTNER01".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_FIXED_AMOUNT.to_string(),
+                discount_value: 5000,
+                max_num_of_uses: None,
+                max_num_of_uses_per_user: None,
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 10, 1, 0, 0, 0)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 12, 1, 0, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_CONSULTANT.to_string(),
+            }),
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
 
-各希望相談開始日時について、その日時の{}日前となると、その日時を選択して了承することができなくなりますのでご注意下さい。
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
 
-本メールはシステムより自動配信されています。
-本メールに返信されましても、回答いたしかねます。
-お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::CouponDiscountReachesFee as u32, resp.1 .0.code);
+    }
 
-【お問い合わせ先】
-Email: {}",
-            user_account_id,
-            consultation_req_id,
-            WEB_SITE_NAME,
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_invalid_coupon_code() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("first-2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::InvalidCouponCodeFormat as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_no_coupon_found() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: None,
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCouponFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_coupon_is_not_valid_yet() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+                discount_value: 10,
+                max_num_of_uses: None,
+                max_num_of_uses_per_user: None,
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 1, 7, 0, 1)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 12, 1, 0, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::CouponIsOutOfValidPeriod as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_coupon_has_expired() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+                discount_value: 10,
+                max_num_of_uses: None,
+                max_num_of_uses_per_user: None,
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 10, 1, 0, 0, 0)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+            num_of_coupon_uses: 0,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::CouponIsOutOfValidPeriod as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_reach_coupon_usage_limit() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+                discount_value: 10,
+                max_num_of_uses: Some(100),
+                max_num_of_uses_per_user: Some(1),
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 10, 1, 0, 0, 0)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 12, 1, 0, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+            num_of_coupon_uses: 100,
+            num_of_coupon_uses_by_user: 0,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ReachCouponUsageLimit as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_consultation_fail_reach_coupon_usage_limit_per_user() {
+        let user_account_id = 12345;
+        let user_email_address = "test1@test.com".to_string();
+        let fee = 4000;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 11, 1, 7, 0, 0)
+            .unwrap();
+        let param = RequestConsultationParam {
+            consultant_id: user_account_id + 67,
+            fee_per_hour_in_yen: fee,
+            first_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 11,
+                hour: 7,
+            },
+            second_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 14,
+                hour: 23,
+            },
+            third_candidate_in_jst: ConsultationDateTime {
+                year: 2022,
+                month: 11,
+                day: 22,
+                hour: 7,
+            },
+            coupon_code: Some("FIRST2022".to_string()),
+        };
+        let op = RequestConsultationOperationMock {
+            consultant_id: user_account_id + 67,
+            consultant_available: true,
+            fee_per_hour_in_yen: Some(fee),
+            user_account_id,
+            candidates: Candidates {
+                first_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 11, 7, 0, 0)
+                    .unwrap(),
+                second_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 14, 23, 0, 0)
+                    .unwrap(),
+                third_candidate_in_jst: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                    .unwrap(),
+            },
+            latest_candidate_date_time_in_jst: JAPANESE_TIME_ZONE
+                .with_ymd_and_hms(2022, 11, 22, 7, 0, 0)
+                .unwrap(),
+            consultation_req_id: 3,
+            consultant_email_address: "test2@test.com".to_string(),
+            coupon: Some(Coupon {
+                coupon_id: 1,
+                discount_type: DISCOUNT_TYPE_PERCENTAGE.to_string(),
+                discount_value: 10,
+                max_num_of_uses: Some(100),
+                max_num_of_uses_per_user: Some(1),
+                valid_from: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 10, 1, 0, 0, 0)
+                    .unwrap(),
+                valid_until: JAPANESE_TIME_ZONE
+                    .with_ymd_and_hms(2022, 12, 1, 0, 0, 0)
+                    .unwrap(),
+                discount_borne_by: DISCOUNT_BORNE_BY_PLATFORM.to_string(),
+            }),
+            num_of_coupon_uses: 50,
+            num_of_coupon_uses_by_user: 1,
+            discount: None,
+        };
+        let send_mail = SendMailMock {};
+
+        let result = handle_request_consultation(
+            user_account_id,
+            user_email_address,
+            param,
+            &current_date_time,
+            op,
+            send_mail,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ReachCouponUsageLimitPerUser as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn test_create_text_for_consultant_mail() {
+        let user_account_id = 1;
+        let consultation_req_id = 1;
+        let first_candidate = "2022年 11月 12日 7時00分";
+        let second_candidate = "2022年 11月 12日 23時00分";
+        let third_candidate = "2022年 11月 22日 7時00分";
+
+        let result = create_text_for_consultant_mail(
+            user_account_id,
+            consultation_req_id,
+            &(
+                first_candidate.to_string(),
+                second_candidate.to_string(),
+                third_candidate.to_string(),
+            ),
+        );
+
+        let expected = format!(
+            r"ユーザーID ({}) から相談申し込み（相談申し込み番号: {}）が届きました。相談者からの希望相談開始日時を下記に記載します。{}へログインし、相談受け付けのページから該当の申し込みの詳細を確認し、了承する、または拒否するをご選択下さい。
+
+希望相談開始日時
+  第一希望: {}
+  第二希望: {}
+  第三希望: {}
+
+各希望相談開始日時について、その日時の{}日前となると、その日時を選択して了承することができなくなりますのでご注意下さい。
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+            user_account_id,
+            consultation_req_id,
+            WEB_SITE_NAME,
             first_candidate,
             second_candidate,
             third_candidate,
@@ -1839,6 +2853,7 @@ Email: {}",
             consultant_id,
             consultation_req_id,
             fee_per_hour_in_yen,
+            0,
             &(
                 first_candidate.to_string(),
                 second_candidate.to_string(),
@@ -1882,4 +2897,65 @@ Email: {}",
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_create_text_for_user_mail_with_discount() {
+        let consultant_id = 2;
+        let consultation_req_id = 13;
+        let fee_per_hour_in_yen = 5000;
+        let discount_in_yen = 500;
+        let first_candidate = "2022年 11月 12日 7時00分";
+        let second_candidate = "2022年 11月 12日 23時00分";
+        let third_candidate = "2022年 11月 22日 7時00分";
+
+        let result = create_text_for_user_mail(
+            consultant_id,
+            consultation_req_id,
+            fee_per_hour_in_yen,
+            discount_in_yen,
+            &(
+                first_candidate.to_string(),
+                second_candidate.to_string(),
+                third_candidate.to_string(),
+            ),
+        );
+
+        let expected = format!(
+            r"下記の内容で相談申し込み（相談申し込み番号: {}）を行いました。
+
+相談相手
+  コンサルタントID: {}
+
+相談料金
+  {} 円（クーポン適用前の相談料金: {} 円、割引額: {} 円）
+
+希望相談開始日時
+  第一希望: {}
+  第二希望: {}
+  第三希望: {}
+
+相談申し込みが了承されるとスケジュールに相談室が作成されます。相談申し込みが拒否されていない限り、希望相談開始日時の{}日前まではコンサルタントが相談申し込みに対して了承する可能性があります。相談申し込みが了承されたことを見逃さないため、各希望相談開始日時の{}日前には{}にログイン後、スケジュールをご確認下さい。
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+            consultation_req_id,
+            consultant_id,
+            fee_per_hour_in_yen - discount_in_yen,
+            fee_per_hour_in_yen,
+            discount_in_yen,
+            first_candidate,
+            second_candidate,
+            third_candidate,
+            *MIN_DURATION_IN_DAYS_BEFORE_CONSULTATION_ACCEPTANCE,
+            *MIN_DURATION_IN_DAYS_BEFORE_CONSULTATION_ACCEPTANCE,
+            WEB_SITE_NAME,
+            INQUIRY_EMAIL_ADDRESS.as_str()
+        );
+
+        assert_eq!(result, expected);
+    }
 }
@@ -7,18 +7,18 @@ use axum::{async_trait, Json};
 use axum_extra::extract::cookie::Cookie;
 use axum_extra::extract::SignedCookieJar;
 use chrono::{DateTime, Duration, FixedOffset};
-use common::opensearch::{delete_document, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::INDEX_NAME;
 use common::smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
 use common::{
     ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, LENGTH_OF_MEETING_IN_MINUTE,
     WEB_SITE_NAME,
 };
 use entity::sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, EntityTrait,
     ModelTrait, QueryFilter, Set, TransactionError, TransactionTrait,
 };
 use once_cell::sync::Lazy;
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -40,14 +40,17 @@ pub(crate) async fn delete_accounts(
     State(smtp_client): State<SmtpClient>,
     State(store): State<RedisSessionStore>,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
 ) -> Result<(StatusCode, SignedCookieJar), ErrResp> {
     let option_cookie = jar.get(SESSION_ID_COOKIE_NAME);
     let user_info = get_user_info_from_cookie(option_cookie.clone(), &store, &pool).await?;
 
     let account_delete_confirmed = query.0.account_delete_confirmed;
     let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
-    let op = DeleteAccountsOperationImpl { pool, index_client };
+    let op = DeleteAccountsOperationImpl {
+        pool,
+        consultant_search_client,
+    };
 
     let _ = handle_delete_accounts(
         user_info.account_id,
@@ -93,7 +96,7 @@ trait DeleteAccountsOperation {
 
 struct DeleteAccountsOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -125,7 +128,7 @@ impl DeleteAccountsOperation for DeleteAccountsOperationImpl {
         index_name: String,
         deleted_date_time: DateTime<FixedOffset>,
     ) -> Result<(), ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         self.pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
                 Box::pin(async move {
@@ -136,11 +139,15 @@ impl DeleteAccountsOperation for DeleteAccountsOperationImpl {
                     )
                     .await?;
 
+                    delete_saved_searches(txn, account_id).await?;
+
+                    delete_favorite_consultants(txn, account_id).await?;
+
                     delete_career_from_index_with_document_exclusive_lock(
                         txn,
                         account_id,
                         index_name,
-                        index_client,
+                        consultant_search_client,
                     )
                     .await?;
 
@@ -220,11 +227,95 @@ async fn insert_deleted_user_account(
     Ok(())
 }
 
+/// 保存した検索条件はアカウント削除後に保持する必要がないため、アカウント削除時に削除する
+async fn delete_saved_searches(
+    txn: &DatabaseTransaction,
+    account_id: i64,
+) -> Result<(), ErrRespStruct> {
+    let saved_search_ids = entity::saved_search::Entity::find()
+        .filter(entity::saved_search::Column::UserAccountId.eq(account_id))
+        .all(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to filter saved_search (user_account_id: {}): {}",
+                account_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?
+        .into_iter()
+        .map(|m| m.saved_search_id)
+        .collect::<Vec<i64>>();
+    if saved_search_ids.is_empty() {
+        return Ok(());
+    }
+
+    let _ = entity::saved_search_matched_consultant::Entity::delete_many()
+        .filter(
+            entity::saved_search_matched_consultant::Column::SavedSearchId
+                .is_in(saved_search_ids.clone()),
+        )
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete saved_search_matched_consultant (saved_search_ids: {:?}): {}",
+                saved_search_ids, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+
+    let _ = entity::saved_search::Entity::delete_many()
+        .filter(entity::saved_search::Column::UserAccountId.eq(account_id))
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete saved_search (user_account_id: {}): {}",
+                account_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(())
+}
+
+/// アカウント削除後は、削除したアカウントがお気に入りに登録したコンサルタントも、
+/// 他のユーザーがお気に入りに登録した削除したアカウント（コンサルタントとして）も不要なため削除する
+async fn delete_favorite_consultants(
+    txn: &DatabaseTransaction,
+    account_id: i64,
+) -> Result<(), ErrRespStruct> {
+    let _ = entity::favorite_consultant::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(entity::favorite_consultant::Column::UserAccountId.eq(account_id))
+                .add(entity::favorite_consultant::Column::ConsultantId.eq(account_id)),
+        )
+        .exec(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to delete favorite_consultant (user_account_id or consultant_id: {}): {}",
+                account_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(())
+}
+
 async fn delete_career_from_index_with_document_exclusive_lock(
     txn: &DatabaseTransaction,
     account_id: i64,
     index_name: String,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let document_option =
         find_document_model_by_user_account_id_with_exclusive_lock(txn, account_id).await?;
@@ -250,7 +341,7 @@ async fn delete_career_from_index_with_document_exclusive_lock(
         }
     })?;
 
-    delete_document(index_name.as_str(), document_id.as_str(), &index_client).await.map_err(|e|{
+    consultant_search_client.delete_document(index_name.as_str(), document_id.as_str()).await.map_err(|e|{
       error!(
         "failed to delete document (user_account_id: {}, index_name: {}, document_id: {}) from Opensearch",
         account_id, index_name, document_id
@@ -2,9 +2,9 @@
 
 pub(crate) mod career;
 pub(crate) mod fee_per_hour_in_yen;
-mod file_name_validator;
 pub(crate) mod identity;
-mod image_converter;
+pub(crate) mod image_converter;
+mod image_quality;
 mod multipart;
 
 use axum::async_trait;
@@ -4,6 +4,8 @@ mod career_validator;
 pub(crate) mod delete;
 pub(crate) mod get;
 pub(crate) mod post;
+pub(crate) mod put;
+pub(crate) mod work_email_verification;
 
 use std::collections::HashSet;
 
@@ -4,7 +4,8 @@ use async_session::serde_json::json;
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::{async_trait, Json};
-use common::opensearch::{update_document, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::INDEX_NAME;
 use common::{ApiError, ErrResp};
 use common::{ErrRespStruct, RespResult};
 use entity::career;
@@ -12,7 +13,6 @@ use entity::sea_orm::{
     ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, TransactionError,
     TransactionTrait,
 };
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
@@ -24,10 +24,10 @@ pub(crate) async fn career(
     User { user_info }: User,
     param: Query<DeleteCareerQueryParam>,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
 ) -> RespResult<DeleteCareerResult> {
     let param = param.0;
-    let op = DeleteCareerOperationImpl::new(pool, index_client);
+    let op = DeleteCareerOperationImpl::new(pool, consultant_search_client);
     handle_career_req(user_info.account_id, param.career_id, op).await
 }
 
@@ -70,12 +70,15 @@ trait DeleteCareerOperation {
 
 struct DeleteCareerOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 impl DeleteCareerOperationImpl {
-    fn new(pool: DatabaseConnection, index_client: OpenSearch) -> Self {
-        Self { pool, index_client }
+    fn new(pool: DatabaseConnection, consultant_search_client: ConsultantSearchClient) -> Self {
+        Self {
+            pool,
+            consultant_search_client,
+        }
     }
 }
 
@@ -100,7 +103,7 @@ impl DeleteCareerOperation for DeleteCareerOperationImpl {
     }
 
     async fn delete_career(&self, account_id: i64, career_id: i64) -> Result<(), ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         let _ = self
             .pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
@@ -144,7 +147,7 @@ impl DeleteCareerOperation for DeleteCareerOperationImpl {
                         document_id.as_str(),
                         career_id,
                         num_of_careers,
-                        index_client,
+                        consultant_search_client,
                     )
                     .await?;
 
@@ -172,7 +175,7 @@ async fn remove_career_from_document(
     document_id: &str,
     career_id: i64,
     num_of_careers: u64,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let source = format!("ctx._source.careers.removeIf(career -> career.career_id == params.career_id); ctx._source.num_of_careers = {}", num_of_careers);
     let script = json!({
@@ -183,7 +186,8 @@ async fn remove_career_from_document(
             }
         }
     });
-    update_document(index_name, document_id, &script, &index_client)
+    consultant_search_client
+        .update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
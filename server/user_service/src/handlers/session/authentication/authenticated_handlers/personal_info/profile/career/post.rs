@@ -7,8 +7,10 @@ use crate::handlers::session::authentication::authenticated_handlers::authentica
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::career_validator::{
     validate_career, CareerValidationError,
 };
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::file_name_validator::validate_extension_is_jpeg;
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::convert_jpeg_to_png;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::{
+    decode_uploaded_file, detect_file_format, encode_to_png, MaxFileSizeInBytes, UploadedFileFormat,
+};
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_quality::check_image_quality;
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::multipart::{
     clone_file_name_if_exists, FileNameAndBinary,
 };
@@ -42,8 +44,21 @@ use uuid::Uuid;
 
 use crate::err::{unexpected_err_resp, Code};
 
-/// 身分証の画像ファイルのバイト単位での最大値
+/// 職務経歴を証明する画像ファイル（jpeg）のバイト単位での最大値
 pub(crate) const MAX_CAREER_IMAGE_SIZE_IN_BYTES: usize = 8 * 1024 * 1024;
+/// 職務経歴を証明する画像ファイル（png）のバイト単位での最大値（可逆圧縮のため、jpegより大きな値とする）
+pub(crate) const MAX_CAREER_PNG_IMAGE_SIZE_IN_BYTES: usize = 16 * 1024 * 1024;
+/// 職務経歴を証明する画像ファイル（heic）のバイト単位での最大値
+pub(crate) const MAX_CAREER_HEIC_IMAGE_SIZE_IN_BYTES: usize = 8 * 1024 * 1024;
+/// 職務経歴を証明するPDFファイル（在籍証明書など）のバイト単位での最大値
+pub(crate) const MAX_CAREER_PDF_SIZE_IN_BYTES: usize = 10 * 1024 * 1024;
+/// 職務経歴を証明するファイルの形式ごとのバイト単位での最大値
+pub(crate) const MAX_CAREER_FILE_SIZE_IN_BYTES: MaxFileSizeInBytes = MaxFileSizeInBytes {
+    jpeg: MAX_CAREER_IMAGE_SIZE_IN_BYTES,
+    png: MAX_CAREER_PNG_IMAGE_SIZE_IN_BYTES,
+    heic: MAX_CAREER_HEIC_IMAGE_SIZE_IN_BYTES,
+    pdf: MAX_CAREER_PDF_SIZE_IN_BYTES,
+};
 
 pub(crate) async fn career(
     VerifiedUser { user_info }: VerifiedUser,
@@ -54,7 +69,7 @@ pub(crate) async fn career(
 ) -> RespResult<CareerResult> {
     let multipart_wrapper = MultipartWrapperImpl { multipart };
     let (career, career_image1, career_image2_option) =
-        handle_multipart(multipart_wrapper, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await?;
+        handle_multipart(multipart_wrapper, MAX_CAREER_FILE_SIZE_IN_BYTES).await?;
 
     let op = SubmitCareerOperationImpl::new(pool, storage_client);
     let image1_file_name_without_ext = Uuid::new_v4().simple().to_string();
@@ -71,15 +86,18 @@ pub(crate) async fn career(
 }
 
 #[derive(Serialize, Debug, PartialEq)]
-pub(crate) struct CareerResult {}
+pub(crate) struct CareerResult {
+    /// 勤務先のメールアドレスによる確認（任意）で、対象の職務経歴確認依頼を指定するために利用する
+    pub(crate) create_career_req_id: i64,
+}
 
 #[async_trait]
-trait MultipartWrapper {
+pub(super) trait MultipartWrapper {
     async fn next_field(&mut self) -> Result<Option<CareerField>, ErrResp>;
 }
 
-struct MultipartWrapperImpl {
-    multipart: Multipart,
+pub(super) struct MultipartWrapperImpl {
+    pub(super) multipart: Multipart,
 }
 
 #[async_trait]
@@ -110,16 +128,37 @@ impl MultipartWrapper for MultipartWrapperImpl {
     }
 }
 
-struct CareerField {
-    name: Option<String>,
-    file_name: Option<String>,
-    data: Result<Bytes, Box<dyn Error>>,
+pub(super) struct CareerField {
+    pub(super) name: Option<String>,
+    pub(super) file_name: Option<String>,
+    pub(super) data: Result<Bytes, Box<dyn Error + Send + Sync>>,
 }
 
 async fn handle_multipart(
-    mut multipart: impl MultipartWrapper,
-    max_image_size_in_bytes: usize,
+    multipart: impl MultipartWrapper,
+    max_file_size_in_bytes: MaxFileSizeInBytes,
 ) -> Result<(Career, Cursor<Vec<u8>>, Option<Cursor<Vec<u8>>>), ErrResp> {
+    let (career_option, career_image1_option, career_image2_option) =
+        extract_career_and_career_images(multipart, max_file_size_in_bytes).await?;
+    let (career, career_image1) =
+        ensure_mandatory_params_exist(career_option, career_image1_option)?;
+    Ok((career, career_image1, career_image2_option))
+}
+
+/// マルチパートから職務経歴と職務経歴を証明する画像（png形式に変換したもの）を取り出す
+///
+/// 必須のパラメータが存在するかどうかは、呼び出し側で確認する。
+pub(super) async fn extract_career_and_career_images(
+    mut multipart: impl MultipartWrapper,
+    max_file_size_in_bytes: MaxFileSizeInBytes,
+) -> Result<
+    (
+        Option<Career>,
+        Option<Cursor<Vec<u8>>>,
+        Option<Cursor<Vec<u8>>>,
+    ),
+    ErrResp,
+> {
     let mut career_option = None;
     let mut career_image1_option = None;
     let mut career_image2_option = None;
@@ -152,13 +191,15 @@ async fn handle_multipart(
             career_option = Some(trim_space_from_career(career));
         } else if name == "career-image1" {
             validate_career_image_file_name(file_name_option)?;
-            validate_career_image_size(data.len(), max_image_size_in_bytes)?;
-            let png_binary = convert_jpeg_to_png(data)?;
+            let format = detect_file_format(&data)?;
+            validate_career_image_size(data.len(), max_file_size_in_bytes.of(format))?;
+            let png_binary = convert_to_png(format, data).await?;
             career_image1_option = Some(png_binary);
         } else if name == "career-image2" {
             validate_career_image_file_name(file_name_option)?;
-            validate_career_image_size(data.len(), max_image_size_in_bytes)?;
-            let png_binary = convert_jpeg_to_png(data)?;
+            let format = detect_file_format(&data)?;
+            validate_career_image_size(data.len(), max_file_size_in_bytes.of(format))?;
+            let png_binary = convert_to_png(format, data).await?;
             career_image2_option = Some(png_binary);
         } else {
             error!("invalid name in field: {}", name);
@@ -170,9 +211,7 @@ async fn handle_multipart(
             ));
         }
     }
-    let (career, career_image1) =
-        ensure_mandatory_params_exist(career_option, career_image1_option)?;
-    Ok((career, career_image1, career_image2_option))
+    Ok((career_option, career_image1_option, career_image2_option))
 }
 
 fn extract_career(data: Bytes) -> Result<Career, ErrResp> {
@@ -197,31 +236,32 @@ fn extract_career(data: Bytes) -> Result<Career, ErrResp> {
     Ok(career)
 }
 
+/// ファイルとして送られてきたかどうかを確認する
+///
+/// ファイルの形式はファイル名（拡張子）ではなく、ファイルの内容から判定する（[detect_file_format]）。
 fn validate_career_image_file_name(file_name_option: Option<String>) -> Result<(), ErrResp> {
-    let file_name = match file_name_option {
-        Some(name) => name,
-        None => {
-            error!("failed to get file name in field");
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiError {
-                    code: Code::NoFileNameFound as u32,
-                }),
-            ));
-        }
-    };
-    validate_extension_is_jpeg(&file_name).map_err(|e| {
-        error!("invalid file name ({}): {}", file_name, e);
-        (
+    if file_name_option.is_none() {
+        error!("failed to get file name in field");
+        return Err((
             StatusCode::BAD_REQUEST,
             Json(ApiError {
-                code: Code::NotJpegExtension as u32,
+                code: Code::NoFileNameFound as u32,
             }),
-        )
-    })?;
+        ));
+    }
     Ok(())
 }
 
+/// アップロードされたファイルの品質を検査し、画像情報以外のメタデータを取り除いたpng画像に変換する
+async fn convert_to_png(
+    format: UploadedFileFormat,
+    data: Bytes,
+) -> Result<Cursor<Vec<u8>>, ErrResp> {
+    let img = decode_uploaded_file(format, data).await?;
+    let _ = check_image_quality(&img)?;
+    encode_to_png(&img)
+}
+
 fn validate_career_image_size(size: usize, max_size_in_bytes: usize) -> Result<(), ErrResp> {
     if size > max_size_in_bytes {
         error!(
@@ -389,7 +429,8 @@ async fn handle_career_req(
         ));
     }
 
-    op.request_create_career(submitted_career, current_date_time)
+    let create_career_req_id = op
+        .request_create_career(submitted_career, current_date_time)
         .await?;
 
     let subject = create_subject(account_id);
@@ -402,7 +443,12 @@ async fn handle_career_req(
             &text,
         )
         .await?;
-    Ok((StatusCode::OK, Json(CareerResult {})))
+    Ok((
+        StatusCode::OK,
+        Json(CareerResult {
+            create_career_req_id,
+        }),
+    ))
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -435,7 +481,7 @@ trait SubmitCareerOperation {
         &self,
         submitted_career: SubmittedCareer,
         current_date_time: DateTime<FixedOffset>,
-    ) -> Result<(), ErrResp>;
+    ) -> Result<i64, ErrResp>;
 }
 
 struct SubmitCareerOperationImpl {
@@ -488,7 +534,7 @@ impl SubmitCareerOperation for SubmitCareerOperationImpl {
         &self,
         submitted_career: SubmittedCareer,
         current_date_time: DateTime<FixedOffset>,
-    ) -> Result<(), ErrResp> {
+    ) -> Result<i64, ErrResp> {
         let account_id = submitted_career.account_id;
         let career = submitted_career.career;
         let career_image1 = submitted_career.career_image1;
@@ -496,9 +542,9 @@ impl SubmitCareerOperation for SubmitCareerOperationImpl {
         let (career_image2_option, image2_file_name_without_ext) =
             clone_file_name_if_exists(submitted_career.career_image2);
         let storage_client = self.storage_client.clone();
-        let _ = self
+        let create_career_req_id = self
             .pool
-            .transaction::<_, (), ErrRespStruct>(|txn| {
+            .transaction::<_, i64, ErrRespStruct>(|txn| {
                 Box::pin(async move {
                     let active_model =
                         SubmitCareerOperationImpl::generate_create_career_req_active_model(
@@ -508,7 +554,7 @@ impl SubmitCareerOperation for SubmitCareerOperationImpl {
                             image2_file_name_without_ext,
                             current_date_time,
                         )?;
-                    let _ = active_model.insert(txn).await.map_err(|e| {
+                    let model = active_model.insert(txn).await.map_err(|e| {
                         error!(
                             "failed to insert create_career_req (user_account_id: {}): {}",
                             account_id, e
@@ -517,14 +563,14 @@ impl SubmitCareerOperation for SubmitCareerOperationImpl {
                             err_resp: unexpected_err_resp(),
                         }
                     })?;
-                    let _ = SubmitCareerOperationImpl::upload_png_images_to_career_storage(
+                    let _ = upload_png_images_to_career_storage(
                         storage_client,
                         account_id,
                         career_image1,
                         career_image2_option,
                     )
                     .await?;
-                    Ok(())
+                    Ok(model.create_career_req_id)
                 })
             })
             .await
@@ -538,7 +584,7 @@ impl SubmitCareerOperation for SubmitCareerOperationImpl {
                     err_resp_struct.err_resp
                 }
             })?;
-        Ok(())
+        Ok(create_career_req_id)
     }
 }
 
@@ -550,39 +596,7 @@ impl SubmitCareerOperationImpl {
         image2_file_name_without_ext: Option<String>,
         current_date_time: DateTime<FixedOffset>,
     ) -> Result<create_career_req::ActiveModel, ErrRespStruct> {
-        let start_date = NaiveDate::from_ymd_opt(
-            career.career_start_date.year,
-            career.career_start_date.month,
-            career.career_start_date.day,
-        )
-        .ok_or_else(|| {
-            error!(
-                "failed to generate start_date (year: {}, month: {}, day: {})",
-                career.career_start_date.year,
-                career.career_start_date.month,
-                career.career_start_date.day
-            );
-            ErrRespStruct {
-                err_resp: unexpected_err_resp(),
-            }
-        })?;
-        let end_date_option = career.career_end_date;
-        let end_date = match end_date_option {
-            Some(ymd) => {
-                if let Some(result) = NaiveDate::from_ymd_opt(ymd.year, ymd.month, ymd.day) {
-                    Some(result)
-                } else {
-                    error!(
-                        "failed to generate end_date (year: {}, month: {}, day: {})",
-                        ymd.year, ymd.month, ymd.day
-                    );
-                    return Err(ErrRespStruct {
-                        err_resp: unexpected_err_resp(),
-                    });
-                }
-            }
-            None => None,
-        };
+        let (start_date, end_date) = convert_career_dates(&career)?;
         Ok(create_career_req::ActiveModel {
             create_career_req_id: NotSet,
             user_account_id: Set(account_id),
@@ -601,47 +615,90 @@ impl SubmitCareerOperationImpl {
             image1_file_name_without_ext: Set(image1_file_name_without_ext),
             image2_file_name_without_ext: Set(image2_file_name_without_ext),
             requested_at: Set(current_date_time),
+            claimed_by: NotSet,
+            claimed_at: NotSet,
+            verified_work_email_domain: NotSet,
         })
     }
+}
 
-    async fn upload_png_images_to_career_storage(
-        storage_client: StorageClient,
-        account_id: i64,
-        career_image1: FileNameAndBinary,
-        career_image2_option: Option<FileNameAndBinary>,
-    ) -> Result<(), ErrRespStruct> {
-        let image1_key = format!("{}/{}.png", account_id, career_image1.0);
-        let image1_obj = career_image1.1.into_inner();
+/// 職務経歴の入社日と退社日を、DBに保存する日付の型に変換する
+pub(super) fn convert_career_dates(
+    career: &Career,
+) -> Result<(NaiveDate, Option<NaiveDate>), ErrRespStruct> {
+    let start_date = NaiveDate::from_ymd_opt(
+        career.career_start_date.year,
+        career.career_start_date.month,
+        career.career_start_date.day,
+    )
+    .ok_or_else(|| {
+        error!(
+            "failed to generate start_date (year: {}, month: {}, day: {})",
+            career.career_start_date.year,
+            career.career_start_date.month,
+            career.career_start_date.day
+        );
+        ErrRespStruct {
+            err_resp: unexpected_err_resp(),
+        }
+    })?;
+    let end_date_option = &career.career_end_date;
+    let end_date = match end_date_option {
+        Some(ymd) => {
+            if let Some(result) = NaiveDate::from_ymd_opt(ymd.year, ymd.month, ymd.day) {
+                Some(result)
+            } else {
+                error!(
+                    "failed to generate end_date (year: {}, month: {}, day: {})",
+                    ymd.year, ymd.month, ymd.day
+                );
+                return Err(ErrRespStruct {
+                    err_resp: unexpected_err_resp(),
+                });
+            }
+        }
+        None => None,
+    };
+    Ok((start_date, end_date))
+}
+
+pub(super) async fn upload_png_images_to_career_storage(
+    storage_client: StorageClient,
+    account_id: i64,
+    career_image1: FileNameAndBinary,
+    career_image2_option: Option<FileNameAndBinary>,
+) -> Result<(), ErrRespStruct> {
+    let image1_key = format!("{}/{}.png", account_id, career_image1.0);
+    let image1_obj = career_image1.1.into_inner();
+    storage_client
+        .upload_object(CAREER_IMAGES_BUCKET_NAME.as_str(), &image1_key, image1_obj)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to upload object (image1 key: {}): {}",
+                image1_key, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    if let Some(career_image2) = career_image2_option {
+        let image2_key = format!("{}/{}.png", account_id, career_image2.0);
+        let image2_obj = career_image2.1.into_inner();
         storage_client
-            .upload_object(CAREER_IMAGES_BUCKET_NAME.as_str(), &image1_key, image1_obj)
+            .upload_object(CAREER_IMAGES_BUCKET_NAME.as_str(), &image2_key, image2_obj)
             .await
             .map_err(|e| {
                 error!(
-                    "failed to upload object (image1 key: {}): {}",
-                    image1_key, e
+                    "failed to upload object (image2 key: {}): {}",
+                    image2_key, e
                 );
                 ErrRespStruct {
                     err_resp: unexpected_err_resp(),
                 }
             })?;
-        if let Some(career_image2) = career_image2_option {
-            let image2_key = format!("{}/{}.png", account_id, career_image2.0);
-            let image2_obj = career_image2.1.into_inner();
-            storage_client
-                .upload_object(CAREER_IMAGES_BUCKET_NAME.as_str(), &image2_key, image2_obj)
-                .await
-                .map_err(|e| {
-                    error!(
-                        "failed to upload object (image2 key: {}): {}",
-                        image2_key, e
-                    );
-                    ErrRespStruct {
-                        err_resp: unexpected_err_resp(),
-                    }
-                })?;
-        }
-        Ok(())
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -741,15 +798,23 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(career, input.0);
-        let career_image1_png =
-            convert_jpeg_to_png(Bytes::from(career_image1.into_inner())).expect("failed to get Ok");
+        let career_image1_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
         assert_eq!(career_image1_png.into_inner(), input.1.into_inner());
-        let career_image2_png =
-            convert_jpeg_to_png(Bytes::from(career_image2.into_inner())).expect("failed to get Ok");
+        let career_image2_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
         assert_eq!(
             career_image2_png.into_inner(),
             input.2.expect("failed to get Ok").into_inner()
@@ -788,7 +853,7 @@ mod tests {
     }
 
     fn create_dummy_career_image1() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(128, 128);
+        let img = create_dummy_textured_image(640, 480);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
         img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
             .expect("failed to get Ok");
@@ -796,13 +861,41 @@ mod tests {
     }
 
     fn create_dummy_career_image2() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(64, 64);
+        let img = create_dummy_textured_image(480, 640);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
         img.write_to(&mut bytes, ImageOutputFormat::Jpeg(90))
             .expect("failed to get Ok");
         bytes
     }
 
+    // 画像の品質の検査（解像度、ほぼ単色かどうか、ぼやけ具合）を通過する画像
+    fn create_dummy_textured_image(width: u32, height: u32) -> RgbImage {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let gradient = x * 150 / width;
+            let checker = if ((x / 16) + (y / 16)) % 2 == 0 {
+                100
+            } else {
+                0
+            };
+            let luma = (gradient + checker) as u8;
+            image::Rgb([luma, luma, luma])
+        })
+    }
+
+    fn create_dummy_jpeg_image(img: &RgbImage) -> Cursor<Vec<u8>> {
+        let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
+        img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
+            .expect("failed to get Ok");
+        bytes
+    }
+
+    fn create_max_file_size_in_bytes_for_jpeg(jpeg: usize) -> MaxFileSizeInBytes {
+        MaxFileSizeInBytes {
+            jpeg,
+            ..MAX_CAREER_FILE_SIZE_IN_BYTES
+        }
+    }
+
     fn create_dummy_career_image_field(
         name: Option<String>,
         file_name: Option<String>,
@@ -833,12 +926,16 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(career, input.0);
-        let career_image1_png =
-            convert_jpeg_to_png(Bytes::from(career_image1.into_inner())).expect("failed to get Ok");
+        let career_image1_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
         assert_eq!(career_image1_png.into_inner(), input.1.into_inner());
         assert_eq!(None, input.2);
     }
@@ -870,15 +967,27 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, max_image_size_in_bytes).await;
+        let result = handle_multipart(
+            mock,
+            create_max_file_size_in_bytes_for_jpeg(max_image_size_in_bytes),
+        )
+        .await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(career, input.0);
-        let career_image1_png =
-            convert_jpeg_to_png(Bytes::from(career_image1.into_inner())).expect("failed to get Ok");
+        let career_image1_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
         assert_eq!(career_image1_png.into_inner(), input.1.into_inner());
-        let career_image2_png =
-            convert_jpeg_to_png(Bytes::from(career_image2.into_inner())).expect("failed to get Ok");
+        let career_image2_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
         assert_eq!(
             career_image2_png.into_inner(),
             input.2.expect("failed to get Ok").into_inner()
@@ -908,7 +1017,7 @@ mod tests {
             invalid_multipart_form_data: true,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -938,7 +1047,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -949,7 +1058,7 @@ mod tests {
     async fn handle_multipart_fail_data_parse() {
         let mock = MultipartWrapperErrMock {};
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -979,7 +1088,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1007,7 +1116,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1031,7 +1140,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1061,7 +1170,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1069,7 +1178,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_multipart_fail_not_jpeg_extension() {
+    async fn handle_multipart_success_file_format_is_detected_regardless_of_file_name() {
         let career = create_dummy_career();
         let career_field = create_dummy_career_field(Some(String::from("career")), &career);
         let career_image1 = create_dummy_career_image1();
@@ -1091,11 +1200,78 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let input = result.expect("failed to get Ok");
+        let career_image2_png = convert_to_png(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(career_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(Some(career_image2_png), input.2);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_success_with_png_images() {
+        let career = create_dummy_career();
+        let career_field = create_dummy_career_field(Some(String::from("career")), &career);
+        let career_image1 = create_dummy_career_image1_png();
+        let career_image1_field = create_dummy_career_image_field(
+            Some(String::from("career-image1")),
+            Some(String::from("test1.png")),
+            career_image1.clone(),
+        );
+        let fields = vec![career_field, career_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let input = result.expect("failed to get Ok");
+        assert_eq!(career, input.0);
+        let career_image1_png = convert_to_png(
+            UploadedFileFormat::Png,
+            Bytes::from(career_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(career_image1_png, input.1);
+        assert_eq!(None, input.2);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_unsupported_file_format() {
+        let career = create_dummy_career();
+        let career_field = create_dummy_career_field(Some(String::from("career")), &career);
+        let career_image1 = create_dummy_career_image1();
+        let career_image1_field = create_dummy_career_image_field(
+            Some(String::from("career-image1")),
+            Some(String::from("test1.jpeg")),
+            career_image1.clone(),
+        );
+        let career_image2 = create_dummy_career_image2_bmp();
+        let career_image2_field = create_dummy_career_image_field(
+            Some(String::from("career-image2")),
+            /* 実体はbmp画像だが、ファイル名はjpegに設定 */
+            Some(String::from("test2.jpg")),
+            career_image2.clone(),
+        );
+        let fields = vec![career_field, career_image1_field, career_image2_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
-        assert_eq!(Code::NotJpegExtension as u32, resp.1.code);
+        assert_eq!(Code::UnsupportedFileFormat as u32, resp.1.code);
     }
 
     #[tokio::test]
@@ -1121,7 +1297,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1173,7 +1349,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1195,28 +1371,22 @@ mod tests {
     async fn handle_multipart_fail_invalid_jpeg_image() {
         let career = create_dummy_career();
         let career_field = create_dummy_career_field(Some(String::from("career")), &career);
-        let career_image1 = create_dummy_career_image1_png();
+        let mut career_image1 = create_dummy_career_image1().into_inner();
+        /* 先頭（マジックナンバー）はjpegのまま、途中以降を切り捨てて壊れた画像にする */
+        career_image1.truncate(32);
         let career_image1_field = create_dummy_career_image_field(
             Some(String::from("career-image1")),
-            /* 実体はpng画像だが、ファイル名で弾かれないようにjpegに設定 */
             Some(String::from("test1.jpeg")),
-            career_image1.clone(),
-        );
-        let career_image2 = create_dummy_career_image2_bmp();
-        let career_image2_field = create_dummy_career_image_field(
-            Some(String::from("career-image2")),
-            /* 実体はbmp画像だが、ファイル名で弾かれないようにjpegに設定 */
-            Some(String::from("test2.jpg")),
-            career_image2.clone(),
+            Cursor::new(career_image1),
         );
-        let fields = vec![career_field, career_image1_field, career_image2_field];
+        let fields = vec![career_field, career_image1_field];
         let mock = MultipartWrapperMock {
             count: 0,
             fields,
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1224,7 +1394,7 @@ mod tests {
     }
 
     fn create_dummy_career_image1_png() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(128, 128);
+        let img = create_dummy_textured_image(640, 480);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
         img.write_to(&mut bytes, ImageOutputFormat::Png)
             .expect("failed to get Ok");
@@ -1267,13 +1437,71 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, max_image_size_in_bytes).await;
+        let result = handle_multipart(
+            mock,
+            create_max_file_size_in_bytes_for_jpeg(max_image_size_in_bytes),
+        )
+        .await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
         assert_eq!(Code::ExceedMaxCareerImageSizeLimit as u32, resp.1.code);
     }
 
+    #[tokio::test]
+    async fn handle_multipart_fail_image_resolution_too_low() {
+        let career = create_dummy_career();
+        let career_field = create_dummy_career_field(Some(String::from("career")), &career);
+        let career_image1 = create_dummy_jpeg_image(&create_dummy_textured_image(320, 240));
+        let career_image1_field = create_dummy_career_image_field(
+            Some(String::from("career-image1")),
+            Some(String::from("test1.jpeg")),
+            career_image1,
+        );
+        let fields = vec![career_field, career_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ImageResolutionTooLow as u32, resp.1.code);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_image_is_blank() {
+        let career = create_dummy_career();
+        let career_field = create_dummy_career_field(Some(String::from("career")), &career);
+        let career_image1 = create_dummy_career_image1();
+        let career_image1_field = create_dummy_career_image_field(
+            Some(String::from("career-image1")),
+            Some(String::from("test1.jpeg")),
+            career_image1,
+        );
+        let career_image2 = create_dummy_jpeg_image(&ImageBuffer::new(640, 480));
+        let career_image2_field = create_dummy_career_image_field(
+            Some(String::from("career-image2")),
+            Some(String::from("test2.jpeg")),
+            career_image2,
+        );
+        let fields = vec![career_field, career_image1_field, career_image2_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::ImageIsBlank as u32, resp.1.code);
+    }
+
     #[tokio::test]
     async fn handle_multipart_fail_fail_invalid_company_name_length() {
         let career = Career {
@@ -1314,7 +1542,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1361,7 +1589,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1408,7 +1636,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1455,7 +1683,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1502,7 +1730,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1549,7 +1777,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1596,7 +1824,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1647,7 +1875,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1698,7 +1926,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1748,7 +1976,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1795,7 +2023,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1842,7 +2070,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1889,7 +2117,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1936,7 +2164,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1983,7 +2211,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -2030,7 +2258,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -2077,7 +2305,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_CAREER_IMAGE_SIZE_IN_BYTES).await;
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -2090,6 +2318,7 @@ mod tests {
         current_date_time: DateTime<FixedOffset>,
         num_of_career: u64,
         num_of_create_career_req: u64,
+        create_career_req_id: i64,
     }
 
     #[async_trait]
@@ -2108,10 +2337,10 @@ mod tests {
             &self,
             submitted_career: SubmittedCareer,
             current_date_time: DateTime<FixedOffset>,
-        ) -> Result<(), ErrResp> {
+        ) -> Result<i64, ErrResp> {
             assert_eq!(self.submitted_career, submitted_career);
             assert_eq!(self.current_date_time, current_date_time);
-            Ok(())
+            Ok(self.create_career_req_id)
         }
     }
 
@@ -2126,12 +2355,14 @@ mod tests {
             career_image2: None,
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
             num_of_create_career_req: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
@@ -2144,7 +2375,12 @@ mod tests {
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
-        assert_eq!(CareerResult {}, resp.1 .0);
+        assert_eq!(
+            CareerResult {
+                create_career_req_id
+            },
+            resp.1 .0
+        );
     }
 
     #[tokio::test]
@@ -2159,12 +2395,14 @@ mod tests {
             career_image2: Some((image2_file_name_without_ext, create_dummy_career_image2())),
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: 0,
             num_of_create_career_req: 0,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
@@ -2177,7 +2415,12 @@ mod tests {
 
         let resp = result.expect("failed to get Ok");
         assert_eq!(StatusCode::OK, resp.0);
-        assert_eq!(CareerResult {}, resp.1 .0);
+        assert_eq!(
+            CareerResult {
+                create_career_req_id
+            },
+            resp.1 .0
+        );
     }
 
     #[tokio::test]
@@ -2191,12 +2434,14 @@ mod tests {
             career_image2: None,
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT,
             num_of_create_career_req: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
@@ -2225,12 +2470,14 @@ mod tests {
             career_image2: None,
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT + 1,
             num_of_create_career_req: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
@@ -2257,12 +2504,14 @@ mod tests {
             career_image2: None,
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
             num_of_create_career_req: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
@@ -2291,12 +2540,14 @@ mod tests {
             career_image2: None,
         };
         let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let create_career_req_id = 4301;
         let op = SubmitCareerOperationMock {
             account_id,
             submitted_career: submitted_career.clone(),
             current_date_time,
             num_of_career: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT - 1,
             num_of_create_career_req: MAX_NUM_OF_CAREER_PER_USER_ACCOUNT + 1,
+            create_career_req_id,
         };
         let smtp_client = SendMailMock::new(
             ADMIN_EMAIL_ADDRESS.to_string(),
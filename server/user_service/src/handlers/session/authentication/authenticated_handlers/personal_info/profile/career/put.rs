@@ -0,0 +1,820 @@
+// Copyright 2023 Ken Miura
+
+use std::io::Cursor;
+
+use axum::async_trait;
+use axum::extract::{Multipart, Query, State};
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use common::smtp::{SendMail, SmtpClient, ADMIN_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
+use common::storage::StorageClient;
+use common::util::{Career, Ymd};
+use common::{ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME};
+use entity::sea_orm::ActiveValue::NotSet;
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionError, TransactionTrait,
+};
+use entity::{career, update_career_req};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::MaxFileSizeInBytes;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::multipart::FileNameAndBinary;
+
+use super::post::{
+    convert_career_dates, extract_career_and_career_images, upload_png_images_to_career_storage,
+    MultipartWrapper, MultipartWrapperImpl, MAX_CAREER_FILE_SIZE_IN_BYTES,
+};
+
+pub(crate) async fn career(
+    VerifiedUser { user_info }: VerifiedUser,
+    param: Query<UpdateCareerQueryParam>,
+    State(smtp_client): State<SmtpClient>,
+    State(storage_client): State<StorageClient>,
+    State(pool): State<DatabaseConnection>,
+    multipart: Multipart,
+) -> RespResult<UpdateCareerResult> {
+    let param = param.0;
+    let multipart_wrapper = MultipartWrapperImpl { multipart };
+    let (career, career_image1_option, career_image2_option) =
+        handle_multipart(multipart_wrapper, MAX_CAREER_FILE_SIZE_IN_BYTES).await?;
+
+    let op = UpdateCareerOperationImpl {
+        pool,
+        storage_client,
+    };
+    let submitted_career_update = SubmittedCareerUpdate {
+        account_id: user_info.account_id,
+        career_id: param.career_id,
+        career,
+        career_image1: career_image1_option
+            .map(|image| (Uuid::new_v4().simple().to_string(), image)),
+        career_image2: career_image2_option
+            .map(|image| (Uuid::new_v4().simple().to_string(), image)),
+    };
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    handle_update_career_req(submitted_career_update, current_date_time, op, smtp_client).await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateCareerQueryParam {
+    pub(crate) career_id: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct UpdateCareerResult {}
+
+/// 更新後の職務経歴と、（提出された場合）職務経歴を証明する画像を返す
+///
+/// 職務経歴を証明する画像が必要かどうかは更新内容によって変わるため、ここでは画像の有無は確認しない（[check_update_career_requirement]で確認する）。
+async fn handle_multipart(
+    multipart: impl MultipartWrapper,
+    max_file_size_in_bytes: MaxFileSizeInBytes,
+) -> Result<(Career, Option<Cursor<Vec<u8>>>, Option<Cursor<Vec<u8>>>), ErrResp> {
+    let (career_option, career_image1_option, career_image2_option) =
+        extract_career_and_career_images(multipart, max_file_size_in_bytes).await?;
+    let career = career_option.ok_or_else(|| {
+        error!("no career found");
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCareerFound as u32,
+            }),
+        )
+    })?;
+    if career_image1_option.is_none() && career_image2_option.is_some() {
+        error!("career-image2 found without career-image1");
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCareerImage1Found as u32,
+            }),
+        ));
+    }
+    Ok((career, career_image1_option, career_image2_option))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SubmittedCareerUpdate {
+    account_id: i64,
+    career_id: i64,
+    career: Career,
+    career_image1: Option<FileNameAndBinary>,
+    career_image2: Option<FileNameAndBinary>,
+}
+
+async fn handle_update_career_req(
+    submitted_career_update: SubmittedCareerUpdate,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl UpdateCareerOperation,
+    send_mail: impl SendMail,
+) -> RespResult<UpdateCareerResult> {
+    let account_id = submitted_career_update.account_id;
+    let career_id = submitted_career_update.career_id;
+
+    // 任意の職務経歴の更新を防ぐため、必ずログインユーザーのアカウントIDに紐付いた職務経歴かチェック
+    let career = op
+        .find_career_by_career_id_and_account_id(career_id, account_id)
+        .await?
+        .ok_or_else(|| {
+            error!(
+                "No career associated with user account found (account_id: {}, career_id: {})",
+                account_id, career_id
+            );
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::NoCareerToHandleFound as u32,
+                }),
+            )
+        })?;
+    check_update_career_requirement(
+        &career,
+        &submitted_career_update.career,
+        submitted_career_update.career_image1.is_some(),
+    )?;
+
+    let update_req_exists = op
+        .check_if_update_career_req_already_exists(career_id)
+        .await?;
+    if update_req_exists {
+        error!(
+            "update career request (career_id: {}) already exists",
+            career_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::UpdateCareerReqAlreadyExists as u32,
+            }),
+        ));
+    }
+
+    info!(
+        "request to update career (account_id: {}, career_id: {})",
+        account_id, career_id
+    );
+    op.request_update_career(submitted_career_update, current_date_time)
+        .await?;
+
+    let subject = create_subject(account_id);
+    let text = create_text(account_id);
+    send_mail
+        .send_mail(
+            ADMIN_EMAIL_ADDRESS.as_str(),
+            SYSTEM_EMAIL_ADDRESS.as_str(),
+            &subject,
+            &text,
+        )
+        .await?;
+    Ok((StatusCode::OK, Json(UpdateCareerResult {})))
+}
+
+fn check_update_career_requirement(
+    career: &Career,
+    career_to_update: &Career,
+    career_image_submitted: bool,
+) -> Result<(), ErrResp> {
+    if career == career_to_update {
+        error!("career ({:?}) is exactly same as submitted one", career);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCareerUpdated as u32,
+            }),
+        ));
+    }
+    if requires_career_image(career, career_to_update) && !career_image_submitted {
+        error!(
+            "career image is required to update career ({:?}) to submitted one ({:?})",
+            career, career_to_update
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::NoCareerImage1Found as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// 職務経歴の更新に、職務経歴を証明する画像の提出が必要かどうかを返す
+///
+/// 退社日と備考は、ユーザーの申告を管理者が確認するのみで証明する画像を必要としない。
+/// そのため、退社日と備考以外の項目が変更されている場合のみ画像の提出を必要とする。
+fn requires_career_image(career: &Career, career_to_update: &Career) -> bool {
+    let exclude_fields_not_requiring_image = |c: &Career| Career {
+        career_end_date: None,
+        note: None,
+        ..c.clone()
+    };
+    exclude_fields_not_requiring_image(career)
+        != exclude_fields_not_requiring_image(career_to_update)
+}
+
+fn create_subject(id: i64) -> String {
+    format!(
+        "[{}] ユーザー (id: {}) からの職務経歴確認依頼 (更新)",
+        WEB_SITE_NAME, id
+    )
+}
+
+fn create_text(id: i64) -> String {
+    format!(
+        "ユーザー (id: {}) からの職務経歴確認依頼 (更新) が届きました。管理者サイトから対応をお願いいたします。",
+        id
+    )
+}
+
+#[async_trait]
+trait UpdateCareerOperation {
+    async fn find_career_by_career_id_and_account_id(
+        &self,
+        career_id: i64,
+        account_id: i64,
+    ) -> Result<Option<Career>, ErrResp>;
+
+    async fn check_if_update_career_req_already_exists(
+        &self,
+        career_id: i64,
+    ) -> Result<bool, ErrResp>;
+
+    async fn request_update_career(
+        &self,
+        submitted_career_update: SubmittedCareerUpdate,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp>;
+}
+
+struct UpdateCareerOperationImpl {
+    pool: DatabaseConnection,
+    storage_client: StorageClient,
+}
+
+#[async_trait]
+impl UpdateCareerOperation for UpdateCareerOperationImpl {
+    async fn find_career_by_career_id_and_account_id(
+        &self,
+        career_id: i64,
+        account_id: i64,
+    ) -> Result<Option<Career>, ErrResp> {
+        let model = career::Entity::find_by_id(career_id)
+            .filter(career::Column::UserAccountId.eq(account_id))
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find career (career_id: {}, user_account_id: {}): {}",
+                    career_id, account_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.map(|m| Career {
+            company_name: m.company_name,
+            department_name: m.department_name,
+            office: m.office,
+            career_start_date: Ymd {
+                year: m.career_start_date.year(),
+                month: m.career_start_date.month(),
+                day: m.career_start_date.day(),
+            },
+            career_end_date: m.career_end_date.map(|date| Ymd {
+                year: date.year(),
+                month: date.month(),
+                day: date.day(),
+            }),
+            contract_type: m.contract_type,
+            profession: m.profession,
+            annual_income_in_man_yen: m.annual_income_in_man_yen,
+            is_manager: m.is_manager,
+            position_name: m.position_name,
+            is_new_graduate: m.is_new_graduate,
+            note: m.note,
+        }))
+    }
+
+    async fn check_if_update_career_req_already_exists(
+        &self,
+        career_id: i64,
+    ) -> Result<bool, ErrResp> {
+        let model = update_career_req::Entity::find_by_id(career_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find update_career_req (career_id: {}): {}",
+                    career_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model.is_some())
+    }
+
+    async fn request_update_career(
+        &self,
+        submitted_career_update: SubmittedCareerUpdate,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrResp> {
+        let account_id = submitted_career_update.account_id;
+        let career_id = submitted_career_update.career_id;
+        let career = submitted_career_update.career;
+        let career_image1_option = submitted_career_update.career_image1;
+        let career_image2_option = submitted_career_update.career_image2;
+        let storage_client = self.storage_client.clone();
+        let _ = self
+            .pool
+            .transaction::<_, (), ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let (start_date, end_date) = convert_career_dates(&career)?;
+                    let active_model = update_career_req::ActiveModel {
+                        career_id: Set(career_id),
+                        user_account_id: Set(account_id),
+                        company_name: Set(career.company_name),
+                        department_name: Set(career.department_name),
+                        office: Set(career.office),
+                        career_start_date: Set(start_date),
+                        career_end_date: Set(end_date),
+                        contract_type: Set(career.contract_type),
+                        profession: Set(career.profession),
+                        annual_income_in_man_yen: Set(career.annual_income_in_man_yen),
+                        is_manager: Set(career.is_manager),
+                        position_name: Set(career.position_name),
+                        is_new_graduate: Set(career.is_new_graduate),
+                        note: Set(career.note),
+                        image1_file_name_without_ext: Set(career_image1_option
+                            .as_ref()
+                            .map(|image1| image1.0.clone())),
+                        image2_file_name_without_ext: Set(career_image2_option
+                            .as_ref()
+                            .map(|image2| image2.0.clone())),
+                        requested_at: Set(current_date_time),
+                        claimed_by: NotSet,
+                        claimed_at: NotSet,
+                    };
+                    let _ = active_model.insert(txn).await.map_err(|e| {
+                        error!(
+                            "failed to insert update_career_req (career_id: {}, user_account_id: {}): {}",
+                            career_id, account_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+                    if let Some(career_image1) = career_image1_option {
+                        let _ = upload_png_images_to_career_storage(
+                            storage_client,
+                            account_id,
+                            career_image1,
+                            career_image2_option,
+                        )
+                        .await?;
+                    }
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("failed to insert update_career_req: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!("failed to insert update_career_req: {}", err_resp_struct);
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use async_session::serde_json;
+    use axum::body::Bytes;
+    use image::{ImageBuffer, ImageOutputFormat, RgbImage};
+
+    use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::post::CareerField;
+    use crate::handlers::tests::SendMailMock;
+
+    use super::*;
+
+    struct DummyCareerField {
+        name: Option<String>,
+        file_name: Option<String>,
+        data: Bytes,
+    }
+
+    struct MultipartWrapperMock {
+        count: usize,
+        fields: Vec<DummyCareerField>,
+    }
+
+    #[async_trait]
+    impl MultipartWrapper for MultipartWrapperMock {
+        async fn next_field(&mut self) -> Result<Option<CareerField>, ErrResp> {
+            let dummy_field = self.fields.get(self.count);
+            let field = dummy_field.map(|f| CareerField {
+                name: f.name.clone(),
+                file_name: f.file_name.clone(),
+                data: Ok(f.data.clone()),
+            });
+            self.count += 1;
+            Ok(field)
+        }
+    }
+
+    fn create_dummy_career() -> Career {
+        Career {
+            company_name: "テスト株式会社".to_string(),
+            department_name: None,
+            office: None,
+            career_start_date: Ymd {
+                year: 2008,
+                month: 4,
+                day: 1,
+            },
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: None,
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+        }
+    }
+
+    fn create_dummy_career_field(career: &Career) -> DummyCareerField {
+        let career_str = serde_json::to_string(career).expect("failed to get Ok");
+        DummyCareerField {
+            name: Some(String::from("career")),
+            file_name: None,
+            data: Bytes::from(career_str),
+        }
+    }
+
+    // 画像の品質の検査（解像度、ほぼ単色かどうか、ぼやけ具合）を通過する画像
+    fn create_dummy_career_image() -> Cursor<Vec<u8>> {
+        let img: RgbImage = ImageBuffer::from_fn(640, 480, |x, y| {
+            let gradient = x * 150 / 640;
+            let checker = if ((x / 16) + (y / 16)) % 2 == 0 {
+                100
+            } else {
+                0
+            };
+            let luma = (gradient + checker) as u8;
+            image::Rgb([luma, luma, luma])
+        });
+        let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
+        img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
+            .expect("failed to get Ok");
+        bytes
+    }
+
+    fn create_dummy_career_image_field(name: &str) -> DummyCareerField {
+        DummyCareerField {
+            name: Some(name.to_string()),
+            file_name: Some(String::from("test.jpeg")),
+            data: Bytes::from(create_dummy_career_image().into_inner()),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_success_without_career_images() {
+        let career = create_dummy_career();
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields: vec![create_dummy_career_field(&career)],
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let (actual_career, career_image1, career_image2) = result.expect("failed to get Ok");
+        assert_eq!(career, actual_career);
+        assert_eq!(None, career_image1);
+        assert_eq!(None, career_image2);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_success_with_career_image1() {
+        let career = create_dummy_career();
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields: vec![
+                create_dummy_career_field(&career),
+                create_dummy_career_image_field("career-image1"),
+            ],
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let (actual_career, career_image1, career_image2) = result.expect("failed to get Ok");
+        assert_eq!(career, actual_career);
+        assert!(career_image1.is_some());
+        assert_eq!(None, career_image2);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_no_career_found() {
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields: vec![create_dummy_career_image_field("career-image1")],
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCareerFound as u32, resp.1.code);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_career_image2_without_career_image1() {
+        let career = create_dummy_career();
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields: vec![
+                create_dummy_career_field(&career),
+                create_dummy_career_image_field("career-image2"),
+            ],
+        };
+
+        let result = handle_multipart(mock, MAX_CAREER_FILE_SIZE_IN_BYTES).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCareerImage1Found as u32, resp.1.code);
+    }
+
+    struct UpdateCareerOperationMock {
+        account_id: i64,
+        career_id: i64,
+        career: Career,
+        update_req_exists: bool,
+        submitted_career_update: SubmittedCareerUpdate,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl UpdateCareerOperation for UpdateCareerOperationMock {
+        async fn find_career_by_career_id_and_account_id(
+            &self,
+            career_id: i64,
+            account_id: i64,
+        ) -> Result<Option<Career>, ErrResp> {
+            if self.career_id != career_id || self.account_id != account_id {
+                return Ok(None);
+            }
+            Ok(Some(self.career.clone()))
+        }
+
+        async fn check_if_update_career_req_already_exists(
+            &self,
+            career_id: i64,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.career_id, career_id);
+            Ok(self.update_req_exists)
+        }
+
+        async fn request_update_career(
+            &self,
+            submitted_career_update: SubmittedCareerUpdate,
+            current_date_time: DateTime<FixedOffset>,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.submitted_career_update, submitted_career_update);
+            assert_eq!(self.current_date_time, current_date_time);
+            Ok(())
+        }
+    }
+
+    fn create_op_mock(
+        submitted_career_update: &SubmittedCareerUpdate,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> UpdateCareerOperationMock {
+        UpdateCareerOperationMock {
+            account_id: submitted_career_update.account_id,
+            career_id: submitted_career_update.career_id,
+            career: create_dummy_career(),
+            update_req_exists: false,
+            submitted_career_update: submitted_career_update.clone(),
+            current_date_time,
+        }
+    }
+
+    fn create_send_mail_mock(account_id: i64) -> SendMailMock {
+        SendMailMock::new(
+            ADMIN_EMAIL_ADDRESS.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            create_subject(account_id),
+            create_text(account_id),
+        )
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_success_with_career_image() {
+        let account_id = 4512;
+        let mut career = create_dummy_career();
+        career.position_name = Some("課長".to_string());
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career,
+            career_image1: Some((
+                Uuid::new_v4().simple().to_string(),
+                create_dummy_career_image(),
+            )),
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let op = create_op_mock(&submitted_career_update, current_date_time);
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_success_career_end_date_and_note_without_career_image() {
+        let account_id = 4512;
+        let mut career = create_dummy_career();
+        career.career_end_date = Some(Ymd {
+            year: 2023,
+            month: 3,
+            day: 31,
+        });
+        career.note = Some("退職しました".to_string());
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career,
+            career_image1: None,
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let op = create_op_mock(&submitted_career_update, current_date_time);
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(UpdateCareerResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_fail_no_career_to_handle_found() {
+        let account_id = 4512;
+        let mut career = create_dummy_career();
+        career.note = Some("備考".to_string());
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career,
+            career_image1: None,
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let mut op = create_op_mock(&submitted_career_update, current_date_time);
+        op.career_id = 99;
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCareerToHandleFound as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_fail_no_career_updated() {
+        let account_id = 4512;
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career: create_dummy_career(),
+            career_image1: Some((
+                Uuid::new_v4().simple().to_string(),
+                create_dummy_career_image(),
+            )),
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let op = create_op_mock(&submitted_career_update, current_date_time);
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCareerUpdated as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_fail_career_image_is_required() {
+        let account_id = 4512;
+        let mut career = create_dummy_career();
+        career.position_name = Some("課長".to_string());
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career,
+            career_image1: None,
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let op = create_op_mock(&submitted_career_update, current_date_time);
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::NoCareerImage1Found as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_update_career_req_fail_update_career_req_already_exists() {
+        let account_id = 4512;
+        let mut career = create_dummy_career();
+        career.note = Some("備考".to_string());
+        let submitted_career_update = SubmittedCareerUpdate {
+            account_id,
+            career_id: 98,
+            career,
+            career_image1: None,
+            career_image2: None,
+        };
+        let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+        let mut op = create_op_mock(&submitted_career_update, current_date_time);
+        op.update_req_exists = true;
+
+        let result = handle_update_career_req(
+            submitted_career_update,
+            current_date_time,
+            op,
+            create_send_mail_mock(account_id),
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::UpdateCareerReqAlreadyExists as u32, resp.1 .0.code);
+    }
+
+    #[test]
+    fn requires_career_image_returns_false_if_only_career_end_date_and_note_are_changed() {
+        let career = create_dummy_career();
+        let mut career_to_update = career.clone();
+        career_to_update.career_end_date = Some(Ymd {
+            year: 2023,
+            month: 3,
+            day: 31,
+        });
+        career_to_update.note = Some("備考".to_string());
+
+        assert!(!requires_career_image(&career, &career_to_update));
+    }
+
+    #[test]
+    fn requires_career_image_returns_true_if_company_name_is_changed() {
+        let career = create_dummy_career();
+        let mut career_to_update = career.clone();
+        career_to_update.company_name = "テスト株式会社２".to_string();
+
+        assert!(requires_career_image(&career, &career_to_update));
+    }
+}
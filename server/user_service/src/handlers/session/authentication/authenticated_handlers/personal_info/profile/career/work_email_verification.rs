@@ -0,0 +1,43 @@
+// Copyright 2023 Ken Miura
+
+//! 職務経歴確認依頼の補足情報として、勤務先のメールアドレスのドメインを確認する処理を集約するモジュール<br>
+//! <br>
+//! 勤務先のメールアドレス宛に確認コードを送信し、ユーザーがそのコードを入力できた場合、
+//! そのメールアドレスのドメインを職務経歴確認依頼に記録する（メールアドレス自体は保存しない）
+
+pub(crate) mod code_req;
+pub(crate) mod verification;
+
+use common::ErrRespStruct;
+use entity::create_career_req;
+use entity::sea_orm::{DatabaseTransaction, EntityTrait, QuerySelect};
+use tracing::error;
+
+use crate::err::unexpected_err_resp;
+
+/// 確認コードの有効期間（分）
+const VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE: i64 = 10;
+/// 確認コードの桁数
+const WORK_EMAIL_VERIFICATION_CODE_LENGTH: usize = 6;
+/// 一つの確認コードに対して試行できる回数の上限
+const MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS: i16 = 5;
+
+async fn find_create_career_req_model_with_exclusive_lock(
+    txn: &DatabaseTransaction,
+    create_career_req_id: i64,
+) -> Result<Option<create_career_req::Model>, ErrRespStruct> {
+    let req_option = create_career_req::Entity::find_by_id(create_career_req_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to find create_career_req (create_career_req_id: {}): {}",
+                create_career_req_id, e
+            );
+            ErrRespStruct {
+                err_resp: unexpected_err_resp(),
+            }
+        })?;
+    Ok(req_option)
+}
@@ -0,0 +1,794 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::extract::State;
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use common::password::hash_password;
+use common::smtp::{SendMail, SmtpClient, INQUIRY_EMAIL_ADDRESS, SYSTEM_EMAIL_ADDRESS};
+use common::util::validator::email_address_validator::validate_email_address;
+use common::{ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE, WEB_SITE_NAME};
+use entity::sea_orm::{
+    ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionError, TransactionTrait,
+};
+use entity::{create_career_req, work_email_verification_code};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+use super::{
+    find_create_career_req_model_with_exclusive_lock,
+    VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE, WORK_EMAIL_VERIFICATION_CODE_LENGTH,
+};
+
+/// 同じ職務経歴確認依頼に対して、確認コードを再送できるようになるまでの間隔（秒）
+const MIN_INTERVAL_OF_WORK_EMAIL_VERIFICATION_CODE_REQ_IN_SECOND: i64 = 60;
+
+/// 勤務先のメールアドレスとして扱わないフリーメール、携帯キャリアのドメイン
+const FREE_EMAIL_DOMAINS: [&str; 14] = [
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.co.jp",
+    "ymail.ne.jp",
+    "icloud.com",
+    "me.com",
+    "outlook.com",
+    "outlook.jp",
+    "hotmail.com",
+    "hotmail.co.jp",
+    "docomo.ne.jp",
+    "ezweb.ne.jp",
+    "au.com",
+    "softbank.ne.jp",
+];
+
+static SUBJECT: Lazy<String> = Lazy::new(|| {
+    format!(
+        "[{}] 勤務先メールアドレス確認コードのお知らせ",
+        WEB_SITE_NAME
+    )
+});
+
+/// 勤務先のメールアドレス宛に、職務経歴確認依頼の確認コードを送信する<br>
+/// <br>
+/// # Errors
+/// メールアドレスが不正な形式の場合、ステータスコード400、エラーコード[common::err::Code::InvalidEmailAddressFormat]を返す<br>
+/// フリーメールのドメインの場合、ステータスコード400、エラーコード[Code::FreeEmailDomainNotAllowed]を返す<br>
+/// ログインユーザーの職務経歴確認依頼が存在しない場合、ステータスコード400、エラーコード[Code::NoCreateCareerReqForWorkEmailVerificationFound]を返す<br>
+/// 退職済みの職務経歴の場合、ステータスコード400、エラーコード[Code::WorkEmailVerificationForEndedCareer]を返す<br>
+/// 前回の送信から一定時間経過していない場合、ステータスコード400、エラーコード[Code::WorkEmailVerificationCodeReqTooFrequent]を返す
+pub(crate) async fn post_work_email_verification_code_req(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(smtp_client): State<SmtpClient>,
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<WorkEmailVerificationCodeReq>,
+) -> RespResult<WorkEmailVerificationCodeReqResult> {
+    let code = generate_verification_code();
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = WorkEmailVerificationCodeReqOperationImpl { pool };
+    handle_work_email_verification_code_req(
+        user_info.account_id,
+        req.create_career_req_id,
+        req.work_email_address.as_str(),
+        code.as_str(),
+        current_date_time,
+        op,
+        smtp_client,
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WorkEmailVerificationCodeReq {
+    create_career_req_id: i64,
+    work_email_address: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct WorkEmailVerificationCodeReqResult {}
+
+/// 数字のみからなる確認コードを生成する（乱数源としてUUID v4のランダムなビットを利用する）
+fn generate_verification_code() -> String {
+    let random = u128::from_be_bytes(*Uuid::new_v4().as_bytes());
+    let modulus = 10_u128.pow(WORK_EMAIL_VERIFICATION_CODE_LENGTH as u32);
+    format!(
+        "{:0width$}",
+        random % modulus,
+        width = WORK_EMAIL_VERIFICATION_CODE_LENGTH
+    )
+}
+
+async fn handle_work_email_verification_code_req(
+    account_id: i64,
+    create_career_req_id: i64,
+    work_email_address: &str,
+    code: &str,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl WorkEmailVerificationCodeReqOperation,
+    send_mail: impl SendMail,
+) -> RespResult<WorkEmailVerificationCodeReqResult> {
+    validate_email_address(work_email_address).map_err(|e| {
+        error!(
+            "failed to validate email address ({}): {}",
+            work_email_address, e
+        );
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: common::err::Code::InvalidEmailAddressFormat as u32,
+            }),
+        )
+    })?;
+    let email_domain = extract_domain(work_email_address)?;
+    if FREE_EMAIL_DOMAINS.contains(&email_domain.as_str()) {
+        error!("free email domain is not allowed ({})", email_domain);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::FreeEmailDomainNotAllowed as u32,
+            }),
+        ));
+    }
+
+    let req = op
+        .find_create_career_req(create_career_req_id)
+        .await?
+        .filter(|r| r.user_account_id == account_id)
+        .ok_or_else(|| {
+            error!(
+                "no create_career_req found (create_career_req_id: {}, user_account_id: {})",
+                create_career_req_id, account_id
+            );
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::NoCreateCareerReqForWorkEmailVerificationFound as u32,
+                }),
+            )
+        })?;
+    // 退職済みの勤務先のメールアドレスは受信できないため、現職の職務経歴のみを対象とする
+    if let Some(career_end_date) = req.career_end_date {
+        error!(
+            "career already ended (create_career_req_id: {}, career_end_date: {})",
+            create_career_req_id, career_end_date
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::WorkEmailVerificationForEndedCareer as u32,
+            }),
+        ));
+    }
+
+    let last_requested_at_option = op.find_last_requested_at(create_career_req_id).await?;
+    if let Some(last_requested_at) = last_requested_at_option {
+        let duration = current_date_time - last_requested_at;
+        if duration < Duration::seconds(MIN_INTERVAL_OF_WORK_EMAIL_VERIFICATION_CODE_REQ_IN_SECOND)
+        {
+            error!(
+                "work email verification code requested too frequently (create_career_req_id: {}, last_requested_at: {}, current_date_time: {})",
+                create_career_req_id, last_requested_at, current_date_time
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::WorkEmailVerificationCodeReqTooFrequent as u32,
+                }),
+            ));
+        }
+    }
+
+    let hashed_code = hash_password(code).map_err(|e| {
+        error!("failed to hash work email verification code: {}", e);
+        unexpected_err_resp()
+    })?;
+    op.create_work_email_verification_code(NewWorkEmailVerificationCode {
+        create_career_req_id,
+        user_account_id: account_id,
+        email_domain: email_domain.clone(),
+        hashed_code,
+        requested_at: current_date_time,
+    })
+    .await?;
+    info!(
+        "created work email verification code (create_career_req_id: {}, user_account_id: {}, email_domain: {}) at {}",
+        create_career_req_id, account_id, email_domain, current_date_time
+    );
+
+    let text = create_text(code);
+    send_mail
+        .send_mail(
+            work_email_address,
+            SYSTEM_EMAIL_ADDRESS.as_str(),
+            &SUBJECT,
+            &text,
+        )
+        .await?;
+    Ok((StatusCode::OK, Json(WorkEmailVerificationCodeReqResult {})))
+}
+
+/// メールアドレスからドメインを取り出す（大文字、小文字の表記揺れを吸収するため、小文字に揃える）
+fn extract_domain(email_address: &str) -> Result<String, ErrResp> {
+    let domain = email_address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.to_ascii_lowercase())
+        .ok_or_else(|| {
+            error!("no domain found in email address ({})", email_address);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: common::err::Code::InvalidEmailAddressFormat as u32,
+                }),
+            )
+        })?;
+    Ok(domain)
+}
+
+fn create_text(code: &str) -> String {
+    format!(
+        r"職務経歴確認依頼の補足情報として、勤務先のメールアドレスの確認を受け付けました。
+
+下記の確認コードを{}の画面に入力し、確認を完了してください。
+
+確認コード: {}
+
+※この確認コードの有効期間は送信時より{}分間です。確認コードが無効となった場合は、最初からやり直してください。
+※本メールにお心あたりが無い場合、他の方が誤ってあなたのメールアドレスを入力した可能性があります。お心あたりがない場合、本メールは破棄していただくようお願いいたします。
+
+本メールはシステムより自動配信されています。
+本メールに返信されましても、回答いたしかねます。
+お問い合わせは、下記のお問い合わせ先までご連絡くださいますようお願いいたします。
+
+【お問い合わせ先】
+Email: {}",
+        WEB_SITE_NAME,
+        code,
+        VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE,
+        INQUIRY_EMAIL_ADDRESS.as_str()
+    )
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CreateCareerReq {
+    user_account_id: i64,
+    career_end_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug)]
+struct NewWorkEmailVerificationCode {
+    create_career_req_id: i64,
+    user_account_id: i64,
+    email_domain: String,
+    hashed_code: Vec<u8>,
+    requested_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait WorkEmailVerificationCodeReqOperation {
+    async fn find_create_career_req(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<CreateCareerReq>, ErrResp>;
+
+    async fn find_last_requested_at(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<DateTime<FixedOffset>>, ErrResp>;
+
+    /// 確認コードを生成する（既に生成済みの確認コードがある場合、それを置き換える）
+    async fn create_work_email_verification_code(
+        &self,
+        new_code: NewWorkEmailVerificationCode,
+    ) -> Result<(), ErrResp>;
+}
+
+struct WorkEmailVerificationCodeReqOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl WorkEmailVerificationCodeReqOperation for WorkEmailVerificationCodeReqOperationImpl {
+    async fn find_create_career_req(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<CreateCareerReq>, ErrResp> {
+        let model_option = create_career_req::Entity::find_by_id(create_career_req_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find create_career_req (create_career_req_id: {}): {}",
+                    create_career_req_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model_option.map(|m| CreateCareerReq {
+            user_account_id: m.user_account_id,
+            career_end_date: m.career_end_date,
+        }))
+    }
+
+    async fn find_last_requested_at(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<DateTime<FixedOffset>>, ErrResp> {
+        let model_option = work_email_verification_code::Entity::find_by_id(create_career_req_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find work_email_verification_code (create_career_req_id: {}): {}",
+                    create_career_req_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model_option.map(|m| m.requested_at))
+    }
+
+    async fn create_work_email_verification_code(
+        &self,
+        new_code: NewWorkEmailVerificationCode,
+    ) -> Result<(), ErrResp> {
+        self.pool
+            .transaction::<_, (), ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let create_career_req_id = new_code.create_career_req_id;
+                    // 職務経歴確認依頼の承認、拒否と並行して確認コードが作られないように、依頼のロックを先に取得する
+                    let req_option =
+                        find_create_career_req_model_with_exclusive_lock(txn, create_career_req_id)
+                            .await?;
+                    if req_option.is_none() {
+                        error!(
+                            "no create_career_req found (create_career_req_id: {})",
+                            create_career_req_id
+                        );
+                        return Err(ErrRespStruct {
+                            err_resp: (
+                                StatusCode::BAD_REQUEST,
+                                Json(ApiError {
+                                    code: Code::NoCreateCareerReqForWorkEmailVerificationFound
+                                        as u32,
+                                }),
+                            ),
+                        });
+                    }
+
+                    let _ = work_email_verification_code::Entity::delete_by_id(
+                        create_career_req_id,
+                    )
+                    .exec(txn)
+                    .await
+                    .map_err(|e| {
+                        error!(
+                            "failed to delete work_email_verification_code (create_career_req_id: {}): {}",
+                            create_career_req_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+
+                    let active_model = work_email_verification_code::ActiveModel {
+                        create_career_req_id: Set(create_career_req_id),
+                        user_account_id: Set(new_code.user_account_id),
+                        email_domain: Set(new_code.email_domain),
+                        hashed_code: Set(new_code.hashed_code),
+                        num_of_attempts: Set(0),
+                        requested_at: Set(new_code.requested_at),
+                    };
+                    let _ = active_model.insert(txn).await.map_err(|e| {
+                        error!(
+                            "failed to insert work_email_verification_code (create_career_req_id: {}): {}",
+                            create_career_req_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!(
+                        "failed to create work_email_verification_code: {}",
+                        err_resp_struct
+                    );
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use common::password::is_password_match;
+
+    use crate::handlers::tests::SendMailMock;
+
+    use super::*;
+
+    struct WorkEmailVerificationCodeReqOperationMock {
+        create_career_req_id: i64,
+        create_career_req: CreateCareerReq,
+        last_requested_at: Option<DateTime<FixedOffset>>,
+        email_domain: String,
+        code: String,
+        current_date_time: DateTime<FixedOffset>,
+    }
+
+    #[async_trait]
+    impl WorkEmailVerificationCodeReqOperation for WorkEmailVerificationCodeReqOperationMock {
+        async fn find_create_career_req(
+            &self,
+            create_career_req_id: i64,
+        ) -> Result<Option<CreateCareerReq>, ErrResp> {
+            if self.create_career_req_id != create_career_req_id {
+                return Ok(None);
+            }
+            Ok(Some(self.create_career_req.clone()))
+        }
+
+        async fn find_last_requested_at(
+            &self,
+            create_career_req_id: i64,
+        ) -> Result<Option<DateTime<FixedOffset>>, ErrResp> {
+            assert_eq!(self.create_career_req_id, create_career_req_id);
+            Ok(self.last_requested_at)
+        }
+
+        async fn create_work_email_verification_code(
+            &self,
+            new_code: NewWorkEmailVerificationCode,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.create_career_req_id, new_code.create_career_req_id);
+            assert_eq!(
+                self.create_career_req.user_account_id,
+                new_code.user_account_id
+            );
+            assert_eq!(self.email_domain, new_code.email_domain);
+            assert!(is_password_match(self.code.as_str(), &new_code.hashed_code)
+                .expect("failed to get Ok"));
+            assert_eq!(self.current_date_time, new_code.requested_at);
+            Ok(())
+        }
+    }
+
+    fn create_op_mock(
+        account_id: i64,
+        create_career_req_id: i64,
+        career_end_date: Option<NaiveDate>,
+        last_requested_at: Option<DateTime<FixedOffset>>,
+        code: &str,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> WorkEmailVerificationCodeReqOperationMock {
+        WorkEmailVerificationCodeReqOperationMock {
+            create_career_req_id,
+            create_career_req: CreateCareerReq {
+                user_account_id: account_id,
+                career_end_date,
+            },
+            last_requested_at,
+            email_domain: "example.co.jp".to_string(),
+            code: code.to_string(),
+            current_date_time,
+        }
+    }
+
+    #[test]
+    fn generate_verification_code_generates_digits_of_fixed_length() {
+        for _ in 0..100 {
+            let code = generate_verification_code();
+            assert_eq!(WORK_EMAIL_VERIFICATION_CODE_LENGTH, code.len());
+            assert!(code.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_success() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@Example.co.jp";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            None,
+            None,
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(WorkEmailVerificationCodeReqResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_success_resend_after_interval() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@example.co.jp";
+        let code = "987654";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let last_requested_at = current_date_time
+            - Duration::seconds(MIN_INTERVAL_OF_WORK_EMAIL_VERIFICATION_CODE_REQ_IN_SECOND);
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            None,
+            Some(last_requested_at),
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(WorkEmailVerificationCodeReqResult {}, resp.1 .0);
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_fail_invalid_email_address() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka.example.co.jp";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            None,
+            None,
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            common::err::Code::InvalidEmailAddressFormat as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_fail_free_email_domain() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@gmail.com";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            None,
+            None,
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::FreeEmailDomainNotAllowed as u32, resp.1 .0.code);
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_fail_req_of_other_user() {
+        let account_id = 6314;
+        let other_account_id = 6315;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@example.co.jp";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let op = create_op_mock(
+            other_account_id,
+            create_career_req_id,
+            None,
+            None,
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::NoCreateCareerReqForWorkEmailVerificationFound as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_fail_ended_career() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@example.co.jp";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            Some(NaiveDate::from_ymd_opt(2022, 3, 31).expect("failed to get Ok")),
+            None,
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::WorkEmailVerificationForEndedCareer as u32,
+            resp.1 .0.code
+        );
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_code_req_fail_too_frequent() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let work_email_address = "taro.tanaka@example.co.jp";
+        let code = "012345";
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let last_requested_at = current_date_time
+            - Duration::seconds(MIN_INTERVAL_OF_WORK_EMAIL_VERIFICATION_CODE_REQ_IN_SECOND - 1);
+        let op = create_op_mock(
+            account_id,
+            create_career_req_id,
+            None,
+            Some(last_requested_at),
+            code,
+            current_date_time,
+        );
+        let send_mail_mock = SendMailMock::new(
+            work_email_address.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            SUBJECT.to_string(),
+            create_text(code),
+        );
+
+        let result = handle_work_email_verification_code_req(
+            account_id,
+            create_career_req_id,
+            work_email_address,
+            code,
+            current_date_time,
+            op,
+            send_mail_mock,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::WorkEmailVerificationCodeReqTooFrequent as u32,
+            resp.1 .0.code
+        );
+    }
+}
@@ -0,0 +1,605 @@
+// Copyright 2023 Ken Miura
+
+use axum::async_trait;
+use axum::extract::State;
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::password::is_password_match;
+use common::{ApiError, ErrResp, ErrRespStruct, RespResult, JAPANESE_TIME_ZONE};
+use entity::sea_orm::sea_query::Expr;
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set,
+    TransactionError, TransactionTrait,
+};
+use entity::{create_career_req, work_email_verification_code};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::err::{unexpected_err_resp, Code};
+use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::verified_user::VerifiedUser;
+
+use super::{
+    find_create_career_req_model_with_exclusive_lock, MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS,
+    VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE, WORK_EMAIL_VERIFICATION_CODE_LENGTH,
+};
+
+/// 勤務先のメールアドレス宛に送信した確認コードを照合し、一致した場合、そのドメインを職務経歴確認依頼に記録する<br>
+/// <br>
+/// # Errors
+/// 確認コードが不正な形式の場合、ステータスコード400、エラーコード[Code::InvalidWorkEmailVerificationCodeFormat]を返す<br>
+/// ログインユーザーの確認コードが存在しない場合、ステータスコード400、エラーコード[Code::NoWorkEmailVerificationCodeFound]を返す<br>
+/// 確認コードの有効期限が切れている場合、ステータスコード400、エラーコード[Code::WorkEmailVerificationCodeExpired]を返す<br>
+/// 試行回数の上限に達している場合、ステータスコード400、エラーコード[Code::ReachWorkEmailVerificationAttemptLimit]を返す<br>
+/// 確認コードが一致しない場合、ステータスコード400、エラーコード[Code::WorkEmailVerificationCodeMismatch]を返す
+pub(crate) async fn post_work_email_verification(
+    VerifiedUser { user_info }: VerifiedUser,
+    State(pool): State<DatabaseConnection>,
+    Json(req): Json<WorkEmailVerification>,
+) -> RespResult<WorkEmailVerificationResult> {
+    let current_date_time = Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    let op = WorkEmailVerificationOperationImpl { pool };
+    handle_work_email_verification(
+        user_info.account_id,
+        req.create_career_req_id,
+        req.code.as_str(),
+        current_date_time,
+        op,
+    )
+    .await
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WorkEmailVerification {
+    create_career_req_id: i64,
+    code: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct WorkEmailVerificationResult {
+    verified_work_email_domain: String,
+}
+
+async fn handle_work_email_verification(
+    account_id: i64,
+    create_career_req_id: i64,
+    code: &str,
+    current_date_time: DateTime<FixedOffset>,
+    op: impl WorkEmailVerificationOperation,
+) -> RespResult<WorkEmailVerificationResult> {
+    if code.len() != WORK_EMAIL_VERIFICATION_CODE_LENGTH
+        || !code.chars().all(|c| c.is_ascii_digit())
+    {
+        error!("invalid work email verification code format ({})", code);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::InvalidWorkEmailVerificationCodeFormat as u32,
+            }),
+        ));
+    }
+
+    let verification_code = op
+        .find_work_email_verification_code(create_career_req_id)
+        .await?
+        .filter(|c| c.user_account_id == account_id)
+        .ok_or_else(|| {
+            error!(
+                "no work_email_verification_code found (create_career_req_id: {}, user_account_id: {})",
+                create_career_req_id, account_id
+            );
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::NoWorkEmailVerificationCodeFound as u32,
+                }),
+            )
+        })?;
+
+    let duration = current_date_time - verification_code.requested_at;
+    if duration > Duration::minutes(VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE) {
+        error!(
+            "work email verification code (requested at {}) already expired at {}",
+            verification_code.requested_at, current_date_time
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::WorkEmailVerificationCodeExpired as u32,
+            }),
+        ));
+    }
+
+    // DBの分離レベルがSerializeでないため、MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTSを超える可能性を考慮し、">="とする
+    if verification_code.num_of_attempts >= MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS {
+        error!(
+            "reach max work email verification attempts (create_career_req_id: {}, num_of_attempts: {}, max: {})",
+            create_career_req_id,
+            verification_code.num_of_attempts,
+            MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::ReachWorkEmailVerificationAttemptLimit as u32,
+            }),
+        ));
+    }
+
+    let is_match = is_password_match(code, &verification_code.hashed_code).map_err(|e| {
+        error!("failed to match work email verification code: {}", e);
+        unexpected_err_resp()
+    })?;
+    if !is_match {
+        op.increment_num_of_attempts(create_career_req_id).await?;
+        error!(
+            "work email verification code mismatch (create_career_req_id: {})",
+            create_career_req_id
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::WorkEmailVerificationCodeMismatch as u32,
+            }),
+        ));
+    }
+
+    op.complete_work_email_verification(
+        create_career_req_id,
+        verification_code.email_domain.clone(),
+    )
+    .await?;
+    info!(
+        "work email verified (create_career_req_id: {}, user_account_id: {}, email_domain: {})",
+        create_career_req_id, account_id, verification_code.email_domain
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(WorkEmailVerificationResult {
+            verified_work_email_domain: verification_code.email_domain,
+        }),
+    ))
+}
+
+#[derive(Clone, Debug)]
+struct VerificationCode {
+    user_account_id: i64,
+    email_domain: String,
+    hashed_code: Vec<u8>,
+    num_of_attempts: i16,
+    requested_at: DateTime<FixedOffset>,
+}
+
+#[async_trait]
+trait WorkEmailVerificationOperation {
+    async fn find_work_email_verification_code(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<VerificationCode>, ErrResp>;
+
+    async fn increment_num_of_attempts(&self, create_career_req_id: i64) -> Result<(), ErrResp>;
+
+    /// 確認済みのドメインを職務経歴確認依頼に記録し、確認コードを削除する
+    async fn complete_work_email_verification(
+        &self,
+        create_career_req_id: i64,
+        email_domain: String,
+    ) -> Result<(), ErrResp>;
+}
+
+struct WorkEmailVerificationOperationImpl {
+    pool: DatabaseConnection,
+}
+
+#[async_trait]
+impl WorkEmailVerificationOperation for WorkEmailVerificationOperationImpl {
+    async fn find_work_email_verification_code(
+        &self,
+        create_career_req_id: i64,
+    ) -> Result<Option<VerificationCode>, ErrResp> {
+        let model_option = work_email_verification_code::Entity::find_by_id(create_career_req_id)
+            .one(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find work_email_verification_code (create_career_req_id: {}): {}",
+                    create_career_req_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(model_option.map(|m| VerificationCode {
+            user_account_id: m.user_account_id,
+            email_domain: m.email_domain,
+            hashed_code: m.hashed_code,
+            num_of_attempts: m.num_of_attempts,
+            requested_at: m.requested_at,
+        }))
+    }
+
+    async fn increment_num_of_attempts(&self, create_career_req_id: i64) -> Result<(), ErrResp> {
+        let _ = work_email_verification_code::Entity::update_many()
+            .col_expr(
+                work_email_verification_code::Column::NumOfAttempts,
+                Expr::col(work_email_verification_code::Column::NumOfAttempts).add(1),
+            )
+            .filter(
+                work_email_verification_code::Column::CreateCareerReqId.eq(create_career_req_id),
+            )
+            .exec(&self.pool)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to increment num_of_attempts (create_career_req_id: {}): {}",
+                    create_career_req_id, e
+                );
+                unexpected_err_resp()
+            })?;
+        Ok(())
+    }
+
+    async fn complete_work_email_verification(
+        &self,
+        create_career_req_id: i64,
+        email_domain: String,
+    ) -> Result<(), ErrResp> {
+        let _ = self
+            .pool
+            .transaction::<_, (), ErrRespStruct>(|txn| {
+                Box::pin(async move {
+                    let req_option =
+                        find_create_career_req_model_with_exclusive_lock(txn, create_career_req_id)
+                            .await?;
+                    // 照合中に職務経歴確認依頼が承認、拒否された場合、記録先がないため確認コードのみ存在しない扱いとする
+                    let req = req_option.ok_or_else(|| {
+                        error!(
+                            "no create_career_req found (create_career_req_id: {})",
+                            create_career_req_id
+                        );
+                        ErrRespStruct {
+                            err_resp: (
+                                StatusCode::BAD_REQUEST,
+                                Json(ApiError {
+                                    code: Code::NoWorkEmailVerificationCodeFound as u32,
+                                }),
+                            ),
+                        }
+                    })?;
+
+                    let mut active_model: create_career_req::ActiveModel = req.into();
+                    active_model.verified_work_email_domain = Set(Some(email_domain.clone()));
+                    let _ = active_model.update(txn).await.map_err(|e| {
+                        error!(
+                            "failed to update verified_work_email_domain (create_career_req_id: {}, email_domain: {}): {}",
+                            create_career_req_id, email_domain, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+
+                    let _ = work_email_verification_code::Entity::delete_by_id(
+                        create_career_req_id,
+                    )
+                    .exec(txn)
+                    .await
+                    .map_err(|e| {
+                        error!(
+                            "failed to delete work_email_verification_code (create_career_req_id: {}): {}",
+                            create_career_req_id, e
+                        );
+                        ErrRespStruct {
+                            err_resp: unexpected_err_resp(),
+                        }
+                    })?;
+                    Ok(())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Connection(db_err) => {
+                    error!("connection error: {}", db_err);
+                    unexpected_err_resp()
+                }
+                TransactionError::Transaction(err_resp_struct) => {
+                    error!(
+                        "failed to complete work email verification: {}",
+                        err_resp_struct
+                    );
+                    err_resp_struct.err_resp
+                }
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use chrono::TimeZone;
+    use common::password::hash_password;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    static HASHED_CODE: Lazy<Vec<u8>> =
+        Lazy::new(|| hash_password("012345").expect("failed to get Ok"));
+
+    struct WorkEmailVerificationOperationMock<'a> {
+        create_career_req_id: i64,
+        verification_code: VerificationCode,
+        incremented: &'a AtomicBool,
+        completed: &'a AtomicBool,
+    }
+
+    #[async_trait]
+    impl<'a> WorkEmailVerificationOperation for WorkEmailVerificationOperationMock<'a> {
+        async fn find_work_email_verification_code(
+            &self,
+            create_career_req_id: i64,
+        ) -> Result<Option<VerificationCode>, ErrResp> {
+            if self.create_career_req_id != create_career_req_id {
+                return Ok(None);
+            }
+            Ok(Some(self.verification_code.clone()))
+        }
+
+        async fn increment_num_of_attempts(
+            &self,
+            create_career_req_id: i64,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.create_career_req_id, create_career_req_id);
+            self.incremented.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn complete_work_email_verification(
+            &self,
+            create_career_req_id: i64,
+            email_domain: String,
+        ) -> Result<(), ErrResp> {
+            assert_eq!(self.create_career_req_id, create_career_req_id);
+            assert_eq!(self.verification_code.email_domain, email_domain);
+            self.completed.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn create_verification_code(
+        account_id: i64,
+        num_of_attempts: i16,
+        requested_at: DateTime<FixedOffset>,
+    ) -> VerificationCode {
+        VerificationCode {
+            user_account_id: account_id,
+            email_domain: "example.co.jp".to_string(),
+            hashed_code: HASHED_CODE.clone(),
+            num_of_attempts,
+            requested_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_success() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let current_date_time = requested_at
+            + Duration::minutes(VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE);
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(
+                account_id,
+                MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS - 1,
+                requested_at,
+            ),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "012345",
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect("failed to get Ok");
+        assert_eq!(StatusCode::OK, resp.0);
+        assert_eq!(
+            WorkEmailVerificationResult {
+                verified_work_email_domain: "example.co.jp".to_string()
+            },
+            resp.1 .0
+        );
+        assert!(!incremented.load(Ordering::SeqCst));
+        assert!(completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_fail_invalid_code_format() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(account_id, 0, requested_at),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "01234a",
+            requested_at,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::InvalidWorkEmailVerificationCodeFormat as u32,
+            resp.1 .0.code
+        );
+        assert!(!incremented.load(Ordering::SeqCst));
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_fail_code_of_other_user() {
+        let account_id = 6314;
+        let other_account_id = 6315;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(other_account_id, 0, requested_at),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "012345",
+            requested_at,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::NoWorkEmailVerificationCodeFound as u32,
+            resp.1 .0.code
+        );
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_fail_expired() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let current_date_time = requested_at
+            + Duration::minutes(VALID_PERIOD_OF_WORK_EMAIL_VERIFICATION_CODE_IN_MINUTE)
+            + Duration::seconds(1);
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(account_id, 0, requested_at),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "012345",
+            current_date_time,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::WorkEmailVerificationCodeExpired as u32,
+            resp.1 .0.code
+        );
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_fail_reach_attempt_limit() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(
+                account_id,
+                MAX_NUM_OF_WORK_EMAIL_VERIFICATION_ATTEMPTS,
+                requested_at,
+            ),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        // 正しい確認コードであっても、上限に達した後は受け付けない
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "012345",
+            requested_at,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::ReachWorkEmailVerificationAttemptLimit as u32,
+            resp.1 .0.code
+        );
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn handle_work_email_verification_fail_code_mismatch() {
+        let account_id = 6314;
+        let create_career_req_id = 834;
+        let requested_at = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2023, 5, 1, 10, 0, 0)
+            .unwrap();
+        let incremented = AtomicBool::new(false);
+        let completed = AtomicBool::new(false);
+        let op = WorkEmailVerificationOperationMock {
+            create_career_req_id,
+            verification_code: create_verification_code(account_id, 0, requested_at),
+            incremented: &incremented,
+            completed: &completed,
+        };
+
+        let result = handle_work_email_verification(
+            account_id,
+            create_career_req_id,
+            "543210",
+            requested_at,
+            op,
+        )
+        .await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(
+            Code::WorkEmailVerificationCodeMismatch as u32,
+            resp.1 .0.code
+        );
+        assert!(incremented.load(Ordering::SeqCst));
+        assert!(!completed.load(Ordering::SeqCst));
+    }
+}
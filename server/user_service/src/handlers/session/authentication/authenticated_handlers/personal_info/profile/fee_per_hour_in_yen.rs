@@ -4,14 +4,14 @@ use async_session::serde_json::json;
 use axum::async_trait;
 use axum::http::StatusCode;
 use axum::{extract::State, Json};
-use common::opensearch::{index_document, update_document, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::INDEX_NAME;
 use common::{ApiError, ErrResp, ErrRespStruct, RespResult};
 use entity::consulting_fee;
 use entity::sea_orm::{
     ActiveModelTrait, DatabaseConnection, EntityTrait, QuerySelect, Set, TransactionError,
     TransactionTrait,
 };
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -25,10 +25,13 @@ use crate::handlers::session::authentication::authenticated_handlers::fee_per_ho
 pub(crate) async fn post_fee_per_hour_in_yen(
     VerifiedUser { user_info }: VerifiedUser,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(fee): Json<Fee>,
 ) -> RespResult<FeePerHourInYenResult> {
-    let op = SubmitFeePerHourInYenOperationImpl { pool, index_client };
+    let op = SubmitFeePerHourInYenOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     handle_fee_per_hour_in_yen_req(user_info.account_id, fee.fee_per_hour_in_yen, op).await
 }
 
@@ -74,7 +77,7 @@ trait SubmitFeePerHourInYenOperation {
 
 struct SubmitFeePerHourInYenOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -84,7 +87,7 @@ impl SubmitFeePerHourInYenOperation for SubmitFeePerHourInYenOperationImpl {
         account_id: i64,
         fee_per_hour_in_yen: i32,
     ) -> Result<(), ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         let _ = self
             .pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
@@ -139,7 +142,7 @@ impl SubmitFeePerHourInYenOperation for SubmitFeePerHourInYenOperationImpl {
                             INDEX_NAME,
                             document_id.to_string().as_str(),
                             fee_per_hour_in_yen,
-                            index_client
+                            consultant_search_client
                         )
                         .await?;
                     } else {
@@ -152,7 +155,7 @@ impl SubmitFeePerHourInYenOperation for SubmitFeePerHourInYenOperationImpl {
                             document_id.to_string().as_str(),
                             account_id,
                             fee_per_hour_in_yen,
-                            index_client
+                            consultant_search_client
                         )
                         .await?;
                     };
@@ -179,7 +182,7 @@ async fn update_new_fee_per_hour_in_yen_on_document(
     index_name: &str,
     document_id: &str,
     fee_per_hour_in_yen: i32,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let value = format!("ctx._source.fee_per_hour_in_yen = {}", fee_per_hour_in_yen);
     let script = json!({
@@ -187,7 +190,7 @@ async fn update_new_fee_per_hour_in_yen_on_document(
             "source": value
         }
     });
-    update_document(index_name, document_id, &script, &index_client)
+    consultant_search_client.update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
@@ -204,7 +207,7 @@ async fn add_new_fee_per_hour_in_yen_into_document(
     document_id: &str,
     account_id: i64,
     fee_per_hour_in_yen: i32,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let new_document = json!({
         "user_account_id": account_id,
@@ -215,7 +218,7 @@ async fn add_new_fee_per_hour_in_yen_into_document(
         "num_of_rated": 0,
         "disabled": false
     });
-    index_document(index_name, document_id, &new_document, &index_client)
+    consultant_search_client.index_document(index_name, document_id, &new_document)
         .await
         .map_err(|e| {
             error!(
@@ -5,8 +5,12 @@ use std::io::Cursor;
 
 use crate::err::Code::IdentityReqAlreadyExists;
 use crate::handlers::session::authentication::authenticated_handlers::authenticated_users::user::User;
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::file_name_validator::validate_extension_is_jpeg;
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::convert_jpeg_to_png;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::{
+    decode_uploaded_file, detect_file_format, encode_to_png, MaxFileSizeInBytes, UploadedFileFormat,
+};
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::image_quality::{
+    check_image_quality, MAX_HAMMING_DISTANCE_FOR_DUPLICATE_IMAGE,
+};
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::multipart::{
     clone_file_name_if_exists, FileNameAndBinary,
 };
@@ -27,10 +31,12 @@ use common::{
 };
 use common::{ErrRespStruct, JAPANESE_TIME_ZONE, WEB_SITE_NAME};
 use entity::prelude::{CreateIdentityReq, UpdateIdentityReq};
+use entity::sea_orm::ActiveValue::NotSet;
 use entity::sea_orm::{
-    ActiveModelTrait, DatabaseConnection, EntityTrait, Set, TransactionError, TransactionTrait,
+    ActiveModelTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, Set, Statement, TransactionError, TransactionTrait,
 };
-use entity::{create_identity_req, update_identity_req};
+use entity::{create_identity_req, identity_image_hash, update_identity_req};
 use serde::Serialize;
 use tracing::{error, info};
 use uuid::Uuid;
@@ -39,8 +45,21 @@ use crate::err::{self, unexpected_err_resp, Code};
 
 use super::identity_validator::{validate_identity, IdentityValidationError};
 
-/// 身分証の画像ファイルのバイト単位での最大値
+/// 身分証の画像ファイル（jpeg）のバイト単位での最大値
 pub(crate) const MAX_IDENTITY_IMAGE_SIZE_IN_BYTES: usize = 8 * 1024 * 1024;
+/// 身分証の画像ファイル（png）のバイト単位での最大値（可逆圧縮のため、jpegより大きな値とする）
+pub(crate) const MAX_IDENTITY_PNG_IMAGE_SIZE_IN_BYTES: usize = 16 * 1024 * 1024;
+/// 身分証の画像ファイル（heic）のバイト単位での最大値
+pub(crate) const MAX_IDENTITY_HEIC_IMAGE_SIZE_IN_BYTES: usize = 8 * 1024 * 1024;
+/// 身分証のPDFファイルのバイト単位での最大値
+pub(crate) const MAX_IDENTITY_PDF_SIZE_IN_BYTES: usize = 10 * 1024 * 1024;
+/// 身分証のファイルの形式ごとのバイト単位での最大値
+pub(crate) const MAX_IDENTITY_FILE_SIZE_IN_BYTES: MaxFileSizeInBytes = MaxFileSizeInBytes {
+    jpeg: MAX_IDENTITY_IMAGE_SIZE_IN_BYTES,
+    png: MAX_IDENTITY_PNG_IMAGE_SIZE_IN_BYTES,
+    heic: MAX_IDENTITY_HEIC_IMAGE_SIZE_IN_BYTES,
+    pdf: MAX_IDENTITY_PDF_SIZE_IN_BYTES,
+};
 
 pub(crate) async fn post_identity(
     User { user_info }: User,
@@ -54,7 +73,7 @@ pub(crate) async fn post_identity(
     let current_date = current_date_time.naive_local().date();
     let (identity, identity_image1, identity_image2_option) = handle_multipart(
         multipart_wrapper,
-        MAX_IDENTITY_IMAGE_SIZE_IN_BYTES,
+        MAX_IDENTITY_FILE_SIZE_IN_BYTES,
         current_date,
     )
     .await?;
@@ -65,8 +84,12 @@ pub(crate) async fn post_identity(
     let submitted_identity = SubmittedIdentity {
         account_id: user_info.account_id,
         identity,
-        identity_image1: (image1_file_name_without_ext, identity_image1),
-        identity_image2: identity_image2_option.map(|image| (image2_file_name_without_ext, image)),
+        identity_image1: (image1_file_name_without_ext, identity_image1.0),
+        identity_image1_perceptual_hash: identity_image1.1,
+        identity_image2: identity_image2_option
+            .as_ref()
+            .map(|image| (image2_file_name_without_ext, image.0.clone())),
+        identity_image2_perceptual_hash: identity_image2_option.map(|image| image.1),
     };
     let result =
         handle_identity_req(submitted_identity, current_date_time, op, smtp_client).await?;
@@ -116,14 +139,24 @@ impl MultipartWrapper for MultipartWrapperImpl {
 struct IdentityField {
     name: Option<String>,
     file_name: Option<String>,
-    data: Result<Bytes, Box<dyn Error>>,
+    data: Result<Bytes, Box<dyn Error + Send + Sync>>,
 }
 
+/// png画像に変換した身分証の画像と、その画像の知覚ハッシュ
+type PngImageAndPerceptualHash = (Cursor<Vec<u8>>, u64);
+
 async fn handle_multipart(
     mut multipart: impl MultipartWrapper,
-    max_image_size_in_bytes: usize,
+    max_file_size_in_bytes: MaxFileSizeInBytes,
     current_date: NaiveDate,
-) -> Result<(Identity, Cursor<Vec<u8>>, Option<Cursor<Vec<u8>>>), ErrResp> {
+) -> Result<
+    (
+        Identity,
+        PngImageAndPerceptualHash,
+        Option<PngImageAndPerceptualHash>,
+    ),
+    ErrResp,
+> {
     let mut identity_option = None;
     let mut identity_image1_option = None;
     let mut identity_image2_option = None;
@@ -156,14 +189,14 @@ async fn handle_multipart(
             identity_option = Some(trim_space_from_identity(identity));
         } else if name == "identity-image1" {
             validate_identity_image_file_name(file_name_option)?;
-            validate_identity_image_size(data.len(), max_image_size_in_bytes)?;
-            let png_binary = convert_jpeg_to_png(data)?;
-            identity_image1_option = Some(png_binary);
+            let format = detect_file_format(&data)?;
+            validate_identity_image_size(data.len(), max_file_size_in_bytes.of(format))?;
+            identity_image1_option = Some(convert_to_png_with_quality_check(format, data).await?);
         } else if name == "identity-image2" {
             validate_identity_image_file_name(file_name_option)?;
-            validate_identity_image_size(data.len(), max_image_size_in_bytes)?;
-            let png_binary = convert_jpeg_to_png(data)?;
-            identity_image2_option = Some(png_binary);
+            let format = detect_file_format(&data)?;
+            validate_identity_image_size(data.len(), max_file_size_in_bytes.of(format))?;
+            identity_image2_option = Some(convert_to_png_with_quality_check(format, data).await?);
         } else {
             error!("invalid name in field: {}", name);
             return Err((
@@ -179,6 +212,17 @@ async fn handle_multipart(
     Ok((identity, identity_image1, identity_image2_option))
 }
 
+/// 画像の品質を検査した上でpng画像に変換し、知覚ハッシュとともに返す
+async fn convert_to_png_with_quality_check(
+    format: UploadedFileFormat,
+    data: Bytes,
+) -> Result<PngImageAndPerceptualHash, ErrResp> {
+    let img = decode_uploaded_file(format, data).await?;
+    let perceptual_hash = check_image_quality(&img)?;
+    let png_binary = encode_to_png(&img)?;
+    Ok((png_binary, perceptual_hash))
+}
+
 fn extract_identity(data: Bytes) -> Result<Identity, ErrResp> {
     let identity_json_str = std::str::from_utf8(&data).map_err(|e| {
         error!("invalid utf-8 sequence: {}", e);
@@ -201,28 +245,19 @@ fn extract_identity(data: Bytes) -> Result<Identity, ErrResp> {
     Ok(identity)
 }
 
+/// ファイルとして送られてきたかどうかを確認する
+///
+/// ファイルの形式はファイル名（拡張子）ではなく、ファイルの内容から判定する（[detect_file_format]）。
 fn validate_identity_image_file_name(file_name_option: Option<String>) -> Result<(), ErrResp> {
-    let file_name = match file_name_option {
-        Some(name) => name,
-        None => {
-            error!("failed to get file name in field");
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ApiError {
-                    code: Code::NoFileNameFound as u32,
-                }),
-            ));
-        }
-    };
-    validate_extension_is_jpeg(&file_name).map_err(|e| {
-        error!("invalid file name ({}): {}", file_name, e);
-        (
+    if file_name_option.is_none() {
+        error!("failed to get file name in field");
+        return Err((
             StatusCode::BAD_REQUEST,
             Json(ApiError {
-                code: Code::NotJpegExtension as u32,
+                code: Code::NoFileNameFound as u32,
             }),
-        )
-    })?;
+        ));
+    }
     Ok(())
 }
 
@@ -244,8 +279,8 @@ fn validate_identity_image_size(size: usize, max_size_in_bytes: usize) -> Result
 
 fn ensure_mandatory_params_exist(
     identity_option: Option<Identity>,
-    identity_image1_option: Option<Cursor<Vec<u8>>>,
-) -> Result<(Identity, Cursor<Vec<u8>>), ErrResp> {
+    identity_image1_option: Option<PngImageAndPerceptualHash>,
+) -> Result<(Identity, PngImageAndPerceptualHash), ErrResp> {
     let identity = match identity_option {
         Some(id) => id,
         None => {
@@ -371,6 +406,7 @@ async fn handle_identity_req(
     send_mail: impl SendMail,
 ) -> RespResult<IdentityResult> {
     let account_id = submitted_identity.account_id;
+    ensure_identity_images_are_not_used_by_other_users(&submitted_identity, &op).await?;
     let identity_option = op
         .find_identity_by_account_id(account_id)
         .await
@@ -410,6 +446,33 @@ async fn handle_identity_req(
     Ok((StatusCode::OK, Json(IdentityResult {})))
 }
 
+async fn ensure_identity_images_are_not_used_by_other_users(
+    submitted_identity: &SubmittedIdentity,
+    op: &impl SubmitIdentityOperation,
+) -> Result<(), ErrResp> {
+    let account_id = submitted_identity.account_id;
+    let perceptual_hashes = std::iter::once(submitted_identity.identity_image1_perceptual_hash)
+        .chain(submitted_identity.identity_image2_perceptual_hash);
+    for perceptual_hash in perceptual_hashes {
+        let used = op
+            .check_if_identity_image_is_used_by_other_users(account_id, perceptual_hash)
+            .await?;
+        if used {
+            error!(
+                "identity image (perceptual hash: {:016x}) submitted by account id ({}) is used by other users",
+                perceptual_hash, account_id
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError {
+                    code: Code::DuplicateIdentityImage as u32,
+                }),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn check_update_identity_requirement(
     identity: &Identity,
     identity_to_update: &Identity,
@@ -521,7 +584,9 @@ struct SubmittedIdentity {
     account_id: i64,
     identity: Identity,
     identity_image1: FileNameAndBinary,
+    identity_image1_perceptual_hash: u64,
     identity_image2: Option<FileNameAndBinary>,
+    identity_image2_perceptual_hash: Option<u64>,
 }
 
 fn create_subject(id: i64, update: bool) -> String {
@@ -540,12 +605,27 @@ fn create_text(id: i64, update: bool) -> String {
     )
 }
 
+/// 他のユーザーが提出した身分証の画像のうち、知覚ハッシュのハミング距離が閾値以下のものを一件取得する
+const FIND_SIMILAR_IDENTITY_IMAGE_OF_OTHER_USERS_SQL: &str = r"
+SELECT image_file_name_without_ext
+FROM ccs_schema.identity_image_hash
+WHERE user_account_id <> $1
+  AND length(replace(((perceptual_hash # $2)::bit(64))::text, '0', '')) <= $3
+LIMIT 1;
+";
+
 #[async_trait]
 trait SubmitIdentityOperation {
     async fn find_identity_by_account_id(
         &self,
         account_id: i64,
     ) -> Result<Option<Identity>, ErrResp>;
+    /// 知覚ハッシュが近い身分証の画像を、他のユーザーが既に提出しているかどうか
+    async fn check_if_identity_image_is_used_by_other_users(
+        &self,
+        account_id: i64,
+        perceptual_hash: u64,
+    ) -> Result<bool, ErrResp>;
     async fn check_if_create_identity_req_already_exists(
         &self,
         account_id: i64,
@@ -614,6 +694,31 @@ impl SubmitIdentityOperation for SubmitIdentityOperationImpl {
         }))
     }
 
+    async fn check_if_identity_image_is_used_by_other_users(
+        &self,
+        account_id: i64,
+        perceptual_hash: u64,
+    ) -> Result<bool, ErrResp> {
+        // 知覚ハッシュは64ビットのビット列として扱うため、BIGINTとの相互変換ではビット表現を維持する
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            FIND_SIMILAR_IDENTITY_IMAGE_OF_OTHER_USERS_SQL,
+            [
+                account_id.into(),
+                (perceptual_hash as i64).into(),
+                (MAX_HAMMING_DISTANCE_FOR_DUPLICATE_IMAGE as i32).into(),
+            ],
+        );
+        let row = self.pool.query_one(stmt).await.map_err(|e| {
+            error!(
+                "failed to find identity_image_hash (user_account_id: {}, perceptual_hash: {:016x}): {}",
+                account_id, perceptual_hash, e
+            );
+            unexpected_err_resp()
+        })?;
+        Ok(row.is_some())
+    }
+
     async fn check_if_create_identity_req_already_exists(
         &self,
         account_id: i64,
@@ -642,11 +747,24 @@ impl SubmitIdentityOperation for SubmitIdentityOperationImpl {
         let image1_file_name_without_ext = identity_image1.0.clone();
         let (identity_image2_option, image2_file_name_without_ext) =
             clone_file_name_if_exists(submitted_identity.identity_image2);
+        let image_hashes = SubmitIdentityOperationImpl::pair_file_names_with_perceptual_hashes(
+            image1_file_name_without_ext.clone(),
+            submitted_identity.identity_image1_perceptual_hash,
+            image2_file_name_without_ext.clone(),
+            submitted_identity.identity_image2_perceptual_hash,
+        );
         let storage_client = self.storage_client.clone();
         let _ = self
             .pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
                 Box::pin(async move {
+                    SubmitIdentityOperationImpl::insert_identity_image_hashes(
+                        txn,
+                        account_id,
+                        image_hashes,
+                        current_date_time,
+                    )
+                    .await?;
                     let active_model =
                         SubmitIdentityOperationImpl::generate_create_identity_req_active_model(
                             account_id,
@@ -716,11 +834,24 @@ impl SubmitIdentityOperation for SubmitIdentityOperationImpl {
         let image1_file_name_without_ext = identity_image1.0.clone();
         let (identity_image2_option, image2_file_name_without_ext) =
             clone_file_name_if_exists(submitted_identity.identity_image2);
+        let image_hashes = SubmitIdentityOperationImpl::pair_file_names_with_perceptual_hashes(
+            image1_file_name_without_ext.clone(),
+            submitted_identity.identity_image1_perceptual_hash,
+            image2_file_name_without_ext.clone(),
+            submitted_identity.identity_image2_perceptual_hash,
+        );
         let storage_client = self.storage_client.clone();
         let _ = self
             .pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
                 Box::pin(async move {
+                    SubmitIdentityOperationImpl::insert_identity_image_hashes(
+                        txn,
+                        account_id,
+                        image_hashes,
+                        current_date_time,
+                    )
+                    .await?;
                     let active_model =
                         SubmitIdentityOperationImpl::generate_update_identity_req_active_model(
                             account_id,
@@ -764,6 +895,44 @@ impl SubmitIdentityOperation for SubmitIdentityOperationImpl {
 }
 
 impl SubmitIdentityOperationImpl {
+    fn pair_file_names_with_perceptual_hashes(
+        image1_file_name_without_ext: String,
+        image1_perceptual_hash: u64,
+        image2_file_name_without_ext: Option<String>,
+        image2_perceptual_hash: Option<u64>,
+    ) -> Vec<(String, u64)> {
+        let image2 = image2_file_name_without_ext.zip(image2_perceptual_hash);
+        std::iter::once((image1_file_name_without_ext, image1_perceptual_hash))
+            .chain(image2)
+            .collect()
+    }
+
+    async fn insert_identity_image_hashes(
+        txn: &DatabaseTransaction,
+        account_id: i64,
+        image_hashes: Vec<(String, u64)>,
+        current_date_time: DateTime<FixedOffset>,
+    ) -> Result<(), ErrRespStruct> {
+        for (image_file_name_without_ext, perceptual_hash) in image_hashes {
+            let active_model = identity_image_hash::ActiveModel {
+                image_file_name_without_ext: Set(image_file_name_without_ext.clone()),
+                user_account_id: Set(account_id),
+                perceptual_hash: Set(perceptual_hash as i64),
+                created_at: Set(current_date_time),
+            };
+            let _ = active_model.insert(txn).await.map_err(|e| {
+                error!(
+                    "failed to insert identity_image_hash (user_account_id: {}, image_file_name_without_ext: {}): {}",
+                    account_id, image_file_name_without_ext, e
+                );
+                ErrRespStruct {
+                    err_resp: unexpected_err_resp(),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
     fn generate_create_identity_req_active_model(
         account_id: i64,
         identity: Identity,
@@ -800,6 +969,8 @@ impl SubmitIdentityOperationImpl {
             image1_file_name_without_ext: Set(image1_file_name_without_ext),
             image2_file_name_without_ext: Set(image2_file_name_without_ext),
             requested_at: Set(current_date_time),
+            claimed_by: NotSet,
+            claimed_at: NotSet,
         })
     }
 
@@ -839,6 +1010,8 @@ impl SubmitIdentityOperationImpl {
             image1_file_name_without_ext: Set(image1_file_name_without_ext),
             image2_file_name_without_ext: Set(image2_file_name_without_ext),
             requested_at: Set(current_date_time),
+            claimed_by: NotSet,
+            claimed_at: NotSet,
         })
     }
 
@@ -992,19 +1165,24 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(identity, input.0);
-        let identity_image1_png = convert_jpeg_to_png(Bytes::from(identity_image1.into_inner()))
-            .expect("failed to get Ok");
-        assert_eq!(identity_image1_png.into_inner(), input.1.into_inner());
-        let identity_image2_png = convert_jpeg_to_png(Bytes::from(identity_image2.into_inner()))
-            .expect("failed to get Ok");
-        assert_eq!(
-            identity_image2_png.into_inner(),
-            input.2.expect("failed to get Ok").into_inner()
-        );
+        let identity_image1_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(identity_image1_png, input.1);
+        let identity_image2_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(Some(identity_image2_png), input.2);
     }
 
     fn create_dummy_identity(current_date: &NaiveDate) -> Identity {
@@ -1040,7 +1218,7 @@ mod tests {
     }
 
     fn create_dummy_identity_image1() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(128, 128);
+        let img = create_dummy_textured_image(640, 480);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
         img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
             .expect("failed to get Ok");
@@ -1048,13 +1226,34 @@ mod tests {
     }
 
     fn create_dummy_identity_image2() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(64, 64);
+        let img = create_dummy_textured_image(480, 640);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
         img.write_to(&mut bytes, ImageOutputFormat::Jpeg(90))
             .expect("failed to get Ok");
         bytes
     }
 
+    // 画像の品質の検査（解像度、ほぼ単色かどうか、ぼやけ具合）を通過する画像
+    fn create_dummy_textured_image(width: u32, height: u32) -> RgbImage {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let gradient = x * 150 / width;
+            let checker = if ((x / 16) + (y / 16)) % 2 == 0 {
+                100
+            } else {
+                0
+            };
+            let luma = (gradient + checker) as u8;
+            image::Rgb([luma, luma, luma])
+        })
+    }
+
+    fn create_max_file_size_in_bytes_for_jpeg(jpeg: usize) -> MaxFileSizeInBytes {
+        MaxFileSizeInBytes {
+            jpeg,
+            ..MAX_IDENTITY_FILE_SIZE_IN_BYTES
+        }
+    }
+
     fn create_dummy_identity_image_field(
         name: Option<String>,
         file_name: Option<String>,
@@ -1090,13 +1289,17 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(identity, input.0);
-        let identity_image1_png = convert_jpeg_to_png(Bytes::from(identity_image1.into_inner()))
-            .expect("failed to get Ok");
-        assert_eq!(identity_image1_png.into_inner(), input.1.into_inner());
+        let identity_image1_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(identity_image1_png, input.1);
         assert_eq!(None, input.2);
     }
 
@@ -1131,19 +1334,29 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, max_image_size_in_bytes, current_date).await;
+        let result = handle_multipart(
+            mock,
+            create_max_file_size_in_bytes_for_jpeg(max_image_size_in_bytes),
+            current_date,
+        )
+        .await;
 
         let input = result.expect("failed to get Ok");
         assert_eq!(identity, input.0);
-        let identity_image1_png = convert_jpeg_to_png(Bytes::from(identity_image1.into_inner()))
-            .expect("failed to get Ok");
-        assert_eq!(identity_image1_png.into_inner(), input.1.into_inner());
-        let identity_image2_png = convert_jpeg_to_png(Bytes::from(identity_image2.into_inner()))
-            .expect("failed to get Ok");
-        assert_eq!(
-            identity_image2_png.into_inner(),
-            input.2.expect("failed to get Ok").into_inner()
-        );
+        let identity_image1_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(identity_image1_png, input.1);
+        let identity_image2_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(Some(identity_image2_png), input.2);
     }
 
     #[tokio::test]
@@ -1177,7 +1390,12 @@ mod tests {
             invalid_multipart_form_data: true,
         };
 
-        let result = handle_multipart(mock, max_image_size_in_bytes, current_date).await;
+        let result = handle_multipart(
+            mock,
+            create_max_file_size_in_bytes_for_jpeg(max_image_size_in_bytes),
+            current_date,
+        )
+        .await;
 
         let resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
@@ -1213,7 +1431,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1229,7 +1447,7 @@ mod tests {
             .date();
         let mock = MultipartWrapperErrMock {};
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1264,7 +1482,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1297,7 +1515,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1326,7 +1544,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1361,7 +1579,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1369,7 +1587,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_multipart_fail_not_jpeg_extension() {
+    async fn handle_multipart_success_file_format_is_detected_regardless_of_file_name() {
         let current_date = JAPANESE_TIME_ZONE
             .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
             .unwrap()
@@ -1396,11 +1614,55 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
-        let err_resp = result.expect_err("failed to get Err");
-        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
-        assert_eq!(Code::NotJpegExtension as u32, err_resp.1.code);
+        let input = result.expect("failed to get Ok");
+        let identity_image2_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(identity_image2.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(Some(identity_image2_png), input.2);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_success_with_png_image() {
+        let current_date = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap()
+            .naive_local()
+            .date();
+        let identity = create_dummy_identity(&current_date);
+        let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
+        let mut identity_image1 = Cursor::new(Vec::with_capacity(50 * 1024));
+        create_dummy_textured_image(640, 480)
+            .write_to(&mut identity_image1, ImageOutputFormat::Png)
+            .expect("failed to get Ok");
+        let identity_image1_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image1")),
+            Some(String::from("test1.png")),
+            identity_image1.clone(),
+        );
+        let fields = vec![identity_field, identity_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
+
+        let input = result.expect("failed to get Ok");
+        assert_eq!(identity, input.0);
+        let identity_image1_png = convert_to_png_with_quality_check(
+            UploadedFileFormat::Png,
+            Bytes::from(identity_image1.into_inner()),
+        )
+        .await
+        .expect("failed to get Ok");
+        assert_eq!(identity_image1_png, input.1);
+        assert_eq!(None, input.2);
     }
 
     #[tokio::test]
@@ -1432,7 +1694,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1492,7 +1754,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1511,7 +1773,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handle_multipart_fail_invalid_jpeg_image() {
+    async fn handle_multipart_fail_unsupported_file_format() {
         let current_date = JAPANESE_TIME_ZONE
             .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
             .unwrap()
@@ -1519,17 +1781,16 @@ mod tests {
             .date();
         let identity = create_dummy_identity(&current_date);
         let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
-        let identity_image1 = create_dummy_identity_image1_png();
+        let identity_image1 = create_dummy_identity_image1();
         let identity_image1_field = create_dummy_identity_image_field(
             Some(String::from("identity-image1")),
-            /* 実体はpng画像だが、ファイル名で弾かれないようにjpegに設定 */
             Some(String::from("test1.jpeg")),
-            identity_image1.clone(),
+            identity_image1,
         );
         let identity_image2 = create_dummy_identity_image2_bmp();
         let identity_image2_field = create_dummy_identity_image_field(
             Some(String::from("identity-image2")),
-            /* 実体はbmp画像だが、ファイル名で弾かれないようにjpegに設定 */
+            /* 実体はbmp画像だが、ファイル名はjpegに設定 */
             Some(String::from("test2.jpeg")),
             identity_image2.clone(),
         );
@@ -1540,17 +1801,141 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::UnsupportedFileFormat as u32, err_resp.1.code);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_invalid_jpeg_image() {
+        let current_date = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap()
+            .naive_local()
+            .date();
+        let identity = create_dummy_identity(&current_date);
+        let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
+        let mut identity_image1 = create_dummy_identity_image1().into_inner();
+        /* 先頭（マジックナンバー）はjpegのまま、途中以降を切り捨てて壊れた画像にする */
+        identity_image1.truncate(32);
+        let identity_image1_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image1")),
+            Some(String::from("test1.jpeg")),
+            Cursor::new(identity_image1),
+        );
+        let fields = vec![identity_field, identity_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
         assert_eq!(Code::InvalidJpegImage as u32, err_resp.1.code);
     }
 
-    fn create_dummy_identity_image1_png() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(128, 128);
+    #[tokio::test]
+    async fn handle_multipart_fail_image_resolution_too_low() {
+        let current_date = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap()
+            .naive_local()
+            .date();
+        let identity = create_dummy_identity(&current_date);
+        let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
+        let identity_image1 = create_dummy_jpeg_image(&create_dummy_textured_image(320, 240));
+        let identity_image1_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image1")),
+            Some(String::from("test1.jpeg")),
+            identity_image1,
+        );
+        let fields = vec![identity_field, identity_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageResolutionTooLow as u32, err_resp.1.code);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_image_is_blank() {
+        let current_date = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap()
+            .naive_local()
+            .date();
+        let identity = create_dummy_identity(&current_date);
+        let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
+        let identity_image1 = create_dummy_identity_image1();
+        let identity_image1_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image1")),
+            Some(String::from("test1.jpeg")),
+            identity_image1,
+        );
+        let identity_image2 = create_dummy_jpeg_image(&ImageBuffer::new(640, 480));
+        let identity_image2_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image2")),
+            Some(String::from("test2.jpeg")),
+            identity_image2,
+        );
+        let fields = vec![identity_field, identity_image1_field, identity_image2_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageIsBlank as u32, err_resp.1.code);
+    }
+
+    #[tokio::test]
+    async fn handle_multipart_fail_image_too_blurry() {
+        let current_date = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap()
+            .naive_local()
+            .date();
+        let identity = create_dummy_identity(&current_date);
+        let identity_field = create_dummy_identity_field(Some(String::from("identity")), &identity);
+        let blurry_img = image::imageops::blur(&create_dummy_textured_image(640, 480), 8.0);
+        let identity_image1 = create_dummy_jpeg_image(&blurry_img);
+        let identity_image1_field = create_dummy_identity_image_field(
+            Some(String::from("identity-image1")),
+            Some(String::from("test1.jpeg")),
+            identity_image1,
+        );
+        let fields = vec![identity_field, identity_image1_field];
+        let mock = MultipartWrapperMock {
+            count: 0,
+            fields,
+            invalid_multipart_form_data: false,
+        };
+
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageTooBlurry as u32, err_resp.1.code);
+    }
+
+    fn create_dummy_jpeg_image(img: &RgbImage) -> Cursor<Vec<u8>> {
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
-        img.write_to(&mut bytes, ImageOutputFormat::Png)
+        img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
             .expect("failed to get Ok");
         bytes
     }
@@ -1595,7 +1980,12 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, max_image_size_in_bytes, current_date).await;
+        let result = handle_multipart(
+            mock,
+            create_max_file_size_in_bytes_for_jpeg(max_image_size_in_bytes),
+            current_date,
+        )
+        .await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1642,7 +2032,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1686,7 +2076,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1730,7 +2120,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1774,7 +2164,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1818,7 +2208,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1862,7 +2252,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1906,7 +2296,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1950,7 +2340,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -1994,7 +2384,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2038,7 +2428,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2082,7 +2472,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2126,7 +2516,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2170,7 +2560,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2214,7 +2604,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2258,7 +2648,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2302,7 +2692,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2346,7 +2736,7 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
@@ -2391,14 +2781,17 @@ mod tests {
             invalid_multipart_form_data: false,
         };
 
-        let result = handle_multipart(mock, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES, current_date).await;
+        let result = handle_multipart(mock, MAX_IDENTITY_FILE_SIZE_IN_BYTES, current_date).await;
 
         let err_resp = result.expect_err("failed to get Err");
         assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
         assert_eq!(Code::InvalidTelNumFormat as u32, err_resp.1.code);
     }
 
+    const IMAGE1_PERCEPTUAL_HASH: u64 = 0x0f0f_0f0f_0f0f_0f0f;
+
     struct SubmitIdentityOperationMock {
+        identity_image_used_by_other_users: bool,
         create_identity_req_exists: bool,
         update_identity_req_exists: bool,
         account_id: i64,
@@ -2417,6 +2810,19 @@ mod tests {
             Ok(self.identity_option.clone())
         }
 
+        async fn check_if_identity_image_is_used_by_other_users(
+            &self,
+            account_id: i64,
+            perceptual_hash: u64,
+        ) -> Result<bool, ErrResp> {
+            assert_eq!(self.account_id, account_id);
+            assert_eq!(
+                self.submitted_identity.identity_image1_perceptual_hash,
+                perceptual_hash
+            );
+            Ok(self.identity_image_used_by_other_users)
+        }
+
         async fn check_if_create_identity_req_already_exists(
             &self,
             account_id: i64,
@@ -2466,9 +2872,12 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: None,
             create_identity_req_exists: false,
             update_identity_req_exists: false,
@@ -2503,7 +2912,9 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let mut identity = submitted_identity.identity.clone();
         identity.telephone_number = String::from("08012345678");
@@ -2512,6 +2923,7 @@ mod tests {
             submitted_identity.identity.telephone_number
         );
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: Some(identity),
             create_identity_req_exists: false,
             update_identity_req_exists: false,
@@ -2546,9 +2958,12 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: None,
             create_identity_req_exists: true,
             update_identity_req_exists: false,
@@ -2583,7 +2998,9 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let mut identity = submitted_identity.identity.clone();
         identity.telephone_number = String::from("08012345678");
@@ -2592,6 +3009,7 @@ mod tests {
             submitted_identity.identity.telephone_number
         );
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: Some(identity),
             create_identity_req_exists: false,
             update_identity_req_exists: true,
@@ -2626,7 +3044,9 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let mut identity = submitted_identity.identity.clone();
         identity.date_of_birth = Ymd {
@@ -2644,6 +3064,7 @@ mod tests {
             submitted_identity.identity.telephone_number
         );
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: Some(identity),
             create_identity_req_exists: false,
             update_identity_req_exists: true,
@@ -2678,7 +3099,9 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let mut identity = submitted_identity.identity.clone();
         identity.first_name = String::from("次郎");
@@ -2689,6 +3112,7 @@ mod tests {
             submitted_identity.identity.telephone_number
         );
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: Some(identity),
             create_identity_req_exists: false,
             update_identity_req_exists: true,
@@ -2723,10 +3147,13 @@ mod tests {
             account_id,
             identity: create_dummy_identity(&current_date),
             identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
             identity_image2: None,
+            identity_image2_perceptual_hash: None,
         };
         let identity = submitted_identity.identity.clone();
         let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: false,
             identity_option: Some(identity),
             create_identity_req_exists: false,
             update_identity_req_exists: true,
@@ -2748,4 +3175,44 @@ mod tests {
         assert_eq!(StatusCode::BAD_REQUEST, resp.0);
         assert_eq!(Code::NoIdentityUpdated as u32, resp.1 .0.code);
     }
+
+    #[tokio::test]
+    async fn handle_identity_req_fail_duplicate_identity_image() {
+        let account_id = 1234;
+        let current_date_time = JAPANESE_TIME_ZONE
+            .with_ymd_and_hms(2022, 3, 7, 15, 30, 45)
+            .unwrap();
+        let current_date = current_date_time.naive_local().date();
+        let image1_file_name_without_ext = Uuid::new_v4().simple().to_string();
+        let submitted_identity = SubmittedIdentity {
+            account_id,
+            identity: create_dummy_identity(&current_date),
+            identity_image1: (image1_file_name_without_ext, create_dummy_identity_image1()),
+            identity_image1_perceptual_hash: IMAGE1_PERCEPTUAL_HASH,
+            identity_image2: None,
+            identity_image2_perceptual_hash: None,
+        };
+        let op = SubmitIdentityOperationMock {
+            identity_image_used_by_other_users: true,
+            identity_option: None,
+            create_identity_req_exists: false,
+            update_identity_req_exists: false,
+            account_id,
+            submitted_identity: submitted_identity.clone(),
+            current_date_time,
+        };
+        let send_mail_mock = SendMailMock::new(
+            ADMIN_EMAIL_ADDRESS.to_string(),
+            SYSTEM_EMAIL_ADDRESS.to_string(),
+            create_subject(account_id, op.identity_option.is_some()),
+            create_text(account_id, op.identity_option.is_some()),
+        );
+
+        let result =
+            handle_identity_req(submitted_identity, current_date_time, op, send_mail_mock).await;
+
+        let resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, resp.0);
+        assert_eq!(Code::DuplicateIdentityImage as u32, resp.1 .0.code);
+    }
 }
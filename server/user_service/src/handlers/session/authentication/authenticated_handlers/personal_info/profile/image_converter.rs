@@ -1,33 +1,347 @@
 // Copyright 2022 Ken Miura
 
+//! アップロードされたファイル（jpeg、png、heic画像またはPDF）をpng画像に変換するモジュール
+//!
+//! 画像ファイルの中のメタデータに悪意ある内容が含まれている場合が考えられるので、画像情報以外のメタデータを取り除く必要がある。
+//! メタデータを取り除くのに画像形式を変換するのが最も容易な実装のため、どの形式のファイルも一度デコードしてからpng画像に変換している。
+//!
+//! jpeg、png画像は[image] crateでデコードする。heic画像とPDFは[image] crateでデコードできないため、
+//! 外部コマンド（heic画像は[HEIF_CONVERT_COMMAND]、PDFは[PDFTOPPM_COMMAND]）でpng画像に変換してからデコードする。
+//! PDFは1ページのもののみ受け付ける（ページ数は[PDFINFO_COMMAND]で確認する）。
+
+use std::env::{split_paths, var_os};
+use std::io::Cursor;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
 use axum::Json;
 use axum::{body::Bytes, http::StatusCode};
 use common::{ApiError, ErrResp};
-use image::{ImageError, ImageFormat};
-use std::io::Cursor;
+use image::{DynamicImage, ImageError, ImageFormat};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
 use tracing::error;
+use uuid::Uuid;
 
 use crate::err::{unexpected_err_resp, Code};
 
-/// jpeg画像をpng画像に変換する<br>
-/// <br>
-/// 画像ファイルの中のメタデータに悪意ある内容が含まれている場合が考えられるので、画像情報以外のメタデータを取り除く必要がある。
-/// メタデータを取り除くのに画像形式を変換するのが最も容易な実装のため、画像形式の変換を行っている。
-pub(super) fn convert_jpeg_to_png(data: Bytes) -> Result<Cursor<Vec<u8>>, ErrResp> {
-    let img = image::io::Reader::with_format(Cursor::new(data), ImageFormat::Jpeg)
+/// heic画像をpng画像に変換するコマンド（libheif-examplesに含まれる）
+const HEIF_CONVERT_COMMAND: &str = "heif-convert";
+/// PDFのページをpng画像に変換するコマンド（poppler-utilsに含まれる）
+const PDFTOPPM_COMMAND: &str = "pdftoppm";
+/// PDFのページ数を確認するコマンド（poppler-utilsに含まれる）
+const PDFINFO_COMMAND: &str = "pdfinfo";
+/// ファイルの変換に必要な外部コマンド
+const EXTERNAL_COMMANDS: [&str; 3] = [HEIF_CONVERT_COMMAND, PDFTOPPM_COMMAND, PDFINFO_COMMAND];
+/// 外部コマンドの実行を待つ最大の時間
+const EXTERNAL_COMMAND_TIMEOUT_IN_SECONDS: u64 = 30;
+/// PDFのページをpng画像に変換する際の長辺のピクセル数
+const PDF_PAGE_LONG_SIDE_IN_PIXELS: u32 = 2048;
+
+/// ファイルの変換に必要な外部コマンドが全てPATH上に存在するか確認する
+///
+/// 存在しないコマンドがある場合、そのコマンド名の一覧をErrとして返す。
+/// 変換を要求されてから失敗することのないよう、サーバーの起動時に確認する。
+pub(crate) fn check_external_commands() -> Result<(), Vec<String>> {
+    let not_found_commands = EXTERNAL_COMMANDS
+        .iter()
+        .filter(|command| !command_exists(command))
+        .map(|command| command.to_string())
+        .collect::<Vec<String>>();
+    if !not_found_commands.is_empty() {
+        return Err(not_found_commands);
+    }
+    Ok(())
+}
+
+fn command_exists(command: &str) -> bool {
+    let Some(paths) = var_os("PATH") else {
+        return false;
+    };
+    split_paths(&paths).any(|dir| is_executable_file(&dir.join(command)))
+}
+
+fn is_executable_file(path: &Path) -> bool {
+    match path.metadata() {
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+/// アップロードを受け付けるファイルの形式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum UploadedFileFormat {
+    Jpeg,
+    Png,
+    Heic,
+    Pdf,
+}
+
+/// ファイルの形式ごとのファイルサイズの上限（バイト単位）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MaxFileSizeInBytes {
+    pub(crate) jpeg: usize,
+    pub(crate) png: usize,
+    pub(crate) heic: usize,
+    pub(crate) pdf: usize,
+}
+
+impl MaxFileSizeInBytes {
+    pub(super) fn of(&self, format: UploadedFileFormat) -> usize {
+        match format {
+            UploadedFileFormat::Jpeg => self.jpeg,
+            UploadedFileFormat::Png => self.png,
+            UploadedFileFormat::Heic => self.heic,
+            UploadedFileFormat::Pdf => self.pdf,
+        }
+    }
+
+    /// 全ての形式の中で最も大きいファイルサイズの上限を返す（リクエストボディのサイズの上限の算出に利用する）
+    pub(crate) fn largest(&self) -> usize {
+        self.jpeg.max(self.png).max(self.heic).max(self.pdf)
+    }
+}
+
+/// ファイルの先頭のバイト列（マジックナンバー）からファイルの形式を判定する
+///
+/// ファイル名（拡張子）はユーザーが自由に変更できるため、形式の判定には利用しない。
+pub(super) fn detect_file_format(data: &[u8]) -> Result<UploadedFileFormat, ErrResp> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Ok(UploadedFileFormat::Jpeg);
+    }
+    if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Ok(UploadedFileFormat::Png);
+    }
+    if data.starts_with(b"%PDF-") {
+        return Ok(UploadedFileFormat::Pdf);
+    }
+    if is_heic(data) {
+        return Ok(UploadedFileFormat::Heic);
+    }
+    error!(
+        "unsupported file format (first bytes: {:02x?})",
+        &data[..data.len().min(12)]
+    );
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(ApiError {
+            code: Code::UnsupportedFileFormat as u32,
+        }),
+    ))
+}
+
+/// ISO BMFFのftypボックスのブランドがHEIF (HEVC) を示すかどうか
+fn is_heic(data: &[u8]) -> bool {
+    const HEIC_BRANDS: [&[u8]; 6] = [b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis"];
+    if data.len() < 12 || &data[4..8] != b"ftyp" {
+        return false;
+    }
+    let major_brand = &data[8..12];
+    HEIC_BRANDS.contains(&major_brand)
+}
+
+/// アップロードされたファイルをデコードする
+///
+/// PDFの場合、1ページのもののみを受け付ける（アップロードする画像1つにつき、1ページとして扱う）。
+/// 2ページ以上のPDFの場合、2ページ目以降を黙って捨てることのないよう、エラーとして返す。
+pub(super) async fn decode_uploaded_file(
+    format: UploadedFileFormat,
+    data: Bytes,
+) -> Result<DynamicImage, ErrResp> {
+    match format {
+        UploadedFileFormat::Jpeg => decode_image(data, ImageFormat::Jpeg, Code::InvalidJpegImage),
+        UploadedFileFormat::Png => decode_image(data, ImageFormat::Png, Code::InvalidPngImage),
+        UploadedFileFormat::Heic => {
+            let png = convert_heic_to_png(data).await?;
+            decode_converted_png(png)
+        }
+        UploadedFileFormat::Pdf => {
+            ensure_pdf_has_single_page(data.clone()).await?;
+            let png = convert_single_page_pdf_to_png(data).await?;
+            decode_converted_png(png)
+        }
+    }
+}
+
+fn decode_image(data: Bytes, format: ImageFormat, code: Code) -> Result<DynamicImage, ErrResp> {
+    image::io::Reader::with_format(Cursor::new(data), format)
         .decode()
         .map_err(|e| {
-            error!("failed to decode jpeg image: {}", e);
+            error!("failed to decode image ({:?}): {}", format, e);
             match e {
-                ImageError::Decoding(_) => (
+                // メモリ上のデータを読み込むため、IoErrorはデータが途中で途切れている（ファイルが壊れている）場合にのみ発生する
+                ImageError::Decoding(_) | ImageError::IoError(_) => (
                     StatusCode::BAD_REQUEST,
-                    Json(ApiError {
-                        code: Code::InvalidJpegImage as u32,
-                    }),
+                    Json(ApiError { code: code as u32 }),
                 ),
                 _ => unexpected_err_resp(),
             }
+        })
+}
+
+/// 外部コマンドで変換したpng画像をデコードする（外部コマンドが正常に終了している以上、デコードできない場合は想定外のエラーとして扱う）
+fn decode_converted_png(png: Vec<u8>) -> Result<DynamicImage, ErrResp> {
+    image::load_from_memory_with_format(&png, ImageFormat::Png).map_err(|e| {
+        error!("failed to decode converted png image: {}", e);
+        unexpected_err_resp()
+    })
+}
+
+async fn convert_heic_to_png(data: Bytes) -> Result<Vec<u8>, ErrResp> {
+    // heif-convertは標準入出力を扱えないため、一時ファイルを介して変換する
+    let file_name = Uuid::new_v4().simple().to_string();
+    let input_path = std::env::temp_dir().join(format!("{}.heic", file_name));
+    let output_path = std::env::temp_dir().join(format!("{}.png", file_name));
+    let result = convert_heic_file_to_png(data, &input_path, &output_path).await;
+    remove_temp_file(&input_path).await;
+    remove_temp_file(&output_path).await;
+    result
+}
+
+async fn convert_heic_file_to_png(
+    data: Bytes,
+    input_path: &PathBuf,
+    output_path: &PathBuf,
+) -> Result<Vec<u8>, ErrResp> {
+    tokio::fs::write(input_path, data).await.map_err(|e| {
+        error!("failed to write heic image ({:?}): {}", input_path, e);
+        unexpected_err_resp()
+    })?;
+    let mut command = Command::new(HEIF_CONVERT_COMMAND);
+    command.arg(input_path).arg(output_path);
+    let _ = run_external_command(command, None, Code::InvalidHeicImage).await?;
+    tokio::fs::read(output_path).await.map_err(|e| {
+        error!(
+            "failed to read converted png image ({:?}): {}",
+            output_path, e
+        );
+        unexpected_err_resp()
+    })
+}
+
+async fn remove_temp_file(path: &PathBuf) {
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            error!("failed to remove temp file ({:?}): {}", path, e);
+        }
+    }
+}
+
+async fn ensure_pdf_has_single_page(data: Bytes) -> Result<(), ErrResp> {
+    let num_of_pages = count_pages_of_pdf(data).await?;
+    if num_of_pages != 1 {
+        error!(
+            "pdf has {} pages (only single page pdf is accepted)",
+            num_of_pages
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError {
+                code: Code::MultiPagePdf as u32,
+            }),
+        ));
+    }
+    Ok(())
+}
+
+async fn count_pages_of_pdf(data: Bytes) -> Result<u32, ErrResp> {
+    // ファイル名に"-"を指定した場合、pdfinfoは標準入力から読み込む
+    let mut command = Command::new(PDFINFO_COMMAND);
+    command.arg("-");
+    let output = run_external_command(command, Some(data), Code::InvalidPdf).await?;
+    let output = String::from_utf8_lossy(&output);
+    parse_num_of_pages(&output).ok_or_else(|| {
+        error!(
+            "failed to find number of pages in output of pdfinfo: {}",
+            output
+        );
+        unexpected_err_resp()
+    })
+}
+
+/// pdfinfoの出力（"Pages:           2"のような行を含む）からページ数を取り出す
+fn parse_num_of_pages(pdfinfo_output: &str) -> Option<u32> {
+    pdfinfo_output.lines().find_map(|line| {
+        line.strip_prefix("Pages:")
+            .and_then(|num_of_pages| num_of_pages.trim().parse::<u32>().ok())
+    })
+}
+
+async fn convert_single_page_pdf_to_png(data: Bytes) -> Result<Vec<u8>, ErrResp> {
+    // 出力先を指定しない場合、pdftoppmは標準出力に出力する
+    let mut command = Command::new(PDFTOPPM_COMMAND);
+    command
+        .args(["-png", "-singlefile", "-f", "1", "-l", "1", "-scale-to"])
+        .arg(PDF_PAGE_LONG_SIDE_IN_PIXELS.to_string())
+        .arg("-");
+    run_external_command(command, Some(data), Code::InvalidPdf).await
+}
+
+/// 外部コマンドを実行し、標準出力の内容を返す
+///
+/// コマンドが異常終了した場合、入力されたファイルが不正であるとみなし、codeをエラーコードとして返す。
+async fn run_external_command(
+    mut command: Command,
+    stdin_data: Option<Bytes>,
+    code: Code,
+) -> Result<Vec<u8>, ErrResp> {
+    let mut child = command
+        .stdin(if stdin_data.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            error!("failed to spawn external command ({:?}): {}", command, e);
+            unexpected_err_resp()
         })?;
+    if let (Some(data), Some(mut stdin)) = (stdin_data, child.stdin.take()) {
+        // 標準出力を読み取る前に全てを書き込もうとすると、パイプのバッファが溢れて処理が進まなくなる可能性があるため、別タスクで書き込む
+        tokio::spawn(async move {
+            if let Err(e) = stdin.write_all(&data).await {
+                error!("failed to write data to stdin of external command: {}", e);
+            }
+        });
+    }
+    let output = tokio::time::timeout(
+        Duration::from_secs(EXTERNAL_COMMAND_TIMEOUT_IN_SECONDS),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| {
+        error!(
+            "external command ({:?}) did not finish within {} seconds",
+            command, EXTERNAL_COMMAND_TIMEOUT_IN_SECONDS
+        );
+        unexpected_err_resp()
+    })?
+    .map_err(|e| {
+        error!("failed to wait external command ({:?}): {}", command, e);
+        unexpected_err_resp()
+    })?;
+    if !output.status.success() {
+        error!(
+            "external command ({:?}) failed (status: {}, stderr: {})",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError { code: code as u32 }),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// デコード済の画像をpng画像にエンコードする（画像情報以外のメタデータは含まれない）
+pub(super) fn encode_to_png(img: &DynamicImage) -> Result<Cursor<Vec<u8>>, ErrResp> {
     let mut bytes = Cursor::new(vec![]);
     img.write_to(&mut bytes, image::ImageOutputFormat::Png)
         .map_err(|e| {
@@ -45,50 +359,297 @@ mod tests {
     use super::*;
 
     #[test]
-    fn convert_jpeg_to_png_returns_ok_if_convert_is_success() {
-        let jpeg_image = create_dummy_jpeg_image();
-        let expected_image = create_converted_image(jpeg_image.clone().into_inner());
+    fn detect_file_format_returns_jpeg_if_jpeg_image_is_passed() {
+        let jpeg_image = create_dummy_image(ImageOutputFormat::Jpeg(85));
+
+        let result = detect_file_format(jpeg_image.get_ref());
+
+        assert_eq!(UploadedFileFormat::Jpeg, result.expect("failed to get Ok"));
+    }
+
+    #[test]
+    fn detect_file_format_returns_png_if_png_image_is_passed() {
+        let png_image = create_dummy_image(ImageOutputFormat::Png);
+
+        let result = detect_file_format(png_image.get_ref());
+
+        assert_eq!(UploadedFileFormat::Png, result.expect("failed to get Ok"));
+    }
+
+    #[test]
+    fn detect_file_format_returns_pdf_if_pdf_is_passed() {
+        let pdf = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n1 0 obj\n";
+
+        let result = detect_file_format(pdf);
+
+        assert_eq!(UploadedFileFormat::Pdf, result.expect("failed to get Ok"));
+    }
+
+    #[test]
+    fn detect_file_format_returns_heic_if_heic_image_is_passed() {
+        let heic = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+
+        let result = detect_file_format(heic);
+
+        assert_eq!(UploadedFileFormat::Heic, result.expect("failed to get Ok"));
+    }
+
+    #[test]
+    fn detect_file_format_returns_err_if_avif_image_is_passed() {
+        // heic画像と同じISO BMFFだが、サポートしていない形式
+        let avif = b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00avifmif1miaf";
+
+        let result = detect_file_format(avif);
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::UnsupportedFileFormat as u32, result.1.code);
+    }
+
+    #[test]
+    fn detect_file_format_returns_err_if_bmp_image_is_passed() {
+        let bmp_image = create_dummy_image(ImageOutputFormat::Bmp);
 
-        let result = convert_jpeg_to_png(Bytes::from(jpeg_image.into_inner()));
+        let result = detect_file_format(bmp_image.get_ref());
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::UnsupportedFileFormat as u32, result.1.code);
+    }
+
+    #[test]
+    fn detect_file_format_returns_err_if_empty_data_is_passed() {
+        let result = detect_file_format(&[]);
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::UnsupportedFileFormat as u32, result.1.code);
+    }
+
+    #[test]
+    fn max_file_size_in_bytes_returns_limit_of_each_format() {
+        let max_file_size_in_bytes = MaxFileSizeInBytes {
+            jpeg: 1,
+            png: 4,
+            heic: 2,
+            pdf: 3,
+        };
+
+        assert_eq!(1, max_file_size_in_bytes.of(UploadedFileFormat::Jpeg));
+        assert_eq!(4, max_file_size_in_bytes.of(UploadedFileFormat::Png));
+        assert_eq!(2, max_file_size_in_bytes.of(UploadedFileFormat::Heic));
+        assert_eq!(3, max_file_size_in_bytes.of(UploadedFileFormat::Pdf));
+        assert_eq!(4, max_file_size_in_bytes.largest());
+    }
+
+    #[tokio::test]
+    async fn decode_uploaded_file_returns_ok_if_jpeg_image_is_passed() {
+        let jpeg_image = create_dummy_image(ImageOutputFormat::Jpeg(85));
+        let expected_image =
+            image::load_from_memory_with_format(jpeg_image.get_ref(), ImageFormat::Jpeg)
+                .expect("failed to get Ok");
+
+        let result = decode_uploaded_file(
+            UploadedFileFormat::Jpeg,
+            Bytes::from(jpeg_image.into_inner()),
+        )
+        .await;
 
         let result_image = result.expect("failed to get Ok");
         assert_eq!(expected_image, result_image);
     }
 
-    #[test]
-    fn convert_jpeg_to_png_returns_err_if_format_other_than_jpg_is_passed() {
-        let png_image = create_dummy_png_image();
+    #[tokio::test]
+    async fn decode_uploaded_file_returns_ok_if_png_image_is_passed() {
+        let png_image = create_dummy_image(ImageOutputFormat::Png);
+        let expected_image =
+            image::load_from_memory_with_format(png_image.get_ref(), ImageFormat::Png)
+                .expect("failed to get Ok");
 
-        let result = convert_jpeg_to_png(Bytes::from(png_image.into_inner()));
+        let result =
+            decode_uploaded_file(UploadedFileFormat::Png, Bytes::from(png_image.into_inner()))
+                .await;
+
+        let result_image = result.expect("failed to get Ok");
+        assert_eq!(expected_image, result_image);
+    }
+
+    #[tokio::test]
+    async fn decode_uploaded_file_returns_err_if_broken_jpeg_image_is_passed() {
+        let mut jpeg_image = create_dummy_image(ImageOutputFormat::Jpeg(85)).into_inner();
+        jpeg_image.truncate(16);
+
+        let result = decode_uploaded_file(UploadedFileFormat::Jpeg, Bytes::from(jpeg_image)).await;
 
         let result = result.expect_err("failed to get Err");
-        assert_eq!(result.0, StatusCode::BAD_REQUEST);
-        assert_eq!(result.1.code, Code::InvalidJpegImage as u32);
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::InvalidJpegImage as u32, result.1.code);
     }
 
-    fn create_dummy_jpeg_image() -> Cursor<Vec<u8>> {
-        let img: RgbImage = ImageBuffer::new(128, 128);
-        let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
-        img.write_to(&mut bytes, ImageOutputFormat::Jpeg(85))
+    #[tokio::test]
+    async fn decode_uploaded_file_returns_err_if_broken_png_image_is_passed() {
+        let mut png_image = create_dummy_image(ImageOutputFormat::Png).into_inner();
+        png_image.truncate(16);
+
+        let result = decode_uploaded_file(UploadedFileFormat::Png, Bytes::from(png_image)).await;
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::InvalidPngImage as u32, result.1.code);
+    }
+
+    #[test]
+    fn encode_to_png_returns_png_image() {
+        let jpeg_image = create_dummy_image(ImageOutputFormat::Jpeg(85));
+        let img = image::load_from_memory_with_format(jpeg_image.get_ref(), ImageFormat::Jpeg)
             .expect("failed to get Ok");
-        bytes
+
+        let result = encode_to_png(&img);
+
+        let png_image = result.expect("failed to get Ok");
+        assert_eq!(
+            UploadedFileFormat::Png,
+            detect_file_format(png_image.get_ref()).expect("failed to get Ok")
+        );
+    }
+
+    #[test]
+    fn command_exists_returns_true_if_command_is_in_path() {
+        assert!(command_exists("sh"));
+    }
+
+    #[test]
+    fn command_exists_returns_false_if_command_is_not_in_path() {
+        assert!(!command_exists("command-that-does-not-exist"));
     }
 
-    fn create_dummy_png_image() -> Cursor<Vec<u8>> {
+    #[test]
+    fn is_executable_file_returns_false_if_file_is_not_executable() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+
+        assert!(!is_executable_file(&path));
+    }
+
+    #[test]
+    fn is_executable_file_returns_false_if_directory_is_passed() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert!(!is_executable_file(path));
+    }
+
+    #[test]
+    fn parse_num_of_pages_returns_num_of_pages_if_pages_line_exists() {
+        let output = "Producer:        test\nPages:           2\nEncrypted:       no\n";
+
+        let result = parse_num_of_pages(output);
+
+        assert_eq!(Some(2), result);
+    }
+
+    #[test]
+    fn parse_num_of_pages_returns_none_if_pages_line_does_not_exist() {
+        let output = "Producer:        test\nEncrypted:       no\n";
+
+        let result = parse_num_of_pages(output);
+
+        assert_eq!(None, result);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn decode_uploaded_file_returns_ok_if_single_page_pdf_is_passed() {
+        assert_external_command_exists(PDFINFO_COMMAND);
+        assert_external_command_exists(PDFTOPPM_COMMAND);
+        // 横長（200pt x 100pt）の1ページのみのPDF
+        let pdf = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/one_page.pdf"
+        ));
+
+        let result = decode_uploaded_file(UploadedFileFormat::Pdf, Bytes::from_static(pdf)).await;
+
+        let result_image = result.expect("failed to get Ok");
+        assert_eq!(PDF_PAGE_LONG_SIDE_IN_PIXELS, result_image.width());
+        assert_eq!(PDF_PAGE_LONG_SIDE_IN_PIXELS / 2, result_image.height());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn decode_uploaded_file_returns_err_if_multi_page_pdf_is_passed() {
+        assert_external_command_exists(PDFINFO_COMMAND);
+        // 1ページ目が横長（200pt x 100pt）、2ページ目が縦長（100pt x 200pt）のPDF
+        let pdf = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/two_pages.pdf"
+        ));
+
+        let result = decode_uploaded_file(UploadedFileFormat::Pdf, Bytes::from_static(pdf)).await;
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::MultiPagePdf as u32, result.1.code);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn decode_uploaded_file_returns_err_if_broken_pdf_is_passed() {
+        assert_external_command_exists(PDFINFO_COMMAND);
+        let pdf = b"%PDF-1.4\nthis is not a pdf\n";
+
+        let result = decode_uploaded_file(UploadedFileFormat::Pdf, Bytes::from_static(pdf)).await;
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::InvalidPdf as u32, result.1.code);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn decode_uploaded_file_returns_err_if_broken_heic_image_is_passed() {
+        assert_external_command_exists(HEIF_CONVERT_COMMAND);
+        let heic = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+
+        let result = decode_uploaded_file(UploadedFileFormat::Heic, Bytes::from_static(heic)).await;
+
+        let result = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, result.0);
+        assert_eq!(Code::InvalidHeicImage as u32, result.1.code);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn decode_uploaded_file_returns_ok_if_heic_image_is_passed() {
+        assert_external_command_exists(HEIF_CONVERT_COMMAND);
+        // 左半分が暗く（輝度40）、右半分が明るい（輝度200）16x16のheic画像（無圧縮（PCM）で符号化している）
+        let heic = include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/left_dark_right_bright.heic"
+        ));
+
+        let result = decode_uploaded_file(UploadedFileFormat::Heic, Bytes::from_static(heic)).await;
+
+        let result_image = result.expect("failed to get Ok").to_luma8();
+        assert_eq!(16, result_image.width());
+        assert_eq!(16, result_image.height());
+        assert!(result_image.get_pixel(0, 0)[0] < result_image.get_pixel(15, 0)[0]);
+        assert!(result_image.get_pixel(0, 15)[0] < result_image.get_pixel(15, 15)[0]);
+    }
+
+    /// 外部コマンドを利用するテストは、外部コマンドがインストールされた環境（開発用コンテナ（.devcontainer/Dockerfile））でのみ動作するため、
+    /// `#[ignore]`を付与し、`cargo test -p user_service image_converter -- --ignored`で実行する（CIでも同じコマンドで実行している）。
+    /// 外部コマンドが見つからない場合、テストを成功扱いにしないよう失敗させる
+    fn assert_external_command_exists(command: &str) {
+        assert!(
+            command_exists(command),
+            "\"{}\" is not found (run tests using external commands in the development container)",
+            command
+        );
+    }
+
+    fn create_dummy_image(format: ImageOutputFormat) -> Cursor<Vec<u8>> {
         let img: RgbImage = ImageBuffer::new(128, 128);
         let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
-        img.write_to(&mut bytes, ImageOutputFormat::Png)
-            .expect("failed to get Ok");
+        img.write_to(&mut bytes, format).expect("failed to get Ok");
         bytes
     }
-
-    fn create_converted_image(jpg_img: Vec<u8>) -> Cursor<Vec<u8>> {
-        let img = image::io::Reader::with_format(Cursor::new(jpg_img), ImageFormat::Jpeg)
-            .decode()
-            .expect("failed to get Ok");
-        let mut png_img = Cursor::new(vec![]);
-        img.write_to(&mut png_img, image::ImageOutputFormat::Png)
-            .expect("failed to get Ok");
-        png_img
-    }
 }
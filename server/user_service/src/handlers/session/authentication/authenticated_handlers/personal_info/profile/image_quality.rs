@@ -0,0 +1,249 @@
+// Copyright 2023 Ken Miura
+
+//! アップロードされた画像が、管理者の確認に耐えうる品質かどうかを検査するモジュール
+//!
+//! 管理者が確認する前にユーザー自身が画像を撮り直せるように、アップロード時に外部サービスを利用せず（オフラインで）検査する。
+
+use axum::http::StatusCode;
+use axum::Json;
+use common::perceptual_hash::calculate_perceptual_hash;
+use common::{ApiError, ErrResp};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage};
+use tracing::error;
+
+use crate::err::Code;
+
+/// 画像の短辺の最小ピクセル数
+pub(super) const MIN_SHORT_SIDE_IN_PIXELS: u32 = 480;
+/// 画像の長辺の最小ピクセル数
+pub(super) const MIN_LONG_SIDE_IN_PIXELS: u32 = 640;
+/// 輝度の標準偏差の最小値。これ未満の画像はほぼ単色（白紙や真っ黒な画像など）とみなす
+const MIN_LUMA_STANDARD_DEVIATION: f64 = 10.0;
+/// ラプラシアンの分散の最小値。これ未満の画像はぼやけているとみなす
+const MIN_LAPLACIAN_VARIANCE: f64 = 100.0;
+/// ぼやけ具合を算出する前に、長辺がこのピクセル数を超える画像は縮小する
+///
+/// 解像度によってラプラシアンの分散が変わらないようにするため、また、計算量を抑えるために縮小する
+const LONG_SIDE_IN_PIXELS_FOR_BLUR_DETECTION: u32 = 1024;
+/// 知覚ハッシュのハミング距離がこの値以下の画像は同じ画像とみなす
+pub(super) const MAX_HAMMING_DISTANCE_FOR_DUPLICATE_IMAGE: u32 = 5;
+
+/// 画像の品質を検査し、問題がなければ画像の知覚ハッシュ (dHash) を返す
+///
+/// 検査は解像度、ほぼ単色かどうか、ぼやけ具合の順に行い、最初に見つかった問題に対応するエラーを返す。
+pub(super) fn check_image_quality(img: &DynamicImage) -> Result<u64, ErrResp> {
+    let (width, height) = (img.width(), img.height());
+    if width.min(height) < MIN_SHORT_SIDE_IN_PIXELS || width.max(height) < MIN_LONG_SIDE_IN_PIXELS {
+        error!(
+            "image resolution is too low (width: {}, height: {}, min short side: {}, min long side: {})",
+            width, height, MIN_SHORT_SIDE_IN_PIXELS, MIN_LONG_SIDE_IN_PIXELS
+        );
+        return Err(create_bad_request_err(Code::ImageResolutionTooLow));
+    }
+    let gray_img = img.to_luma8();
+    let standard_deviation = calculate_luma_standard_deviation(&gray_img);
+    if standard_deviation < MIN_LUMA_STANDARD_DEVIATION {
+        error!(
+            "image is blank (luma standard deviation: {}, min luma standard deviation: {})",
+            standard_deviation, MIN_LUMA_STANDARD_DEVIATION
+        );
+        return Err(create_bad_request_err(Code::ImageIsBlank));
+    }
+    let laplacian_variance = calculate_laplacian_variance(&shrink_for_blur_detection(&gray_img));
+    if laplacian_variance < MIN_LAPLACIAN_VARIANCE {
+        error!(
+            "image is too blurry (laplacian variance: {}, min laplacian variance: {})",
+            laplacian_variance, MIN_LAPLACIAN_VARIANCE
+        );
+        return Err(create_bad_request_err(Code::ImageTooBlurry));
+    }
+    Ok(calculate_perceptual_hash(&gray_img))
+}
+
+fn create_bad_request_err(code: Code) -> ErrResp {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ApiError { code: code as u32 }),
+    )
+}
+
+fn calculate_luma_standard_deviation(gray_img: &GrayImage) -> f64 {
+    let values = gray_img.pixels().map(|p| p.0[0] as f64);
+    calculate_variance(values).sqrt()
+}
+
+fn shrink_for_blur_detection(gray_img: &GrayImage) -> GrayImage {
+    let (width, height) = gray_img.dimensions();
+    let long_side = width.max(height);
+    if long_side <= LONG_SIDE_IN_PIXELS_FOR_BLUR_DETECTION {
+        return gray_img.clone();
+    }
+    let new_width =
+        ((width as u64 * LONG_SIDE_IN_PIXELS_FOR_BLUR_DETECTION as u64) / long_side as u64) as u32;
+    let new_height =
+        ((height as u64 * LONG_SIDE_IN_PIXELS_FOR_BLUR_DETECTION as u64) / long_side as u64) as u32;
+    imageops::resize(
+        gray_img,
+        new_width.max(1),
+        new_height.max(1),
+        FilterType::Triangle,
+    )
+}
+
+/// 4近傍のラプラシアンフィルタを適用した結果の分散を返す（値が小さいほど輪郭がぼやけている）
+fn calculate_laplacian_variance(gray_img: &GrayImage) -> f64 {
+    let (width, height) = gray_img.dimensions();
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+    let luma = |x: u32, y: u32| gray_img.get_pixel(x, y).0[0] as f64;
+    let values = (1..height - 1).flat_map(|y| {
+        (1..width - 1).map(move |x| {
+            luma(x, y - 1) + luma(x - 1, y) + luma(x + 1, y) + luma(x, y + 1) - 4.0 * luma(x, y)
+        })
+    });
+    calculate_variance(values)
+}
+
+fn calculate_variance(values: impl Iterator<Item = f64>) -> f64 {
+    let (count, sum, sum_of_squares) = values.fold((0_u64, 0.0, 0.0), |(c, s, ss), v| {
+        (c + 1, s + v, ss + v * v)
+    });
+    if count == 0 {
+        return 0.0;
+    }
+    let mean = sum / count as f64;
+    (sum_of_squares / count as f64 - mean * mean).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use image::{ImageBuffer, ImageFormat, ImageOutputFormat, RgbImage};
+
+    use super::*;
+
+    fn create_dummy_image(width: u32, height: u32, horizontal_gradient: bool) -> RgbImage {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let gradient = if horizontal_gradient {
+                x * 150 / width
+            } else {
+                y * 150 / height
+            };
+            let checker = if ((x / 16) + (y / 16)) % 2 == 0 {
+                100
+            } else {
+                0
+            };
+            let luma = (gradient + checker) as u8;
+            image::Rgb([luma, luma, luma])
+        })
+    }
+
+    fn encode_to_jpeg(img: &RgbImage, quality: u8) -> DynamicImage {
+        let mut bytes = Cursor::new(Vec::with_capacity(50 * 1024));
+        img.write_to(&mut bytes, ImageOutputFormat::Jpeg(quality))
+            .expect("failed to get Ok");
+        image::load_from_memory_with_format(bytes.get_ref(), ImageFormat::Jpeg)
+            .expect("failed to get Ok")
+    }
+
+    #[test]
+    fn check_image_quality_returns_ok_if_image_has_enough_quality() {
+        let img = encode_to_jpeg(&create_dummy_image(640, 480, true), 85);
+
+        let result = check_image_quality(&img);
+
+        result.expect("failed to get Ok");
+    }
+
+    #[test]
+    fn check_image_quality_returns_ok_if_portrait_image_has_enough_quality() {
+        let img = encode_to_jpeg(&create_dummy_image(480, 640, true), 85);
+
+        let result = check_image_quality(&img);
+
+        result.expect("failed to get Ok");
+    }
+
+    #[test]
+    fn check_image_quality_returns_err_if_short_side_is_less_than_min() {
+        let img = encode_to_jpeg(&create_dummy_image(640, 479, true), 85);
+
+        let result = check_image_quality(&img);
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageResolutionTooLow as u32, err_resp.1.code);
+    }
+
+    #[test]
+    fn check_image_quality_returns_err_if_long_side_is_less_than_min() {
+        let img = encode_to_jpeg(&create_dummy_image(639, 600, true), 85);
+
+        let result = check_image_quality(&img);
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageResolutionTooLow as u32, err_resp.1.code);
+    }
+
+    #[test]
+    fn check_image_quality_returns_err_if_image_is_blank() {
+        let white_img = ImageBuffer::from_pixel(640, 480, image::Rgb([255, 255, 255]));
+        let img = encode_to_jpeg(&white_img, 85);
+
+        let result = check_image_quality(&img);
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageIsBlank as u32, err_resp.1.code);
+    }
+
+    #[test]
+    fn check_image_quality_returns_err_if_image_is_too_blurry() {
+        let blurry_img = imageops::blur(&create_dummy_image(640, 480, true), 8.0);
+        let img = encode_to_jpeg(&blurry_img, 85);
+
+        let result = check_image_quality(&img);
+
+        let err_resp = result.expect_err("failed to get Err");
+        assert_eq!(StatusCode::BAD_REQUEST, err_resp.0);
+        assert_eq!(Code::ImageTooBlurry as u32, err_resp.1.code);
+    }
+
+    #[test]
+    fn check_image_quality_returns_ok_if_large_image_has_enough_quality() {
+        let img = encode_to_jpeg(&create_dummy_image(2048, 1536, true), 85);
+
+        let result = check_image_quality(&img);
+
+        result.expect("failed to get Ok");
+    }
+
+    #[test]
+    fn perceptual_hashes_of_same_image_are_close_even_if_compressed_differently() {
+        let original = create_dummy_image(640, 480, true);
+        let img1 = encode_to_jpeg(&original, 95);
+        let img2 = encode_to_jpeg(&original, 50);
+
+        let hash1 = check_image_quality(&img1).expect("failed to get Ok");
+        let hash2 = check_image_quality(&img2).expect("failed to get Ok");
+
+        assert!((hash1 ^ hash2).count_ones() <= MAX_HAMMING_DISTANCE_FOR_DUPLICATE_IMAGE);
+    }
+
+    #[test]
+    fn perceptual_hashes_of_different_images_are_far() {
+        let img1 = encode_to_jpeg(&create_dummy_image(640, 480, true), 85);
+        let img2 = encode_to_jpeg(&create_dummy_image(640, 480, false), 85);
+
+        let hash1 = check_image_quality(&img1).expect("failed to get Ok");
+        let hash2 = check_image_quality(&img2).expect("failed to get Ok");
+
+        assert!((hash1 ^ hash2).count_ones() > MAX_HAMMING_DISTANCE_FOR_DUPLICATE_IMAGE);
+    }
+}
@@ -7,7 +7,8 @@ use axum::async_trait;
 use axum::http::StatusCode;
 use axum::{extract::State, Json};
 use chrono::Datelike;
-use common::opensearch::{index_document, update_document, INDEX_NAME};
+use common::consultant_search::{ConsultantSearchBackend, ConsultantSearchClient};
+use common::opensearch::INDEX_NAME;
 use common::util::{Identity, Ymd};
 use common::{ApiError, ErrResp, ErrRespStruct, RespResult};
 use entity::sea_orm::{
@@ -16,7 +17,6 @@ use entity::sea_orm::{
 };
 use entity::{career, consulting_fee};
 use once_cell::sync::Lazy;
-use opensearch::OpenSearch;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
@@ -48,12 +48,15 @@ static KATAKANA_LOWER_CASE_UPPER_CASE_SET: Lazy<HashSet<(String, String)>> = Laz
 pub(crate) async fn post_bank_account(
     User { user_info }: User,
     State(pool): State<DatabaseConnection>,
-    State(index_client): State<OpenSearch>,
+    State(consultant_search_client): State<ConsultantSearchClient>,
     Json(bank_account_register_req): Json<BankAccountRegisterReq>,
 ) -> RespResult<BankAccountResult> {
     let bank_account = bank_account_register_req.bank_account;
     let non_profit_objective = bank_account_register_req.non_profit_objective;
-    let op = SubmitBankAccountOperationImpl { pool, index_client };
+    let op = SubmitBankAccountOperationImpl {
+        pool,
+        consultant_search_client,
+    };
     handle_bank_account_req(user_info.account_id, bank_account, non_profit_objective, op).await
 }
 
@@ -181,7 +184,7 @@ trait SubmitBankAccountOperation {
 
 struct SubmitBankAccountOperationImpl {
     pool: DatabaseConnection,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 }
 
 #[async_trait]
@@ -266,7 +269,7 @@ impl SubmitBankAccountOperation for SubmitBankAccountOperationImpl {
         account_id: i64,
         bank_account: BankAccount,
     ) -> Result<(), ErrResp> {
-        let index_client = self.index_client.clone();
+        let consultant_search_client = self.consultant_search_client.clone();
         self.pool
             .transaction::<_, (), ErrRespStruct>(|txn| {
                 Box::pin(async move {
@@ -290,7 +293,7 @@ impl SubmitBankAccountOperation for SubmitBankAccountOperationImpl {
                                 "update document for \"is_bank_account_registered\" (account_id: {}, document_id: {})",
                                 account_id, document_id
                             );
-                            update_is_bank_account_registered_on_document(INDEX_NAME, document_id.to_string().as_str(), index_client).await?;
+                            update_is_bank_account_registered_on_document(INDEX_NAME, document_id.to_string().as_str(), consultant_search_client).await?;
                         } else {
                             // document_idとしてuser_account_idを利用
                             let document_id = account_id;
@@ -303,7 +306,7 @@ impl SubmitBankAccountOperation for SubmitBankAccountOperationImpl {
                                 INDEX_NAME,
                                 document_id.to_string().as_str(),
                                 account_id,
-                                index_client,
+                                consultant_search_client,
                             ).await?;
                         };
                     }
@@ -400,7 +403,7 @@ async fn create_bank_account(
 async fn update_is_bank_account_registered_on_document(
     index_name: &str,
     document_id: &str,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let value = format!("ctx._source.is_bank_account_registered = {}", true);
     let script = json!({
@@ -408,7 +411,8 @@ async fn update_is_bank_account_registered_on_document(
             "source": value
         }
     });
-    update_document(index_name, document_id, &script, &index_client)
+    consultant_search_client
+        .update_document(index_name, document_id, &script)
         .await
         .map_err(|e| {
             error!(
@@ -424,7 +428,7 @@ async fn add_new_document_with_is_bank_account_registered(
     index_name: &str,
     document_id: &str,
     account_id: i64,
-    index_client: OpenSearch,
+    consultant_search_client: ConsultantSearchClient,
 ) -> Result<(), ErrRespStruct> {
     let new_document = json!({
         "user_account_id": account_id,
@@ -435,7 +439,8 @@ async fn add_new_document_with_is_bank_account_registered(
         "num_of_rated": 0,
         "disabled": false
     });
-    index_document(index_name, document_id, &new_document, &index_client)
+    consultant_search_client
+        .index_document(index_name, document_id, &new_document)
         .await
         .map_err(|e| {
             error!(
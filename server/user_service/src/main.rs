@@ -11,7 +11,7 @@ use crate::handlers::session::authentication::authenticated_handlers::consultati
 use crate::handlers::session::authentication::authenticated_handlers::delete_accounts::delete_accounts;
 use crate::handlers::account_creation::temp_accounts::post_temp_accounts;
 use crate::handlers::session::authentication::authenticated_handlers::agreement::post_agreement;
-use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_request::acceptance::{post_consultation_request_acceptance, KEY_TO_BANK_CODE, KEY_TO_BANK_NAME, KEY_TO_BANK_BRANCH_CODE, KEY_TO_BANK_BRANCH_NAME, KEY_TO_BANK_ACCOUNT_NUMBER, KEY_TO_BANK_ACCOUNT_HOLDER_NAME};
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_request::acceptance::{post_consultation_request_acceptance, KEY_TO_BANK_CODE, KEY_TO_BANK_NAME, KEY_TO_BANK_BRANCH_CODE, KEY_TO_BANK_BRANCH_NAME, KEY_TO_BANK_ACCOUNT_HOLDER_NAME};
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_request::detail::get_consultation_request_detail;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_request::list::get_consultation_requests;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultation_request::rejection::post_consultation_request_rejection;
@@ -20,12 +20,23 @@ use crate::handlers::session::authentication::authenticated_handlers::consultati
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultations::get_consultations;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::detail::get_consultant_detail;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::search::post_consultants_search;
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::post::MAX_CAREER_IMAGE_SIZE_IN_BYTES;
-use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::{delete, get, post};
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::search_suggestions::get_consultants_search_suggestions;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::similar::get_similar_consultants;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::favorite::delete::delete_favorite_consultant;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::favorite::list::get_favorite_consultants;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::favorite::post::post_favorite_consultant;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::saved_search::delete::delete_saved_search;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::saved_search::list::get_saved_searches;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::consultant::saved_search::post::post_saved_search;
+use crate::handlers::saved_search_alert_unsubscribe::post_saved_search_alert_unsubscribe;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::post::MAX_CAREER_FILE_SIZE_IN_BYTES;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::{delete, get, post, put};
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::work_email_verification::code_req::post_work_email_verification_code_req;
+use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::career::work_email_verification::verification::post_work_email_verification;
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::fee_per_hour_in_yen::post_fee_per_hour_in_yen;
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::get_profile;
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::profile::identity::post::{
-    post_identity, MAX_IDENTITY_IMAGE_SIZE_IN_BYTES,
+    post_identity, MAX_IDENTITY_FILE_SIZE_IN_BYTES,
 };
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::rewards::bank_account::post_bank_account;
 use crate::handlers::session::authentication::authenticated_handlers::personal_info::rewards::get_reward;
@@ -38,6 +49,8 @@ use crate::handlers::session::authentication::authenticated_handlers::mfs_settin
 use crate::handlers::session::authentication::authenticated_handlers::mfs_setting::temp_secret::get::get_temp_mfa_secret;
 use crate::handlers::session::authentication::authenticated_handlers::mfs_setting::temp_secret::post::post_temp_mfa_secret;
 use crate::handlers::news::get_news;
+use crate::handlers::payment_webhook::post_payment_webhook;
+use crate::handlers::session::authentication::authenticated_handlers::consultation::card_checkout_session::post_card_checkout_session;
 use crate::handlers::session::password_change::change_req::post_password_change_req;
 use crate::handlers::session::password_change::password_update::post_password_update;
 use crate::handlers::session::authentication::authenticated_handlers::consultation::rating::{
@@ -66,9 +79,17 @@ use common::smtp::{
 use common::storage::{
     KEY_TO_AWS_S3_ENDPOINT_URI, KEY_TO_AWS_S3_REGION, KEY_TO_IDENTITY_IMAGES_BUCKET_NAME, KEY_TO_CAREER_IMAGES_BUCKET_NAME, StorageClient, AWS_S3_REGION, AWS_S3_ACCESS_KEY_ID, AWS_S3_SECRET_ACCESS_KEY, AWS_S3_ENDPOINT_URI,
 };
+use common::consultant_search::{
+    KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH, ConsultantSearchClient, USE_DB_FOR_CONSULTANT_SEARCH,
+};
+use common::payment::{
+    KEY_TO_USE_STUB_PAYMENT_GATEWAY, KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI, KEY_TO_PAYMENT_GATEWAY_SECRET_KEY,
+    KEY_TO_PAYMENT_GATEWAY_WEBHOOK_SECRET, PaymentGatewayClient, USE_STUB_PAYMENT_GATEWAY, PAYMENT_GATEWAY_ENDPOINT_URI, PAYMENT_GATEWAY_SECRET_KEY,
+};
 use common::util::{check_env_vars};
 use common::{AppState, RequestLogElements, KEY_TO_URL_FOR_FRONT_END, create_key_for_singed_cookie, KEY_TO_USE_ECS_TASK_ROLE, USE_ECS_TASK_ROLE};
 use handlers::session::authentication::authenticated_handlers::consultation::consultation_room::{KEY_TO_SKY_WAY_APPLICATION_ID, KEY_TO_SKY_WAY_SECRET_KEY};
+use handlers::session::authentication::authenticated_handlers::personal_info::profile::image_converter::check_external_commands;
 use dotenv::dotenv;
 use entity::sea_orm::{ConnectOptions, Database};
 use handlers::session::authentication::authenticated_handlers::terms_of_use::KEY_TO_TERMS_OF_USE_VERSION;
@@ -120,11 +141,15 @@ static ENV_VARS: Lazy<Vec<String>> = Lazy::new(|| {
         KEY_TO_AWS_SES_REGION.to_string(),
         KEY_TO_AWS_SES_ENDPOINT_URI.to_string(),
         KEY_TO_USE_ECS_TASK_ROLE.to_string(),
+        KEY_TO_USE_STUB_PAYMENT_GATEWAY.to_string(),
+        KEY_TO_USE_DB_FOR_CONSULTANT_SEARCH.to_string(),
+        KEY_TO_PAYMENT_GATEWAY_ENDPOINT_URI.to_string(),
+        KEY_TO_PAYMENT_GATEWAY_SECRET_KEY.to_string(),
+        KEY_TO_PAYMENT_GATEWAY_WEBHOOK_SECRET.to_string(),
         KEY_TO_BANK_CODE.to_string(),
         KEY_TO_BANK_NAME.to_string(),
         KEY_TO_BANK_BRANCH_CODE.to_string(),
         KEY_TO_BANK_BRANCH_NAME.to_string(),
-        KEY_TO_BANK_ACCOUNT_NUMBER.to_string(),
         KEY_TO_BANK_ACCOUNT_HOLDER_NAME.to_string(),
     ]
 });
@@ -137,6 +162,12 @@ fn main() {
         println!("{:?}", result.unwrap_err());
         std::process::exit(1);
     }
+    let result = check_external_commands();
+    if result.is_err() {
+        println!("failed to find mandatory external commands (following commands are needed)");
+        println!("{:?}", result.unwrap_err());
+        std::process::exit(1);
+    }
     let num = num_cpus::get();
     tokio::runtime::Builder::new_multi_thread()
         .worker_threads(num)
@@ -181,7 +212,7 @@ async fn main_internal(num_of_cpus: u32) {
         .wait_for_connect()
         .await
         .expect("failed to connect redis");
-    let store = RedisSessionStore::from_pool(redis_pool, None);
+    let store = RedisSessionStore::from_pool(redis_pool.clone(), None);
 
     let opensearch_url = var(KEY_TO_OPENSEARCH_ENDPOINT_URI).unwrap_or_else(|_| {
         panic!(
@@ -247,13 +278,31 @@ async fn main_internal(num_of_cpus: u32) {
         .await
     };
 
+    let payment_gateway_client = if *USE_STUB_PAYMENT_GATEWAY {
+        PaymentGatewayClient::new_stub()
+    } else {
+        PaymentGatewayClient::new(
+            PAYMENT_GATEWAY_ENDPOINT_URI.as_str(),
+            PAYMENT_GATEWAY_SECRET_KEY.as_str(),
+        )
+    };
+
+    let consultant_search_client = if *USE_DB_FOR_CONSULTANT_SEARCH {
+        ConsultantSearchClient::new_database(pool.clone())
+    } else {
+        ConsultantSearchClient::new(index_client.clone())
+    };
+
     let state = AppState {
         store,
+        redis_pool,
         index_client,
         pool,
         key_for_signed_cookie,
         smtp_client,
         storage_client,
+        payment_gateway_client,
+        consultant_search_client,
     };
 
     let app = Router::new()
@@ -270,18 +319,29 @@ async fn main_internal(num_of_cpus: u32) {
                 .route("/password-update", post(post_password_update))
                 .route("/profile", get(get_profile))
                 .route("/rewards", get(get_reward))
-                .merge(Router::new().route("/identity", post(post_identity).layer(DefaultBodyLimit::max(MAX_IDENTITY_IMAGE_SIZE_IN_BYTES * 2 + 1024 * 1024))))
-                .merge(Router::new().route("/career", post(post::career).get(get::career).delete(delete::career)).layer(DefaultBodyLimit::max(MAX_CAREER_IMAGE_SIZE_IN_BYTES * 2 + 1024 * 1024)))
+                .merge(Router::new().route("/identity", post(post_identity).layer(DefaultBodyLimit::max(MAX_IDENTITY_FILE_SIZE_IN_BYTES.largest() * 2 + 1024 * 1024))))
+                .merge(Router::new().route("/career", post(post::career).get(get::career).put(put::career).delete(delete::career)).layer(DefaultBodyLimit::max(MAX_CAREER_FILE_SIZE_IN_BYTES.largest() * 2 + 1024 * 1024)))
+                .route("/work-email-verification-code-req", post(post_work_email_verification_code_req))
+                .route("/work-email-verification", post(post_work_email_verification))
                 .route("/fee-per-hour-in-yen", post(post_fee_per_hour_in_yen))
                 .route("/bank-account", post(post_bank_account))
                 .route("/consultants-search", post(post_consultants_search))
+                .route("/consultants-search-suggestions", get(get_consultants_search_suggestions))
                 .route("/consultant-detail", get(get_consultant_detail))
+                .route("/similar-consultants", get(get_similar_consultants))
+                .route("/saved-search", post(post_saved_search).delete(delete_saved_search))
+                .route("/saved-searches", get(get_saved_searches))
+                .route("/saved-search-alert-unsubscribe", post(post_saved_search_alert_unsubscribe))
+                .route("/favorite-consultant", post(post_favorite_consultant).delete(delete_favorite_consultant))
+                .route("/favorite-consultants", get(get_favorite_consultants))
                 .route("/fee-per-hour-in-yen-for-application", get(get_fee_per_hour_in_yen_for_application))
                 .route("/request-consultation", post(post_request_consultation))
                 .route("/consultation-requests", get(get_consultation_requests))
                 .route("/consultation-request-detail", get(get_consultation_request_detail))
                 .route("/consultation-request-rejection", post(post_consultation_request_rejection))
                 .route("/consultation-request-acceptance", post(post_consultation_request_acceptance))
+                .route("/card-checkout-session", post(post_card_checkout_session))
+                .route("/payment-webhook", post(post_payment_webhook))
                 .route("/consultations", get(get_consultations))
                 .route("/user-side-info", get(get_user_side_info))
                 .route("/consultant-side-info", get(get_consultant_side_info))
@@ -0,0 +1,326 @@
+// Copyright 2023 Ken Miura
+
+use std::{collections::HashMap, error::Error};
+
+use chrono::NaiveDate;
+use common::opensearch::create_document_source;
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde_json::{json, Value};
+
+/// 一度にDBから取得し、ドキュメントを作成する数
+pub(crate) const NUM_OF_DOCUMENTS_PER_CHUNK: u64 = 100;
+
+/// DBの内容から作成したドキュメント
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Document {
+    pub(crate) document_id: i64,
+    pub(crate) user_account_id: i64,
+    pub(crate) source: Value,
+}
+
+/// user_account_idがlast_user_account_idより大きいdocumentを、user_account_idの昇順にlimit件取得する
+pub(crate) async fn find_documents(
+    conn: &DatabaseConnection,
+    last_user_account_id: Option<i64>,
+    limit: u64,
+) -> Result<Vec<entity::document::Model>, Box<dyn Error>> {
+    let mut query = entity::document::Entity::find();
+    if let Some(last_user_account_id) = last_user_account_id {
+        query = query.filter(entity::document::Column::UserAccountId.gt(last_user_account_id));
+    }
+    let models = query
+        .order_by_asc(entity::document::Column::UserAccountId)
+        .limit(limit)
+        .all(conn)
+        .await
+        .map_err(|e| {
+            format!(
+                "failed to find document (last_user_account_id: {:?}, limit: {}): {}",
+                last_user_account_id, limit, e
+            )
+        })?;
+    Ok(models)
+}
+
+/// documentsに対応するドキュメントをDBの内容から作成する
+///
+/// 2番目の戻り値は、ユーザーアカウントが存在しないため、ドキュメントを作成できなかったdocument
+pub(crate) async fn create_documents(
+    conn: &DatabaseConnection,
+    documents: &[entity::document::Model],
+    current_date: NaiveDate,
+) -> Result<(Vec<Document>, Vec<entity::document::Model>), Box<dyn Error>> {
+    let user_account_ids: Vec<i64> = documents.iter().map(|d| d.user_account_id).collect();
+
+    let user_accounts = entity::user_account::Entity::find()
+        .filter(entity::user_account::Column::UserAccountId.is_in(user_account_ids.clone()))
+        .all(conn)
+        .await
+        .map_err(|e| format!("failed to find user_account: {}", e))?;
+    let careers = entity::career::Entity::find()
+        .filter(entity::career::Column::UserAccountId.is_in(user_account_ids.clone()))
+        .order_by_asc(entity::career::Column::CareerId)
+        .all(conn)
+        .await
+        .map_err(|e| format!("failed to find career: {}", e))?;
+    let fees = entity::consulting_fee::Entity::find()
+        .filter(entity::consulting_fee::Column::UserAccountId.is_in(user_account_ids.clone()))
+        .all(conn)
+        .await
+        .map_err(|e| format!("failed to find consulting_fee: {}", e))?;
+    let bank_accounts = entity::bank_account::Entity::find()
+        .filter(entity::bank_account::Column::UserAccountId.is_in(user_account_ids.clone()))
+        .all(conn)
+        .await
+        .map_err(|e| format!("failed to find bank_account: {}", e))?;
+    let ratings = entity::consultant_rating::Entity::find()
+        .filter(entity::consultant_rating::Column::ConsultantId.is_in(user_account_ids))
+        .filter(entity::consultant_rating::Column::Rating.is_not_null())
+        .all(conn)
+        .await
+        .map_err(|e| format!("failed to find consultant_rating: {}", e))?;
+
+    let mut ratings_by_consultant: HashMap<i64, Vec<i16>> = HashMap::new();
+    for rating in ratings {
+        if let Some(r) = rating.rating {
+            ratings_by_consultant
+                .entry(rating.consultant_id)
+                .or_default()
+                .push(r);
+        }
+    }
+
+    let mut results = Vec::with_capacity(documents.len());
+    let mut orphans = Vec::new();
+    for document in documents {
+        let user_account_id = document.user_account_id;
+        let Some(user_account) = user_accounts
+            .iter()
+            .find(|u| u.user_account_id == user_account_id)
+        else {
+            orphans.push(document.clone());
+            continue;
+        };
+        let careers: Vec<&entity::career::Model> = careers
+            .iter()
+            .filter(|c| c.user_account_id == user_account_id)
+            .collect();
+        let fee_per_hour_in_yen = fees
+            .iter()
+            .find(|f| f.user_account_id == user_account_id)
+            .map(|f| f.fee_per_hour_in_yen);
+        let is_bank_account_registered = bank_accounts
+            .iter()
+            .any(|b| b.user_account_id == user_account_id);
+        let ratings = ratings_by_consultant
+            .remove(&user_account_id)
+            .unwrap_or_default();
+        results.push(Document {
+            document_id: document.document_id,
+            user_account_id,
+            source: create_document_source(
+                user_account_id,
+                &careers,
+                fee_per_hour_in_yen,
+                is_bank_account_registered,
+                ratings,
+                user_account.disabled_at.is_some(),
+                current_date,
+            ),
+        });
+    }
+    Ok((results, orphans))
+}
+
+/// DBの内容から作成したドキュメント（expected）とインデックスに格納されているドキュメント（indexed）の差分を、差分のあるフィールドの説明として返す
+pub(crate) fn find_differences(expected: &Value, indexed: &Value) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    // 職務経歴を一度も登録していないドキュメント（相談料、または口座の登録時に作成したドキュメント）にはnum_of_careersが存在しないため、0として扱う
+    let indexed_num_of_careers = if indexed["num_of_careers"].is_null() {
+        json!(0)
+    } else {
+        indexed["num_of_careers"].clone()
+    };
+    if expected["num_of_careers"] != indexed_num_of_careers {
+        differences.push(format!(
+            "num_of_careers (expected: {}, indexed: {})",
+            expected["num_of_careers"], indexed_num_of_careers
+        ));
+    }
+    for key in [
+        "user_account_id",
+        "fee_per_hour_in_yen",
+        "is_bank_account_registered",
+        "num_of_rated",
+        "disabled",
+    ] {
+        if expected[key] != indexed[key] {
+            differences.push(format!(
+                "{} (expected: {}, indexed: {})",
+                key, expected[key], indexed[key]
+            ));
+        }
+    }
+    if !is_same_rating(&expected["rating"], &indexed["rating"]) {
+        differences.push(format!(
+            "rating (expected: {}, indexed: {})",
+            expected["rating"], indexed["rating"]
+        ));
+    }
+
+    let empty = vec![];
+    let expected_careers = expected["careers"].as_array().unwrap_or(&empty);
+    let indexed_careers = indexed["careers"].as_array().unwrap_or(&empty);
+    for expected_career in expected_careers {
+        let career_id = &expected_career["career_id"];
+        match indexed_careers
+            .iter()
+            .find(|c| &c["career_id"] == career_id)
+        {
+            Some(indexed_career) => {
+                if expected_career != indexed_career {
+                    differences.push(format!(
+                        "careers (career_id: {}, expected: {}, indexed: {})",
+                        career_id, expected_career, indexed_career
+                    ));
+                }
+            }
+            None => differences.push(format!("careers (career_id: {} is not indexed)", career_id)),
+        }
+    }
+    for indexed_career in indexed_careers {
+        let career_id = &indexed_career["career_id"];
+        if !expected_careers
+            .iter()
+            .any(|c| &c["career_id"] == career_id)
+        {
+            differences.push(format!(
+                "careers (career_id: {} is indexed but not found in database)",
+                career_id
+            ));
+        }
+    }
+
+    differences
+}
+
+fn is_same_rating(expected: &Value, indexed: &Value) -> bool {
+    match (expected.as_f64(), indexed.as_f64()) {
+        (Some(e), Some(i)) => (e - i).abs() < 1e-9,
+        (None, None) => expected.is_null() && indexed.is_null(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use common::opensearch::create_career_document;
+
+    use super::*;
+
+    fn create_career(career_id: i64, user_account_id: i64) -> entity::career::Model {
+        entity::career::Model {
+            career_id,
+            user_account_id,
+            company_name: "テスト株式会社".to_string(),
+            company_id: 1,
+            department_name: None,
+            office: Some("東京事業所".to_string()),
+            career_start_date: NaiveDate::from_ymd_opt(2015, 4, 1).expect("failed to get Ok"),
+            career_end_date: None,
+            contract_type: "regular".to_string(),
+            profession: None,
+            annual_income_in_man_yen: Some(500),
+            is_manager: false,
+            position_name: None,
+            is_new_graduate: true,
+            note: None,
+            verified_work_email_domain: None,
+        }
+    }
+
+    #[test]
+    fn find_differences_returns_empty_if_same() {
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let career = create_career(10, 1);
+        let expected = create_document_source(
+            1,
+            &[&career],
+            Some(5000),
+            true,
+            vec![3],
+            false,
+            current_date,
+        );
+
+        let result = find_differences(&expected, &expected.clone());
+
+        assert!(result.is_empty(), "{:?}", result);
+    }
+
+    #[test]
+    fn find_differences_treats_missing_num_of_careers_as_zero() {
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let expected =
+            create_document_source(1, &[], Some(5000), false, vec![], false, current_date);
+        let indexed = json!({
+            "user_account_id": 1,
+            "careers": [],
+            "fee_per_hour_in_yen": 5000,
+            "is_bank_account_registered": false,
+            "rating": null,
+            "num_of_rated": 0,
+            "disabled": false
+        });
+
+        let result = find_differences(&expected, &indexed);
+
+        assert!(result.is_empty(), "{:?}", result);
+    }
+
+    #[test]
+    fn find_differences_detects_drifted_fields() {
+        let current_date = NaiveDate::from_ymd_opt(2023, 9, 15).expect("failed to get Ok");
+        let career1 = create_career(10, 1);
+        let career2 = create_career(11, 1);
+        let expected = create_document_source(
+            1,
+            &[&career1, &career2],
+            Some(5000),
+            true,
+            vec![3, 4],
+            false,
+            current_date,
+        );
+        let mut drifted_career1 = create_career_document(&career1, current_date);
+        drifted_career1["years_of_service"] = json!(7);
+        let indexed = json!({
+            "user_account_id": 1,
+            "careers": [drifted_career1, {"career_id": 12}],
+            "num_of_careers": 2,
+            "fee_per_hour_in_yen": 4000,
+            "is_bank_account_registered": true,
+            "rating": 3.0,
+            "num_of_rated": 1,
+            "disabled": true
+        });
+
+        let result = find_differences(&expected, &indexed);
+
+        assert_eq!(7, result.len(), "{:?}", result);
+        assert!(result[0].starts_with("fee_per_hour_in_yen"));
+        assert!(result[1].starts_with("num_of_rated"));
+        assert!(result[2].starts_with("disabled"));
+        assert!(result[3].starts_with("rating"));
+        assert!(result[4].starts_with("careers (career_id: 10,"));
+        assert_eq!("careers (career_id: 11 is not indexed)", result[5]);
+        assert_eq!(
+            "careers (career_id: 12 is indexed but not found in database)",
+            result[6]
+        );
+    }
+}
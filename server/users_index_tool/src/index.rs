@@ -0,0 +1,391 @@
+// Copyright 2023 Ken Miura
+
+use std::{collections::HashMap, error::Error};
+
+use common::opensearch::{
+    index_definition::{create_index_definition, find_index_definition_version},
+    search_documents, INDEX_NAME,
+};
+use opensearch::{
+    http::response::Response,
+    indices::{
+        IndicesCreateParts, IndicesDeleteParts, IndicesExistsParts, IndicesGetAliasParts,
+        IndicesGetMappingParts, IndicesGetParts, IndicesRefreshParts,
+    },
+    CountParts, OpenSearch,
+};
+use serde_json::{json, Value};
+
+/// インデックス名の接頭辞（接頭辞の後に世代を示す数字を続ける）
+const VERSIONED_INDEX_NAME_PREFIX: &str = "users_v";
+
+/// エイリアス（[INDEX_NAME]）の現在の状態
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum AliasState {
+    /// エイリアスが存在し、値が示すインデックスを向いている
+    Alias(Vec<String>),
+    /// エイリアスではなく、[INDEX_NAME]という名前のインデックスが存在する（エイリアスを導入する前に作成したインデックスを利用している）
+    Index,
+    /// エイリアスもインデックスも存在しない
+    NotFound,
+}
+
+/// document_idsに対応するドキュメントの_sourceを、ドキュメントIDごとに取得する
+pub(crate) async fn get_indexed_documents(
+    document_ids: &[i64],
+    client: &OpenSearch,
+) -> Result<HashMap<i64, Value>, Box<dyn Error>> {
+    let ids: Vec<String> = document_ids.iter().map(|id| id.to_string()).collect();
+    let query = json!({
+        "query": {
+            "ids": {
+                "values": ids
+            }
+        }
+    });
+    let query_result = search_documents(
+        INDEX_NAME,
+        0,
+        document_ids.len() as i64,
+        None,
+        &query,
+        client,
+    )
+    .await
+    .map_err(|e| {
+        format!(
+            "failed to search documents (status code: {}, response body: {:?}, document_ids: {:?})",
+            e.0, e.1, document_ids
+        )
+    })?;
+    let hits = query_result["hits"]["hits"]
+        .as_array()
+        .ok_or_else(|| format!("failed to get hits: {}", query_result))?;
+    let mut results = HashMap::with_capacity(hits.len());
+    for hit in hits {
+        let document_id = hit["_id"]
+            .as_str()
+            .ok_or_else(|| format!("failed to find _id: {}", hit))?
+            .parse::<i64>()?;
+        results.insert(document_id, hit["_source"].clone());
+    }
+    Ok(results)
+}
+
+pub(crate) async fn count_documents(
+    index_name: &str,
+    client: &OpenSearch,
+) -> Result<i64, Box<dyn Error>> {
+    let response = client
+        .count(CountParts::Index(&[index_name]))
+        .send()
+        .await?;
+    let body = read_success_response(response, "count documents").await?;
+    let count = body["count"]
+        .as_i64()
+        .ok_or_else(|| format!("failed to get count: {}", body))?;
+    Ok(count)
+}
+
+pub(crate) async fn find_alias_state(client: &OpenSearch) -> Result<AliasState, Box<dyn Error>> {
+    let response = client
+        .indices()
+        .get_alias(IndicesGetAliasParts::Name(&[INDEX_NAME]))
+        .send()
+        .await?;
+    if response.status_code().is_success() {
+        let body = response.json::<Value>().await?;
+        let mut index_names: Vec<String> = body
+            .as_object()
+            .map(|m| m.keys().cloned().collect())
+            .unwrap_or_default();
+        index_names.sort();
+        return Ok(AliasState::Alias(index_names));
+    }
+    let response = client
+        .indices()
+        .exists(IndicesExistsParts::Index(&[INDEX_NAME]))
+        .send()
+        .await?;
+    if response.status_code().is_success() {
+        Ok(AliasState::Index)
+    } else {
+        Ok(AliasState::NotFound)
+    }
+}
+
+/// 既存のバージョン付きインデックスの名前を返す
+pub(crate) async fn find_versioned_index_names(
+    client: &OpenSearch,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let pattern = format!("{}*", VERSIONED_INDEX_NAME_PREFIX);
+    let response = client
+        .indices()
+        .get(IndicesGetParts::Index(&[pattern.as_str()]))
+        .send()
+        .await?;
+    let body = read_success_response(response, "get indices").await?;
+    let index_names: Vec<String> = body
+        .as_object()
+        .map(|m| m.keys().cloned().collect())
+        .unwrap_or_default();
+    Ok(index_names)
+}
+
+/// 既存のインデックスの中で最も大きい世代に1を加えたインデックス名を返す
+pub(crate) fn create_next_versioned_index_name(index_names: &[String]) -> String {
+    let max_generation = index_names
+        .iter()
+        .filter_map(|name| parse_generation(name))
+        .max()
+        .unwrap_or(0);
+    format!("{}{}", VERSIONED_INDEX_NAME_PREFIX, max_generation + 1)
+}
+
+/// index_namesの中から、current_index_nameの一つ前の世代のインデックス名を返す
+pub(crate) fn find_previous_versioned_index_name(
+    index_names: &[String],
+    current_index_name: &str,
+) -> Option<String> {
+    let current_generation = parse_generation(current_index_name)?;
+    index_names
+        .iter()
+        .filter_map(|name| parse_generation(name).map(|g| (g, name)))
+        .filter(|(g, _)| *g < current_generation)
+        .max_by_key(|(g, _)| *g)
+        .map(|(_, name)| name.clone())
+}
+
+fn parse_generation(index_name: &str) -> Option<u32> {
+    index_name
+        .strip_prefix(VERSIONED_INDEX_NAME_PREFIX)
+        .and_then(|generation| generation.parse::<u32>().ok())
+}
+
+/// [create_index_definition]の定義でインデックスを作成する
+pub(crate) async fn create_index(
+    index_name: &str,
+    client: &OpenSearch,
+) -> Result<(), Box<dyn Error>> {
+    let response = client
+        .indices()
+        .create(IndicesCreateParts::Index(index_name))
+        .body(create_index_definition())
+        .send()
+        .await?;
+    let _ = read_success_response(response, "create index").await?;
+    Ok(())
+}
+
+pub(crate) async fn delete_index(
+    index_name: &str,
+    client: &OpenSearch,
+) -> Result<(), Box<dyn Error>> {
+    let response = client
+        .indices()
+        .delete(IndicesDeleteParts::Index(&[index_name]))
+        .send()
+        .await?;
+    let _ = read_success_response(response, "delete index").await?;
+    Ok(())
+}
+
+pub(crate) async fn refresh_index(
+    index_name: &str,
+    client: &OpenSearch,
+) -> Result<(), Box<dyn Error>> {
+    let response = client
+        .indices()
+        .refresh(IndicesRefreshParts::Index(&[index_name]))
+        .send()
+        .await?;
+    let _ = read_success_response(response, "refresh index").await?;
+    Ok(())
+}
+
+/// インデックスに記録されている定義のバージョンを返す（記録されていない場合、None）
+pub(crate) async fn find_index_definition_version_of(
+    index_name: &str,
+    client: &OpenSearch,
+) -> Result<Option<u32>, Box<dyn Error>> {
+    let response = client
+        .indices()
+        .get_mapping(IndicesGetMappingParts::Index(&[index_name]))
+        .send()
+        .await?;
+    let body = read_success_response(response, "get mapping").await?;
+    // エイリアス名を指定した場合でもレスポンスのキーは実体のインデックス名となるため、最初の要素を利用する
+    let mapping = body
+        .as_object()
+        .and_then(|m| m.values().next())
+        .map(|v| v["mappings"].clone())
+        .ok_or_else(|| format!("failed to find mappings: {}", body))?;
+    Ok(find_index_definition_version(&mapping))
+}
+
+/// source_index_nameの全ドキュメントをdest_index_nameにコピーする
+pub(crate) async fn reindex(
+    source_index_name: &str,
+    dest_index_name: &str,
+    client: &OpenSearch,
+) -> Result<(), Box<dyn Error>> {
+    let response = client
+        .reindex()
+        .wait_for_completion(true)
+        .refresh(true)
+        .body(json!({
+            "source": {
+                "index": source_index_name
+            },
+            "dest": {
+                "index": dest_index_name
+            }
+        }))
+        .send()
+        .await?;
+    let body = read_success_response(response, "reindex").await?;
+    let failures = body["failures"].as_array().map(|f| f.len()).unwrap_or(0);
+    if failures != 0 {
+        return Err(format!("failed to reindex some documents: {}", body).into());
+    }
+    Ok(())
+}
+
+/// エイリアス（[INDEX_NAME]）の向き先をnew_index_nameに切り替える
+///
+/// 現在のエイリアスの向き先からはエイリアスを外すのみで、インデックスは削除しない（ロールバックで再度向き先とするため）。
+/// ただし、[AliasState::Index]の場合、同じ名前のエイリアスを作成するためにそのインデックスを削除する。
+/// いずれの場合も一回のリクエストで行うため、切り替えはアトミックに行われる。
+pub(crate) async fn switch_alias(
+    new_index_name: &str,
+    alias_state: &AliasState,
+    client: &OpenSearch,
+) -> Result<(), Box<dyn Error>> {
+    let actions = create_switch_alias_actions(new_index_name, alias_state);
+    let response = client
+        .indices()
+        .update_aliases()
+        .body(json!({ "actions": actions }))
+        .send()
+        .await?;
+    let _ = read_success_response(response, "update aliases").await?;
+    Ok(())
+}
+
+fn create_switch_alias_actions(new_index_name: &str, alias_state: &AliasState) -> Vec<Value> {
+    let mut actions = vec![json!({"add": {"index": new_index_name, "alias": INDEX_NAME}})];
+    match alias_state {
+        AliasState::Alias(index_names) => {
+            for index_name in index_names.iter().filter(|n| *n != new_index_name) {
+                actions.push(json!({"remove": {"index": index_name, "alias": INDEX_NAME}}));
+            }
+        }
+        AliasState::Index => actions.push(json!({"remove_index": {"index": INDEX_NAME}})),
+        AliasState::NotFound => {}
+    }
+    actions
+}
+
+async fn read_success_response(
+    response: Response,
+    operation: &str,
+) -> Result<Value, Box<dyn Error>> {
+    let status_code = response.status_code();
+    let body = response.json::<Value>().await?;
+    if !status_code.is_success() {
+        return Err(format!(
+            "failed to {} (status code: {}, response body: {})",
+            operation, status_code, body
+        )
+        .into());
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn create_next_versioned_index_name_returns_first_generation_if_no_index_exists() {
+        let result = create_next_versioned_index_name(&[]);
+
+        assert_eq!("users_v1", result);
+    }
+
+    #[test]
+    fn create_next_versioned_index_name_returns_next_of_max_generation() {
+        let index_names = vec![
+            "users_v2".to_string(),
+            "users_v10".to_string(),
+            "users_v9".to_string(),
+            "users_vx".to_string(),
+        ];
+
+        let result = create_next_versioned_index_name(&index_names);
+
+        assert_eq!("users_v11", result);
+    }
+
+    #[test]
+    fn find_previous_versioned_index_name_returns_previous_generation() {
+        let index_names = vec![
+            "users_v2".to_string(),
+            "users_v10".to_string(),
+            "users_v9".to_string(),
+            "users_v11".to_string(),
+        ];
+
+        let result = find_previous_versioned_index_name(&index_names, "users_v10");
+
+        assert_eq!(Some("users_v9".to_string()), result);
+    }
+
+    #[test]
+    fn find_previous_versioned_index_name_returns_none_if_no_previous_generation() {
+        let index_names = vec!["users_v1".to_string(), "users_v2".to_string()];
+
+        let result = find_previous_versioned_index_name(&index_names, "users_v1");
+
+        assert_eq!(None, result);
+    }
+
+    #[test]
+    fn create_switch_alias_actions_removes_alias_from_current_indices() {
+        let alias_state = AliasState::Alias(vec!["users_v1".to_string()]);
+
+        let result = create_switch_alias_actions("users_v2", &alias_state);
+
+        assert_eq!(
+            vec![
+                json!({"add": {"index": "users_v2", "alias": "users"}}),
+                json!({"remove": {"index": "users_v1", "alias": "users"}})
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn create_switch_alias_actions_removes_index_having_same_name_as_alias() {
+        let result = create_switch_alias_actions("users_v1", &AliasState::Index);
+
+        assert_eq!(
+            vec![
+                json!({"add": {"index": "users_v1", "alias": "users"}}),
+                json!({"remove_index": {"index": "users"}})
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn create_switch_alias_actions_only_adds_alias_if_not_found() {
+        let result = create_switch_alias_actions("users_v1", &AliasState::NotFound);
+
+        assert_eq!(
+            vec![json!({"add": {"index": "users_v1", "alias": "users"}})],
+            result
+        );
+    }
+}
@@ -0,0 +1,446 @@
+// Copyright 2023 Ken Miura
+
+mod document;
+mod index;
+
+use std::{
+    env::{args, var},
+    error::Error,
+    process::exit,
+};
+
+use chrono::NaiveDate;
+use common::{
+    admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD},
+    db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT},
+    opensearch::{
+        bulk_index_documents, create_client, index_definition::INDEX_DEFINITION_VERSION,
+        INDEX_NAME, KEY_TO_OPENSEARCH_AUTH, KEY_TO_OPENSEARCH_ENDPOINT_URI,
+        KEY_TO_OPENSEARCH_PASSWORD, KEY_TO_OPENSEARCH_USERNAME,
+    },
+    util::check_env_vars,
+    JAPANESE_TIME_ZONE,
+};
+use dotenv::dotenv;
+use entity::sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use opensearch::OpenSearch;
+use serde_json::Value;
+
+use crate::document::{
+    create_documents, find_differences, find_documents, NUM_OF_DOCUMENTS_PER_CHUNK,
+};
+use crate::index::{
+    count_documents, create_index, create_next_versioned_index_name, delete_index,
+    find_alias_state, find_index_definition_version_of, find_previous_versioned_index_name,
+    find_versioned_index_names, get_indexed_documents, refresh_index, reindex, switch_alias,
+    AliasState,
+};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const INVALID_ARG_LENGTH: i32 = 3;
+const INVALID_SUB_COMMAND: i32 = 4;
+const APPLICATION_ERR: i32 = 5;
+const INCONSISTENCY_FOUND: i32 = 6;
+
+const SUB_COMMANDS: &str = "init | verify | rebuild | migrate | rollback";
+
+fn main() {
+    let _ = dotenv().ok();
+
+    // 標準出力に出力される内容を見やすくしたいため、ログの初期化は行わない
+
+    let args: Vec<String> = args().collect();
+    if args.len() != 2 {
+        println!("usage: {} [ {} ]", args[0], SUB_COMMANDS);
+        exit(INVALID_ARG_LENGTH);
+    }
+    let cmd = args[1].as_str();
+    let needs_db = match cmd {
+        "verify" | "rebuild" => true,
+        "init" | "migrate" | "rollback" => false,
+        _ => {
+            println!("invalid subcommand: {}", cmd);
+            println!("valid subcommand [ {} ]", SUB_COMMANDS);
+            exit(INVALID_SUB_COMMAND);
+        }
+    };
+
+    let mut env_vars = vec![
+        KEY_TO_OPENSEARCH_ENDPOINT_URI.to_string(),
+        KEY_TO_OPENSEARCH_AUTH.to_string(),
+        KEY_TO_OPENSEARCH_USERNAME.to_string(),
+        KEY_TO_OPENSEARCH_PASSWORD.to_string(),
+    ];
+    if needs_db {
+        env_vars.extend([
+            KEY_TO_DB_HOST.to_string(),
+            KEY_TO_DB_PORT.to_string(),
+            KEY_TO_DB_NAME.to_string(),
+            KEY_TO_DB_ADMIN_NAME.to_string(),
+            KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+        ]);
+    }
+    let result = check_env_vars(env_vars);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal(cmd))
+}
+
+async fn main_internal(cmd: &str) {
+    let index_client = create_index_client().unwrap_or_else(|e| {
+        println!("failed to create OpenSearch client: {}", e);
+        exit(CONNECTION_ERROR);
+    });
+
+    if cmd == "init" {
+        exit_with(init_index(&index_client).await);
+    } else if cmd == "migrate" {
+        exit_with(migrate_index(&index_client).await);
+    } else if cmd == "rollback" {
+        exit_with(rollback_index(&index_client).await);
+    }
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let conn = connect(&database_url).await.unwrap_or_else(|e| {
+        println!(
+            "failed to establish connection (database_url: {}): {}",
+            database_url, e
+        );
+        exit(CONNECTION_ERROR);
+    });
+
+    let current_date = chrono::Utc::now()
+        .with_timezone(&(*JAPANESE_TIME_ZONE))
+        .date_naive();
+
+    if cmd == "verify" {
+        verify(&conn, &index_client, current_date).await;
+    } else {
+        exit_with(rebuild_index(&conn, &index_client, current_date).await);
+    }
+}
+
+fn exit_with(result: Result<(), Box<dyn Error>>) -> ! {
+    match result {
+        Ok(_) => exit(SUCCESS),
+        Err(e) => {
+            println!("application error: {}", e);
+            exit(APPLICATION_ERR);
+        }
+    }
+}
+
+async fn connect(database_url: &str) -> Result<DatabaseConnection, Box<dyn Error>> {
+    let mut opt = ConnectOptions::new(database_url.to_string());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let conn = Database::connect(opt).await.map_err(Box::new)?;
+    Ok(conn)
+}
+
+fn create_index_client() -> Result<OpenSearch, Box<dyn Error>> {
+    let opensearch_url = var(KEY_TO_OPENSEARCH_ENDPOINT_URI)?;
+    let opensearch_auth = var(KEY_TO_OPENSEARCH_AUTH)?.parse::<bool>()?;
+    let opensearch_username = var(KEY_TO_OPENSEARCH_USERNAME)?;
+    let opensearch_password = var(KEY_TO_OPENSEARCH_PASSWORD)?;
+    let index_client = create_client(
+        opensearch_url.as_str(),
+        opensearch_auth,
+        opensearch_username.as_str(),
+        opensearch_password.as_str(),
+    )?;
+    Ok(index_client)
+}
+
+async fn verify(conn: &DatabaseConnection, index_client: &OpenSearch, current_date: NaiveDate) {
+    match verify_documents(conn, index_client, current_date).await {
+        Ok(num_of_inconsistencies) => {
+            if num_of_inconsistencies == 0 {
+                println!("no inconsistency found");
+                exit(SUCCESS)
+            } else {
+                println!("{} inconsistency(ies) found", num_of_inconsistencies);
+                exit(INCONSISTENCY_FOUND)
+            }
+        }
+        Err(e) => {
+            println!("application error: {}", e);
+            exit(APPLICATION_ERR);
+        }
+    }
+}
+
+/// DBの内容から作成したドキュメントとインデックスに格納されているドキュメントを比較し、差分を標準出力に出力する
+///
+/// 戻り値は見つかった不整合の数
+async fn verify_documents(
+    conn: &DatabaseConnection,
+    index_client: &OpenSearch,
+    current_date: NaiveDate,
+) -> Result<usize, Box<dyn Error>> {
+    let mut num_of_documents = 0;
+    let mut num_of_inconsistencies = 0;
+    let mut last_user_account_id = None;
+    loop {
+        let documents =
+            find_documents(conn, last_user_account_id, NUM_OF_DOCUMENTS_PER_CHUNK).await?;
+        let Some(last_document) = documents.last() else {
+            break;
+        };
+        last_user_account_id = Some(last_document.user_account_id);
+        num_of_documents += documents.len();
+
+        let document_ids: Vec<i64> = documents.iter().map(|d| d.document_id).collect();
+        let indexed_documents = get_indexed_documents(&document_ids, index_client).await?;
+        let (expected_documents, orphans) =
+            create_documents(conn, &documents, current_date).await?;
+
+        for orphan in orphans {
+            num_of_inconsistencies += 1;
+            println!(
+                "[document_id: {}, user_account_id: {}] user_account not found",
+                orphan.document_id, orphan.user_account_id
+            );
+        }
+        for expected in expected_documents {
+            let Some(indexed) = indexed_documents.get(&expected.document_id) else {
+                num_of_inconsistencies += 1;
+                println!(
+                    "[document_id: {}, user_account_id: {}] not indexed",
+                    expected.document_id, expected.user_account_id
+                );
+                continue;
+            };
+            let differences = find_differences(&expected.source, indexed);
+            if !differences.is_empty() {
+                num_of_inconsistencies += 1;
+                for difference in differences {
+                    println!(
+                        "[document_id: {}, user_account_id: {}] {}",
+                        expected.document_id, expected.user_account_id, difference
+                    );
+                }
+            }
+        }
+    }
+
+    let num_of_indexed_documents = count_documents(INDEX_NAME, index_client).await?;
+    if num_of_indexed_documents != num_of_documents as i64 {
+        num_of_inconsistencies += 1;
+        println!(
+            "number of documents differs (database: {}, index: {})",
+            num_of_documents, num_of_indexed_documents
+        );
+    }
+    println!("{} document(s) verified", num_of_documents);
+    Ok(num_of_inconsistencies)
+}
+
+/// DBの内容から新しいインデックスを作成し、エイリアスの向き先を新しいインデックスに切り替える
+///
+/// 再構築中に行われたインデックスへの更新は新しいインデックスに反映されないため、サービス停止中に実行するか、実行後にverifyで不整合がないことを確認する
+async fn rebuild_index(
+    conn: &DatabaseConnection,
+    index_client: &OpenSearch,
+    current_date: NaiveDate,
+) -> Result<(), Box<dyn Error>> {
+    let alias_state = find_alias_state(index_client).await?;
+    let index_names = find_versioned_index_names(index_client).await?;
+    let new_index_name = create_next_versioned_index_name(&index_names);
+    create_index(&new_index_name, index_client).await?;
+    println!("index \"{}\" created", new_index_name);
+
+    let mut num_of_documents = 0;
+    let mut last_user_account_id = None;
+    loop {
+        let documents =
+            find_documents(conn, last_user_account_id, NUM_OF_DOCUMENTS_PER_CHUNK).await?;
+        let Some(last_document) = documents.last() else {
+            break;
+        };
+        last_user_account_id = Some(last_document.user_account_id);
+
+        let (new_documents, orphans) = create_documents(conn, &documents, current_date).await?;
+        for orphan in orphans {
+            println!(
+                "skip document because user_account not found (document_id: {}, user_account_id: {})",
+                orphan.document_id, orphan.user_account_id
+            );
+        }
+        if new_documents.is_empty() {
+            continue;
+        }
+        let bulk_body: Vec<(String, Value)> = new_documents
+            .iter()
+            .map(|d| (d.document_id.to_string(), d.source.clone()))
+            .collect();
+        bulk_index_documents(&new_index_name, &bulk_body, index_client)
+            .await
+            .map_err(|e| {
+                format!(
+                    "failed to bulk index documents (status code: {}, response body: {:?}, index_name: {})",
+                    e.0, e.1, new_index_name
+                )
+            })?;
+        num_of_documents += new_documents.len();
+    }
+
+    refresh_index(&new_index_name, index_client).await?;
+    let num_of_indexed_documents = count_documents(&new_index_name, index_client).await?;
+    if num_of_indexed_documents != num_of_documents as i64 {
+        return Err(format!(
+            "number of documents differs, so alias was not switched (database: {}, index \"{}\": {})",
+            num_of_documents, new_index_name, num_of_indexed_documents
+        )
+        .into());
+    }
+    println!(
+        "{} document(s) indexed into \"{}\"",
+        num_of_documents, new_index_name
+    );
+
+    switch_alias(&new_index_name, &alias_state, index_client).await?;
+    print_switched(&new_index_name, &alias_state);
+    Ok(())
+}
+
+/// エイリアス（[INDEX_NAME]）とその向き先となるインデックスを作成する
+///
+/// 既にエイリアス、またはインデックスが存在する場合はエラーとする
+async fn init_index(index_client: &OpenSearch) -> Result<(), Box<dyn Error>> {
+    let alias_state = find_alias_state(index_client).await?;
+    if alias_state != AliasState::NotFound {
+        return Err(format!(
+            "\"{}\" already exists ({:?}), use migrate or rebuild instead",
+            INDEX_NAME, alias_state
+        )
+        .into());
+    }
+    let index_names = find_versioned_index_names(index_client).await?;
+    let new_index_name = create_next_versioned_index_name(&index_names);
+    create_index(&new_index_name, index_client).await?;
+    println!(
+        "index \"{}\" created (definition version: {})",
+        new_index_name, INDEX_DEFINITION_VERSION
+    );
+    switch_alias(&new_index_name, &alias_state, index_client).await?;
+    print_switched(&new_index_name, &alias_state);
+    Ok(())
+}
+
+/// 現在の定義で新しいインデックスを作成し、現在のインデックスの全ドキュメントをコピーした後、エイリアスの向き先を新しいインデックスに切り替える
+///
+/// 現在のインデックスの定義のバージョンが[INDEX_DEFINITION_VERSION]以上の場合、何もしない。
+/// 移行前のインデックスはrollbackのために削除せずに残す。
+/// 移行中に行われたインデックスへの更新は新しいインデックスに反映されないため、サービス停止中に実行するか、実行後にverifyで不整合がないことを確認する
+async fn migrate_index(index_client: &OpenSearch) -> Result<(), Box<dyn Error>> {
+    let alias_state = find_alias_state(index_client).await?;
+    let current_index_name = match &alias_state {
+        AliasState::Alias(index_names) => {
+            if index_names.len() != 1 {
+                return Err(format!(
+                    "alias \"{}\" must point to exactly one index: {:?}",
+                    INDEX_NAME, index_names
+                )
+                .into());
+            }
+            index_names[0].clone()
+        }
+        AliasState::Index => {
+            println!(
+                "\"{}\" is not an alias, so it will be deleted after migration and cannot be rolled back",
+                INDEX_NAME
+            );
+            INDEX_NAME.to_string()
+        }
+        AliasState::NotFound => {
+            return Err(format!("\"{}\" not found, use init instead", INDEX_NAME).into())
+        }
+    };
+
+    let current_version =
+        find_index_definition_version_of(&current_index_name, index_client).await?;
+    if let Some(current_version) = current_version {
+        if current_version >= INDEX_DEFINITION_VERSION {
+            println!(
+                "index \"{}\" is up to date (definition version: {})",
+                current_index_name, current_version
+            );
+            return Ok(());
+        }
+    }
+
+    let index_names = find_versioned_index_names(index_client).await?;
+    let new_index_name = create_next_versioned_index_name(&index_names);
+    create_index(&new_index_name, index_client).await?;
+    println!(
+        "index \"{}\" created (definition version: {})",
+        new_index_name, INDEX_DEFINITION_VERSION
+    );
+
+    reindex(&current_index_name, &new_index_name, index_client).await?;
+    let num_of_documents = count_documents(&current_index_name, index_client).await?;
+    let num_of_copied_documents = count_documents(&new_index_name, index_client).await?;
+    if num_of_documents != num_of_copied_documents {
+        delete_index(&new_index_name, index_client).await?;
+        return Err(format!(
+            "number of documents differs, so index \"{}\" was deleted (index \"{}\": {}, index \"{}\": {})",
+            new_index_name, current_index_name, num_of_documents, new_index_name, num_of_copied_documents
+        )
+        .into());
+    }
+    println!(
+        "{} document(s) copied from \"{}\" (definition version: {:?}) into \"{}\"",
+        num_of_documents, current_index_name, current_version, new_index_name
+    );
+
+    switch_alias(&new_index_name, &alias_state, index_client).await?;
+    print_switched(&new_index_name, &alias_state);
+    Ok(())
+}
+
+/// エイリアス（[INDEX_NAME]）の向き先を、現在の向き先の一つ前の世代のインデックスに戻す
+///
+/// 現在の向き先のインデックスは削除せずに残す
+async fn rollback_index(index_client: &OpenSearch) -> Result<(), Box<dyn Error>> {
+    let alias_state = find_alias_state(index_client).await?;
+    let current_index_name = match &alias_state {
+        AliasState::Alias(index_names) if index_names.len() == 1 => index_names[0].clone(),
+        _ => {
+            return Err(format!(
+                "alias \"{}\" must point to exactly one index: {:?}",
+                INDEX_NAME, alias_state
+            )
+            .into())
+        }
+    };
+    let index_names = find_versioned_index_names(index_client).await?;
+    let previous_index_name = find_previous_versioned_index_name(&index_names, &current_index_name)
+        .ok_or_else(|| format!("no index found before \"{}\"", current_index_name))?;
+    switch_alias(&previous_index_name, &alias_state, index_client).await?;
+    print_switched(&previous_index_name, &alias_state);
+    Ok(())
+}
+
+fn print_switched(new_index_name: &str, alias_state: &AliasState) {
+    println!(
+        "alias \"{}\" switched to \"{}\" (previous: {:?})",
+        INDEX_NAME, new_index_name, alias_state
+    );
+}
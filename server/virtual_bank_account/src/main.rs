@@ -0,0 +1,328 @@
+// Copyright 2023 Ken Miura
+
+use chrono::{DateTime, FixedOffset};
+use common::admin::{KEY_TO_DB_ADMIN_NAME, KEY_TO_DB_ADMIN_PASSWORD};
+use common::db::{construct_db_url, KEY_TO_DB_HOST, KEY_TO_DB_NAME, KEY_TO_DB_PORT};
+use common::util::check_env_vars;
+use common::JAPANESE_TIME_ZONE;
+use dotenv::dotenv;
+use entity::sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectOptions, Database, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionError, TransactionTrait,
+};
+use std::{env::args, error::Error, fmt, fs::read_to_string, process::exit};
+
+const SUCCESS: i32 = 0;
+const ENV_VAR_CAPTURE_FAILURE: i32 = 1;
+const CONNECTION_ERROR: i32 = 2;
+const INVALID_ARG_LENGTH: i32 = 3;
+const INVALID_SUB_COMMAND: i32 = 4;
+const APPLICATION_ERR: i32 = 5;
+
+const ACCOUNT_NUMBER_LENGTH: usize = 7;
+
+fn main() {
+    let _ = dotenv().ok();
+    let result = check_env_vars(vec![
+        KEY_TO_DB_HOST.to_string(),
+        KEY_TO_DB_PORT.to_string(),
+        KEY_TO_DB_NAME.to_string(),
+        KEY_TO_DB_ADMIN_NAME.to_string(),
+        KEY_TO_DB_ADMIN_PASSWORD.to_string(),
+    ]);
+    if result.is_err() {
+        println!("failed to resolve mandatory env vars (following env vars are needed)");
+        println!("{:?}", result.unwrap_err());
+        exit(ENV_VAR_CAPTURE_FAILURE);
+    }
+
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("failed to build Runtime")
+        .block_on(main_internal())
+}
+
+async fn main_internal() {
+    // 標準出力に出力される内容を見やすくしたい＋管理者向けの口座登録しかしないのでログで見る必要のある重要な箇所もない
+    // 従ってログの初期化は行わない
+
+    let database_url = construct_db_url(
+        KEY_TO_DB_HOST,
+        KEY_TO_DB_PORT,
+        KEY_TO_DB_NAME,
+        KEY_TO_DB_ADMIN_NAME,
+        KEY_TO_DB_ADMIN_PASSWORD,
+    );
+    let conn = connect(&database_url).await.unwrap_or_else(|e| {
+        println!(
+            "failed to establish connection (database_url: {}): {}",
+            database_url, e
+        );
+        exit(CONNECTION_ERROR);
+    });
+
+    let args: Vec<String> = args().collect();
+    if args.len() < 2 {
+        println!("usage: {} [ load | list ] [SUB_COMMAND_ARGS...]", args[0]);
+        exit(INVALID_ARG_LENGTH);
+    }
+    let cmd = &args[1];
+    if cmd == "load" {
+        load(&conn, args).await;
+    } else if cmd == "list" {
+        list(&conn, args).await;
+    } else {
+        println!("invalid subcommand: {}", cmd);
+        println!("valid subcommand [ load | list ]");
+        exit(INVALID_SUB_COMMAND);
+    }
+}
+
+async fn connect(database_url: &str) -> Result<DatabaseConnection, Box<dyn Error + Send + Sync>> {
+    let mut opt = ConnectOptions::new(database_url.to_string());
+    opt.max_connections(1).min_connections(1).sqlx_logging(true);
+    let conn = Database::connect(opt).await.map_err(Box::new)?;
+    Ok(conn)
+}
+
+async fn load(conn: &DatabaseConnection, args: Vec<String>) {
+    if args.len() != 3 {
+        println!("usage: {} load \"path_to_account_number_list\"", args[0]);
+        println!("ex: {} load ./virtual_bank_accounts.txt", args[0]);
+        exit(INVALID_ARG_LENGTH);
+    }
+    let contents = read_to_string(&args[2]).unwrap_or_else(|e| {
+        println!("application error: failed to read {}: {}", &args[2], e);
+        exit(APPLICATION_ERR);
+    });
+    let account_numbers = parse_account_numbers(&contents).unwrap_or_else(|e| {
+        println!("application error: {}", e);
+        exit(APPLICATION_ERR);
+    });
+    let current_date_time = chrono::Utc::now().with_timezone(&(*JAPANESE_TIME_ZONE));
+    match load_accounts(conn, account_numbers, current_date_time).await {
+        Ok((num_of_inserted, num_of_skipped)) => {
+            println!(
+                "{} account(s) loaded, {} account(s) skipped (already exist)",
+                num_of_inserted, num_of_skipped
+            );
+            exit(SUCCESS)
+        }
+        Err(e) => {
+            println!("application error: {}", e);
+            exit(APPLICATION_ERR);
+        }
+    };
+}
+
+/// 銀行から提供された一覧（1行に1つの口座番号を記載したもの）を解析する。
+///
+/// 前後の空白は除去し、空行は無視する。7桁の数字以外の行が含まれる場合、エラーを返す。
+fn parse_account_numbers(contents: &str) -> Result<Vec<String>, InvalidAccountNumberError> {
+    let mut account_numbers = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let account_number = line.trim();
+        if account_number.is_empty() {
+            continue;
+        }
+        if account_number.chars().count() != ACCOUNT_NUMBER_LENGTH
+            || !account_number.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(InvalidAccountNumberError {
+                line_number: i + 1,
+                account_number: account_number.to_string(),
+            });
+        }
+        if !account_numbers.contains(&account_number.to_string()) {
+            account_numbers.push(account_number.to_string());
+        }
+    }
+    Ok(account_numbers)
+}
+
+async fn load_accounts(
+    conn: &DatabaseConnection,
+    account_numbers: Vec<String>,
+    current_date_time: DateTime<FixedOffset>,
+) -> Result<(usize, usize), Box<dyn Error + Send + Sync>> {
+    let result = conn
+        .transaction::<_, (usize, usize), TxErr>(|txn| {
+            Box::pin(async move {
+                let mut num_of_inserted = 0;
+                let mut num_of_skipped = 0;
+                for account_number in account_numbers {
+                    let count = entity::virtual_bank_account::Entity::find()
+                        .filter(
+                            entity::virtual_bank_account::Column::AccountNumber
+                                .eq(account_number.clone()),
+                        )
+                        .count(txn)
+                        .await
+                        .map_err(|e| TxErr(Box::new(e)))?;
+                    if count != 0 {
+                        num_of_skipped += 1;
+                        continue;
+                    }
+                    let active_model = entity::virtual_bank_account::ActiveModel {
+                        account_number: Set(account_number),
+                        consultation_id: Set(None),
+                        assigned_at: Set(None),
+                        created_at: Set(current_date_time),
+                        ..Default::default()
+                    };
+                    let _ = active_model
+                        .insert(txn)
+                        .await
+                        .map_err(|e| TxErr(Box::new(e)))?;
+                    num_of_inserted += 1;
+                }
+                Ok((num_of_inserted, num_of_skipped))
+            })
+        })
+        .await
+        .map_err(|e| match e {
+            TransactionError::Connection(db_err) => Box::new(db_err),
+            TransactionError::Transaction(tx_err) => tx_err.0,
+        })?;
+    Ok(result)
+}
+
+async fn list(conn: &DatabaseConnection, args: Vec<String>) {
+    if args.len() != 2 {
+        println!("usage: {} list", args[0]);
+        println!("ex: {} list", args[0]);
+        exit(INVALID_ARG_LENGTH);
+    }
+    match list_accounts(conn).await {
+        Ok(accounts) => {
+            println!("account_number, consultation_id, assigned_at, created_at");
+            accounts.iter().for_each(|account| {
+                let consultation_id = match account.consultation_id {
+                    Some(c) => c.to_string(),
+                    None => "None".to_string(),
+                };
+                let assigned_at = match account.assigned_at {
+                    Some(t) => t.with_timezone(&*JAPANESE_TIME_ZONE).to_rfc3339(),
+                    None => "None".to_string(),
+                };
+                println!(
+                    "{}, {}, \"{}\", \"{}\"",
+                    account.account_number,
+                    consultation_id,
+                    assigned_at,
+                    account
+                        .created_at
+                        .with_timezone(&*JAPANESE_TIME_ZONE)
+                        .to_rfc3339()
+                );
+            });
+            let num_of_unassigned = accounts
+                .iter()
+                .filter(|a| a.consultation_id.is_none())
+                .count();
+            println!(
+                "total: {}, unassigned: {}",
+                accounts.len(),
+                num_of_unassigned
+            );
+            exit(SUCCESS)
+        }
+        Err(e) => {
+            println!("application error: {}", e);
+            exit(APPLICATION_ERR);
+        }
+    };
+}
+
+async fn list_accounts(
+    conn: &DatabaseConnection,
+) -> Result<Vec<entity::virtual_bank_account::Model>, Box<dyn Error + Send + Sync>> {
+    let accounts = entity::virtual_bank_account::Entity::find()
+        .order_by_asc(entity::virtual_bank_account::Column::VirtualBankAccountId)
+        .all(conn)
+        .await
+        .map_err(Box::new)?;
+    Ok(accounts)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct InvalidAccountNumberError {
+    line_number: usize,
+    account_number: String,
+}
+
+impl fmt::Display for InvalidAccountNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid account number ({}) at line {}",
+            self.account_number, self.line_number
+        )
+    }
+}
+
+impl Error for InvalidAccountNumberError {}
+
+#[derive(Debug)]
+struct TxErr(Box<dyn Error + Send + Sync>);
+
+impl fmt::Display for TxErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error in transaction: {}", self.0)
+    }
+}
+
+impl Error for TxErr {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_account_numbers_success() {
+        let contents = "1234567\n 2345678 \n\n3456789\n1234567\n";
+
+        let result = parse_account_numbers(contents).expect("failed to get Ok");
+
+        assert_eq!(
+            vec![
+                "1234567".to_string(),
+                "2345678".to_string(),
+                "3456789".to_string()
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn parse_account_numbers_fail_invalid_length() {
+        let contents = "1234567\n123456\n";
+
+        let result = parse_account_numbers(contents).expect_err("failed to get Err");
+
+        assert_eq!(
+            InvalidAccountNumberError {
+                line_number: 2,
+                account_number: "123456".to_string()
+            },
+            result
+        );
+    }
+
+    #[test]
+    fn parse_account_numbers_fail_non_digit() {
+        let contents = "123456a\n";
+
+        let result = parse_account_numbers(contents).expect_err("failed to get Err");
+
+        assert_eq!(
+            InvalidAccountNumberError {
+                line_number: 1,
+                account_number: "123456a".to_string()
+            },
+            result
+        );
+    }
+}